@@ -1,8 +1,10 @@
 use makepad_widgets::*;
-use matrix_sdk::ruma::OwnedRoomId;
+use tracing::{error, info};
+use matrix_sdk::ruma::{matrix_uri::MatrixId, OwnedRoomId};
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    home::{main_desktop_ui::RoomsPanelAction, new_message_context_menu::NewMessageContextMenuWidgetRefExt, room_screen::MessageAction, rooms_list::RoomsListAction}, login::login_screen::LoginAction, shared::popup_list::PopupNotificationAction, verification::VerificationAction, verification_modal::{VerificationModalAction, VerificationModalWidgetRefExt}
+    app_lock::{AppLockScreenWidgetRefExt, INACTIVITY_CHECK_INTERVAL_SECS}, deep_link::DeepLinkAction, home::{contacts::{ContactsAction, ContactsUpdate, ContactsWidgetRefExt}, event_source_modal::{EventSourceModalAction, EventSourceModalWidgetRefExt}, export_chat_modal::{ExportChatModalAction, ExportChatModalWidgetRefExt}, inbox::{InboxAction, InboxWidgetRefExt}, leave_room_modal::{LeaveRoomModalAction, LeaveRoomModalWidgetRefExt}, main_desktop_ui::RoomsPanelAction, moderation_dashboard::{ModerationDashboardAction, ModerationDashboardWidgetRefExt}, new_message_context_menu::NewMessageContextMenuWidgetRefExt, room_context_menu::RoomContextMenuWidgetRefExt, room_hover_preview::RoomHoverPreviewWidgetRefExt, room_screen::{self, MessageAction}, rooms_list::RoomsListAction, room_wallpaper_modal::{RoomWallpaperModalAction, RoomWallpaperModalWidgetRefExt}, share_room_modal::{ShareRoomModalAction, ShareRoomModalWidgetRefExt}}, i18n::{tr, tr_args}, login::login_screen::LoginAction, profile::presence, settings::{app_settings::app_settings, keyboard_shortcuts::{shortcut_action_for, ShortcutAction}, settings_screen::{SettingsAction, SettingsScreenWidgetRefExt}}, shared::{performance_overlay::{self, PerformanceOverlayWidgetRefExt}, popup_list::{enqueue_popup_notification, PopupNotificationAction}}, sliding_sync::{self, submit_async_request, MatrixRequest}, utils, verification::VerificationAction, verification_modal::{VerificationModalAction, VerificationModalWidgetRefExt}
 };
 
 live_design! {
@@ -14,9 +16,22 @@ live_design! {
     use crate::home::home_screen::HomeScreen;
     use crate::profile::my_profile_screen::MyProfileScreen;
     use crate::verification_modal::VerificationModal;
+    use crate::app_lock::AppLockScreen;
+    use crate::home::export_chat_modal::ExportChatModal;
+    use crate::home::moderation_dashboard::ModerationDashboard;
+    use crate::home::inbox::Inbox;
+    use crate::home::contacts::Contacts;
+    use crate::home::share_room_modal::ShareRoomModal;
+    use crate::home::room_wallpaper_modal::RoomWallpaperModal;
+    use crate::home::leave_room_modal::LeaveRoomModal;
+    use crate::home::event_source_modal::EventSourceModal;
+    use crate::home::room_context_menu::RoomContextMenu;
+    use crate::home::room_hover_preview::RoomHoverPreview;
     use crate::login::login_screen::LoginScreen;
     use crate::shared::popup_list::PopupList;
     use crate::home::new_message_context_menu::*;
+    use crate::settings::settings_screen::SettingsScreen;
+    use crate::shared::performance_overlay::PerformanceOverlay;
     
     APP_TAB_COLOR = #344054
     APP_TAB_COLOR_HOVER = #636e82
@@ -114,6 +129,10 @@ live_design! {
                         visible: true
                         login_screen = <LoginScreen> {}
                     }
+                    settings_screen_view = <View> {
+                        visible: false
+                        settings_screen = <SettingsScreen> {}
+                    }
                     popup = <PopupNotification> {
                         margin: {top: 45, right: 13},
                         content: {
@@ -124,6 +143,8 @@ live_design! {
                     // Context menus should be shown above other UI elements,
                     // but beneath the verification modal.
                     new_message_context_menu = <NewMessageContextMenu> { }
+                    room_context_menu = <RoomContextMenu> { }
+                    room_hover_preview = <RoomHoverPreview> { }
                     
                     // message_source_modal = <Modal> {
                     //     content: {
@@ -131,6 +152,54 @@ live_design! {
                     //     }
                     // }
 
+                    export_chat_modal = <Modal> {
+                        content: {
+                            export_chat_modal_inner = <ExportChatModal> {}
+                        }
+                    }
+
+                    moderation_dashboard_modal = <Modal> {
+                        content: {
+                            moderation_dashboard = <ModerationDashboard> {}
+                        }
+                    }
+
+                    inbox_modal = <Modal> {
+                        content: {
+                            inbox = <Inbox> {}
+                        }
+                    }
+
+                    contacts_modal = <Modal> {
+                        content: {
+                            contacts = <Contacts> {}
+                        }
+                    }
+
+                    share_room_modal = <Modal> {
+                        content: {
+                            share_room_modal_inner = <ShareRoomModal> {}
+                        }
+                    }
+
+                    room_wallpaper_modal = <Modal> {
+                        content: {
+                            room_wallpaper_modal_inner = <RoomWallpaperModal> {}
+                        }
+                    }
+
+                    leave_room_modal = <Modal> {
+                        content: {
+                            leave_room_modal_inner = <LeaveRoomModal> {}
+                        }
+                    }
+
+                    event_source_modal = <Modal> {
+                        content: {
+                            event_source_modal_inner = <EventSourceModal> {}
+                        }
+                    }
+
                     // We want the verification modal to always show up on top of
                     // all other elements when an incoming verification request is received.
                     verification_modal = <Modal> {
@@ -138,6 +207,32 @@ live_design! {
                             verification_modal_inner = <VerificationModal> {}
                         }
                     }
+
+                    // Hides all other content while the app window isn't focused,
+                    // if enabled in the privacy settings.
+                    privacy_shield = <View> {
+                        visible: false
+                        width: Fill, height: Fill
+                        align: {x: 0.5, y: 0.5}
+                        show_bg: true
+                        draw_bg: {
+                            color: (COLOR_PRIMARY)
+                        }
+
+                        privacy_shield_label = <Label> {
+                            width: Fit, height: Fit
+                            draw_text: {
+                                color: (MESSAGE_TEXT_COLOR)
+                                text_style: <REGULAR_TEXT>{font_size: 12.0}
+                            }
+                        }
+                    }
+
+                    // The app lock screen hides all other content (including modals) while the app is locked.
+                    app_lock_screen = <AppLockScreen> {}
+
+                    // The debug performance overlay is drawn on top of everything else.
+                    performance_overlay = <PerformanceOverlay> {}
                 }
             } // end of body
         }
@@ -153,6 +248,16 @@ pub struct App {
 
     #[rust]
     app_state: AppState,
+    /// The target of a deep link that we have yet to navigate to,
+    /// e.g., because the user isn't logged in yet or the target room hasn't been loaded yet.
+    #[rust]
+    pending_deep_link: Option<MatrixId>,
+    /// When the user last interacted with the app, used to lock the app after inactivity.
+    #[rust]
+    last_activity: Option<std::time::Instant>,
+    /// The timer used to periodically check whether the app should be locked due to inactivity.
+    #[rust]
+    app_lock_timer: Timer,
 }
 
 impl LiveRegister for App {
@@ -164,8 +269,10 @@ impl LiveRegister for App {
         makepad_widgets::live_design(cx);
         crate::shared::live_design(cx);
         crate::verification_modal::live_design(cx);
+        crate::app_lock::live_design(cx);
         crate::home::live_design(cx);
         crate::profile::live_design(cx);
+        crate::settings::live_design(cx);
         crate::login::live_design(cx);
     }
 }
@@ -181,21 +288,96 @@ impl MatchEvent for App {
         // Initialize the project directory here from the main UI thread
         // such that background threads/tasks will be able to can access it.
         let _app_data_dir = crate::app_data_dir();
-        log!("App::handle_startup(): app_data_dir: {:?}", _app_data_dir);
+        // Initialize logging as early as possible, such that no log messages are lost.
+        crate::logging::init();
+        info!("App::handle_startup(): app_data_dir: {:?}", _app_data_dir);
+        crate::settings::app_settings::load_app_settings();
+        crate::settings::storage::request_temp_storage_cleanup();
+        crate::spell_check::load_dictionary_from_settings();
+        crate::network_status::start_monitoring();
+
+        if app_settings().app_lock.is_enabled() {
+            self.ui.app_lock_screen(id!(app_lock_screen)).lock(cx);
+        }
+        self.last_activity = Some(std::time::Instant::now());
+        self.app_lock_timer = cx.start_interval(INACTIVITY_CHECK_INTERVAL_SECS);
 
         self.update_login_visibility(cx);
 
-        log!("App::handle_startup(): starting matrix sdk loop");
+        self.pending_deep_link = sliding_sync::launch_target_from_args(&std::env::args().collect::<Vec<_>>());
+        if let Some(target) = &self.pending_deep_link {
+            info!("App::handle_startup(): launched with target {target:?}");
+        }
+        crate::single_instance::start_listener();
+
+        info!("App::handle_startup(): starting matrix sdk loop");
         crate::sliding_sync::start_matrix_tokio().unwrap();
     }
 
+    fn handle_signal(&mut self, cx: &mut Cx) {
+        // Images decoded in the background may be shown in any room,
+        // so they're processed once here rather than by each room.
+        crate::image_decoding::process_decoded_images(cx);
+        // The target room of a pending deep link may have just been loaded.
+        if self.pending_deep_link.is_some() {
+            self.navigate_to_pending_deep_link(cx);
+        }
+    }
+
+    fn handle_key_down(&mut self, cx: &mut Cx, event: &KeyEvent) {
+        if !self.are_shortcuts_enabled() { return }
+        // Post the action bound to this key combination (if any),
+        // such that the relevant widgets can handle it.
+        if let Some(shortcut_action) = shortcut_action_for(event) {
+            cx.action(shortcut_action);
+        }
+    }
+
     fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions) {
+        if self.ui.button(id!(settings_button)).clicked(actions) {
+            self.show_settings_screen(cx);
+        }
+        if self.ui.button(id!(inbox_button)).clicked(actions) {
+            self.ui.inbox(id!(inbox)).on_shown(cx);
+            self.ui.modal(id!(inbox_modal)).open(cx);
+        }
+        if self.ui.button(id!(contacts_button)).clicked(actions) {
+            self.ui.contacts(id!(contacts)).on_shown(cx);
+            self.ui.modal(id!(contacts_modal)).open(cx);
+        }
+
         for action in actions {
             if let Some(LoginAction::LoginSuccess) = action.downcast_ref() {
-                log!("Received LoginAction::LoginSuccess, hiding login view.");
+                info!("Received LoginAction::LoginSuccess, hiding login view.");
                 self.app_state.logged_in = true;
                 self.update_login_visibility(cx);
                 self.ui.redraw(cx);
+                self.navigate_to_pending_deep_link(cx);
+                // Our own presence is shown in the spaces dock and the settings screen.
+                if let Some(user_id) = sliding_sync::current_user_id() {
+                    presence::fetch_presence(&user_id);
+                }
+            }
+
+            match action.downcast_ref() {
+                Some(DeepLinkAction::Navigate(target)) => {
+                    self.pending_deep_link = Some(target.clone());
+                    self.navigate_to_pending_deep_link(cx);
+                }
+                Some(DeepLinkAction::Focus) => {
+                    if let Some(mut window) = self.ui.borrow_mut::<Window>() {
+                        window.window.restore(cx);
+                    }
+                }
+                Some(DeepLinkAction::OpenExternally(url)) => {
+                    if let Err(e) = robius_open::Uri::new(url).open() {
+                        error!("Failed to open URL {url:?}: {e:?}");
+                        enqueue_popup_notification(tr_args("deep_link.open_failed", &[("url", url)]));
+                    } else {
+                        enqueue_popup_notification(tr("deep_link.not_joined").to_string());
+                    }
+                }
+                None => { }
             }
 
             // Handle an action requesting to open the new message context menu.
@@ -212,6 +394,33 @@ impl MatchEvent for App {
                 self.ui.redraw(cx);
             }
 
+            match action.as_widget_action().cast() {
+                SettingsAction::Open => {
+                    self.show_settings_screen(cx);
+                }
+                SettingsAction::Close => {
+                    self.ui.view(id!(settings_screen_view)).set_visible(cx, false);
+                    self.ui.redraw(cx);
+                }
+                SettingsAction::None => { }
+            }
+
+            // Other shortcut actions are handled by the widgets they relate to.
+            match action.downcast_ref() {
+                Some(ShortcutAction::OpenSettings) => {
+                    self.show_settings_screen(cx);
+                }
+                Some(ShortcutAction::ToggleSidebar) => {
+                    let spaces = self.ui.view(id!(spaces));
+                    spaces.set_visible(cx, !spaces.visible());
+                    self.ui.redraw(cx);
+                }
+                Some(ShortcutAction::TogglePerformanceOverlay) => {
+                    self.ui.performance_overlay(id!(performance_overlay)).toggle(cx);
+                }
+                _ => {}
+            }
+
             match action.downcast_ref() {
                 Some(PopupNotificationAction::Open) => {
                     self.ui.popup_notification(id!(popup)).open(cx);
@@ -224,7 +433,7 @@ impl MatchEvent for App {
 
             match action.as_widget_action().cast() {
                 // A room has been selected, update the app state and navigate to the main content view.
-                RoomsListAction::Selected { room_id, room_index: _, room_name } => {
+                RoomsListAction::Selected { room_id, room_name } => {
                     self.app_state.rooms_panel.selected_room = Some(SelectedRoom {
                         room_id: room_id.clone(),
                         room_name: room_name.clone(),
@@ -242,6 +451,35 @@ impl MatchEvent for App {
                         .set_text(cx, &room_name.unwrap_or_else(|| format!("Room ID {}", &room_id)));
                     self.ui.redraw(cx);
                 }
+                // Handle an action requesting to open the context menu for a room in the rooms list.
+                RoomsListAction::OpenRoomContextMenu { details, abs_pos } => {
+                    let room_context_menu = self.ui.room_context_menu(id!(room_context_menu));
+                    let expected_dimensions = room_context_menu.show(cx, details);
+                    // Ensure the context menu does not spill over the window's bounds.
+                    let rect = self.ui.area().rect(cx);
+                    let pos_x = min(abs_pos.x, rect.size.x - expected_dimensions.x);
+                    let pos_y = min(abs_pos.y, rect.size.y - expected_dimensions.y);
+                    room_context_menu.apply_over(cx, live! {
+                        main_content = { margin: { left: (pos_x), top: (pos_y) } }
+                    });
+                    self.ui.redraw(cx);
+                }
+                // Handle an action requesting to show or hide the hover preview for a room in the rooms list.
+                RoomsListAction::ShowRoomHoverPreview { room_id, room_name, abs_pos } => {
+                    let room_hover_preview = self.ui.room_hover_preview(id!(room_hover_preview));
+                    let expected_dimensions = room_hover_preview.show(cx, room_id, room_name);
+                    // Ensure the hover preview does not spill over the window's bounds.
+                    let rect = self.ui.area().rect(cx);
+                    let pos_x = min(abs_pos.x, rect.size.x - expected_dimensions.x);
+                    let pos_y = min(abs_pos.y, rect.size.y - expected_dimensions.y);
+                    room_hover_preview.apply_over(cx, live! {
+                        main_content = { margin: { left: (pos_x), top: (pos_y) } }
+                    });
+                    self.ui.redraw(cx);
+                }
+                RoomsListAction::HideRoomHoverPreview => {
+                    self.ui.room_hover_preview(id!(room_hover_preview)).hide(cx);
+                }
                 RoomsListAction::None => { }
             }
 
@@ -259,7 +497,9 @@ impl MatchEvent for App {
             // Therefore, we cannot use `as_widget_action().cast()` to match them.
             //
             // Note: other verification actions are handled by the verification modal itself.
-            if let Some(VerificationAction::RequestReceived(state)) = action.downcast_ref() {
+            if let Some(
+                VerificationAction::RequestReceived(state) | VerificationAction::RequestSent(state)
+            ) = action.downcast_ref() {
                 self.ui.verification_modal(id!(verification_modal_inner))
                     .initialize_with_data(cx, state.clone());
                 self.ui.modal(id!(verification_modal)).open(cx);
@@ -268,41 +508,154 @@ impl MatchEvent for App {
                 self.ui.modal(id!(verification_modal)).close(cx);
             }
 
-            // // message source modal handling.
-            // match action.as_widget_action().cast() {
-            //     MessageAction::MessageSourceModalOpen { room_id: _, event_id: _, original_json: _ } => {
-            //        // self.ui.message_source(id!(message_source_modal_inner)).initialize_with_data(room_id, event_id, original_json);
-            //        // self.ui.modal(id!(message_source_modal)).open(cx);
-            //     }
-            //     MessageAction::MessageSourceModalClose => {
-            //         self.ui.modal(id!(message_source_modal)).close(cx);
-            //     }
-            //     _ => {}
-            // }
+            match action.as_widget_action().cast() {
+                ExportChatModalAction::Open { room_id, room_name } => {
+                    self.ui.export_chat_modal(id!(export_chat_modal_inner))
+                        .initialize(cx, room_id, room_name);
+                    self.ui.modal(id!(export_chat_modal)).open(cx);
+                }
+                ExportChatModalAction::Close => {
+                    self.ui.modal(id!(export_chat_modal)).close(cx);
+                }
+                ExportChatModalAction::None => { }
+            }
+
+            match action.as_widget_action().cast() {
+                ModerationDashboardAction::Open { room_id, room_name, powers, summary } => {
+                    self.ui.moderation_dashboard(id!(moderation_dashboard))
+                        .initialize(cx, room_id, room_name, powers, summary);
+                    self.ui.modal(id!(moderation_dashboard_modal)).open(cx);
+                }
+                ModerationDashboardAction::Close => {
+                    self.ui.modal(id!(moderation_dashboard_modal)).close(cx);
+                }
+                ModerationDashboardAction::None => { }
+            }
+
+            match action.as_widget_action().cast() {
+                InboxAction::JumpToEvent { room_id, room_name, event_id } => {
+                    self.ui.modal(id!(inbox_modal)).close(cx);
+                    room_screen::jump_to_event(room_id.clone(), event_id);
+                    cx.widget_action(
+                        self.ui.widget_uid(),
+                        &Scope::default().path,
+                        RoomsListAction::Selected { room_id, room_name },
+                    );
+                }
+                InboxAction::Close => {
+                    self.ui.modal(id!(inbox_modal)).close(cx);
+                }
+                InboxAction::None => { }
+            }
+
+            if let ContactsAction::Close = action.as_widget_action().cast() {
+                self.ui.modal(id!(contacts_modal)).close(cx);
+            }
+
+            match action.downcast_ref() {
+                Some(ContactsUpdate::DirectMessageReady { room_id, room_name }) => {
+                    self.ui.modal(id!(contacts_modal)).close(cx);
+                    cx.widget_action(
+                        self.ui.widget_uid(),
+                        &Scope::default().path,
+                        RoomsListAction::Selected {
+                            room_id: room_id.clone(),
+                            room_name: room_name.clone(),
+                        },
+                    );
+                }
+                Some(ContactsUpdate::DirectMessageFailed { user_id, error }) => {
+                    enqueue_popup_notification(tr_args(
+                        "contacts.open_dm_failed",
+                        &[("user", user_id.as_str()), ("error", error)],
+                    ));
+                }
+                _ => { }
+            }
+
+            match action.as_widget_action().cast() {
+                ShareRoomModalAction::Open { room_id, room_name, canonical_alias } => {
+                    self.ui.share_room_modal(id!(share_room_modal_inner))
+                        .initialize(cx, room_id, room_name, canonical_alias);
+                    self.ui.modal(id!(share_room_modal)).open(cx);
+                }
+                ShareRoomModalAction::Close => {
+                    self.ui.modal(id!(share_room_modal)).close(cx);
+                }
+                ShareRoomModalAction::None => { }
+            }
+
+            match action.as_widget_action().cast() {
+                RoomWallpaperModalAction::Open { room_id, room_name } => {
+                    self.ui.room_wallpaper_modal(id!(room_wallpaper_modal_inner))
+                        .initialize(cx, room_id, room_name);
+                    self.ui.modal(id!(room_wallpaper_modal)).open(cx);
+                }
+                RoomWallpaperModalAction::Close => {
+                    self.ui.modal(id!(room_wallpaper_modal)).close(cx);
+                }
+                RoomWallpaperModalAction::None => { }
+            }
+
+            match action.as_widget_action().cast() {
+                LeaveRoomModalAction::Open { room_id, room_name } => {
+                    self.ui.leave_room_modal(id!(leave_room_modal_inner))
+                        .initialize(cx, room_id, room_name);
+                    self.ui.modal(id!(leave_room_modal)).open(cx);
+                }
+                LeaveRoomModalAction::Close => {
+                    self.ui.modal(id!(leave_room_modal)).close(cx);
+                }
+                LeaveRoomModalAction::LastAdminChecked { .. } | LeaveRoomModalAction::None => { }
+            }
+
+            match action.as_widget_action().cast() {
+                EventSourceModalAction::Open(source) => {
+                    self.ui.event_source_modal(id!(event_source_modal_inner)).initialize(cx, *source);
+                    self.ui.modal(id!(event_source_modal)).open(cx);
+                }
+                EventSourceModalAction::Close => {
+                    self.ui.modal(id!(event_source_modal)).close(cx);
+                }
+                EventSourceModalAction::None => { }
+            }
+        }
+    }
+
+    fn handle_foreground(&mut self, cx: &mut Cx) {
+        // Timers may not fire while the app is in the background,
+        // so we must check for inactivity before any content is shown again.
+        self.lock_if_inactive(cx);
+    }
+
+    fn handle_app_lost_focus(&mut self, cx: &mut Cx) {
+        if app_settings().hide_content_when_unfocused {
+            self.ui.label(id!(privacy_shield_label)).set_text(cx, tr("app.content_hidden"));
+            self.ui.view(id!(privacy_shield)).set_visible(cx, true);
+            self.ui.redraw(cx);
+        }
+    }
+
+    fn handle_app_got_focus(&mut self, cx: &mut Cx) {
+        let privacy_shield = self.ui.view(id!(privacy_shield));
+        if privacy_shield.visible() {
+            privacy_shield.set_visible(cx, false);
+            self.ui.redraw(cx);
         }
     }
 
     /*
     fn handle_shutdown(&mut self, _cx: &mut Cx) {
-        log!("App::handle_shutdown()");
-    }
-    fn handle_foreground(&mut self, _cx: &mut Cx) {
-        log!("App::handle_foreground()");
+        info!("App::handle_shutdown()");
     }
     fn handle_background(&mut self, _cx: &mut Cx) {
-        log!("App::handle_background()");
+        info!("App::handle_background()");
     }
     fn handle_pause(&mut self, _cx: &mut Cx) {
-        log!("App::handle_pause()");
+        info!("App::handle_pause()");
     }
     fn handle_resume(&mut self, _cx: &mut Cx) {
-        log!("App::handle_resume()");
-    }
-    fn handle_app_got_focus(&mut self, _cx: &mut Cx) {
-        log!("App::handle_app_got_focus()");
-    }
-    fn handle_app_lost_focus(&mut self, _cx: &mut Cx) {
-        log!("App::handle_app_lost_focus()");
+        info!("App::handle_resume()");
     }
     */
 }
@@ -312,10 +665,29 @@ impl AppMain for App {
         if let Event::WindowGeomChange(window_geom_change_event) = event {
             self.app_state.window_geom = Some(window_geom_change_event.new_geom.clone());
         }
+        let is_interactive = utils::is_interactive_hit_event(event);
+        if is_interactive {
+            self.last_activity = Some(std::time::Instant::now());
+        }
+        if self.app_lock_timer.is_event(event).is_some() {
+            self.lock_if_inactive(cx);
+        }
+        // While the app is locked, only the lock screen may receive user input.
+        let app_lock_screen = self.ui.app_lock_screen(id!(app_lock_screen));
+        if is_interactive && app_lock_screen.is_locked() {
+            let scope = &mut Scope::with_data(&mut self.app_state);
+            app_lock_screen.handle_event(cx, event, scope);
+            return;
+        }
+
         // Forward events to the MatchEvent trait implementation.
         self.match_event(cx, event);
         let scope = &mut Scope::with_data(&mut self.app_state);
+        let draw_start = matches!(event, Event::Draw(_)).then(std::time::Instant::now);
         self.ui.handle_event(cx, event, scope);
+        if let Some(draw_start) = draw_start {
+            performance_overlay::record_frame_time(draw_start.elapsed());
+        }
 
         /*
          * TODO: I'd like for this to work, but it doesn't behave as expected.
@@ -352,6 +724,125 @@ impl AppMain for App {
 }
 
 impl App {
+    /// Returns `true` if app-wide keyboard shortcuts should currently be handled,
+    /// which is only the case while the main UI is shown without anything on top of it.
+    ///
+    /// Shortcuts must not fire while the user is interacting with a modal,
+    /// the settings screen, the login screen, or the app lock screen.
+    fn are_shortcuts_enabled(&self) -> bool {
+        if !self.app_state.logged_in
+            || self.ui.app_lock_screen(id!(app_lock_screen)).is_locked()
+            || self.ui.view(id!(settings_screen_view)).visible()
+        {
+            return false;
+        }
+        let modals = [
+            id!(export_chat_modal),
+            id!(moderation_dashboard_modal),
+            id!(inbox_modal),
+            id!(contacts_modal),
+            id!(share_room_modal),
+            id!(room_wallpaper_modal),
+            id!(leave_room_modal),
+            id!(event_source_modal),
+            id!(verification_modal),
+        ];
+        !modals.into_iter().any(|modal| self.ui.modal(modal).is_open())
+    }
+
+    /// Locks the app if the app lock is enabled and the user has been inactive for too long.
+    fn lock_if_inactive(&mut self, cx: &mut Cx) {
+        let timeout = {
+            let settings = app_settings();
+            if !settings.app_lock.is_enabled() { return }
+            let Some(timeout) = settings.app_lock.timeout.duration() else { return };
+            timeout
+        };
+        if self.last_activity.is_some_and(|last| last.elapsed() >= timeout) {
+            self.ui.app_lock_screen(id!(app_lock_screen)).lock(cx);
+        }
+    }
+
+    fn show_settings_screen(&mut self, cx: &mut Cx) {
+        self.ui.settings_screen(id!(settings_screen)).on_shown(cx);
+        self.ui.view(id!(settings_screen_view)).set_visible(cx, true);
+        self.ui.redraw(cx);
+    }
+
+    /// Navigates to the target of the pending deep link, if any.
+    ///
+    /// If the target room isn't known yet, the deep link remains pending
+    /// such that we can try again once more rooms have been loaded.
+    fn navigate_to_pending_deep_link(&mut self, cx: &mut Cx) {
+        if !self.app_state.logged_in { return }
+        let Some(target) = self.pending_deep_link.take() else { return };
+        let Some(client) = sliding_sync::get_client() else {
+            self.pending_deep_link = Some(target);
+            return;
+        };
+
+        let room_id = match &target {
+            MatrixId::Room(room_id) => {
+                if client.get_room(room_id).is_none() {
+                    info!("Deep link target room {room_id} isn't known, opening it externally.");
+                    cx.action(DeepLinkAction::OpenExternally(room_id.matrix_to_uri().to_string()));
+                    return;
+                }
+                room_id.clone()
+            }
+            MatrixId::Event(room_or_alias_id, event_id) => match OwnedRoomId::try_from(room_or_alias_id.clone()) {
+                Ok(room_id) => {
+                    if client.get_room(&room_id).is_none() {
+                        info!("Deep link target room {room_id} isn't known, opening event {event_id} externally.");
+                        cx.action(DeepLinkAction::OpenExternally(
+                            room_id.matrix_to_event_uri(event_id.clone()).to_string()
+                        ));
+                        return;
+                    }
+                    room_screen::jump_to_event(room_id.clone(), event_id.clone());
+                    room_id
+                }
+                Err(room_alias_id) => {
+                    submit_async_request(MatrixRequest::ResolveRoomAlias(room_alias_id));
+                    return;
+                }
+            },
+            MatrixId::RoomAlias(room_alias_id) => {
+                submit_async_request(MatrixRequest::ResolveRoomAlias(room_alias_id.clone()));
+                return;
+            }
+            MatrixId::User(user_id) => match client.get_dm_room(user_id) {
+                Some(room) => room.room_id().to_owned(),
+                None => {
+                    enqueue_popup_notification(tr_args(
+                        "deep_link.no_dm_room",
+                        &[("user_id", user_id.as_str())],
+                    ));
+                    return;
+                }
+            },
+            _ => {
+                enqueue_popup_notification(tr("deep_link.unsupported").to_string());
+                return;
+            }
+        };
+
+        if !sliding_sync::is_room_loaded(&room_id) {
+            // Store it as a room link, such that an alias doesn't get resolved again.
+            self.pending_deep_link = Some(MatrixId::Room(room_id));
+            return;
+        }
+        let room_name = client.get_room(&room_id)
+            .and_then(|room| room.cached_display_name())
+            .map(|dn| dn.to_string());
+        info!("Navigating to deep link target room {room_id}");
+        cx.widget_action(
+            self.ui.widget_uid(),
+            &Scope::default().path,
+            RoomsListAction::Selected { room_id, room_name },
+        );
+    }
+
     fn update_login_visibility(&self, cx: &mut Cx) {
         let show_login = !self.app_state.logged_in;
         if !show_login {
@@ -380,7 +871,7 @@ pub struct RoomsPanelState {
 /// Represents a room currently or previously selected by the user.
 ///
 /// One `SelectedRoom` is considered equal to another if their `room_id`s are equal.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SelectedRoom {
     pub room_id: OwnedRoomId,
     pub room_name: Option<String>,
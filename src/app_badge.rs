@@ -0,0 +1,98 @@
+//! The badge shown on the app icon, e.g., the macOS dock badge,
+//! which shows the total number of unread messages or mentions across all rooms.
+//!
+//! Setting the badge is currently only supported on macOS.
+//! Windows taskbar overlays require rendering the count into an icon,
+//! and Android launchers only show badges for posted notifications,
+//! neither of which Robrix does yet, so the badge is a no-op on those platforms.
+
+use std::sync::Mutex;
+
+use crate::settings::app_settings::{app_settings, AppBadge};
+
+/// The largest count shown in the badge; larger counts are shown as e.g. "99+".
+const MAX_BADGE_COUNT: u64 = 99;
+
+/// The total unread counts across all rooms, from which the badge is computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UnreadTotals {
+    pub unread_messages: u64,
+    pub unread_mentions: u64,
+}
+
+/// The most recent unread totals and the badge label that was last set for them.
+static BADGE_STATE: Mutex<(UnreadTotals, Option<String>)> = Mutex::new((
+    UnreadTotals { unread_messages: 0, unread_mentions: 0 },
+    None,
+));
+
+/// Returns the badge label for the given unread totals, or `None` if no badge should be shown.
+pub fn badge_label(totals: UnreadTotals, mode: AppBadge) -> Option<String> {
+    let count = match mode {
+        AppBadge::Off => 0,
+        AppBadge::MentionsOnly => totals.unread_mentions,
+        AppBadge::AllUnread => totals.unread_messages,
+    };
+    match count {
+        0 => None,
+        c if c > MAX_BADGE_COUNT => Some(format!("{MAX_BADGE_COUNT}+")),
+        c => Some(c.to_string()),
+    }
+}
+
+/// Updates the app icon badge to show the given unread totals.
+///
+/// The platform badge is only updated if its label actually changed.
+pub fn update_app_badge(totals: UnreadTotals) {
+    let label = badge_label(totals, app_settings().app_badge);
+    let mut state = BADGE_STATE.lock().unwrap();
+    state.0 = totals;
+    if state.1 != label {
+        set_platform_badge(label.as_deref());
+        state.1 = label;
+    }
+}
+
+/// Re-computes the app icon badge from the most recent unread totals,
+/// e.g., after the badge setting has changed.
+pub fn refresh_app_badge() {
+    let totals = BADGE_STATE.lock().unwrap().0;
+    update_app_badge(totals);
+}
+
+#[cfg(target_os = "macos")]
+fn set_platform_badge(label: Option<&str>) {
+    use objc2_app_kit::NSApplication;
+    use objc2_foundation::{MainThreadMarker, NSString};
+
+    let Some(mtm) = MainThreadMarker::new() else {
+        tracing::error!("BUG: the app badge must be set from the main thread.");
+        return;
+    };
+    let label = label.map(NSString::from_str);
+    let app = NSApplication::sharedApplication(mtm);
+    // SAFETY: this is called on the main thread, as ensured by the `MainThreadMarker` above.
+    unsafe {
+        app.dockTile().setBadgeLabel(label.as_deref());
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn set_platform_badge(_label: Option<&str>) { }
+
+#[cfg(test)]
+mod tests_app_badge {
+    use super::*;
+
+    #[test]
+    fn test_badge_label() {
+        let totals = UnreadTotals { unread_messages: 12, unread_mentions: 2 };
+        assert_eq!(badge_label(totals, AppBadge::AllUnread).as_deref(), Some("12"));
+        assert_eq!(badge_label(totals, AppBadge::MentionsOnly).as_deref(), Some("2"));
+        assert_eq!(badge_label(totals, AppBadge::Off), None);
+
+        let totals = UnreadTotals { unread_messages: 150, unread_mentions: 0 };
+        assert_eq!(badge_label(totals, AppBadge::AllUnread).as_deref(), Some("99+"));
+        assert_eq!(badge_label(totals, AppBadge::MentionsOnly), None);
+    }
+}
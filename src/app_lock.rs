@@ -0,0 +1,505 @@
+//! An optional app lock, which hides all room content behind a lock screen
+//! upon launch and after a period of inactivity, until the user enters their PIN
+//! or authenticates using the platform's biometrics (e.g., Touch ID, Windows Hello).
+//!
+//! The PIN is only stored as a salted PBKDF2-HMAC-SHA256 hash in the app settings,
+//! and repeated incorrect attempts lock out further attempts for increasingly long periods.
+//! Note that the app lock protects against casual access on a shared or unattended device;
+//! it does *not* encrypt any data stored on disk.
+
+use std::time::{Duration, SystemTime};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use makepad_widgets::*;
+use tracing::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::{
+    i18n::{tr, tr_args},
+    settings::app_settings::{app_settings, update_app_settings},
+    shared::popup_list::enqueue_popup_notification,
+};
+
+/// The minimum number of digits in an app lock PIN.
+pub const MIN_PIN_LEN: usize = 4;
+/// The maximum number of digits in an app lock PIN.
+pub const MAX_PIN_LEN: usize = 12;
+/// The number of PBKDF2 iterations used to hash the PIN,
+/// which makes guessing it from the settings file slower.
+const PIN_HASH_ITERATIONS: u32 = if cfg!(test) { 1_000 } else { 600_000 };
+/// The number of incorrect PIN attempts allowed before further attempts are locked out.
+const MAX_FREE_PIN_ATTEMPTS: u32 = 5;
+/// The lockout period after the first attempt beyond [`MAX_FREE_PIN_ATTEMPTS`],
+/// which doubles upon each further incorrect attempt.
+const BASE_LOCKOUT_SECS: u64 = 30;
+/// The maximum lockout period after an incorrect PIN attempt.
+const MAX_LOCKOUT_SECS: u64 = 60 * 60;
+/// How often the app checks whether it has been inactive for long enough to be locked, in seconds.
+pub const INACTIVITY_CHECK_INTERVAL_SECS: f64 = 15.0;
+
+/// After how much inactivity the app is locked.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppLockTimeout {
+    /// The app is only locked when it is launched.
+    OnLaunchOnly,
+    OneMinute,
+    #[default]
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+}
+
+impl AppLockTimeout {
+    /// All options, in the order they are shown in the settings screen.
+    pub const ALL: [AppLockTimeout; 5] = [
+        AppLockTimeout::OnLaunchOnly,
+        AppLockTimeout::OneMinute,
+        AppLockTimeout::FiveMinutes,
+        AppLockTimeout::FifteenMinutes,
+        AppLockTimeout::OneHour,
+    ];
+
+    /// Returns the period of inactivity after which the app is locked, if any.
+    pub fn duration(self) -> Option<Duration> {
+        match self {
+            AppLockTimeout::OnLaunchOnly => None,
+            AppLockTimeout::OneMinute => Some(Duration::from_secs(60)),
+            AppLockTimeout::FiveMinutes => Some(Duration::from_secs(5 * 60)),
+            AppLockTimeout::FifteenMinutes => Some(Duration::from_secs(15 * 60)),
+            AppLockTimeout::OneHour => Some(Duration::from_secs(60 * 60)),
+        }
+    }
+}
+
+/// The user's app lock settings. The app lock is enabled once the user has set a PIN.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppLockSettings {
+    /// The base64-encoded salted hash of the user's PIN, if the app lock is enabled.
+    pub pin_hash: Option<String>,
+    /// The base64-encoded random salt used to hash the user's PIN.
+    pub pin_salt: String,
+    /// After how much inactivity the app is locked.
+    pub timeout: AppLockTimeout,
+    /// Whether the user can also unlock the app using the platform's biometric authentication.
+    pub use_biometrics: bool,
+    /// The number of consecutive incorrect PIN attempts.
+    ///
+    /// This is persisted such that restarting the app doesn't reset the lockout.
+    pub failed_attempts: u32,
+    /// The time (in seconds since the Unix epoch) until which PIN attempts are locked out.
+    pub locked_out_until: Option<u64>,
+}
+
+impl AppLockSettings {
+    /// Returns `true` if the app lock is enabled, i.e., the user has set a PIN.
+    pub fn is_enabled(&self) -> bool {
+        self.pin_hash.is_some()
+    }
+
+    /// Sets the user's PIN (with a newly-generated salt), which enables the app lock.
+    ///
+    /// Hashing the PIN is deliberately slow, so this must not be called on the main UI thread;
+    /// use [`start_setting_pin()`] instead.
+    pub fn set_pin(&mut self, pin: &str) {
+        self.pin_salt = STANDARD.encode(rand::random::<[u8; 16]>());
+        self.pin_hash = Some(hash_pin(&self.pin_salt, pin));
+        self.reset_failed_attempts();
+    }
+
+    /// Removes the user's PIN, which disables the app lock.
+    pub fn remove_pin(&mut self) {
+        self.pin_hash = None;
+        self.pin_salt.clear();
+        self.reset_failed_attempts();
+    }
+
+    /// Returns `true` if the given PIN matches the user's PIN.
+    ///
+    /// Hashing the PIN is deliberately slow, so this must not be called on the main UI thread;
+    /// use [`start_verifying_pin()`] instead.
+    pub fn verify_pin(&self, pin: &str) -> bool {
+        self.pin_hash.as_deref().is_some_and(|hash| hash == hash_pin(&self.pin_salt, pin))
+    }
+
+    /// Returns how long PIN attempts are still locked out for, if they are locked out at `now`.
+    pub fn lockout_remaining(&self, now: SystemTime) -> Option<Duration> {
+        let until = SystemTime::UNIX_EPOCH + Duration::from_secs(self.locked_out_until?);
+        until.duration_since(now).ok().filter(|remaining| !remaining.is_zero())
+    }
+
+    /// Records an incorrect PIN attempt at `now`, which locks out further attempts
+    /// for an increasingly long period once more than [`MAX_FREE_PIN_ATTEMPTS`] have failed.
+    pub fn record_failed_attempt(&mut self, now: SystemTime) {
+        self.failed_attempts = self.failed_attempts.saturating_add(1);
+        let Some(excess) = self.failed_attempts.checked_sub(MAX_FREE_PIN_ATTEMPTS + 1) else { return };
+        let lockout_secs = BASE_LOCKOUT_SECS
+            .saturating_mul(1u64.checked_shl(excess).unwrap_or(u64::MAX))
+            .min(MAX_LOCKOUT_SECS);
+        let now_secs = now.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default().as_secs();
+        self.locked_out_until = Some(now_secs + lockout_secs);
+    }
+
+    /// Clears the record of incorrect PIN attempts, e.g., after the app was unlocked.
+    pub fn reset_failed_attempts(&mut self) {
+        self.failed_attempts = 0;
+        self.locked_out_until = None;
+    }
+}
+
+/// Returns `true` if the given text is a valid app lock PIN, i.e., only a few digits.
+pub fn is_valid_pin(pin: &str) -> bool {
+    (MIN_PIN_LEN..=MAX_PIN_LEN).contains(&pin.len())
+        && pin.chars().all(|c| c.is_ascii_digit())
+}
+
+/// Returns the base64-encoded PBKDF2-HMAC-SHA256 hash of the given PIN with the given salt.
+fn hash_pin(salt: &str, pin: &str) -> String {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(pin.as_bytes(), salt.as_bytes(), PIN_HASH_ITERATIONS, &mut key);
+    STANDARD.encode(key)
+}
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    pub AppLockScreen = {{AppLockScreen}} {
+        visible: false
+        width: Fill, height: Fill
+        flow: Down
+        align: {x: 0.5, y: 0.5}
+        spacing: 15
+        show_bg: true
+        draw_bg: {
+            color: (COLOR_PRIMARY)
+        }
+
+        title = <Label> {
+            width: Fit, height: Fit
+            draw_text: {
+                color: (COLOR_TEXT)
+                text_style: <TITLE_TEXT>{font_size: 16.0}
+            }
+            text: "Robrix is locked"
+        }
+
+        prompt = <Label> {
+            width: Fit, height: Fit
+            draw_text: {
+                color: (MESSAGE_TEXT_COLOR)
+                text_style: <REGULAR_TEXT>{font_size: 11.0}
+            }
+        }
+
+        pin_input = <RobrixTextInput> {
+            width: 250, height: 40
+            empty_message: "PIN"
+            draw_text: { text_style: { is_secret: true } }
+        }
+
+        error_label = <Label> {
+            width: Fit, height: Fit
+            draw_text: {
+                color: (COLOR_DANGER_RED)
+                text_style: <REGULAR_TEXT>{font_size: 10.0}
+            }
+            text: ""
+        }
+
+        <View> {
+            width: Fit, height: Fit
+            flow: Right
+            spacing: 10
+
+            biometrics_button = <RobrixIconButton> {
+                padding: {left: 15, right: 15}
+                draw_icon: {
+                    svg_file: (ICON_CHECKMARK)
+                    color: (COLOR_TEXT)
+                }
+                icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                text: "Use biometrics"
+            }
+
+            unlock_button = <RobrixIconButton> {
+                padding: {left: 15, right: 15}
+                draw_icon: {
+                    svg_file: (ICON_CHECKMARK)
+                    color: (COLOR_SELECTED_PRIMARY),
+                }
+                icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+
+                draw_bg: {
+                    border_color: (COLOR_SELECTED_PRIMARY),
+                    color: #f0f7ff // light blue
+                }
+                text: "Unlock"
+                draw_text:{
+                    color: (COLOR_SELECTED_PRIMARY),
+                }
+            }
+        }
+    }
+}
+
+/// Actions related to the app lock.
+#[derive(Clone, Debug, DefaultNone)]
+pub enum AppLockAction {
+    /// The result of a biometric authentication attempt, posted from a background thread.
+    BiometricResult(bool),
+    /// The result of checking an entered PIN, posted from a background thread.
+    PinVerified(bool),
+    /// A new PIN was hashed and saved on a background thread, which enabled the app lock.
+    PinSet,
+    None,
+}
+
+/// The lock screen that covers the entire app while it is locked.
+#[derive(Live, LiveHook, Widget)]
+pub struct AppLockScreen {
+    #[deref] view: View,
+    /// Whether a biometric authentication attempt is currently in progress.
+    #[rust] biometrics_pending: bool,
+    /// Whether an entered PIN is currently being checked.
+    #[rust] pin_pending: bool,
+}
+
+impl Widget for AppLockScreen {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.label(id!(title)).set_text(cx, tr("app_lock.title"));
+        self.label(id!(prompt)).set_text(cx, tr("app_lock.prompt"));
+        self.button(id!(unlock_button)).set_text(cx, tr("app_lock.unlock"));
+        self.button(id!(biometrics_button)).set_text(cx, tr("app_lock.use_biometrics"));
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for AppLockScreen {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        if !self.view.visible { return }
+
+        let pin_input = self.text_input(id!(pin_input));
+        if (self.button(id!(unlock_button)).clicked(actions) || pin_input.returned(actions).is_some())
+            && !self.pin_pending
+        {
+            let pin = pin_input.text();
+            pin_input.set_text(cx, "");
+            if let Some(remaining) = app_settings().app_lock.lockout_remaining(SystemTime::now()) {
+                self.show_error(cx, &lockout_message(remaining));
+            } else {
+                self.pin_pending = true;
+                self.label(id!(error_label)).set_text(cx, "");
+                start_verifying_pin(pin.trim().to_owned());
+            }
+        }
+
+        if self.button(id!(biometrics_button)).clicked(actions) && !self.biometrics_pending {
+            self.biometrics_pending = true;
+            start_biometric_authentication();
+        }
+
+        for action in actions {
+            match action.downcast_ref() {
+                Some(AppLockAction::BiometricResult(success)) => {
+                    self.biometrics_pending = false;
+                    if *success {
+                        self.unlock(cx);
+                    } else {
+                        self.show_error(cx, tr("app_lock.biometrics_failed"));
+                    }
+                }
+                Some(AppLockAction::PinVerified(success)) => {
+                    self.pin_pending = false;
+                    if *success {
+                        self.unlock(cx);
+                        continue;
+                    }
+                    let now = SystemTime::now();
+                    update_app_settings(|s| s.app_lock.record_failed_attempt(now));
+                    let error = match app_settings().app_lock.lockout_remaining(now) {
+                        Some(remaining) => lockout_message(remaining),
+                        None => tr("app_lock.wrong_pin").to_string(),
+                    };
+                    self.show_error(cx, &error);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl AppLockScreen {
+    /// Shows this lock screen, which hides all other app content until the user unlocks it.
+    fn lock(&mut self, cx: &mut Cx) {
+        if self.view.visible { return }
+        info!("Locking the app.");
+        self.view.set_visible(cx, true);
+        self.label(id!(error_label)).set_text(cx, "");
+        self.button(id!(biometrics_button)).set_visible(cx, app_settings().app_lock.use_biometrics);
+        let pin_input = self.text_input(id!(pin_input));
+        pin_input.set_text(cx, "");
+        pin_input.set_key_focus(cx);
+        self.redraw(cx);
+    }
+
+    /// Shows the given error message below the PIN input, and re-focuses that input.
+    fn show_error(&mut self, cx: &mut Cx, error: &str) {
+        self.label(id!(error_label)).set_text(cx, error);
+        self.text_input(id!(pin_input)).set_key_focus(cx);
+        self.redraw(cx);
+    }
+
+    /// Hides this lock screen.
+    fn unlock(&mut self, cx: &mut Cx) {
+        info!("Unlocking the app.");
+        if app_settings().app_lock.failed_attempts > 0 {
+            update_app_settings(|s| s.app_lock.reset_failed_attempts());
+        }
+        self.view.set_visible(cx, false);
+        cx.redraw_all();
+    }
+}
+
+impl AppLockScreenRef {
+    /// See [`AppLockScreen::lock()`].
+    pub fn lock(&self, cx: &mut Cx) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.lock(cx);
+    }
+
+    /// Returns `true` if the app is currently locked.
+    pub fn is_locked(&self) -> bool {
+        self.borrow().is_some_and(|inner| inner.view.visible)
+    }
+}
+
+/// Returns the message shown when PIN attempts are locked out for the given `remaining` duration.
+fn lockout_message(remaining: Duration) -> String {
+    // Round up such that the user is never told to wait for 0 seconds.
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    tr_args("app_lock.locked_out", &[("seconds", &seconds.to_string())])
+}
+
+/// Checks the given PIN against the user's PIN on a background thread,
+/// which posts an [`AppLockAction::PinVerified`] with the result.
+fn start_verifying_pin(pin: String) {
+    let app_lock = app_settings().app_lock.clone();
+    std::thread::spawn(move || {
+        Cx::post_action(AppLockAction::PinVerified(app_lock.verify_pin(&pin)));
+    });
+}
+
+/// Hashes and saves the given new PIN on a background thread, which enables the app lock.
+///
+/// Once saved, this shows a popup notification and posts an [`AppLockAction::PinSet`].
+pub fn start_setting_pin(pin: String) {
+    std::thread::spawn(move || {
+        let mut new_pin = AppLockSettings::default();
+        new_pin.set_pin(&pin);
+        update_app_settings(|s| {
+            s.app_lock.pin_hash = new_pin.pin_hash;
+            s.app_lock.pin_salt = new_pin.pin_salt;
+            s.app_lock.reset_failed_attempts();
+        });
+        enqueue_popup_notification(tr("settings.app_lock.pin_saved").to_string());
+        Cx::post_action(AppLockAction::PinSet);
+    });
+}
+
+/// Prompts the user to authenticate using the platform's biometrics on a background thread,
+/// which posts an [`AppLockAction::BiometricResult`] once the user has responded.
+fn start_biometric_authentication() {
+    use robius_authentication::{
+        AndroidText, BiometricStrength, Context, PolicyBuilder, Text, WindowsText,
+    };
+
+    std::thread::spawn(|| {
+        let Some(policy) = PolicyBuilder::new()
+            .biometrics(Some(BiometricStrength::Strong))
+            .password(true)
+            .watch(true)
+            .build()
+        else {
+            error!("Failed to build the biometric authentication policy.");
+            Cx::post_action(AppLockAction::BiometricResult(false));
+            return;
+        };
+        let Some(windows) = WindowsText::new("Robrix", tr("app_lock.biometrics_reason")) else {
+            Cx::post_action(AppLockAction::BiometricResult(false));
+            return;
+        };
+        let text = Text {
+            android: AndroidText {
+                title: "Robrix",
+                subtitle: None,
+                description: Some(tr("app_lock.biometrics_reason")),
+            },
+            apple: tr("app_lock.biometrics_reason"),
+            windows,
+        };
+        let result = Context::new(()).blocking_authenticate(text, &policy);
+        if let Err(e) = &result {
+            warn!("Biometric authentication failed: {e:?}");
+        }
+        Cx::post_action(AppLockAction::BiometricResult(result.is_ok()));
+    });
+}
+
+#[cfg(test)]
+mod tests_app_lock {
+    use super::*;
+
+    #[test]
+    fn test_pin() {
+        assert!(is_valid_pin("1234"));
+        assert!(!is_valid_pin("123"));
+        assert!(!is_valid_pin("12a4"));
+        assert!(!is_valid_pin("1234567890123"));
+
+        let mut settings = AppLockSettings::default();
+        assert!(!settings.is_enabled());
+        assert!(!settings.verify_pin("1234"));
+        settings.set_pin("1234");
+        assert!(settings.is_enabled());
+        assert!(settings.verify_pin("1234"));
+        assert!(!settings.verify_pin("4321"));
+        settings.remove_pin();
+        assert!(!settings.verify_pin("1234"));
+    }
+
+    #[test]
+    fn test_lockout_after_failed_attempts() {
+        let mut settings = AppLockSettings::default();
+        settings.set_pin("1234");
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000);
+        for _ in 0..MAX_FREE_PIN_ATTEMPTS {
+            settings.record_failed_attempt(now);
+            assert_eq!(settings.lockout_remaining(now), None);
+        }
+        settings.record_failed_attempt(now);
+        assert_eq!(settings.lockout_remaining(now), Some(Duration::from_secs(BASE_LOCKOUT_SECS)));
+        settings.record_failed_attempt(now);
+        assert_eq!(settings.lockout_remaining(now), Some(Duration::from_secs(2 * BASE_LOCKOUT_SECS)));
+        let later = now + Duration::from_secs(2 * BASE_LOCKOUT_SECS);
+        assert_eq!(settings.lockout_remaining(later), None);
+
+        for _ in 0..64 {
+            settings.record_failed_attempt(now);
+        }
+        assert_eq!(settings.lockout_remaining(now), Some(Duration::from_secs(MAX_LOCKOUT_SECS)));
+
+        settings.reset_failed_attempts();
+        assert_eq!(settings.failed_attempts, 0);
+        assert_eq!(settings.lockout_remaining(now), None);
+    }
+}
@@ -0,0 +1,137 @@
+//! Detection of users who are bridged into Matrix from other chat platforms,
+//! e.g., IRC, Telegram, or Discord.
+//!
+//! A user is considered bridged if their user ID matches the naming pattern
+//! of a well-known bridge, or the pattern of a bridge that a room declares
+//! via an `m.bridge` state event (or its older `uk.half-shot.bridge` equivalent).
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use matrix_sdk::ruma::{OwnedRoomId, OwnedUserId, RoomId, UserId};
+use serde_json::Value;
+
+/// The types of state events that describe a bridge set up in a room,
+/// as defined by MSC2346.
+pub const BRIDGE_STATE_EVENT_TYPES: [&str; 2] = ["m.bridge", "uk.half-shot.bridge"];
+
+/// The user ID localpart prefixes used by the puppet users of well-known bridges,
+/// and the name of the platform each one bridges to.
+const KNOWN_BRIDGE_PREFIXES: &[(&str, &str)] = &[
+    ("telegram_", "Telegram"),
+    ("discord_", "Discord"),
+    ("whatsapp_", "WhatsApp"),
+    ("signal_", "Signal"),
+    ("slack_", "Slack"),
+    ("irc_", "IRC"),
+    ("facebook_", "Facebook"),
+    ("messenger_", "Messenger"),
+    ("instagram_", "Instagram"),
+    ("twitter_", "Twitter"),
+    ("gmessages_", "Google Messages"),
+    ("googlechat_", "Google Chat"),
+    ("imessage_", "iMessage"),
+    ("linkedin_", "LinkedIn"),
+    ("xmpp_", "XMPP"),
+];
+
+/// A bridge to another chat platform that is set up in a room.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RoomBridge {
+    /// The bridge's identifier for the remote platform, e.g., `telegram`.
+    pub protocol_id: String,
+    /// The human-readable name of the remote platform, e.g., `Telegram`.
+    pub protocol_name: String,
+    /// The user ID of the bridge's bot user, if given.
+    pub bridgebot: Option<OwnedUserId>,
+}
+
+impl RoomBridge {
+    /// Parses the content of a bridge state event.
+    pub fn from_state_event_content(content: &Value) -> Option<Self> {
+        let protocol = content.get("protocol")?;
+        let protocol_id = protocol.get("id")?.as_str()?.to_owned();
+        let protocol_name = protocol.get("displayname")
+            .and_then(Value::as_str)
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| protocol_id.clone());
+        let bridgebot = content.get("bridgebot")
+            .and_then(Value::as_str)
+            .and_then(|s| UserId::parse(s).ok());
+        Some(Self { protocol_id, protocol_name, bridgebot })
+    }
+
+    /// Returns whether the given user is a puppet or bot user of this bridge.
+    fn is_bridged_user(&self, user_id: &UserId) -> bool {
+        if self.bridgebot.as_deref() == Some(user_id) {
+            return true;
+        }
+        let localpart = user_id.localpart().trim_start_matches('_').to_lowercase();
+        localpart.strip_prefix(&self.protocol_id.to_lowercase())
+            .is_some_and(|rest| rest.starts_with('_'))
+    }
+}
+
+/// The bridges set up in each room, as declared by that room's bridge state events.
+static ROOM_BRIDGES: Mutex<BTreeMap<OwnedRoomId, Vec<RoomBridge>>> = Mutex::new(BTreeMap::new());
+
+/// Sets the bridges that are set up in the given room.
+pub fn set_room_bridges(room_id: OwnedRoomId, bridges: Vec<RoomBridge>) {
+    ROOM_BRIDGES.lock().unwrap().insert(room_id, bridges);
+}
+
+/// Returns the name of the platform that the given user is bridged from, if any.
+///
+/// Bridges declared by the given room take precedence over well-known bridge naming patterns.
+pub fn bridged_platform(user_id: &UserId, room_id: Option<&RoomId>) -> Option<String> {
+    if let Some(room_id) = room_id {
+        let room_bridges = ROOM_BRIDGES.lock().unwrap();
+        let bridge = room_bridges.get(room_id)
+            .and_then(|bridges| bridges.iter().find(|b| b.is_bridged_user(user_id)));
+        if let Some(bridge) = bridge {
+            return Some(bridge.protocol_name.clone());
+        }
+    }
+    known_bridged_platform(user_id).map(ToOwned::to_owned)
+}
+
+/// Returns the name of the platform that the given user is bridged from,
+/// based solely on the naming patterns of well-known bridges.
+///
+/// Bridges commonly prefix their puppet users' localparts with the platform name,
+/// optionally preceded by an underscore, e.g., `@telegram_1234:example.org`
+/// or `@_discord_1234:example.org`.
+pub fn known_bridged_platform(user_id: &UserId) -> Option<&'static str> {
+    let localpart = user_id.localpart().trim_start_matches('_');
+    KNOWN_BRIDGE_PREFIXES.iter()
+        .find(|(prefix, _)| localpart.starts_with(prefix))
+        .map(|(_, platform)| *platform)
+}
+
+#[cfg(test)]
+mod tests_bridges {
+    use matrix_sdk::ruma::user_id;
+
+    use super::*;
+
+    #[test]
+    fn test_known_bridged_platform() {
+        assert_eq!(known_bridged_platform(user_id!("@telegram_1234:example.org")), Some("Telegram"));
+        assert_eq!(known_bridged_platform(user_id!("@_discord_1234:example.org")), Some("Discord"));
+        assert_eq!(known_bridged_platform(user_id!("@alice:example.org")), None);
+        assert_eq!(known_bridged_platform(user_id!("@telegram:example.org")), None);
+    }
+
+    #[test]
+    fn test_room_bridge_from_state_event() {
+        let content = serde_json::json!({
+            "bridgebot": "@mattermostbot:example.org",
+            "protocol": { "id": "mattermost", "displayname": "Mattermost" },
+        });
+        let bridge = RoomBridge::from_state_event_content(&content).unwrap();
+        assert_eq!(bridge.protocol_name, "Mattermost");
+        assert!(bridge.is_bridged_user(user_id!("@mattermostbot:example.org")));
+        assert!(bridge.is_bridged_user(user_id!("@mattermost_bob:example.org")));
+        assert!(bridge.is_bridged_user(user_id!("@_mattermost_bob:example.org")));
+        assert!(!bridge.is_bridged_user(user_id!("@bob:example.org")));
+    }
+}
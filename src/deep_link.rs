@@ -0,0 +1,45 @@
+//! Handling of deep links to Matrix rooms, users, and events,
+//! i.e., `matrix:` URIs and `https://matrix.to/#/...` links.
+//!
+//! Robrix registers itself as the system handler for the `matrix:` and `robrix:` URI schemes
+//! (see the `deep_link_protocols` in `Cargo.toml`), in which case the OS launches Robrix
+//! with the deep link as a command-line argument.
+//! Links to other rooms that are clicked within Robrix are also handled here.
+
+use matrix_sdk::ruma::{matrix_uri::MatrixId, MatrixToUri, MatrixUri};
+
+/// The scheme of deep links that are specific to Robrix, e.g., `robrix:r/room:example.org`.
+///
+/// These are otherwise identical to `matrix:` URIs.
+const ROBRIX_SCHEME: &str = "robrix:";
+
+/// An action that requests navigating to the target of a deep link.
+///
+/// These are posted as non-widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum DeepLinkAction {
+    /// Navigate to the given Matrix room, room alias, user, or event.
+    Navigate(MatrixId),
+    /// Open the given `matrix.to` link in another app, e.g., a web browser,
+    /// because it refers to a room that the user hasn't joined and thus can't be shown here.
+    OpenExternally(String),
+    /// Bring the app's window to the front, e.g., because Robrix was launched again.
+    Focus,
+}
+
+/// Parses the given string as a `matrix:` URI, a `robrix:` URI, or a `matrix.to` link.
+pub fn parse_deep_link(link: &str) -> Option<MatrixId> {
+    let link = link.trim();
+    if let Some(rest) = link.strip_prefix(ROBRIX_SCHEME) {
+        return MatrixUri::parse(&format!("matrix:{rest}")).ok()
+            .map(|uri| uri.id().clone());
+    }
+    MatrixUri::parse(link).map(|uri| uri.id().clone())
+        .or_else(|_| MatrixToUri::parse(link).map(|uri| uri.id().clone()))
+        .ok()
+}
+
+/// Returns whether the given command-line argument is a deep link.
+pub fn is_deep_link(arg: &str) -> bool {
+    parse_deep_link(arg).is_some()
+}
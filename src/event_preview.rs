@@ -8,7 +8,7 @@
 use matrix_sdk::ruma::events::{room::{guest_access::GuestAccess, history_visibility::HistoryVisibility, join_rules::JoinRule, message::{MessageFormat, MessageType}}, AnySyncMessageLikeEvent, AnySyncTimelineEvent, FullStateEventContent, SyncMessageLikeEvent};
 use matrix_sdk_ui::timeline::{self, AnyOtherFullStateEventContent, EventTimelineItem, MemberProfileChange, MembershipChange, RoomMembershipChange, TimelineItemContent};
 
-use crate::utils;
+use crate::{i18n::{tr, tr_args}, utils};
 
 /// What should be displayed before the text preview of an event.
 pub enum BeforeText {
@@ -53,21 +53,21 @@ pub fn text_preview_of_timeline_item(
     match content {
         TimelineItemContent::Message(m) => text_preview_of_message(m, sender_username),
         TimelineItemContent::RedactedMessage => TextPreview::from((
-            String::from("[Message was deleted]"),
+            tr("preview.message_deleted").to_string(),
             BeforeText::UsernameWithColon,
         )),
         TimelineItemContent::Sticker(sticker) => TextPreview::from((
-            format!("[Sticker]: <i>{}</i>", sticker.content().body),
+            text_preview_of_sticker(&sticker.content().body),
             BeforeText::UsernameWithColon,
         )),
         TimelineItemContent::UnableToDecrypt(_encrypted_msg) => TextPreview::from((
-            String::from("[Unable to decrypt message]"),
+            tr("preview.unable_to_decrypt").to_string(),
             BeforeText::UsernameWithColon,
         )),
         TimelineItemContent::MembershipChange(membership_change) => {
             text_preview_of_room_membership_change(membership_change)
                 .unwrap_or_else(|| TextPreview::from((
-                    String::from("<i>underwent a membership change</i>"),
+                    tr("preview.membership_change").to_string(),
                     BeforeText::UsernameWithoutColon,
                 )))
         }
@@ -77,30 +77,31 @@ pub fn text_preview_of_timeline_item(
         TimelineItemContent::OtherState(other_state) => {
             text_preview_of_other_state(other_state)
                 .unwrap_or_else(|| TextPreview::from((
-                    String::from("<i>initiated another state change</i>"),
+                    tr("preview.other_state").to_string(),
                     BeforeText::UsernameWithoutColon,
                 )))
         }
         TimelineItemContent::FailedToParseMessageLike { event_type, .. } => TextPreview::from((
-            format!("[Failed to parse <i>{}</i> message]", event_type),
+            tr_args("preview.failed_to_parse_message", &[("event_type", &event_type.to_string())]),
             BeforeText::UsernameWithColon,
         )),
         TimelineItemContent::FailedToParseState { event_type, .. } => TextPreview::from((
-            format!("[Failed to parse <i>{}</i> state]", event_type),
+            tr_args("preview.failed_to_parse_state", &[("event_type", &event_type.to_string())]),
             BeforeText::UsernameWithColon,
         )),
         TimelineItemContent::Poll(poll_state) => TextPreview::from((
-            format!("[Poll]: {}", poll_state.fallback_text()
-                .unwrap_or_else(|| poll_state.results().question)
-            ),
+            tr_args("preview.poll", &[(
+                "question",
+                &poll_state.fallback_text().unwrap_or_else(|| poll_state.results().question),
+            )]),
             BeforeText::UsernameWithColon,
         )),
         TimelineItemContent::CallInvite => TextPreview::from((
-            String::from("[Call Invitation]"),
+            tr("preview.call_invite").to_string(),
             BeforeText::UsernameWithColon,
         )),
         TimelineItemContent::CallNotify => TextPreview::from((
-            String::from("[Call Notification]"),
+            tr("preview.call_notify").to_string(),
             BeforeText::UsernameWithColon,
         )),
     }
@@ -114,13 +115,13 @@ pub fn body_of_timeline_item(
 ) -> String {
     match event_tl_item.content() {
         TimelineItemContent::Message(m) => m.body().into(),
-        TimelineItemContent::RedactedMessage => "[Message was deleted]".into(),
+        TimelineItemContent::RedactedMessage => tr("preview.message_deleted").into(),
         TimelineItemContent::Sticker(sticker) => sticker.content().body.clone(),
-        TimelineItemContent::UnableToDecrypt(_encrypted_msg) => "[Unable to Decrypt]".into(),
+        TimelineItemContent::UnableToDecrypt(_encrypted_msg) => tr("body.unable_to_decrypt").into(),
         TimelineItemContent::MembershipChange(membership_change) => {
             text_preview_of_room_membership_change(membership_change)
                 .unwrap_or_else(|| TextPreview::from((
-                    String::from("underwent a membership change."),
+                    tr("body.membership_change").to_string(),
                     BeforeText::UsernameWithoutColon,
                 )))
                 .format_with(&utils::get_or_fetch_event_sender(event_tl_item, None))
@@ -134,24 +135,42 @@ pub fn body_of_timeline_item(
         TimelineItemContent::OtherState(other_state) => {
             text_preview_of_other_state(other_state)
                 .unwrap_or_else(|| TextPreview::from((
-                    String::from("initiated another state change."),
+                    tr("body.other_state").to_string(),
                     BeforeText::UsernameWithoutColon,
                 )))
                 .format_with(&utils::get_or_fetch_event_sender(event_tl_item, None))
         }
         TimelineItemContent::FailedToParseMessageLike { event_type, error } => {
-            format!("Failed to parse {} message. Error: {}", event_type, error)
+            tr_args("body.failed_to_parse_message", &[
+                ("event_type", &event_type.to_string()),
+                ("error", &error.to_string()),
+            ])
         }
         TimelineItemContent::FailedToParseState { event_type, error, state_key } => {
-            format!("Failed to parse {} state; key: {}. Error: {}", event_type, state_key, error)
+            tr_args("body.failed_to_parse_state", &[
+                ("event_type", &event_type.to_string()),
+                ("state_key", state_key),
+                ("error", &error.to_string()),
+            ])
         }
         TimelineItemContent::Poll(poll_state) => {
-            format!("[Poll]: {}", 
-                poll_state.fallback_text().unwrap_or_else(|| poll_state.results().question)
-            )
+            tr_args("preview.poll", &[(
+                "question",
+                &poll_state.fallback_text().unwrap_or_else(|| poll_state.results().question),
+            )])
         }
-        TimelineItemContent::CallInvite => String::from("[Call Invitation]"),
-        TimelineItemContent::CallNotify => String::from("[Call Notification]"),
+        TimelineItemContent::CallInvite => tr("preview.call_invite").into(),
+        TimelineItemContent::CallNotify => tr("preview.call_notify").into(),
+    }
+}
+
+
+/// Returns a text preview of a sticker with the given `body` (its description).
+fn text_preview_of_sticker(body: &str) -> String {
+    if body.trim().is_empty() {
+        tr("preview.sticker_no_body").to_string()
+    } else {
+        tr_args("preview.sticker", &[("body", body)])
     }
 }
 
@@ -162,13 +181,13 @@ pub fn text_preview_of_message(
     sender_username: &str,
 ) -> TextPreview {
     let text = match message.msgtype() {
-        MessageType::Audio(audio) => format!(
-            "[Audio]: <i>{}</i>",
-            if let Some(formatted_body) = audio.formatted.as_ref() {
+        MessageType::Audio(audio) => tr_args(
+            "preview.audio",
+            &[("body", if let Some(formatted_body) = audio.formatted.as_ref() {
                 &formatted_body.body
             } else {
                 &audio.body
-            }
+            })],
         ),
         MessageType::Emote(emote) => format!(
             "* {} {}",
@@ -179,25 +198,25 @@ pub fn text_preview_of_message(
                 &emote.body
             }
         ),
-        MessageType::File(file) => format!(
-            "[File]: <i>{}</i>",
-            if let Some(formatted_body) = file.formatted.as_ref() {
+        MessageType::File(file) => tr_args(
+            "preview.file",
+            &[("body", if let Some(formatted_body) = file.formatted.as_ref() {
                 &formatted_body.body
             } else {
                 &file.body
-            }
+            })],
         ),
-        MessageType::Image(image) => format!(
-            "[Image]: <i>{}</i>",
-            if let Some(formatted_body) = image.formatted.as_ref() {
+        MessageType::Image(image) => tr_args(
+            "preview.image",
+            &[("body", if let Some(formatted_body) = image.formatted.as_ref() {
                 &formatted_body.body
             } else {
                 &image.body
-            }
+            })],
         ),
-        MessageType::Location(location) => format!(
-            "[Location]: <i>{}</i>",
-            location.body,
+        MessageType::Location(location) => tr_args(
+            "preview.location",
+            &[("body", &location.body)],
         ),
         MessageType::Notice(notice) => format!("<i>{}</i>",
             if let Some(formatted_body) = notice.formatted.as_ref() {
@@ -206,10 +225,12 @@ pub fn text_preview_of_message(
                 &notice.body
             }
         ),
-        MessageType::ServerNotice(notice) => format!(
-            "[Server Notice]: <i>{} -- {}</i>",
-            notice.server_notice_type.as_str(),
-            notice.body,
+        MessageType::ServerNotice(notice) => tr_args(
+            "preview.server_notice",
+            &[
+                ("kind", notice.server_notice_type.as_str()),
+                ("body", &notice.body),
+            ],
         ),
         MessageType::Text(text) => {
             text.formatted
@@ -225,25 +246,25 @@ pub fn text_preview_of_message(
                 )
                 .unwrap_or_else(|| utils::linkify(&text.body, false).to_string())
         }
-        MessageType::VerificationRequest(verification) => format!(
-            "[Verification Request] <i>to user {}</i>",
-            verification.to,
+        MessageType::VerificationRequest(verification) => tr_args(
+            "preview.verification_request",
+            &[("user", verification.to.as_str())],
         ),
-        MessageType::Video(video) => format!(
-            "[Video]: <i>{}</i>",
-            if let Some(formatted_body) = video.formatted.as_ref() {
+        MessageType::Video(video) => tr_args(
+            "preview.video",
+            &[("body", if let Some(formatted_body) = video.formatted.as_ref() {
                 &formatted_body.body
             } else {
                 &video.body
-            }
+            })],
         ),
-        MessageType::_Custom(custom) => format!(
-            "[Custom message]: {:?}",
-            custom,
+        MessageType::_Custom(custom) => tr_args(
+            "preview.custom_message",
+            &[("body", &format!("{custom:?}"))],
         ),
-        other => format!(
-            "[Unknown message type]: {}",
-            other.body(),
+        other => tr_args(
+            "preview.unknown_message",
+            &[("body", other.body())],
         )
     };
     TextPreview::from((text, BeforeText::UsernameWithColon))
@@ -274,17 +295,24 @@ pub fn text_preview_of_redacted_message(
     let text = match redactor_and_reason {
         Some((redactor, Some(reason))) => {
             // TODO: get the redactor's display name if possible
-            format!("{} deleted {}'s message: {:?}.", redactor, original_sender, reason)
+            tr_args("redacted.with_reason", &[
+                ("redactor", redactor.as_str()),
+                ("sender", original_sender),
+                ("reason", &format!("{reason:?}")),
+            ])
         }
         Some((redactor, None)) => {
             if redactor == event_tl_item.sender() {
-                format!("{} deleted their own message.", original_sender)
+                tr_args("redacted.own", &[("sender", original_sender)])
             } else {
-                format!("{} deleted {}'s message.", redactor, original_sender)
+                tr_args("redacted.by_other", &[
+                    ("redactor", redactor.as_str()),
+                    ("sender", original_sender),
+                ])
             }
         }
         None => {
-            format!("{}'s message was deleted.", original_sender)
+            tr_args("redacted.unknown", &[("sender", original_sender)])
         }
     };
     TextPreview::from((text, BeforeText::Nothing))
@@ -297,83 +325,84 @@ pub fn text_preview_of_other_state(
 ) -> Option<TextPreview> {
     let text = match other_state.content() {
         AnyOtherFullStateEventContent::RoomAliases(FullStateEventContent::Original { content, .. }) => {
-            let mut s = String::from("set this room's aliases to ");
-            let last_alias = content.aliases.len() - 1;
-            for (i, alias) in content.aliases.iter().enumerate() {
-                s.push_str(alias.as_str());
-                if i != last_alias {
-                    s.push_str(", ");
-                }
-            }
-            s.push('.');
-            Some(s)
+            let aliases = content.aliases.iter()
+                .map(|alias| alias.as_str())
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(tr_args("state.aliases", &[("aliases", &aliases)]))
         }
         AnyOtherFullStateEventContent::RoomAvatar(_) => {
-            Some(String::from("set this room's avatar picture."))
+            Some(tr("state.avatar").to_string())
         }
         AnyOtherFullStateEventContent::RoomCanonicalAlias(FullStateEventContent::Original { content, .. }) => {
-            Some(format!("set the main address of this room to {}.",
-                content.alias.as_ref().map(|a| a.as_str()).unwrap_or("none")
-            ))
+            Some(tr_args("state.canonical_alias", &[(
+                "alias",
+                content.alias.as_ref().map(|a| a.as_str()).unwrap_or(tr("state.canonical_alias.none")),
+            )]))
         }
         AnyOtherFullStateEventContent::RoomCreate(FullStateEventContent::Original { content, .. }) => {
-            Some(format!("created this room (v{}).", content.room_version.as_str()))
+            Some(tr_args("state.create", &[("version", content.room_version.as_str())]))
         }
         AnyOtherFullStateEventContent::RoomEncryption(_) => {
-            Some(String::from("enabled encryption in this room."))
+            Some(tr("state.encryption").to_string())
         }
         AnyOtherFullStateEventContent::RoomGuestAccess(FullStateEventContent::Original { content, .. }) => {
             Some(match &content.guest_access {
-                GuestAccess::CanJoin => String::from("has allowed guests to join this room."),
-                GuestAccess::Forbidden => String::from("has forbidden guests from joining this room."),
-                custom => format!("has set custom guest access rules for this room: {}", custom.as_str()),
+                GuestAccess::CanJoin => tr("state.guest_access.can_join").to_string(),
+                GuestAccess::Forbidden => tr("state.guest_access.forbidden").to_string(),
+                custom => tr_args("state.guest_access.custom", &[("rules", custom.as_str())]),
             })
         }
         AnyOtherFullStateEventContent::RoomHistoryVisibility(FullStateEventContent::Original { content, .. }) => {
-            Some(format!("set this room's history to be visible by {}",
-                match &content.history_visibility {
-                    HistoryVisibility::Invited => "invited users, since they were invited.",
-                    HistoryVisibility::Joined => "joined users, since they joined.",
-                    HistoryVisibility::Shared => "joined users, for all of time.",
-                    HistoryVisibility::WorldReadable => "anyone for all time.",
-                    custom => custom.as_str(),
-                },
-            ))
+            Some(match &content.history_visibility {
+                HistoryVisibility::Invited => tr("state.history_visibility.invited").to_string(),
+                HistoryVisibility::Joined => tr("state.history_visibility.joined").to_string(),
+                HistoryVisibility::Shared => tr("state.history_visibility.shared").to_string(),
+                HistoryVisibility::WorldReadable => tr("state.history_visibility.world_readable").to_string(),
+                custom => tr_args("state.history_visibility.custom", &[("visibility", custom.as_str())]),
+            })
         }
         AnyOtherFullStateEventContent::RoomJoinRules(FullStateEventContent::Original { content, .. }) => {
             Some(match &content.join_rule {
-                JoinRule::Public => String::from("set this room to be joinable by anyone."),
-                JoinRule::Knock => String::from("set this room to be joinable by invite only or by request."),
-                JoinRule::Private => String::from("set this room to be private."),
-                JoinRule::Restricted(_) => String::from("set this room to be joinable by invite only or with restrictions."),
-                JoinRule::KnockRestricted(_) => String::from("set this room to be joinable by invite only or requestable with restrictions."),
-                JoinRule::Invite  => String::from("set this room to be joinable by invite only."),
-                custom => format!("set custom join rules for this room: {}", custom.as_str()),
+                JoinRule::Public => tr("state.join_rules.public").to_string(),
+                JoinRule::Knock => tr("state.join_rules.knock").to_string(),
+                JoinRule::Private => tr("state.join_rules.private").to_string(),
+                JoinRule::Restricted(_) => tr("state.join_rules.restricted").to_string(),
+                JoinRule::KnockRestricted(_) => tr("state.join_rules.knock_restricted").to_string(),
+                JoinRule::Invite => tr("state.join_rules.invite").to_string(),
+                custom => tr_args("state.join_rules.custom", &[("rules", custom.as_str())]),
             })
         }
         AnyOtherFullStateEventContent::RoomPinnedEvents(FullStateEventContent::Original { content, .. }) => {
-            Some(format!("pinned {} events in this room.", content.pinned.len()))
+            Some(tr_args("state.pinned_events", &[("count", &content.pinned.len().to_string())]))
         }
         AnyOtherFullStateEventContent::RoomName(FullStateEventContent::Original { content, .. }) => {
-            Some(format!("changed this room's name to {:?}.", content.name))
+            Some(tr_args("state.name", &[("name", &format!("{:?}", content.name))]))
         }
         AnyOtherFullStateEventContent::RoomPowerLevels(_) => {
-            Some(String::from("set the power levels for this room."))
+            Some(tr("state.power_levels").to_string())
         }
         AnyOtherFullStateEventContent::RoomServerAcl(_) => {
-            Some(String::from("set the server access control list for this room."))
+            Some(tr("state.server_acl").to_string())
         }
         AnyOtherFullStateEventContent::RoomTombstone(FullStateEventContent::Original { content, .. }) => {
-            Some(format!("closed this room and upgraded it to {}", content.replacement_room.matrix_to_uri()))
+            Some(tr_args("state.tombstone", &[("room", &content.replacement_room.matrix_to_uri().to_string())]))
         }
         AnyOtherFullStateEventContent::RoomTopic(FullStateEventContent::Original { content, .. }) => {
-            Some(format!("changed this room's topic to {:?}.", content.topic))
+            Some(tr_args("state.topic", &[("topic", &format!("{:?}", content.topic))]))
         }
         AnyOtherFullStateEventContent::SpaceParent(_) => {
-            Some(format!("set this room's parent space to {}.", other_state.state_key()))
+            Some(tr_args("state.space_parent", &[("room", other_state.state_key())]))
         }
         AnyOtherFullStateEventContent::SpaceChild(_) => {
-            Some(format!("added a new child to this space: {}.", other_state.state_key()))
+            Some(tr_args("state.space_child", &[("room", other_state.state_key())]))
+        }
+        AnyOtherFullStateEventContent::BeaconInfo(FullStateEventContent::Original { content, .. }) => {
+            Some(if content.is_live() {
+                tr("state.beacon_info.live").to_string()
+            } else {
+                tr("state.beacon_info.stopped").to_string()
+            })
         }
         _other => {
             // log!("*** Unhandled: {:?}.", _other);
@@ -392,18 +421,18 @@ pub fn text_preview_of_member_profile_change(
     let name_text = if let Some(name_change) = change.displayname_change() {
         let old = name_change.old.as_deref().unwrap_or(username);
         if let Some(new) = name_change.new.as_ref() {
-            format!("{old} changed their display name to {new:?}")
+            tr_args("profile.display_name_changed", &[("old", old), ("new", &format!("{new:?}"))])
         } else {
-            format!("{old} removed their display name")
+            tr_args("profile.display_name_removed", &[("old", old)])
         }
     } else {
         String::new()
     };
     let avatar_text = if let Some(_avatar_change) = change.avatar_url_change() {
         if name_text.is_empty() {
-            format!("{} changed their profile picture", username)
+            tr_args("profile.avatar_changed", &[("user", username)])
         } else {
-            String::from(" and changed their profile picture")
+            tr("profile.avatar_changed_also").to_string()
         }
     } else {
         String::new()
@@ -423,6 +452,7 @@ pub fn text_preview_of_room_membership_change(
     let dn = change.display_name();
     let change_user_id = dn.as_deref()
         .unwrap_or_else(|| change.user_id().as_str());
+    let user = [("user", change_user_id)];
     let text = match change.change() {
         None
         | Some(MembershipChange::NotImplemented)
@@ -432,33 +462,45 @@ pub fn text_preview_of_room_membership_change(
             return None;
         }
         Some(MembershipChange::Joined) =>
-            String::from("joined this room."),
+            tr("membership.joined").to_string(),
         Some(MembershipChange::Left) =>
-            String::from("left this room."),
+            tr("membership.left").to_string(),
         Some(MembershipChange::Banned) =>
-            format!("banned {} from this room.", change_user_id),
+            tr_args("membership.banned", &user),
         Some(MembershipChange::Unbanned) =>
-            format!("unbanned {} from this room.", change_user_id),
+            tr_args("membership.unbanned", &user),
         Some(MembershipChange::Kicked) =>
-            format!("kicked {} from this room.", change_user_id),
+            tr_args("membership.kicked", &user),
         Some(MembershipChange::Invited) =>
-            format!("invited {} to this room.", change_user_id),
+            tr_args("membership.invited", &user),
         Some(MembershipChange::KickedAndBanned) =>
-            format!("kicked and banned {} from this room.", change_user_id),
+            tr_args("membership.kicked_and_banned", &user),
         Some(MembershipChange::InvitationAccepted) =>
-            String::from("accepted an invitation to this room."),
+            tr("membership.invitation_accepted").to_string(),
         Some(MembershipChange::InvitationRejected) =>
-            String::from("rejected an invitation to this room."),
+            tr("membership.invitation_rejected").to_string(),
         Some(MembershipChange::InvitationRevoked) =>
-            format!("revoked {}'s invitation to this room.", change_user_id),
+            tr_args("membership.invitation_revoked", &user),
         Some(MembershipChange::Knocked) =>
-            String::from("requested to join this room."),
+            tr("membership.knocked").to_string(),
         Some(MembershipChange::KnockAccepted) =>
-            format!("accepted {}'s request to join this room.", change_user_id),
+            tr_args("membership.knock_accepted", &user),
         Some(MembershipChange::KnockRetracted) =>
-            String::from("retracted their request to join this room."),
+            tr("membership.knock_retracted").to_string(),
         Some(MembershipChange::KnockDenied) =>
-            format!("denied {}'s request to join this room.", change_user_id),
+            tr_args("membership.knock_denied", &user),
+    };
+    // Show why a user was removed, e.g., which policy list rule caused an automatic ban.
+    let reason = match change.content() {
+        FullStateEventContent::Original { content, .. } if matches!(
+            change.change(),
+            Some(MembershipChange::Banned | MembershipChange::Kicked | MembershipChange::KickedAndBanned)
+        ) => content.reason.as_deref(),
+        _ => None,
+    };
+    let text = match reason {
+        Some(reason) => format!("{text} {}", tr_args("membership.reason", &[("reason", reason)])),
+        None => text,
     };
     Some(TextPreview::from((text, BeforeText::UsernameWithoutColon)))
 }
@@ -0,0 +1,541 @@
+//! A list of the current user's contacts, i.e., the other users with whom they have a direct message room,
+//! with the ability to jump to each contact's DM room or to start a new DM with any user by their ID.
+//!
+//! Contacts are derived from the direct targets of each joined room,
+//! which the Matrix SDK maintains based on the user's `m.direct` account data.
+//!
+//! Other users can also be found by their email address or phone number via an identity server,
+//! but only after the user has consented to it; see [`crate::identity_server`].
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::{OwnedRoomId, OwnedUserId, UserId};
+
+use crate::{
+    i18n::{tr, tr_args},
+    identity_server::{TermsPolicy, ThreePidMedium},
+    settings::app_settings::{app_settings, update_app_settings},
+    sliding_sync::{current_user_id, submit_async_request, MatrixRequest},
+};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    ContactRow = <View> {
+        width: Fill, height: Fit
+        flow: Right,
+        spacing: 8,
+        align: {y: 0.5}
+        padding: {top: 5, bottom: 5}
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Down,
+            spacing: 2,
+
+            name = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Ellipsis,
+                    text_style: <USERNAME_TEXT_STYLE>{font_size: 10},
+                    color: #000
+                }
+            }
+            user_id = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Ellipsis,
+                    text_style: <REGULAR_TEXT>{font_size: 9},
+                    color: (COLOR_TEXT_IDLE)
+                }
+            }
+        }
+
+        message_button = <RobrixIconButton> {
+            align: {x: 0.5, y: 0.5}
+            padding: {left: 8, right: 8, top: 5, bottom: 5}
+            draw_icon: {
+                svg_file: (ICON_JUMP)
+                color: (COLOR_TEXT),
+            }
+            icon_walk: {width: 12, height: 12, margin: {left: -2, right: -1} }
+            draw_text:{
+                text_style: <REGULAR_TEXT>{font_size: 9},
+            }
+            text: "Message"
+        }
+    }
+
+    pub ContactList = {{ContactList}} {
+        width: Fill, height: Fit
+        flow: Down
+        item: <ContactRow> {}
+    }
+
+    pub Contacts = {{Contacts}} {
+        width: Fit
+        height: Fit
+
+        <RoundedView> {
+            flow: Down
+            width: 500
+            height: Fit
+            padding: {top: 25, right: 30 bottom: 30 left: 30}
+            spacing: 5
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 3.0
+            }
+
+            title = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    text_style: <TITLE_TEXT>{font_size: 13},
+                    color: #000
+                }
+                text: "Contacts"
+            }
+
+            description = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 9.5},
+                    color: (MESSAGE_TEXT_COLOR)
+                }
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                margin: {top: 10, bottom: 5}
+                flow: Right,
+                spacing: 8,
+                align: {y: 0.5}
+
+                add_contact_input = <RobrixTextInput> {
+                    width: Fill, height: Fit
+                    padding: 8
+                    draw_bg: {
+                        border_width: 1.0
+                    }
+                    empty_message: "@user:example.org, email or +phone"
+                }
+
+                add_contact_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15, top: 8, bottom: 8}
+                    draw_icon: {
+                        svg_file: (ICON_ADD)
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                    draw_bg: {
+                        border_color: (COLOR_SELECTED_PRIMARY),
+                        color: #f0f7ff // light blue
+                    }
+                    draw_text:{
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                    text: "Message"
+                }
+            }
+
+            // Asks for the user's consent before contacting an identity server.
+            consent_view = <RoundedView> {
+                visible: false
+                width: Fill, height: Fit
+                flow: Down,
+                spacing: 8,
+                margin: {top: 5, bottom: 5}
+                padding: 10
+                show_bg: true
+                draw_bg: {
+                    color: #f0f7ff // light blue
+                    radius: 3.0
+                }
+
+                consent_text = <Label> {
+                    width: Fill, height: Fit
+                    draw_text: {
+                        wrap: Word,
+                        text_style: <REGULAR_TEXT>{font_size: 9.5},
+                        color: #000
+                    }
+                }
+
+                terms_label = <Label> {
+                    width: Fill, height: Fit
+                    draw_text: {
+                        wrap: Word,
+                        text_style: <REGULAR_TEXT>{font_size: 9},
+                        color: (COLOR_SELECTED_PRIMARY)
+                    }
+                }
+
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right,
+                    spacing: 8,
+                    align: {x: 1.0, y: 0.5}
+
+                    consent_cancel_button = <RobrixIconButton> {
+                        padding: {left: 15, right: 15, top: 8, bottom: 8}
+                        text: "Cancel"
+                    }
+
+                    consent_accept_button = <RobrixIconButton> {
+                        padding: {left: 15, right: 15, top: 8, bottom: 8}
+                        draw_bg: {
+                            border_color: (COLOR_SELECTED_PRIMARY),
+                            color: (COLOR_SELECTED_PRIMARY)
+                        }
+                        draw_text:{
+                            color: (COLOR_PRIMARY),
+                        }
+                        text: "Agree"
+                    }
+                }
+            }
+
+            status_label = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 9.5},
+                    color: (COLOR_TEXT_IDLE)
+                }
+            }
+
+            <ScrollYView> {
+                width: Fill, height: 360
+                contact_list = <ContactList> {}
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                margin: {top: 20}
+                align: {x: 1.0, y: 0.5}
+
+                close_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_CLOSE)
+                        color: (COLOR_TEXT),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                    text: "Close"
+                }
+            }
+        }
+    }
+}
+
+/// A user with whom the current user has a direct message room.
+#[derive(Clone, Debug)]
+pub struct Contact {
+    pub user_id: OwnedUserId,
+    /// The user's display name in their DM room, if they have set one.
+    pub display_name: Option<String>,
+    /// The DM room shared with this user.
+    pub room_id: OwnedRoomId,
+    pub room_name: Option<String>,
+}
+
+impl Contact {
+    /// Returns the name to display for this contact, falling back to their user ID.
+    pub fn displayable_name(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(self.user_id.as_str())
+    }
+}
+
+/// Actions sent from the background async worker in response to contacts-related requests.
+#[derive(Debug)]
+pub enum ContactsUpdate {
+    /// The list of contacts was fetched in response to [`MatrixRequest::GetContacts`].
+    Loaded(Vec<Contact>),
+    /// The DM room with a user is ready to be shown, in response to
+    /// [`MatrixRequest::OpenOrCreateDirectMessage`].
+    DirectMessageReady {
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+    },
+    /// Finding or creating the DM room with the given user failed.
+    DirectMessageFailed {
+        user_id: OwnedUserId,
+        error: String,
+    },
+    /// The identity server requires the user to accept the given terms
+    /// before the given address can be looked up.
+    IdentityServerTermsRequired {
+        medium: ThreePidMedium,
+        address: String,
+        policies: Vec<TermsPolicy>,
+    },
+    /// The given address was looked up via the identity server,
+    /// in response to [`MatrixRequest::LookupThreePid`].
+    ThreePidLookupFinished {
+        address: String,
+        /// The user ID bound to the address, if any.
+        user_id: Option<OwnedUserId>,
+    },
+    /// Looking up the given address via the identity server failed.
+    ThreePidLookupFailed {
+        address: String,
+        error: String,
+    },
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ContactsAction {
+    None,
+    /// Close the contacts list.
+    Close,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct Contacts {
+    #[deref] view: View,
+    /// The address to look up via the identity server once the user has given their consent.
+    #[rust] pending_lookup: Option<(ThreePidMedium, String)>,
+    /// The identity server's terms that the user is being asked to accept.
+    #[rust] pending_terms: Vec<TermsPolicy>,
+}
+
+impl Widget for Contacts {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for Contacts {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(id!(close_button)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, ContactsAction::Close);
+            return;
+        }
+
+        let add_contact_input = self.text_input(id!(add_contact_input));
+        if self.button(id!(add_contact_button)).clicked(actions)
+            || add_contact_input.returned(actions).is_some()
+        {
+            self.add_contact(cx, &add_contact_input.text());
+        }
+
+        if self.button(id!(consent_accept_button)).clicked(actions) {
+            let accepted_terms = std::mem::take(&mut self.pending_terms);
+            update_app_settings(|settings| {
+                settings.identity_server.consent_given = true;
+                settings.identity_server.accepted_terms.extend(accepted_terms.into_iter().map(|p| p.url));
+            });
+            self.view(id!(consent_view)).set_visible(cx, false);
+            if let Some((medium, address)) = self.pending_lookup.take() {
+                self.lookup_address(cx, medium, address);
+            }
+        }
+        if self.button(id!(consent_cancel_button)).clicked(actions) {
+            self.pending_lookup = None;
+            self.pending_terms.clear();
+            self.view(id!(consent_view)).set_visible(cx, false);
+            self.label(id!(status_label)).set_text(cx, "");
+            self.redraw(cx);
+        }
+
+        for action in actions {
+            match action.downcast_ref() {
+                Some(ContactsUpdate::Loaded(contacts)) => {
+                    let status = if contacts.is_empty() {
+                        tr("contacts.empty").to_string()
+                    } else {
+                        String::new()
+                    };
+                    self.label(id!(status_label)).set_text(cx, &status);
+                    self.contact_list(id!(contact_list)).set_contacts(cx, contacts.clone());
+                    self.redraw(cx);
+                }
+                Some(ContactsUpdate::DirectMessageFailed { user_id, error }) => {
+                    self.label(id!(status_label)).set_text(cx, &tr_args(
+                        "contacts.open_dm_failed",
+                        &[("user", user_id.as_str()), ("error", error)],
+                    ));
+                    self.redraw(cx);
+                }
+                Some(ContactsUpdate::IdentityServerTermsRequired { medium, address, policies }) => {
+                    self.pending_lookup = Some((*medium, address.clone()));
+                    self.pending_terms = policies.clone();
+                    self.show_consent(cx);
+                }
+                Some(ContactsUpdate::ThreePidLookupFinished { address, user_id: Some(user_id) }) => {
+                    // Fill in the found user ID such that the user can choose whether to message them.
+                    self.text_input(id!(add_contact_input)).set_text(cx, user_id.as_str());
+                    self.label(id!(status_label)).set_text(cx, &tr_args(
+                        "contacts.lookup_found",
+                        &[("address", address), ("user", user_id.as_str())],
+                    ));
+                    self.redraw(cx);
+                }
+                Some(ContactsUpdate::ThreePidLookupFinished { address, user_id: None }) => {
+                    self.label(id!(status_label)).set_text(cx, &tr_args(
+                        "contacts.lookup_not_found",
+                        &[("address", address)],
+                    ));
+                    self.redraw(cx);
+                }
+                Some(ContactsUpdate::ThreePidLookupFailed { address, error }) => {
+                    self.label(id!(status_label)).set_text(cx, &tr_args(
+                        "contacts.lookup_failed",
+                        &[("address", address), ("error", error)],
+                    ));
+                    self.redraw(cx);
+                }
+                _ => { }
+            }
+        }
+    }
+}
+
+impl Contacts {
+    /// Invoke this when the contacts list is about to be shown to refresh its content.
+    fn on_shown(&mut self, cx: &mut Cx) {
+        self.label(id!(title)).set_text(cx, tr("contacts.title"));
+        self.label(id!(description)).set_text(cx, tr("contacts.description"));
+        self.label(id!(status_label)).set_text(cx, tr("contacts.loading"));
+        self.button(id!(add_contact_button)).set_text(cx, tr("contacts.message"));
+        self.button(id!(close_button)).set_text(cx, tr("contacts.close"));
+        self.button(id!(consent_accept_button)).set_text(cx, tr("contacts.consent_accept"));
+        self.button(id!(consent_cancel_button)).set_text(cx, tr("contacts.consent_cancel"));
+        submit_async_request(MatrixRequest::GetContacts);
+        self.redraw(cx);
+    }
+
+    /// Opens the DM room with the user whose ID is given, creating it if necessary.
+    ///
+    /// If an email address or phone number is given instead, it is looked up via the identity server.
+    fn add_contact(&mut self, cx: &mut Cx, user_id: &str) {
+        let user_id = user_id.trim();
+        if let Some((medium, address)) = ThreePidMedium::parse_address(user_id) {
+            if app_settings().identity_server.consent_given {
+                self.lookup_address(cx, medium, address);
+            } else {
+                self.pending_lookup = Some((medium, address));
+                self.show_consent(cx);
+            }
+            return;
+        }
+        let status = match UserId::parse(user_id) {
+            Ok(user_id) if current_user_id().as_ref() == Some(&user_id) => {
+                tr("contacts.cannot_add_self").to_string()
+            }
+            Ok(user_id) => {
+                let status = tr_args("contacts.opening_dm", &[("user", user_id.as_str())]);
+                submit_async_request(MatrixRequest::OpenOrCreateDirectMessage { user_id });
+                self.text_input(id!(add_contact_input)).set_text(cx, "");
+                status
+            }
+            Err(_) => tr_args("contacts.invalid_user_id", &[("user", user_id)]),
+        };
+        self.label(id!(status_label)).set_text(cx, &status);
+        self.redraw(cx);
+    }
+
+    /// Asks the user to consent to contacting the identity server,
+    /// and to accept any of its terms in `pending_terms`.
+    fn show_consent(&mut self, cx: &mut Cx) {
+        let server = app_settings().identity_server.url.clone();
+        self.label(id!(consent_text)).set_text(cx, &tr_args(
+            "contacts.identity_server_consent",
+            &[("server", &server)],
+        ));
+        let terms = self.pending_terms.iter()
+            .map(|p| format!("{}: {}", p.name, p.url))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.label(id!(terms_label)).set_text(cx, &terms);
+        self.label(id!(terms_label)).set_visible(cx, !terms.is_empty());
+        self.view(id!(consent_view)).set_visible(cx, true);
+        self.label(id!(status_label)).set_text(cx, "");
+        self.redraw(cx);
+    }
+
+    /// Looks up the given address via the identity server, which the user has consented to.
+    fn lookup_address(&mut self, cx: &mut Cx, medium: ThreePidMedium, address: String) {
+        self.label(id!(status_label)).set_text(cx, &tr_args(
+            "contacts.looking_up",
+            &[("address", &address)],
+        ));
+        submit_async_request(MatrixRequest::LookupThreePid { medium, address });
+        self.redraw(cx);
+    }
+}
+
+impl ContactsRef {
+    /// See [`Contacts::on_shown()`].
+    pub fn on_shown(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.on_shown(cx);
+        }
+    }
+}
+
+
+/// A list of contacts, each shown with a button to open their DM room.
+#[derive(Live, LiveHook, Widget)]
+pub struct ContactList {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    #[rust] children: Vec<(WidgetRef, Contact)>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for ContactList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (row, _) in &self.children {
+            row.handle_event(cx, event, scope);
+        }
+        let Event::Actions(actions) = event else { return };
+        for (row, contact) in &self.children {
+            if row.button(id!(message_button)).clicked(actions) {
+                cx.action(ContactsUpdate::DirectMessageReady {
+                    room_id: contact.room_id.clone(),
+                    room_name: contact.room_name.clone(),
+                });
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (row, _) in &self.children {
+            let _ = row.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl ContactListRef {
+    /// Replaces the displayed contacts with the given ones.
+    pub fn set_contacts(&self, cx: &mut Cx, contacts: Vec<Contact>) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.children.clear();
+        for contact in contacts {
+            let row = WidgetRef::new_from_ptr(cx, inner.item);
+            row.label(id!(name)).set_text(cx, contact.displayable_name());
+            row.label(id!(user_id)).set_text(cx, contact.user_id.as_str());
+            row.button(id!(message_button)).set_text(cx, tr("contacts.message"));
+            inner.children.push((row, contact));
+        }
+        inner.redraw(cx);
+    }
+}
@@ -0,0 +1,375 @@
+//! A modal dialog that shows the source (raw JSON) of a timeline event.
+//!
+//! For encrypted events, it also shows how the event was (or wasn't) decrypted:
+//! the Megolm session ID, the sender's Curve25519 key and device, and how far the chain of trust
+//! from that device to the sender's verified identity extends, which helps to debug
+//! "unable to decrypt" reports.
+
+use makepad_widgets::*;
+use matrix_sdk::{
+    deserialized_responses::{AlgorithmInfo, DeviceLinkProblem, EncryptionInfo, VerificationLevel, VerificationState},
+    ruma::{OwnedDeviceId, OwnedEventId, OwnedRoomId, OwnedUserId},
+};
+use matrix_sdk_ui::timeline::{EncryptedMessage, EventTimelineItem, TimelineItemContent};
+
+use crate::i18n::{tr, tr_args};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    EventSourceText = <Label> {
+        width: Fill, height: Fit
+        draw_text: {
+            wrap: Word,
+            text_style: <REGULAR_TEXT>{font_size: 9.5},
+            color: (MESSAGE_TEXT_COLOR)
+        }
+    }
+
+    pub EventSourceModal = {{EventSourceModal}} {
+        width: Fit
+        height: Fit
+
+        <RoundedView> {
+            flow: Down
+            width: 600
+            height: Fit
+            padding: {top: 25, right: 30 bottom: 30 left: 30}
+            spacing: 10
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 3.0
+            }
+
+            title = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    text_style: <TITLE_TEXT>{font_size: 13},
+                    color: #000
+                }
+            }
+
+            ids = <EventSourceText> {}
+
+            decryption_view = <View> {
+                visible: false
+                width: Fill, height: Fit
+                flow: Down
+                spacing: 5
+
+                decryption_title = <Label> {
+                    width: Fill, height: Fit
+                    draw_text: {
+                        text_style: <TITLE_TEXT>{font_size: 11},
+                        color: #000
+                    }
+                }
+                decryption_info = <EventSourceText> {}
+            }
+
+            <ScrollYView> {
+                width: Fill, height: 350
+                padding: 8
+                show_bg: true
+                draw_bg: {
+                    color: #f5f5f5
+                }
+
+                source = <EventSourceText> {
+                    draw_text: {
+                        text_style: <REGULAR_TEXT>{font_size: 9},
+                    }
+                }
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                margin: {top: 10}
+                flow: Right,
+                align: {x: 1.0, y: 0.5}
+                spacing: 10
+
+                close_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_CLOSE)
+                        color: (COLOR_TEXT),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                }
+
+                copy_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_COPY)
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+
+                    draw_bg: {
+                        border_color: (COLOR_SELECTED_PRIMARY),
+                        color: #f0f7ff // light blue
+                    }
+                    draw_text:{
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// How an encrypted event was decrypted, or why it couldn't be decrypted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecryptionDebugInfo {
+    /// Whether the event was successfully decrypted.
+    pub decrypted: bool,
+    /// The ID of the Megolm session that the event was encrypted with.
+    pub session_id: Option<String>,
+    /// The Curve25519 key of the device that sent the event.
+    pub sender_key: Option<String>,
+    /// The user who sent the event.
+    pub sender: OwnedUserId,
+    /// The device that sent the event, if known.
+    pub sender_device: Option<OwnedDeviceId>,
+    /// The i18n key describing the chain of trust from the sender's device to their verified identity,
+    /// which is only known if the event was decrypted.
+    pub verification_key: Option<&'static str>,
+    /// Why the event couldn't be decrypted, if known.
+    pub utd_cause: Option<String>,
+}
+
+impl DecryptionDebugInfo {
+    /// Returns the decryption info of the given event, if it is an encrypted event.
+    pub fn from_event(event: &EventTimelineItem) -> Option<Self> {
+        if let TimelineItemContent::UnableToDecrypt(encrypted) = event.content() {
+            let (session_id, sender_key, sender_device, utd_cause) = match encrypted {
+                EncryptedMessage::MegolmV1AesSha2 { sender_key, device_id, session_id, cause } => (
+                    Some(session_id.clone()),
+                    Some(sender_key.clone()),
+                    Some(device_id.clone()),
+                    Some(format!("{cause:?}")),
+                ),
+                EncryptedMessage::OlmV1Curve25519AesSha2 { sender_key } => (None, Some(sender_key.clone()), None, None),
+                EncryptedMessage::Unknown => (None, None, None, None),
+            };
+            return Some(Self {
+                decrypted: false,
+                session_id,
+                sender_key,
+                sender: event.sender().to_owned(),
+                sender_device,
+                verification_key: None,
+                utd_cause,
+            });
+        }
+        event.encryption_info().map(Self::from_encryption_info)
+    }
+
+    /// Returns the decryption info of an event that was decrypted with the given encryption info.
+    fn from_encryption_info(info: &EncryptionInfo) -> Self {
+        let sender_key = match &info.algorithm_info {
+            AlgorithmInfo::MegolmV1AesSha2 { curve25519_key, .. } => Some(curve25519_key.clone()),
+        };
+        Self {
+            decrypted: true,
+            session_id: info.session_id.clone(),
+            sender_key,
+            sender: info.sender.clone(),
+            sender_device: info.sender_device.clone(),
+            verification_key: Some(verification_description_key(&info.verification_state)),
+            utd_cause: None,
+        }
+    }
+
+    /// Returns a human-readable report of this info, one detail per line.
+    pub fn to_report(&self) -> String {
+        let unknown = tr("event_source.unknown");
+        let status = if self.decrypted { tr("event_source.decrypted") } else { tr("event_source.not_decrypted") };
+        let mut lines = vec![
+            status.to_owned(),
+            tr_args("event_source.session_id", &[("value", self.session_id.as_deref().unwrap_or(unknown))]),
+            tr_args("event_source.sender_key", &[("value", self.sender_key.as_deref().unwrap_or(unknown))]),
+            tr_args("event_source.sender_device", &[
+                ("user", self.sender.as_str()),
+                ("device", self.sender_device.as_deref().map_or(unknown, |d| d.as_str())),
+            ]),
+        ];
+        if let Some(key) = self.verification_key {
+            lines.push(tr_args("event_source.verification", &[("value", tr(key))]));
+        }
+        if let Some(cause) = &self.utd_cause {
+            lines.push(tr_args("event_source.utd_cause", &[("value", cause)]));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Returns the i18n key describing how far the chain of trust extends
+/// from the device that sent an event to that sender's verified identity.
+fn verification_description_key(state: &VerificationState) -> &'static str {
+    match state {
+        VerificationState::Verified => "event_source.verification.verified",
+        VerificationState::Unverified(VerificationLevel::UnverifiedIdentity) => "event_source.verification.unverified_identity",
+        VerificationState::Unverified(VerificationLevel::VerificationViolation) => "event_source.verification.violation",
+        VerificationState::Unverified(VerificationLevel::UnsignedDevice) => "event_source.verification.unsigned_device",
+        VerificationState::Unverified(VerificationLevel::None(DeviceLinkProblem::MissingDevice)) => "event_source.verification.missing_device",
+        VerificationState::Unverified(VerificationLevel::None(DeviceLinkProblem::InsecureSource)) => "event_source.verification.insecure_source",
+    }
+}
+
+/// The source of an event to be shown in the [`EventSourceModal`].
+#[derive(Clone, Debug)]
+pub struct EventSource {
+    pub room_id: OwnedRoomId,
+    pub event_id: Option<OwnedEventId>,
+    /// The event's original JSON, pretty-printed, which is unavailable for local echoes.
+    pub json: Option<String>,
+    /// How the event was decrypted, if it is an encrypted event.
+    pub decryption: Option<DecryptionDebugInfo>,
+}
+
+impl EventSource {
+    /// Returns the source of the given event in the given room.
+    pub fn from_event(room_id: OwnedRoomId, event: &EventTimelineItem) -> Self {
+        let json = event.original_json()
+            .and_then(|raw| serde_json::from_str::<serde_json::Value>(raw.json().get()).ok())
+            .and_then(|value| serde_json::to_string_pretty(&value).ok());
+        Self {
+            room_id,
+            event_id: event.event_id().map(ToOwned::to_owned),
+            json,
+            decryption: DecryptionDebugInfo::from_event(event),
+        }
+    }
+
+    /// Returns the full text that is copied to the clipboard, including the decryption info.
+    fn to_report(&self) -> String {
+        let mut report = self.ids_text();
+        if let Some(decryption) = &self.decryption {
+            report.push_str("\n\n");
+            report.push_str(&decryption.to_report());
+        }
+        report.push_str("\n\n");
+        report.push_str(self.json.as_deref().unwrap_or(tr("event_source.no_json")));
+        report
+    }
+
+    /// Returns the text identifying this event and its room.
+    fn ids_text(&self) -> String {
+        tr_args("event_source.ids", &[
+            ("event_id", self.event_id.as_deref().map_or(tr("event_source.unknown"), |e| e.as_str())),
+            ("room_id", self.room_id.as_str()),
+        ])
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum EventSourceModalAction {
+    None,
+    /// Open the event source modal to show the given event's source.
+    Open(Box<EventSource>),
+    Close,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct EventSourceModal {
+    #[deref] view: View,
+    /// The source of the event being shown.
+    #[rust] source: Option<EventSource>,
+}
+
+impl Widget for EventSourceModal {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for EventSourceModal {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(id!(close_button)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, EventSourceModalAction::Close);
+        }
+        if self.button(id!(copy_button)).clicked(actions) {
+            if let Some(source) = &self.source {
+                cx.copy_to_clipboard(&source.to_report());
+            }
+        }
+    }
+}
+
+impl EventSourceModal {
+    /// Shows the given event source in this modal.
+    fn initialize(&mut self, cx: &mut Cx, source: EventSource) {
+        self.label(id!(title)).set_text(cx, tr("event_source.title"));
+        self.label(id!(ids)).set_text(cx, &source.ids_text());
+        self.view(id!(decryption_view)).set_visible(cx, source.decryption.is_some());
+        if let Some(decryption) = &source.decryption {
+            self.label(id!(decryption_title)).set_text(cx, tr("event_source.decryption"));
+            self.label(id!(decryption_info)).set_text(cx, &decryption.to_report());
+        }
+        self.label(id!(source)).set_text(cx, source.json.as_deref().unwrap_or(tr("event_source.no_json")));
+        self.button(id!(close_button)).set_text(cx, tr("event_source.close"));
+        self.button(id!(copy_button)).set_text(cx, tr("event_source.copy"));
+        self.source = Some(source);
+        self.redraw(cx);
+    }
+}
+
+impl EventSourceModalRef {
+    /// See [`EventSourceModal::initialize()`].
+    pub fn initialize(&self, cx: &mut Cx, source: EventSource) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.initialize(cx, source);
+    }
+}
+
+#[cfg(test)]
+mod tests_event_source_modal {
+    use matrix_sdk::ruma::{device_id, user_id};
+
+    use super::*;
+
+    #[test]
+    fn test_decryption_report() {
+        let info = DecryptionDebugInfo {
+            decrypted: false,
+            session_id: Some("SESSION".into()),
+            sender_key: Some("CURVEKEY".into()),
+            sender: user_id!("@alice:example.org").to_owned(),
+            sender_device: Some(device_id!("ALICEDEVICE").to_owned()),
+            verification_key: None,
+            utd_cause: Some("Unknown".into()),
+        };
+        let report = info.to_report();
+        assert!(report.contains("SESSION"));
+        assert!(report.contains("CURVEKEY"));
+        assert!(report.contains("@alice:example.org"));
+        assert!(report.contains("ALICEDEVICE"));
+        assert!(report.contains("Unknown"));
+
+        let decrypted = DecryptionDebugInfo {
+            decrypted: true,
+            sender_device: None,
+            verification_key: Some(verification_description_key(&VerificationState::Verified)),
+            utd_cause: None,
+            ..info
+        };
+        let report = decrypted.to_report();
+        assert!(report.contains(tr("event_source.verification.verified")));
+        assert!(report.contains(tr("event_source.unknown")));
+    }
+}
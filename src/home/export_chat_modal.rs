@@ -0,0 +1,319 @@
+//! A modal dialog for exporting the full history of a room to a file,
+//! which shows the progress of the export while it runs in the background.
+
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::OwnedRoomId;
+
+use crate::{
+    i18n::{tr, tr_args},
+    room_export::{RoomExportAction, RoomExportFormat},
+    sliding_sync::{submit_async_request, MatrixRequest},
+};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    ExportFormatButton = <RobrixIconButton> {
+        align: {x: 0.5, y: 0.5}
+        padding: {left: 15, right: 15}
+        draw_icon: {
+            svg_file: (ICON_HTML_FILE)
+            color: (COLOR_SELECTED_PRIMARY),
+        }
+        icon_walk: {width: 16, height: 16, margin: {left: -2, right: -1} }
+
+        draw_bg: {
+            border_color: (COLOR_SELECTED_PRIMARY),
+            color: #f0f7ff // light blue
+        }
+        draw_text:{
+            color: (COLOR_SELECTED_PRIMARY),
+        }
+    }
+
+    pub ExportChatModal = {{ExportChatModal}} {
+        width: Fit
+        height: Fit
+
+        <RoundedView> {
+            flow: Down
+            width: 400
+            height: Fit
+            padding: {top: 25, right: 30 bottom: 30 left: 45}
+            spacing: 10
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 3.0
+            }
+
+            title = <View> {
+                width: Fill,
+                height: Fit,
+                flow: Right
+                padding: {top: 0, bottom: 40}
+                align: {x: 0.5, y: 0.0}
+
+                title_label = <Label> {
+                    text: "Export Chat"
+                    draw_text: {
+                        text_style: <TITLE_TEXT>{font_size: 13},
+                        color: #000
+                    }
+                }
+            }
+
+            body = <View> {
+                width: Fill,
+                height: Fit,
+                flow: Down,
+                spacing: 40,
+
+                prompt = <Label> {
+                    width: Fill
+                    draw_text: {
+                        text_style: <REGULAR_TEXT>{
+                            font_size: 11.5,
+                            height_factor: 1.3
+                        },
+                        color: #000
+                        wrap: Word
+                    }
+                }
+
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right,
+                    align: {x: 1.0, y: 0.5}
+                    spacing: 20
+
+                    cancel_button = <RobrixIconButton> {
+                        align: {x: 0.5, y: 0.5}
+                        padding: {left: 15, right: 15}
+                        draw_icon: {
+                            svg_file: (ICON_BLOCK_USER)
+                            color: (COLOR_DANGER_RED),
+                        }
+                        icon_walk: {width: 16, height: 16, margin: {left: -2, right: -1} }
+
+                        draw_bg: {
+                            border_color: (COLOR_DANGER_RED),
+                            color: #fff0f0 // light red
+                        }
+                        text: "Cancel"
+                        draw_text:{
+                            color: (COLOR_DANGER_RED),
+                        }
+                    }
+
+                    json_button = <ExportFormatButton> {
+                        text: "JSON"
+                    }
+
+                    html_button = <ExportFormatButton> {
+                        text: "HTML"
+                    }
+
+                    members_button = <ExportFormatButton> {
+                        text: "Members (CSV)"
+                    }
+
+                    ok_button = <RobrixIconButton> {
+                        visible: false
+                        align: {x: 0.5, y: 0.5}
+                        padding: {left: 15, right: 15}
+                        draw_icon: {
+                            svg_file: (ICON_CHECKMARK)
+                            color: (COLOR_ACCEPT_GREEN),
+                        }
+                        icon_walk: {width: 16, height: 16, margin: {left: -2, right: -1} }
+
+                        draw_bg: {
+                            border_color: (COLOR_ACCEPT_GREEN),
+                            color: #f0fff0 // light green
+                        }
+                        text: "Ok"
+                        draw_text:{
+                            color: (COLOR_ACCEPT_GREEN),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ExportChatModal {
+    #[deref] view: View,
+    /// The room whose history is to be exported.
+    #[rust] room_id: Option<OwnedRoomId>,
+    /// The flag used to cancel the ongoing export, if there is one.
+    #[rust] cancel: Option<Arc<AtomicBool>>,
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ExportChatModalAction {
+    None,
+    /// Open the export modal for the given room.
+    Open {
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+    },
+    Close,
+}
+
+impl Widget for ExportChatModal {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for ExportChatModal {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        let widget_uid = self.widget_uid();
+        let modal_dismissed = actions
+            .iter()
+            .any(|a| matches!(a.downcast_ref(), Some(ModalAction::Dismissed)));
+        let cancel_clicked = self.button(id!(cancel_button)).clicked(actions);
+        let ok_clicked = self.button(id!(ok_button)).clicked(actions);
+
+        if cancel_clicked || ok_clicked || modal_dismissed {
+            self.cancel_export();
+            // If the modal was dismissed by clicking outside of it, we MUST NOT emit
+            // a `ExportChatModalAction::Close` action, as that would cause
+            // an infinite action feedback loop.
+            if !modal_dismissed {
+                cx.widget_action(widget_uid, &scope.path, ExportChatModalAction::Close);
+            }
+            return;
+        }
+
+        if self.button(id!(json_button)).clicked(actions) {
+            self.start_export(cx, RoomExportFormat::Json);
+        }
+        if self.button(id!(html_button)).clicked(actions) {
+            self.start_export(cx, RoomExportFormat::Html);
+        }
+
+        if self.button(id!(members_button)).clicked(actions) {
+            self.start_member_export(cx);
+        }
+
+        for action in actions {
+            // `RoomExportAction`s come from a background task, so they are NOT widget actions.
+            let Some(export_action) = action.downcast_ref::<RoomExportAction>() else { continue };
+            if self.cancel.is_none() { continue }
+            match export_action {
+                RoomExportAction::Progress { room_id, num_events } if Some(room_id) == self.room_id.as_ref() => {
+                    self.label(id!(prompt)).set_text(cx, &tr_args(
+                        "export.progress",
+                        &[("count", &num_events.to_string())],
+                    ));
+                }
+                RoomExportAction::Finished { room_id, path } if Some(room_id) == self.room_id.as_ref() => {
+                    self.label(id!(prompt)).set_text(cx, &tr_args(
+                        "export.finished",
+                        &[("path", &path.display().to_string())],
+                    ));
+                    self.show_final_state(cx);
+                }
+                RoomExportAction::Failed { room_id, error } if Some(room_id) == self.room_id.as_ref() => {
+                    self.label(id!(prompt)).set_text(cx, &tr_args(
+                        "export.failed",
+                        &[("error", error)],
+                    ));
+                    self.show_final_state(cx);
+                }
+                _ => { }
+            }
+            self.redraw(cx);
+        }
+    }
+}
+
+impl ExportChatModal {
+    /// Resets this modal to prompt the user to choose an export format for the given room.
+    fn initialize(&mut self, cx: &mut Cx, room_id: OwnedRoomId, room_name: Option<String>) {
+        self.cancel_export();
+        let room_name = room_name.unwrap_or_else(|| room_id.to_string());
+        self.label(id!(title_label)).set_text(cx, tr("export.title"));
+        self.label(id!(prompt)).set_text(cx, &tr_args("export.prompt", &[("room", &room_name)]));
+        self.button(id!(cancel_button)).set_text(cx, tr("export.cancel"));
+        self.button(id!(cancel_button)).set_visible(cx, true);
+        self.button(id!(json_button)).set_text(cx, tr("export.json"));
+        self.button(id!(json_button)).set_visible(cx, true);
+        self.button(id!(html_button)).set_text(cx, tr("export.html"));
+        self.button(id!(html_button)).set_visible(cx, true);
+        self.button(id!(members_button)).set_text(cx, tr("export.members_csv"));
+        self.button(id!(members_button)).set_visible(cx, true);
+        self.button(id!(ok_button)).set_text(cx, tr("export.ok"));
+        self.button(id!(ok_button)).set_visible(cx, false);
+        self.room_id = Some(room_id);
+        self.redraw(cx);
+    }
+
+    /// Starts exporting the room's history in the background.
+    fn start_export(&mut self, cx: &mut Cx, format: RoomExportFormat) {
+        let Some(room_id) = self.room_id.clone() else { return };
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel = Some(cancel.clone());
+        submit_async_request(MatrixRequest::ExportRoomHistory { room_id, format, cancel });
+        self.label(id!(prompt)).set_text(cx, &tr_args("export.progress", &[("count", "0")]));
+        self.hide_format_buttons(cx);
+        self.redraw(cx);
+    }
+
+    /// Starts exporting the room's member list to a CSV file in the background.
+    fn start_member_export(&mut self, cx: &mut Cx) {
+        let Some(room_id) = self.room_id.clone() else { return };
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.cancel = Some(cancel.clone());
+        submit_async_request(MatrixRequest::ExportRoomMembers { room_id, cancel });
+        self.label(id!(prompt)).set_text(cx, tr("export.members_progress"));
+        self.hide_format_buttons(cx);
+        self.redraw(cx);
+    }
+
+    /// Hides the buttons that start an export, once one has been started.
+    fn hide_format_buttons(&mut self, cx: &mut Cx) {
+        self.button(id!(json_button)).set_visible(cx, false);
+        self.button(id!(html_button)).set_visible(cx, false);
+        self.button(id!(members_button)).set_visible(cx, false);
+    }
+
+    /// Shows only the "Ok" button, which closes this modal.
+    fn show_final_state(&mut self, cx: &mut Cx) {
+        self.cancel = None;
+        self.button(id!(cancel_button)).set_visible(cx, false);
+        self.button(id!(ok_button)).set_visible(cx, true);
+    }
+
+    /// Cancels the ongoing export, if there is one.
+    fn cancel_export(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl ExportChatModalRef {
+    /// See [`ExportChatModal::initialize()`].
+    pub fn initialize(&self, cx: &mut Cx, room_id: OwnedRoomId, room_name: Option<String>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.initialize(cx, room_id, room_name);
+        }
+    }
+}
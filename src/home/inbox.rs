@@ -0,0 +1,367 @@
+//! An inbox that aggregates the messages in all rooms that either mention the current user
+//! or reply to one of their messages, with a button to jump to each message in its room.
+//!
+//! Messages are gathered by each room's background timeline subscriber,
+//! so only messages within the locally-known part of a room's timeline are included.
+
+use crossbeam_queue::SegQueue;
+use makepad_widgets::*;
+use matrix_sdk::ruma::{MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedRoomId};
+use matrix_sdk_ui::timeline::{EventTimelineItem, TimelineDetails, TimelineItemContent};
+
+use crate::{
+    event_preview::body_of_timeline_item,
+    i18n::{tr, tr_args},
+    sliding_sync::current_user_id,
+    utils::{self, get_or_fetch_event_sender},
+};
+
+/// The maximum number of entries kept in the inbox; older entries are dropped.
+const MAX_INBOX_ENTRIES: usize = 100;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    InboxEntryRow = <View> {
+        width: Fill, height: Fit
+        flow: Right,
+        spacing: 8,
+        align: {y: 0.5}
+        padding: {top: 5, bottom: 5}
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Down,
+            spacing: 2,
+
+            header = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Ellipsis,
+                    text_style: <USERNAME_TEXT_STYLE>{font_size: 9},
+                    color: (COLOR_TEXT_IDLE)
+                }
+            }
+            text = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Ellipsis,
+                    text_style: <REGULAR_TEXT>{font_size: 10},
+                    color: #000
+                }
+            }
+        }
+
+        jump_button = <RobrixIconButton> {
+            align: {x: 0.5, y: 0.5}
+            padding: {left: 8, right: 8, top: 5, bottom: 5}
+            draw_icon: {
+                svg_file: (ICON_JUMP)
+                color: (COLOR_TEXT),
+            }
+            icon_walk: {width: 12, height: 12, margin: {left: -2, right: -1} }
+            draw_text:{
+                text_style: <REGULAR_TEXT>{font_size: 9},
+            }
+            text: "Jump"
+        }
+    }
+
+    pub InboxEntryList = {{InboxEntryList}} {
+        width: Fill, height: Fit
+        flow: Down
+        item: <InboxEntryRow> {}
+    }
+
+    pub Inbox = {{Inbox}} {
+        width: Fit
+        height: Fit
+
+        <RoundedView> {
+            flow: Down
+            width: 500
+            height: Fit
+            padding: {top: 25, right: 30 bottom: 30 left: 30}
+            spacing: 5
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 3.0
+            }
+
+            title = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    text_style: <TITLE_TEXT>{font_size: 13},
+                    color: #000
+                }
+                text: "Inbox"
+            }
+
+            description = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 9.5},
+                    color: (MESSAGE_TEXT_COLOR)
+                }
+            }
+
+            empty_label = <Label> {
+                width: Fill, height: Fit
+                margin: {top: 10}
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 10},
+                    color: (COLOR_TEXT_IDLE)
+                }
+            }
+
+            <ScrollYView> {
+                width: Fill, height: 360
+                entries_list = <InboxEntryList> {}
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                margin: {top: 20}
+                align: {x: 1.0, y: 0.5}
+
+                close_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_CLOSE)
+                        color: (COLOR_TEXT),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                    text: "Close"
+                }
+            }
+        }
+    }
+}
+
+/// Why a message was added to the inbox.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum InboxEntryKind {
+    /// The message mentions the current user.
+    Mention,
+    /// The message replies to one of the current user's messages.
+    Reply,
+}
+
+/// A message that mentions the current user or replies to one of their messages.
+#[derive(Clone, Debug)]
+pub struct InboxEntry {
+    pub kind: InboxEntryKind,
+    pub room_id: OwnedRoomId,
+    pub room_name: Option<String>,
+    pub event_id: OwnedEventId,
+    /// The displayable name of the message's sender.
+    pub sender_name: String,
+    /// The text of the message.
+    pub body: String,
+    pub timestamp: MilliSecondsSinceUnixEpoch,
+}
+
+impl InboxEntry {
+    /// Returns an inbox entry for the given timeline event if it belongs in the inbox,
+    /// i.e., if it's a message from another user that mentions or replies to the current user.
+    pub fn from_event(
+        room_id: &OwnedRoomId,
+        room_name: Option<String>,
+        event_tl_item: &EventTimelineItem,
+    ) -> Option<Self> {
+        if event_tl_item.is_own() { return None }
+        let current_user_id = current_user_id()?;
+        let event_id = event_tl_item.event_id()?.to_owned();
+        let TimelineItemContent::Message(message) = event_tl_item.content() else { return None };
+
+        let kind = if message.mentions().is_some_and(|m| m.user_ids.contains(&current_user_id)) {
+            InboxEntryKind::Mention
+        } else if message.in_reply_to().is_some_and(|in_reply_to| matches!(
+            &in_reply_to.event,
+            TimelineDetails::Ready(replied_to) if replied_to.sender() == &*current_user_id
+        )) {
+            InboxEntryKind::Reply
+        } else {
+            return None;
+        };
+
+        Some(Self {
+            kind,
+            room_id: room_id.clone(),
+            room_name,
+            event_id,
+            sender_name: get_or_fetch_event_sender(event_tl_item, Some(room_id)),
+            body: body_of_timeline_item(event_tl_item),
+            timestamp: event_tl_item.timestamp(),
+        })
+    }
+}
+
+static PENDING_INBOX_ENTRIES: SegQueue<InboxEntry> = SegQueue::new();
+
+/// Enqueues a new entry to be added to the inbox
+/// and signals the UI that a new update is available to be handled.
+pub fn enqueue_inbox_entry(entry: InboxEntry) {
+    PENDING_INBOX_ENTRIES.push(entry);
+    SignalToUI::set_ui_signal();
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum InboxAction {
+    None,
+    /// Close the inbox.
+    Close,
+    /// Jump to the given message in its room.
+    JumpToEvent {
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+        event_id: OwnedEventId,
+    },
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct Inbox {
+    #[deref] view: View,
+    /// All entries in the inbox, newest first.
+    #[rust] entries: Vec<InboxEntry>,
+}
+
+impl Widget for Inbox {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if let Event::Signal = event {
+            self.process_pending_entries(cx);
+        }
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for Inbox {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(id!(close_button)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, InboxAction::Close);
+        }
+    }
+}
+
+impl Inbox {
+    /// Adds all pending entries to this inbox, replacing any existing entries for the same events.
+    fn process_pending_entries(&mut self, cx: &mut Cx) {
+        let mut changed = false;
+        while let Some(entry) = PENDING_INBOX_ENTRIES.pop() {
+            self.entries.retain(|e| e.event_id != entry.event_id);
+            let index = self.entries.partition_point(|e| e.timestamp > entry.timestamp);
+            self.entries.insert(index, entry);
+            changed = true;
+        }
+        if changed {
+            self.entries.truncate(MAX_INBOX_ENTRIES);
+            self.show_entries(cx);
+        }
+    }
+
+    /// Invoke this when the inbox is about to be shown to update its content.
+    fn on_shown(&mut self, cx: &mut Cx) {
+        self.label(id!(title)).set_text(cx, tr("inbox.title"));
+        self.label(id!(description)).set_text(cx, tr("inbox.description"));
+        self.label(id!(empty_label)).set_text(cx, tr("inbox.empty"));
+        self.button(id!(close_button)).set_text(cx, tr("inbox.close"));
+        self.process_pending_entries(cx);
+        self.show_entries(cx);
+    }
+
+    fn show_entries(&mut self, cx: &mut Cx) {
+        self.label(id!(empty_label)).set_visible(cx, self.entries.is_empty());
+        self.inbox_entry_list(id!(entries_list)).set_entries(cx, self.entries.clone());
+        self.redraw(cx);
+    }
+}
+
+impl InboxRef {
+    /// See [`Inbox::on_shown()`].
+    pub fn on_shown(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.on_shown(cx);
+        }
+    }
+}
+
+
+/// A list of inbox entries, each shown with a button to jump to that message.
+#[derive(Live, LiveHook, Widget)]
+pub struct InboxEntryList {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    #[rust] children: Vec<(WidgetRef, InboxEntry)>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for InboxEntryList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (row, _) in &self.children {
+            row.handle_event(cx, event, scope);
+        }
+        let Event::Actions(actions) = event else { return };
+        for (row, entry) in &self.children {
+            if row.button(id!(jump_button)).clicked(actions) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    InboxAction::JumpToEvent {
+                        room_id: entry.room_id.clone(),
+                        room_name: entry.room_name.clone(),
+                        event_id: entry.event_id.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (row, _) in &self.children {
+            let _ = row.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl InboxEntryListRef {
+    /// Replaces the displayed entries with the given ones.
+    pub fn set_entries(&self, cx: &mut Cx, entries: Vec<InboxEntry>) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.children.clear();
+        for entry in entries {
+            let row = WidgetRef::new_from_ptr(cx, inner.item);
+            let room_name = entry.room_name.clone().unwrap_or_else(|| entry.room_id.to_string());
+            let time = utils::relative_format(&entry.timestamp).unwrap_or_default();
+            let header_key = match entry.kind {
+                InboxEntryKind::Mention => "inbox.mentioned_in",
+                InboxEntryKind::Reply => "inbox.replied_in",
+            };
+            row.label(id!(header)).set_text(cx, &format!(
+                "{} · {time}",
+                tr_args(header_key, &[("user", &entry.sender_name), ("room", &room_name)]),
+            ));
+            row.label(id!(text)).set_text(cx, &entry.body);
+            row.button(id!(jump_button)).set_text(cx, tr("inbox.jump"));
+            inner.children.push((row, entry));
+        }
+        inner.redraw(cx);
+    }
+}
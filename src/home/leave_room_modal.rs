@@ -0,0 +1,252 @@
+//! A modal dialog for confirming that the user wants to leave a room,
+//! which optionally also forgets the room after leaving it.
+//!
+//! If the user is the room's last admin, the modal warns them that nobody will be able
+//! to administer the room after they leave, and offers to promote another member to admin first.
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::{OwnedRoomId, OwnedUserId};
+
+use crate::{
+    i18n::{tr, tr_args},
+    shared::popup_list::enqueue_popup_notification,
+    sliding_sync::{submit_async_request, MatrixRequest},
+};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    pub LeaveRoomModal = {{LeaveRoomModal}} {
+        width: Fit
+        height: Fit
+
+        <RoundedView> {
+            flow: Down
+            width: 400
+            height: Fit
+            padding: {top: 25, right: 30 bottom: 30 left: 30}
+            spacing: 10
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 3.0
+            }
+
+            title = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    text_style: <TITLE_TEXT>{font_size: 13},
+                    color: #000
+                }
+            }
+
+            prompt = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 10},
+                    color: (MESSAGE_TEXT_COLOR)
+                }
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                flow: Right
+                align: {y: 0.5}
+                forget_checkbox = <CheckBox> { text: "" }
+                forget_label = <Label> {
+                    width: Fill, height: Fit
+                    draw_text: {
+                        wrap: Word,
+                        text_style: <REGULAR_TEXT>{font_size: 10},
+                        color: (COLOR_TEXT)
+                    }
+                }
+            }
+
+            last_admin_view = <View> {
+                visible: false
+                width: Fill, height: Fit
+                flow: Down
+                spacing: 8
+
+                last_admin_warning = <Label> {
+                    width: Fill, height: Fit
+                    draw_text: {
+                        wrap: Word,
+                        text_style: <REGULAR_TEXT>{font_size: 10},
+                        color: (COLOR_DANGER_RED)
+                    }
+                }
+                transfer_input = <RobrixTextInput> {
+                    width: Fill, height: Fit
+                    padding: 8
+                    draw_bg: {
+                        border_width: 1.0
+                    }
+                    empty_message: "@user:example.org"
+                }
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                margin: {top: 10}
+                flow: Right,
+                align: {x: 1.0, y: 0.5}
+                spacing: 10
+
+                cancel_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_CLOSE)
+                        color: (COLOR_TEXT),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                }
+
+                leave_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_JUMP)
+                        color: (COLOR_DANGER_RED),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+
+                    draw_bg: {
+                        border_color: (COLOR_DANGER_RED),
+                        color: #fff0f0
+                    }
+                    draw_text:{
+                        color: (COLOR_DANGER_RED),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum LeaveRoomModalAction {
+    None,
+    /// Open the leave room modal for the given room.
+    Open {
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+    },
+    Close,
+    /// Whether the current user is the last admin of the given room,
+    /// posted in response to a [`MatrixRequest::CheckLastAdmin`].
+    LastAdminChecked {
+        room_id: OwnedRoomId,
+        is_last_admin: bool,
+    },
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct LeaveRoomModal {
+    #[deref] view: View,
+    /// The room that the user is about to leave.
+    #[rust] room_id: Option<OwnedRoomId>,
+    /// Whether to forget the room after leaving it.
+    #[rust] forget: bool,
+    /// Whether the user is the room's last admin, in which case they can first transfer admin rights.
+    #[rust] is_last_admin: bool,
+}
+
+impl Widget for LeaveRoomModal {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for LeaveRoomModal {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(id!(cancel_button)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, LeaveRoomModalAction::Close);
+            return;
+        }
+
+        if let Some(forget) = self.check_box(id!(forget_checkbox)).changed(actions) {
+            self.forget = forget;
+        }
+
+        for action in actions {
+            if let Some(LeaveRoomModalAction::LastAdminChecked { room_id, is_last_admin }) = action.downcast_ref() {
+                if self.room_id.as_ref() == Some(room_id) {
+                    self.is_last_admin = *is_last_admin;
+                    self.view(id!(last_admin_view)).set_visible(cx, *is_last_admin);
+                    self.redraw(cx);
+                }
+            }
+        }
+
+        let Some(room_id) = self.room_id.clone() else { return };
+        if self.button(id!(leave_button)).clicked(actions) {
+            let transfer_admin_to = if self.is_last_admin {
+                let user_id = self.text_input(id!(transfer_input)).text().trim().to_owned();
+                if user_id.is_empty() {
+                    None
+                } else {
+                    match OwnedUserId::try_from(user_id.as_str()) {
+                        Ok(user_id) => Some(user_id),
+                        Err(_) => {
+                            enqueue_popup_notification(tr_args("leave_room.invalid_user_id", &[("user_id", &user_id)]));
+                            return;
+                        }
+                    }
+                }
+            } else {
+                None
+            };
+            submit_async_request(MatrixRequest::LeaveRoom {
+                room_id,
+                forget: self.forget,
+                transfer_admin_to,
+            });
+            cx.widget_action(self.widget_uid(), &scope.path, LeaveRoomModalAction::Close);
+        }
+    }
+}
+
+impl LeaveRoomModal {
+    /// Resets this modal to confirm leaving the given room,
+    /// and checks whether the user is that room's last admin.
+    fn initialize(&mut self, cx: &mut Cx, room_id: OwnedRoomId, room_name: Option<String>) {
+        let room_name = room_name.unwrap_or_else(|| room_id.to_string());
+        submit_async_request(MatrixRequest::CheckLastAdmin { room_id: room_id.clone() });
+        self.room_id = Some(room_id);
+        self.forget = false;
+        self.is_last_admin = false;
+
+        self.label(id!(title)).set_text(cx, tr("leave_room.title"));
+        self.label(id!(prompt)).set_text(cx, &tr_args("leave_room.prompt", &[("room", &room_name)]));
+        self.check_box(id!(forget_checkbox)).set_selected(cx, false);
+        self.label(id!(forget_label)).set_text(cx, tr("leave_room.forget"));
+        self.label(id!(last_admin_warning)).set_text(cx, tr("leave_room.last_admin_warning"));
+        self.text_input(id!(transfer_input)).set_text(cx, "");
+        self.view(id!(last_admin_view)).set_visible(cx, false);
+        self.button(id!(cancel_button)).set_text(cx, tr("leave_room.cancel"));
+        self.button(id!(leave_button)).set_text(cx, tr("leave_room.leave"));
+        self.redraw(cx);
+    }
+}
+
+impl LeaveRoomModalRef {
+    /// See [`LeaveRoomModal::initialize()`].
+    pub fn initialize(&self, cx: &mut Cx, room_id: OwnedRoomId, room_name: Option<String>) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.initialize(cx, room_id, room_name);
+    }
+}
@@ -1,4 +1,5 @@
 use makepad_widgets::*;
+use tracing::{info, warn};
 use matrix_sdk::ruma::OwnedEventId;
 
 use crate::sliding_sync::TimelineRequestSender;
@@ -148,7 +149,7 @@ pub struct LoadingPane {
 impl Drop for LoadingPane {
     fn drop(&mut self) {
         if let LoadingPaneState::BackwardsPaginateUntilEvent { target_event_id, request_sender, .. } = &self.state {
-            warning!("Dropping LoadingPane with target_event_id: {}", target_event_id);
+            warn!("Dropping LoadingPane with target_event_id: {}", target_event_id);
             request_sender.send_if_modified(|requests| {
                 let initial_len = requests.len();
                 requests.retain(|r| &r.target_event_id != target_event_id);
@@ -209,7 +210,7 @@ impl Widget for LoadingPane {
                     // such that they can stop looking for the target event.
                     requests.len() != initial_len
                 });
-                log!("LoadingPane: {} cancel request for target_event_id: {target_event_id}",
+                info!("LoadingPane: {} cancel request for target_event_id: {target_event_id}",
                     if _did_send { "Sent" } else { "Did not send" },
                 );
             }
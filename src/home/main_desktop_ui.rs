@@ -1,7 +1,13 @@
 use makepad_widgets::*;
+use tracing::error;
 use std::collections::HashMap;
 
-use crate::app::SelectedRoom;
+use crate::{
+    app::SelectedRoom,
+    persistent_state::{self, SavedDockState},
+    settings::app_settings::{app_settings, update_app_settings},
+    sliding_sync::{current_user_id, is_room_loaded},
+};
 
 use super::room_screen::RoomScreenWidgetRefExt;
 live_design! {
@@ -71,15 +77,58 @@ pub struct MainDesktopUI {
     /// which would trigger redraw of whole Widget.
     #[rust]
     most_recently_selected_room: Option<SelectedRoom>,
+
+    /// Whether the previously-saved dock state has been loaded (or found not to exist).
+    #[rust]
+    saved_dock_state_loaded: bool,
+
+    /// The previously-saved dock state that is waiting to be restored.
+    ///
+    /// Each saved room tab is re-opened once that room has been loaded,
+    /// after which it is removed from this state.
+    #[rust]
+    pending_dock_state: Option<SavedDockState>,
+
+    /// Whether the user's saved rooms sidebar width has been applied to the dock.
+    #[rust]
+    sidebar_width_restored: bool,
+
+    /// The rooms sidebar width that is waiting to be saved, after the user has resized it.
+    #[rust]
+    pending_sidebar_width: Option<f64>,
+
+    /// A timer used to save the rooms sidebar width only once the user has stopped resizing it.
+    #[rust]
+    save_sidebar_width_timer: Timer,
 }
 
+/// How long to wait after the rooms sidebar was last resized before saving its width.
+const SAVE_SIDEBAR_WIDTH_DELAY_SECS: f64 = 0.5;
+
 impl Widget for MainDesktopUI {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        // A signal may indicate that the user has logged in or that more rooms have been loaded,
+        // so we try to restore the previously-saved dock state.
+        if let Event::Signal = event {
+            self.restore_dock_state(cx);
+        }
+        if self.save_sidebar_width_timer.is_event(event).is_some() {
+            if let Some(width) = self.pending_sidebar_width.take() {
+                update_app_settings(|s| s.rooms_sidebar_width = width);
+            }
+        }
         self.match_event(cx, event);
         self.view.handle_event(cx, event, scope);
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if !self.sidebar_width_restored {
+            self.sidebar_width_restored = true;
+            let width = app_settings().rooms_sidebar_width;
+            self.view.dock(id!(dock)).apply_over(cx, live! {
+                root = Splitter { align: FromA((width)) }
+            });
+        }
         self.view.draw_walk(cx, scope, walk)
     }
 }
@@ -99,6 +148,7 @@ impl MainDesktopUI {
         if self.open_rooms.contains_key(&room_id_as_live_id) {
             dock.select_tab(cx, room_id_as_live_id);
             self.most_recently_selected_room = Some(room);
+            self.save_dock_state();
             return;
         }
 
@@ -135,6 +185,7 @@ impl MainDesktopUI {
         }
         
         self.most_recently_selected_room = Some(room);
+        self.save_dock_state();
     }
 
     /// Closes a tab in the dock and focuses in the latest open room
@@ -176,6 +227,75 @@ impl MainDesktopUI {
         dock.close_tab(cx, tab_id);
         self.tab_to_close = None;
         self.open_rooms.remove(&tab_id);
+        self.save_dock_state();
+    }
+
+    /// Saves the current dock state for the current user, such that it can be restored on startup.
+    fn save_dock_state(&self) {
+        let Some(user_id) = current_user_id() else { return };
+        let mut open_rooms = self.room_order.clone();
+        // Don't forget any saved rooms that haven't been restored yet.
+        if let Some(pending) = self.pending_dock_state.as_ref() {
+            let not_yet_restored: Vec<_> = pending.open_rooms.iter()
+                .filter(|room| !open_rooms.contains(room))
+                .cloned()
+                .collect();
+            open_rooms.extend(not_yet_restored);
+        }
+        let dock_state = SavedDockState {
+            open_rooms,
+            selected_room: self.most_recently_selected_room.as_ref().map(|room| room.room_id.clone()),
+        };
+        if let Err(e) = persistent_state::save_dock_state(&user_id, &dock_state) {
+            error!("Failed to save dock state for user {user_id}: {e}");
+        }
+    }
+
+    /// Re-opens the tabs of any previously-saved rooms that have now been loaded,
+    /// and re-selects the previously-selected room once its tab has been re-opened.
+    fn restore_dock_state(&mut self, cx: &mut Cx) {
+        if !self.saved_dock_state_loaded {
+            let Some(user_id) = current_user_id() else { return };
+            self.saved_dock_state_loaded = true;
+            self.pending_dock_state = persistent_state::load_dock_state(&user_id);
+        }
+        let Some(mut pending) = self.pending_dock_state.take() else { return };
+
+        let (loaded_rooms, unloaded_rooms) = std::mem::take(&mut pending.open_rooms)
+            .into_iter()
+            .partition::<Vec<_>, _>(|room| is_room_loaded(&room.room_id));
+        pending.open_rooms = unloaded_rooms;
+        if loaded_rooms.is_empty() {
+            self.pending_dock_state = Some(pending);
+            return;
+        }
+        // Keep the not-yet-restored rooms pending while re-opening tabs,
+        // such that they're included in the dock state saved by `focus_or_create_tab()`.
+        let selected_room_id = pending.selected_room.clone();
+        self.pending_dock_state = Some(pending);
+        for room in loaded_rooms {
+            self.focus_or_create_tab(cx, room);
+        }
+
+        // Re-select the previously-selected room, if its tab is now open.
+        let selected_room = selected_room_id.and_then(|room_id|
+            self.room_order.iter().find(|room| room.room_id == room_id).cloned()
+        );
+        if let Some(selected_room) = selected_room {
+            if let Some(pending) = self.pending_dock_state.as_mut() {
+                pending.selected_room = None;
+            }
+            self.focus_or_create_tab(cx, selected_room.clone());
+            cx.widget_action(
+                self.widget_uid(),
+                &HeapLiveIdPath::default(),
+                RoomsPanelAction::RoomFocused(selected_room),
+            );
+        }
+        if self.pending_dock_state.as_ref().is_some_and(|p| p.open_rooms.is_empty()) {
+            self.pending_dock_state = None;
+        }
+        self.redraw(cx);
     }
 }
 
@@ -202,7 +322,8 @@ impl MatchEvent for MainDesktopUI {
                             RoomsPanelAction::RoomFocused(selected_room.clone()),
                         );
                         self.most_recently_selected_room = Some(selected_room.clone());
-                    }   
+                    }
+                    self.save_dock_state();
                 }
                 DockAction::TabCloseWasPressed(tab_id) => {
                     self.tab_to_close = Some(tab_id);
@@ -236,13 +357,21 @@ impl MatchEvent for MainDesktopUI {
                         dock.drop_move(cx, drop_event.abs, *internal_id);
                     }
                 }
+                // When the user resizes the rooms sidebar, save its new width
+                // once they have stopped dragging the splitter.
+                DockAction::SplitPanelChanged { panel_id, align, .. } => {
+                    if let (true, SplitterAlign::FromA(width)) = (panel_id == live_id!(root), align) {
+                        self.pending_sidebar_width = Some(width);
+                        cx.stop_timer(self.save_sidebar_width_timer);
+                        self.save_sidebar_width_timer = cx.start_timeout(SAVE_SIDEBAR_WIDTH_DELAY_SECS);
+                    }
+                }
                 _ => (),
             }
 
             // Handle RoomsList actions
             if let super::rooms_list::RoomsListAction::Selected {
                 room_id,
-                room_index: _,
                 room_name,
             } = action.cast() {
                 // Note that this cannot be performed within draw_walk() as the draw flow prevents from
@@ -1,7 +1,8 @@
 use makepad_widgets::*;
 
 use crate::{
-    app::AppState, home::room_screen::RoomScreenWidgetExt
+    app::AppState, home::room_screen::RoomScreenWidgetExt,
+    utils::{horizontal_swipe, SwipeDirection, SWIPE_BACK_EDGE_WIDTH},
 };
 
 live_design! {
@@ -41,6 +42,24 @@ pub struct MainMobileUI {
 impl Widget for MainMobileUI {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         self.view.handle_event(cx, event, scope);
+
+        // A rightwards swipe that starts at the left edge of the screen
+        // navigates back to the rooms list, like the header's back button.
+        // We use capture overload such that we still observe touches
+        // that were captured by inner widgets, e.g., the timeline.
+        let area = self.view.area();
+        if let Hit::FingerUp(fe) = event.hits_with_capture_overload(cx, area, true) {
+            if fe.device.is_touch()
+                && fe.abs_start.x - area.rect(cx).pos.x <= SWIPE_BACK_EDGE_WIDTH
+                && horizontal_swipe(fe.abs_start, fe.abs) == Some(SwipeDirection::Right)
+            {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    StackNavigationAction::NavigateBack,
+                );
+            }
+        }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
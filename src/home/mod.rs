@@ -8,21 +8,39 @@ pub mod main_mobile_ui;
 pub mod room_preview;
 pub mod room_screen;
 pub mod room_read_receipt;
+pub mod timestamp;
 pub mod rooms_list;
 pub mod rooms_sidebar;
 pub mod spaces_dock;
 pub mod welcome_screen;
 pub mod event_reaction_list;
 pub mod new_message_context_menu;
+pub mod room_context_menu;
+pub mod room_hover_preview;
+pub mod export_chat_modal;
+pub mod moderation_dashboard;
+pub mod inbox;
+pub mod contacts;
+pub mod share_room_modal;
+pub mod room_wallpaper_modal;
+pub mod room_filter_tabs;
+pub mod leave_room_modal;
+pub mod event_source_modal;
+pub mod scheduled_messages_list;
 
 pub fn live_design(cx: &mut Cx) {
     home_screen::live_design(cx);
     loading_pane::live_design(cx);
+    room_filter_tabs::live_design(cx);
     rooms_list::live_design(cx);
     room_preview::live_design(cx);
     new_message_context_menu::live_design(cx);
+    room_context_menu::live_design(cx);
+    room_hover_preview::live_design(cx);
+    scheduled_messages_list::live_design(cx);
     room_screen::live_design(cx);
     room_read_receipt::live_design(cx);
+    timestamp::live_design(cx);
     rooms_sidebar::live_design(cx);
     main_mobile_ui::live_design(cx);
     main_desktop_ui::live_design(cx);
@@ -30,4 +48,12 @@ pub fn live_design(cx: &mut Cx) {
     welcome_screen::live_design(cx);
     light_themed_dock::live_design(cx);
     event_reaction_list::live_design(cx);
+    export_chat_modal::live_design(cx);
+    moderation_dashboard::live_design(cx);
+    inbox::live_design(cx);
+    contacts::live_design(cx);
+    share_room_modal::live_design(cx);
+    room_wallpaper_modal::live_design(cx);
+    leave_room_modal::live_design(cx);
+    event_source_modal::live_design(cx);
 }
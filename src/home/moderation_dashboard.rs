@@ -0,0 +1,805 @@
+//! A dashboard for room moderators that summarizes recent activity in a room,
+//! i.e., recent joins, messages, and redactions,
+//! with one-click actions to kick or ban users and to remove their messages.
+//!
+//! Users who can ban in a room can also subscribe it to moderation policy lists here,
+//! users who can send a tombstone event can upgrade the room to a newer room version,
+//! and users who can change the room's history visibility or guest access can do so here.
+//!
+//! The dashboard is only offered to users who have at least one of the above powers in a room.
+
+use imbl::Vector;
+use makepad_widgets::*;
+use matrix_sdk::ruma::{events::room::{guest_access::GuestAccess, history_visibility::HistoryVisibility}, MilliSecondsSinceUnixEpoch, OwnedRoomId, OwnedUserId, RoomOrAliasId, RoomVersionId};
+use matrix_sdk_ui::timeline::{MembershipChange, TimelineEventItemId, TimelineItem, TimelineItemContent};
+use std::sync::Arc;
+
+use crate::{
+    event_preview::body_of_timeline_item,
+    i18n::{tr, tr_args},
+    policy_lists::{self, PolicyListAction},
+    shared::popup_list::enqueue_popup_notification,
+    sliding_sync::{get_client, submit_async_request, MatrixRequest, UserPowerLevels},
+    utils::{self, get_or_fetch_event_sender},
+};
+
+/// The maximum number of entries shown in each section of the dashboard.
+const MAX_ENTRIES_PER_SECTION: usize = 8;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    ModerationButton = <RobrixIconButton> {
+        align: {x: 0.5, y: 0.5}
+        padding: {left: 8, right: 8, top: 5, bottom: 5}
+        draw_icon: {
+            svg_file: (ICON_BLOCK_USER)
+            color: (COLOR_DANGER_RED),
+        }
+        icon_walk: {width: 12, height: 12, margin: {left: -2, right: -1} }
+
+        draw_bg: {
+            border_color: (COLOR_DANGER_RED),
+            color: #fff0f0 // light red
+        }
+        draw_text:{
+            text_style: <REGULAR_TEXT>{font_size: 9},
+            color: (COLOR_DANGER_RED),
+        }
+    }
+
+    ModerationEntryRow = <View> {
+        width: Fill, height: Fit
+        flow: Right,
+        spacing: 6,
+        align: {y: 0.5}
+        padding: {top: 3, bottom: 3}
+
+        text = <Label> {
+            width: Fill, height: Fit
+            draw_text: {
+                wrap: Ellipsis,
+                text_style: <REGULAR_TEXT>{font_size: 10},
+                color: #000
+            }
+        }
+        kick_button = <ModerationButton> { text: "Kick" }
+        ban_button = <ModerationButton> { text: "Ban" }
+        redact_button = <ModerationButton> {
+            draw_icon: { svg_file: (ICON_TRASH) }
+            text: "Remove"
+        }
+    }
+
+    pub ModerationEntryList = {{ModerationEntryList}} {
+        width: Fill, height: Fit
+        flow: Down
+    }
+
+    ModerationSectionTitle = <Label> {
+        width: Fill, height: Fit
+        margin: {top: 10}
+        draw_text: {
+            text_style: <USERNAME_TEXT_STYLE>{font_size: 11},
+            color: #000
+        }
+    }
+
+    pub ModerationDashboard = {{ModerationDashboard}} {
+        width: Fit
+        height: Fit
+
+        <RoundedView> {
+            flow: Down
+            width: 500
+            height: Fit
+            padding: {top: 25, right: 30 bottom: 30 left: 30}
+            spacing: 5
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 3.0
+            }
+
+            title = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    text_style: <TITLE_TEXT>{font_size: 13},
+                    color: #000
+                }
+                text: "Moderation"
+            }
+
+            description = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 9.5},
+                    color: (MESSAGE_TEXT_COLOR)
+                }
+            }
+
+            joins_title = <ModerationSectionTitle> {}
+            joins_list = <ModerationEntryList> {
+                item: <ModerationEntryRow> {}
+            }
+
+            messages_title = <ModerationSectionTitle> {}
+            messages_list = <ModerationEntryList> {
+                item: <ModerationEntryRow> {}
+            }
+
+            redactions_title = <ModerationSectionTitle> {}
+            redactions_list = <ModerationEntryList> {
+                item: <ModerationEntryRow> {}
+            }
+
+            policy_lists = <View> {
+                width: Fill, height: Fit
+                flow: Down,
+                spacing: 5
+
+                policy_lists_title = <ModerationSectionTitle> {}
+                policy_lists_label = <Label> {
+                    width: Fill, height: Fit
+                    draw_text: {
+                        wrap: Word,
+                        text_style: <REGULAR_TEXT>{font_size: 10},
+                        color: #000
+                    }
+                }
+
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right,
+                    spacing: 6,
+                    align: {y: 0.5}
+
+                    policy_list_input = <RobrixTextInput> {
+                        width: Fill, height: Fit
+                        empty_message: "#policy-list:example.org"
+                    }
+                    subscribe_button = <ModerationButton> {
+                        draw_icon: { svg_file: (ICON_LINK) }
+                        text: "Subscribe"
+                    }
+                    unsubscribe_button = <ModerationButton> {
+                        draw_icon: { svg_file: (ICON_CLOSE) }
+                        text: "Unsubscribe"
+                    }
+                }
+            }
+
+            room_access = <View> {
+                width: Fill, height: Fit
+                flow: Down,
+                spacing: 5
+
+                room_access_title = <ModerationSectionTitle> {}
+
+                history_visibility_view = <View> {
+                    width: Fill, height: Fit
+                    flow: Down,
+                    spacing: 3
+
+                    <View> {
+                        width: Fill, height: Fit
+                        flow: Right,
+                        spacing: 6,
+                        align: {y: 0.5}
+
+                        history_visibility_label = <Label> {
+                            width: Fill, height: Fit
+                            draw_text: {
+                                text_style: <REGULAR_TEXT>{font_size: 10},
+                                color: #000
+                            }
+                        }
+                        // KEEP IN SYNC WITH `HISTORY_VISIBILITY_OPTIONS`.
+                        history_visibility_dropdown = <DropDown> {
+                            width: 220, height: Fit
+                            labels: ["Anyone", "Members (all history)", "Members (since invited)", "Members (since joined)"]
+                        }
+                    }
+                    history_visibility_description = <Label> {
+                        width: Fill, height: Fit
+                        draw_text: {
+                            wrap: Word,
+                            text_style: <REGULAR_TEXT>{font_size: 9.5},
+                            color: (MESSAGE_TEXT_COLOR)
+                        }
+                    }
+                }
+
+                guest_access_view = <View> {
+                    width: Fill, height: Fit
+                    flow: Down,
+                    spacing: 3
+
+                    <View> {
+                        width: Fill, height: Fit
+                        flow: Right,
+                        spacing: 6,
+                        align: {y: 0.5}
+
+                        guest_access_label = <Label> {
+                            width: Fill, height: Fit
+                            draw_text: {
+                                text_style: <REGULAR_TEXT>{font_size: 10},
+                                color: #000
+                            }
+                        }
+                        // KEEP IN SYNC WITH `GUEST_ACCESS_OPTIONS`.
+                        guest_access_dropdown = <DropDown> {
+                            width: 220, height: Fit
+                            labels: ["Guests can join", "Guests are forbidden"]
+                        }
+                    }
+                    guest_access_description = <Label> {
+                        width: Fill, height: Fit
+                        draw_text: {
+                            wrap: Word,
+                            text_style: <REGULAR_TEXT>{font_size: 9.5},
+                            color: (MESSAGE_TEXT_COLOR)
+                        }
+                    }
+                }
+            }
+
+            room_upgrade = <View> {
+                width: Fill, height: Fit
+                flow: Down,
+                spacing: 5
+
+                room_upgrade_title = <ModerationSectionTitle> {}
+                room_upgrade_label = <Label> {
+                    width: Fill, height: Fit
+                    draw_text: {
+                        wrap: Word,
+                        text_style: <REGULAR_TEXT>{font_size: 10},
+                        color: #000
+                    }
+                }
+
+                <View> {
+                    width: Fill, height: Fit
+                    flow: Right,
+                    spacing: 6,
+                    align: {y: 0.5}
+
+                    room_version_input = <RobrixTextInput> {
+                        width: Fill, height: Fit
+                        empty_message: "11"
+                    }
+                    upgrade_button = <ModerationButton> {
+                        draw_icon: { svg_file: (ICON_JUMP) }
+                        text: "Upgrade"
+                    }
+                }
+
+                room_upgrade_status = <Label> {
+                    width: Fill, height: Fit
+                    draw_text: {
+                        wrap: Word,
+                        text_style: <REGULAR_TEXT>{font_size: 9.5},
+                        color: (MESSAGE_TEXT_COLOR)
+                    }
+                }
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                margin: {top: 20}
+                align: {x: 1.0, y: 0.5}
+
+                close_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_CLOSE)
+                        color: (COLOR_TEXT),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                    text: "Close"
+                }
+            }
+        }
+    }
+}
+
+/// The kind of a recent event shown in the moderation dashboard.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ModerationEntryKind {
+    /// A user joined the room.
+    Join,
+    /// A user sent a message.
+    Message,
+    /// A user's message was redacted.
+    Redaction,
+}
+
+/// A recent event in a room that may be of interest to moderators.
+#[derive(Clone, Debug)]
+pub struct ModerationEntry {
+    pub kind: ModerationEntryKind,
+    /// The user who joined, sent the message, or whose message was redacted.
+    pub user_id: OwnedUserId,
+    /// The displayable name of that user.
+    pub username: String,
+    /// A short description of the event, e.g., the message's text.
+    pub description: String,
+    pub timestamp: MilliSecondsSinceUnixEpoch,
+    /// The ID of the message, used to redact it.
+    pub timeline_event_id: TimelineEventItemId,
+}
+
+/// The recent activity in a room that is shown in the moderation dashboard, newest first.
+#[derive(Clone, Debug, Default)]
+pub struct ModerationSummary {
+    pub joins: Vec<ModerationEntry>,
+    pub messages: Vec<ModerationEntry>,
+    pub redactions: Vec<ModerationEntry>,
+}
+
+impl ModerationSummary {
+    /// Gathers the recent activity from the given room's loaded timeline items.
+    pub fn from_timeline_items(room_id: &OwnedRoomId, items: &Vector<Arc<TimelineItem>>) -> Self {
+        let mut summary = Self::default();
+        for event_tl_item in items.iter().rev().filter_map(|item| item.as_event()) {
+            let (kind, user_id, section) = match event_tl_item.content() {
+                TimelineItemContent::MembershipChange(change)
+                    if change.change() == Some(MembershipChange::Joined) =>
+                {
+                    (ModerationEntryKind::Join, change.user_id().to_owned(), &mut summary.joins)
+                }
+                TimelineItemContent::Message(_) | TimelineItemContent::Sticker(_) => {
+                    (ModerationEntryKind::Message, event_tl_item.sender().to_owned(), &mut summary.messages)
+                }
+                TimelineItemContent::RedactedMessage => {
+                    (ModerationEntryKind::Redaction, event_tl_item.sender().to_owned(), &mut summary.redactions)
+                }
+                _ => continue,
+            };
+            if section.len() >= MAX_ENTRIES_PER_SECTION { continue }
+            section.push(ModerationEntry {
+                kind,
+                user_id,
+                username: get_or_fetch_event_sender(event_tl_item, Some(room_id)),
+                description: body_of_timeline_item(event_tl_item),
+                timestamp: event_tl_item.timestamp(),
+                timeline_event_id: event_tl_item.identifier(),
+            });
+        }
+        summary
+    }
+}
+
+/// The moderation powers that the current user has in a room.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ModeratorPowers {
+    pub can_kick: bool,
+    pub can_ban: bool,
+    pub can_redact: bool,
+    pub can_upgrade_room: bool,
+    pub can_change_history_visibility: bool,
+    pub can_change_guest_access: bool,
+}
+
+impl From<UserPowerLevels> for ModeratorPowers {
+    fn from(user_power: UserPowerLevels) -> Self {
+        Self {
+            can_kick: user_power.can_kick(),
+            can_ban: user_power.can_ban(),
+            can_redact: user_power.can_redact_others(),
+            can_upgrade_room: user_power.can_upgrade_room(),
+            can_change_history_visibility: user_power.can_change_history_visibility(),
+            can_change_guest_access: user_power.can_change_guest_access(),
+        }
+    }
+}
+
+impl ModeratorPowers {
+    /// Returns `true` if the user has any moderation powers at all.
+    pub fn any(self) -> bool {
+        self.can_kick || self.can_ban || self.can_redact || self.can_upgrade_room
+            || self.can_change_history_visibility || self.can_change_guest_access
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ModerationDashboardAction {
+    None,
+    /// Open the moderation dashboard for the given room.
+    Open {
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+        powers: ModeratorPowers,
+        summary: ModerationSummary,
+    },
+    Close,
+}
+
+/// The history visibility options shown in the dashboard,
+/// along with the keys of each one's dropdown label and explanation.
+///
+/// KEEP IN SYNC WITH the `labels` of the `history_visibility_dropdown` DSL.
+const HISTORY_VISIBILITY_OPTIONS: [(HistoryVisibility, &str, &str); 4] = [
+    (HistoryVisibility::WorldReadable, "room_access.history.world_readable.label", "room_access.history.world_readable"),
+    (HistoryVisibility::Shared, "room_access.history.shared.label", "room_access.history.shared"),
+    (HistoryVisibility::Invited, "room_access.history.invited.label", "room_access.history.invited"),
+    (HistoryVisibility::Joined, "room_access.history.joined.label", "room_access.history.joined"),
+];
+
+/// The guest access options shown in the dashboard,
+/// along with the keys of each one's dropdown label and explanation.
+///
+/// KEEP IN SYNC WITH the `labels` of the `guest_access_dropdown` DSL.
+const GUEST_ACCESS_OPTIONS: [(GuestAccess, &str, &str); 2] = [
+    (GuestAccess::CanJoin, "room_access.guests.can_join.label", "room_access.guests.can_join"),
+    (GuestAccess::Forbidden, "room_access.guests.forbidden.label", "room_access.guests.forbidden"),
+];
+
+/// A step of upgrading a room to a newer room version.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RoomUpgradeStep {
+    /// The server is creating the successor room and tombstoning the old one.
+    Upgrading,
+    /// The upgrade is being announced in the old room.
+    Announcing,
+}
+
+/// Actions sent from the background room upgrade task to the UI thread.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum RoomUpgradeAction {
+    /// The upgrade of the given room has reached the given step.
+    Progress {
+        room_id: OwnedRoomId,
+        step: RoomUpgradeStep,
+    },
+    /// The given room was upgraded and replaced by the new room.
+    Finished {
+        room_id: OwnedRoomId,
+        new_room_id: OwnedRoomId,
+    },
+    /// The upgrade failed.
+    Failed {
+        room_id: OwnedRoomId,
+        error: String,
+    },
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ModerationDashboard {
+    #[deref] view: View,
+    /// The room that this dashboard is currently shown for.
+    #[rust] room_id: Option<OwnedRoomId>,
+    /// Whether an upgrade of this dashboard's room is in progress.
+    #[rust] is_upgrading: bool,
+}
+
+impl Widget for ModerationDashboard {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for ModerationDashboard {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(id!(close_button)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, ModerationDashboardAction::Close);
+        }
+        let Some(room_id) = self.room_id.clone() else { return };
+
+        let subscribe_clicked = self.button(id!(subscribe_button)).clicked(actions);
+        let unsubscribe_clicked = self.button(id!(unsubscribe_button)).clicked(actions);
+        if subscribe_clicked || unsubscribe_clicked {
+            let input = self.text_input(id!(policy_list_input));
+            let entered = input.text();
+            match RoomOrAliasId::parse(entered.trim()) {
+                Ok(list) if subscribe_clicked => {
+                    submit_async_request(MatrixRequest::SubscribePolicyList { room_id: room_id.clone(), list });
+                    input.set_text(cx, "");
+                }
+                Ok(list) => {
+                    submit_async_request(MatrixRequest::UnsubscribePolicyList { room_id: room_id.clone(), list });
+                    input.set_text(cx, "");
+                }
+                Err(_) => enqueue_popup_notification(tr_args("policy.invalid_list", &[("list", entered.trim())])),
+            }
+        }
+
+        if let Some(index) = self.drop_down(id!(history_visibility_dropdown)).selected(actions) {
+            if let Some((visibility, _, description_key)) = HISTORY_VISIBILITY_OPTIONS.get(index) {
+                self.label(id!(history_visibility_description)).set_text(cx, tr(*description_key));
+                submit_async_request(MatrixRequest::SetHistoryVisibility {
+                    room_id: room_id.clone(),
+                    visibility: visibility.clone(),
+                });
+            }
+        }
+        if let Some(index) = self.drop_down(id!(guest_access_dropdown)).selected(actions) {
+            if let Some((guest_access, _, description_key)) = GUEST_ACCESS_OPTIONS.get(index) {
+                self.label(id!(guest_access_description)).set_text(cx, tr(*description_key));
+                submit_async_request(MatrixRequest::SetGuestAccess {
+                    room_id: room_id.clone(),
+                    guest_access: guest_access.clone(),
+                });
+            }
+        }
+
+        if self.button(id!(upgrade_button)).clicked(actions) && !self.is_upgrading {
+            let input = self.text_input(id!(room_version_input));
+            let entered = input.text();
+            let new_version = match entered.trim() {
+                "" => None,
+                version => match RoomVersionId::try_from(version) {
+                    Ok(version) => Some(version),
+                    Err(_) => {
+                        enqueue_popup_notification(tr_args("room_upgrade.invalid_version", &[("version", version)]));
+                        return;
+                    }
+                },
+            };
+            submit_async_request(MatrixRequest::UpgradeRoom { room_id: room_id.clone(), new_version });
+            self.set_upgrade_status(cx, true, tr("room_upgrade.starting"));
+        }
+
+        for action in actions {
+            if let Some(PolicyListAction::Updated { room_id: updated_room_id }) = action.downcast_ref() {
+                if *updated_room_id == room_id {
+                    self.show_policy_lists(cx, &room_id);
+                }
+            }
+
+            // `RoomUpgradeAction`s come from a background task, so they are NOT widget actions.
+            match action.downcast_ref::<RoomUpgradeAction>() {
+                Some(RoomUpgradeAction::Progress { room_id: upgraded_room_id, step }) if *upgraded_room_id == room_id => {
+                    let status = match step {
+                        RoomUpgradeStep::Upgrading => tr("room_upgrade.upgrading"),
+                        RoomUpgradeStep::Announcing => tr("room_upgrade.announcing"),
+                    };
+                    self.set_upgrade_status(cx, true, status);
+                }
+                Some(RoomUpgradeAction::Finished { room_id: upgraded_room_id, new_room_id }) if *upgraded_room_id == room_id => {
+                    let status = tr_args("room_upgrade.finished", &[("room_id", new_room_id.as_str())]);
+                    enqueue_popup_notification(status.clone());
+                    self.set_upgrade_status(cx, false, &status);
+                }
+                Some(RoomUpgradeAction::Failed { room_id: upgraded_room_id, error }) if *upgraded_room_id == room_id => {
+                    let status = tr_args("room_upgrade.failed", &[("error", error)]);
+                    enqueue_popup_notification(status.clone());
+                    self.set_upgrade_status(cx, false, &status);
+                }
+                _ => { }
+            }
+        }
+    }
+}
+
+impl ModerationDashboard {
+    /// Populates this dashboard with the given room's recent activity.
+    fn initialize(
+        &mut self,
+        cx: &mut Cx,
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+        powers: ModeratorPowers,
+        summary: ModerationSummary,
+    ) {
+        let room_name = room_name.unwrap_or_else(|| room_id.to_string());
+        self.label(id!(title)).set_text(cx, &tr_args("moderation.title", &[("room", &room_name)]));
+        self.label(id!(description)).set_text(cx, tr("moderation.description"));
+        self.button(id!(close_button)).set_text(cx, tr("moderation.close"));
+
+        let sections = [
+            (id!(joins_title), id!(joins_list), "moderation.recent_joins", summary.joins),
+            (id!(messages_title), id!(messages_list), "moderation.recent_messages", summary.messages),
+            (id!(redactions_title), id!(redactions_list), "moderation.recent_redactions", summary.redactions),
+        ];
+        for (title_id, list_id, title_key, entries) in sections {
+            self.label(title_id).set_text(cx, &tr_args(title_key, &[("count", &entries.len().to_string())]));
+            self.moderation_entry_list(list_id).set_entries(cx, room_id.clone(), powers, entries);
+        }
+
+        // Policy lists can only be enforced by users who are able to ban.
+        self.view(id!(policy_lists)).set_visible(cx, powers.can_ban);
+        self.label(id!(policy_lists_title)).set_text(cx, tr("policy.title"));
+        self.button(id!(subscribe_button)).set_text(cx, tr("policy.subscribe"));
+        self.button(id!(unsubscribe_button)).set_text(cx, tr("policy.unsubscribe"));
+        self.show_policy_lists(cx, &room_id);
+
+        self.show_room_access(cx, &room_id, powers);
+
+        self.view(id!(room_upgrade)).set_visible(cx, powers.can_upgrade_room);
+        self.label(id!(room_upgrade_title)).set_text(cx, tr("room_upgrade.title"));
+        self.label(id!(room_upgrade_label)).set_text(cx, tr("room_upgrade.description"));
+        self.text_input(id!(room_version_input)).set_text(cx, "");
+        self.button(id!(upgrade_button)).set_text(cx, tr("room_upgrade.upgrade"));
+        self.set_upgrade_status(cx, false, "");
+        self.room_id = Some(room_id);
+        self.redraw(cx);
+    }
+
+    /// Shows the given room's current history visibility and guest access,
+    /// each of which is only shown if the user is allowed to change it.
+    fn show_room_access(&mut self, cx: &mut Cx, room_id: &OwnedRoomId, powers: ModeratorPowers) {
+        self.view(id!(room_access)).set_visible(cx, powers.can_change_history_visibility || powers.can_change_guest_access);
+        self.label(id!(room_access_title)).set_text(cx, tr("room_access.title"));
+        self.label(id!(history_visibility_label)).set_text(cx, tr("room_access.history"));
+        self.label(id!(guest_access_label)).set_text(cx, tr("room_access.guests"));
+        self.view(id!(history_visibility_view)).set_visible(cx, powers.can_change_history_visibility);
+        self.view(id!(guest_access_view)).set_visible(cx, powers.can_change_guest_access);
+        self.drop_down(id!(history_visibility_dropdown)).set_labels(
+            cx,
+            HISTORY_VISIBILITY_OPTIONS.iter().map(|(_, label_key, _)| tr(*label_key).to_string()).collect(),
+        );
+        self.drop_down(id!(guest_access_dropdown)).set_labels(
+            cx,
+            GUEST_ACCESS_OPTIONS.iter().map(|(_, label_key, _)| tr(*label_key).to_string()).collect(),
+        );
+
+        let Some(room) = get_client().and_then(|client| client.get_room(room_id)) else { return };
+        let history_visibility = room.history_visibility_or_default();
+        let history_index = HISTORY_VISIBILITY_OPTIONS.iter()
+            .position(|(visibility, _, _)| *visibility == history_visibility)
+            .unwrap_or(1);
+        self.drop_down(id!(history_visibility_dropdown)).set_selected_item(cx, history_index);
+        self.label(id!(history_visibility_description)).set_text(cx, tr(HISTORY_VISIBILITY_OPTIONS[history_index].2));
+
+        let guest_access = room.guest_access();
+        let guest_index = GUEST_ACCESS_OPTIONS.iter()
+            .position(|(access, _, _)| *access == guest_access)
+            .unwrap_or(1);
+        self.drop_down(id!(guest_access_dropdown)).set_selected_item(cx, guest_index);
+        self.label(id!(guest_access_description)).set_text(cx, tr(GUEST_ACCESS_OPTIONS[guest_index].2));
+    }
+
+    /// Shows the given status of upgrading this dashboard's room,
+    /// disabling the upgrade button while an upgrade is in progress.
+    fn set_upgrade_status(&mut self, cx: &mut Cx, is_upgrading: bool, status: &str) {
+        self.is_upgrading = is_upgrading;
+        self.label(id!(room_upgrade_status)).set_text(cx, status);
+        self.button(id!(upgrade_button)).set_enabled(cx, !is_upgrading);
+        self.redraw(cx);
+    }
+
+    /// Shows the policy lists that the given room is subscribed to.
+    fn show_policy_lists(&mut self, cx: &mut Cx, room_id: &OwnedRoomId) {
+        let lists = policy_lists::subscribed_lists(room_id);
+        let text = if lists.is_empty() {
+            tr("policy.none").to_string()
+        } else {
+            lists.iter().map(|list| list.as_str()).collect::<Vec<_>>().join("\n")
+        };
+        self.label(id!(policy_lists_label)).set_text(cx, &text);
+        self.redraw(cx);
+    }
+}
+
+impl ModerationDashboardRef {
+    /// See [`ModerationDashboard::initialize()`].
+    pub fn initialize(
+        &self,
+        cx: &mut Cx,
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+        powers: ModeratorPowers,
+        summary: ModerationSummary,
+    ) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.initialize(cx, room_id, room_name, powers, summary);
+        }
+    }
+}
+
+
+/// A list of recent events, each shown with the moderation actions that apply to it.
+#[derive(Live, LiveHook, Widget)]
+pub struct ModerationEntryList {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    #[rust] room_id: Option<OwnedRoomId>,
+    #[rust] children: Vec<(WidgetRef, ModerationEntry)>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for ModerationEntryList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (row, _) in &self.children {
+            row.handle_event(cx, event, scope);
+        }
+        let Event::Actions(actions) = event else { return };
+        let Some(room_id) = self.room_id.clone() else { return };
+        for (row, entry) in &self.children {
+            let kick_button = row.button(id!(kick_button));
+            let ban_button = row.button(id!(ban_button));
+            let redact_button = row.button(id!(redact_button));
+            if kick_button.clicked(actions) {
+                submit_async_request(MatrixRequest::KickUser {
+                    room_id: room_id.clone(),
+                    user_id: entry.user_id.clone(),
+                    reason: None,
+                });
+                kick_button.set_enabled(cx, false);
+            }
+            if ban_button.clicked(actions) {
+                submit_async_request(MatrixRequest::BanUser {
+                    room_id: room_id.clone(),
+                    user_id: entry.user_id.clone(),
+                    reason: None,
+                });
+                ban_button.set_enabled(cx, false);
+                kick_button.set_enabled(cx, false);
+            }
+            if redact_button.clicked(actions) {
+                submit_async_request(MatrixRequest::RedactMessage {
+                    room_id: room_id.clone(),
+                    timeline_event_id: entry.timeline_event_id.clone(),
+                    reason: None,
+                });
+                redact_button.set_enabled(cx, false);
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (row, _) in &self.children {
+            let _ = row.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl ModerationEntryListRef {
+    /// Replaces the displayed entries with the given ones.
+    pub fn set_entries(
+        &self,
+        cx: &mut Cx,
+        room_id: OwnedRoomId,
+        powers: ModeratorPowers,
+        entries: Vec<ModerationEntry>,
+    ) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.children.clear();
+        for entry in entries {
+            let row = WidgetRef::new_from_ptr(cx, inner.item);
+            let time = utils::relative_format(&entry.timestamp).unwrap_or_default();
+            let text = match entry.kind {
+                ModerationEntryKind::Join => format!("{} ({})", entry.username, entry.user_id),
+                ModerationEntryKind::Message | ModerationEntryKind::Redaction => {
+                    format!("{}: {}", entry.username, entry.description)
+                }
+            };
+            row.label(id!(text)).set_text(cx, &format!("{time} · {text}"));
+
+            let kick_button = row.button(id!(kick_button));
+            kick_button.set_text(cx, tr("moderation.kick"));
+            kick_button.set_visible(cx, powers.can_kick && entry.kind == ModerationEntryKind::Join);
+            let ban_button = row.button(id!(ban_button));
+            ban_button.set_text(cx, tr("moderation.ban"));
+            ban_button.set_visible(cx, powers.can_ban);
+            let redact_button = row.button(id!(redact_button));
+            redact_button.set_text(cx, tr("moderation.remove"));
+            redact_button.set_visible(cx, powers.can_redact && entry.kind == ModerationEntryKind::Message);
+
+            inner.children.push((row, entry));
+        }
+        inner.room_id = Some(room_id);
+        inner.redraw(cx);
+    }
+}
@@ -127,6 +127,16 @@ live_design! {
                 text: "Reply"
             }
 
+            quote_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_QUOTE)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+                text: "Quote"
+            }
+
             divider_after_react_reply = <LineH> {
                 margin: {top: 3, bottom: 3}
                 draw_bg: {color: (COLOR_DIVIDER_DARK)}
@@ -207,6 +217,16 @@ live_design! {
                 text: "Jump to Related Event"
             }
 
+            move_read_marker_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_CHECKMARK)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3} }
+                text: "Mark Read Up to Here"
+            }
+
             divider_before_report_delete = <LineH> {
                 margin: {top: 3, bottom: 3}
                 draw_bg: {color: (COLOR_DIVIDER_DARK)}
@@ -423,6 +443,14 @@ impl WidgetMatchEvent for NewMessageContextMenu {
             );
             close_menu = true;
         }
+        else if self.button(id!(quote_button)).clicked(actions) {
+            cx.widget_action(
+                details.room_screen_widget_uid,
+                &scope.path,
+                MessageAction::Quote(details.clone()),
+            );
+            close_menu = true;
+        }
         else if self.button(id!(edit_message_button)).clicked(actions) {
             cx.widget_action(
                 details.room_screen_widget_uid,
@@ -487,6 +515,14 @@ impl WidgetMatchEvent for NewMessageContextMenu {
             );
             close_menu = true;
         }
+        else if self.button(id!(move_read_marker_button)).clicked(actions) {
+            cx.widget_action(
+                details.room_screen_widget_uid,
+                &scope.path,
+                MessageAction::MoveReadMarker(details.clone()),
+            );
+            close_menu = true;
+        }
         // else if self.button(id!(report_button)).clicked(actions) {
         //     cx.widget_action(
         //         details.room_screen_widget_uid,
@@ -547,6 +583,7 @@ impl NewMessageContextMenu {
 
         let react_button = self.view.button(id!(react_button));
         let reply_button = self.view.button(id!(reply_button));
+        let quote_button = self.view.button(id!(quote_button));
         let edit_button = self.view.button(id!(edit_message_button));
         let pin_button = self.view.button(id!(pin_button));
         let copy_text_button = self.view.button(id!(copy_text_button));
@@ -554,15 +591,17 @@ impl NewMessageContextMenu {
         let copy_link_button = self.view.button(id!(copy_link_to_message_button));
         let view_source_button = self.view.button(id!(view_source_button));
         let jump_to_related_button = self.view.button(id!(jump_to_related_button));
+        let move_read_marker_button = self.view.button(id!(move_read_marker_button));
         // let report_button = self.view.button(id!(report_button));
         let delete_button = self.view.button(id!(delete_button));
 
         // Determine which buttons should be shown.
         // Note that some buttons are always enabled:
-        // `copy_text_button`, `copy_link_to_message_button`, and `view_source_button`
+        // `quote_button`, `copy_text_button`, `copy_link_to_message_button`, and `view_source_button`
         let show_react = details.abilities.contains(MessageAbilities::CanReact);
         let show_reply_to = details.abilities.contains(MessageAbilities::CanReplyTo);
-        let show_divider_after_react_reply = show_react || show_reply_to;
+        let show_quote = true;
+        let show_divider_after_react_reply = show_react || show_reply_to || show_quote;
         let show_edit = details.abilities.contains(MessageAbilities::CanEdit);
         let show_pin: bool;
         let show_copy_text = true;
@@ -570,6 +609,8 @@ impl NewMessageContextMenu {
         let show_copy_link = true;
         let show_view_source = true;
         let show_jump_to_related = details.related_event_id.is_some();
+        // Unsent local messages can't be marked as read.
+        let show_move_read_marker = details.event_id.is_some();
         // let show_report = true;
         let show_delete = details.abilities.contains(MessageAbilities::CanDelete);
         let show_divider_before_report_delete = show_delete; // || show_report;
@@ -592,6 +633,7 @@ impl NewMessageContextMenu {
         pin_button.set_visible(cx, show_pin);
         copy_html_button.set_visible(cx, show_copy_html);
         jump_to_related_button.set_visible(cx, show_jump_to_related);
+        move_read_marker_button.set_visible(cx, show_move_read_marker);
         self.view.view(id!(divider_before_report_delete)).set_visible(cx, show_divider_before_report_delete);
         // report_button.set_visible(cx, show_report);
         delete_button.set_visible(cx, show_delete);
@@ -599,6 +641,7 @@ impl NewMessageContextMenu {
         // Reset the hover state of each button.
         react_button.reset_hover(cx);
         reply_button.reset_hover(cx);
+        quote_button.reset_hover(cx);
         edit_button.reset_hover(cx);
         pin_button.reset_hover(cx);
         copy_text_button.reset_hover(cx);
@@ -606,6 +649,7 @@ impl NewMessageContextMenu {
         copy_link_button.reset_hover(cx);
         view_source_button.reset_hover(cx);
         jump_to_related_button.reset_hover(cx);
+        move_read_marker_button.reset_hover(cx);
         // report_button.reset_hover(cx);
         delete_button.reset_hover(cx);
 
@@ -618,6 +662,7 @@ impl NewMessageContextMenu {
         let num_visible_buttons = 
             show_react as u8
             + show_reply_to as u8
+            + show_quote as u8
             + show_edit as u8
             + show_pin as u8
             + show_copy_text as u8
@@ -625,6 +670,7 @@ impl NewMessageContextMenu {
             + show_copy_link as u8
             + show_view_source as u8
             + show_jump_to_related as u8
+            + show_move_read_marker as u8
             // + show_report as u8
             + show_delete as u8;
 
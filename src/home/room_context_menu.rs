@@ -0,0 +1,402 @@
+//! A context menu of quick actions that appears when the user right-clicks,
+//! long-presses, or swipes left on a room in the rooms list.
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::{OwnedRoomAliasId, OwnedRoomId};
+
+use crate::{
+    home::{leave_room_modal::LeaveRoomModalAction, room_wallpaper_modal::RoomWallpaperModalAction, share_room_modal::ShareRoomModalAction},
+    i18n::tr,
+    settings::app_settings::{app_settings, update_app_settings},
+    shared::popup_list::enqueue_popup_notification,
+    sliding_sync::{submit_async_request, MatrixRequest},
+};
+
+const BUTTON_HEIGHT: f64 = 30.0; // KEEP IN SYNC WITH BUTTON_HEIGHT BELOW
+const MENU_WIDTH: f64 = 215.0;   // KEEP IN SYNC WITH MENU_WIDTH BELOW
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::helpers::*;
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::*;
+
+    BUTTON_HEIGHT = 30  // KEEP IN SYNC WITH BUTTON_HEIGHT ABOVE
+    MENU_WIDTH = 215    // KEEP IN SYNC WITH MENU_WIDTH ABOVE
+
+    pub RoomContextMenu = {{RoomContextMenu}} {
+        visible: false,
+        flow: Overlay,
+        width: Fill,
+        height: Fill,
+        cursor: Default,
+        // Align to top-left such that our coordinate adjustment
+        // when showing this menu pane will work correctly.
+        align: {x: 0, y: 0}
+
+        // Show a slightly darkened translucent background to make the menu stand out.
+        show_bg: true
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                return vec4(0., 0., 0., 0.3)
+            }
+        }
+
+        main_content = <RoundedView> {
+            flow: Down
+            width: (MENU_WIDTH),
+            height: Fit,
+            padding: 15
+            spacing: 2
+            align: {x: 0, y: 0}
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 5.0
+                border_width: 0.5
+                border_color: #888
+            }
+
+            mark_read_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_CHECKMARK)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+                text: "" // set dynamically
+            }
+
+            mute_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_BLOCK_USER)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+                text: "" // set dynamically
+            }
+
+            read_receipts_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_VIEW_SOURCE)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+                text: "" // set dynamically to "Hide Read Receipts Here" or "Share Read Receipts Here"
+            }
+
+            favorite_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_PIN)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+                text: "" // set dynamically to "Add to Favorites" or "Remove from Favorites"
+            }
+
+            copy_room_link_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_LINK)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+                text: "" // set dynamically
+            }
+
+            share_room_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_COPY)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+                text: "" // set dynamically
+            }
+
+            wallpaper_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_EDIT)
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+                text: "" // set dynamically
+            }
+
+            divider_before_leave = <LineH> {
+                margin: {top: 3, bottom: 3}
+                draw_bg: {color: (COLOR_DIVIDER_DARK)}
+                width: Fill,
+            }
+
+            leave_button = <RobrixIconButton> {
+                height: (BUTTON_HEIGHT)
+                width: Fill,
+                draw_icon: {
+                    svg_file: (ICON_CLOSE)
+                    color: (COLOR_DANGER_RED),
+                }
+                icon_walk: {width: 16, height: 16, margin: {right: 3}}
+
+                draw_bg: {
+                    border_color: (COLOR_DANGER_RED),
+                    color: #fff0f0
+                }
+                text: "" // set dynamically
+                draw_text:{
+                    color: (COLOR_DANGER_RED),
+                }
+            }
+        }
+    }
+}
+
+/// Details about the room that define its context menu content.
+#[derive(Clone, Debug)]
+pub struct RoomContextMenuDetails {
+    pub room_id: OwnedRoomId,
+    pub room_name: Option<String>,
+    /// The canonical alias of the room, which is preferred over its ID
+    /// when copying a link to the room.
+    pub canonical_alias: Option<OwnedRoomAliasId>,
+    /// Whether the room currently has any unread messages.
+    pub has_unread: bool,
+    /// Whether the room is currently tagged as a favorite.
+    pub is_favourite: bool,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct RoomContextMenu {
+    #[deref] view: View,
+    #[rust] details: Option<RoomContextMenuDetails>,
+}
+
+impl Widget for RoomContextMenu {
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.details.is_none() {
+            self.visible = false;
+        };
+
+        self.view.draw_walk(cx, scope, walk)
+    }
+
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if !self.visible { return; }
+        self.view.handle_event(cx, event, scope);
+
+        let area = self.view.area();
+
+        // Close the menu if:
+        // 1. The back navigational gesture/action occurs (e.g., Back on Android),
+        // 2. The escape key is pressed if this menu has key focus,
+        // 3. The user clicks/touches outside the main_content view area.
+        // 4. The user scrolls anywhere.
+        let close_menu = matches!(event, Event::BackPressed)                    // 1
+        || match event.hits_with_capture_overload(cx, area, true) {
+            Hit::KeyUp(key) => key.key_code == KeyCode::Escape,                 // 2
+            Hit::FingerDown(_) => {
+                cx.set_key_focus(area);
+                false
+            }
+            Hit::FingerUp(fue) if fue.is_over => {
+                !self.view(id!(main_content)).area().rect(cx).contains(fue.abs) // 3
+            }
+            Hit::FingerScroll(_) => true,                                       // 4
+            _ => false,
+        };
+        if close_menu {
+            self.close(cx);
+            return;
+        }
+
+        self.widget_match_event(cx, event, scope);
+    }
+}
+
+impl WidgetMatchEvent for RoomContextMenu {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        let Some(details) = self.details.as_ref() else { return };
+        let room_id = details.room_id.clone();
+        let mut close_menu = false;
+
+        if self.button(id!(mark_read_button)).clicked(actions) {
+            submit_async_request(MatrixRequest::MarkRoomAsRead { room_id });
+            close_menu = true;
+        }
+        else if self.button(id!(mute_button)).clicked(actions) {
+            submit_async_request(MatrixRequest::ToggleRoomMute { room_id });
+            close_menu = true;
+        }
+        else if self.button(id!(read_receipts_button)).clicked(actions) {
+            let send = !app_settings().send_read_receipts_in(&room_id);
+            update_app_settings(|s| s.set_send_read_receipts_in(room_id, send));
+            enqueue_popup_notification(tr(if send {
+                "room_menu.read_receipts_shared"
+            } else {
+                "room_menu.read_receipts_hidden"
+            }).to_string());
+            close_menu = true;
+        }
+        else if self.button(id!(favorite_button)).clicked(actions) {
+            submit_async_request(MatrixRequest::SetRoomFavourite {
+                room_id,
+                is_favourite: !details.is_favourite,
+            });
+            close_menu = true;
+        }
+        else if self.button(id!(copy_room_link_button)).clicked(actions) {
+            let matrix_to_uri = match details.canonical_alias.as_ref() {
+                Some(alias) => alias.matrix_to_uri(),
+                None => details.room_id.matrix_to_uri(),
+            };
+            cx.copy_to_clipboard(&matrix_to_uri.to_string());
+            enqueue_popup_notification(tr("room_menu.link_copied").to_string());
+            close_menu = true;
+        }
+        else if self.button(id!(share_room_button)).clicked(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                ShareRoomModalAction::Open {
+                    room_id,
+                    room_name: details.room_name.clone(),
+                    canonical_alias: details.canonical_alias.clone(),
+                },
+            );
+            close_menu = true;
+        }
+        else if self.button(id!(wallpaper_button)).clicked(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                RoomWallpaperModalAction::Open {
+                    room_id,
+                    room_name: details.room_name.clone(),
+                },
+            );
+            close_menu = true;
+        }
+        else if self.button(id!(leave_button)).clicked(actions) {
+            cx.widget_action(
+                self.widget_uid(),
+                &scope.path,
+                LeaveRoomModalAction::Open {
+                    room_id,
+                    room_name: details.room_name.clone(),
+                },
+            );
+            close_menu = true;
+        }
+
+        if close_menu {
+            self.close(cx);
+        }
+    }
+}
+
+impl RoomContextMenu {
+    /// Returns `true` if this menu is currently being shown.
+    pub fn is_currently_shown(&self, _cx: &mut Cx) -> bool {
+        self.visible
+    }
+
+    /// Shows this context menu with the given room details.
+    ///
+    /// Returns the expected (approximate) dimensions of the context menu,
+    /// which can be used to proactively reposition it such that it fits on screen.
+    pub fn show(&mut self, cx: &mut Cx, details: RoomContextMenuDetails) -> DVec2 {
+        self.details = Some(details);
+        self.visible = true;
+        cx.set_key_focus(self.view.area());
+        let height = self.set_button_visibility(cx);
+        dvec2(MENU_WIDTH, height)
+    }
+
+    /// Sets up all of the buttons based this context menu's inner details.
+    ///
+    /// Returns the total height of all visible items.
+    fn set_button_visibility(&mut self, cx: &mut Cx) -> f64 {
+        let Some(details) = self.details.as_ref() else { return 0.0 };
+
+        let mark_read_button = self.view.button(id!(mark_read_button));
+        let mute_button = self.view.button(id!(mute_button));
+        let read_receipts_button = self.view.button(id!(read_receipts_button));
+        let favorite_button = self.view.button(id!(favorite_button));
+        let copy_room_link_button = self.view.button(id!(copy_room_link_button));
+        let share_room_button = self.view.button(id!(share_room_button));
+        let wallpaper_button = self.view.button(id!(wallpaper_button));
+        let leave_button = self.view.button(id!(leave_button));
+
+        // Only rooms with unread messages can be marked as read;
+        // all other buttons are always shown.
+        let show_mark_read = details.has_unread;
+        mark_read_button.set_visible(cx, show_mark_read);
+
+        mark_read_button.set_text(cx, tr("room_menu.mark_read"));
+        mute_button.set_text(cx, tr("room_menu.toggle_mute"));
+        read_receipts_button.set_text(cx, if app_settings().send_read_receipts_in(&details.room_id) {
+            tr("room_menu.hide_read_receipts")
+        } else {
+            tr("room_menu.share_read_receipts")
+        });
+        favorite_button.set_text(cx, if details.is_favourite {
+            tr("room_menu.unfavorite")
+        } else {
+            tr("room_menu.favorite")
+        });
+        copy_room_link_button.set_text(cx, tr("room_menu.copy_link"));
+        share_room_button.set_text(cx, tr("room_menu.share"));
+        wallpaper_button.set_text(cx, tr("room_menu.wallpaper"));
+        leave_button.set_text(cx, tr("room_menu.leave"));
+
+        // Reset the hover state of each button.
+        mark_read_button.reset_hover(cx);
+        mute_button.reset_hover(cx);
+        read_receipts_button.reset_hover(cx);
+        favorite_button.reset_hover(cx);
+        copy_room_link_button.reset_hover(cx);
+        share_room_button.reset_hover(cx);
+        wallpaper_button.reset_hover(cx);
+        leave_button.reset_hover(cx);
+
+        self.redraw(cx);
+
+        let num_visible_buttons = 7 + show_mark_read as u8;
+
+        // Calculate and return the total expected height:
+        (num_visible_buttons as f64 * (BUTTON_HEIGHT + 2.0 + 2.0))
+            + 10.0  // the divider before the leave button
+            + 20.0  // top and bottom padding
+            + 1.0   // top and bottom border
+            - 4.0   // no 2.0 spacers at the top and bottom
+    }
+
+    fn close(&mut self, cx: &mut Cx) {
+        self.visible = false;
+        self.details = None;
+        cx.revert_key_focus();
+        self.redraw(cx);
+    }
+}
+
+impl RoomContextMenuRef {
+    /// See [`RoomContextMenu::is_currently_shown()`].
+    pub fn is_currently_shown(&self, cx: &mut Cx) -> bool {
+        let Some(inner) = self.borrow() else { return false };
+        inner.is_currently_shown(cx)
+    }
+
+    /// See [`RoomContextMenu::show()`].
+    pub fn show(&self, cx: &mut Cx, details: RoomContextMenuDetails) -> DVec2 {
+        let Some(mut inner) = self.borrow_mut() else { return DVec2::default() };
+        inner.show(cx, details)
+    }
+}
@@ -0,0 +1,447 @@
+//! Named room filters that the user can save, shown as tabs above the rooms list.
+//!
+//! Selecting a tab restricts the rooms list to the rooms that match that tab's filter,
+//! in addition to any search keywords. Filters are re-applied as rooms are updated,
+//! e.g., a room disappears from an "unread only" tab once it has been read.
+
+use makepad_widgets::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::tr,
+    settings::app_settings::{app_settings, update_app_settings},
+    shared::popup_list::enqueue_popup_notification,
+};
+
+use super::rooms_list::RoomsListEntry;
+
+/// A named filter that determines which rooms are shown in the rooms list.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedRoomFilter {
+    /// The name shown on this filter's tab.
+    pub name: String,
+    /// Only show rooms with unread messages.
+    pub unread_only: bool,
+    /// Only show direct message rooms.
+    pub dms_only: bool,
+    /// Hide rooms whose notifications have been muted.
+    pub hide_muted: bool,
+}
+
+impl SavedRoomFilter {
+    /// Returns `true` if the given room should be shown when this filter is selected.
+    pub fn matches(&self, room: &RoomsListEntry) -> bool {
+        (!self.unread_only || room.num_unread_messages > 0)
+            && (!self.dms_only || room.direct_user_id.is_some())
+            && (!self.hide_muted || !room.is_muted)
+    }
+}
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    FilterTabButton = <RobrixIconButton> {
+        padding: {top: 6, bottom: 6, left: 10, right: 10}
+        draw_bg: {
+            instance selected: 0.0
+            color: (COLOR_SECONDARY)
+            radius: 10.0
+
+            fn get_color(self) -> vec4 {
+                return mix(
+                    mix(self.color, mix(self.color, self.color_hover, 0.2), self.hover),
+                    (COLOR_SELECTED_PRIMARY),
+                    self.selected
+                )
+            }
+        }
+        draw_text: {
+            instance selected: 0.0
+            text_style: <REGULAR_TEXT>{font_size: 9.5}
+
+            fn get_color(self) -> vec4 {
+                return mix((COLOR_TEXT), #fff, self.selected)
+            }
+        }
+    }
+
+    FilterCheckBoxRow = <View> {
+        width: Fill, height: Fit
+        flow: Right
+        align: {y: 0.5}
+        checkbox = <CheckBox> { text: "" }
+        label = <Label> {
+            width: Fill, height: Fit
+            draw_text: {
+                wrap: Word
+                color: (COLOR_TEXT)
+                text_style: <REGULAR_TEXT>{font_size: 9.5}
+            }
+        }
+    }
+
+    RoomFilterTabList = {{RoomFilterTabList}} {
+        width: Fill, height: Fit
+        flow: RightWrap
+        spacing: 5
+        item: <FilterTabButton> {}
+    }
+
+    pub RoomFilterTabs = {{RoomFilterTabs}} {
+        width: Fill, height: Fit
+        flow: Down
+        spacing: 5
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Right
+            align: {y: 0.5}
+            spacing: 5
+
+            tabs = <RoomFilterTabList> {}
+
+            edit_filter_button = <RobrixIconButton> {
+                visible: false
+                padding: 6
+                draw_icon: {
+                    svg_file: (ICON_EDIT)
+                    color: (COLOR_TEXT)
+                }
+                icon_walk: {width: 12, height: 12}
+            }
+
+            add_filter_button = <RobrixIconButton> {
+                padding: 6
+                draw_icon: {
+                    svg_file: (ICON_ADD)
+                    color: (COLOR_TEXT)
+                }
+                icon_walk: {width: 12, height: 12}
+            }
+        }
+
+        editor = <RoundedView> {
+            visible: false
+            width: Fill, height: Fit
+            flow: Down
+            spacing: 5
+            padding: 10
+            show_bg: true
+            draw_bg: {
+                color: (COLOR_SECONDARY)
+                radius: 4.0
+            }
+
+            name_input = <RobrixTextInput> {
+                width: Fill, height: Fit
+                padding: 6
+                empty_message: "Filter name"
+            }
+            unread_only_row = <FilterCheckBoxRow> {}
+            dms_only_row = <FilterCheckBoxRow> {}
+            hide_muted_row = <FilterCheckBoxRow> {}
+
+            <View> {
+                width: Fill, height: Fit
+                flow: Right
+                align: {x: 1.0, y: 0.5}
+                spacing: 5
+
+                delete_filter_button = <RobrixIconButton> {
+                    padding: {top: 6, bottom: 6, left: 10, right: 10}
+                    draw_icon: {
+                        svg_file: (ICON_TRASH)
+                        color: (COLOR_DANGER_RED),
+                    }
+                    icon_walk: {width: 12, height: 12}
+                    draw_text: {
+                        color: (COLOR_DANGER_RED),
+                    }
+                }
+                cancel_filter_button = <RobrixIconButton> {
+                    padding: {top: 6, bottom: 6, left: 10, right: 10}
+                }
+                save_filter_button = <RobrixIconButton> {
+                    padding: {top: 6, bottom: 6, left: 10, right: 10}
+                    draw_icon: {
+                        svg_file: (ICON_CHECKMARK)
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                    icon_walk: {width: 12, height: 12}
+                    draw_text: {
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Actions emitted by the [`RoomFilterTabs`] widget.
+#[derive(Clone, Debug, DefaultNone)]
+pub enum RoomFilterTabsAction {
+    /// The given filter was selected, or all rooms should be shown if `None`.
+    Selected(Option<SavedRoomFilter>),
+    None,
+}
+
+/// Actions emitted by the [`RoomFilterTabList`] widget.
+#[derive(Clone, Debug, DefaultNone)]
+enum RoomFilterTabListAction {
+    /// The tab at the given index was clicked, in which index 0 is the "All" tab.
+    Clicked(usize),
+    None,
+}
+
+/// A list of tab buttons: the "All" tab, followed by one for each saved filter.
+#[derive(Live, LiveHook, Widget)]
+pub struct RoomFilterTabList {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    #[rust] children: Vec<ButtonRef>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for RoomFilterTabList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for button in &self.children {
+            button.handle_event(cx, event, scope);
+        }
+        if let Event::Actions(actions) = event {
+            if let Some(index) = self.children.iter().position(|button| button.clicked(actions)) {
+                cx.widget_action(self.widget_uid(), &scope.path, RoomFilterTabListAction::Clicked(index));
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for button in &self.children {
+            let _ = button.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl RoomFilterTabListRef {
+    /// Replaces the displayed tabs with the given tab names, highlighting the selected one.
+    fn set_tabs(&self, cx: &mut Cx, names: &[String], selected: usize) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.children.clear();
+        for (index, name) in names.iter().enumerate() {
+            let button = WidgetRef::new_from_ptr(cx, inner.item).as_button();
+            let selected = if index == selected { 1.0 } else { 0.0 };
+            button.set_text(cx, name);
+            button.apply_over(cx, live! {
+                draw_bg: { selected: (selected) }
+                draw_text: { selected: (selected) }
+            });
+            inner.children.push(button);
+        }
+        inner.redraw(cx);
+    }
+
+    /// Returns the index of the tab that was clicked, if any.
+    fn clicked(&self, actions: &Actions) -> Option<usize> {
+        match actions.find_widget_action(self.widget_uid())?.cast() {
+            RoomFilterTabListAction::Clicked(index) => Some(index),
+            RoomFilterTabListAction::None => None,
+        }
+    }
+}
+
+/// The tabs above the rooms list for selecting, adding, and editing saved room filters.
+#[derive(Live, LiveHook, Widget)]
+pub struct RoomFilterTabs {
+    #[deref] view: View,
+    /// The index of the selected filter within the saved room filters, or `None` for all rooms.
+    #[rust] selected: Option<usize>,
+    /// The index of the saved filter being edited, if the editor is showing an existing filter.
+    #[rust] editing: Option<usize>,
+    /// The filter being edited in the editor, which is only saved once the user clicks save.
+    #[rust] draft: SavedRoomFilter,
+    /// Whether the tabs have been populated from the saved room filters.
+    #[rust] initialized: bool,
+}
+
+impl Widget for RoomFilterTabs {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if !self.initialized {
+            self.initialized = true;
+            self.refresh_tabs(cx);
+        }
+        self.view(id!(unread_only_row)).label(id!(label)).set_text(cx, tr("room_filter.unread_only"));
+        self.view(id!(dms_only_row)).label(id!(label)).set_text(cx, tr("room_filter.dms_only"));
+        self.view(id!(hide_muted_row)).label(id!(label)).set_text(cx, tr("room_filter.hide_muted"));
+        self.button(id!(delete_filter_button)).set_text(cx, tr("room_filter.delete"));
+        self.button(id!(cancel_filter_button)).set_text(cx, tr("room_filter.cancel"));
+        self.button(id!(save_filter_button)).set_text(cx, tr("room_filter.save"));
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for RoomFilterTabs {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if let Some(index) = self.room_filter_tab_list(id!(tabs)).clicked(actions) {
+            self.select(cx, scope, index.checked_sub(1));
+        }
+
+        if self.button(id!(add_filter_button)).clicked(actions) {
+            self.show_editor(cx, None);
+        }
+        if self.button(id!(edit_filter_button)).clicked(actions) {
+            self.show_editor(cx, self.selected);
+        }
+        if self.button(id!(cancel_filter_button)).clicked(actions) {
+            self.hide_editor(cx);
+        }
+        if let Some(unread_only) = self.view(id!(unread_only_row)).check_box(id!(checkbox)).changed(actions) {
+            self.draft.unread_only = unread_only;
+        }
+        if let Some(dms_only) = self.view(id!(dms_only_row)).check_box(id!(checkbox)).changed(actions) {
+            self.draft.dms_only = dms_only;
+        }
+        if let Some(hide_muted) = self.view(id!(hide_muted_row)).check_box(id!(checkbox)).changed(actions) {
+            self.draft.hide_muted = hide_muted;
+        }
+
+        if self.button(id!(delete_filter_button)).clicked(actions) {
+            if let Some(index) = self.editing {
+                update_app_settings(|s| {
+                    if index < s.saved_room_filters.len() {
+                        s.saved_room_filters.remove(index);
+                    }
+                });
+                self.hide_editor(cx);
+                self.select(cx, scope, None);
+            }
+        }
+
+        if self.button(id!(save_filter_button)).clicked(actions) {
+            let name = self.text_input(id!(name_input)).text().trim().to_owned();
+            if name.is_empty() {
+                enqueue_popup_notification(tr("room_filter.empty_name").to_string());
+                return;
+            }
+            let filter = SavedRoomFilter { name, ..self.draft.clone() };
+            let editing = self.editing;
+            let mut saved_index = 0;
+            update_app_settings(|s| {
+                saved_index = match editing.filter(|i| *i < s.saved_room_filters.len()) {
+                    Some(index) => {
+                        s.saved_room_filters[index] = filter;
+                        index
+                    }
+                    None => {
+                        s.saved_room_filters.push(filter);
+                        s.saved_room_filters.len() - 1
+                    }
+                };
+            });
+            self.hide_editor(cx);
+            self.select(cx, scope, Some(saved_index));
+        }
+    }
+}
+
+impl RoomFilterTabs {
+    /// Re-populates the tabs from the saved room filters.
+    fn refresh_tabs(&mut self, cx: &mut Cx) {
+        let names: Vec<String> = std::iter::once(tr("room_filter.all").to_owned())
+            .chain(app_settings().saved_room_filters.iter().map(|f| f.name.clone()))
+            .collect();
+        if self.selected.is_some_and(|i| i + 1 >= names.len()) {
+            self.selected = None;
+        }
+        self.room_filter_tab_list(id!(tabs)).set_tabs(cx, &names, self.selected.map_or(0, |i| i + 1));
+        self.button(id!(edit_filter_button)).set_visible(cx, self.selected.is_some());
+        self.redraw(cx);
+    }
+
+    /// Selects the saved filter at the given index (or all rooms if `None`)
+    /// and emits a [`RoomFilterTabsAction::Selected`] action with that filter.
+    fn select(&mut self, cx: &mut Cx, scope: &mut Scope, index: Option<usize>) {
+        self.selected = index;
+        self.refresh_tabs(cx);
+        let filter = self.selected.and_then(|i| app_settings().saved_room_filters.get(i).cloned());
+        cx.widget_action(self.widget_uid(), &scope.path, RoomFilterTabsAction::Selected(filter));
+    }
+
+    /// Shows the filter editor, pre-filled with the saved filter at the given index (if any).
+    fn show_editor(&mut self, cx: &mut Cx, index: Option<usize>) {
+        let filter = index
+            .and_then(|i| app_settings().saved_room_filters.get(i).cloned())
+            .unwrap_or_default();
+        self.editing = index;
+        self.text_input(id!(name_input)).set_text(cx, &filter.name);
+        self.view(id!(unread_only_row)).check_box(id!(checkbox)).set_selected(cx, filter.unread_only);
+        self.view(id!(dms_only_row)).check_box(id!(checkbox)).set_selected(cx, filter.dms_only);
+        self.view(id!(hide_muted_row)).check_box(id!(checkbox)).set_selected(cx, filter.hide_muted);
+        self.button(id!(delete_filter_button)).set_visible(cx, index.is_some());
+        self.draft = filter;
+        self.view(id!(editor)).set_visible(cx, true);
+        self.redraw(cx);
+    }
+
+    fn hide_editor(&mut self, cx: &mut Cx) {
+        self.editing = None;
+        self.view(id!(editor)).set_visible(cx, false);
+        self.redraw(cx);
+    }
+}
+
+#[cfg(test)]
+mod tests_room_filter_tabs {
+    use matrix_sdk::ruma::{owned_room_id, owned_user_id};
+
+    use super::*;
+    use crate::home::rooms_list::RoomPreviewAvatar;
+
+    fn room(num_unread_messages: u64, is_dm: bool, is_muted: bool) -> RoomsListEntry {
+        RoomsListEntry {
+            room_id: owned_room_id!("!room:example.org"),
+            room_name: None,
+            num_unread_messages,
+            num_unread_mentions: 0,
+            canonical_alias: None,
+            alt_aliases: Vec::new(),
+            tags: None,
+            latest: None,
+            avatar: RoomPreviewAvatar::default(),
+            has_been_paginated: false,
+            has_requested_avatar: false,
+            is_selected: false,
+            direct_user_id: is_dm.then(|| owned_user_id!("@alice:example.org")),
+            is_muted,
+            typing_users: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_saved_room_filter_matches() {
+        let all = SavedRoomFilter::default();
+        assert!(all.matches(&room(0, false, true)));
+
+        let unread_dms = SavedRoomFilter { unread_only: true, dms_only: true, ..Default::default() };
+        assert!(unread_dms.matches(&room(3, true, false)));
+        assert!(!unread_dms.matches(&room(0, true, false)));
+        assert!(!unread_dms.matches(&room(3, false, false)));
+
+        let unmuted = SavedRoomFilter { hide_muted: true, ..Default::default() };
+        assert!(unmuted.matches(&room(0, false, false)));
+        assert!(!unmuted.matches(&room(0, false, true)));
+    }
+}
@@ -0,0 +1,231 @@
+//! A popover that appears when the user hovers over a room in the desktop rooms list,
+//! showing the room's topic, member count, and latest messages without opening the room.
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::OwnedRoomId;
+
+use crate::{
+    i18n::{tr, tr_args},
+    shared::html_or_plaintext::HtmlOrPlaintextWidgetExt,
+    sliding_sync::{submit_async_request, MatrixRequest},
+};
+
+/// The maximum number of recent messages shown in the popover.
+pub const NUM_HOVER_PREVIEW_MESSAGES: usize = 3;
+
+const POPOVER_WIDTH: f64 = 300.0;  // KEEP IN SYNC WITH POPOVER_WIDTH BELOW
+/// The approximate height of the popover, used to keep it within the window's bounds.
+const POPOVER_HEIGHT: f64 = 200.0;
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::helpers::*;
+    use crate::shared::styles::*;
+    use crate::shared::html_or_plaintext::HtmlOrPlaintext;
+
+    POPOVER_WIDTH = 300  // KEEP IN SYNC WITH POPOVER_WIDTH ABOVE
+
+    HoverPreviewMessage = <HtmlOrPlaintext> {
+        visible: false
+        html_view = { html = {
+            font_size: 9.0,
+            draw_normal:      { text_style: { font_size: 9.0 } },
+            draw_italic:      { text_style: { font_size: 9.0 } },
+            draw_bold:        { text_style: { font_size: 9.0 } },
+            draw_bold_italic: { text_style: { font_size: 9.0 } },
+            draw_fixed:       { text_style: { font_size: 9.0 } },
+        } }
+        plaintext_view = { pt_label = {
+            draw_text: {
+                text_style: { font_size: 9.0 },
+            }
+        } }
+    }
+
+    pub RoomHoverPreview = {{RoomHoverPreview}} {
+        visible: false,
+        flow: Overlay,
+        width: Fill,
+        height: Fill,
+        // Align to top-left such that our coordinate adjustment
+        // when showing this popover will work correctly.
+        align: {x: 0, y: 0}
+
+        main_content = <RoundedView> {
+            flow: Down
+            width: (POPOVER_WIDTH),
+            height: Fit,
+            padding: 12
+            spacing: 6
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 5.0
+                border_width: 0.5
+                border_color: #888
+            }
+
+            room_name = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    color: #000,
+                    wrap: Ellipsis,
+                    text_style: <USERNAME_TEXT_STYLE>{ font_size: 10.5 }
+                }
+            }
+            member_count = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    color: (TIMESTAMP_TEXT_COLOR),
+                    text_style: <REGULAR_TEXT>{ font_size: 8.5 }
+                }
+            }
+            topic = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    color: (MESSAGE_TEXT_COLOR),
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{ font_size: 9.0 }
+                }
+            }
+
+            <LineH> {
+                margin: {top: 3, bottom: 3}
+                draw_bg: {color: (COLOR_DIVIDER_DARK)}
+                width: Fill,
+            }
+
+            status_label = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    color: (MESSAGE_NOTICE_TEXT_COLOR),
+                    text_style: <REGULAR_TEXT>{ font_size: 9.0 }
+                }
+            }
+            message_0 = <HoverPreviewMessage> {}
+            message_1 = <HoverPreviewMessage> {}
+            message_2 = <HoverPreviewMessage> {}
+        }
+    }
+}
+
+/// The details shown in a room's hover preview, which are fetched in the background.
+#[derive(Clone, Debug)]
+pub struct RoomHoverPreviewInfo {
+    pub room_id: OwnedRoomId,
+    pub topic: Option<String>,
+    pub num_joined_members: u64,
+    /// The Html-formatted text previews of the room's latest messages, oldest first.
+    pub recent_messages: Vec<String>,
+}
+
+/// Actions sent to the UI thread when a room's hover preview details have been fetched.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum RoomHoverPreviewAction {
+    Loaded(RoomHoverPreviewInfo),
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct RoomHoverPreview {
+    #[deref] view: View,
+    /// The room whose preview is currently shown, if any.
+    #[rust] room_id: Option<OwnedRoomId>,
+}
+
+impl Widget for RoomHoverPreview {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if !self.visible { return; }
+        self.view.handle_event(cx, event, scope);
+
+        if let Event::Actions(actions) = event {
+            for action in actions {
+                let Some(RoomHoverPreviewAction::Loaded(info)) = action.downcast_ref() else { continue };
+                if self.room_id.as_ref() == Some(&info.room_id) {
+                    self.populate(cx, info);
+                }
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.room_id.is_none() {
+            self.visible = false;
+        }
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl RoomHoverPreview {
+    /// Shows this popover for the given room and requests the room's details.
+    ///
+    /// Returns the expected (approximate) dimensions of the popover,
+    /// which can be used to proactively reposition it such that it fits on screen.
+    pub fn show(&mut self, cx: &mut Cx, room_id: OwnedRoomId, room_name: Option<String>) -> DVec2 {
+        self.label(id!(room_name)).set_text(cx, room_name.as_deref().unwrap_or(room_id.as_str()));
+        self.label(id!(member_count)).set_text(cx, "");
+        self.label(id!(topic)).set_visible(cx, false);
+        self.label(id!(status_label)).set_text(cx, tr("room_hover_preview.loading"));
+        self.label(id!(status_label)).set_visible(cx, true);
+        for message in [id!(message_0), id!(message_1), id!(message_2)] {
+            self.html_or_plaintext(message).set_visible(cx, false);
+        }
+        submit_async_request(MatrixRequest::GetRoomHoverPreview { room_id: room_id.clone() });
+        self.room_id = Some(room_id);
+        self.visible = true;
+        self.redraw(cx);
+        dvec2(POPOVER_WIDTH, POPOVER_HEIGHT)
+    }
+
+    /// Hides this popover.
+    pub fn hide(&mut self, cx: &mut Cx) {
+        if self.room_id.take().is_some() {
+            self.visible = false;
+            self.redraw(cx);
+        }
+    }
+
+    /// Fills in this popover with the fetched details of its room.
+    fn populate(&mut self, cx: &mut Cx, info: &RoomHoverPreviewInfo) {
+        self.label(id!(member_count)).set_text(cx, &tr_args(
+            "room_hover_preview.members",
+            &[("count", &info.num_joined_members.to_string())],
+        ));
+        let topic = info.topic.as_deref().map(str::trim).filter(|t| !t.is_empty());
+        self.label(id!(topic)).set_text(cx, topic.unwrap_or(tr("room_hover_preview.no_topic")));
+        self.label(id!(topic)).set_visible(cx, true);
+
+        self.label(id!(status_label)).set_text(cx, tr("room_hover_preview.no_messages"));
+        self.label(id!(status_label)).set_visible(cx, info.recent_messages.is_empty());
+        for (i, message) in [id!(message_0), id!(message_1), id!(message_2)].into_iter().enumerate() {
+            let message_view = self.html_or_plaintext(message);
+            match info.recent_messages.get(i) {
+                Some(text) => {
+                    message_view.show_html(cx, text);
+                    message_view.set_visible(cx, true);
+                }
+                None => message_view.set_visible(cx, false),
+            }
+        }
+        self.redraw(cx);
+    }
+}
+
+impl RoomHoverPreviewRef {
+    /// See [`RoomHoverPreview::show()`].
+    pub fn show(&self, cx: &mut Cx, room_id: OwnedRoomId, room_name: Option<String>) -> DVec2 {
+        let Some(mut inner) = self.borrow_mut() else { return DVec2::default() };
+        inner.show(cx, room_id, room_name)
+    }
+
+    /// See [`RoomHoverPreview::hide()`].
+    pub fn hide(&self, cx: &mut Cx) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.hide(cx);
+    }
+}
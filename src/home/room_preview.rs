@@ -1,11 +1,17 @@
 use makepad_widgets::*;
+use matrix_sdk::ruma::OwnedUserId;
 
 use crate::{
+    i18n::{tr, tr_args},
+    profile::presence::{get_presence, show_presence_dot, PresenceAction},
+    settings::app_settings::{app_settings, DisplayDensity},
     shared::{
         avatar::AvatarWidgetExt,
         html_or_plaintext::HtmlOrPlaintextWidgetExt,
+        styles::DensityStyle,
+        typing_animation::TypingAnimationWidgetExt,
     },
-    utils::{self, relative_format},
+    utils::{self, horizontal_swipe, relative_format, SwipeDirection},
 };
 
 use super::rooms_list::{RoomPreviewAvatar, RoomsListEntry};
@@ -18,9 +24,20 @@ live_design! {
     use crate::shared::helpers::*;
     use crate::shared::avatar::Avatar;
     use crate::shared::html_or_plaintext::HtmlOrPlaintext;
+    use crate::shared::typing_animation::TypingAnimation;
     pub UNREAD_HIGHLIGHT_COLOR = #FF0000;
     pub UNREAD_DEFAULT_COLOR = #AAA;
 
+    // An avatar with a presence dot in its bottom-right corner,
+    // which is only shown for direct message rooms.
+    AvatarWithPresence = <View> {
+        width: Fit, height: Fit
+        flow: Overlay
+        align: { x: 1.0, y: 1.0 }
+        avatar = <Avatar> {}
+        presence_dot = <PresenceDot> {}
+    }
+
     RoomName = <Label> {
         width: Fill, height: Fit
         draw_text:{
@@ -44,6 +61,28 @@ live_design! {
 
     MessagePreview = <View> {
         width: Fill, height: Fit
+        flow: Overlay
+
+        // Shown instead of the latest message while other users are typing in this room.
+        typing_preview = <View> {
+            visible: false
+            width: Fill, height: Fit
+            flow: Right
+            align: {y: 0.5}
+            typing_label = <Label> {
+                width: Fit, height: Fit
+                draw_text: {
+                    color: (TYPING_NOTICE_TEXT_COLOR),
+                    wrap: Ellipsis,
+                    text_style: <REGULAR_TEXT>{ font_size: 9.3 }
+                }
+            }
+            typing_animation = <TypingAnimation> {
+                draw_bg: {
+                    color: (TYPING_NOTICE_TEXT_COLOR),
+                }
+            }
+        }
 
         latest_message = <HtmlOrPlaintext> {
             html_view = { html = {
@@ -161,21 +200,22 @@ live_design! {
                     height: Fit
                     flow: Overlay
                     align: { x: 1.0 }
-                    avatar = <Avatar> {}
+                    <AvatarWithPresence> {}
                     unread_badge = <UnreadBadge> {}
                 }
             }
             IconAndName = <RoomPreviewContent> {
                 padding: 5.
                 align: {x: 0.5, y: 0.5}
-                avatar = <Avatar> {}
+                <AvatarWithPresence> {}
                 room_name = <RoomName> {}
                 unread_badge = <UnreadBadge> {}
             }
             FullPreview = <RoomPreviewContent> {
                 padding: 10
-                avatar = <Avatar> {}
-                <View> {
+                use_display_density: true
+                <AvatarWithPresence> {}
+                text_column = <View> {
                     flow: Down
                     width: Fill, height: 56
                     align: { x: 0.0, y: 0.0 }
@@ -211,12 +251,35 @@ live_design! {
 pub struct RoomPreview {
     #[deref]
     view: View,
+    /// A timer used to detect long presses on this room preview.
+    #[rust] long_press_timer: Timer,
+    /// The position at which an ongoing press began, if it may still become a long press.
+    #[rust] long_press_pos: Option<DVec2>,
+    /// Whether the ongoing press already opened the context menu,
+    /// in which case releasing it must not also select the room.
+    #[rust] long_press_fired: bool,
+    /// A timer used to show the hover preview once the mouse has rested on this room preview.
+    #[rust] hover_timer: Timer,
+    /// Whether this room's hover preview is currently shown.
+    #[rust] hover_preview_shown: bool,
 }
 
 #[derive(Clone, DefaultNone, Debug)]
 pub enum RoomPreviewAction {
     None,
     Click,
+    /// The user right-clicked, long-pressed, or swiped left on this room preview,
+    /// requesting its context menu of quick actions be shown at the given position.
+    OpenContextMenu {
+        abs_pos: DVec2,
+    },
+    /// The mouse has rested on this room preview, so its hover preview
+    /// should be shown at the given position.
+    ShowHoverPreview {
+        abs_pos: DVec2,
+    },
+    /// The mouse has left this room preview, so its hover preview should be hidden.
+    HideHoverPreview,
 }
 
 impl LiveHook for RoomPreview {
@@ -236,18 +299,71 @@ impl Widget for RoomPreview {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         let uid = self.widget_uid();
 
+        /// 500ms long press is default on Android/iOS
+        const LONG_PRESS_DURATION: f64 = 0.500;
+        /// How long the mouse must rest on a room preview before its hover preview is shown.
+        const HOVER_PREVIEW_DELAY: f64 = 0.600;
+
+        if self.hover_timer.is_event(event).is_some() {
+            cx.stop_timer(self.hover_timer);
+            let rect = self.view.area().rect(cx);
+            self.hover_preview_shown = true;
+            cx.widget_action(uid, &scope.path, RoomPreviewAction::ShowHoverPreview {
+                abs_pos: dvec2(rect.pos.x + rect.size.x + 4.0, rect.pos.y),
+            });
+        }
+
+        if let Some(abs_pos) = self.long_press_pos {
+            if self.long_press_timer.is_event(event).is_some() {
+                cx.stop_timer(self.long_press_timer);
+                self.long_press_pos = None;
+                self.long_press_fired = true;
+                cx.widget_action(uid, &scope.path, RoomPreviewAction::OpenContextMenu { abs_pos });
+            }
+        }
+
         match event.hits(cx, self.view.area()) {
-            Hit::FingerDown(_fe) => {
+            // Hover previews are only shown in the desktop rooms list.
+            Hit::FingerHoverIn(_) if cx.display_context.is_desktop() => {
+                self.hover_timer = cx.start_timeout(HOVER_PREVIEW_DELAY);
+            }
+            Hit::FingerHoverOut(_) => {
+                self.hide_hover_preview(cx, uid, scope);
+            }
+            Hit::FingerDown(fe) => {
+                self.hide_hover_preview(cx, uid, scope);
                 cx.set_key_focus(self.view.area());
+                self.long_press_fired = false;
+                if fe.is_primary_hit() {
+                    self.long_press_pos = Some(fe.abs);
+                    self.long_press_timer = cx.start_timeout(LONG_PRESS_DURATION);
+                }
+            }
+            // Moving the finger means the user is scrolling, not long-pressing.
+            Hit::FingerMove(fe) if (fe.abs_start - fe.abs).length() >= 3.0 => {
+                cx.stop_timer(self.long_press_timer);
+                self.long_press_pos = None;
             }
-            Hit::FingerUp(fe) if fe.is_over && fe.is_primary_hit() => {
+            Hit::FingerUp(fe) => {
+                cx.stop_timer(self.long_press_timer);
+                self.long_press_pos = None;
                 // This logic is taken from FingerUpEvent::was_tap(), but we ignore
                 // the time check because we want to allow for slower taps or long presses.
                 // All we do here is check that the finger hasn't moved too much (more than 3 pixels)
                 // since the start of the gesture (the FingerDown hit), because that would mean
                 // the user is trying to scroll rather than wanting to select a room.
-                if (fe.abs_start - fe.abs).length() < 3.0 {
-                    cx.widget_action(uid, &scope.path, RoomPreviewAction::Click);
+                // Swiping left on a touch screen reveals this room's quick actions.
+                if fe.is_over && fe.device.is_touch()
+                    && horizontal_swipe(fe.abs_start, fe.abs) == Some(SwipeDirection::Left)
+                {
+                    cx.widget_action(uid, &scope.path, RoomPreviewAction::OpenContextMenu { abs_pos: fe.abs_start });
+                }
+                else if fe.is_over && !self.long_press_fired && (fe.abs_start - fe.abs).length() < 3.0 {
+                    if fe.device.mouse_button().is_some_and(|b| b.is_secondary()) {
+                        cx.widget_action(uid, &scope.path, RoomPreviewAction::OpenContextMenu { abs_pos: fe.abs });
+                    } else if fe.is_primary_hit() {
+                        cx.widget_action(uid, &scope.path, RoomPreviewAction::Click);
+                    }
                 }
             }
             _ => (),
@@ -261,6 +377,17 @@ impl Widget for RoomPreview {
     }
 }
 
+impl RoomPreview {
+    /// Cancels a pending hover preview, and hides the hover preview if it is shown.
+    fn hide_hover_preview(&mut self, cx: &mut Cx, uid: WidgetUid, scope: &mut Scope) {
+        cx.stop_timer(self.hover_timer);
+        if self.hover_preview_shown {
+            self.hover_preview_shown = false;
+            cx.widget_action(uid, &scope.path, RoomPreviewAction::HideHoverPreview);
+        }
+    }
+}
+
 impl RoomPreviewRef {
     pub fn clicked(&self, actions: &Actions) -> bool {
         if let RoomPreviewAction::Click = actions.find_widget_action(self.widget_uid()).cast() {
@@ -274,14 +401,34 @@ impl RoomPreviewRef {
 pub struct RoomPreviewContent {
     #[deref]
     view: View,
+    /// Whether this preview's sizes and spacings should follow the user's display density.
+    #[live] use_display_density: bool,
+    /// The display density that this preview's sizes and spacings were last adjusted for.
+    #[rust] applied_density: Option<DisplayDensity>,
+    /// The other user in the direct message room shown in this preview, if any.
+    #[rust] direct_user_id: Option<OwnedUserId>,
 }
 
 impl Widget for RoomPreviewContent {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         self.view.handle_event(cx, event, scope);
+
+        // Redraw this preview if the direct message partner's presence has been fetched.
+        if let (Event::Actions(actions), Some(direct_user_id)) = (event, self.direct_user_id.as_ref()) {
+            let presence_updated = actions.iter().any(|action| matches!(
+                action.downcast_ref(),
+                Some(PresenceAction::Updated(user_id)) if user_id == direct_user_id
+            ));
+            if presence_updated {
+                self.redraw(cx);
+            }
+        }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.use_display_density {
+            self.apply_display_density(cx);
+        }
         if let Some(room_info) = scope.props.get::<RoomsListEntry>() {
             if let Some(ref name) = room_info.room_name {
                 self.view.label(id!(room_name)).set_text(cx, name);
@@ -308,6 +455,10 @@ impl Widget for RoomPreviewContent {
                     );
                 }
             }
+            self.show_typing_users(cx, &room_info.typing_users);
+            self.direct_user_id = room_info.direct_user_id.clone();
+            let presence = room_info.direct_user_id.as_deref().and_then(get_presence);
+            show_presence_dot(cx, &self.view.view(id!(presence_dot)), presence.as_ref());
 
             let unread_badge = self.view(id!(unread_badge)); 
             // Helper function to format the rounded rectangle.
@@ -368,6 +519,49 @@ impl Widget for RoomPreviewContent {
 }
 
 impl RoomPreviewContent {
+    /// Shows an animated typing indicator in place of the latest message
+    /// if any other users are typing in this room.
+    fn show_typing_users(&mut self, cx: &mut Cx, typing_users: &[String]) {
+        let typing_preview = self.view.view(id!(typing_preview));
+        let typing_animation = self.view.typing_animation(id!(typing_preview.typing_animation));
+        let typing_text = match typing_users {
+            [] => None,
+            [user] => Some(tr_args("rooms_list.typing_one", &[("user", user)])),
+            [user1, user2] => Some(tr_args("rooms_list.typing_two", &[("user1", user1), ("user2", user2)])),
+            _ => Some(tr("rooms_list.typing_many").to_string()),
+        };
+        let is_typing = typing_text.is_some();
+        if let Some(text) = typing_text {
+            self.view.label(id!(typing_preview.typing_label)).set_text(cx, &text);
+            if !typing_preview.visible() {
+                typing_animation.animate(cx);
+            }
+        } else if typing_preview.visible() {
+            typing_animation.stop_animation();
+        }
+        typing_preview.set_visible(cx, is_typing);
+        self.view.html_or_plaintext(id!(latest_message)).set_visible(cx, !is_typing);
+    }
+
+    /// Adjusts this preview's sizes and spacings to the current display density, if it changed.
+    fn apply_display_density(&mut self, cx: &mut Cx) {
+        let density = app_settings().display_density;
+        if self.applied_density == Some(density) {
+            return;
+        }
+        self.applied_density = Some(density);
+        let style = DensityStyle::of(density);
+        self.view.apply_over(cx, live! {
+            padding: (style.room_preview_padding)
+        });
+        self.view.avatar(id!(avatar)).apply_over(cx, live! {
+            width: (style.room_avatar_size), height: (style.room_avatar_size)
+        });
+        self.view.view(id!(text_column)).apply_over(cx, live! {
+            height: (style.room_preview_text_height)
+        });
+    }
+
     /// Updates the styling of the preview based on whether the room is selected or not.
     pub fn update_preview_colors(&mut self, cx: &mut Cx, is_selected: bool) {
         let bg_color;
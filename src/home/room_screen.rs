@@ -1,41 +1,56 @@
 //! A room screen is the UI page that displays a single Room's timeline of events/messages
 //! along with a message input bar at the bottom.
 
-use std::{borrow::Cow, collections::BTreeMap, ops::{DerefMut, Range}, sync::{Arc, Mutex}, time::SystemTime};
+use std::{borrow::Cow, collections::{BTreeMap, BTreeSet, HashMap}, convert::Infallible, ops::{DerefMut, Range}, sync::{Arc, Mutex}, time::SystemTime};
 
 use bytesize::ByteSize;
+use chrono::Local;
 use imbl::Vector;
 use makepad_widgets::*;
+use tracing::{error, info, warn};
 use matrix_sdk::{
     ruma::{
-        events::{receipt::Receipt, room::{
+        events::{receipt::Receipt, Mentions, room::{
             message::{
                 AudioMessageEventContent, CustomEventContent, EmoteMessageEventContent, FileMessageEventContent, FormattedBody, ImageMessageEventContent, KeyVerificationRequestEventContent, LocationMessageEventContent, MessageFormat, MessageType, NoticeMessageEventContent, RoomMessageEventContent, ServerNoticeMessageEventContent, TextMessageEventContent, VideoMessageEventContent
             }, ImageInfo, MediaSource
-        }, sticker::StickerEventContent}, matrix_uri::MatrixId, uint, EventId, MatrixToUri, MatrixUri, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedMxcUri, OwnedRoomId
+        }, sticker::StickerEventContent}, matrix_uri::MatrixId, uint, EventId, MatrixToUri, MatrixUri, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedMxcUri, OwnedRoomId, OwnedUserId, RoomId, UserId
     }, OwnedServerName
 };
 use matrix_sdk_ui::timeline::{
-    self, EventTimelineItem, InReplyToDetails, MemberProfileChange, RepliedToInfo, RoomMembershipChange, TimelineDetails, TimelineItem, TimelineItemContent, TimelineItemKind, VirtualTimelineItem
+    self, EventSendState, EventTimelineItem, InReplyToDetails, MemberProfileChange, MembershipChange, RepliedToInfo, RoomMembershipChange, TimelineDetails, TimelineItem, TimelineItemContent, TimelineItemKind, VirtualTimelineItem
 };
 use robius_location::Coordinates;
 
 use crate::{
-    avatar_cache, event_preview::{body_of_timeline_item, text_preview_of_member_profile_change, text_preview_of_other_state, text_preview_of_redacted_message, text_preview_of_room_membership_change, text_preview_of_timeline_item}, home::loading_pane::{LoadingPaneState, LoadingPaneWidgetExt}, location::{get_latest_location, init_location_subscriber, request_location_update, LocationAction, LocationRequest, LocationUpdate}, media_cache::{MediaCache, MediaCacheEntry}, profile::{
+    app::AppState, avatar_cache, bridges, room_wallpaper, deep_link::DeepLinkAction, home::{export_chat_modal::ExportChatModalAction, moderation_dashboard::{ModerationDashboardAction, ModeratorPowers, ModerationSummary}}, image_decoding::{self, DecodedImage, ImageDecodingAction}, event_preview::{body_of_timeline_item, text_preview_of_member_profile_change, text_preview_of_other_state, text_preview_of_redacted_message, text_preview_of_room_membership_change, text_preview_of_timeline_item}, home::loading_pane::{LoadingPaneState, LoadingPaneWidgetExt}, i18n::{tr, tr_args}, location::{get_latest_location, init_location_subscriber, request_location_update, LocationAction, LocationRequest, LocationUpdate}, media_cache::{MediaCache, MediaCacheEntry}, network_status::{self, NetworkStatusAction}, profile::{
+        presence,
         user_profile::{AvatarState, ShowUserProfileAction, UserProfile, UserProfileAndRoomId, UserProfilePaneInfo, UserProfileSlidingPaneRef, UserProfileSlidingPaneWidgetExt},
         user_profile_cache,
     }, shared::{
-        avatar::AvatarWidgetRefExt, html_or_plaintext::{HtmlOrPlaintextRef, HtmlOrPlaintextWidgetRefExt}, jump_to_bottom_button::{JumpToBottomButtonWidgetExt, UnreadMessageCount}, popup_list::enqueue_popup_notification, text_or_image::{TextOrImageRef, TextOrImageWidgetRefExt}, typing_animation::TypingAnimationWidgetExt
-    }, sliding_sync::{self, get_client, submit_async_request, take_timeline_endpoints, BackwardsPaginateUntilEventRequest, MatrixRequest, PaginationDirection, TimelineRequestSender, UserPowerLevels}, utils::{self, unix_time_millis_to_datetime, ImageFormat, MediaFormatConst, MEDIA_THUMBNAIL_FORMAT},
+        autocomplete::{self, AutocompleteListWidgetExt, MentionableRoom, Suggestion}, spelling_bar::SpellingBarWidgetExt, formatting_toolbar::{self, FormattingToolbarWidgetExt}, avatar::AvatarWidgetRefExt, html_or_plaintext::{HtmlOrPlaintextRef, HtmlOrPlaintextWidgetExt, HtmlOrPlaintextWidgetRefExt}, jump_to_bottom_button::{JumpToBottomButtonWidgetExt, UnreadMessageCount}, performance_overlay, popup_list::enqueue_popup_notification, styles::DensityStyle, text_or_image::{TextOrImageRef, TextOrImageStatus, TextOrImageWidgetExt, TextOrImageWidgetRefExt}, typing_animation::TypingAnimationWidgetExt
+    }, settings::{app_settings::{app_settings, update_app_settings, DisplayDensity, EnterKeyBehavior, ReadMarkerClearing}, keyboard_shortcuts::ShortcutAction, snippets}, sliding_sync::{self, get_client, submit_async_request, take_timeline_endpoints, BackwardsPaginateUntilEventRequest, MatrixRequest, PaginationDirection, TimelineRequestSender, UserPowerLevels}, utils::{self, horizontal_swipe, unix_time_millis_to_datetime, ImageFormat, MediaFormatConst, SwipeDirection, MEDIA_THUMBNAIL_FORMAT, SWIPE_BACK_EDGE_WIDTH},
 };
 use crate::home::event_reaction_list::ReactionListWidgetRefExt;
+use crate::home::event_source_modal::{EventSource, EventSourceModalAction};
 use crate::home::room_read_receipt::AvatarRowWidgetRefExt;
+use crate::home::scheduled_messages_list::ScheduledMessagesListWidgetExt;
+use crate::scheduled_messages::{self, SendLaterTime};
+use crate::home::timestamp::TimestampWidgetRefExt;
 use rangemap::RangeSet;
 
 use super::{event_reaction_list::ReactionData, loading_pane::LoadingPaneRef, new_message_context_menu::{MessageAbilities, MessageDetails}, room_read_receipt::{self, populate_read_receipts, MAX_VISIBLE_AVATARS_IN_READ_RECEIPT}};
 
 const GEO_URI_SCHEME: &str = "geo:";
 
+/// The URL of the Element Call web app, to which joining a group call is handed off.
+///
+/// Robrix doesn't embed Element Call as a widget, so it cannot share the user's session
+/// with it via the widget API. Handing over our access token in the URL would expose it
+/// to the browser's history and the web app, so instead Element Call asks the user
+/// to log in (or join as a guest) itself.
+const ELEMENT_CALL_URL: &str = "https://call.element.io";
+
 const MESSAGE_NOTICE_TEXT_COLOR: Vec3 = Vec3 { x: 0.5, y: 0.5, z: 0.5 };
 const COLOR_DANGER_RED: Vec3 = Vec3 { x: 0.862, y: 0.0, z: 0.02 };
 
@@ -53,16 +68,22 @@ live_design! {
     use crate::shared::html_or_plaintext::*;
     use crate::shared::icon_button::*;
     use crate::home::room_read_receipt::*;
+    use crate::home::timestamp::Timestamp;
     use crate::profile::user_profile::UserProfileSlidingPane;
     use crate::shared::typing_animation::TypingAnimation;
     use crate::shared::icon_button::*;
     use crate::shared::jump_to_bottom_button::*;
+    use crate::shared::autocomplete::AutocompleteList;
+    use crate::shared::spelling_bar::SpellingBar;
+    use crate::home::scheduled_messages_list::ScheduledMessagesList;
     use crate::home::loading_pane::*;
     use crate::home::event_reaction_list::*;
 
     IMG_DEFAULT_AVATAR = dep("crate://self/resources/img/default_avatar.png")
 
     ICO_LOCATION_PERSON = dep("crate://self/resources/icons/location-person.svg")
+    ICO_COLLAPSE = dep("crate://self/resources/icons/collapse.svg")
+    ICO_CLOCK = dep("crate://self/resources/icons/clock.svg")
 
     COLOR_BG = #xfff8ee
     COLOR_OVERLAY_BG = #x000000d8
@@ -76,16 +97,6 @@ live_design! {
 
     FillerX = <View> {height: Fill}
 
-    Timestamp = <Label> {
-        width: Fit, height: Fit
-        padding: { bottom: 0.0, left: 0.0, right: 0.0 }
-        draw_text: {
-            text_style: <TIMESTAMP_TEXT_STYLE> {},
-            color: (TIMESTAMP_TEXT_COLOR)
-        }
-        text: " "
-    }
-
     REACTION_TEXT_COLOR = #4c00b0
 
     // The content of a reply preview, which shows a small preview
@@ -140,9 +151,24 @@ live_design! {
                     text_style: <MESSAGE_TEXT_STYLE> { font_size: (MESSAGE_REPLY_PREVIEW_FONT_SIZE) },
                 }
             } }
+            // Emoji-only messages are not enlarged within a reply preview.
+            large_emoji_view = { emoji_label = {
+                draw_text: {
+                    text_style: <MESSAGE_TEXT_STYLE> { font_size: (MESSAGE_REPLY_PREVIEW_FONT_SIZE) },
+                }
+            } }
         }
     }
 
+    // A small avatar of a user who is typing, shown in the typing notice.
+    TypingAvatar = <Avatar> {
+        visible: false
+        width: 16., height: 16.
+        text_view = { text = { draw_text: {
+            text_style: { font_size: 6.0 }
+        }}}
+    }
+
     // A small inline preview of a message that was replied to by another message
     // within the room timeline.
     // That is, this view contains a preview of the earlier message
@@ -219,6 +245,55 @@ live_design! {
     // An empty view that takes up no space in the portal list.
     Empty = <View> { }
 
+    DeliveryStatusButton = <RobrixIconButton> {
+        align: {x: 0.5, y: 0.5}
+        padding: {left: 6, right: 6, top: 3, bottom: 3}
+        icon_walk: {width: 10, height: 10, margin: {left: -2, right: -1} }
+        draw_text: {
+            text_style: <REGULAR_TEXT>{font_size: 8},
+        }
+    }
+
+    // Shows whether the current user's message is still being sent, has been sent, or failed to send.
+    // Messages that failed to send can be retried or deleted.
+    DeliveryStatus = <View> {
+        visible: false
+        width: Fill, height: Fit
+        flow: Right,
+        spacing: 6,
+        align: {y: 0.5}
+        margin: {top: 2, bottom: 2}
+
+        status_label = <Label> {
+            width: Fit, height: Fit
+            draw_text: {
+                text_style: <REGULAR_TEXT>{font_size: 8},
+                color: (TIMESTAMP_TEXT_COLOR)
+            }
+        }
+        retry_button = <DeliveryStatusButton> {
+            draw_icon: {
+                svg_file: (ICON_SEND)
+                color: (COLOR_TEXT),
+            }
+            text: "Retry"
+        }
+        delete_button = <DeliveryStatusButton> {
+            draw_icon: {
+                svg_file: (ICON_TRASH)
+                color: (COLOR_DANGER_RED),
+            }
+            draw_bg: {
+                border_color: (COLOR_DANGER_RED),
+                color: #fff0f0
+            }
+            draw_text: {
+                color: (COLOR_DANGER_RED),
+            }
+            text: "Delete"
+        }
+    }
+
     // The view used for each text-based message event in a room's timeline.
     Message = {{Message}} {
         width: Fill,
@@ -237,6 +312,8 @@ live_design! {
 
             instance mentions_bar_color: #ffffff
             instance mentions_bar_width: 4.0
+            // Whether this message is selected via keyboard navigation of the timeline.
+            instance keyboard_focus: 0.0
 
             fn pixel(self) -> vec4 {
                 let base_color = mix(
@@ -261,6 +338,10 @@ live_design! {
                 sdf.rect(0., 0., self.mentions_bar_width, self.rect_size.y);
                 sdf.fill(self.mentions_bar_color);
 
+                // draw the keyboard focus ring
+                sdf.box(1., 1., self.rect_size.x - 2., self.rect_size.y - 2., 3.);
+                sdf.stroke(vec4(0.29, 0.56, 0.89, self.keyboard_focus), 2.);
+
                 return sdf.result;
             }
         }
@@ -347,6 +428,25 @@ live_design! {
                     flow: Right,
                     width: Fill,
                     height: Fit,
+                    // Shows the platform that the sender is bridged from, if any.
+                    bridge_badge = <RoundedView> {
+                        visible: false
+                        width: Fit, height: Fit
+                        margin: {top: 10.0, right: 6.0}
+                        padding: {left: 5.0, right: 5.0, top: 2.0, bottom: 2.0}
+                        show_bg: true
+                        draw_bg: {
+                            color: (COLOR_SECONDARY)
+                            radius: 3.0
+                        }
+                        bridge_label = <Label> {
+                            width: Fit, height: Fit
+                            draw_text: {
+                                text_style: <REGULAR_TEXT>{font_size: 8},
+                                color: (COLOR_TEXT)
+                            }
+                        }
+                    }
                     username = <Label> {
                         width: Fill,
                         margin: {bottom: 9.0, top: 11.0, right: 10.0,}
@@ -371,6 +471,7 @@ live_design! {
                     avatar_row = <AvatarRow> {}
                 }
 
+                delivery_status = <DeliveryStatus> {}
             }
         }
     }
@@ -408,6 +509,8 @@ live_design! {
                     reaction_list = <ReactionList> { }
                     avatar_row = <AvatarRow> {}
                 }
+
+                delivery_status = <DeliveryStatus> {}
             }
         }
     }
@@ -459,10 +562,50 @@ live_design! {
         height: Fit,
         margin: 0.0
         cursor: Default
-        flow: Right,
+        flow: Down,
         padding: { top: 1.0, bottom: 1.0, right: 10.0 }
         spacing: 0.0
         margin: { left: 2.5, top: 4.0, bottom: 4.0}
+
+        // A summary of the run of membership and profile changes that starts with this event,
+        // which is only shown for the first event of a long enough run.
+        run_summary = <View> {
+            visible: false,
+            width: Fill,
+            height: Fit
+            flow: Right,
+            padding: { left: 7.0, top: 2.0, bottom: 2.0 }
+            spacing: 5.0
+            align: {y: 0.5}
+
+            <View> { width: 70.0, height: Fit }
+
+            run_summary_text = <Label> {
+                width: Fill,
+                height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <SMALL_STATE_TEXT_STYLE> {},
+                    color: (SMALL_STATE_TEXT_COLOR)
+                }
+                text: ""
+            }
+
+            run_toggle_button = <RobrixIconButton> {
+                padding: {left: 6, right: 6, top: 3, bottom: 3}
+                draw_icon: {
+                    svg_file: (ICO_COLLAPSE)
+                    color: (SMALL_STATE_TEXT_COLOR)
+                }
+                icon_walk: {width: 10, height: 10, margin: {right: -2}}
+                draw_text: {
+                    text_style: <SMALL_STATE_TEXT_STYLE> {},
+                    color: (SMALL_STATE_TEXT_COLOR)
+                }
+                text: "Show all"
+            }
+        }
+
         body = <View> {
             width: Fill,
             height: Fit
@@ -476,12 +619,7 @@ live_design! {
                 width: 70.0,
                 height: Fit
 
-                timestamp = <Timestamp> {
-                    draw_text: {
-                        text_style: <TIMESTAMP_TEXT_STYLE> {},
-                        color: (TIMESTAMP_TEXT_COLOR)
-                    }
-                }
+                timestamp = <Timestamp> {}
             }
 
             avatar = <Avatar> {
@@ -589,6 +727,32 @@ live_design! {
         align: {x: 0.5, y: 0.0} // center horizontally, align to top vertically
         flow: Overlay,
 
+        // The room's optional wallpaper, drawn behind all timeline items.
+        wallpaper = <View> {
+            visible: false
+            width: Fill, height: Fill
+            flow: Overlay
+            show_bg: true
+            draw_bg: {
+                color: (COLOR_PRIMARY_DARKER)
+            }
+
+            wallpaper_image = <Image> {
+                visible: false
+                width: Fill, height: Fill
+                fit: Biggest
+            }
+
+            // A translucent black layer that dims the wallpaper such that messages remain readable.
+            wallpaper_dim = <View> {
+                width: Fill, height: Fill
+                show_bg: true
+                draw_bg: {
+                    color: #0000
+                }
+            }
+        }
+
         list = <PortalList> {
             height: Fill,
             width: Fill
@@ -697,6 +861,166 @@ live_design! {
         }
     }
 
+    // Lets the user choose when to send the entered message later.
+    SendLaterView = <View> {
+        visible: false
+        width: Fill
+        height: Fit
+        flow: Down
+        padding: {left: 12.0, top: 12.0, bottom: 12.0, right: 10.0}
+        spacing: 10
+
+        show_bg: true,
+        draw_bg: {
+            color: #xF0F5FF,
+        }
+
+        prompt_label = <Label> {
+            width: Fill,
+            height: Fit,
+            draw_text: {
+                wrap: Word,
+                color: (MESSAGE_TEXT_COLOR),
+                text_style: <MESSAGE_TEXT_STYLE>{ font_size: 10.0 },
+            }
+            text: "Send this message later:"
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Right,
+            align: {x: 0.0, y: 0.5}
+            spacing: 10
+
+            // KEEP IN SYNC WITH `SendLaterTime::ALL`.
+            time_dropdown = <DropDown> {
+                width: 200, height: Fit
+                labels: ["In 30 minutes", "In 1 hour", "In 3 hours", "Tomorrow at 9:00", "Custom time..."]
+            }
+            custom_time_input = <RobrixTextInput> {
+                visible: false
+                width: Fill, height: Fit
+                empty_message: "HH:MM or YYYY-MM-DD HH:MM"
+            }
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Right,
+            align: {x: 0.0, y: 0.5}
+            spacing: 15
+
+            cancel_button = <RobrixIconButton> {
+                align: {x: 0.5, y: 0.5}
+                padding: {left: 15, right: 15}
+                draw_icon: {
+                    svg_file: (ICON_CLOSE)
+                    color: (COLOR_DANGER_RED),
+                }
+                icon_walk: {width: 16, height: 16, margin: {left: -2, right: -1, top: -1} }
+
+                draw_bg: {
+                    border_color: (COLOR_DANGER_RED),
+                    color: #fff0f0 // light red
+                }
+                text: "Cancel"
+                draw_text:{
+                    color: (COLOR_DANGER_RED),
+                }
+            }
+
+            schedule_button = <RobrixIconButton> {
+                align: {x: 0.5, y: 0.5}
+                padding: {left: 15, right: 15}
+                draw_icon: {
+                    svg_file: (ICO_CLOCK)
+                    color: (COLOR_ACCEPT_GREEN),
+                }
+                icon_walk: {width: 16, height: 16, margin: {left: -2, right: -1} }
+
+                draw_bg: {
+                    border_color: (COLOR_ACCEPT_GREEN),
+                    color: #f0fff0 // light green
+                }
+                text: "Schedule"
+                draw_text:{
+                    color: (COLOR_ACCEPT_GREEN),
+                }
+            }
+        }
+    }
+
+    // Asks the user to confirm sending a message that notifies everyone in the room via `@room`.
+    RoomMentionConfirmation = <View> {
+        visible: false
+        width: Fill
+        height: Fit
+        flow: Down
+        padding: {left: 12.0, top: 12.0, bottom: 12.0, right: 10.0}
+        spacing: 15
+
+        show_bg: true,
+        draw_bg: {
+            color: #xFFF8E1,
+        }
+
+        prompt_label = <Label> {
+            width: Fill,
+            height: Fit,
+            draw_text: {
+                wrap: Word,
+                color: (MESSAGE_TEXT_COLOR),
+                text_style: <MESSAGE_TEXT_STYLE>{ font_size: 10.0 },
+            }
+            text: ""
+        }
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Right,
+            align: {x: 0.0, y: 0.5}
+            spacing: 15
+
+            cancel_button = <RobrixIconButton> {
+                align: {x: 0.5, y: 0.5}
+                padding: {left: 15, right: 15}
+                draw_icon: {
+                    svg_file: (ICON_CLOSE)
+                    color: (COLOR_DANGER_RED),
+                }
+                icon_walk: {width: 16, height: 16, margin: {left: -2, right: -1, top: -1} }
+
+                draw_bg: {
+                    border_color: (COLOR_DANGER_RED),
+                    color: #fff0f0 // light red
+                }
+                text: "Cancel"
+                draw_text:{
+                    color: (COLOR_DANGER_RED),
+                }
+            }
+
+            confirm_button = <RobrixIconButton> {
+                align: {x: 0.5, y: 0.5}
+                padding: {left: 15, right: 15}
+                draw_icon: {
+                    svg_file: (ICON_SEND)
+                    color: (COLOR_ACCEPT_GREEN),
+                }
+                icon_walk: {width: 16, height: 16, margin: {left: -2, right: -1} }
+
+                draw_bg: {
+                    border_color: (COLOR_ACCEPT_GREEN),
+                    color: #f0fff0 // light green
+                }
+                text: "Notify everyone"
+                draw_text:{
+                    color: (COLOR_ACCEPT_GREEN),
+                }
+            }
+        }
+    }
+
     pub RoomScreen = {{RoomScreen}} {
         width: Fill, height: Fill,
         cursor: Default,
@@ -720,7 +1044,65 @@ live_design! {
                 width: Fill, height: Fill,
                 flow: Down,
 
-                // First, display the timeline of all messages/events.
+                // At the very top, display the room topic truncated to a single line.
+                // Clicking on it shows the full topic in the `topic_popover` below.
+                topic_bar = <View> {
+                    visible: false
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    padding: {left: 12.0, top: 6.0, bottom: 6.0, right: 12.0}
+                    cursor: Hand
+                    show_bg: true,
+                    draw_bg: {
+                        color: (COLOR_PRIMARY)
+                    }
+
+                    topic_label = <Label> {
+                        width: Fill, height: Fit
+                        draw_text: {
+                            wrap: Ellipsis,
+                            color: (MESSAGE_NOTICE_TEXT_COLOR),
+                            text_style: <REGULAR_TEXT>{font_size: 9.5}
+                        }
+                        text: ""
+                    }
+                }
+
+                // Below that, display a banner if a group call is ongoing in this room.
+                call_banner = <View> {
+                    visible: false
+                    width: Fill, height: Fit
+                    flow: Right
+                    align: {y: 0.5}
+                    padding: {left: 12.0, top: 6.0, bottom: 6.0, right: 12.0}
+                    show_bg: true,
+                    draw_bg: {
+                        color: #e8f4ff,
+                    }
+
+                    call_banner_label = <Label> {
+                        width: Fill, height: Fit
+                        draw_text: {
+                            color: (TYPING_NOTICE_TEXT_COLOR),
+                            text_style: <REGULAR_TEXT>{font_size: 10}
+                        }
+                        text: "A group call is in progress."
+                    }
+
+                    join_call_button = <RobrixIconButton> {
+                        padding: {left: 12, right: 12, top: 6, bottom: 6}
+                        draw_bg: {
+                            color: (COLOR_SELECTED_PRIMARY)
+                        }
+                        draw_text: {
+                            color: (COLOR_PRIMARY)
+                        }
+                        text: "Join call"
+                    }
+                }
+
+                // Then, display the timeline of all messages/events.
                 timeline = <Timeline> {}
 
                 // Below that, display an optional preview of the message that the user
@@ -781,6 +1163,16 @@ live_design! {
                         color: #e8f4ff,
                     }
 
+                    // The avatars of the first few users who are typing.
+                    typing_avatars = <View> {
+                        width: Fit, height: Fill
+                        flow: Right, spacing: -4
+                        align: {y: 0.5}
+                        typing_avatar_0 = <TypingAvatar> { }
+                        typing_avatar_1 = <TypingAvatar> { }
+                        typing_avatar_2 = <TypingAvatar> { }
+                    }
+
                     typing_label = <Label> {
                         align: {x: 0.0, y: 0.5},
                         padding: {left: 5.0, right: 0.0}
@@ -803,6 +1195,24 @@ live_design! {
                 // Below that, display a preview of the current location that a user is about to send.
                 location_preview = <LocationPreview> { }
 
+                // Below that, ask the user to confirm sending a message that mentions `@room`.
+                room_mention_confirmation = <RoomMentionConfirmation> { }
+
+                // Below that, show the messages scheduled to be sent to this room later.
+                scheduled_messages_list = <ScheduledMessagesList> { }
+
+                // Below that, let the user choose when to send the entered message later.
+                send_later_view = <SendLaterView> { }
+
+                // Below that, display suggestions for the token currently being typed, e.g., an emoji shortcode.
+                autocomplete_list = <AutocompleteList> { }
+
+                // Below that, list the misspelled words in the message being composed.
+                spelling_bar = <SpellingBar> { }
+
+                // Below that, show the buttons that apply Markdown formatting, if enabled.
+                formatting_toolbar = <FormattingToolbar> { }
+
                 // Below that, display a view that holds the message input bar and send button.
                 input_bar = <View> {
                     width: Fill, height: Fit
@@ -816,6 +1226,28 @@ live_design! {
                         color: (COLOR_PRIMARY)
                     }
 
+                    // Toggles whether membership and profile changes are hidden in this room.
+                    state_events_button = <IconButton> {
+                        draw_icon: {svg_file: (ICO_COLLAPSE)},
+                        icon_walk: {width: Fit, height: 22, margin: {left: 0, bottom: 1, right: 3}},
+                        text: "",
+                    }
+
+                    // Opens the moderation dashboard, only shown to users with moderation powers.
+                    moderation_button = <IconButton> {
+                        visible: false
+                        draw_icon: {svg_file: (ICON_BLOCK_USER)},
+                        icon_walk: {width: Fit, height: 22, margin: {left: 0, bottom: 1, right: 3}},
+                        text: "",
+                    }
+
+                    // Opens a dialog to export this room's history to a file.
+                    export_button = <IconButton> {
+                        draw_icon: {svg_file: (ICON_HTML_FILE)},
+                        icon_walk: {width: Fit, height: 22, margin: {left: 0, bottom: 1, right: 3}},
+                        text: "",
+                    }
+
                     location_button = <IconButton> {
                         draw_icon: {svg_file: (ICO_LOCATION_PERSON)},
                         icon_walk: {width: Fit, height: 26, margin: {left: 0, bottom: -1, right: 3}},
@@ -829,6 +1261,13 @@ live_design! {
                         empty_message: "Write a message (in Markdown) ..."
                     }
 
+                    // Opens the options to send the entered message later.
+                    send_later_button = <IconButton> {
+                        draw_icon: {svg_file: (ICO_CLOCK)},
+                        icon_walk: {width: Fit, height: 22, margin: {left: 0, bottom: 1, right: 3}},
+                        text: "",
+                    }
+
                     send_message_button = <IconButton> {
                         draw_icon: {svg_file: (ICON_SEND)},
                         icon_walk: {width: Fit, height: 25, margin: {left: -3} },
@@ -934,12 +1373,94 @@ live_design! {
                             width: Fill,
                             height: Fit,
                             draw_text: {
-                                text_style: <THEME_FONT_REGULAR>{font_size: 9},
-                                text_wrap: Word,
-                                color: (COLOR_PRIMARY)
+                                text_style: <THEME_FONT_REGULAR>{font_size: 9},
+                                text_wrap: Word,
+                                color: (COLOR_PRIMARY)
+                            }
+                        }
+                    }
+                }
+            }
+
+            // The full room topic, shown upon clicking the truncated topic in the `topic_bar`.
+            // Links within the topic are handled just like links in the timeline.
+            topic_popover = <View> {
+                visible: false
+                width: Fill, height: Fit
+                padding: {left: 8, right: 8, top: 4}
+
+                <RoundedView> {
+                    width: Fill, height: Fit
+                    flow: Right
+                    padding: 12
+                    spacing: 8
+                    show_bg: true
+                    draw_bg: {
+                        color: #fff
+                        border_width: 1.0
+                        border_color: #D0D5DD
+                        radius: 4.0
+                    }
+
+                    full_topic = <HtmlOrPlaintext> { }
+
+                    close_topic_button = <IconButton> {
+                        width: Fit,
+                        height: Fit,
+
+                        draw_icon: {
+                            svg_file: (ICON_CLOSE),
+                            fn get_color(self) -> vec4 {
+                               return (COLOR_META)
+                            }
+                        }
+                        icon_walk: {width: 12, height: 12}
+                    }
+                }
+            }
+
+            // A larger popup of a replied-to message, shown upon hovering over
+            // or long-pressing a reply preview in the timeline.
+            replied_to_message_popup = <View> {
+                visible: false
+                width: Fill, height: Fill
+
+                popup_content = <RoundedView> {
+                    width: 420, height: Fit
+                    flow: Down
+                    padding: 12
+                    show_bg: true
+                    draw_bg: {
+                        color: #fff
+                        border_width: 1.0
+                        border_color: #D0D5DD
+                        radius: 4.0
+                    }
+
+                    <View> {
+                        width: Fill, height: Fit
+                        flow: Right, spacing: 8
+                        margin: {bottom: 8}
+                        align: {y: 0.5}
+
+                        popup_avatar = <Avatar> {
+                            width: 28, height: 28
+                            text_view = { text = { draw_text: {
+                                text_style: { font_size: 9.0 }
+                            }}}
+                        }
+
+                        popup_username = <Label> {
+                            width: Fill, height: Fit
+                            draw_text: {
+                                text_style: <USERNAME_TEXT_STYLE> { font_size: 11 },
+                                color: (USERNAME_TEXT_COLOR)
+                                wrap: Ellipsis,
                             }
                         }
                     }
+
+                    popup_body = <HtmlOrPlaintext> { }
                 }
             }
 
@@ -1002,6 +1523,16 @@ pub struct RoomScreen {
     #[rust] room_name: String,
     /// The persistent UI-relevant states for the room that this widget is currently displaying.
     #[rust] tl_state: Option<TimelineUiState>,
+    /// The index of the timeline item that is currently selected via keyboard navigation, if any.
+    #[rust] keyboard_selected_item: Option<usize>,
+    /// The timer that periodically redraws this room's timeline such that its relative timestamps stay current.
+    #[rust] timestamp_refresh_timer: Timer,
+    /// The timer after which the read marker is moved past the messages the user has viewed,
+    /// if the read marker is set to clear after a delay.
+    #[rust] read_marker_timer: Timer,
+    /// Whether the wallpaper image is still being fetched or decoded,
+    /// such that the wallpaper must be re-applied once it's available.
+    #[rust] wallpaper_image_pending: bool,
 }
 impl Drop for RoomScreen {
     fn drop(&mut self) {
@@ -1022,10 +1553,50 @@ impl Widget for RoomScreen {
         let user_profile_sliding_pane = self.user_profile_sliding_pane(id!(user_profile_sliding_pane));
         let loading_pane = self.loading_pane(id!(loading_pane));
 
+        if self.timestamp_refresh_timer.is_empty() {
+            self.timestamp_refresh_timer = cx.start_interval(utils::RELATIVE_TIMESTAMP_REFRESH_SECS);
+        } else if self.timestamp_refresh_timer.is_event(event).is_some() {
+            self.redraw(cx);
+        }
+
+        if self.read_marker_timer.is_event(event).is_some() {
+            self.read_marker_timer = Timer::empty();
+            if let Some(tl) = self.tl_state.as_mut() {
+                if let Some(event_id) = tl.pending_fully_read_event.take() {
+                    submit_async_request(MatrixRequest::FullyReadReceipt {
+                        room_id: tl.room_id.clone(),
+                        event_id,
+                    });
+                }
+            }
+        }
+
+        // If the user chose for Enter to send messages, intercept Enter key presses in the message input
+        // before it inserts a newline, and instead insert a newline upon Ctrl/Cmd+Enter or Shift+Enter.
+        if let Event::KeyDown(ke) = event {
+            if ke.key_code == KeyCode::ReturnKey
+                && app_settings().enter_key_behavior == EnterKeyBehavior::SendMessage
+                && cx.has_key_focus(self.text_input(id!(message_input)).area())
+            {
+                if ke.modifiers.is_primary() || ke.modifiers.shift {
+                    // Forward a plain Enter key press to the message input, which inserts a newline.
+                    let newline_event = Event::KeyDown(KeyEvent {
+                        modifiers: KeyModifiers::default(),
+                        ..ke.clone()
+                    });
+                    self.view.handle_event(cx, &newline_event, scope);
+                } else {
+                    self.send_entered_message(cx, false);
+                }
+                return;
+            }
+        }
+
         // Currently, a Signal event is only used to tell this widget
         // that its timeline events have been updated in the background.
         if let Event::Signal = event {
             self.process_timeline_updates(cx, &portal_list);
+            self.jump_to_pending_target(cx, &portal_list);
 
             // Ideally we would do this elsewhere on the main thread, because it's not room-specific,
             // but it doesn't hurt to do it here.
@@ -1035,6 +1606,16 @@ impl Widget for RoomScreen {
             avatar_cache::process_avatar_updates(cx);
         }
 
+        // Hide the replied-to message popup upon any new click, touch, or scroll.
+        let is_new_press_or_scroll = match event {
+            Event::MouseDown(_) | Event::Scroll(_) => true,
+            Event::TouchUpdate(e) => e.touches.iter().any(|t| t.state == TouchState::Start),
+            _ => false,
+        };
+        if is_new_press_or_scroll {
+            self.hide_replied_to_message_popup(cx);
+        }
+
         if let Event::Actions(actions) = event {
             let tooltip = self.tooltip(id!(room_screen_tooltip));
             for (_, wr) in portal_list.items_with_actions(actions) {
@@ -1095,6 +1676,69 @@ impl Widget for RoomScreen {
                 if avatar_row_ref.hover_out(actions) {
                     tooltip.hide(cx);
                 }
+                let timestamp_ref = wr.timestamp(id!(profile.timestamp));
+                if let RoomScreenTooltipActions::HoverInTimestamp {
+                    tooltip_pos,
+                    tooltip_width,
+                    callout_offset,
+                    text,
+                    pointing_up,
+                } = timestamp_ref.hover_in(actions) {
+                    tooltip.show_with_options(cx, tooltip_pos, &text);
+                    tooltip.apply_over(cx, live!(
+                        content: {
+                            width: (tooltip_width)
+                            rounded_view = {
+                                draw_bg: {
+                                    callout_offset: (callout_offset)
+                                    pointing_up: (if pointing_up { 1.0 } else { 0.0 })
+                                }
+                            }
+                        }
+                    ));
+                }
+                if timestamp_ref.hover_out(actions) {
+                    tooltip.hide(cx);
+                }
+            }
+
+            // Expand or collapse a run of membership and profile changes.
+            for (item_id, wr) in portal_list.items_with_actions(actions) {
+                if !wr.button(id!(run_toggle_button)).clicked(actions) { continue }
+                let Some(tl) = self.tl_state.as_mut() else { continue };
+                let Some(event_id) = tl.items.get(item_id)
+                    .and_then(|item| item.as_event())
+                    .and_then(|ev| ev.event_id())
+                    .map(ToOwned::to_owned)
+                else { continue };
+                if !tl.expanded_state_event_runs.remove(&event_id) {
+                    tl.expanded_state_event_runs.insert(event_id);
+                }
+                self.redraw(cx);
+            }
+
+            // When the network becomes (un)metered or the auto-download setting changes, redraw all images,
+            // such that placeholders are downloaded automatically if they now should be.
+            let network_status_changed = actions.iter()
+                .any(|action| action.downcast_ref::<NetworkStatusAction>().is_some());
+            if network_status_changed {
+                if let Some(tl) = self.tl_state.as_mut() {
+                    tl.content_drawn_since_last_update.clear();
+                }
+                self.redraw(cx);
+            }
+
+            // Redraw this room if any of the images it requested have finished decoding.
+            let has_decoded_images = actions.iter().any(|action| matches!(
+                action.downcast_ref(),
+                Some(ImageDecodingAction::Decoded(room_ids)) if self.room_id.as_ref().is_some_and(|id| room_ids.contains(id))
+            ));
+            if has_decoded_images {
+                // The wallpaper image may be one of the images that finished decoding.
+                if self.wallpaper_image_pending {
+                    self.update_wallpaper(cx);
+                }
+                self.redraw(cx);
             }
 
             self.handle_message_actions(cx, actions, &portal_list, &loading_pane);
@@ -1110,8 +1754,13 @@ impl Widget for RoomScreen {
                             MessageAction::HighlightMessage(item_id),
                         );
                         tl.message_highlight_animation_state = MessageHighlightAnimationState::Off;
-                        // Adjust the scrolled-to item's position to be slightly beneath the top of the viewport.
-                        // portal_list.set_first_id_and_scroll(portal_list.first_id(), 15.0);
+                        // `smooth_scroll_to` doesn't accept a scroll offset, so we adjust the position
+                        // of the scrolled-to item afterwards, using its estimated height
+                        // to center it vertically within the viewport.
+                        if let Some(height) = tl.estimated_item_height(item_id) {
+                            let viewport_height = portal_list.area().rect(cx).size.y;
+                            portal_list.set_first_id_and_scroll(item_id, ((viewport_height - height) / 2.0).max(0.0));
+                        }
                     }
                 }
 
@@ -1151,9 +1800,74 @@ impl Widget for RoomScreen {
                 self.redraw(cx);
             }
 
+            // Handle the button that hides or shows membership and profile changes in this room.
+            if self.button(id!(state_events_button)).clicked(actions) {
+                if let Some(room_id) = self.room_id.clone() {
+                    let hide = !app_settings().hide_state_events_in(&room_id);
+                    update_app_settings(|s| s.set_hide_state_events_in(room_id, hide));
+                    enqueue_popup_notification(tr(if hide {
+                        "room.state_events.hidden"
+                    } else {
+                        "room.state_events.shown"
+                    }).to_string());
+                    self.redraw(cx);
+                }
+            }
+
+            // Handle the button that opens the moderation dashboard for this room.
+            if self.button(id!(moderation_button)).clicked(actions) {
+                if let Some(tl) = self.tl_state.as_ref() {
+                    cx.widget_action(
+                        room_screen_widget_uid,
+                        &scope.path,
+                        ModerationDashboardAction::Open {
+                            room_id: tl.room_id.clone(),
+                            room_name: self.room_name.clone(),
+                            powers: ModeratorPowers::from(tl.user_power),
+                            summary: ModerationSummary::from_timeline_items(&tl.room_id, &tl.items),
+                        },
+                    );
+                }
+            }
+
+            // Handle the button that exports this room's history.
+            if self.button(id!(export_button)).clicked(actions) {
+                if let Some(room_id) = self.room_id.clone() {
+                    cx.widget_action(
+                        room_screen_widget_uid,
+                        &scope.path,
+                        ExportChatModalAction::Open { room_id, room_name: self.room_name.clone() },
+                    );
+                }
+            }
+
+            // Clicking on the truncated topic toggles the popover showing the full topic.
+            if self.view(id!(topic_bar)).finger_up(actions).is_some() {
+                let topic_popover = self.view(id!(topic_popover));
+                topic_popover.set_visible(cx, !topic_popover.visible());
+                self.redraw(cx);
+            }
+            if self.button(id!(close_topic_button)).clicked(actions) {
+                self.view(id!(topic_popover)).set_visible(cx, false);
+                self.redraw(cx);
+            }
+
+            // Handle the join call button being clicked by handing off to Element Call,
+            // which will prompt the user to log in if needed (see `ELEMENT_CALL_URL`).
+            if self.button(id!(join_call_button)).clicked(actions) {
+                if let Some(room_id) = self.room_id.as_ref() {
+                    let encoded_room_id: String = url::form_urlencoded::byte_serialize(room_id.as_bytes()).collect();
+                    let url = format!("{ELEMENT_CALL_URL}/room/#/?roomId={encoded_room_id}");
+                    if let Err(e) = robius_open::Uri::new(&url).open() {
+                        error!("Failed to open Element Call URL {:?}. Error: {:?}", url, e);
+                        enqueue_popup_notification(tr("room.call.open_failed").to_string());
+                    }
+                }
+            }
+
             // Handle the add location button being clicked.
             if self.button(id!(location_button)).clicked(actions) {
-                log!("Add location button clicked; requesting current location...");
+                info!("Add location button clicked; requesting current location...");
                 if let Err(_e) = init_location_subscriber(cx) {
                     error!("Failed to initialize location subscriber");
                     enqueue_popup_notification(String::from("Failed to initialize location services."));
@@ -1188,35 +1902,77 @@ impl Widget for RoomScreen {
 
             // Handle the send message button being clicked and enter key being pressed.
             let message_input = self.text_input(id!(message_input));
-            let send_message_shortcut_pressed = message_input
-                .key_down_unhandled(actions)
-                .is_some_and(|ke| ke.key_code == KeyCode::ReturnKey && ke.modifiers.is_primary());
+            let unhandled_key = message_input.key_down_unhandled(actions);
+            // If Enter sends messages, that is handled above, before the message input receives the key press.
+            let send_message_shortcut_pressed = app_settings().enter_key_behavior == EnterKeyBehavior::InsertNewline
+                && unhandled_key.as_ref().is_some_and(|ke| ke.key_code == KeyCode::ReturnKey && ke.modifiers.is_primary());
+
+            // Handle an autocomplete suggestion being clicked, or the first one being accepted via Tab.
+            let autocomplete_list = self.autocomplete_list(id!(autocomplete_list));
+            let selected_suggestion = autocomplete_list.selected(actions).or_else(||
+                unhandled_key.as_ref()
+                    .filter(|ke| ke.key_code == KeyCode::Tab && !ke.modifiers.shift)
+                    .and_then(|_| autocomplete_list.first_suggestion())
+            );
+            if let Some(suggestion) = selected_suggestion {
+                self.apply_autocomplete_suggestion(cx, suggestion);
+            }
+
+            // Handle a misspelled word being replaced with a suggested correction.
+            if let Some(replacement) = self.spelling_bar(id!(spelling_bar)).replacement(actions) {
+                let text = message_input.text();
+                if text.get(replacement.range.clone()) == Some(replacement.word.as_str()) {
+                    let new_text = format!(
+                        "{}{}{}",
+                        &text[..replacement.range.start],
+                        replacement.replacement,
+                        &text[replacement.range.end..],
+                    );
+                    message_input.set_text(cx, &new_text);
+                    self.spelling_bar(id!(spelling_bar)).check(cx, &new_text);
+                }
+                message_input.set_key_focus(cx);
+            }
+            // Handle a formatting toolbar button being clicked.
+            if let Some(formatting) = self.formatting_toolbar(id!(formatting_toolbar)).applied(actions) {
+                let text = message_input.text();
+                let selection = formatting_toolbar::text_input_selection(&message_input);
+                let (new_text, new_selection) = formatting_toolbar::apply_formatting(&text, selection, formatting);
+                message_input.set_text(cx, &new_text);
+                formatting_toolbar::set_text_input_selection(cx, &message_input, new_selection);
+                message_input.set_key_focus(cx);
+                self.spelling_bar(id!(spelling_bar)).check(cx, &new_text);
+            }
             if send_message_shortcut_pressed
                 || self.button(id!(send_message_button)).clicked(actions)
             {
-                let entered_text = message_input.text().trim().to_string();
-                if !entered_text.is_empty() {
-                    let room_id = self.room_id.clone().unwrap();
-                    log!("Sending message to room {}: {:?}", room_id, entered_text);
-                    let message = if let Some(html_text) = entered_text.strip_prefix("/html") {
-                        RoomMessageEventContent::text_html(html_text, html_text)
-                    } else if let Some(plain_text) = entered_text.strip_prefix("/plain") {
-                        RoomMessageEventContent::text_plain(plain_text)
-                    } else {
-                        RoomMessageEventContent::text_markdown(entered_text)
-                    };
-                    submit_async_request(MatrixRequest::SendMessage {
-                        room_id,
-                        message,
-                        replied_to: self.tl_state.as_mut().and_then(
-                            |tl| tl.replying_to.take().map(|(_, rep)| rep)
-                        ),
-                        // TODO: support attaching mentions, etc.
-                    });
+                self.send_entered_message(cx, false);
+            }
 
-                    self.clear_replying_to(cx);
-                    message_input.set_text(cx, "");
-                }
+            // Handle the user confirming or cancelling a message that notifies the whole room.
+            if self.button(id!(room_mention_confirmation.confirm_button)).clicked(actions) {
+                self.send_entered_message(cx, true);
+            }
+            if self.button(id!(room_mention_confirmation.cancel_button)).clicked(actions) {
+                self.view(id!(room_mention_confirmation)).set_visible(cx, false);
+                self.redraw(cx);
+            }
+
+            // Handle the "Send later" button and the options for when to send the entered message.
+            if self.button(id!(send_later_button)).clicked(actions) {
+                let is_visible = self.view(id!(send_later_view)).visible();
+                self.show_send_later_view(cx, !is_visible);
+            }
+            if let Some(index) = self.drop_down(id!(send_later_view.time_dropdown)).selected(actions) {
+                let is_custom = SendLaterTime::ALL.get(index) == Some(&SendLaterTime::Custom);
+                self.text_input(id!(send_later_view.custom_time_input)).set_visible(cx, is_custom);
+                self.redraw(cx);
+            }
+            if self.button(id!(send_later_view.cancel_button)).clicked(actions) {
+                self.show_send_later_view(cx, false);
+            }
+            if self.button(id!(send_later_view.schedule_button)).clicked(actions) {
+                self.schedule_entered_message(cx);
             }
 
             // Handle the jump to bottom button: update its visibility, and handle clicks.
@@ -1226,8 +1982,38 @@ impl Widget for RoomScreen {
                 actions,
             );
 
-            // Handle a typing action on the message input box.
+            // Handle keyboard shortcuts, but only if this room is the one currently shown.
+            let is_selected_room = scope.data.get::<AppState>()
+                .and_then(|app_state| app_state.rooms_panel.selected_room.as_ref())
+                .is_some_and(|selected| Some(&selected.room_id) == self.room_id.as_ref());
+            if is_selected_room {
+                for action in actions {
+                    if let Some(ShortcutAction::ReplyToLastMessage) = action.downcast_ref() {
+                        self.reply_to_latest_message(cx);
+                    }
+                }
+            }
+
+            // Expand a completed snippet trigger or emoji shortcode while typing,
+            // or otherwise update the autocomplete suggestions.
             if let Some(new_text) = message_input.changed(actions) {
+                // The pending `@room` confirmation no longer applies to the edited message.
+                self.view(id!(room_mention_confirmation)).set_visible(cx, false);
+                let expanded_snippet = snippets::expand_trailing_snippet(&new_text, &app_settings().snippets);
+                if let Some(expanded_text) = expanded_snippet {
+                    message_input.set_text(cx, &expanded_text);
+                    autocomplete_list.set_suggestions(cx, Vec::new());
+                } else if let Some(replaced_text) = autocomplete::replace_trailing_emoji_shortcode(&new_text) {
+                    message_input.set_text(cx, &replaced_text);
+                    autocomplete_list.set_suggestions(cx, Vec::new());
+                } else {
+                    self.update_autocomplete_suggestions(cx, &new_text);
+                }
+                self.spelling_bar(id!(spelling_bar)).check(cx, &message_input.text());
+            }
+
+            // Handle a typing action on the message input box.
+            if let Some(new_text) = message_input.changed(actions).filter(|_| app_settings().send_typing_notices) {
                 submit_async_request(MatrixRequest::SendTypingNotice {
                     room_id: self.room_id.clone().unwrap(),
                     typing: !new_text.is_empty(),
@@ -1323,10 +2109,20 @@ impl Widget for RoomScreen {
             // Add back any unhandled actions to the global action list.
             cx.extend_actions(actions_generated_within_this_room_screen);
         }
+
+        // The timeline gets key focus when the user clicks or touches anywhere within it,
+        // which enables keyboard navigation of its messages.
+        let timeline_area = self.view(id!(timeline)).area();
+        match event.hits_with_capture_overload(cx, timeline_area, true) {
+            Hit::FingerDown(_) => cx.set_key_focus(timeline_area),
+            Hit::KeyDown(ke) => self.handle_timeline_key_down(cx, &ke, scope, &portal_list),
+            _ => { }
+        }
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
         let room_screen_widget_uid = self.widget_uid();
+        self.view(id!(formatting_toolbar)).set_visible(cx, app_settings().show_formatting_toolbar);
         while let Some(subview) = self.view.draw_walk(cx, scope, walk).step() {
             // We only care about drawing the portal list.
             let portal_list_ref = subview.as_portal_list();
@@ -1337,14 +2133,29 @@ impl Widget for RoomScreen {
             let Some(tl_state) = self.tl_state.as_mut() else {
                 return DrawStep::done();
             };
+            // Usernames (including those in reply previews) must be redrawn
+            // if their colorization has been toggled since they were last drawn.
+            let colorize_usernames = app_settings().colorize_usernames;
+            if tl_state.usernames_colorized != colorize_usernames {
+                tl_state.usernames_colorized = colorize_usernames;
+                tl_state.content_drawn_since_last_update.clear();
+                tl_state.profile_drawn_since_last_update.clear();
+            }
             let room_id = &tl_state.room_id;
             let tl_items = &tl_state.items;
+            let hide_state_events = app_settings().hide_state_events_in(room_id);
 
             // Set the portal list's range based on the number of timeline items.
             let last_item_id = tl_items.len();
 
             let list = list_ref.deref_mut();
             list.set_item_range(cx, 0, last_item_id);
+            performance_overlay::record_timeline_redraw();
+
+            // The run of membership and profile changes that the most recently drawn item belongs to.
+            // Visible items are drawn in order, so each run only needs to be found once per draw.
+            let mut state_event_run: Option<StateEventRun> = None;
+            let keyboard_selected_item = self.keyboard_selected_item;
 
             while let Some(item_id) = list.next_visible_item(cx) {
                 let item = {
@@ -1356,6 +2167,11 @@ impl Widget for RoomScreen {
                         continue;
                     };
 
+                    if !hide_state_events && !state_event_run.as_ref().is_some_and(|run| run.range.contains(&tl_idx)) {
+                        state_event_run = StateEventRun::find(tl_items, tl_idx, &tl_state.expanded_state_event_runs);
+                    }
+                    let run = state_event_run.as_ref().filter(|run| run.range.contains(&tl_idx));
+
                     // Determine whether this item's content and profile have been drawn since the last update.
                     // Pass this state to each of the `populate_*` functions so they can attempt to re-use
                     // an item in the timeline's portallist that was previously populated, if one exists.
@@ -1406,6 +2222,17 @@ impl Widget for RoomScreen {
                                 &RedactedMessageEventMarker,
                                 item_drawn_status,
                             ),
+                            // Membership and profile changes can be hidden to reduce noise in busy rooms.
+                            TimelineItemContent::MembershipChange(_) | TimelineItemContent::ProfileChange(_) if hide_state_events => {
+                                (list.item(cx, item_id, live_id!(Empty)), ItemDrawnStatus::new())
+                            }
+                            // A collapsed run of membership and profile changes is shown only as a summary
+                            // in its first item, so all of its other items are hidden.
+                            TimelineItemContent::MembershipChange(_) | TimelineItemContent::ProfileChange(_)
+                                if run.is_some_and(|run| !run.is_expanded && run.range.start != tl_idx) =>
+                            {
+                                (list.item(cx, item_id, live_id!(Empty)), ItemDrawnStatus::new())
+                            }
                             TimelineItemContent::MembershipChange(membership_change) => populate_small_state_event(
                                 cx,
                                 list,
@@ -1454,6 +2281,17 @@ impl Widget for RoomScreen {
                         }
                     };
 
+                    // Show the summary of the run above its first item, and hide that item if the run is collapsed.
+                    if let Some(run) = run.filter(|run| run.range.start == tl_idx) {
+                        item.view(id!(run_summary)).set_visible(cx, true);
+                        item.label(id!(run_summary_text)).set_text(cx, &run.summary);
+                        item.button(id!(run_toggle_button)).set_text(
+                            cx,
+                            tr(if run.is_expanded { "room.state_event_run.hide" } else { "room.state_event_run.show" }),
+                        );
+                        item.view(id!(body)).set_visible(cx, run.is_expanded);
+                    }
+
                     // Now that we've drawn the item, add its index to the set of drawn items.
                     if item_new_draw_status.content_drawn {
                         tl_state.content_drawn_since_last_update.insert(tl_idx .. tl_idx + 1);
@@ -1463,7 +2301,19 @@ impl Widget for RoomScreen {
                     }
                     item
                 };
+                item.as_message().set_keyboard_selected(keyboard_selected_item == Some(item_id));
                 item.draw_all(cx, &mut Scope::empty());
+
+                // Remember the drawn height of this event item for estimating its position later.
+                let event_id = tl_items.get(item_id)
+                    .and_then(|item| item.as_event())
+                    .and_then(|ev| ev.event_id());
+                if let Some(event_id) = event_id {
+                    let height = item.area().rect(cx).size.y;
+                    if height > 0.0 {
+                        tl_state.item_heights.insert(event_id.to_owned(), height);
+                    }
+                }
             }
         }
 
@@ -1486,6 +2336,7 @@ impl RoomScreen {
         let mut should_continue_backwards_pagination = false;
         let mut num_updates = 0;
         let mut typing_users = Vec::new();
+        let mut wallpaper_changed = false;
         while let Ok(update) = tl.update_receiver.try_recv() {
             num_updates += 1;
             match update {
@@ -1504,7 +2355,7 @@ impl RoomScreen {
                 TimelineUpdate::NewItems { new_items, changed_indices, is_append, clear_cache } => {
                     if new_items.is_empty() {
                         if !tl.items.is_empty() {
-                            log!("Timeline::handle_event(): timeline (had {} items) was cleared for room {}", tl.items.len(), tl.room_id);
+                            info!("Timeline::handle_event(): timeline (had {} items) was cleared for room {}", tl.items.len(), tl.room_id);
                         }
 
                         // If the bottom of the timeline (the last event) is visible, then we should
@@ -1537,7 +2388,7 @@ impl RoomScreen {
                         // log!("Timeline::handle_event(): no jump necessary for updated timeline of same length: {}", items.len());
                     }
                     else if curr_first_id > new_items.len() {
-                        log!("Timeline::handle_event(): jumping to bottom: curr_first_id {} is out of bounds for {} new items", curr_first_id, new_items.len());
+                        info!("Timeline::handle_event(): jumping to bottom: curr_first_id {} is out of bounds for {} new items", curr_first_id, new_items.len());
                         portal_list.set_first_id_and_scroll(new_items.len().saturating_sub(1), 0.0);
                         portal_list.set_tail_range(true);
                         jump_to_bottom.update_visibility(cx, true);
@@ -1546,7 +2397,7 @@ impl RoomScreen {
                         find_new_item_matching_current_item(cx, portal_list, curr_first_id, &tl.items, &new_items)
                     {
                         if curr_item_idx != new_item_idx {
-                            log!("Timeline::handle_event(): jumping view from event index {curr_item_idx} to new index {new_item_idx}, scroll {new_item_scroll}, event ID {_event_id}");
+                            info!("Timeline::handle_event(): jumping view from event index {curr_item_idx} to new index {new_item_idx}, scroll {new_item_scroll}, event ID {_event_id}");
                             portal_list.set_first_id_and_scroll(new_item_idx, new_item_scroll);
                             tl.prev_first_index = Some(new_item_idx);
                             // Set scrolled_past_read_marker false when we jump to a new event
@@ -1560,7 +2411,7 @@ impl RoomScreen {
                     // TODO: after an (un)ignore user event, all timelines are cleared. Handle that here.
                     //
                     else {
-                        warning!("!!! Couldn't find new event with matching ID for ANY event currently visible in the portal list");
+                        warn!("!!! Couldn't find new event with matching ID for ANY event currently visible in the portal list");
                     }
 
                     // If new items were appended to the end of the timeline, show an unread messages badge on the jump to bottom button.
@@ -1569,6 +2420,16 @@ impl RoomScreen {
                             // Immediately show the unread badge with no count while we fetch the actual count in the background.
                             jump_to_bottom.show_unread_message_badge(cx, UnreadMessageCount::Unknown);
                             submit_async_request(MatrixRequest::GetNumberUnreadMessages{ room_id: room_id.clone() });
+                            // Also preview the newest message, if it was sent by another user.
+                            if let Some(newest_event) = new_items.iter().rev().find_map(|item| item.as_event()) {
+                                if !newest_event.is_own() {
+                                    jump_to_bottom.show_new_message_preview(
+                                        cx,
+                                        &utils::get_or_fetch_event_sender(newest_event, Some(room_id)),
+                                        &body_of_timeline_item(newest_event),
+                                    );
+                                }
+                            }
                         }
                     }
 
@@ -1587,7 +2448,7 @@ impl RoomScreen {
                             ref mut events_paginated, target_event_id, ..
                         } = &mut loading_pane_state {
                             *events_paginated += new_items.len().saturating_sub(tl.items.len());
-                            log!("While finding target event {target_event_id}, loaded {events_paginated} messages...");
+                            info!("While finding target event {target_event_id}, loaded {events_paginated} messages...");
                             // Here, we assume that we have not yet found the target event,
                             // so we need to continue paginating backwards.
                             // If the target event has already been found, it will be handled
@@ -1602,6 +2463,14 @@ impl RoomScreen {
                         tl.profile_drawn_since_last_update.remove(changed_indices.clone());
                         // log!("Timeline::handle_event(): changed_indices: {changed_indices:?}, items len: {}\ncontent drawn: {:#?}\nprofile drawn: {:#?}", items.len(), tl.content_drawn_since_last_update, tl.profile_drawn_since_last_update);
                     }
+                    // Keep the keyboard selection on the same event, whose index may have changed.
+                    self.keyboard_selected_item = self.keyboard_selected_item
+                        .and_then(|selected| tl.items.get(selected)?.as_event()?.event_id())
+                        .and_then(|selected_event_id| new_items.iter().position(|item| item
+                            .as_event()
+                            .and_then(|ev| ev.event_id())
+                            .is_some_and(|ev_id| ev_id == selected_event_id)
+                        ));
                     tl.items = new_items;
                     done_loading = true;
                 }
@@ -1635,9 +2504,7 @@ impl RoomScreen {
                         //       we should deduplicate them at some point.
                         let speed = 50.0;
                         // Scroll to the message right above the replied-to message.
-                        // FIXME: `smooth_scroll_to` should accept a scroll offset parameter too,
-                        //       so that we can scroll to the replied-to message and have it
-                        //       appear beneath the top of the viewport.
+                        // Once scrolling has finished, the replied-to message is centered in the viewport.
                         portal_list.smooth_scroll_to(cx, index.saturating_sub(1), speed, None);
                         // start highlight animation.
                         tl.message_highlight_animation_state = MessageHighlightAnimationState::Pending {
@@ -1696,8 +2563,16 @@ impl RoomScreen {
                     // Here, to be most efficient, we could redraw only the user avatars and names in the timeline,
                     // but for now we just fall through and let the final `redraw()` call re-draw the whole timeline view.
                 }
+                TimelineUpdate::RoomBridgesFetched => {
+                    // Bridge badges are drawn as part of the profile, which is otherwise cached.
+                    tl.profile_drawn_since_last_update.clear();
+                }
+                TimelineUpdate::RoomWallpaperChanged => {
+                    wallpaper_changed = true;
+                }
+
                 TimelineUpdate::MediaFetched => {
-                    log!("Timeline::handle_event(): media fetched for room {}", tl.room_id);
+                    info!("Timeline::handle_event(): media fetched for room {}", tl.room_id);
                     // Here, to be most efficient, we could redraw only the media items in the timeline,
                     // but for now we just fall through and let the final `redraw()` call re-draw the whole timeline view.
                 }
@@ -1720,6 +2595,8 @@ impl RoomScreen {
                         .set_visible(cx, can_send_message);
                     self.view.view(id!(can_not_send_message_notice))
                         .set_visible(cx, !can_send_message);
+                    self.view.button(id!(moderation_button))
+                        .set_visible(cx, ModeratorPowers::from(user_power_level).any());
                 }
 
                 TimelineUpdate::OwnUserReadReceipt(receipt) => {
@@ -1741,6 +2618,21 @@ impl RoomScreen {
         }
 
         if !typing_users.is_empty() {
+            // Show the avatars of the first few typing users, from the avatar cache.
+            const TYPING_AVATAR_IDS: [&[LiveId]; 3] = [id!(typing_avatar_0), id!(typing_avatar_1), id!(typing_avatar_2)];
+            let typing_avatars = self.view.view(id!(typing_avatars));
+            for (i, avatar_id) in TYPING_AVATAR_IDS.into_iter().enumerate() {
+                let avatar = typing_avatars.avatar(avatar_id);
+                match (typing_users.get(i), self.room_id.as_ref()) {
+                    (Some((user_id, _)), Some(room_id)) => {
+                        avatar.set_avatar_and_get_username(cx, room_id, user_id, None, None);
+                        avatar.set_visible(cx, true);
+                    }
+                    _ => avatar.set_visible(cx, false),
+                }
+            }
+
+            let typing_users: Vec<&String> = typing_users.iter().map(|(_, name)| name).collect();
             let typing_notice_text = match typing_users.as_slice() {
                 [] => String::new(),
                 [user] => format!("{user} is typing "),
@@ -1773,11 +2665,133 @@ impl RoomScreen {
 
         if num_updates > 0 {
             // log!("Applied {} timeline updates for room {}, redrawing with {} items...", num_updates, tl.room_id, tl.items.len());
+            // A new call member state event may have started or ended a group call.
+            self.update_call_banner(cx);
+            // A new topic state event may have changed the room topic.
+            self.update_topic_bar(cx);
+            // The wallpaper image may have just been fetched.
+            if wallpaper_changed || self.wallpaper_image_pending {
+                self.update_wallpaper(cx);
+            }
             self.redraw(cx);
         }
     }
 
 
+    /// Handles a key press while this room's timeline has key focus.
+    ///
+    /// The arrow keys and PageUp/PageDown move the selection between messages,
+    /// and Escape clears it. Enter opens the selected message's context menu,
+    /// while `r` starts replying to the selected message.
+    fn handle_timeline_key_down(
+        &mut self,
+        cx: &mut Cx,
+        ke: &KeyEvent,
+        scope: &mut Scope,
+        portal_list: &PortalListRef,
+    ) {
+        // Key combinations with modifiers are reserved for keyboard shortcuts.
+        if ke.modifiers.is_primary() || ke.modifiers.alt { return }
+        let Some(tl) = self.tl_state.as_ref() else { return };
+
+        let page_size = portal_list.visible_items().max(1) as isize;
+        let steps = match ke.key_code {
+            KeyCode::ArrowUp => -1,
+            KeyCode::ArrowDown => 1,
+            KeyCode::PageUp => -page_size,
+            KeyCode::PageDown => page_size,
+            KeyCode::Escape => {
+                self.keyboard_selected_item = None;
+                self.redraw(cx);
+                return;
+            }
+            KeyCode::ReturnKey | KeyCode::KeyR => {
+                if let Some(item_id) = self.keyboard_selected_item {
+                    cx.widget_action(
+                        self.widget_uid(),
+                        &scope.path,
+                        MessageAction::KeyPressedOnSelected { item_id, key_code: ke.key_code },
+                    );
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        // Without a current selection, we start from the visible end of the timeline
+        // in the direction of movement.
+        let first_visible = portal_list.first_id();
+        let start = match self.keyboard_selected_item {
+            Some(selected) => selected as isize,
+            None if steps < 0 => (first_visible + portal_list.visible_items()).min(tl.items.len()) as isize,
+            None => first_visible as isize - 1,
+        };
+        let is_message = |index: usize| tl.items.get(index)
+            .and_then(|item| item.as_event())
+            .is_some_and(|ev| matches!(ev.content(), TimelineItemContent::Message(_) | TimelineItemContent::Sticker(_)));
+        let Some(new_selection) = utils::step_selection(tl.items.len(), start, steps, is_message) else { return };
+
+        // Scroll the newly-selected message into view.
+        let num_visible = portal_list.visible_items();
+        if new_selection < first_visible {
+            portal_list.set_first_id_and_scroll(new_selection, 0.0);
+        } else if new_selection + 1 >= first_visible + num_visible {
+            portal_list.set_first_id_and_scroll((new_selection + 2).saturating_sub(num_visible), 0.0);
+        }
+        self.keyboard_selected_item = Some(new_selection);
+        self.redraw(cx);
+    }
+
+    /// Jumps to the event that was requested for this room via [`jump_to_event()`], if any.
+    ///
+    /// If that event isn't in the locally-known timeline, this searches for it
+    /// by paginating backwards, just like when jumping to a replied-to message.
+    fn jump_to_pending_target(&mut self, cx: &mut Cx, portal_list: &PortalListRef) {
+        let loading_pane = self.loading_pane(id!(loading_pane));
+        let Some(tl) = self.tl_state.as_mut() else { return };
+        // Wait until the timeline has received its initial items.
+        if tl.items.is_empty() { return }
+        let Some(target_event_id) = PENDING_JUMP_TARGETS.lock().unwrap().remove(&tl.room_id) else { return };
+
+        let target_index = tl.items.iter().position(|item| item
+            .as_event()
+            .and_then(|e| e.event_id())
+            .is_some_and(|ev_id| ev_id == target_event_id)
+        );
+        if let Some(index) = target_index {
+            // Scroll to the message right before the target message,
+            // such that the target message is centered in the viewport.
+            portal_list.smooth_scroll_to(cx, index.saturating_sub(1), 50.0, None);
+            tl.message_highlight_animation_state = MessageHighlightAnimationState::Pending {
+                item_id: index
+            };
+        } else {
+            loading_pane.set_state(
+                cx,
+                LoadingPaneState::BackwardsPaginateUntilEvent {
+                    target_event_id: target_event_id.clone(),
+                    events_paginated: 0,
+                    request_sender: tl.request_sender.clone(),
+                },
+            );
+            loading_pane.show(cx);
+            let room_id = tl.room_id.clone();
+            let current_tl_len = tl.items.len();
+            tl.request_sender.send_if_modified(|requests| {
+                requests.retain(|r| r.room_id != room_id);
+                requests.push(BackwardsPaginateUntilEventRequest {
+                    room_id,
+                    target_event_id,
+                    // We already searched through all locally-known items above.
+                    starting_index: 0,
+                    current_tl_len,
+                });
+                true
+            });
+        }
+        self.redraw(cx);
+    }
+
     /// Handles a link being clicked in any child widgets of this RoomScreen.
     ///
     /// Returns `true` if the given `action` was indeed an `HtmlLinkAction::Clicked` action.
@@ -1796,27 +2810,21 @@ impl RoomScreen {
                         if self.room_id.as_ref() == Some(room_id) {
                             return true;
                         }
-                        if let Err(e) = robius_open::Uri::new(&url).open() {
-                            error!("Failed to open URL {:?}. Error: {:?}", url, e);
-                            enqueue_popup_notification("Could not open URL: {url}".to_string());
-                        }
-                        if let Some(_known_room) = get_client().and_then(|c| c.get_room(room_id)) {
-                            log!("TODO: jump to known room {}", room_id);
+                        if get_client().and_then(|c| c.get_room(room_id)).is_some() {
+                            cx.action(DeepLinkAction::Navigate(id.clone()));
                         } else {
-                            log!("TODO: fetch and display room preview for room {}", room_id);
+                            if let Err(e) = robius_open::Uri::new(&url).open() {
+                                error!("Failed to open URL {:?}. Error: {:?}", url, e);
+                                enqueue_popup_notification(tr_args("deep_link.open_failed", &[("url", &url)]));
+                            }
+                            info!("TODO: fetch and display room preview for room {}", room_id);
                         }
                         true
                     }
-                    MatrixId::RoomAlias(room_alias) => {
-                        if let Err(e) = robius_open::Uri::new(&url).open() {
-                            error!("Failed to open URL {:?}. Error: {:?}", url, e);
-                            enqueue_popup_notification("Could not open URL: {url}".to_string());
-                        }
-                        log!("TODO: open room alias {}", room_alias);
-                        // TODO: open a room loading screen that shows a spinner
-                        //       while our background async task calls Client::resolve_room_alias()
-                        //       and then either jumps to the room if known, or fetches and displays
-                        //       a room preview for that room.
+                    MatrixId::RoomAlias(_room_alias) => {
+                        // The alias is resolved in the background, after which we jump to the room,
+                        // or open the link externally if the user hasn't joined that room.
+                        cx.action(DeepLinkAction::Navigate(id.clone()));
                         true
                     }
                     MatrixId::User(user_id) => {
@@ -1846,14 +2854,15 @@ impl RoomScreen {
                         true
                     }
                     MatrixId::Event(room_id, event_id) => {
-                        if let Err(e) = robius_open::Uri::new(&url).open() {
-                            error!("Failed to open URL {:?}. Error: {:?}", url, e);
-                            enqueue_popup_notification("Could not open URL: {url}".to_string());
+                        let is_known_room = RoomId::parse(room_id.as_str()).ok()
+                            .and_then(|room_id| get_client().and_then(|c| c.get_room(&room_id)))
+                            .is_some();
+                        if is_known_room {
+                            cx.action(DeepLinkAction::Navigate(id.clone()));
+                        } else if let Err(e) = robius_open::Uri::new(&url).open() {
+                            error!("Failed to open URL {:?} to event {event_id}. Error: {:?}", url, e);
+                            enqueue_popup_notification(tr_args("deep_link.open_failed", &[("url", &url)]));
                         }
-                        log!("TODO: open event {} in room {}", event_id, room_id);
-                        // TODO: this requires the same first step as the `MatrixId::Room` case above,
-                        //       but then we need to call Room::event_with_context() to get the event
-                        //       and its context (surrounding events ?).
                         true
                     }
                     _ => false,
@@ -1869,10 +2878,10 @@ impl RoomScreen {
             }
 
             if !link_was_handled {
-                log!("Opening URL \"{}\"", url);
+                info!("Opening URL \"{}\"", url);
                 if let Err(e) = robius_open::Uri::new(&url).open() {
                     error!("Failed to open URL {:?}. Error: {:?}", url, e);
-                    enqueue_popup_notification("Could not open URL: {url}".to_string());
+                    enqueue_popup_notification(tr_args("deep_link.open_failed", &[("url", &url)]));
                 }
             }
             true
@@ -1938,6 +2947,38 @@ impl RoomScreen {
                         );
                     }
                 }
+                MessageAction::Quote(details) => {
+                    let Some(tl) = self.tl_state.as_ref() else { return };
+                    let quote = tl.items
+                        .get(details.item_id)
+                        .and_then(|tl_item| tl_item.as_event())
+                        .filter(|ev| ev.event_id() == details.event_id.as_deref())
+                        .map(|ev| formatting_toolbar::markdown_quote(
+                            &body_of_timeline_item(ev),
+                            &utils::get_or_fetch_event_sender(ev, Some(&tl.room_id)),
+                        ));
+                    let Some(quote) = quote else {
+                        enqueue_popup_notification(tr("message.quote_not_found").to_string());
+                        error!("MessageAction::Quote: couldn't find event [{}] {:?} to quote in room {:?}",
+                            details.item_id,
+                            details.event_id.as_deref(),
+                            self.room_id,
+                        );
+                        continue;
+                    };
+                    // Add the quote below any text that the user has already entered.
+                    let message_input = self.text_input(id!(message_input));
+                    let entered_text = message_input.text();
+                    let new_text = if entered_text.trim().is_empty() {
+                        quote
+                    } else {
+                        format!("{}\n\n{quote}", entered_text.trim_end())
+                    };
+                    message_input.set_text(cx, &new_text);
+                    message_input.set_key_focus(cx);
+                    self.spelling_bar(id!(spelling_bar)).check(cx, &new_text);
+                    self.redraw(cx);
+                }
                 MessageAction::Edit(_details) => {
                     // TODO
                     enqueue_popup_notification("Editing messages is not yet implemented.".to_string());
@@ -2018,34 +3059,40 @@ impl RoomScreen {
                         );
                     }
                 }
-                MessageAction::ViewSource(_details) => {
-                    enqueue_popup_notification("Viewing an event's source is not yet implemented.".to_string());
-                    // TODO: re-use Franco's implementation below:
-
-                    // let Some(tl) = self.tl_state.as_mut() else { continue };
-                    // let Some(event_tl_item) = tl.items
-                    //     .get(details.item_id)
-                    //     .and_then(|tl_item| tl_item.as_event().cloned())
-                    //     .filter(|ev| ev.event_id() == details.event_id.as_deref())
-                    // else {
-                    //     continue;
-                    // };
-
-                    // let Some(_message_event) = event_tl_item.content().as_message() else {
-                    //     continue;
-                    // };
-
-                    // let original_json: Option<serde_json::Value> = event_tl_item
-                    //     .original_json()
-                    //     .and_then(|raw_event| serde_json::to_value(raw_event).ok());
-                    // let room_id = self.room_id.to_owned();
-                    // let event_id = event_tl_item.event_id().map(|e| e.to_owned());
-
-                    // cx.widget_action(
-                    //     widget_uid,
-                    //     &scope.path,
-                    //     MessageAction::MessageSourceModalOpen { room_id, event_id, original_json },
-                    // );
+                MessageAction::ViewSource(details) => {
+                    let Some(tl) = self.tl_state.as_ref() else { continue };
+                    let Some(event_tl_item) = tl.items
+                        .get(details.item_id)
+                        .and_then(|tl_item| tl_item.as_event())
+                        .filter(|ev| ev.event_id() == details.event_id.as_deref())
+                    else {
+                        enqueue_popup_notification(tr("event_source.not_found").to_string());
+                        continue;
+                    };
+                    let source = EventSource::from_event(tl.room_id.clone(), event_tl_item);
+                    cx.widget_action(
+                        room_screen_widget_uid,
+                        &Scope::empty().path,
+                        EventSourceModalAction::Open(Box::new(source)),
+                    );
+                }
+                MessageAction::DownloadMedia(details) => {
+                    let Some(tl) = self.tl_state.as_mut() else { continue };
+                    let image_info_source = tl.items
+                        .get(details.item_id)
+                        .and_then(|tl_item| tl_item.as_event())
+                        .filter(|ev| ev.event_id() == details.event_id.as_deref())
+                        .and_then(|ev| match ev.content() {
+                            TimelineItemContent::Message(message) => MessageOrSticker::Message(message).get_type().get_image_info(),
+                            TimelineItemContent::Sticker(sticker) => MessageOrSticker::Sticker(sticker.content()).get_type().get_image_info(),
+                            _ => None,
+                        });
+                    let Some((image_info, original_source)) = image_info_source else { continue };
+                    if let MediaSource::Plain(mxc_uri) = preferred_image_source(image_info, original_source) {
+                        tl.media_cache.try_get_media_or_fetch(mxc_uri, Some(MEDIA_THUMBNAIL_FORMAT.into()));
+                        tl.content_drawn_since_last_update.remove(details.item_id .. details.item_id + 1);
+                        self.redraw(cx);
+                    }
                 }
                 MessageAction::JumpToRelated(details) => {
                     let Some(tl) = self.tl_state.as_mut() else { continue };
@@ -2082,9 +3129,7 @@ impl RoomScreen {
                         // log!("The related message {replied_to_event} was immediately found in room {}, scrolling to from index {reply_message_item_id} --> {index} (first ID {}).", tl.room_id, portal_list.first_id());
                         let speed = 50.0;
                         // Scroll to the message right *before* the replied-to message.
-                        // FIXME: `smooth_scroll_to` should accept a "scroll offset" (first scroll) parameter too,
-                        //       so that we can scroll to the replied-to message and have it
-                        //       appear beneath the top of the viewport.
+                        // Once scrolling has finished, the replied-to message is centered in the viewport.
                         portal_list.smooth_scroll_to(cx, index.saturating_sub(1), speed, None);
                         // start highlight animation.
                         tl.message_highlight_animation_state = MessageHighlightAnimationState::Pending {
@@ -2107,7 +3152,7 @@ impl RoomScreen {
 
                         tl.request_sender.send_if_modified(|requests| {
                             if let Some(existing) = requests.iter_mut().find(|r| r.room_id == tl.room_id) {
-                                warning!("Unexpected: room {} already had an existing timeline request in progress, event: {:?}", tl.room_id, existing.target_event_id);
+                                warn!("Unexpected: room {} already had an existing timeline request in progress, event: {:?}", tl.room_id, existing.target_event_id);
                                 // We might as well re-use this existing request...
                                 existing.target_event_id = related_event_id.clone();
                             } else {
@@ -2153,13 +3198,59 @@ impl RoomScreen {
                         );
                     }
                 }
+                MessageAction::RetrySend(details) => {
+                    let Some(tl) = self.tl_state.as_ref() else { return };
+                    let timeline_event_id = tl.items.get(details.item_id)
+                        .and_then(|item| item.as_event())
+                        .filter(|ev| ev.send_state().is_some())
+                        .map(|ev| ev.identifier());
+                    if let Some(timeline_event_id) = timeline_event_id {
+                        submit_async_request(MatrixRequest::RetrySendMessage {
+                            room_id: tl.room_id.clone(),
+                            timeline_event_id,
+                        });
+                    } else {
+                        enqueue_popup_notification(tr("message.retry_send_not_found").to_string());
+                        error!("MessageAction::RetrySend: couldn't find unsent message [{}] in room {}",
+                            details.item_id,
+                            tl.room_id,
+                        );
+                    }
+                }
                 // MessageAction::Report(details) => {
                 //     // TODO
                 //     enqueue_popup_notification("Reporting messages is not yet implemented.".to_string());
                 // }
 
+                MessageAction::MoveReadMarker(details) => {
+                    let Some(tl) = self.tl_state.as_mut() else { continue };
+                    let Some(event_id) = details.event_id.clone() else { continue };
+                    // Servers ignore moving the marker backwards, so don't even try if the marker is shown below this message.
+                    let read_marker_index = tl.items.iter().position(|item|
+                        matches!(item.kind(), TimelineItemKind::Virtual(VirtualTimelineItem::ReadMarker))
+                    );
+                    if read_marker_index.is_some_and(|index| details.item_id < index) {
+                        enqueue_popup_notification(tr("message.read_marker_only_forwards").to_string());
+                        continue;
+                    }
+                    // Keep the marker where the user put it, rather than moving it again upon scrolling.
+                    tl.read_marker_pinned = true;
+                    tl.pending_fully_read_event = None;
+                    submit_async_request(MatrixRequest::MoveFullyReadMarker {
+                        room_id: tl.room_id.clone(),
+                        event_id,
+                    });
+                }
+                MessageAction::ShowRepliedToMessage { details, abs_pos } => {
+                    self.show_replied_to_message_popup(cx, &details, abs_pos);
+                }
+                MessageAction::HideRepliedToMessage => {
+                    self.hide_replied_to_message_popup(cx);
+                }
                 // This is handled within the Message widget itself.
                 MessageAction::HighlightMessage(..) => { }
+                // This is handled within the Message widget itself.
+                MessageAction::KeyPressedOnSelected { .. } => { }
                 // This is handled by the top-level App itself.
                 MessageAction::OpenMessageContextMenu { .. } => { }
                 // This isn't yet handled, as we need to completely redesign it.
@@ -2171,6 +3262,58 @@ impl RoomScreen {
         }
     }
 
+    /// Shows a popup of the full message that the given message replied to, near the given position.
+    ///
+    /// This lets the user read the original message without scrolling away from the reply.
+    fn show_replied_to_message_popup(&mut self, cx: &mut Cx, details: &MessageDetails, abs_pos: DVec2) {
+        let Some(tl) = self.tl_state.as_ref() else { return };
+        let Some(event_tl_item) = tl.items.get(details.item_id).and_then(|item| item.as_event()) else { return };
+        if event_tl_item.event_id() != details.event_id.as_deref() {
+            return;
+        }
+        let TimelineItemContent::Message(message) = event_tl_item.content() else { return };
+        let Some(in_reply_to) = message.in_reply_to() else { return };
+        let TimelineDetails::Ready(replied_to_event) = &in_reply_to.event else { return };
+
+        let popup = self.view(id!(replied_to_message_popup));
+        let (username, _) = popup.avatar(id!(popup_avatar)).set_avatar_and_get_username(
+            cx,
+            &tl.room_id,
+            replied_to_event.sender(),
+            Some(replied_to_event.sender_profile()),
+            Some(in_reply_to.event_id.as_ref()),
+        );
+        let popup_username = popup.label(id!(popup_username));
+        popup_username.set_text(cx, &username);
+        set_username_color(cx, &popup_username, replied_to_event.sender());
+        populate_preview_of_timeline_item(
+            cx,
+            &popup.html_or_plaintext(id!(popup_body)),
+            replied_to_event.content(),
+            &username,
+        );
+
+        // Show the popup just below the given position, keeping it within this RoomScreen's bounds.
+        const POPUP_WIDTH: f64 = 420.0;
+        let rect = self.view.area().rect(cx);
+        let left = (abs_pos.x - rect.pos.x).min(rect.size.x - POPUP_WIDTH).max(0.0);
+        let top = (abs_pos.y - rect.pos.y + 16.0).max(0.0);
+        popup.apply_over(cx, live! {
+            popup_content = { margin: { left: (left), top: (top) } }
+        });
+        popup.set_visible(cx, true);
+        self.redraw(cx);
+    }
+
+    /// Hides the popup of a replied-to message, if it's shown.
+    fn hide_replied_to_message_popup(&mut self, cx: &mut Cx) {
+        let popup = self.view(id!(replied_to_message_popup));
+        if popup.visible() {
+            popup.set_visible(cx, false);
+            self.redraw(cx);
+        }
+    }
+
     /// Shows the user profile sliding pane with the given avatar info.
     fn show_user_profile(
         &mut self,
@@ -2200,9 +3343,9 @@ impl RoomScreen {
             replying_to.0.event_id(),
         );
 
-        replying_preview_view
-            .label(id!(reply_preview_content.reply_preview_username))
-            .set_text(cx, replying_preview_username.as_str());
+        let username_label = replying_preview_view.label(id!(reply_preview_content.reply_preview_username));
+        username_label.set_text(cx, replying_preview_username.as_str());
+        set_username_color(cx, &username_label, replying_to.0.sender());
 
         populate_preview_of_timeline_item(
             cx,
@@ -2225,6 +3368,20 @@ impl RoomScreen {
         self.redraw(cx);
     }
 
+    /// Starts replying to the latest message in this room's timeline, if any.
+    fn reply_to_latest_message(&mut self, cx: &mut Cx) {
+        let Some(tl) = self.tl_state.as_ref() else { return };
+        let latest_message = tl.items.iter().rev()
+            .filter_map(|tl_item| tl_item.as_event())
+            .find(|ev| matches!(ev.content(), TimelineItemContent::Message(_)))
+            .cloned();
+        if let Some(event_tl_item) = latest_message {
+            if let Ok(replied_to_info) = event_tl_item.replied_to_info() {
+                self.show_replying_to(cx, (event_tl_item, replied_to_info));
+            }
+        }
+    }
+
     /// Clears (and makes invisible) the preview of the message
     /// that the user is currently replying to.
     fn clear_replying_to(&mut self, cx: &mut Cx) {
@@ -2252,6 +3409,10 @@ impl RoomScreen {
 
         // Obtain the current user's power levels for this room.
         submit_async_request(MatrixRequest::GetRoomPowerLevels { room_id: room_id.clone() });
+        // Obtain the bridges set up in this room, in order to show which users are bridged.
+        submit_async_request(MatrixRequest::GetRoomBridges { room_id: room_id.clone() });
+        // Obtain this room's wallpaper, which may have been changed on another device.
+        submit_async_request(MatrixRequest::GetRoomWallpaper { room_id: room_id.clone() });
 
         let state_opt = TIMELINE_STATES.lock().unwrap().remove(&room_id);
         let (mut tl_state, first_time_showing_room) = if let Some(existing) = state_opt {
@@ -2270,6 +3431,8 @@ impl RoomScreen {
                 items: Vector::new(),
                 content_drawn_since_last_update: RangeSet::new(),
                 profile_drawn_since_last_update: RangeSet::new(),
+                item_heights: HashMap::new(),
+                expanded_state_event_runs: BTreeSet::new(),
                 update_receiver,
                 request_sender,
                 media_cache: MediaCache::new(MediaFormatConst::File, Some(update_sender)),
@@ -2280,10 +3443,17 @@ impl RoomScreen {
                 prev_first_index: None,
                 scrolled_past_read_marker: false,
                 latest_own_user_receipt: None,
+                pending_fully_read_event: None,
+                read_marker_pinned: false,
+                usernames_colorized: app_settings().colorize_usernames,
             };
             (new_tl_state, true)
         };
 
+        // Show the messages scheduled to be sent to this room, and hide the options of any other room.
+        self.scheduled_messages_list(id!(scheduled_messages_list)).set_room(cx, room_id.clone());
+        self.view(id!(send_later_view)).set_visible(cx, false);
+
         // Subscribe to typing notices, but hide the typing notice view initially.
         self.view(id!(typing_notice)).set_visible(cx, false);
         submit_async_request(
@@ -2298,7 +3468,7 @@ impl RoomScreen {
         // because we want to show the user some messages as soon as possible
         // when they first open the room, and there might not be any messages yet.
         if first_time_showing_room && !tl_state.fully_paginated {
-            log!("Sending a first-time backwards pagination request for room {}", room_id);
+            info!("Sending a first-time backwards pagination request for room {}", room_id);
             submit_async_request(MatrixRequest::PaginateRoomTimeline {
                 room_id: room_id.clone(),
                 num_events: 50,
@@ -2313,6 +3483,10 @@ impl RoomScreen {
 
         // Now, restore the visual state of this timeline from its previously-saved state.
         self.restore_state(cx, &mut tl_state);
+        self.update_call_banner(cx);
+        self.view(id!(topic_popover)).set_visible(cx, false);
+        self.update_topic_bar(cx);
+        self.update_wallpaper(cx);
 
         // As the final step, store the tl_state for this room into this RoomScreen widget,
         // such that it can be accessed in future event/draw handlers.
@@ -2329,6 +3503,86 @@ impl RoomScreen {
         self.redraw(cx);
     }
 
+    /// Shows the group call banner if there is an ongoing group call in this room,
+    /// otherwise hides it.
+    fn update_call_banner(&mut self, cx: &mut Cx) {
+        let has_active_call = self.room_id.as_ref()
+            .and_then(|room_id| get_client()?.get_room(room_id))
+            .is_some_and(|room| room.has_active_room_call());
+        self.view(id!(call_banner)).set_visible(cx, has_active_call);
+        if has_active_call {
+            self.label(id!(call_banner_label)).set_text(cx, tr("room.call.in_progress"));
+            self.button(id!(join_call_button)).set_text(cx, tr("room.call.join"));
+        }
+    }
+
+    /// Shows the first line of the room's topic in the topic bar if the room has a topic,
+    /// otherwise hides the topic bar.
+    ///
+    /// The full topic, with its links made clickable, is kept up-to-date in the topic popover.
+    fn update_topic_bar(&mut self, cx: &mut Cx) {
+        let topic = self.room_id.as_ref()
+            .and_then(|room_id| get_client()?.get_room(room_id))
+            .and_then(|room| room.topic())
+            .filter(|topic| !topic.trim().is_empty());
+        let Some(topic) = topic else {
+            self.view(id!(topic_bar)).set_visible(cx, false);
+            self.view(id!(topic_popover)).set_visible(cx, false);
+            return;
+        };
+        let first_line = topic.trim().lines().next().unwrap_or_default();
+        self.label(id!(topic_label)).set_text(cx, first_line);
+        self.view(id!(topic_bar)).set_visible(cx, true);
+        let full_topic = self.html_or_plaintext(id!(full_topic));
+        match utils::linkify(topic.trim(), false) {
+            Cow::Owned(linkified_html) => full_topic.show_html(cx, utils::mention_pills(&linkified_html)),
+            Cow::Borrowed(plaintext) => full_topic.show_plaintext(cx, plaintext),
+        }
+    }
+
+    /// Applies this room's wallpaper (if it has one) to the background of the timeline.
+    fn update_wallpaper(&mut self, cx: &mut Cx) {
+        self.wallpaper_image_pending = false;
+        let wallpaper_view = self.view(id!(timeline.wallpaper));
+        let Some(wallpaper) = self.room_id.as_deref().and_then(room_wallpaper::room_wallpaper) else {
+            wallpaper_view.set_visible(cx, false);
+            return;
+        };
+        // Without a color, the room screen's default background shows through.
+        let color = wallpaper.color_vec4().unwrap_or(vec4(0.0, 0.0, 0.0, 0.0));
+        let dim = wallpaper.dim.clamp(0.0, 1.0);
+        wallpaper_view.apply_over(cx, live! {
+            draw_bg: { color: (color) }
+            wallpaper_dim = { draw_bg: { color: (vec4(0.0, 0.0, 0.0, dim)) } }
+        });
+        wallpaper_view.set_visible(cx, true);
+
+        let wallpaper_image = self.image(id!(timeline.wallpaper_image));
+        let texture = wallpaper.image_url.zip(self.tl_state.as_mut())
+            .and_then(|(mxc_uri, tl_state)| {
+                match tl_state.media_cache.try_get_media_or_fetch(mxc_uri.clone(), None) {
+                    MediaCacheEntry::Loaded(data) => match image_decoding::get_or_decode_image(cx, &tl_state.room_id, &mxc_uri, &data) {
+                        DecodedImage::Decoded(texture) => Some(texture),
+                        DecodedImage::Decoding => {
+                            self.wallpaper_image_pending = true;
+                            None
+                        }
+                        DecodedImage::Failed(_) => None,
+                    },
+                    MediaCacheEntry::Requested => {
+                        self.wallpaper_image_pending = true;
+                        None
+                    }
+                    MediaCacheEntry::Failed => None,
+                }
+            });
+        wallpaper_image.set_visible(cx, texture.is_some());
+        if texture.is_some() {
+            wallpaper_image.set_texture(cx, texture);
+        }
+        self.redraw(cx);
+    }
+
     /// Invoke this when this RoomScreen/timeline is being hidden or no longer being shown.
     fn hide_timeline(&mut self) {
         let Some(room_id) = self.room_id.clone() else { return };
@@ -2356,6 +3610,9 @@ impl RoomScreen {
             error!("Timeline::save_state(): skipping due to missing state, room {:?}", self.room_id);
             return;
         };
+        // The user is no longer viewing this room, so any delayed read marker move is abandoned.
+        tl.pending_fully_read_event = None;
+        tl.read_marker_pinned = false;
 
         let portal_list = self.portal_list(id!(list));
         let first_index = portal_list.first_id();
@@ -2370,6 +3627,44 @@ impl RoomScreen {
         TIMELINE_STATES.lock().unwrap().insert(tl.room_id.clone(), tl);
     }
 
+    /// Shows autocomplete suggestions for the token being typed at the end of the given message input text:
+    /// emoji after a `:`, rooms after a `#`, the `@room` mention after an `@`, and snippets after a `/`.
+    fn update_autocomplete_suggestions(&mut self, cx: &mut Cx, text: &str) {
+        let suggestions = if let Some((_, query)) = autocomplete::trailing_token(text, autocomplete::EMOJI_TRIGGER) {
+            autocomplete::emoji_suggestions(query)
+        } else if let Some((_, query)) = autocomplete::trailing_token(text, snippets::SNIPPET_TRIGGER) {
+            autocomplete::snippet_suggestions(query, &app_settings().snippets)
+        } else if let Some((_, query)) = autocomplete::trailing_token(text, autocomplete::ROOM_TRIGGER) {
+            let rooms = get_client()
+                .map(|client| client.joined_rooms())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|room| MentionableRoom {
+                    room_id: room.room_id().to_owned(),
+                    name: room.cached_display_name().map(|name| name.to_string()),
+                    canonical_alias: room.canonical_alias(),
+                });
+            autocomplete::room_suggestions(query, rooms)
+        } else if let Some((_, query)) = autocomplete::trailing_token(text, autocomplete::USER_TRIGGER) {
+            let can_notify_room = self.tl_state.as_ref().is_some_and(|tl| tl.user_power.can_notify_room());
+            autocomplete::room_mention_suggestion(query, can_notify_room).into_iter().collect()
+        } else {
+            Vec::new()
+        };
+        self.autocomplete_list(id!(autocomplete_list)).set_suggestions(cx, suggestions);
+    }
+
+    /// Replaces the token being typed at the end of the message input with the given suggestion.
+    fn apply_autocomplete_suggestion(&mut self, cx: &mut Cx, suggestion: Suggestion) {
+        let message_input = self.text_input(id!(message_input));
+        let text = message_input.text();
+        if let Some((start, _)) = autocomplete::trailing_token(&text, suggestion.trigger) {
+            message_input.set_text(cx, &autocomplete::replace_trailing_token(&text, start, &suggestion.replacement));
+        }
+        self.autocomplete_list(id!(autocomplete_list)).set_suggestions(cx, Vec::new());
+        message_input.set_key_focus(cx);
+    }
+
     /// Restores the previously-saved visual UI state of this room.
     ///
     /// Note: this accepts a direct reference to the timeline's UI state,
@@ -2390,8 +3685,9 @@ impl RoomScreen {
         }
 
         let saved_message_input_state = std::mem::take(message_input_state);
-        self.text_input(id!(message_input))
-            .restore_state(saved_message_input_state);
+        let message_input = self.text_input(id!(message_input));
+        message_input.restore_state(saved_message_input_state);
+        self.spelling_bar(id!(spelling_bar)).check(cx, &message_input.text());
         if let Some(replying_to_event) = replying_to.take() {
             self.show_replying_to(cx, replying_to_event);
         } else {
@@ -2416,15 +3712,130 @@ impl RoomScreen {
         self.hide_timeline();
         // Reset the the state of the inner loading pane.
         self.loading_pane(id!(loading_pane)).take_state();
+        self.keyboard_selected_item = None;
         self.room_name = room_name;
+        // Fetch the presence of the other user in a direct message room, which is shown in the rooms list.
+        if let Some(user_id) = get_client()
+            .and_then(|client| client.get_room(&room_id))
+            .and_then(|room| sliding_sync::direct_user_id(&room))
+        {
+            presence::fetch_presence(&user_id);
+        }
         self.room_id = Some(room_id);
         self.show_timeline(cx);
     }
 
+    /// Sends the message that the user has entered into the message input box.
+    ///
+    /// If the message would notify everyone in the room via `@room`,
+    /// the user must first confirm it, which is indicated by `room_mention_confirmed`.
+    fn send_entered_message(&mut self, cx: &mut Cx, room_mention_confirmed: bool) {
+        let message_input = self.text_input(id!(message_input));
+        let entered_text = message_input.text().trim().to_string();
+        if entered_text.is_empty() { return }
+        let Some(room_id) = self.room_id.clone() else { return };
+
+        // Only attach an `@room` mention if the user is allowed to notify the whole room,
+        // otherwise it would be rejected or ignored by other clients.
+        let can_notify_room = self.tl_state.as_ref().is_some_and(|tl| tl.user_power.can_notify_room());
+        let mentions_room = can_notify_room && autocomplete::contains_room_mention(&entered_text);
+        let confirmation = self.view(id!(room_mention_confirmation));
+        if mentions_room && !room_mention_confirmed {
+            let num_notified = get_client()
+                .and_then(|client| client.get_room(&room_id))
+                .map_or(0, |room| room.joined_members_count().saturating_sub(1));
+            self.label(id!(room_mention_confirmation.prompt_label)).set_text(
+                cx,
+                &tr_args("room.room_mention.confirm", &[("count", &num_notified.to_string())]),
+            );
+            self.button(id!(room_mention_confirmation.cancel_button)).set_text(cx, tr("room.room_mention.cancel"));
+            self.button(id!(room_mention_confirmation.confirm_button)).set_text(cx, tr("room.room_mention.send"));
+            confirmation.set_visible(cx, true);
+            self.redraw(cx);
+            return;
+        }
+        confirmation.set_visible(cx, false);
+
+        info!("Sending message to room {}: {:?}", room_id, entered_text);
+        let message = if let Some(html_text) = entered_text.strip_prefix("/html") {
+            RoomMessageEventContent::text_html(html_text, html_text)
+        } else if let Some(plain_text) = entered_text.strip_prefix("/plain") {
+            RoomMessageEventContent::text_plain(plain_text)
+        } else {
+            RoomMessageEventContent::text_markdown(&entered_text)
+        };
+        let message = if mentions_room {
+            message.add_mentions(Mentions::with_room_mention())
+        } else {
+            message
+        };
+        submit_async_request(MatrixRequest::SendMessage {
+            room_id,
+            message,
+            replied_to: self.tl_state.as_mut().and_then(
+                |tl| tl.replying_to.take().map(|(_, rep)| rep)
+            ),
+            // TODO: support attaching mentions, etc.
+        });
+
+        self.clear_replying_to(cx);
+        message_input.set_text(cx, "");
+        self.autocomplete_list(id!(autocomplete_list)).set_suggestions(cx, Vec::new());
+        self.spelling_bar(id!(spelling_bar)).check(cx, "");
+        self.redraw(cx);
+    }
+
+    /// Shows or hides the options for when to send the entered message later.
+    fn show_send_later_view(&mut self, cx: &mut Cx, visible: bool) {
+        let send_later_view = self.view(id!(send_later_view));
+        if visible {
+            self.label(id!(send_later_view.prompt_label)).set_text(cx, tr("scheduled.prompt"));
+            self.button(id!(send_later_view.cancel_button)).set_text(cx, tr("scheduled.cancel"));
+            self.button(id!(send_later_view.schedule_button)).set_text(cx, tr("scheduled.schedule"));
+        }
+        send_later_view.set_visible(cx, visible);
+        self.redraw(cx);
+    }
+
+    /// Schedules the message that the user has entered into the message input box
+    /// to be sent at the time chosen in the "Send later" options.
+    fn schedule_entered_message(&mut self, cx: &mut Cx) {
+        let message_input = self.text_input(id!(message_input));
+        let entered_text = message_input.text().trim().to_string();
+        if entered_text.is_empty() {
+            enqueue_popup_notification(tr("scheduled.empty").to_string());
+            return;
+        }
+        let Some(room_id) = self.room_id.clone() else { return };
+
+        let now = Local::now();
+        let send_at = match SendLaterTime::ALL.get(self.drop_down(id!(send_later_view.time_dropdown)).selected_item()) {
+            Some(SendLaterTime::Custom) | None => scheduled_messages::parse_send_time(
+                &self.text_input(id!(send_later_view.custom_time_input)).text(),
+                now,
+            ),
+            Some(preset) => preset.send_at(now),
+        };
+        let Some(send_at) = send_at.and_then(scheduled_messages::to_timestamp) else {
+            enqueue_popup_notification(tr("scheduled.invalid_time").to_string());
+            return;
+        };
+        match scheduled_messages::schedule(room_id, entered_text, send_at) {
+            Ok(()) => {
+                enqueue_popup_notification(tr("scheduled.scheduled").to_string());
+                message_input.set_text(cx, "");
+                self.autocomplete_list(id!(autocomplete_list)).set_suggestions(cx, Vec::new());
+                self.spelling_bar(id!(spelling_bar)).check(cx, "");
+                self.show_send_later_view(cx, false);
+            }
+            Err(e) => enqueue_popup_notification(tr_args("scheduled.schedule_failed", &[("error", &e.to_string())])),
+        }
+    }
+
     /// Sends read receipts based on the current scroll position of the timeline.
     fn send_user_read_receipts_based_on_scroll_pos(
         &mut self,
-        _cx: &mut Cx,
+        cx: &mut Cx,
         actions: &ActionsBuf,
         portal_list: &PortalListRef,
     ) {
@@ -2434,6 +3845,7 @@ impl RoomScreen {
         }
         let first_index = portal_list.first_id();
         let Some(tl_state) = self.tl_state.as_mut() else { return };
+        let mut fully_read_event = None;
 
         if let Some(ref mut index) = tl_state.prev_first_index {
             // to detect change of scroll when scroll ends
@@ -2449,15 +3861,15 @@ impl RoomScreen {
                         *index = first_index;
                         return;
                     };
+                    // If the user doesn't want to share read receipts (in this room or at all),
+                    // we still send a private one such that the room's unread message count is properly updated.
                     submit_async_request(MatrixRequest::ReadReceipt {
                         room_id: tl_state.room_id.clone(),
                         event_id: last_event_id.to_owned(),
+                        private: !app_settings().send_read_receipts_in(&tl_state.room_id),
                     });
                     if tl_state.scrolled_past_read_marker {
-                        submit_async_request(MatrixRequest::FullyReadReceipt {
-                            room_id: tl_state.room_id.clone(),
-                            event_id: last_event_id.to_owned(),
-                        });
+                        fully_read_event = Some(last_event_id.to_owned());
                     } else {
                         if let Some(own_user_receipt_timestamp) = &tl_state.latest_own_user_receipt.clone()
                         .and_then(|receipt| receipt.ts) {
@@ -2474,10 +3886,7 @@ impl RoomScreen {
                                 && own_user_receipt_timestamp <= &last_timestamp
                             {
                                 tl_state.scrolled_past_read_marker = true;
-                                submit_async_request(MatrixRequest::FullyReadReceipt {
-                                    room_id: tl_state.room_id.clone(),
-                                    event_id: last_event_id.to_owned(),
-                                });
+                                fully_read_event = Some(last_event_id.to_owned());
                             }
 
                         }
@@ -2488,6 +3897,36 @@ impl RoomScreen {
         } else {
             tl_state.prev_first_index = Some(first_index);
         }
+
+        if let Some(event_id) = fully_read_event {
+            self.advance_read_marker(cx, event_id);
+        }
+    }
+
+    /// Moves the read marker forward to the given event that the user has scrolled to,
+    /// either immediately or after a delay, depending on the app settings.
+    fn advance_read_marker(&mut self, cx: &mut Cx, event_id: OwnedEventId) {
+        let Some(tl_state) = self.tl_state.as_mut() else { return };
+        if tl_state.read_marker_pinned {
+            return;
+        }
+        let settings = app_settings();
+        match settings.read_marker_clearing {
+            ReadMarkerClearing::Immediately => {
+                submit_async_request(MatrixRequest::FullyReadReceipt {
+                    room_id: tl_state.room_id.clone(),
+                    event_id,
+                });
+            }
+            ReadMarkerClearing::AfterDelay => {
+                tl_state.pending_fully_read_event = Some(event_id);
+                if self.read_marker_timer.is_empty() {
+                    self.read_marker_timer = cx.start_timeout(settings.read_marker_delay_secs as f64);
+                }
+            }
+            // The read marker is only moved by the "Mark as Read" room action.
+            ReadMarkerClearing::OnlyWhenMarkedRead => { }
+        }
     }
 
     /// Sends a backwards pagination request if the user is scrolling up
@@ -2504,7 +3943,7 @@ impl RoomScreen {
 
         let first_index = portal_list.first_id();
         if first_index == 0 && tl.last_scrolled_index > 0 {
-            log!("Scrolled up from item {} --> 0, sending back pagination request for room {}",
+            info!("Scrolled up from item {} --> 0, sending back pagination request for room {}",
                 tl.last_scrolled_index, tl.room_id,
             );
             submit_async_request(MatrixRequest::PaginateRoomTimeline {
@@ -2569,6 +4008,22 @@ pub enum RoomScreenTooltipActions {
         /// If false, it is pointing left
         pointing_up: bool
     },
+    /// Mouse over event when the mouse is over a message's timestamp.
+    HoverInTimestamp {
+        tooltip_pos: DVec2,
+        tooltip_width: f64,
+        /// Pointed arrow position relative to the tooltip.
+        ///
+        /// It is calculated from the right corner of tooltip to position arrow
+        /// to point towards the center of the hovered widget.
+        callout_offset: f64,
+        /// The full date, time, and delivery details of the message.
+        text: String,
+        /// Boolean indicating if the callout should be pointing up.
+        ///
+        /// If false, it is pointing left
+        pointing_up: bool
+    },
     /// Mouse out event and clear tooltip.
     HoverOut,
     None,
@@ -2633,13 +4088,19 @@ pub enum TimelineUpdate {
     /// though the success or failure of the request is not yet known until the client
     /// requests the member info via a timeline event's `sender_profile()` method.
     RoomMembersFetched,
+    /// A notice that the bridges set up in this room have been fetched,
+    /// such that bridged users' badges must be redrawn.
+    RoomBridgesFetched,
+    /// A notice that this room's wallpaper has been fetched or changed,
+    /// such that it must be re-applied to the timeline background.
+    RoomWallpaperChanged,
     /// A notice that one or more requested media items (images, videos, etc.)
     /// that should be displayed in this timeline have now been fetched and are available.
     MediaFetched,
     /// A notice that one or more members of a this room are currently typing.
     TypingUsers {
-        /// The list of users (their displayable name) who are currently typing in this room.
-        users: Vec<String>,
+        /// The list of users (their user ID and displayable name) who are currently typing in this room.
+        users: Vec<(OwnedUserId, String)>,
     },
     /// An update containing the currently logged-in user's power levels for this room.
     UserPowerLevels(UserPowerLevels),
@@ -2650,6 +4111,18 @@ pub enum TimelineUpdate {
 /// The global set of all timeline states, one entry per room.
 static TIMELINE_STATES: Mutex<BTreeMap<OwnedRoomId, TimelineUiState>> = Mutex::new(BTreeMap::new());
 
+/// The events that a room's timeline should jump to once it's shown, one entry per room.
+static PENDING_JUMP_TARGETS: Mutex<BTreeMap<OwnedRoomId, OwnedEventId>> = Mutex::new(BTreeMap::new());
+
+/// Requests that the timeline of the given room jumps to the given event,
+/// either right away if that room is currently shown, or once it has been shown.
+///
+/// The room itself must be separately selected in order to be shown.
+pub fn jump_to_event(room_id: OwnedRoomId, event_id: OwnedEventId) {
+    PENDING_JUMP_TARGETS.lock().unwrap().insert(room_id, event_id);
+    SignalToUI::set_ui_signal();
+}
+
 /// The UI-side state of a single room's timeline, which is only accessed/updated by the UI thread.
 ///
 /// This struct should only include states that need to be persisted for a given room
@@ -2691,6 +4164,20 @@ struct TimelineUiState {
     /// Same as `content_drawn_since_last_update`, but for the event **profiles** (avatar, username).
     profile_drawn_since_last_update: RangeSet<usize>,
 
+    /// The most recently measured height of each event item that has been drawn, keyed by event ID.
+    ///
+    /// Unlike item indices, event IDs remain stable when items are inserted into
+    /// or removed from the timeline, so these heights remain valid across updates.
+    /// They're used to estimate the position of items that aren't currently drawn,
+    /// e.g., when jumping to a target event.
+    item_heights: HashMap<OwnedEventId, f64>,
+
+    /// The runs of consecutive membership and profile changes that the user has expanded,
+    /// keyed by the event ID of the first event in each run.
+    ///
+    /// All other runs that are long enough are collapsed into a single summary row.
+    expanded_state_event_runs: BTreeSet<OwnedEventId>,
+
     /// The channel receiver for timeline updates for this room.
     ///
     /// Here we use a synchronous (non-async) channel because the receiver runs
@@ -2747,6 +4234,34 @@ struct TimelineUiState {
     /// When new message come in, this value is reset to `false`.
     scrolled_past_read_marker: bool,
     latest_own_user_receipt: Option<Receipt>,
+    /// The event that the read marker will be moved to once the `read_marker_timer` fires,
+    /// if the read marker is set to clear after a delay.
+    pending_fully_read_event: Option<OwnedEventId>,
+    /// Whether the user manually moved the read marker while viewing this room,
+    /// in which case it isn't automatically moved again until the room is reopened.
+    read_marker_pinned: bool,
+    /// Whether usernames were colorized when this timeline's items were last drawn,
+    /// such that they can be redrawn if that setting has since changed.
+    usernames_colorized: bool,
+}
+
+impl TimelineUiState {
+    /// Returns the estimated height of the timeline item at the given index.
+    ///
+    /// This is the item's most recently measured height if it has been drawn before,
+    /// otherwise the average height of all measured items (if any).
+    fn estimated_item_height(&self, index: usize) -> Option<f64> {
+        let measured = self.items.get(index)
+            .and_then(|item| item.as_event())
+            .and_then(|ev| ev.event_id())
+            .and_then(|event_id| self.item_heights.get(event_id));
+        if let Some(height) = measured {
+            return Some(*height);
+        }
+        (!self.item_heights.is_empty()).then(||
+            self.item_heights.values().sum::<f64>() / self.item_heights.len() as f64
+        )
+    }
 }
 
 #[derive(Default, Debug)]
@@ -2822,7 +4337,7 @@ fn find_new_item_matching_current_item(
             // some may be zeroed-out, so we need to account for that possibility by only
             // using events that have a real non-zero area
             if let Some(pos_offset) = portal_list.position_of_item(cx, *idx_curr) {
-                log!("Found matching event ID {event_id} at index {idx_new} in new items list, corresponding to current item index {idx_curr} at pos offset {pos_offset}");
+                info!("Found matching event ID {event_id} at index {idx_new} in new items list, corresponding to current item index {idx_curr} at pos offset {pos_offset}");
                 return Some((*idx_curr, idx_new, pos_offset, event_id.to_owned()));
             }
         }
@@ -2964,6 +4479,17 @@ impl MessageOrStickerType<'_> {
 }
 
 
+/// Colors the given username label with the given sender's color if usernames are colorized,
+/// otherwise with the default username color.
+fn set_username_color(cx: &mut Cx, username_label: &LabelRef, sender: &UserId) {
+    if app_settings().colorize_usernames {
+        let color = utils::username_color(sender);
+        username_label.apply_over(cx, live!(draw_text: { color: (color) }));
+    } else {
+        username_label.apply_over(cx, live!(draw_text: { color: (USERNAME_TEXT_COLOR) }));
+    }
+}
+
 /// Creates, populates, and adds a Message liveview widget to the given `PortalList`
 /// with the given `item_id`.
 ///
@@ -3176,6 +4702,7 @@ fn populate_message_view(
                 let image_info = mtype.get_image_info();
                 let is_image_fully_drawn = populate_image_message_content(
                     cx,
+                    room_id,
                     &item.text_or_image(id!(content.message)),
                     image_info,
                     message.body(),
@@ -3356,8 +4883,15 @@ fn populate_message_view(
                         color: (MESSAGE_NOTICE_TEXT_COLOR),
                     }
                 ));
+            } else {
+                set_username_color(cx, &username_label, event_tl_item.sender());
             }
             username_label.set_text(cx, &username);
+            let bridged_platform = bridges::bridged_platform(event_tl_item.sender(), Some(room_id));
+            item.view(id!(content.bridge_badge)).set_visible(cx, bridged_platform.is_some());
+            if let Some(platform) = bridged_platform {
+                item.label(id!(content.bridge_badge.bridge_label)).set_text(cx, &platform);
+            }
             new_drawn_status.profile_drawn = profile_drawn;
         }
         else {
@@ -3369,6 +4903,7 @@ fn populate_message_view(
                     draw_bg: { background_color: (COLOR_DANGER_RED), }
                 }
             ));
+            item.view(id!(content.bridge_badge)).set_visible(cx, false);
             username_label.set_text(cx, "Server notice");
             username_label.apply_over(cx, live!(
                 draw_text: {
@@ -3399,18 +4934,20 @@ fn populate_message_view(
         mentions_user: does_message_mention_current_user(&message),
     });
 
-    // Set the timestamp.
+    populate_delivery_status(cx, &item.view(id!(content.delivery_status)), event_tl_item);
+
+    // Set the timestamp, along with a tooltip showing its full date and delivery details.
+    let tooltip_text = timestamp_tooltip_text(event_tl_item, &message);
     if let Some(dt) = unix_time_millis_to_datetime(&ts_millis) {
-        // format as AM/PM 12-hour time
-        item.label(id!(profile.timestamp))
-            .set_text(cx, &format!("{}", dt.time().format("%l:%M %P")));
+        item.timestamp(id!(profile.timestamp))
+            .set_timestamp_and_tooltip(cx, ts_millis, Some(tooltip_text));
         if !use_compact_view {
-            item.label(id!(profile.datestamp))
-                .set_text(cx, &format!("{}", dt.date_naive()));
+            item.timestamp(id!(profile.datestamp))
+                .set_text_and_tooltip(cx, &format!("{}", dt.date_naive()), None);
         }
     } else {
-        item.label(id!(profile.timestamp))
-            .set_text(cx, &format!("{}", ts_millis.get()));
+        item.timestamp(id!(profile.timestamp))
+            .set_text_and_tooltip(cx, &format!("{}", ts_millis.get()), Some(tooltip_text));
     }
 
     (item, new_drawn_status)
@@ -3427,11 +4964,11 @@ fn does_message_mention_current_user(
 
     match message {
         // This covers both direct mentions ("@user"), @room mentions, and a replied-to message.
-        MessageOrSticker::Message(msg) => {
-            msg.mentions().is_some_and(|mentions|
-                mentions.room || mentions.user_ids.contains(&current_user_id)
-            )
-        }
+        MessageOrSticker::Message(msg) => match msg.mentions() {
+            Some(mentions) => mentions.room || mentions.user_ids.contains(&current_user_id),
+            // Older clients don't send intentional mentions, so fall back to the message body.
+            None => autocomplete::contains_room_mention(msg.body()),
+        },
         MessageOrSticker::Sticker(_) => false, // Stickers can't mention users.
     }
 }
@@ -3443,17 +4980,19 @@ fn populate_text_message_content(
     body: &str,
     formatted_body: Option<&FormattedBody>,
 ) {
+    // The message consists solely of a few emoji, which we show at a large size.
+    if utils::is_large_emoji_text(body) {
+        message_content_widget.show_large_emoji(cx, body.trim());
+    }
     // The message was HTML-formatted rich text.
-    if let Some(fb) = formatted_body.as_ref()
+    else if let Some(fb) = formatted_body.as_ref()
         .and_then(|fb| (fb.format == MessageFormat::Html).then_some(fb))
     {
-        message_content_widget.show_html(
-            cx,
-            utils::linkify(
-                utils::trim_start_html_whitespace(&fb.body),
-                true,
-            )
+        let linkified_html = utils::linkify(
+            utils::trim_start_html_whitespace(&fb.body),
+            true,
         );
+        message_content_widget.show_html(cx, utils::mention_pills(&linkified_html));
     }
     // The message was non-HTML plaintext.
     else {
@@ -3469,6 +5008,7 @@ fn populate_text_message_content(
 /// Returns whether the image message content was fully drawn.
 fn populate_image_message_content(
     cx: &mut Cx2d,
+    room_id: &OwnedRoomId,
     text_or_image_ref: &TextOrImageRef,
     image_info_source: Option<(Option<ImageInfo>, MediaSource)>,
     body: &str,
@@ -3495,24 +5035,40 @@ fn populate_image_message_content(
     }
 
     let mut fully_drawn = false;
+    let auto_download = network_status::should_auto_download_media();
 
     // A closure that fetches and shows the image from the given `mxc_uri`,
     // marking it as fully drawn if the image was available.
     let mut fetch_and_show_image_uri = |cx: &mut Cx2d, mxc_uri: OwnedMxcUri| {
+        // If media shouldn't be downloaded automatically, show a placeholder
+        // that the user can tap to download the image, see `MessageAction::DownloadMedia`.
+        if !auto_download && media_cache.try_get_media(&mxc_uri).is_none() {
+            text_or_image_ref.show_text(cx, format!("{body}\n\n{}", tr("media.tap_to_download_image")));
+            fully_drawn = true;
+            return;
+        }
         match media_cache.try_get_media_or_fetch(mxc_uri.clone(), Some(MEDIA_THUMBNAIL_FORMAT.into())) {
             MediaCacheEntry::Loaded(data) => {
-                let show_image_result = text_or_image_ref.show_image(cx, |cx, img| {
-                    utils::load_png_or_jpg(&img, cx, &data)
-                        .map(|()| img.size_in_pixels(cx).unwrap_or_default())
-                });
-                if let Err(e) = show_image_result {
-                    let err_str = format!("{body}\n\nFailed to display image: {e:?}");
-                    error!("{err_str}");
-                    text_or_image_ref.show_text(cx, &err_str);
+                // Large images are decoded on a background thread to avoid stalling this draw routine.
+                match image_decoding::get_or_decode_image(cx, room_id, &mxc_uri, &data) {
+                    DecodedImage::Decoding => {
+                        text_or_image_ref.show_text(cx, format!("{body}\n\n{}", tr("media.loading_image")));
+                        // Do not consider this image as being fully drawn, as it's still being decoded.
+                        fully_drawn = false;
+                    }
+                    DecodedImage::Decoded(texture) => {
+                        let _ = text_or_image_ref.show_image(cx, |cx, img| {
+                            img.set_texture(cx, Some(texture));
+                            Ok::<_, Infallible>(img.size_in_pixels(cx).unwrap_or_default())
+                        });
+                        // We're done drawing the image, so mark it as fully drawn.
+                        fully_drawn = true;
+                    }
+                    DecodedImage::Failed(e) => {
+                        text_or_image_ref.show_text(cx, format!("{body}\n\nFailed to display image: {e}"));
+                        fully_drawn = true;
+                    }
                 }
-
-                // We're done drawing the image, so mark it as fully drawn.
-                fully_drawn = true;
             }
             MediaCacheEntry::Requested => {
                 text_or_image_ref.show_text(cx, format!("{body}\n\nFetching image from {:?}", mxc_uri));
@@ -3546,11 +5102,7 @@ fn populate_image_message_content(
 
     match image_info_source {
         Some((image_info, original_source)) => {
-            // Use the provided thumbnail URI if it exists; otherwise use the original URI.
-            let media_source = image_info
-                .and_then(|image_info| image_info.thumbnail_source)
-                .unwrap_or(original_source);
-            fetch_and_show_media_source(cx, media_source);
+            fetch_and_show_media_source(cx, preferred_image_source(image_info, original_source));
         }
         None => {
             text_or_image_ref.show_text(cx, "{body}\n\nImage message had no source URL.");
@@ -3562,6 +5114,14 @@ fn populate_image_message_content(
 }
 
 
+/// Returns the source from which an image is fetched for display in the timeline:
+/// its thumbnail if it has one, otherwise the original image.
+fn preferred_image_source(image_info: Option<ImageInfo>, original_source: MediaSource) -> MediaSource {
+    image_info
+        .and_then(|image_info| image_info.thumbnail_source)
+        .unwrap_or(original_source)
+}
+
 /// Draws a file message's content into the given `message_content_widget`.
 ///
 /// Returns whether the file message content was fully drawn.
@@ -3757,9 +5317,10 @@ fn draw_replied_to_message(
 
                 fully_drawn = is_avatar_fully_drawn;
 
-                replied_to_message_view
-                    .label(id!(replied_to_message_content.reply_preview_username))
-                    .set_text(cx, in_reply_to_username.as_str());
+                let username_label = replied_to_message_view
+                    .label(id!(replied_to_message_content.reply_preview_username));
+                username_label.set_text(cx, in_reply_to_username.as_str());
+                set_username_color(cx, &username_label, replied_to_event.sender());
                 let msg_body = replied_to_message_view.html_or_plaintext(id!(reply_preview_body));
                 populate_preview_of_timeline_item(
                     cx,
@@ -3866,6 +5427,111 @@ trait SmallStateEventContent {
     ) -> (WidgetRef, ItemDrawnStatus);
 }
 
+/// The minimum number of consecutive membership and profile changes
+/// that are collapsed into a single summary row.
+const MIN_STATE_EVENT_RUN_LENGTH: usize = 3;
+
+/// A run of consecutive membership and profile changes in a timeline,
+/// e.g., "Alice, Bob, and 12 others joined".
+struct StateEventRun {
+    /// The range of timeline item indices in this run.
+    range: Range<usize>,
+    /// Whether the user has expanded this run to show all of its events.
+    is_expanded: bool,
+    /// The displayable summary of all changes in this run.
+    summary: String,
+}
+
+/// The kind of change that a membership or profile change event represents,
+/// used to summarize a run of such events.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum StateEventRunChange {
+    Joined,
+    Left,
+    Profile,
+    Other,
+}
+
+impl StateEventRun {
+    /// Returns the run of membership and profile changes that contains the item at the given index,
+    /// if that item is such a change and the run is at least [`MIN_STATE_EVENT_RUN_LENGTH`] long.
+    fn find(
+        items: &Vector<Arc<TimelineItem>>,
+        index: usize,
+        expanded_runs: &BTreeSet<OwnedEventId>,
+    ) -> Option<Self> {
+        let in_run = |i: usize| items.get(i).and_then(|item| state_event_run_change(item)).is_some();
+        if !in_run(index) {
+            return None;
+        }
+        let mut start = index;
+        while start > 0 && in_run(start - 1) {
+            start -= 1;
+        }
+        let mut end = index + 1;
+        while in_run(end) {
+            end += 1;
+        }
+        if end - start < MIN_STATE_EVENT_RUN_LENGTH {
+            return None;
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        let mut changes = Vec::with_capacity(end - start);
+        for item in items.iter().skip(start).take(end - start) {
+            let Some((change, name)) = state_event_run_change(item) else { continue };
+            if !names.contains(&name) {
+                names.push(name);
+            }
+            changes.push(change);
+        }
+        let names = utils::human_readable_list(&names, 2);
+        let summary = match changes[0] {
+            StateEventRunChange::Joined if changes.iter().all(|c| *c == StateEventRunChange::Joined) =>
+                tr_args("room.state_event_run.joined", &[("names", &names)]),
+            StateEventRunChange::Left if changes.iter().all(|c| *c == StateEventRunChange::Left) =>
+                tr_args("room.state_event_run.left", &[("names", &names)]),
+            StateEventRunChange::Profile if changes.iter().all(|c| *c == StateEventRunChange::Profile) =>
+                tr_args("room.state_event_run.changed_profiles", &[("names", &names)]),
+            _ => tr_args(
+                "room.state_event_run.mixed",
+                &[("count", &changes.len().to_string()), ("names", &names)],
+            ),
+        };
+        let is_expanded = items.get(start)
+            .and_then(|item| item.as_event())
+            .and_then(|ev| ev.event_id())
+            .is_some_and(|event_id| expanded_runs.contains(event_id));
+        Some(Self { range: start..end, is_expanded, summary })
+    }
+}
+
+/// Returns the kind of change and the displayable name of the affected user
+/// if the given timeline item is a membership or profile change that can be part of a run.
+///
+/// Membership changes that aren't displayed at all end a run.
+fn state_event_run_change(item: &TimelineItem) -> Option<(StateEventRunChange, String)> {
+    let event_tl_item = item.as_event()?;
+    match event_tl_item.content() {
+        TimelineItemContent::MembershipChange(change) => {
+            text_preview_of_room_membership_change(change)?;
+            let kind = match change.change() {
+                Some(MembershipChange::Joined | MembershipChange::InvitationAccepted) => StateEventRunChange::Joined,
+                Some(MembershipChange::Left) => StateEventRunChange::Left,
+                _ => StateEventRunChange::Other,
+            };
+            let name = change.display_name().unwrap_or_else(|| change.user_id().to_string());
+            Some((kind, name))
+        }
+        TimelineItemContent::ProfileChange(_) => Some((
+            StateEventRunChange::Profile,
+            get_profile_display_name(event_tl_item)
+                .unwrap_or_else(|| event_tl_item.sender().to_string()),
+        )),
+        _ => None,
+    }
+}
+
 /// An empty marker struct used for populating redacted messages.
 struct RedactedMessageEventMarker;
 
@@ -3981,6 +5647,10 @@ fn populate_small_state_event(
 ) -> (WidgetRef, ItemDrawnStatus) {
     let mut new_drawn_status = item_drawn_status;
     let (item, existed) = list.item_with_existed(cx, item_id, live_id!(SmallStateEvent));
+    // This item may have previously been the first item of a run of state events,
+    // so reset it to show only its own event, which is adjusted afterwards if needed.
+    item.view(id!(run_summary)).set_visible(cx, false);
+    item.view(id!(body)).set_visible(cx, true);
     // The content of a small state event view may depend on the profile info,
     // so we can only mark the content as drawn after the profile has been fully drawn and cached.
     let skip_redrawing_profile = existed && item_drawn_status.profile_drawn;
@@ -4030,22 +5700,72 @@ fn populate_small_state_event(
     )
 }
 
-/// Sets the text of the `Label` at the given `item`'s live ID path
-/// to a typical 12-hour AM/PM timestamp format.
+/// Sets the `Timestamp` at the given `item`'s live ID path to show the given time,
+/// either relative to now or in a typical 12-hour AM/PM format; see [`utils::timeline_timestamp_text()`].
 fn set_timestamp(
     cx: &mut Cx,
     item: &WidgetRef,
     live_id_path: &[LiveId],
     timestamp: MilliSecondsSinceUnixEpoch,
 ) {
-    if let Some(dt) = unix_time_millis_to_datetime(&timestamp) {
-        // format as AM/PM 12-hour time
-        item.label(live_id_path)
-            .set_text(cx, &format!("{}", dt.time().format("%l:%M %P")));
+    item.timestamp(live_id_path).set_timestamp_and_tooltip(cx, timestamp, None);
+}
+
+/// Returns the text of the tooltip shown when hovering over a message's timestamp,
+/// which includes the message's full local date and time, its origin server timestamp,
+/// and whether it has been sent or edited.
+fn timestamp_tooltip_text(event_tl_item: &EventTimelineItem, message: &MessageOrSticker) -> String {
+    let ts_millis = event_tl_item.timestamp();
+    let mut lines = Vec::with_capacity(4);
+    if let Some(dt) = unix_time_millis_to_datetime(&ts_millis) {
+        lines.push(dt.format("%A, %B %-d, %Y, %l:%M:%S %P").to_string());
+        lines.push(tr_args("timestamp.server_time", &[
+            ("time", &dt.to_utc().format("%Y-%m-%d %H:%M:%S UTC").to_string()),
+            ("millis", &ts_millis.get().to_string()),
+        ]));
+    }
+    let status_key = match event_tl_item.send_state() {
+        Some(EventSendState::NotSentYet) => "timestamp.sending",
+        Some(EventSendState::SendingFailed { .. }) => "timestamp.send_failed",
+        Some(EventSendState::Sent { .. }) => "timestamp.sent",
+        None if event_tl_item.is_own() => "timestamp.sent",
+        None => "timestamp.received",
+    };
+    lines.push(tr(status_key).to_string());
+    if matches!(message, MessageOrSticker::Message(msg) if msg.is_edited()) {
+        lines.push(tr("timestamp.edited").to_string());
+    }
+    lines.join("\n")
+}
+
+/// Shows the delivery status of the given event if it is a local echo of the current user's message,
+/// i.e., a message that hasn't yet been acknowledged by the homeserver.
+///
+/// Messages that failed to send are shown with buttons to retry sending them or to delete them.
+fn populate_delivery_status(cx: &mut Cx, delivery_status: &ViewRef, event_tl_item: &EventTimelineItem) {
+    let (status_key, failed) = match event_tl_item.send_state() {
+        Some(EventSendState::NotSentYet) => ("timestamp.sending", false),
+        Some(EventSendState::SendingFailed { .. }) => ("timestamp.send_failed", true),
+        Some(EventSendState::Sent { .. }) => ("timestamp.sent", false),
+        None => {
+            delivery_status.set_visible(cx, false);
+            return;
+        }
+    };
+    delivery_status.set_visible(cx, true);
+    let status_label = delivery_status.label(id!(status_label));
+    status_label.set_text(cx, tr(status_key));
+    if failed {
+        status_label.apply_over(cx, live!(draw_text: { color: (COLOR_DANGER_RED) }));
     } else {
-        item.label(live_id_path)
-            .set_text(cx, &format!("{}", timestamp.get()));
+        status_label.apply_over(cx, live!(draw_text: { color: (TIMESTAMP_TEXT_COLOR) }));
     }
+    let retry_button = delivery_status.button(id!(retry_button));
+    retry_button.set_visible(cx, failed);
+    retry_button.set_text(cx, tr("message.retry_send"));
+    let delete_button = delivery_status.button(id!(delete_button));
+    delete_button.set_visible(cx, failed);
+    delete_button.set_text(cx, tr("message.delete_unsent"));
 }
 
 /// Returns the display name of the sender of the given `event_tl_item`, if available.
@@ -4175,6 +5895,9 @@ pub enum MessageAction {
     },
     /// The user clicked the "reply" button on a message.
     Reply(MessageDetails),
+    /// The user clicked the "quote" button on a message,
+    /// which inserts that message into the message input as a Markdown block quote.
+    Quote(MessageDetails),
     /// The user clicked the "edit" button on a message.
     Edit(MessageDetails),
     /// The user clicked the "pin" button on a message.
@@ -4189,10 +5912,27 @@ pub enum MessageAction {
     CopyLink(MessageDetails),
     /// The user clicked the "view source" button on a message.
     ViewSource(MessageDetails),
+    /// The user tapped an image placeholder to download the image,
+    /// which wasn't downloaded automatically due to the media auto-download setting.
+    DownloadMedia(MessageDetails),
+    /// The user hovered over or long-pressed the replied-to message preview above a message,
+    /// and wants to see a larger popup of that replied-to message.
+    ShowRepliedToMessage {
+        details: MessageDetails,
+        /// The absolute position of the hover or long press.
+        abs_pos: DVec2,
+    },
+    /// The popup of a replied-to message should be hidden.
+    HideRepliedToMessage,
     /// The user clicked the "jump to related" button on a message,
     /// indicating that they want to auto-scroll back to the related message,
     /// e.g., a replied-to message.
     JumpToRelated(MessageDetails),
+    /// The user clicked the "move read marker here" button on a message,
+    /// indicating that they want the "New Messages" marker to be placed after that message.
+    MoveReadMarker(MessageDetails),
+    /// The user clicked the "retry" button on a message that failed to send.
+    RetrySend(MessageDetails),
     /// The user clicked the "delete" button on a message.
     #[doc(alias("delete"))]
     Redact {
@@ -4205,6 +5945,12 @@ pub enum MessageAction {
 
     /// The message at the given item index in the timeline should be highlighted.
     HighlightMessage(usize),
+    /// A key was pressed while the message at the given item index in the timeline
+    /// was selected via keyboard navigation.
+    KeyPressedOnSelected {
+        item_id: usize,
+        key_code: KeyCode,
+    },
     /// The user requested that we show a context menu with actions
     /// that can be performed on a given message.
     OpenMessageContextMenu {
@@ -4241,8 +5987,16 @@ pub struct Message {
     #[rust] long_press_timer: Timer,
     /// The current status of the long-press gesture on the message body.
     #[rust] long_press_state: LongPressState,
+    /// A timer used to show the replied-to message popup after briefly hovering over the reply preview.
+    #[rust] reply_hover_timer: Timer,
+    /// The position at which the pointer is hovering over the reply preview, if it is.
+    #[rust] reply_hover_pos: Option<DVec2>,
 
     #[rust] details: Option<MessageDetails>,
+    /// The display density that this message's sizes and spacings were last adjusted for.
+    #[rust] applied_density: Option<DisplayDensity>,
+    /// Whether this message is currently selected via keyboard navigation of the timeline.
+    #[rust] keyboard_selected: bool,
 }
 
 impl Widget for Message {
@@ -4259,6 +6013,17 @@ impl Widget for Message {
 
         let Some(details) = self.details.clone() else { return };
 
+        // Show the replied-to message popup once the pointer has hovered over the reply preview for a moment.
+        if self.reply_hover_timer.is_event(event).is_some() {
+            if let Some(abs_pos) = self.reply_hover_pos {
+                cx.widget_action(
+                    details.room_screen_widget_uid,
+                    &scope.path,
+                    MessageAction::ShowRepliedToMessage { details: details.clone(), abs_pos },
+                );
+            }
+        }
+
         /// 500ms long press is default on Android/iOS
         const LONG_PRESS_DURATION: f64 = 0.500;
 
@@ -4269,13 +6034,22 @@ impl Widget for Message {
         // Then, we handle other context menu events: a long press start/end, and right-click.
         if let LongPressState::Pressing(abs_pos) = &self.long_press_state {
             if self.long_press_timer.is_event(event).is_some() {
-                cx.widget_action(
-                    details.room_screen_widget_uid,
-                    &scope.path,
+                // A long press on the replied-to message preview shows that message in a popup.
+                let action = if self.is_over_replied_to_message(cx, *abs_pos) {
+                    MessageAction::ShowRepliedToMessage {
+                        details: details.clone(),
+                        abs_pos: *abs_pos,
+                    }
+                } else {
                     MessageAction::OpenMessageContextMenu {
                         details: details.clone(),
                         abs_pos: *abs_pos,
                     }
+                };
+                cx.widget_action(
+                    details.room_screen_widget_uid,
+                    &scope.path,
+                    action,
                 );
                 cx.stop_timer(self.long_press_timer);
                 self.long_press_state = LongPressState::None;
@@ -4361,25 +6135,95 @@ impl Widget for Message {
                         MessageAction::JumpToRelated(details.clone()),
                     );
                 }
+                // If the hit occurred on an image that is shown as text (e.g., a placeholder), download it.
+                let text_or_image = self.text_or_image(id!(content.message));
+                let is_image_text_shown = text_or_image.borrow()
+                    .is_some_and(|t| t.status() == TextOrImageStatus::Text);
+                if fe.is_primary_hit() && is_image_text_shown && text_or_image.area().rect(cx).contains(fe.abs) {
+                    cx.widget_action(
+                        details.room_screen_widget_uid,
+                        &scope.path,
+                        MessageAction::DownloadMedia(details.clone()),
+                    );
+                }
+            }
+            // A rightwards swipe on a touch screen replies to this message.
+            // Swipes that start at the left edge of the screen are reserved for navigating back.
+            Hit::FingerUp(fe) if fe.device.is_touch()
+                && fe.abs_start.x > SWIPE_BACK_EDGE_WIDTH
+                && horizontal_swipe(fe.abs_start, fe.abs) == Some(SwipeDirection::Right) =>
+            {
+                cx.stop_timer(self.long_press_timer);
+                self.long_press_state = LongPressState::None;
+                if details.abilities.contains(MessageAbilities::CanReplyTo) {
+                    cx.widget_action(
+                        details.room_screen_widget_uid,
+                        &scope.path,
+                        MessageAction::Reply(details.clone()),
+                    );
+                }
             }
             // a long press has ended
             Hit::FingerUp(_) | Hit::FingerMove(_) => {
                 cx.stop_timer(self.long_press_timer);
                 self.long_press_state = LongPressState::None;
             }
-            Hit::FingerHoverIn(_fhi) => {
+            Hit::FingerHoverIn(fhi) => {
                 self.animator_play(cx, id!(hover.on));
+                self.update_reply_hover(cx, scope, &details, Some(fhi.abs));
                 // TODO: here, show the "action bar" buttons upon hover-in
             }
+            Hit::FingerHoverOver(fho) => {
+                self.update_reply_hover(cx, scope, &details, Some(fho.abs));
+            }
             Hit::FingerHoverOut(_fho) => {
                 self.animator_play(cx, id!(hover.off));
+                self.update_reply_hover(cx, scope, &details, None);
                 // TODO: here, hide the "action bar" buttons upon hover-out
             }
             _ => { }
         }
 
         if let Event::Actions(actions) = event {
+            if self.button(id!(delivery_status.retry_button)).clicked(actions) {
+                cx.widget_action(
+                    details.room_screen_widget_uid,
+                    &scope.path,
+                    MessageAction::RetrySend(details.clone()),
+                );
+            }
+            if self.button(id!(delivery_status.delete_button)).clicked(actions) {
+                cx.widget_action(
+                    details.room_screen_widget_uid,
+                    &scope.path,
+                    MessageAction::Redact { details: details.clone(), reason: None },
+                );
+            }
             for action in actions {
+                // Translate key presses on this message (when selected via keyboard navigation)
+                // into the corresponding message actions.
+                if let MessageAction::KeyPressedOnSelected { item_id, key_code } = action
+                    .as_widget_action()
+                    .widget_uid_eq(details.room_screen_widget_uid)
+                    .cast()
+                {
+                    if item_id != details.item_id { continue }
+                    let message_action = match key_code {
+                        KeyCode::ReturnKey => Some(MessageAction::OpenMessageContextMenu {
+                            details: details.clone(),
+                            abs_pos: self.view.area().rect(cx).pos + dvec2(20.0, 20.0),
+                        }),
+                        KeyCode::KeyR if details.abilities.contains(MessageAbilities::CanReplyTo) => {
+                            Some(MessageAction::Reply(details.clone()))
+                        }
+                        _ => None,
+                    };
+                    if let Some(message_action) = message_action {
+                        cx.widget_action(details.room_screen_widget_uid, &scope.path, message_action);
+                    }
+                    continue;
+                }
+
                 match action.as_widget_action().cast() {
                     MessageAction::HighlightMessage(id) if id == details.item_id => {
                         self.animator_play(cx, id!(highlight.on));
@@ -4405,12 +6249,79 @@ impl Widget for Message {
                 )
             )
         }
+        self.apply_display_density(cx);
+        let keyboard_focus = if self.keyboard_selected { 1.0 } else { 0.0 };
+        self.view.apply_over(cx, live!(draw_bg: { keyboard_focus: (keyboard_focus) }));
 
         self.view.draw_walk(cx, scope, walk)
     }
 }
 
 impl Message {
+    /// Returns whether the given absolute position is over this message's replied-to message preview.
+    fn is_over_replied_to_message(&self, cx: &Cx, abs_pos: DVec2) -> bool {
+        let replied_to_message = self.view(id!(replied_to_message));
+        replied_to_message.visible() && replied_to_message.area().rect(cx).contains(abs_pos)
+    }
+
+    /// Starts or cancels showing the replied-to message popup as the pointer moves over this message.
+    ///
+    /// `abs_pos` is the pointer's position, or `None` if it has left this message.
+    fn update_reply_hover(&mut self, cx: &mut Cx, scope: &mut Scope, details: &MessageDetails, abs_pos: Option<DVec2>) {
+        /// How long the pointer must hover over the reply preview before the popup is shown.
+        const REPLY_HOVER_DELAY: f64 = 0.4;
+
+        let over_reply = abs_pos.filter(|pos| self.is_over_replied_to_message(cx, *pos));
+        match (self.reply_hover_pos.is_some(), over_reply.is_some()) {
+            (false, true) => {
+                self.reply_hover_timer = cx.start_timeout(REPLY_HOVER_DELAY);
+            }
+            (true, false) => {
+                cx.stop_timer(self.reply_hover_timer);
+                cx.widget_action(
+                    details.room_screen_widget_uid,
+                    &scope.path,
+                    MessageAction::HideRepliedToMessage,
+                );
+            }
+            _ => { }
+        }
+        self.reply_hover_pos = over_reply;
+    }
+
+    /// Adjusts this message's sizes and spacings to the current display density, if it changed.
+    fn apply_display_density(&mut self, cx: &mut Cx) {
+        let density = app_settings().display_density;
+        if self.applied_density == Some(density) {
+            return;
+        }
+        self.applied_density = Some(density);
+        let style = DensityStyle::of(density);
+        // Condensed messages have no avatar and use their own smaller vertical padding,
+        // so we only keep their profile column aligned with that of full messages.
+        if self.view.widget(id!(body.profile.avatar)).is_empty() {
+            self.view.apply_over(cx, live! {
+                replied_to_message = { replied_to_message_content = {
+                    margin: { left: (style.message_profile_width + 9.0) }
+                } }
+                body = { profile = { width: (style.message_profile_width) } }
+            });
+        } else {
+            self.view.apply_over(cx, live! {
+                body = {
+                    padding: (style.message_padding)
+                    profile = {
+                        width: (style.message_profile_width)
+                        avatar = {
+                            width: (style.message_avatar_size),
+                            height: (style.message_avatar_size)
+                        }
+                    }
+                }
+            });
+        }
+    }
+
     fn set_data(&mut self, details: MessageDetails) {
         self.details = Some(details);
     }
@@ -4421,6 +6332,12 @@ impl MessageRef {
         let Some(mut inner) = self.borrow_mut() else { return };
         inner.set_data(details);
     }
+
+    /// Sets whether this message is currently selected via keyboard navigation of the timeline.
+    fn set_keyboard_selected(&self, selected: bool) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.keyboard_selected = selected;
+    }
 }
 
 /// Calculates the optimal position for a tooltip based on the widget's rectangle and
@@ -0,0 +1,269 @@
+//! A modal dialog for setting a room's wallpaper, i.e., a background color and/or image
+//! shown behind the room's timeline, along with how much that wallpaper is dimmed.
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::{MxcUri, OwnedRoomId};
+
+use crate::{
+    i18n::{tr, tr_args},
+    room_wallpaper::{self, parse_hex_color, RoomWallpaper},
+    shared::popup_list::enqueue_popup_notification,
+    sliding_sync::{submit_async_request, MatrixRequest},
+};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    WallpaperFieldLabel = <Label> {
+        width: Fill, height: Fit
+        draw_text: {
+            text_style: <REGULAR_TEXT>{font_size: 10},
+            color: (COLOR_TEXT)
+        }
+    }
+
+    pub RoomWallpaperModal = {{RoomWallpaperModal}} {
+        width: Fit
+        height: Fit
+
+        <RoundedView> {
+            flow: Down
+            width: 400
+            height: Fit
+            padding: {top: 25, right: 30 bottom: 30 left: 30}
+            spacing: 10
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 3.0
+            }
+
+            title = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    text_style: <TITLE_TEXT>{font_size: 13},
+                    color: #000
+                }
+                text: "Room Wallpaper"
+            }
+
+            prompt = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 10},
+                    color: (MESSAGE_TEXT_COLOR)
+                }
+            }
+
+            color_label = <WallpaperFieldLabel> { }
+            color_input = <RobrixTextInput> {
+                width: Fill, height: Fit
+                padding: 8
+                draw_bg: {
+                    border_width: 1.0
+                }
+                empty_message: "#336699"
+            }
+
+            image_label = <WallpaperFieldLabel> { }
+            image_input = <RobrixTextInput> {
+                width: Fill, height: Fit
+                padding: 8
+                draw_bg: {
+                    border_width: 1.0
+                }
+                empty_message: "mxc://example.org/abc123"
+            }
+
+            dim_label = <WallpaperFieldLabel> { }
+            dim_slider = <Slider> {
+                width: Fill, height: Fit
+                text: ""
+                min: 0.0
+                max: 1.0
+                step: 0.05
+                precision: 2
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                margin: {top: 10}
+                flow: Right,
+                align: {x: 1.0, y: 0.5}
+                spacing: 10
+
+                cancel_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_CLOSE)
+                        color: (COLOR_TEXT),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                    text: "Cancel"
+                }
+
+                remove_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_TRASH)
+                        color: (COLOR_DANGER_RED),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+
+                    draw_bg: {
+                        border_color: (COLOR_DANGER_RED),
+                        color: #fff0f0
+                    }
+                    text: "Remove"
+                    draw_text:{
+                        color: (COLOR_DANGER_RED),
+                    }
+                }
+
+                save_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_CHECKMARK)
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+
+                    draw_bg: {
+                        border_color: (COLOR_SELECTED_PRIMARY),
+                        color: #f0f7ff // light blue
+                    }
+                    text: "Save"
+                    draw_text:{
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum RoomWallpaperModalAction {
+    None,
+    /// Open the wallpaper modal for the given room.
+    Open {
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+    },
+    Close,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct RoomWallpaperModal {
+    #[deref] view: View,
+    /// The room whose wallpaper is being set.
+    #[rust] room_id: Option<OwnedRoomId>,
+    /// The currently-selected amount by which the wallpaper is dimmed.
+    #[rust] dim: f32,
+}
+
+impl Widget for RoomWallpaperModal {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for RoomWallpaperModal {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(id!(cancel_button)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, RoomWallpaperModalAction::Close);
+            return;
+        }
+
+        if let Some(dim) = self.slider(id!(dim_slider)).slided(actions) {
+            self.dim = dim as f32;
+        }
+
+        let Some(room_id) = self.room_id.clone() else { return };
+
+        if self.button(id!(remove_button)).clicked(actions) {
+            submit_async_request(MatrixRequest::SetRoomWallpaper { room_id, wallpaper: None });
+            cx.widget_action(self.widget_uid(), &scope.path, RoomWallpaperModalAction::Close);
+            return;
+        }
+
+        if self.button(id!(save_button)).clicked(actions) {
+            let color = self.text_input(id!(color_input)).text().trim().to_owned();
+            if !color.is_empty() && parse_hex_color(&color).is_none() {
+                enqueue_popup_notification(tr_args("room_wallpaper.invalid_color", &[("color", &color)]));
+                return;
+            }
+            let image_url = self.text_input(id!(image_input)).text().trim().to_owned();
+            let image_url = if image_url.is_empty() {
+                None
+            } else {
+                let mxc_uri = <&MxcUri>::from(image_url.as_str());
+                if !mxc_uri.is_valid() {
+                    enqueue_popup_notification(tr_args("room_wallpaper.invalid_image_url", &[("url", &image_url)]));
+                    return;
+                }
+                Some(mxc_uri.to_owned())
+            };
+            let wallpaper = RoomWallpaper {
+                color: (!color.is_empty()).then_some(color),
+                image_url,
+                dim: self.dim,
+            };
+            if wallpaper.is_empty() {
+                enqueue_popup_notification(tr("room_wallpaper.empty").to_string());
+                return;
+            }
+            submit_async_request(MatrixRequest::SetRoomWallpaper { room_id, wallpaper: Some(wallpaper) });
+            cx.widget_action(self.widget_uid(), &scope.path, RoomWallpaperModalAction::Close);
+        }
+    }
+}
+
+impl RoomWallpaperModal {
+    /// Resets this modal to set the wallpaper of the given room,
+    /// pre-filled with that room's current wallpaper.
+    fn initialize(&mut self, cx: &mut Cx, room_id: OwnedRoomId, room_name: Option<String>) {
+        let wallpaper = room_wallpaper::room_wallpaper(&room_id).unwrap_or_default();
+        let room_name = room_name.unwrap_or_else(|| room_id.to_string());
+        self.room_id = Some(room_id);
+        self.dim = wallpaper.dim.clamp(0.0, 1.0);
+
+        self.label(id!(title)).set_text(cx, tr("room_wallpaper.title"));
+        self.label(id!(prompt)).set_text(cx, &tr_args("room_wallpaper.prompt", &[("room", &room_name)]));
+        self.label(id!(color_label)).set_text(cx, tr("room_wallpaper.color"));
+        self.label(id!(image_label)).set_text(cx, tr("room_wallpaper.image_url"));
+        self.label(id!(dim_label)).set_text(cx, tr("room_wallpaper.dim"));
+        self.text_input(id!(color_input)).set_text(cx, wallpaper.color.as_deref().unwrap_or_default());
+        self.text_input(id!(image_input)).set_text(
+            cx,
+            wallpaper.image_url.as_ref().map(|uri| uri.as_str()).unwrap_or_default(),
+        );
+        self.slider(id!(dim_slider)).set_value(cx, self.dim as f64);
+        self.button(id!(cancel_button)).set_text(cx, tr("room_wallpaper.cancel"));
+        self.button(id!(remove_button)).set_text(cx, tr("room_wallpaper.remove"));
+        self.button(id!(save_button)).set_text(cx, tr("room_wallpaper.save"));
+        self.redraw(cx);
+    }
+}
+
+impl RoomWallpaperModalRef {
+    /// See [`RoomWallpaperModal::initialize()`].
+    pub fn initialize(&self, cx: &mut Cx, room_id: OwnedRoomId, room_name: Option<String>) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.initialize(cx, room_id, room_name);
+    }
+}
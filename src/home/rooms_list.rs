@@ -2,17 +2,25 @@ use std::{cmp::Ordering, collections::HashMap, ops::Deref};
 use crossbeam_queue::SegQueue;
 use imbl::HashSet;
 use makepad_widgets::*;
-use matrix_sdk::ruma::{events::tag::{TagName, Tags}, MilliSecondsSinceUnixEpoch, OwnedRoomAliasId, OwnedRoomId};
+use tracing::{error, info};
+use matrix_sdk::ruma::{events::tag::{TagName, Tags}, MilliSecondsSinceUnixEpoch, OwnedRoomAliasId, OwnedRoomId, OwnedUserId};
 use bitflags::bitflags;
-use crate::{app::AppState, shared::jump_to_bottom_button::UnreadMessageCount, sliding_sync::{submit_async_request, MatrixRequest, PaginationDirection}};
+use crate::{app::AppState, app_badge::{update_app_badge, UnreadTotals}, settings::keyboard_shortcuts::ShortcutAction, shared::jump_to_bottom_button::UnreadMessageCount, sliding_sync::{submit_async_request, MatrixRequest, PaginationDirection}, utils::RELATIVE_TIMESTAMP_REFRESH_SECS};
 
-use super::{room_preview::RoomPreviewAction, rooms_sidebar::RoomsViewAction};
+use super::{room_context_menu::RoomContextMenuDetails, room_filter_tabs::{RoomFilterTabsAction, SavedRoomFilter}, room_preview::RoomPreviewAction, rooms_sidebar::RoomsViewAction};
 
 /// Whether to pre-paginate visible rooms at least once in order to
 /// be able to display the latest message in the room preview,
 /// and to have something to immediately show when a user first opens a room.
 const PREPAGINATE_VISIBLE_ROOMS: bool = true;
 
+/// The maximum number of pending rooms list updates to process upon a single signal.
+///
+/// This prevents accounts with thousands of rooms from blocking the UI thread
+/// while the initial list of rooms is loaded; any remaining updates
+/// are processed upon the next signal.
+const MAX_ROOM_UPDATES_PER_SIGNAL: usize = 250;
+
 live_design! {
     use link::theme::*;
     use link::shaders::*;
@@ -97,6 +105,16 @@ pub enum RoomsListUpdate {
         count: UnreadMessageCount,
         unread_mentions: u64,
     },
+    /// Update whether the given room's notifications have been muted.
+    UpdateIsMuted {
+        room_id: OwnedRoomId,
+        is_muted: bool,
+    },
+    /// Update the display names of the users who are currently typing in the given room.
+    UpdateTypingUsers {
+        room_id: OwnedRoomId,
+        typing_users: Vec<String>,
+    },
     /// Update the displayable name for the given room.
     UpdateRoomName {
         room_id: OwnedRoomId,
@@ -129,17 +147,29 @@ pub fn enqueue_rooms_list_update(update: RoomsListUpdate) {
     SignalToUI::set_ui_signal();
 }
 
-pub type RoomIndex = usize;
-
-
 #[derive(Debug, Clone, DefaultNone)]
 pub enum RoomsListAction {
+    /// A room was selected, either in the rooms list or from elsewhere,
+    /// e.g., from the list of rooms shared with a user in their user profile pane.
     Selected {
-        /// The index (into the `all_rooms` vector) of the selected `RoomPreviewEntry`.
-        room_index: RoomIndex,
         room_id: OwnedRoomId,
         room_name: Option<String>,
     },
+    /// The user right-clicked, long-pressed, or swiped on a room in the rooms list,
+    /// requesting that room's context menu be shown at the given position.
+    OpenRoomContextMenu {
+        details: RoomContextMenuDetails,
+        abs_pos: DVec2,
+    },
+    /// The mouse has rested on a room in the rooms list,
+    /// requesting that room's hover preview be shown at the given position.
+    ShowRoomHoverPreview {
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+        abs_pos: DVec2,
+    },
+    /// The mouse has left a room in the rooms list, so its hover preview should be hidden.
+    HideRoomHoverPreview,
     None,
 }
 
@@ -171,8 +201,20 @@ pub struct RoomsListEntry {
     /// be able to display the latest message in the room preview,
     /// and to have something to immediately show when a user first opens a room.
     pub has_been_paginated: bool,
+    /// Whether this room's avatar image has been requested.
+    /// We only fetch a room's avatar once the room is first drawn,
+    /// such that accounts with thousands of rooms don't fetch all avatars at once.
+    pub has_requested_avatar: bool,
     /// Whether this room is currently selected in the UI.
     pub is_selected: bool,
+    /// The other user in this room, if it is a direct message room with exactly one other user.
+    ///
+    /// This is used to show that user's presence on top of the room's avatar.
+    pub direct_user_id: Option<OwnedUserId>,
+    /// Whether the user has muted this room's notifications.
+    pub is_muted: bool,
+    /// The display names of the other users who are currently typing in this room.
+    pub typing_users: Vec<String>,
 }
 
 #[derive(Debug)]
@@ -238,6 +280,7 @@ type SortFn = dyn Fn(&RoomsListEntry, &RoomsListEntry) -> Ordering;
 pub struct RoomDisplayFilterBuilder {
     keywords: String,
     filter_criteria: RoomFilterCriteria,
+    saved_filter: Option<SavedRoomFilter>,
     sort_fn: Option<Box<SortFn>>,
 }
 /// ## Example
@@ -259,6 +302,7 @@ impl RoomDisplayFilterBuilder {
         Self {
             keywords: String::new(),
             filter_criteria: RoomFilterCriteria::default(),
+            saved_filter: None,
             sort_fn: None,
         }
     }
@@ -273,6 +317,12 @@ impl RoomDisplayFilterBuilder {
         self
     }
 
+    /// Only matches rooms that also match the given saved filter, if any.
+    pub fn set_saved_filter(mut self, saved_filter: Option<SavedRoomFilter>) -> Self {
+        self.saved_filter = saved_filter;
+        self
+    }
+
     pub fn sort_by<F>(mut self, sort_fn: F) -> Self
     where
         F: Fn(&RoomsListEntry, &RoomsListEntry) -> Ordering + 'static
@@ -380,8 +430,12 @@ impl RoomDisplayFilterBuilder {
     pub fn build(self) -> (RoomDisplayFilter, Option<Box<SortFn>>) {
         let keywords = self.keywords;
         let filter_criteria = self.filter_criteria;
+        let saved_filter = self.saved_filter;
 
         let filter = RoomDisplayFilter(Box::new(move |room| {
+            if saved_filter.as_ref().is_some_and(|f| !f.matches(room)) {
+                return false;
+            }
             if keywords.is_empty() || filter_criteria.is_empty() {
                 return true;
             }
@@ -413,6 +467,10 @@ pub struct RoomsList {
     /// when its value changes. Instead, you must manually invoke it on the set of `all_rooms`
     /// in order to update the set of `displayed_rooms` accordingly.
     #[rust] display_filter: RoomDisplayFilter,
+    /// The keywords that the user is currently searching for, if any.
+    #[rust] search_keywords: String,
+    /// The saved filter of the currently-selected filter tab, if any.
+    #[rust] tab_filter: Option<SavedRoomFilter>,
     
     /// The list of rooms currently displayed in the UI, in order from top to bottom.
     /// This must be a strict subset of the rooms present in `all_rooms`, and should be determined
@@ -430,9 +488,60 @@ pub struct RoomsList {
     #[rust] current_active_room_index: Option<usize>,
     /// The maximum number of rooms that will ever be loaded.
     #[rust] max_known_rooms: Option<u32>,
+    /// The timer that periodically redraws this list such that its relative timestamps stay current.
+    #[rust] timestamp_refresh_timer: Timer,
 }
 
 impl RoomsList {
+    /// Re-builds the display filter from the current search keywords and filter tab,
+    /// and then re-applies it to the set of all rooms.
+    ///
+    /// If `scroll_to_top` is `true`, the list is also scrolled back to the first room.
+    fn update_displayed_rooms(&mut self, cx: &mut Cx, scroll_to_top: bool) {
+        let portal_list = self.view.portal_list(id!(list));
+        if self.search_keywords.is_empty() && self.tab_filter.is_none() {
+            // Reset the displayed rooms list to show all rooms.
+            self.display_filter = RoomDisplayFilter::default();
+            self.displayed_rooms = self.all_rooms.keys().cloned().collect();
+            self.update_status_rooms_count();
+        } else {
+            let (filter, sort_fn) = RoomDisplayFilterBuilder::new()
+                .set_keywords(self.search_keywords.clone())
+                .set_filter_criteria(RoomFilterCriteria::All)
+                .set_saved_filter(self.tab_filter.clone())
+                .build();
+            self.display_filter = filter;
+
+            let new_displayed_rooms = if let Some(sort_fn) = sort_fn {
+                let mut filtered_rooms: Vec<_> = self.all_rooms
+                    .iter()
+                    .filter(|(_, room)| (self.display_filter)(room))
+                    .collect();
+
+                filtered_rooms.sort_by(|(_, room_a), (_, room_b)| sort_fn(room_a, room_b));
+
+                filtered_rooms
+                    .into_iter()
+                    .map(|(room_id, _)| room_id.clone())
+                    .collect()
+            } else {
+                self.all_rooms
+                    .iter()
+                    .filter(|(_, room)| (self.display_filter)(room))
+                    .map(|(room_id, _)| room_id.clone())
+                    .collect()
+            };
+
+            // Update the displayed rooms list and redraw it.
+            self.displayed_rooms = new_displayed_rooms;
+            self.update_status_matching_rooms();
+        }
+        if scroll_to_top {
+            portal_list.set_first_id_and_scroll(0, 0.0);
+        }
+        self.redraw(cx);
+    }
+
     /// Updates the status message to show how many rooms have been loaded.
     fn update_status_rooms_count(&mut self) {
         self.status = if let Some(max_rooms) = self.max_known_rooms {
@@ -451,14 +560,62 @@ impl RoomsList {
             n => format!("Found {} matching rooms.", n),
         }
     }
+
+    /// Selects the next (or previous) displayed room that has unread messages,
+    /// starting from the currently-selected room and wrapping around the list.
+    fn select_unread_room(&mut self, cx: &mut Cx, scope: &mut Scope, forward: bool) {
+        let count = self.displayed_rooms.len();
+        if count == 0 { return; }
+        let start = match (self.current_active_room_index, forward) {
+            (Some(index), _) => index,
+            // With no room selected, start from either end of the list.
+            (None, true) => count - 1,
+            (None, false) => 0,
+        };
+        let Some((room_index, room_id, room_name)) = (1..=count)
+            .map(|offset| if forward {
+                (start + offset) % count
+            } else {
+                (start + count - (offset % count)) % count
+            })
+            .filter_map(|i| self.displayed_rooms.get(i).and_then(|id| self.all_rooms.get(id)).map(|room| (i, room)))
+            .find(|(_, room)| room.num_unread_messages > 0)
+            .map(|(i, room)| (i, room.room_id.clone(), room.room_name.clone()))
+        else {
+            return;
+        };
+
+        self.current_active_room_index = Some(room_index);
+        cx.widget_action(
+            self.widget_uid(),
+            &scope.path,
+            RoomsListAction::Selected {
+                room_id,
+                room_name,
+            }
+        );
+        self.view.portal_list(id!(list)).set_first_id_and_scroll(room_index, 0.0);
+        self.redraw(cx);
+    }
 }
 
 impl Widget for RoomsList {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if self.timestamp_refresh_timer.is_empty() {
+            self.timestamp_refresh_timer = cx.start_interval(RELATIVE_TIMESTAMP_REFRESH_SECS);
+        } else if self.timestamp_refresh_timer.is_event(event).is_some() {
+            self.redraw(cx);
+        }
+
         // Process all pending updates to the list of all rooms, and then redraw it.
         if matches!(event, Event::Signal) {
+            let start = std::time::Instant::now();
             let mut num_updates: usize = 0;
-            while let Some(update) = PENDING_ROOM_UPDATES.pop() {
+            // Whether the selected filter tab must be re-applied to all rooms,
+            // because a room property that it depends on has changed.
+            let mut needs_refilter = false;
+            while num_updates < MAX_ROOM_UPDATES_PER_SIGNAL {
+                let Some(update) = PENDING_ROOM_UPDATES.pop() else { break };
                 num_updates += 1;
                 match update {
                     RoomsListUpdate::AddRoom(room) => {
@@ -494,10 +651,26 @@ impl Widget for RoomsList {
                                 UnreadMessageCount::Unknown => (0, 0),
                                 UnreadMessageCount::Known(count) => (count, unread_mentions),
                             };
+                            needs_refilter |= self.tab_filter.is_some();
                         } else {
                             error!("Error: couldn't find room {} to update unread messages count", room_id);
                         }
                     }
+                    RoomsListUpdate::UpdateIsMuted { room_id, is_muted } => {
+                        if let Some(room) = self.all_rooms.get_mut(&room_id) {
+                            room.is_muted = is_muted;
+                            needs_refilter |= self.tab_filter.is_some();
+                        } else {
+                            error!("Error: couldn't find room {room_id} to update muted state");
+                        }
+                    }
+                    RoomsListUpdate::UpdateTypingUsers { room_id, typing_users } => {
+                        if let Some(room) = self.all_rooms.get_mut(&room_id) {
+                            room.typing_users = typing_users;
+                        } else {
+                            error!("Error: couldn't find room {room_id} to update typing users");
+                        }
+                    }
                     RoomsListUpdate::UpdateRoomName { room_id, new_room_name } => {
                         if let Some(room) = self.all_rooms.get_mut(&room_id) {
                             let was_displayed = (self.display_filter)(room);
@@ -568,45 +741,91 @@ impl Widget for RoomsList {
                     }
                 }
             }
+            if needs_refilter {
+                self.update_displayed_rooms(cx, false);
+            }
             if num_updates > 0 {
-                log!("RoomsList: processed {} updates to the list of all rooms", num_updates);
+                info!("RoomsList: processed {} updates to the list of all rooms in {:?}", num_updates, start.elapsed());
+                update_app_badge(self.all_rooms.values().fold(UnreadTotals::default(), |totals, room| UnreadTotals {
+                    unread_messages: totals.unread_messages + room.num_unread_messages,
+                    unread_mentions: totals.unread_mentions + room.num_unread_mentions,
+                }));
                 self.redraw(cx);
             }
+            // Process the remaining updates in a later signal, after this one has been drawn.
+            if !PENDING_ROOM_UPDATES.is_empty() {
+                SignalToUI::set_ui_signal();
+            }
         }
 
         // Now, handle any actions on this widget, e.g., a user selecting a room.
         let widget_uid = self.widget_uid();
         for list_action in cx.capture_actions(|cx| self.view.handle_event(cx, event, scope)) {
-            if let RoomPreviewAction::Click = list_action.as_widget_action().cast() {
-                let widget_action = list_action.as_widget_action();
-
-                let Some(displayed_room_index) = self.displayed_rooms_map
-                    .iter()
-                    .find(|&(&room_widget_uid, _)| widget_action.widget_uid_eq(room_widget_uid).is_some())
-                    .map(|(_, &room_index)| room_index)
-                else {
-                    error!("BUG: couldn't find displayed index of clicked room for widget action {widget_action:?}");
-                    continue;
-                };
-                let Some(room_details) = self.displayed_rooms
-                    .get(displayed_room_index)
-                    .and_then(|room_id| self.all_rooms.get(room_id))
-                else {
-                    error!("BUG: couldn't get room details for room at displayed index {displayed_room_index}");
-                    continue;
-                };
+            let widget_action = list_action.as_widget_action();
+            let preview_action: RoomPreviewAction = widget_action.cast();
+            if matches!(preview_action, RoomPreviewAction::None) {
+                continue;
+            }
 
-                self.current_active_room_index = Some(displayed_room_index);
-                cx.widget_action(
-                    widget_uid,
-                    &scope.path,
-                    RoomsListAction::Selected {
-                        room_index: displayed_room_index,
-                        room_id: room_details.room_id.to_owned(),
+            let Some(displayed_room_index) = self.displayed_rooms_map
+                .iter()
+                .find(|&(&room_widget_uid, _)| widget_action.widget_uid_eq(room_widget_uid).is_some())
+                .map(|(_, &room_index)| room_index)
+            else {
+                error!("BUG: couldn't find displayed index of clicked room for widget action {widget_action:?}");
+                continue;
+            };
+            let Some(room_details) = self.displayed_rooms
+                .get(displayed_room_index)
+                .and_then(|room_id| self.all_rooms.get(room_id))
+            else {
+                error!("BUG: couldn't get room details for room at displayed index {displayed_room_index}");
+                continue;
+            };
+
+            match preview_action {
+                RoomPreviewAction::Click => {
+                    self.current_active_room_index = Some(displayed_room_index);
+                    cx.widget_action(
+                        widget_uid,
+                        &scope.path,
+                        RoomsListAction::Selected {
+                            room_id: room_details.room_id.to_owned(),
+                            room_name: room_details.room_name.clone(),
+                        }
+                    );
+                    self.redraw(cx);
+                }
+                RoomPreviewAction::OpenContextMenu { abs_pos } => {
+                    let details = RoomContextMenuDetails {
+                        room_id: room_details.room_id.clone(),
                         room_name: room_details.room_name.clone(),
-                    }
-                );
-                self.redraw(cx);
+                        canonical_alias: room_details.canonical_alias.clone(),
+                        has_unread: room_details.num_unread_messages > 0,
+                        is_favourite: room_details.tags.as_ref()
+                            .is_some_and(|tags| tags.contains_key(&TagName::Favorite)),
+                    };
+                    cx.widget_action(
+                        widget_uid,
+                        &scope.path,
+                        RoomsListAction::OpenRoomContextMenu { details, abs_pos },
+                    );
+                }
+                RoomPreviewAction::ShowHoverPreview { abs_pos } => {
+                    cx.widget_action(
+                        widget_uid,
+                        &scope.path,
+                        RoomsListAction::ShowRoomHoverPreview {
+                            room_id: room_details.room_id.clone(),
+                            room_name: room_details.room_name.clone(),
+                            abs_pos,
+                        },
+                    );
+                }
+                RoomPreviewAction::HideHoverPreview => {
+                    cx.widget_action(widget_uid, &scope.path, RoomsListAction::HideRoomHoverPreview);
+                }
+                RoomPreviewAction::None => { }
             }
         }
         self.widget_match_event(cx, event, scope);
@@ -658,6 +877,13 @@ impl Widget for RoomsList {
                             direction: PaginationDirection::Backwards,
                         });
                     }
+                    // Fetch the room's avatar image if it hasn't been fetched yet.
+                    if !room_info.has_requested_avatar {
+                        room_info.has_requested_avatar = true;
+                        submit_async_request(MatrixRequest::FetchRoomAvatar {
+                            room_id: room_info.room_id.clone(),
+                        });
+                    }
 
                     // Pass the room info down to the RoomPreview widget via Scope.
                     scope = Scope::with_props(&*room_info);
@@ -687,51 +913,22 @@ impl Widget for RoomsList {
 }
 
 impl WidgetMatchEvent for RoomsList {
-    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
         for action in actions {
-            if let RoomsViewAction::Search(keywords) = action.as_widget_action().cast() {
-                let portal_list = self.view.portal_list(id!(list));
-                if keywords.is_empty() {
-                    // Reset the displayed rooms list to show all rooms.
-                    self.display_filter = RoomDisplayFilter::default();
-                    self.displayed_rooms = self.all_rooms.keys().cloned().collect();
-                    self.update_status_rooms_count();
-                    portal_list.set_first_id_and_scroll(0, 0.0);
-                    self.redraw(cx);
-                    return;
-                }
+            match action.downcast_ref() {
+                Some(ShortcutAction::NextUnreadRoom) => self.select_unread_room(cx, scope, true),
+                Some(ShortcutAction::PreviousUnreadRoom) => self.select_unread_room(cx, scope, false),
+                _ => {}
+            }
 
-                let (filter, sort_fn) = RoomDisplayFilterBuilder::new()
-                    .set_keywords(keywords.clone())
-                    .set_filter_criteria(RoomFilterCriteria::All)
-                    .build();
-                self.display_filter = filter;
-
-                let new_displayed_rooms = if let Some(sort_fn) = sort_fn {
-                    let mut filtered_rooms: Vec<_> = self.all_rooms
-                        .iter()
-                        .filter(|(_, room)| (self.display_filter)(room))
-                        .collect();
-
-                    filtered_rooms.sort_by(|(_, room_a), (_, room_b)| sort_fn(room_a, room_b));
-
-                    filtered_rooms
-                        .into_iter()
-                        .map(|(room_id, _)| room_id.clone())
-                        .collect()
-                } else {
-                    self.all_rooms
-                        .iter()
-                        .filter(|(_, room)| (self.display_filter)(room))
-                        .map(|(room_id, _)| room_id.clone())
-                        .collect()
-                };
+            if let RoomsViewAction::Search(keywords) = action.as_widget_action().cast() {
+                self.search_keywords = keywords;
+                self.update_displayed_rooms(cx, true);
+            }
 
-                // Update the displayed rooms list and redraw it.
-                self.displayed_rooms = new_displayed_rooms;
-                self.update_status_matching_rooms();
-                portal_list.set_first_id_and_scroll(0, 0.0);
-                self.redraw(cx);
+            if let RoomFilterTabsAction::Selected(tab_filter) = action.as_widget_action().cast() {
+                self.tab_filter = tab_filter;
+                self.update_displayed_rooms(cx, true);
             }
         }
     }
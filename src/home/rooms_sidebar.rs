@@ -12,6 +12,7 @@ live_design! {
     use crate::shared::search_bar::SearchBar;
 
     use crate::home::rooms_list::RoomsList;
+    use crate::home::room_filter_tabs::RoomFilterTabs;
 
     RoomsView = {{RoomsView}} {
         show_bg: true,
@@ -41,6 +42,7 @@ live_design! {
                 empty_message: "Search rooms..."
             }
         }
+        room_filter_tabs = <RoomFilterTabs> {}
         <CachedWidget> {
             rooms_list = <RoomsList> {}
         }
@@ -0,0 +1,172 @@
+//! A list of the messages scheduled to be sent later to a room,
+//! shown above the message input bar, each with a button to cancel it.
+//! Messages that failed to be sent also have a button to retry sending them.
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::OwnedRoomId;
+
+use crate::{
+    i18n::{tr, tr_args},
+    scheduled_messages::{self, ScheduledMessage, ScheduledMessagesAction},
+    shared::popup_list::enqueue_popup_notification,
+    utils::unix_time_millis_to_datetime,
+};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    ScheduledMessageRow = <View> {
+        width: Fill, height: Fit
+        flow: Right,
+        spacing: 8,
+        align: {y: 0.5}
+        padding: {top: 2, bottom: 2}
+
+        text = <Label> {
+            width: Fill, height: Fit
+            draw_text: {
+                wrap: Ellipsis,
+                text_style: <REGULAR_TEXT>{font_size: 9.5},
+                color: (MESSAGE_TEXT_COLOR)
+            }
+        }
+        retry_button = <RobrixIconButton> {
+            visible: false
+            padding: {left: 8, right: 8, top: 4, bottom: 4}
+            draw_icon: {
+                svg_file: (ICON_SEND)
+                color: (COLOR_SELECTED_PRIMARY),
+            }
+            icon_walk: {width: 10, height: 10}
+            draw_text: {
+                text_style: <REGULAR_TEXT>{font_size: 9},
+                color: (COLOR_SELECTED_PRIMARY),
+            }
+        }
+        cancel_button = <RobrixIconButton> {
+            padding: {left: 8, right: 8, top: 4, bottom: 4}
+            draw_icon: {
+                svg_file: (ICON_CLOSE)
+                color: (COLOR_DANGER_RED),
+            }
+            icon_walk: {width: 10, height: 10}
+            draw_text: {
+                text_style: <REGULAR_TEXT>{font_size: 9},
+                color: (COLOR_DANGER_RED),
+            }
+        }
+    }
+
+    pub ScheduledMessagesList = {{ScheduledMessagesList}} {
+        width: Fill, height: Fit
+        flow: Down
+        padding: {left: 12, right: 10, top: 6, bottom: 6}
+        item: <ScheduledMessageRow> {}
+    }
+}
+
+/// The messages scheduled to be sent to the currently-displayed room.
+///
+/// This draws nothing if there are no scheduled messages.
+#[derive(Live, LiveHook, Widget)]
+pub struct ScheduledMessagesList {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+    #[rust] room_id: Option<OwnedRoomId>,
+    /// Each displayed row along with the ID of the scheduled message it shows.
+    #[rust] rows: Vec<(u64, WidgetRef)>,
+}
+
+impl Widget for ScheduledMessagesList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (_, row) in &self.rows {
+            row.handle_event(cx, event, scope);
+        }
+        let Event::Actions(actions) = event else { return };
+
+        let cancelled = self.rows.iter()
+            .find(|(_, row)| row.button(id!(cancel_button)).clicked(actions))
+            .map(|(id, _)| *id);
+        if let Some(id) = cancelled {
+            match scheduled_messages::cancel(id) {
+                Ok(Some(_)) => enqueue_popup_notification(tr("scheduled.cancelled").to_string()),
+                Ok(None) => { }
+                Err(e) => enqueue_popup_notification(tr_args("scheduled.cancel_failed", &[("error", &e.to_string())])),
+            }
+        }
+
+        let retried = self.rows.iter()
+            .find(|(_, row)| row.button(id!(retry_button)).clicked(actions))
+            .map(|(id, _)| *id);
+        if let Some(id) = retried {
+            if let Err(e) = scheduled_messages::set_failed(id, None) {
+                enqueue_popup_notification(tr_args("scheduled.schedule_failed", &[("error", &e.to_string())]));
+            }
+        }
+
+        // `ScheduledMessagesAction`s are posted from background tasks, so they are NOT widget actions.
+        for action in actions {
+            if let Some(ScheduledMessagesAction::Updated { room_id }) = action.downcast_ref() {
+                if self.room_id.as_ref() == Some(room_id) {
+                    self.refresh(cx);
+                }
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if self.rows.is_empty() {
+            return DrawStep::done();
+        }
+        cx.begin_turtle(walk, self.layout);
+        for (_, row) in &self.rows {
+            let _ = row.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl ScheduledMessagesList {
+    /// Rebuilds the rows of this list from the messages scheduled for its room.
+    fn refresh(&mut self, cx: &mut Cx) {
+        self.rows.clear();
+        let Some(room_id) = self.room_id.as_ref() else { return };
+        for message in scheduled_messages::scheduled_in(room_id) {
+            let row = WidgetRef::new_from_ptr(cx, self.item);
+            row.label(id!(text)).set_text(cx, &row_text(&message));
+            row.button(id!(cancel_button)).set_text(cx, tr("scheduled.cancel"));
+            row.button(id!(retry_button)).set_text(cx, tr("scheduled.retry"));
+            row.button(id!(retry_button)).set_visible(cx, message.failed.is_some());
+            self.rows.push((message.id, row));
+        }
+        self.redraw(cx);
+    }
+}
+
+impl ScheduledMessagesListRef {
+    /// Shows the messages scheduled to be sent to the given room.
+    pub fn set_room(&self, cx: &mut Cx, room_id: OwnedRoomId) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.room_id = Some(room_id);
+        inner.refresh(cx);
+    }
+}
+
+/// Returns the text shown for a scheduled message, i.e., when it will be sent and a preview of its text.
+fn row_text(message: &ScheduledMessage) -> String {
+    let send_at = unix_time_millis_to_datetime(&message.send_at)
+        .map(|datetime| datetime.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    let preview = message.text.lines().next().unwrap_or_default();
+    match &message.failed {
+        Some(error) => tr_args("scheduled.row_failed", &[("error", error), ("text", preview)]),
+        None => tr_args("scheduled.row", &[("time", &send_at), ("text", preview)]),
+    }
+}
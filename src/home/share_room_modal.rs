@@ -0,0 +1,240 @@
+//! A modal dialog for sharing a room with others, which shows the room's `matrix.to` link,
+//! lets the user copy it, and can optionally display it as a QR code for in-person sharing.
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::{OwnedRoomAliasId, OwnedRoomId};
+
+use crate::{
+    i18n::{tr, tr_args},
+    shared::popup_list::enqueue_popup_notification,
+    utils::qr_code_image,
+};
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    pub ShareRoomModal = {{ShareRoomModal}} {
+        width: Fit
+        height: Fit
+
+        <RoundedView> {
+            flow: Down
+            width: 400
+            height: Fit
+            padding: {top: 25, right: 30 bottom: 30 left: 30}
+            spacing: 10
+
+            show_bg: true
+            draw_bg: {
+                color: #fff
+                radius: 3.0
+            }
+
+            title = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    text_style: <TITLE_TEXT>{font_size: 13},
+                    color: #000
+                }
+                text: "Share Room"
+            }
+
+            prompt = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 10},
+                    color: (MESSAGE_TEXT_COLOR)
+                }
+            }
+
+            link = <Label> {
+                width: Fill, height: Fit
+                padding: 8
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{font_size: 10},
+                    color: (COLOR_SELECTED_PRIMARY)
+                }
+            }
+
+            qr_code_view = <View> {
+                visible: false
+                width: Fill, height: Fit
+                align: {x: 0.5}
+
+                qr_code = <Image> {
+                    width: 200, height: 200
+                    fit: Smallest
+                }
+            }
+
+            <View> {
+                width: Fill, height: Fit
+                margin: {top: 10}
+                flow: Right,
+                align: {x: 1.0, y: 0.5}
+                spacing: 10
+
+                close_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_CLOSE)
+                        color: (COLOR_TEXT),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                    text: "Close"
+                }
+
+                qr_code_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_LINK)
+                        color: (COLOR_TEXT),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+                    text: "Show QR Code"
+                }
+
+                copy_button = <RobrixIconButton> {
+                    align: {x: 0.5, y: 0.5}
+                    padding: {left: 15, right: 15}
+                    draw_icon: {
+                        svg_file: (ICON_COPY)
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                    icon_walk: {width: 14, height: 14, margin: {left: -2, right: -1} }
+
+                    draw_bg: {
+                        border_color: (COLOR_SELECTED_PRIMARY),
+                        color: #f0f7ff // light blue
+                    }
+                    text: "Copy Link"
+                    draw_text:{
+                        color: (COLOR_SELECTED_PRIMARY),
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum ShareRoomModalAction {
+    None,
+    /// Open the share modal for the given room.
+    Open {
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+        /// The canonical alias of the room, which is preferred over its ID in the shared link.
+        canonical_alias: Option<OwnedRoomAliasId>,
+    },
+    Close,
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct ShareRoomModal {
+    #[deref] view: View,
+    /// The `matrix.to` link to the room being shared.
+    #[rust] link: String,
+    /// Whether the QR code for the link has already been generated and shown.
+    #[rust] qr_code_shown: bool,
+}
+
+impl Widget for ShareRoomModal {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for ShareRoomModal {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(id!(close_button)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, ShareRoomModalAction::Close);
+            return;
+        }
+
+        if self.button(id!(copy_button)).clicked(actions) {
+            cx.copy_to_clipboard(&self.link);
+            enqueue_popup_notification(tr("room_menu.link_copied").to_string());
+        }
+
+        if self.button(id!(qr_code_button)).clicked(actions) {
+            self.toggle_qr_code(cx);
+        }
+    }
+}
+
+impl ShareRoomModal {
+    /// Resets this modal to share the given room.
+    fn initialize(
+        &mut self,
+        cx: &mut Cx,
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+        canonical_alias: Option<OwnedRoomAliasId>,
+    ) {
+        self.link = match canonical_alias.as_ref() {
+            Some(alias) => alias.matrix_to_uri().to_string(),
+            None => room_id.matrix_to_uri().to_string(),
+        };
+        self.qr_code_shown = false;
+        let room_name = room_name.unwrap_or_else(|| room_id.to_string());
+        self.label(id!(title)).set_text(cx, tr("share_room.title"));
+        self.label(id!(prompt)).set_text(cx, &tr_args("share_room.prompt", &[("room", &room_name)]));
+        self.label(id!(link)).set_text(cx, &self.link);
+        self.button(id!(close_button)).set_text(cx, tr("share_room.close"));
+        self.button(id!(copy_button)).set_text(cx, tr("share_room.copy_link"));
+        self.button(id!(qr_code_button)).set_text(cx, tr("share_room.show_qr_code"));
+        self.view(id!(qr_code_view)).set_visible(cx, false);
+        self.redraw(cx);
+    }
+
+    /// Shows or hides the QR code for the room link, generating it if it's not yet shown.
+    fn toggle_qr_code(&mut self, cx: &mut Cx) {
+        let qr_code_view = self.view(id!(qr_code_view));
+        if qr_code_view.visible() {
+            qr_code_view.set_visible(cx, false);
+            self.button(id!(qr_code_button)).set_text(cx, tr("share_room.show_qr_code"));
+            self.redraw(cx);
+            return;
+        }
+
+        if !self.qr_code_shown {
+            let Some(image_buffer) = qr_code_image(self.link.as_bytes()) else {
+                enqueue_popup_notification(tr("share_room.qr_code_failed").to_string());
+                return;
+            };
+            self.image(id!(qr_code)).set_texture(cx, Some(image_buffer.into_new_texture(cx)));
+            self.qr_code_shown = true;
+        }
+        qr_code_view.set_visible(cx, true);
+        self.button(id!(qr_code_button)).set_text(cx, tr("share_room.hide_qr_code"));
+        self.redraw(cx);
+    }
+}
+
+impl ShareRoomModalRef {
+    /// See [`ShareRoomModal::initialize()`].
+    pub fn initialize(
+        &self,
+        cx: &mut Cx,
+        room_id: OwnedRoomId,
+        room_name: Option<String>,
+        canonical_alias: Option<OwnedRoomAliasId>,
+    ) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.initialize(cx, room_id, room_name, canonical_alias);
+    }
+}
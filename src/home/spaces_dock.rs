@@ -1,6 +1,11 @@
 use makepad_widgets::*;
 
-use crate::shared::color_tooltip::*;
+use crate::{
+    profile::presence::{get_presence, show_presence_dot, PresenceAction},
+    settings::settings_screen::SettingsAction,
+    shared::color_tooltip::*,
+    sliding_sync::current_user_id,
+};
 
 live_design! {
     use link::theme::*;
@@ -14,6 +19,8 @@ live_design! {
 
     ICON_HOME = dep("crate://self/resources/icons/home.svg")
     ICON_SETTINGS = dep("crate://self/resources/icons/settings.svg")
+    ICON_INBOX = dep("crate://self/resources/icons/double_chat.svg")
+    ICON_CONTACTS = dep("crate://self/resources/icon_user.svg")
 
     Filler = <View> {
         height: Fill, width: Fill
@@ -60,6 +67,12 @@ live_design! {
             verification_badge = <VerificationBadge> {}
         }
 
+        <View> {
+            align: { x: 1.0, y: 1.0 }
+            padding: 6
+            presence_dot = <PresenceDot> {}
+        }
+
         profile_tooltip = <ColorTooltip> {}
 
     }
@@ -93,14 +106,59 @@ live_design! {
         }
     }
 
+    Inbox = <View> {
+        width: Fit, height: Fit
+        // FIXME: the extra padding on the right is because the icon is not correctly centered
+        // within its parent
+        padding: {top: 8, left: 8, right: 12, bottom: 8}
+        align: {x: 0.5, y: 0.5}
+        inbox_button = <Button> {
+            draw_bg: {
+                fn pixel(self) -> vec4 {
+                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                    return sdf.result
+                }
+            }
+            draw_icon: {
+                svg_file: (ICON_INBOX),
+                fn get_color(self) -> vec4 {
+                    return #1C274C;
+                }
+            }
+            icon_walk: {width: 25, height: Fit}
+        }
+    }
+
+    Contacts = <View> {
+        width: Fit, height: Fit
+        // FIXME: the extra padding on the right is because the icon is not correctly centered
+        // within its parent
+        padding: {top: 8, left: 8, right: 12, bottom: 8}
+        align: {x: 0.5, y: 0.5}
+        contacts_button = <Button> {
+            draw_bg: {
+                fn pixel(self) -> vec4 {
+                    let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                    return sdf.result
+                }
+            }
+            draw_icon: {
+                svg_file: (ICON_CONTACTS),
+                fn get_color(self) -> vec4 {
+                    return #1C274C;
+                }
+            }
+            icon_walk: {width: 25, height: Fit}
+        }
+    }
+
     Settings = <View> {
         width: Fit, height: Fit
         // FIXME: the extra padding on the right is because the icon is not correctly centered
         // within its parent
         padding: {top: 8, left: 8, right: 12, bottom: 8}
         align: {x: 0.5, y: 0.5}
-        <Button> {
-            enabled: false
+        settings_button = <Button> {
             draw_bg: {
                 fn pixel(self) -> vec4 {
                     let sdf = Sdf2d::viewport(self.pos * self.rect_size);
@@ -110,8 +168,7 @@ live_design! {
             draw_icon: {
                 svg_file: (ICON_SETTINGS),
                 fn get_color(self) -> vec4 {
-                    return (COLOR_TEXT_IDLE);
-                    // return #x566287; // grayed-out #1C274C until enabled
+                    return #1C274C;
                 }
             }
             icon_walk: {width: 25, height: Fit}
@@ -135,6 +192,10 @@ live_design! {
 
             <Home> {}
 
+            <Inbox> {}
+
+            <Contacts> {}
+
             <Filler> {}
 
             <Settings> {}
@@ -160,6 +221,14 @@ live_design! {
 
             <Filler> {}
 
+            <Inbox> {}
+
+            <Filler> {}
+
+            <Contacts> {}
+
+            <Filler> {}
+
             <Settings> {}
 
             <Filler> {}
@@ -188,8 +257,22 @@ impl Widget for Profile {
     fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
         self.view.handle_event(cx, event, scope);
 
+        // Clicking on the user's avatar opens the settings screen,
+        // in which the user can change their profile, presence and status.
+        if let Hit::FingerUp(fe) = event.hits(cx, self.view.view(id!(text_view)).area()) {
+            if fe.is_over && fe.is_primary_hit() && fe.was_tap() {
+                cx.widget_action(self.widget_uid(), &scope.path, SettingsAction::Open);
+            }
+        }
+
         if let Event::Actions(actions) = event {
             for action in actions {
+                // Redraw our presence dot once our own presence has been fetched.
+                if let Some(PresenceAction::Updated(user_id)) = action.downcast_ref() {
+                    if current_user_id().as_ref() == Some(user_id) {
+                        self.redraw(cx);
+                    }
+                }
                 match action.as_widget_action().cast() {
                     ProfileTooltipAction::Show { pos, text, color } => {
                         self.view.color_tooltip(id!(profile_tooltip))
@@ -205,6 +288,8 @@ impl Widget for Profile {
     }
 
     fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        let presence = current_user_id().and_then(|user_id| get_presence(&user_id));
+        show_presence_dot(cx, &self.view.view(id!(presence_dot)), presence.as_ref());
         self.view.draw_walk(cx, scope, walk)
     }
 }
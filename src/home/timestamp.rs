@@ -0,0 +1,146 @@
+//! A label showing the short time of a timeline event,
+//! which shows a tooltip with the event's full date and delivery details when hovered.
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::MilliSecondsSinceUnixEpoch;
+
+use crate::{app::AppState, utils::timeline_timestamp_text};
+
+use super::room_screen::{room_screen_tooltip_position_helper, RoomScreenTooltipActions};
+
+/// The width of the tooltip shown when hovering over a timestamp.
+const TOOLTIP_WIDTH: f64 = 260.0;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+
+    pub Timestamp = {{Timestamp}} {
+        width: Fit, height: Fit
+        padding: { bottom: 0.0, left: 0.0, right: 0.0 }
+
+        ts_label = <Label> {
+            width: Fit, height: Fit
+            padding: 0.0
+            draw_text: {
+                text_style: <TIMESTAMP_TEXT_STYLE> {},
+                color: (TIMESTAMP_TEXT_COLOR)
+            }
+            text: " "
+        }
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct Timestamp {
+    #[deref] view: View,
+    /// The text shown in a tooltip when this timestamp is hovered, if any.
+    #[rust] tooltip_text: Option<String>,
+    /// The time shown by this timestamp, if its text is derived from it upon each draw
+    /// such that it stays up to date with the current time, e.g., "5 mins ago".
+    #[rust] timestamp: Option<MilliSecondsSinceUnixEpoch>,
+}
+
+impl Widget for Timestamp {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+
+        let Some(tooltip_text) = self.tooltip_text.as_ref() else { return };
+        let uid = self.widget_uid();
+        let area = self.view.area();
+        match event.hits(cx, area) {
+            Hit::FingerHoverIn(_) => {
+                let Some(app_state) = scope.data.get::<AppState>() else { return };
+                let Some(window_geom) = &app_state.window_geom else { return };
+                let (tooltip_pos, callout_offset, pointing_up) =
+                    room_screen_tooltip_position_helper(area.rect(cx), window_geom, TOOLTIP_WIDTH);
+                cx.widget_action(uid, &scope.path, RoomScreenTooltipActions::HoverInTimestamp {
+                    tooltip_pos,
+                    tooltip_width: TOOLTIP_WIDTH,
+                    callout_offset,
+                    text: tooltip_text.clone(),
+                    pointing_up,
+                });
+            }
+            Hit::FingerHoverOut(_) => {
+                cx.widget_action(uid, &scope.path, RoomScreenTooltipActions::HoverOut);
+            }
+            _ => { }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        if let Some(timestamp) = self.timestamp {
+            let text = timeline_timestamp_text(&timestamp)
+                .unwrap_or_else(|| timestamp.get().to_string());
+            self.label(id!(ts_label)).set_text(cx, &text);
+        }
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl Timestamp {
+    /// Sets the displayed text of this timestamp and the text of its tooltip.
+    ///
+    /// If `tooltip_text` is `None`, no tooltip is shown when hovering over this timestamp.
+    pub fn set_text_and_tooltip(&mut self, cx: &mut Cx, text: &str, tooltip_text: Option<String>) {
+        self.label(id!(ts_label)).set_text(cx, text);
+        self.tooltip_text = tooltip_text;
+        self.timestamp = None;
+    }
+
+    /// Sets the time shown by this timestamp and the text of its tooltip.
+    ///
+    /// The displayed text is refreshed upon each draw; see [`timeline_timestamp_text()`].
+    pub fn set_timestamp_and_tooltip(
+        &mut self,
+        cx: &mut Cx,
+        timestamp: MilliSecondsSinceUnixEpoch,
+        tooltip_text: Option<String>,
+    ) {
+        self.timestamp = Some(timestamp);
+        self.tooltip_text = tooltip_text;
+        self.redraw(cx);
+    }
+}
+
+impl TimestampRef {
+    /// See [`Timestamp::set_text_and_tooltip()`].
+    pub fn set_text_and_tooltip(&self, cx: &mut Cx, text: &str, tooltip_text: Option<String>) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_text_and_tooltip(cx, text, tooltip_text);
+        }
+    }
+
+    /// See [`Timestamp::set_timestamp_and_tooltip()`].
+    pub fn set_timestamp_and_tooltip(
+        &self,
+        cx: &mut Cx,
+        timestamp: MilliSecondsSinceUnixEpoch,
+        tooltip_text: Option<String>,
+    ) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.set_timestamp_and_tooltip(cx, timestamp, tooltip_text);
+        }
+    }
+
+    /// Returns the tooltip action emitted when this timestamp was hovered over, if any.
+    pub fn hover_in(&self, actions: &Actions) -> RoomScreenTooltipActions {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            item.cast()
+        } else {
+            RoomScreenTooltipActions::None
+        }
+    }
+
+    /// Returns `true` if the mouse stopped hovering over this timestamp.
+    pub fn hover_out(&self, actions: &Actions) -> bool {
+        if let Some(item) = actions.find_widget_action(self.widget_uid()) {
+            matches!(item.cast(), RoomScreenTooltipActions::HoverOut)
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,1131 @@
+//! A lightweight string-resource layer for translating Robrix's user-facing text.
+//!
+//! Each translatable string is identified by a `&'static str` key, e.g., `"preview.message_deleted"`.
+//! The English table is the source of truth: every key must exist there.
+//! Other languages may omit any key, in which case the English text is used as a fallback.
+//!
+//! Strings may contain named placeholders like `{user}`, which are filled in by [`tr_args()`].
+//!
+//! Text that is defined statically within a `live_design!` block cannot be translated
+//! by this module; widgets that want translated labels must set their text from Rust code
+//! (see the `SettingsScreen` for an example).
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use tracing::warn;
+use serde::{Deserialize, Serialize};
+
+/// The languages that Robrix's UI can be displayed in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum Language {
+    #[default]
+    English = 0,
+    SimplifiedChinese = 1,
+}
+
+impl Language {
+    /// All supported languages, in the order they are shown in the language picker.
+    pub const ALL: [Language; 2] = [Language::English, Language::SimplifiedChinese];
+
+    /// Returns the name of this language, written in that language itself.
+    pub fn display_name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::SimplifiedChinese => "简体中文",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        Self::ALL.get(value as usize).copied().unwrap_or_default()
+    }
+
+    fn table(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => EN,
+            Language::SimplifiedChinese => ZH_CN,
+        }
+    }
+}
+
+static CURRENT_LANGUAGE: AtomicU8 = AtomicU8::new(Language::English as u8);
+
+/// Sets the language that all subsequent calls to [`tr()`] and [`tr_args()`] will use.
+pub fn set_language(language: Language) {
+    CURRENT_LANGUAGE.store(language as u8, Ordering::Relaxed);
+}
+
+/// Returns the language currently used to display the UI.
+pub fn current_language() -> Language {
+    Language::from_u8(CURRENT_LANGUAGE.load(Ordering::Relaxed))
+}
+
+fn lookup(table: &'static [(&'static str, &'static str)], key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, v)| *v)
+}
+
+/// Returns the translation of the given `key` in the current language.
+///
+/// Falls back to the English text if the current language lacks a translation,
+/// and to the key itself if the key is unknown.
+pub fn tr(key: &'static str) -> &'static str {
+    lookup(current_language().table(), key)
+        .or_else(|| lookup(EN, key))
+        .unwrap_or_else(|| {
+            warn!("i18n: missing string resource for key {key:?}");
+            key
+        })
+}
+
+/// Returns the translation of the given `key` in the current language,
+/// with each `{name}` placeholder replaced by its corresponding value in `args`.
+///
+/// The template is scanned only once, so any placeholders within the values themselves
+/// (e.g., a room named `{user}`) are left as-is rather than being substituted again.
+/// Placeholders without a corresponding value are also left as-is.
+pub fn tr_args(key: &'static str, args: &[(&str, &str)]) -> String {
+    fill_placeholders(tr(key), args)
+}
+
+/// Replaces each `{name}` placeholder in the given template with its value in `args`.
+fn fill_placeholders(template: &str, args: &[(&str, &str)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        text.push_str(&rest[..open]);
+        let after_open = &rest[open + 1..];
+        let value = after_open.find('}').and_then(|close| {
+            let name = &after_open[..close];
+            args.iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, value)| (*value, close))
+        });
+        match value {
+            Some((value, close)) => {
+                text.push_str(value);
+                rest = &after_open[close + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = after_open;
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+
+/// The English string table, which must contain every key.
+const EN: &[(&str, &str)] = &[
+    // Settings screen
+    ("settings.title", "Settings"),
+    ("settings.language", "Language"),
+    ("settings.language.description", "The language used to display Robrix's interface."),
+    ("settings.display", "Display Density"),
+    ("settings.display.description", "Compact density shows more messages and rooms at once by using smaller avatars and less spacing."),
+    ("settings.display.comfortable", "Comfortable"),
+    ("settings.display.compact", "Compact"),
+    ("settings.app_badge", "App Icon Badge"),
+    ("settings.app_badge.description", "Which unread count to show on the app icon. This is currently only supported on macOS."),
+    ("settings.app_badge.none", "No badge"),
+    ("settings.app_badge.mentions", "Unread mentions"),
+    ("settings.app_badge.all", "All unread messages"),
+    ("settings.proxy", "Network Proxy"),
+    ("settings.proxy.description", "Connect to your homeserver through an HTTP or SOCKS5 proxy, e.g., when behind a firewall or using Tor."),
+    ("settings.proxy.enabled", "Use a proxy"),
+    ("settings.proxy.save", "Save proxy settings"),
+    ("settings.proxy.saved", "Proxy settings saved. They will take effect the next time Robrix starts."),
+    ("settings.proxy.invalid", "Please enter a valid proxy host and port."),
+    ("settings.proxy.invalid_port", "The proxy port must be a number between 1 and 65535."),
+    ("settings.proxy.host_placeholder", "Host, e.g., 127.0.0.1"),
+    ("settings.proxy.port_placeholder", "Port"),
+    ("settings.proxy.username_placeholder", "Username (optional)"),
+    ("settings.proxy.password_placeholder", "Password (optional)"),
+    ("settings.timeline", "Timeline"),
+    ("settings.timeline.description", "Hidden events can still be shown in individual rooms using the button next to the message input."),
+    ("settings.timeline.hide_state_events", "Hide membership and profile changes"),
+    ("settings.timeline.colorize_usernames", "Show each sender's name in their own color"),
+    ("settings.timeline.relative_timestamps", "Show times like \"5 mins ago\""),
+    ("settings.timeline.relative_timestamps.never", "Never"),
+    ("settings.timeline.relative_timestamps.hour", "Up to 1 hour"),
+    ("settings.timeline.relative_timestamps.day", "Up to 1 day"),
+    ("settings.timeline.relative_timestamps.week", "Up to 1 week"),
+    ("settings.timeline.read_marker_clearing", "Move the \"New Messages\" marker"),
+    ("settings.timeline.read_marker_clearing.immediately", "Immediately"),
+    ("settings.timeline.read_marker_clearing.after_viewing", "After viewing for a while"),
+    ("settings.timeline.read_marker_clearing.when_marked_read", "Only when marked as read"),
+    ("settings.timeline.read_marker_delay", "Seconds of viewing before moving it"),
+    ("settings.timeline.read_marker_delay_saved", "Saved the read marker delay."),
+    ("settings.timeline.invalid_read_marker_delay", "The read marker delay must be a positive whole number."),
+    ("composer.mention_room", "Notify everyone in this room"),
+    ("room.call.in_progress", "A group call is in progress."),
+    ("room.call.join", "Join call"),
+    ("room.call.open_failed", "Could not open Element Call."),
+    ("room.room_mention.confirm", "This message mentions @room and will notify {count} people in this room. Send it anyway?"),
+    ("room.room_mention.cancel", "Cancel"),
+    ("room.room_mention.send", "Notify everyone"),
+    ("media.tap_to_download_image", "Tap to download this image."),
+    ("media.loading_image", "Loading image..."),
+    ("rooms_list.typing_one", "{user} is typing"),
+    ("rooms_list.typing_two", "{user1} and {user2} are typing"),
+    ("rooms_list.typing_many", "Several people are typing"),
+    ("room_hover_preview.loading", "Loading..."),
+    ("room_hover_preview.members", "{count} members"),
+    ("room_hover_preview.no_topic", "No topic"),
+    ("room_hover_preview.no_messages", "No recent messages"),
+    ("event_source.title", "Event Source"),
+    ("event_source.ids", "Event ID: {event_id}\nRoom ID: {room_id}"),
+    ("event_source.decryption", "Decryption"),
+    ("event_source.decrypted", "This event was decrypted successfully."),
+    ("event_source.not_decrypted", "This event could not be decrypted."),
+    ("event_source.session_id", "Session ID: {value}"),
+    ("event_source.sender_key", "Sender key: {value}"),
+    ("event_source.sender_device", "Sent by {user} from device {device}"),
+    ("event_source.verification", "Verification: {value}"),
+    ("event_source.utd_cause", "Reason: {value}"),
+    ("event_source.unknown", "unknown"),
+    ("event_source.verification.verified", "the sending device is signed by the sender's identity, which you have verified."),
+    ("event_source.verification.unverified_identity", "the sending device is signed by the sender's identity, but you haven't verified that identity."),
+    ("event_source.verification.violation", "the sender's identity was previously verified, but it has changed since."),
+    ("event_source.verification.unsigned_device", "the sending device is not signed by the sender's identity."),
+    ("event_source.verification.missing_device", "the sending device is unknown or has been deleted."),
+    ("event_source.verification.insecure_source", "the keys for this event came from an insecure source, e.g., key backup or a forwarded key."),
+    ("event_source.no_json", "The source of this event isn't available, e.g., because it hasn't been sent yet."),
+    ("event_source.not_found", "Couldn't find this message in the timeline."),
+    ("event_source.copy", "Copy"),
+    ("event_source.close", "Close"),
+    ("snippets.add", "Add snippet"),
+    ("snippets.remove", "Remove"),
+    ("snippets.saved", "Saved the snippet {trigger}."),
+    ("snippets.text_empty", "Enter the text that the snippet should insert."),
+    ("snippets.name_empty", "Enter a name for the snippet, e.g., /sig."),
+    ("snippets.name_too_long", "Snippet names can be at most 32 characters long."),
+    ("snippets.name_invalid_characters", "Snippet names can only contain letters, digits, '-', and '_'."),
+    ("snippets.name_reserved", "That name is already used by a message command."),
+    ("spell_check.misspelled", "Spelling:"),
+    ("spell_check.suggestions_for", "Replace \"{word}\" with:"),
+    ("spell_check.no_suggestions", "No suggestions for \"{word}\"."),
+    ("spell_check.load_failed", "Failed to load the spell checking dictionary for {language}: {error}"),
+    ("scheduled.prompt", "Send this message later:"),
+    ("scheduled.schedule", "Schedule"),
+    ("scheduled.cancel", "Cancel"),
+    ("scheduled.empty", "Enter a message to schedule first."),
+    ("scheduled.invalid_time", "Enter a future time as HH:MM or YYYY-MM-DD HH:MM."),
+    ("scheduled.scheduled", "Scheduled the message. It will be sent while Robrix is running."),
+    ("scheduled.schedule_failed", "Failed to schedule the message: {error}"),
+    ("scheduled.cancelled", "Cancelled the scheduled message."),
+    ("scheduled.cancel_failed", "Failed to cancel the scheduled message: {error}"),
+    ("scheduled.row", "{time}: {text}"),
+    ("scheduled.room_not_found", "the room is no longer available"),
+    ("scheduled.send_failed", "Failed to send scheduled message \"{text}\": {error}"),
+    ("scheduled.row_failed", "Failed to send ({error}): {text}"),
+    ("scheduled.retry", "Retry"),
+    ("room.state_events.hidden", "Membership and profile changes are now hidden in this room."),
+    ("room.state_events.shown", "Membership and profile changes are now shown in this room."),
+    ("room.state_event_run.joined", "{names} joined"),
+    ("room.state_event_run.left", "{names} left"),
+    ("room.state_event_run.changed_profiles", "{names} changed their profiles"),
+    ("room.state_event_run.mixed", "{count} membership and profile changes by {names}"),
+    ("room.state_event_run.show", "Show all"),
+    ("room.state_event_run.hide", "Hide"),
+    ("timestamp.server_time", "Server time: {time} ({millis})"),
+    ("timestamp.sending", "Sending..."),
+    ("timestamp.send_failed", "Failed to send"),
+    ("timestamp.sent", "Sent"),
+    ("timestamp.received", "Received"),
+    ("timestamp.edited", "Edited"),
+    ("message.retry_send", "Retry"),
+    ("message.delete_unsent", "Delete"),
+    ("message.quote_attribution", "{user} wrote:"),
+    ("message.quote_not_found", "Couldn't find the message to quote."),
+    ("message.retry_send_not_found", "Couldn't find the unsent message to retry."),
+    ("message.read_marker_only_forwards", "The read marker can only be moved to a message after its current position."),
+    ("message.read_marker_move_failed", "Failed to move the read marker: {error}"),
+    ("message.retry_send_failed", "Failed to retry sending message: {error}"),
+    ("room_menu.mark_read", "Mark as Read"),
+    ("room_menu.toggle_mute", "Mute / Unmute"),
+    ("room_menu.hide_read_receipts", "Hide Read Receipts Here"),
+    ("room_menu.share_read_receipts", "Share Read Receipts Here"),
+    ("room_menu.read_receipts_hidden", "Other members of this room will no longer see which messages you have read."),
+    ("room_menu.read_receipts_shared", "Other members of this room can now see which messages you have read."),
+    ("room_menu.favorite", "Add to Favorites"),
+    ("room_menu.unfavorite", "Remove from Favorites"),
+    ("room_menu.copy_link", "Copy Link to Room"),
+    ("room_menu.share", "Share Room..."),
+    ("room_menu.wallpaper", "Set Wallpaper..."),
+    ("room_menu.leave", "Leave Room"),
+    ("room_menu.link_copied", "Copied link to room."),
+    ("room_menu.mark_read_failed", "Failed to mark room as read: {error}"),
+    ("room_menu.muted", "Muted room notifications."),
+    ("room_menu.unmuted", "Unmuted room notifications."),
+    ("room_menu.mute_failed", "Failed to change room notifications: {error}"),
+    ("room_menu.favorite_failed", "Failed to update favorites: {error}"),
+    ("room_menu.left", "Left {room}."),
+    ("room_menu.leave_failed", "Failed to leave {room}: {error}"),
+    ("inbox.title", "Inbox"),
+    ("inbox.description", "Messages from all rooms that mention you or reply to your messages."),
+    ("inbox.empty", "Nothing here yet. Mentions and replies will show up here as they arrive."),
+    ("inbox.close", "Close"),
+    ("inbox.jump", "Jump"),
+    ("inbox.mentioned_in", "{user} mentioned you in {room}"),
+    ("inbox.replied_in", "{user} replied to you in {room}"),
+    ("contacts.title", "Contacts"),
+    ("contacts.description", "People you have direct message rooms with. Enter a user ID to start a new conversation, or an email address or phone number to find someone via an identity server."),
+    ("contacts.loading", "Loading contacts..."),
+    ("contacts.empty", "No contacts yet. Direct message rooms will show up here."),
+    ("contacts.close", "Close"),
+    ("contacts.message", "Message"),
+    ("contacts.invalid_user_id", "\"{user}\" is not a valid user ID, e.g., @alice:matrix.org"),
+    ("contacts.cannot_add_self", "You cannot send a direct message to yourself."),
+    ("contacts.opening_dm", "Opening a direct message with {user}..."),
+    ("contacts.open_dm_failed", "Failed to open a direct message with {user}: {error}"),
+    ("contacts.identity_server_consent", "To find people by email address or phone number, Robrix needs to send a hashed copy of the address you entered to the identity server {server}. Addresses are never sent in plain text. Do you agree to use this identity server and its terms below?"),
+    ("contacts.consent_accept", "Agree"),
+    ("contacts.consent_cancel", "Cancel"),
+    ("contacts.looking_up", "Looking up {address}..."),
+    ("contacts.lookup_found", "{address} belongs to {user}. Press Message to start a conversation."),
+    ("contacts.lookup_not_found", "No Matrix user was found for {address}."),
+    ("contacts.lookup_failed", "Failed to look up {address}: {error}"),
+    ("share_room.title", "Share Room"),
+    ("share_room.prompt", "Anyone with this link can find and join {room}."),
+    ("share_room.close", "Close"),
+    ("share_room.copy_link", "Copy Link"),
+    ("share_room.show_qr_code", "Show QR Code"),
+    ("share_room.hide_qr_code", "Hide QR Code"),
+    ("share_room.qr_code_failed", "Failed to generate a QR code for this room."),
+    ("leave_room.title", "Leave Room"),
+    ("leave_room.prompt", "Are you sure you want to leave {room}?"),
+    ("leave_room.forget", "Also forget this room, removing it from your list of past rooms"),
+    ("leave_room.last_admin_warning", "You are the last admin of this room. Nobody will be able to change its settings after you leave. To promote another member to admin first, enter their user ID:"),
+    ("leave_room.cancel", "Cancel"),
+    ("leave_room.leave", "Leave"),
+    ("leave_room.invalid_user_id", "\"{user_id}\" is not a valid user ID."),
+    ("leave_room.transfer_failed", "Did not leave the room, because promoting {user_id} to admin failed: {error}"),
+    ("leave_room.forget_failed", "Failed to forget {room}: {error}"),
+    ("room_filter.all", "All"),
+    ("room_filter.unread_only", "Only rooms with unread messages"),
+    ("room_filter.dms_only", "Only direct messages"),
+    ("room_filter.hide_muted", "Hide muted rooms"),
+    ("room_filter.delete", "Delete"),
+    ("room_filter.cancel", "Cancel"),
+    ("room_filter.save", "Save"),
+    ("room_filter.empty_name", "Please enter a name for this filter."),
+    ("room_wallpaper.title", "Room Wallpaper"),
+    ("room_wallpaper.prompt", "Choose a background for {room}. It is only visible to you, on all of your devices."),
+    ("room_wallpaper.color", "Background color (e.g., #336699)"),
+    ("room_wallpaper.image_url", "Background image (mxc:// URL)"),
+    ("room_wallpaper.dim", "Dimming"),
+    ("room_wallpaper.cancel", "Cancel"),
+    ("room_wallpaper.remove", "Remove"),
+    ("room_wallpaper.save", "Save"),
+    ("room_wallpaper.invalid_color", "\"{color}\" is not a valid hex color."),
+    ("room_wallpaper.invalid_image_url", "\"{url}\" is not a valid mxc:// URL."),
+    ("room_wallpaper.empty", "Enter a background color or image, or remove the wallpaper instead."),
+    ("room_wallpaper.save_failed", "Failed to save the room wallpaper: {error}"),
+    ("settings.profile", "Profile"),
+    ("settings.profile.description", "Your display name is visible to everyone in the rooms you've joined."),
+    ("profile.save_display_name", "Save"),
+    ("profile.not_loaded", "Your profile hasn't been loaded yet, please try again."),
+    ("profile.display_name_saved", "Display name updated."),
+    ("profile.display_name_failed", "Failed to update display name: {error}"),
+    ("presence.online", "Online"),
+    ("presence.unavailable", "Away"),
+    ("presence.offline", "Offline"),
+    ("presence.set_status", "Set status"),
+    ("presence.status_placeholder", "Status message"),
+    ("presence.status_saved", "Your status has been updated."),
+    ("presence.status_failed", "Failed to update status: {error}"),
+    ("profile.shared_rooms", "Rooms in common: {count}"),
+    ("profile.bridged_from", "Bridged from {platform}"),
+    ("profile.remove_messages", "Remove Recent Messages"),
+    ("profile.remove_messages_progress", "Removed {redacted} of {found} messages found so far..."),
+    ("profile.remove_messages_reason", "Reason (optional)"),
+    ("profile.remove_messages_scan_limit", "Only the latest {count} events in this room were checked."),
+    ("profile.remove_messages_finished", "Removed {redacted} messages ({failed} failed)."),
+    ("profile.remove_messages_failed", "Failed to remove recent messages: {error}"),
+    ("profile.devices", "Devices"),
+    ("profile.device.verified", "{name} ({device_id})\nVerified"),
+    ("profile.device.unverified", "{name} ({device_id})\nNot verified"),
+    ("profile.device.current_verified", "{name} ({device_id}) — this device\nVerified"),
+    ("profile.device.current_unverified", "{name} ({device_id}) — this device\nNot verified"),
+    ("profile.device.unknown_name", "Unknown device"),
+    ("profile.device.verify", "Verify"),
+    ("verification.unknown_device", "Cannot verify unknown device {device} of user {user}."),
+    ("verification.get_device_failed", "Failed to get device {device} of user {user}: {error}"),
+    ("verification.request_failed", "Failed to request verification of device {device}: {error}"),
+    ("deep_link.alias_not_found", "Could not find a room with the alias {alias}."),
+    ("deep_link.no_dm_room", "You don't have a direct message room with {user_id}."),
+    ("deep_link.unsupported", "This kind of Matrix link isn't supported yet."),
+    ("deep_link.not_joined", "You haven't joined this room, so it was opened in your web browser."),
+    ("deep_link.open_failed", "Could not open the link {url}."),
+    ("export.title", "Export Chat"),
+    ("export.prompt", "Export the full history of {room} to a file in your downloads folder. This may take a while for large rooms.\n\nChoose a format: HTML is easy to read, JSON contains every event. Or, export the room's member list as a CSV spreadsheet."),
+    ("export.members_csv", "Members (CSV)"),
+    ("export.members_progress", "Exporting the member list..."),
+    ("export.cancel", "Cancel"),
+    ("export.json", "JSON"),
+    ("export.html", "HTML"),
+    ("export.ok", "Ok"),
+    ("export.progress", "Exporting... {count} events fetched so far."),
+    ("export.finished", "Export complete. Saved to:\n{path}"),
+    ("export.failed", "Export failed: {error}"),
+    ("moderation.title", "Moderation: {room}"),
+    ("moderation.description", "Recent activity among the loaded messages in this room. Actions take effect immediately."),
+    ("moderation.close", "Close"),
+    ("moderation.recent_joins", "Recent joins ({count})"),
+    ("moderation.recent_messages", "Recent messages ({count})"),
+    ("moderation.recent_redactions", "Recently removed messages ({count})"),
+    ("moderation.kick", "Kick"),
+    ("moderation.ban", "Ban"),
+    ("moderation.remove", "Remove"),
+    ("moderation.kicked", "Kicked {user_id}."),
+    ("moderation.kick_failed", "Failed to kick {user_id}: {error}"),
+    ("moderation.banned", "Banned {user_id}."),
+    ("moderation.ban_failed", "Failed to ban {user_id}: {error}"),
+    ("room_access.title", "Room access"),
+    ("room_access.history", "Who can read history?"),
+    ("room_access.history.world_readable", "Anyone, including people who haven't joined, can read this room's entire history."),
+    ("room_access.history.shared", "Members can read the entire history, including messages sent before they joined."),
+    ("room_access.history.invited", "Members can only read messages sent after they were invited."),
+    ("room_access.history.joined", "Members can only read messages sent after they joined."),
+    ("room_access.history.world_readable.label", "Anyone"),
+    ("room_access.history.shared.label", "Members (all history)"),
+    ("room_access.history.invited.label", "Members (since invited)"),
+    ("room_access.history.joined.label", "Members (since joined)"),
+    ("room_access.guests", "Guest access"),
+    ("room_access.guests.can_join", "Guest accounts, which don't need to register, can join this room."),
+    ("room_access.guests.forbidden", "Only registered users can join this room."),
+    ("room_access.guests.can_join.label", "Guests can join"),
+    ("room_access.guests.forbidden.label", "Guests are forbidden"),
+    ("room_access.history_updated", "Updated who can read this room's history."),
+    ("room_access.guests_updated", "Updated guest access to this room."),
+    ("room_access.update_failed", "Failed to update room access: {error}"),
+    ("room_upgrade.title", "Room version"),
+    ("room_upgrade.description", "Upgrading replaces this room with a new room that uses the given room version (or the server's default version if left empty). Members are pointed to the new room, and this room becomes read-only."),
+    ("room_upgrade.upgrade", "Upgrade"),
+    ("room_upgrade.invalid_version", "\"{version}\" is not a valid room version."),
+    ("room_upgrade.starting", "Starting the upgrade..."),
+    ("room_upgrade.upgrading", "Creating the new room..."),
+    ("room_upgrade.announcing", "Announcing the upgrade in this room..."),
+    ("room_upgrade.finished", "This room was upgraded and replaced by {room_id}."),
+    ("room_upgrade.failed", "Failed to upgrade this room: {error}"),
+    ("policy.title", "Policy lists"),
+    ("policy.none", "This room is not subscribed to any policy lists. Members matching a subscribed list's ban rules are banned automatically."),
+    ("policy.subscribe", "Subscribe"),
+    ("policy.unsubscribe", "Unsubscribe"),
+    ("policy.invalid_list", "\"{list}\" is not a valid room ID or alias."),
+    ("policy.subscribed", "Subscribed to policy list {list}."),
+    ("policy.subscribe_failed", "Failed to subscribe to policy list {list}: {error}"),
+    ("policy.unsubscribe_failed", "Failed to unsubscribe from policy list {list}: {error}"),
+    ("policy.auto_banned", "Banned {user_id} due to policy list {list}: {reason}"),
+    ("settings.privacy", "Privacy"),
+    ("settings.privacy.description", "Choose what other room members can see about your activity."),
+    ("settings.privacy.send_read_receipts", "Send read receipts"),
+    ("settings.privacy.send_typing_notices", "Send typing notifications"),
+    ("settings.privacy.hide_content_when_unfocused", "Hide all content while Robrix isn't the focused window, e.g., while sharing your screen"),
+    ("settings.composer", "Composer"),
+    ("settings.composer.description", "Options for writing messages."),
+    ("settings.composer.spell_check", "Check spelling while typing"),
+    ("settings.composer.enter_key_behavior", "When pressing Enter"),
+    ("settings.composer.enter_key_behavior.newline", "Insert a newline (Ctrl/Cmd+Enter sends)"),
+    ("settings.composer.enter_key_behavior.send", "Send the message (Ctrl/Cmd+Enter inserts a newline)"),
+    ("settings.composer.formatting_toolbar", "Show formatting buttons above the message input"),
+    ("settings.composer.spell_check_language", "Spell checking language"),
+    ("settings.composer.spell_check_language_saved", "Saved the spell checking language."),
+    ("settings.composer.unknown_spell_check_language", "No dictionary is installed for \"{language}\"."),
+    ("settings.composer.dictionaries", "Installed dictionaries: {languages}"),
+    ("settings.composer.no_dictionaries", "No Hunspell dictionaries were found. Add .aff and .dic files to the \"dictionaries\" folder in Robrix's data directory."),
+    ("settings.snippets", "Snippets"),
+    ("settings.snippets.description", "Saved blocks of text that you can insert into a message by typing / followed by the snippet's name, e.g., /sig. Type a space after the full name or pick a snippet from the suggestions to insert it."),
+    ("app.content_hidden", "Content is hidden while Robrix isn't focused."),
+    ("settings.app_lock", "App Lock"),
+    ("settings.app_lock.description", "Require a PIN or biometric authentication to view Robrix upon launch and after a period of inactivity. This does not encrypt any data on this device."),
+    ("settings.app_lock.enabled", "The app lock is enabled."),
+    ("settings.app_lock.disabled", "The app lock is disabled. Set a PIN to enable it."),
+    ("settings.app_lock.set_pin", "Set PIN"),
+    ("settings.app_lock.remove", "Disable app lock"),
+    ("settings.app_lock.timeout", "Lock the app"),
+    ("settings.app_lock.timeout.on_launch", "Only on launch"),
+    ("settings.app_lock.timeout.one_minute", "After 1 minute"),
+    ("settings.app_lock.timeout.five_minutes", "After 5 minutes"),
+    ("settings.app_lock.timeout.fifteen_minutes", "After 15 minutes"),
+    ("settings.app_lock.timeout.one_hour", "After 1 hour"),
+    ("settings.app_lock.biometrics", "Allow unlocking with biometrics (e.g., Touch ID or Windows Hello)"),
+    ("settings.app_lock.pin_saved", "Your app lock PIN has been saved."),
+    ("settings.app_lock.invalid_pin", "The PIN must consist of {min} to {max} digits."),
+    ("settings.app_lock.removed", "The app lock has been disabled."),
+    ("app_lock.title", "Robrix is locked"),
+    ("app_lock.prompt", "Enter your PIN to unlock Robrix."),
+    ("app_lock.unlock", "Unlock"),
+    ("app_lock.use_biometrics", "Use biometrics"),
+    ("app_lock.wrong_pin", "Incorrect PIN. Please try again."),
+    ("app_lock.locked_out", "Too many incorrect attempts. Please try again in {seconds} seconds."),
+    ("app_lock.biometrics_failed", "Biometric authentication failed. Please enter your PIN instead."),
+    ("app_lock.biometrics_reason", "Unlock Robrix"),
+    ("settings.storage", "Storage"),
+    ("settings.storage.description", "Databases still used by a logged-in account are never removed."),
+    ("settings.storage.sdk_store", "Databases"),
+    ("settings.storage.media_cache", "Media cache (in memory)"),
+    ("settings.storage.temp_storage", "Temporary files"),
+    ("settings.storage.remove_unused", "Remove unused"),
+    ("settings.storage.clear", "Clear"),
+    ("settings.storage.media_auto_download", "Download media automatically"),
+    ("settings.storage.media_auto_download.always", "Always"),
+    ("settings.storage.media_auto_download.unmetered", "Only when not on a metered network"),
+    ("settings.storage.media_auto_download.never", "Never"),
+    ("settings.storage.media_cache_max", "Media cache limit (MB)"),
+    ("settings.storage.save", "Save"),
+    ("settings.storage.media_cache_max_saved", "Media cache limit saved."),
+    ("settings.storage.invalid_media_cache_max", "The media cache limit must be a positive whole number."),
+    ("settings.storage.temp_storage_max", "Temporary files limit (MB)"),
+    ("settings.storage.temp_storage_max_age", "Remove temporary files after (days)"),
+    ("settings.storage.temp_storage_limits_saved", "Temporary file limits saved. They are applied upon the next startup."),
+    ("settings.storage.invalid_temp_storage_limits", "The temporary file limits must be positive whole numbers."),
+    ("settings.storage.usage_of_limit", "{size} of {limit}"),
+    ("settings.storage.cleared", "Freed up {size}."),
+    ("settings.storage.clear_failed", "Failed to clear storage: {error}"),
+    ("settings.diagnostics", "About & Diagnostics"),
+    ("settings.diagnostics.description", "Include this info when reporting a bug. Exported diagnostics also include the app logs, with passwords and access tokens removed."),
+    ("settings.diagnostics.export", "Export diagnostics"),
+    ("settings.diagnostics.copy", "Copy to clipboard"),
+    ("settings.diagnostics.copied", "Diagnostics copied to the clipboard."),
+    ("settings.diagnostics.exported", "Diagnostics saved to {path}"),
+    ("settings.diagnostics.export_failed", "Failed to export diagnostics: {error}"),
+    ("settings.logs", "Developer: Logs"),
+    ("settings.logs.description", "Choose how much detail is logged by each part of Robrix, and view the most recent lines of the current log file. The RUST_LOG environment variable overrides these log levels."),
+    ("settings.logs.ui_level", "User interface log level"),
+    ("settings.logs.sliding_sync_level", "Matrix sync log level"),
+    ("settings.logs.dependencies_level", "Libraries log level"),
+    ("settings.logs.level.off", "Off"),
+    ("settings.logs.level.error", "Errors"),
+    ("settings.logs.level.warn", "Warnings"),
+    ("settings.logs.level.info", "Info"),
+    ("settings.logs.level.debug", "Debug"),
+    ("settings.logs.level.trace", "Trace"),
+    ("settings.logs.follow", "Follow"),
+    ("settings.logs.refresh", "Refresh"),
+    ("settings.logs.no_file", "No log file has been written yet."),
+    ("settings.logs.read_failed", "Failed to read log file {path}: {error}"),
+    ("settings.logs.showing", "Showing the last {count} matching lines of {path}"),
+    ("settings.shortcuts", "Keyboard Shortcuts"),
+    ("settings.shortcuts.description", "Key combinations are written like \"Primary+Shift+K\", where Primary is Command on macOS and Ctrl elsewhere."),
+    ("settings.shortcuts.save", "Save shortcuts"),
+    ("settings.shortcuts.reset", "Reset to defaults"),
+    ("settings.shortcuts.saved", "Keyboard shortcuts saved."),
+    ("settings.shortcuts.invalid", "Invalid shortcut for \"{action}\": {error}"),
+    ("settings.shortcuts.duplicate", "\"{combo}\" is used for more than one shortcut."),
+    ("shortcut.next_unread_room", "Go to next unread room"),
+    ("shortcut.previous_unread_room", "Go to previous unread room"),
+    ("shortcut.focus_search", "Search"),
+    ("shortcut.reply_to_last_message", "Reply to the latest message"),
+    ("shortcut.toggle_sidebar", "Show/hide the sidebar"),
+    ("shortcut.open_settings", "Open settings"),
+    ("shortcut.toggle_performance_overlay", "Show/hide performance metrics"),
+
+    // Text previews of timeline items
+    ("preview.message_deleted", "[Message was deleted]"),
+    ("preview.sticker", "Sticker: <i>{body}</i>"),
+    ("preview.sticker_no_body", "Sticker"),
+    ("preview.unable_to_decrypt", "[Unable to decrypt message]"),
+    ("preview.membership_change", "<i>underwent a membership change</i>"),
+    ("preview.other_state", "<i>initiated another state change</i>"),
+    ("preview.failed_to_parse_message", "[Failed to parse <i>{event_type}</i> message]"),
+    ("preview.failed_to_parse_state", "[Failed to parse <i>{event_type}</i> state]"),
+    ("preview.poll", "Poll: {question}"),
+    ("preview.call_invite", "Incoming call"),
+    ("preview.call_notify", "Call started"),
+    ("preview.audio", "[Audio]: <i>{body}</i>"),
+    ("preview.file", "[File]: <i>{body}</i>"),
+    ("preview.image", "[Image]: <i>{body}</i>"),
+    ("preview.location", "[Location]: <i>{body}</i>"),
+    ("preview.server_notice", "[Server Notice]: <i>{kind} -- {body}</i>"),
+    ("preview.verification_request", "[Verification Request] <i>to user {user}</i>"),
+    ("preview.video", "[Video]: <i>{body}</i>"),
+    ("preview.custom_message", "[Custom message]: {body}"),
+    ("preview.unknown_message", "[Unknown message type]: {body}"),
+
+    // Plaintext bodies of timeline items
+    ("body.unable_to_decrypt", "[Unable to Decrypt]"),
+    ("body.membership_change", "underwent a membership change."),
+    ("body.other_state", "initiated another state change."),
+    ("body.failed_to_parse_message", "Failed to parse {event_type} message. Error: {error}"),
+    ("body.failed_to_parse_state", "Failed to parse {event_type} state; key: {state_key}. Error: {error}"),
+
+    // Redacted messages
+    ("redacted.with_reason", "{redactor} deleted {sender}'s message: {reason}."),
+    ("redacted.own", "{sender} deleted their own message."),
+    ("redacted.by_other", "{redactor} deleted {sender}'s message."),
+    ("redacted.unknown", "{sender}'s message was deleted."),
+
+    // Other state events
+    ("state.aliases", "set this room's aliases to {aliases}."),
+    ("state.avatar", "set this room's avatar picture."),
+    ("state.canonical_alias", "set the main address of this room to {alias}."),
+    ("state.canonical_alias.none", "none"),
+    ("state.create", "created this room (v{version})."),
+    ("state.encryption", "enabled encryption in this room."),
+    ("state.guest_access.can_join", "has allowed guests to join this room."),
+    ("state.guest_access.forbidden", "has forbidden guests from joining this room."),
+    ("state.guest_access.custom", "has set custom guest access rules for this room: {rules}"),
+    ("state.history_visibility.invited", "set this room's history to be visible by invited users, since they were invited."),
+    ("state.history_visibility.joined", "set this room's history to be visible by joined users, since they joined."),
+    ("state.history_visibility.shared", "set this room's history to be visible by joined users, for all of time."),
+    ("state.history_visibility.world_readable", "set this room's history to be visible by anyone for all time."),
+    ("state.history_visibility.custom", "set this room's history to be visible by {visibility}"),
+    ("state.join_rules.public", "set this room to be joinable by anyone."),
+    ("state.join_rules.knock", "set this room to be joinable by invite only or by request."),
+    ("state.join_rules.private", "set this room to be private."),
+    ("state.join_rules.restricted", "set this room to be joinable by invite only or with restrictions."),
+    ("state.join_rules.knock_restricted", "set this room to be joinable by invite only or requestable with restrictions."),
+    ("state.join_rules.invite", "set this room to be joinable by invite only."),
+    ("state.join_rules.custom", "set custom join rules for this room: {rules}"),
+    ("state.pinned_events", "pinned {count} events in this room."),
+    ("state.name", "changed this room's name to {name}."),
+    ("state.power_levels", "set the power levels for this room."),
+    ("state.server_acl", "set the server access control list for this room."),
+    ("state.tombstone", "closed this room and upgraded it to {room}"),
+    ("state.topic", "changed this room's topic to {topic}."),
+    ("state.space_parent", "set this room's parent space to {room}."),
+    ("state.space_child", "added a new child to this space: {room}."),
+    ("state.beacon_info.live", "started sharing their live location."),
+    ("state.beacon_info.stopped", "stopped sharing their live location."),
+
+    // Member profile changes
+    ("profile.display_name_changed", "{old} changed their display name to {new}"),
+    ("profile.display_name_removed", "{old} removed their display name"),
+    ("profile.avatar_changed", "{user} changed their profile picture"),
+    ("profile.avatar_changed_also", " and changed their profile picture"),
+
+    // Room membership changes
+    ("membership.joined", "joined this room."),
+    ("membership.left", "left this room."),
+    ("membership.banned", "banned {user} from this room."),
+    ("membership.unbanned", "unbanned {user} from this room."),
+    ("membership.kicked", "kicked {user} from this room."),
+    ("membership.invited", "invited {user} to this room."),
+    ("membership.kicked_and_banned", "kicked and banned {user} from this room."),
+    ("membership.invitation_accepted", "accepted an invitation to this room."),
+    ("membership.invitation_rejected", "rejected an invitation to this room."),
+    ("membership.invitation_revoked", "revoked {user}'s invitation to this room."),
+    ("membership.knocked", "requested to join this room."),
+    ("membership.knock_accepted", "accepted {user}'s request to join this room."),
+    ("membership.knock_retracted", "retracted their request to join this room."),
+    ("membership.knock_denied", "denied {user}'s request to join this room."),
+    ("membership.reason", "Reason: {reason}"),
+];
+
+
+/// The Simplified Chinese string table.
+const ZH_CN: &[(&str, &str)] = &[
+    // Settings screen
+    ("settings.title", "设置"),
+    ("settings.language", "语言"),
+    ("settings.language.description", "Robrix 界面所使用的语言。"),
+    ("settings.display", "显示密度"),
+    ("settings.display.description", "紧凑密度使用更小的头像和更少的间距，以便同时显示更多消息和房间。"),
+    ("settings.display.comfortable", "舒适"),
+    ("settings.display.compact", "紧凑"),
+    ("settings.app_badge", "应用图标角标"),
+    ("settings.app_badge.description", "在应用图标上显示哪种未读计数。目前仅支持 macOS。"),
+    ("settings.app_badge.none", "不显示徽章"),
+    ("settings.app_badge.mentions", "未读提及"),
+    ("settings.app_badge.all", "所有未读消息"),
+    ("settings.proxy", "网络代理"),
+    ("settings.proxy.description", "通过 HTTP 或 SOCKS5 代理连接到你的主服务器，例如在防火墙之后或使用 Tor 时。"),
+    ("settings.proxy.enabled", "使用代理"),
+    ("settings.proxy.save", "保存代理设置"),
+    ("settings.proxy.saved", "代理设置已保存，将在 Robrix 下次启动时生效。"),
+    ("settings.proxy.invalid", "请输入有效的代理主机和端口。"),
+    ("settings.proxy.invalid_port", "代理端口必须是 1 到 65535 之间的数字。"),
+    ("settings.proxy.host_placeholder", "主机，例如 127.0.0.1"),
+    ("settings.proxy.port_placeholder", "端口"),
+    ("settings.proxy.username_placeholder", "用户名（可选）"),
+    ("settings.proxy.password_placeholder", "密码（可选）"),
+    ("settings.timeline", "时间线"),
+    ("settings.timeline.description", "可以使用消息输入框旁边的按钮在单个房间中显示或隐藏这些事件。"),
+    ("settings.timeline.hide_state_events", "隐藏成员变动和个人资料变更"),
+    ("settings.timeline.colorize_usernames", "以不同颜色显示每位发送者的名称"),
+    ("settings.timeline.relative_timestamps", "显示相对时间 (如 \"5 分钟前\")"),
+    ("settings.timeline.relative_timestamps.never", "从不"),
+    ("settings.timeline.relative_timestamps.hour", "1 小时以内"),
+    ("settings.timeline.relative_timestamps.day", "1 天以内"),
+    ("settings.timeline.relative_timestamps.week", "1 周以内"),
+    ("settings.timeline.read_marker_clearing", "移动 \"新消息\" 标记"),
+    ("settings.timeline.read_marker_clearing.immediately", "立即"),
+    ("settings.timeline.read_marker_clearing.after_viewing", "查看一段时间后"),
+    ("settings.timeline.read_marker_clearing.when_marked_read", "仅在标记为已读时"),
+    ("settings.timeline.read_marker_delay", "查看多少秒后移动标记"),
+    ("settings.timeline.read_marker_delay_saved", "已保存已读标记延迟。"),
+    ("settings.timeline.invalid_read_marker_delay", "已读标记延迟必须是正整数。"),
+    ("composer.mention_room", "通知此房间中的所有人"),
+    ("room.call.in_progress", "群组通话正在进行中。"),
+    ("room.call.join", "加入通话"),
+    ("room.call.open_failed", "无法打开 Element Call。"),
+    ("room.room_mention.confirm", "此消息提及了 @room，将通知此房间中的 {count} 人。仍要发送吗？"),
+    ("room.room_mention.cancel", "取消"),
+    ("room.room_mention.send", "通知所有人"),
+    ("media.tap_to_download_image", "点按以下载此图片。"),
+    ("media.loading_image", "正在加载图片..."),
+    ("rooms_list.typing_one", "{user} 正在输入"),
+    ("rooms_list.typing_two", "{user1} 和 {user2} 正在输入"),
+    ("rooms_list.typing_many", "多人正在输入"),
+    ("room_hover_preview.loading", "正在加载..."),
+    ("room_hover_preview.members", "{count} 位成员"),
+    ("room_hover_preview.no_topic", "没有主题"),
+    ("room_hover_preview.no_messages", "没有最近的消息"),
+    ("event_source.title", "事件源"),
+    ("event_source.ids", "事件 ID: {event_id}\n房间 ID: {room_id}"),
+    ("event_source.decryption", "解密"),
+    ("event_source.decrypted", "此事件已成功解密。"),
+    ("event_source.not_decrypted", "此事件无法解密。"),
+    ("event_source.session_id", "会话 ID: {value}"),
+    ("event_source.sender_key", "发送者密钥: {value}"),
+    ("event_source.sender_device", "由 {user} 从设备 {device} 发送"),
+    ("event_source.verification", "验证: {value}"),
+    ("event_source.utd_cause", "原因: {value}"),
+    ("event_source.unknown", "未知"),
+    ("event_source.verification.verified", "发送设备已由发送者的身份签名，且你已验证该身份。"),
+    ("event_source.verification.unverified_identity", "发送设备已由发送者的身份签名，但你尚未验证该身份。"),
+    ("event_source.verification.violation", "发送者的身份曾经过验证，但之后已更改。"),
+    ("event_source.verification.unsigned_device", "发送设备未由发送者的身份签名。"),
+    ("event_source.verification.missing_device", "发送设备未知或已被删除。"),
+    ("event_source.verification.insecure_source", "此事件的密钥来自不安全的来源，例如密钥备份或转发的密钥。"),
+    ("event_source.no_json", "此事件的源不可用，例如因为它尚未发送。"),
+    ("event_source.not_found", "在时间线中找不到此消息。"),
+    ("event_source.copy", "复制"),
+    ("event_source.close", "关闭"),
+    ("snippets.add", "添加片段"),
+    ("snippets.remove", "删除"),
+    ("snippets.saved", "已保存片段 {trigger}。"),
+    ("snippets.text_empty", "请输入该片段要插入的文本。"),
+    ("snippets.name_empty", "请输入片段名称，例如 /sig。"),
+    ("snippets.name_too_long", "片段名称最多为 32 个字符。"),
+    ("snippets.name_invalid_characters", "片段名称只能包含字母、数字、'-' 和 '_'。"),
+    ("snippets.name_reserved", "该名称已被消息命令使用。"),
+    ("spell_check.misspelled", "拼写："),
+    ("spell_check.suggestions_for", "将 \"{word}\" 替换为："),
+    ("spell_check.no_suggestions", "没有 \"{word}\" 的拼写建议。"),
+    ("spell_check.load_failed", "加载 {language} 拼写检查词典失败: {error}"),
+    ("scheduled.prompt", "稍后发送此消息："),
+    ("scheduled.schedule", "定时发送"),
+    ("scheduled.cancel", "取消"),
+    ("scheduled.empty", "请先输入要定时发送的消息。"),
+    ("scheduled.invalid_time", "请输入将来的时间，格式为 HH:MM 或 YYYY-MM-DD HH:MM。"),
+    ("scheduled.scheduled", "已定时发送该消息。Robrix 运行时将会发送。"),
+    ("scheduled.schedule_failed", "定时发送消息失败: {error}"),
+    ("scheduled.cancelled", "已取消定时消息。"),
+    ("scheduled.cancel_failed", "取消定时消息失败: {error}"),
+    ("scheduled.row", "{time}: {text}"),
+    ("scheduled.room_not_found", "该房间已不可用"),
+    ("scheduled.send_failed", "发送定时消息 \"{text}\" 失败: {error}"),
+    ("scheduled.row_failed", "发送失败（{error}）: {text}"),
+    ("scheduled.retry", "重试"),
+    ("room.state_events.hidden", "此房间中的成员变动和个人资料变更现已隐藏。"),
+    ("room.state_events.shown", "此房间中的成员变动和个人资料变更现已显示。"),
+    ("room.state_event_run.joined", "{names} 加入了"),
+    ("room.state_event_run.left", "{names} 离开了"),
+    ("room.state_event_run.changed_profiles", "{names} 更改了个人资料"),
+    ("room.state_event_run.mixed", "{names} 的 {count} 项成员和个人资料变更"),
+    ("room.state_event_run.show", "全部显示"),
+    ("room.state_event_run.hide", "隐藏"),
+    ("timestamp.server_time", "服务器时间: {time} ({millis})"),
+    ("timestamp.sending", "发送中..."),
+    ("timestamp.send_failed", "发送失败"),
+    ("timestamp.sent", "已发送"),
+    ("timestamp.received", "已接收"),
+    ("timestamp.edited", "已编辑"),
+    ("message.retry_send", "重试"),
+    ("message.delete_unsent", "删除"),
+    ("message.quote_attribution", "{user} 写道："),
+    ("message.quote_not_found", "找不到要引用的消息。"),
+    ("message.retry_send_not_found", "找不到要重试的未发送消息。"),
+    ("message.read_marker_only_forwards", "已读标记只能移动到其当前位置之后的消息。"),
+    ("message.read_marker_move_failed", "移动已读标记失败: {error}"),
+    ("message.retry_send_failed", "重试发送消息失败: {error}"),
+    ("room_menu.mark_read", "标记为已读"),
+    ("room_menu.toggle_mute", "静音 / 取消静音"),
+    ("room_menu.hide_read_receipts", "在此隐藏已读回执"),
+    ("room_menu.share_read_receipts", "在此分享已读回执"),
+    ("room_menu.read_receipts_hidden", "此房间的其他成员将不再看到你已读的消息。"),
+    ("room_menu.read_receipts_shared", "此房间的其他成员现在可以看到你已读的消息。"),
+    ("room_menu.favorite", "添加到收藏"),
+    ("room_menu.unfavorite", "从收藏中移除"),
+    ("room_menu.copy_link", "复制房间链接"),
+    ("room_menu.share", "分享房间..."),
+    ("room_menu.wallpaper", "设置壁纸..."),
+    ("room_menu.leave", "离开房间"),
+    ("room_menu.link_copied", "已复制房间链接。"),
+    ("room_menu.mark_read_failed", "标记房间为已读失败: {error}"),
+    ("room_menu.muted", "已将房间通知静音。"),
+    ("room_menu.unmuted", "已取消房间通知静音。"),
+    ("room_menu.mute_failed", "更改房间通知失败: {error}"),
+    ("room_menu.favorite_failed", "更新收藏失败: {error}"),
+    ("room_menu.left", "已离开 {room}。"),
+    ("room_menu.leave_failed", "离开 {room} 失败: {error}"),
+    ("inbox.title", "收件箱"),
+    ("inbox.description", "所有房间中提及你或回复你消息的消息。"),
+    ("inbox.empty", "暂无内容。提及和回复会在到达时显示在这里。"),
+    ("inbox.close", "关闭"),
+    ("inbox.jump", "跳转"),
+    ("inbox.mentioned_in", "{user} 在 {room} 中提及了你"),
+    ("inbox.replied_in", "{user} 在 {room} 中回复了你"),
+    ("contacts.title", "联系人"),
+    ("contacts.description", "与你有私聊房间的人。输入用户 ID 以开始新的对话, 或输入邮箱地址或手机号通过身份服务器查找联系人。"),
+    ("contacts.loading", "正在加载联系人..."),
+    ("contacts.empty", "暂无联系人。私聊房间会显示在这里。"),
+    ("contacts.close", "关闭"),
+    ("contacts.message", "发消息"),
+    ("contacts.invalid_user_id", "\"{user}\" 不是有效的用户 ID, 例如 @alice:matrix.org"),
+    ("contacts.cannot_add_self", "你不能给自己发送私信。"),
+    ("contacts.opening_dm", "正在打开与 {user} 的私聊..."),
+    ("contacts.open_dm_failed", "无法打开与 {user} 的私聊: {error}"),
+    ("contacts.identity_server_consent", "要通过邮箱地址或手机号查找联系人, Robrix 需要将你输入的地址的哈希值发送到身份服务器 {server}。地址绝不会以明文发送。你是否同意使用该身份服务器及其以下条款?"),
+    ("contacts.consent_accept", "同意"),
+    ("contacts.consent_cancel", "取消"),
+    ("contacts.looking_up", "正在查找 {address}..."),
+    ("contacts.lookup_found", "{address} 属于 {user}。点击发消息以开始对话。"),
+    ("contacts.lookup_not_found", "未找到与 {address} 对应的 Matrix 用户。"),
+    ("contacts.lookup_failed", "查找 {address} 失败: {error}"),
+    ("share_room.title", "分享房间"),
+    ("share_room.prompt", "任何拥有此链接的人都可以找到并加入 {room}。"),
+    ("share_room.close", "关闭"),
+    ("share_room.copy_link", "复制链接"),
+    ("share_room.show_qr_code", "显示二维码"),
+    ("share_room.hide_qr_code", "隐藏二维码"),
+    ("share_room.qr_code_failed", "无法为此房间生成二维码。"),
+    ("leave_room.title", "离开房间"),
+    ("leave_room.prompt", "确定要离开 {room} 吗？"),
+    ("leave_room.forget", "同时忘记此房间，将其从过往房间列表中移除"),
+    ("leave_room.last_admin_warning", "你是此房间的最后一位管理员。你离开后，将没有人能够更改房间设置。如需先将其他成员提升为管理员，请输入其用户 ID："),
+    ("leave_room.cancel", "取消"),
+    ("leave_room.leave", "离开"),
+    ("leave_room.invalid_user_id", "“{user_id}”不是有效的用户 ID。"),
+    ("leave_room.transfer_failed", "未能将 {user_id} 提升为管理员，因此没有离开房间：{error}"),
+    ("leave_room.forget_failed", "无法忘记 {room}：{error}"),
+    ("room_filter.all", "全部"),
+    ("room_filter.unread_only", "仅显示有未读消息的房间"),
+    ("room_filter.dms_only", "仅显示私聊"),
+    ("room_filter.hide_muted", "隐藏已静音的房间"),
+    ("room_filter.delete", "删除"),
+    ("room_filter.cancel", "取消"),
+    ("room_filter.save", "保存"),
+    ("room_filter.empty_name", "请输入此筛选器的名称。"),
+    ("room_wallpaper.title", "房间壁纸"),
+    ("room_wallpaper.prompt", "为 {room} 选择背景。它仅对您可见，并会同步到您的所有设备。"),
+    ("room_wallpaper.color", "背景颜色（例如 #336699）"),
+    ("room_wallpaper.image_url", "背景图片（mxc:// 链接）"),
+    ("room_wallpaper.dim", "暗化"),
+    ("room_wallpaper.cancel", "取消"),
+    ("room_wallpaper.remove", "移除"),
+    ("room_wallpaper.save", "保存"),
+    ("room_wallpaper.invalid_color", "“{color}” 不是有效的十六进制颜色。"),
+    ("room_wallpaper.invalid_image_url", "“{url}” 不是有效的 mxc:// 链接。"),
+    ("room_wallpaper.empty", "请输入背景颜色或图片，或改为移除壁纸。"),
+    ("room_wallpaper.save_failed", "保存房间壁纸失败: {error}"),
+    ("settings.profile", "个人资料"),
+    ("settings.profile.description", "你已加入的房间中的所有人都能看到你的显示名称。"),
+    ("profile.save_display_name", "保存"),
+    ("profile.not_loaded", "你的个人资料尚未加载，请稍后重试。"),
+    ("profile.display_name_saved", "显示名称已更新。"),
+    ("profile.display_name_failed", "更新显示名称失败: {error}"),
+    ("presence.online", "在线"),
+    ("presence.unavailable", "离开"),
+    ("presence.offline", "离线"),
+    ("presence.set_status", "设置状态"),
+    ("presence.status_placeholder", "状态消息"),
+    ("presence.status_saved", "你的状态已更新。"),
+    ("presence.status_failed", "更新状态失败: {error}"),
+    ("profile.shared_rooms", "共同房间: {count}"),
+    ("profile.bridged_from", "桥接自 {platform}"),
+    ("profile.remove_messages", "删除最近消息"),
+    ("profile.remove_messages_progress", "已删除目前找到的 {found} 条消息中的 {redacted} 条..."),
+    ("profile.remove_messages_reason", "原因（可选）"),
+    ("profile.remove_messages_scan_limit", "仅检查了此房间中最新的 {count} 个事件。"),
+    ("profile.remove_messages_finished", "已删除 {redacted} 条消息（{failed} 条失败）。"),
+    ("profile.remove_messages_failed", "删除最近消息失败: {error}"),
+    ("profile.devices", "设备"),
+    ("profile.device.verified", "{name} ({device_id})\n已验证"),
+    ("profile.device.unverified", "{name} ({device_id})\n未验证"),
+    ("profile.device.current_verified", "{name} ({device_id}) — 此设备\n已验证"),
+    ("profile.device.current_unverified", "{name} ({device_id}) — 此设备\n未验证"),
+    ("profile.device.unknown_name", "未知设备"),
+    ("profile.device.verify", "验证"),
+    ("verification.unknown_device", "无法验证用户 {user} 的未知设备 {device}。"),
+    ("verification.get_device_failed", "获取用户 {user} 的设备 {device} 失败: {error}"),
+    ("verification.request_failed", "请求验证设备 {device} 失败: {error}"),
+    ("deep_link.alias_not_found", "找不到别名为 {alias} 的房间。"),
+    ("deep_link.no_dm_room", "你与 {user_id} 没有私聊房间。"),
+    ("deep_link.unsupported", "暂不支持此类 Matrix 链接。"),
+    ("deep_link.not_joined", "你尚未加入此房间，已在网页浏览器中打开。"),
+    ("deep_link.open_failed", "无法打开链接 {url}。"),
+    ("export.title", "导出聊天记录"),
+    ("export.prompt", "将 {room} 的完整历史记录导出到下载文件夹中的文件。对于大型房间，这可能需要一些时间。\n\n请选择格式: HTML 便于阅读，JSON 包含所有事件。也可以将房间成员列表导出为 CSV 表格。"),
+    ("export.members_csv", "成员 (CSV)"),
+    ("export.members_progress", "正在导出成员列表..."),
+    ("export.cancel", "取消"),
+    ("export.ok", "确定"),
+    ("export.progress", "正在导出... 已获取 {count} 个事件。"),
+    ("export.finished", "导出完成。已保存到:\n{path}"),
+    ("export.failed", "导出失败: {error}"),
+    ("moderation.title", "管理: {room}"),
+    ("moderation.description", "此房间已加载消息中的近期活动。操作会立即生效。"),
+    ("moderation.close", "关闭"),
+    ("moderation.recent_joins", "最近加入 ({count})"),
+    ("moderation.recent_messages", "最近消息 ({count})"),
+    ("moderation.recent_redactions", "最近删除的消息 ({count})"),
+    ("moderation.kick", "踢出"),
+    ("moderation.ban", "封禁"),
+    ("moderation.remove", "删除"),
+    ("moderation.kicked", "已踢出 {user_id}。"),
+    ("moderation.kick_failed", "踢出 {user_id} 失败: {error}"),
+    ("moderation.banned", "已封禁 {user_id}。"),
+    ("moderation.ban_failed", "封禁 {user_id} 失败: {error}"),
+    ("room_access.title", "房间访问"),
+    ("room_access.history", "谁可以阅读历史记录？"),
+    ("room_access.history.world_readable", "任何人（包括未加入的人）都可以阅读此房间的全部历史记录。"),
+    ("room_access.history.shared", "成员可以阅读全部历史记录，包括加入之前发送的消息。"),
+    ("room_access.history.invited", "成员只能阅读被邀请之后发送的消息。"),
+    ("room_access.history.joined", "成员只能阅读加入之后发送的消息。"),
+    ("room_access.history.world_readable.label", "任何人"),
+    ("room_access.history.shared.label", "成员 (全部历史)"),
+    ("room_access.history.invited.label", "成员 (自被邀请起)"),
+    ("room_access.history.joined.label", "成员 (自加入起)"),
+    ("room_access.guests", "访客访问"),
+    ("room_access.guests.can_join", "无需注册的访客账号可以加入此房间。"),
+    ("room_access.guests.forbidden", "只有已注册的用户可以加入此房间。"),
+    ("room_access.guests.can_join.label", "允许访客加入"),
+    ("room_access.guests.forbidden.label", "禁止访客加入"),
+    ("room_access.history_updated", "已更新谁可以阅读此房间的历史记录。"),
+    ("room_access.guests_updated", "已更新此房间的访客访问。"),
+    ("room_access.update_failed", "更新房间访问失败: {error}"),
+    ("room_upgrade.title", "房间版本"),
+    ("room_upgrade.description", "升级会用一个使用指定房间版本（留空则使用服务器默认版本）的新房间替换此房间。成员会被引导至新房间，此房间将变为只读。"),
+    ("room_upgrade.upgrade", "升级"),
+    ("room_upgrade.invalid_version", "\"{version}\" 不是有效的房间版本。"),
+    ("room_upgrade.starting", "正在开始升级..."),
+    ("room_upgrade.upgrading", "正在创建新房间..."),
+    ("room_upgrade.announcing", "正在此房间中公告升级..."),
+    ("room_upgrade.finished", "此房间已升级并被 {room_id} 替换。"),
+    ("room_upgrade.failed", "升级此房间失败: {error}"),
+    ("policy.title", "策略列表"),
+    ("policy.none", "此房间未订阅任何策略列表。匹配已订阅列表封禁规则的成员将被自动封禁。"),
+    ("policy.subscribe", "订阅"),
+    ("policy.unsubscribe", "取消订阅"),
+    ("policy.invalid_list", "\"{list}\" 不是有效的房间 ID 或别名。"),
+    ("policy.subscribed", "已订阅策略列表 {list}。"),
+    ("policy.subscribe_failed", "订阅策略列表 {list} 失败: {error}"),
+    ("policy.unsubscribe_failed", "取消订阅策略列表 {list} 失败: {error}"),
+    ("policy.auto_banned", "根据策略列表 {list} 封禁了 {user_id}: {reason}"),
+    ("settings.privacy", "隐私"),
+    ("settings.privacy.description", "选择其他房间成员可以看到的你的活动信息。"),
+    ("settings.privacy.send_read_receipts", "发送已读回执"),
+    ("settings.privacy.send_typing_notices", "发送正在输入通知"),
+    ("settings.privacy.hide_content_when_unfocused", "当 Robrix 不是焦点窗口时隐藏所有内容（例如共享屏幕时）"),
+    ("settings.composer", "消息编辑"),
+    ("settings.composer.description", "编写消息的选项。"),
+    ("settings.composer.spell_check", "输入时检查拼写"),
+    ("settings.composer.enter_key_behavior", "按下回车键时"),
+    ("settings.composer.enter_key_behavior.newline", "插入换行 (Ctrl/Cmd+Enter 发送)"),
+    ("settings.composer.enter_key_behavior.send", "发送消息 (Ctrl/Cmd+Enter 插入换行)"),
+    ("settings.composer.formatting_toolbar", "在消息输入框上方显示格式按钮"),
+    ("settings.composer.spell_check_language", "拼写检查语言"),
+    ("settings.composer.spell_check_language_saved", "已保存拼写检查语言。"),
+    ("settings.composer.unknown_spell_check_language", "未安装 \"{language}\" 的词典。"),
+    ("settings.composer.dictionaries", "已安装的词典: {languages}"),
+    ("settings.composer.no_dictionaries", "未找到 Hunspell 词典。请将 .aff 和 .dic 文件添加到 Robrix 数据目录中的 \"dictionaries\" 文件夹。"),
+    ("settings.snippets", "文本片段"),
+    ("settings.snippets.description", "保存的文本块，在消息中输入 / 加片段名称（例如 /sig）即可插入。输入完整名称后加空格，或从建议中选择片段即可插入。"),
+    ("app.content_hidden", "Robrix 未获得焦点时，内容已隐藏。"),
+    ("settings.app_lock", "应用锁"),
+    ("settings.app_lock.description", "启动时以及一段时间无操作后，需要输入 PIN 码或进行生物识别才能查看 Robrix。这不会加密此设备上的任何数据。"),
+    ("settings.app_lock.enabled", "应用锁已启用。"),
+    ("settings.app_lock.disabled", "应用锁未启用。设置 PIN 码即可启用。"),
+    ("settings.app_lock.set_pin", "设置 PIN 码"),
+    ("settings.app_lock.remove", "停用应用锁"),
+    ("settings.app_lock.timeout", "锁定应用"),
+    ("settings.app_lock.timeout.on_launch", "仅在启动时"),
+    ("settings.app_lock.timeout.one_minute", "1 分钟后"),
+    ("settings.app_lock.timeout.five_minutes", "5 分钟后"),
+    ("settings.app_lock.timeout.fifteen_minutes", "15 分钟后"),
+    ("settings.app_lock.timeout.one_hour", "1 小时后"),
+    ("settings.app_lock.biometrics", "允许使用生物识别解锁（例如 Touch ID 或 Windows Hello）"),
+    ("settings.app_lock.pin_saved", "应用锁 PIN 码已保存。"),
+    ("settings.app_lock.invalid_pin", "PIN 码必须由 {min} 到 {max} 位数字组成。"),
+    ("settings.app_lock.removed", "应用锁已停用。"),
+    ("app_lock.title", "Robrix 已锁定"),
+    ("app_lock.prompt", "输入 PIN 码以解锁 Robrix。"),
+    ("app_lock.unlock", "解锁"),
+    ("app_lock.use_biometrics", "使用生物识别"),
+    ("app_lock.wrong_pin", "PIN 码错误，请重试。"),
+    ("app_lock.locked_out", "错误次数过多，请在 {seconds} 秒后重试。"),
+    ("app_lock.biometrics_failed", "生物识别失败，请改为输入 PIN 码。"),
+    ("app_lock.biometrics_reason", "解锁 Robrix"),
+    ("settings.storage", "存储"),
+    ("settings.storage.description", "仍被已登录账户使用的数据库永远不会被删除。"),
+    ("settings.storage.sdk_store", "数据库"),
+    ("settings.storage.media_cache", "媒体缓存（内存）"),
+    ("settings.storage.temp_storage", "临时文件"),
+    ("settings.storage.remove_unused", "删除未使用的"),
+    ("settings.storage.clear", "清除"),
+    ("settings.storage.media_auto_download", "自动下载媒体"),
+    ("settings.storage.media_auto_download.always", "总是"),
+    ("settings.storage.media_auto_download.unmetered", "仅在非计费网络时"),
+    ("settings.storage.media_auto_download.never", "从不"),
+    ("settings.storage.media_cache_max", "媒体缓存上限（MB）"),
+    ("settings.storage.save", "保存"),
+    ("settings.storage.media_cache_max_saved", "媒体缓存上限已保存。"),
+    ("settings.storage.invalid_media_cache_max", "媒体缓存上限必须是正整数。"),
+    ("settings.storage.temp_storage_max", "临时文件上限（MB）"),
+    ("settings.storage.temp_storage_max_age", "临时文件保留天数"),
+    ("settings.storage.temp_storage_limits_saved", "临时文件限制已保存, 将在下次启动时生效。"),
+    ("settings.storage.invalid_temp_storage_limits", "临时文件限制必须是正整数。"),
+    ("settings.storage.usage_of_limit", "{size} / {limit}"),
+    ("settings.storage.cleared", "已释放 {size}。"),
+    ("settings.storage.clear_failed", "清除存储失败: {error}"),
+    ("settings.diagnostics", "关于与诊断"),
+    ("settings.diagnostics.description", "报告问题时请附上这些信息。导出的诊断信息还包含应用日志，其中的密码和访问令牌已被移除。"),
+    ("settings.diagnostics.export", "导出诊断信息"),
+    ("settings.diagnostics.copy", "复制到剪贴板"),
+    ("settings.diagnostics.copied", "诊断信息已复制到剪贴板。"),
+    ("settings.diagnostics.exported", "诊断信息已保存到 {path}"),
+    ("settings.diagnostics.export_failed", "导出诊断信息失败: {error}"),
+    ("settings.logs", "开发者: 日志"),
+    ("settings.logs.description", "选择 Robrix 各部分记录日志的详细程度, 并查看当前日志文件的最新内容。RUST_LOG 环境变量会覆盖这些日志级别。"),
+    ("settings.logs.ui_level", "用户界面日志级别"),
+    ("settings.logs.sliding_sync_level", "Matrix 同步日志级别"),
+    ("settings.logs.dependencies_level", "依赖库日志级别"),
+    ("settings.logs.level.off", "关闭"),
+    ("settings.logs.level.error", "错误"),
+    ("settings.logs.level.warn", "警告"),
+    ("settings.logs.level.info", "信息"),
+    ("settings.logs.level.debug", "调试"),
+    ("settings.logs.level.trace", "跟踪"),
+    ("settings.logs.follow", "跟随"),
+    ("settings.logs.refresh", "刷新"),
+    ("settings.logs.no_file", "尚未写入任何日志文件。"),
+    ("settings.logs.read_failed", "读取日志文件 {path} 失败: {error}"),
+    ("settings.logs.showing", "显示 {path} 中最后 {count} 行匹配的日志"),
+    ("settings.shortcuts", "键盘快捷键"),
+    ("settings.shortcuts.description", "组合键的写法如 \"Primary+Shift+K\"，其中 Primary 在 macOS 上为 Command 键，在其他平台上为 Ctrl 键。"),
+    ("settings.shortcuts.save", "保存快捷键"),
+    ("settings.shortcuts.reset", "恢复默认"),
+    ("settings.shortcuts.saved", "键盘快捷键已保存。"),
+    ("settings.shortcuts.invalid", "\"{action}\" 的快捷键无效: {error}"),
+    ("settings.shortcuts.duplicate", "\"{combo}\" 被用于多个快捷键。"),
+    ("shortcut.next_unread_room", "前往下一个未读房间"),
+    ("shortcut.previous_unread_room", "前往上一个未读房间"),
+    ("shortcut.focus_search", "搜索"),
+    ("shortcut.reply_to_last_message", "回复最新消息"),
+    ("shortcut.toggle_sidebar", "显示/隐藏侧边栏"),
+    ("shortcut.open_settings", "打开设置"),
+    ("shortcut.toggle_performance_overlay", "显示/隐藏性能指标"),
+
+    // Text previews of timeline items
+    ("preview.message_deleted", "[消息已被删除]"),
+    ("preview.sticker", "贴纸: <i>{body}</i>"),
+    ("preview.sticker_no_body", "贴纸"),
+    ("preview.unable_to_decrypt", "[无法解密消息]"),
+    ("preview.membership_change", "<i>的成员身份发生了变化</i>"),
+    ("preview.other_state", "<i>发起了其他状态变更</i>"),
+    ("preview.failed_to_parse_message", "[无法解析 <i>{event_type}</i> 消息]"),
+    ("preview.failed_to_parse_state", "[无法解析 <i>{event_type}</i> 状态]"),
+    ("preview.poll", "投票: {question}"),
+    ("preview.call_invite", "来电"),
+    ("preview.call_notify", "通话已开始"),
+    ("preview.audio", "[音频]: <i>{body}</i>"),
+    ("preview.file", "[文件]: <i>{body}</i>"),
+    ("preview.image", "[图片]: <i>{body}</i>"),
+    ("preview.location", "[位置]: <i>{body}</i>"),
+    ("preview.server_notice", "[服务器通知]: <i>{kind} -- {body}</i>"),
+    ("preview.verification_request", "[验证请求] <i>发给用户 {user}</i>"),
+    ("preview.video", "[视频]: <i>{body}</i>"),
+    ("preview.custom_message", "[自定义消息]: {body}"),
+    ("preview.unknown_message", "[未知消息类型]: {body}"),
+
+    // Plaintext bodies of timeline items
+    ("body.unable_to_decrypt", "[无法解密]"),
+    ("body.membership_change", "的成员身份发生了变化。"),
+    ("body.other_state", "发起了其他状态变更。"),
+    ("body.failed_to_parse_message", "无法解析 {event_type} 消息。错误: {error}"),
+    ("body.failed_to_parse_state", "无法解析 {event_type} 状态; 键: {state_key}。错误: {error}"),
+
+    // Redacted messages
+    ("redacted.with_reason", "{redactor} 删除了 {sender} 的消息: {reason}。"),
+    ("redacted.own", "{sender} 删除了自己的消息。"),
+    ("redacted.by_other", "{redactor} 删除了 {sender} 的消息。"),
+    ("redacted.unknown", "{sender} 的消息已被删除。"),
+
+    // Other state events
+    ("state.aliases", "将此房间的别名设置为 {aliases}。"),
+    ("state.avatar", "设置了此房间的头像。"),
+    ("state.canonical_alias", "将此房间的主地址设置为 {alias}。"),
+    ("state.canonical_alias.none", "无"),
+    ("state.create", "创建了此房间 (v{version})。"),
+    ("state.encryption", "在此房间中启用了加密。"),
+    ("state.guest_access.can_join", "允许访客加入此房间。"),
+    ("state.guest_access.forbidden", "禁止访客加入此房间。"),
+    ("state.guest_access.custom", "为此房间设置了自定义访客规则: {rules}"),
+    ("state.history_visibility.invited", "将此房间的历史记录设置为受邀用户自受邀起可见。"),
+    ("state.history_visibility.joined", "将此房间的历史记录设置为已加入用户自加入起可见。"),
+    ("state.history_visibility.shared", "将此房间的全部历史记录设置为已加入用户可见。"),
+    ("state.history_visibility.world_readable", "将此房间的全部历史记录设置为任何人可见。"),
+    ("state.history_visibility.custom", "将此房间的历史记录可见性设置为 {visibility}"),
+    ("state.join_rules.public", "将此房间设置为任何人都可加入。"),
+    ("state.join_rules.knock", "将此房间设置为仅限邀请或申请加入。"),
+    ("state.join_rules.private", "将此房间设置为私密。"),
+    ("state.join_rules.restricted", "将此房间设置为仅限邀请或满足限制条件后加入。"),
+    ("state.join_rules.knock_restricted", "将此房间设置为仅限邀请或满足限制条件后申请加入。"),
+    ("state.join_rules.invite", "将此房间设置为仅限邀请加入。"),
+    ("state.join_rules.custom", "为此房间设置了自定义加入规则: {rules}"),
+    ("state.pinned_events", "在此房间中置顶了 {count} 条事件。"),
+    ("state.name", "将此房间的名称更改为 {name}。"),
+    ("state.power_levels", "设置了此房间的权限等级。"),
+    ("state.server_acl", "设置了此房间的服务器访问控制列表。"),
+    ("state.tombstone", "关闭了此房间并将其升级为 {room}"),
+    ("state.topic", "将此房间的主题更改为 {topic}。"),
+    ("state.space_parent", "将此房间的上级空间设置为 {room}。"),
+    ("state.space_child", "向此空间添加了新的子房间: {room}。"),
+    ("state.beacon_info.live", "开始共享实时位置。"),
+    ("state.beacon_info.stopped", "停止共享实时位置。"),
+
+    // Member profile changes
+    ("profile.display_name_changed", "{old} 将显示名称更改为 {new}"),
+    ("profile.display_name_removed", "{old} 移除了显示名称"),
+    ("profile.avatar_changed", "{user} 更换了头像"),
+    ("profile.avatar_changed_also", "并更换了头像"),
+
+    // Room membership changes
+    ("membership.joined", "加入了此房间。"),
+    ("membership.left", "离开了此房间。"),
+    ("membership.banned", "将 {user} 封禁出此房间。"),
+    ("membership.unbanned", "解除了对 {user} 的封禁。"),
+    ("membership.kicked", "将 {user} 移出了此房间。"),
+    ("membership.invited", "邀请 {user} 加入此房间。"),
+    ("membership.kicked_and_banned", "将 {user} 移出并封禁。"),
+    ("membership.invitation_accepted", "接受了加入此房间的邀请。"),
+    ("membership.invitation_rejected", "拒绝了加入此房间的邀请。"),
+    ("membership.invitation_revoked", "撤销了对 {user} 的邀请。"),
+    ("membership.knocked", "申请加入此房间。"),
+    ("membership.knock_accepted", "接受了 {user} 的加入申请。"),
+    ("membership.knock_retracted", "撤回了加入此房间的申请。"),
+    ("membership.knock_denied", "拒绝了 {user} 的加入申请。"),
+    ("membership.reason", "原因: {reason}"),
+];
+
+#[cfg(test)]
+mod tests_i18n {
+    use super::*;
+
+    #[test]
+    fn test_fill_placeholders() {
+        assert_eq!(fill_placeholders("{user} joined {room}", &[("user", "Alice"), ("room", "Lobby")]), "Alice joined Lobby");
+        assert_eq!(fill_placeholders("{missing} and {", &[("user", "Alice")]), "{missing} and {");
+    }
+
+    #[test]
+    fn test_values_are_not_substituted_again() {
+        let text = fill_placeholders("{user} joined {room}", &[("user", "{room}"), ("room", "Lobby")]);
+        assert_eq!(text, "{room} joined Lobby");
+    }
+}
@@ -0,0 +1,292 @@
+//! Lookup of Matrix users by their email address or phone number (their third-party IDs, or 3PIDs)
+//! via an identity server, using the v2 Identity Service API.
+//!
+//! An identity server is only ever contacted after the user has explicitly consented to it
+//! and accepted its terms of service. Addresses are never sent in plaintext:
+//! only their peppered SHA-256 hashes are sent, as required by the v2 lookup API.
+
+use std::{collections::BTreeSet, fmt};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use matrix_sdk::{
+    ruma::{api::client::account::request_openid_token, OwnedUserId, UserId},
+    Client,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::settings::app_settings::app_settings;
+
+/// The identity server used if the user hasn't chosen a different one.
+pub const DEFAULT_IDENTITY_SERVER: &str = "https://vector.im";
+
+/// The user's choice of identity server and their consent to using it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdentityServerSettings {
+    /// Whether the user has consented to sending hashed email addresses and phone numbers
+    /// to the identity server in order to find other users.
+    pub consent_given: bool,
+    /// The base URL of the identity server.
+    pub url: String,
+    /// The URLs of the identity server's terms of service policies that the user has accepted.
+    pub accepted_terms: BTreeSet<String>,
+}
+
+impl Default for IdentityServerSettings {
+    fn default() -> Self {
+        Self {
+            consent_given: false,
+            url: DEFAULT_IDENTITY_SERVER.to_owned(),
+            accepted_terms: BTreeSet::new(),
+        }
+    }
+}
+
+/// The kind of a third-party ID.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ThreePidMedium {
+    Email,
+    /// A phone number, in international format without the leading `+`.
+    Msisdn,
+}
+
+impl ThreePidMedium {
+    fn as_str(self) -> &'static str {
+        match self {
+            ThreePidMedium::Email => "email",
+            ThreePidMedium::Msisdn => "msisdn",
+        }
+    }
+
+    /// Determines the medium of the given email address or international phone number,
+    /// and returns it along with the address normalized as required for lookups.
+    ///
+    /// Returns `None` if the given text is neither, e.g., if it's a Matrix user ID.
+    pub fn parse_address(address: &str) -> Option<(Self, String)> {
+        let address = address.trim();
+        if let Some((local, domain)) = address.split_once('@') {
+            return (!local.is_empty() && domain.contains('.') && !domain.contains(':'))
+                .then(|| (ThreePidMedium::Email, address.to_lowercase()));
+        }
+        let digits: String = address.strip_prefix('+')?
+            .chars()
+            .filter(|c| !matches!(c, ' ' | '-' | '.' | '(' | ')'))
+            .collect();
+        ((5..=15).contains(&digits.len()) && digits.chars().all(|c| c.is_ascii_digit()))
+            .then_some((ThreePidMedium::Msisdn, digits))
+    }
+}
+
+/// A policy document within an identity server's terms of service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TermsPolicy {
+    pub name: String,
+    pub url: String,
+}
+
+/// An error that occurred while looking up a 3PID via an identity server.
+#[derive(Debug)]
+pub enum IdentityServerError {
+    /// The user has not yet accepted the given terms of service policies of the identity server.
+    TermsNotAccepted(Vec<TermsPolicy>),
+    /// The identity server doesn't support hashed lookups using SHA-256.
+    UnsupportedAlgorithm,
+    /// The identity server sent a response that is missing required fields.
+    InvalidResponse,
+    NotLoggedIn,
+    Homeserver(matrix_sdk::HttpError),
+    Http(reqwest::Error),
+}
+
+impl fmt::Display for IdentityServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentityServerError::TermsNotAccepted(_) => write!(f, "the identity server's terms have not been accepted"),
+            IdentityServerError::UnsupportedAlgorithm => write!(f, "the identity server doesn't support hashed lookups"),
+            IdentityServerError::InvalidResponse => write!(f, "the identity server sent an invalid response"),
+            IdentityServerError::NotLoggedIn => write!(f, "not logged in"),
+            IdentityServerError::Homeserver(e) => write!(f, "{e}"),
+            IdentityServerError::Http(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<matrix_sdk::HttpError> for IdentityServerError {
+    fn from(e: matrix_sdk::HttpError) -> Self {
+        IdentityServerError::Homeserver(e)
+    }
+}
+
+impl From<reqwest::Error> for IdentityServerError {
+    fn from(e: reqwest::Error) -> Self {
+        IdentityServerError::Http(e)
+    }
+}
+
+/// Returns the hash of the given address as sent to the identity server's `lookup` endpoint.
+fn hash_address(medium: ThreePidMedium, address: &str, pepper: &str) -> String {
+    let digest = Sha256::digest(format!("{address} {} {pepper}", medium.as_str()));
+    URL_SAFE_NO_PAD.encode(digest)
+}
+
+/// Parses the policies from an identity server's `terms` response,
+/// preferring each policy's English version if there are multiple languages.
+fn parse_terms(response: &Value) -> Vec<TermsPolicy> {
+    let Some(policies) = response.get("policies").and_then(Value::as_object) else {
+        return Vec::new();
+    };
+    policies.values()
+        .filter_map(|policy| {
+            let policy = policy.as_object()?;
+            let localized = policy.get("en")
+                .or_else(|| policy.values().find(|v| v.get("url").is_some()))?;
+            Some(TermsPolicy {
+                name: localized.get("name")?.as_str()?.to_owned(),
+                url: localized.get("url")?.as_str()?.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Returns an HTTP client for talking to the identity server, using the proxy from the app settings.
+fn http_client() -> Result<reqwest::Client, IdentityServerError> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = app_settings().proxy.url() {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Looks up the Matrix user ID bound to the given normalized address on the given identity server.
+///
+/// This registers with the identity server using an OpenID token from the homeserver,
+/// and fails with [`IdentityServerError::TermsNotAccepted`] if the identity server has
+/// terms of service policies that are not in `settings.accepted_terms`.
+pub async fn lookup_3pid(
+    client: &Client,
+    settings: &IdentityServerSettings,
+    medium: ThreePidMedium,
+    address: &str,
+) -> Result<Option<OwnedUserId>, IdentityServerError> {
+    let base_url = settings.url.trim_end_matches('/');
+    let http = http_client()?;
+    let user_id = client.user_id().ok_or(IdentityServerError::NotLoggedIn)?;
+
+    // An OpenID token proves our identity to the identity server,
+    // which gives us an access token for its other endpoints in return.
+    let openid = client.send(request_openid_token::v3::Request::new(user_id.to_owned()), None).await?;
+    let register: Value = http.post(format!("{base_url}/_matrix/identity/v2/account/register"))
+        .json(&json!({
+            "access_token": openid.access_token,
+            "token_type": "Bearer",
+            "matrix_server_name": openid.matrix_server_name,
+            "expires_in": openid.expires_in.as_secs(),
+        }))
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+    let token = register.get("token")
+        .and_then(Value::as_str)
+        .ok_or(IdentityServerError::InvalidResponse)?;
+
+    let terms: Value = http.get(format!("{base_url}/_matrix/identity/v2/terms"))
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+    let policies = parse_terms(&terms);
+    let unaccepted: Vec<TermsPolicy> = policies.iter()
+        .filter(|p| !settings.accepted_terms.contains(&p.url))
+        .cloned()
+        .collect();
+    if !unaccepted.is_empty() {
+        return Err(IdentityServerError::TermsNotAccepted(unaccepted));
+    }
+    if !policies.is_empty() {
+        let urls: Vec<&str> = policies.iter().map(|p| p.url.as_str()).collect();
+        http.post(format!("{base_url}/_matrix/identity/v2/terms"))
+            .bearer_auth(token)
+            .json(&json!({ "user_accepts": urls }))
+            .send().await?
+            .error_for_status()?;
+    }
+
+    let hash_details: Value = http.get(format!("{base_url}/_matrix/identity/v2/hash_details"))
+        .bearer_auth(token)
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+    let pepper = hash_details.get("lookup_pepper")
+        .and_then(Value::as_str)
+        .ok_or(IdentityServerError::InvalidResponse)?;
+    let supports_sha256 = hash_details.get("algorithms")
+        .and_then(Value::as_array)
+        .is_some_and(|algorithms| algorithms.iter().any(|a| a.as_str() == Some("sha256")));
+    if !supports_sha256 {
+        return Err(IdentityServerError::UnsupportedAlgorithm);
+    }
+
+    let hash = hash_address(medium, address, pepper);
+    let lookup: Value = http.post(format!("{base_url}/_matrix/identity/v2/lookup"))
+        .bearer_auth(token)
+        .json(&json!({
+            "addresses": [&hash],
+            "algorithm": "sha256",
+            "pepper": pepper,
+        }))
+        .send().await?
+        .error_for_status()?
+        .json().await?;
+    Ok(lookup.get("mappings")
+        .and_then(|mappings| mappings.get(&hash))
+        .and_then(Value::as_str)
+        .and_then(|user_id| UserId::parse(user_id).ok()))
+}
+
+#[cfg(test)]
+mod tests_identity_server {
+    use super::*;
+
+    #[test]
+    fn test_parse_address() {
+        assert_eq!(
+            ThreePidMedium::parse_address(" Alice@Example.com "),
+            Some((ThreePidMedium::Email, "alice@example.com".to_owned())),
+        );
+        assert_eq!(
+            ThreePidMedium::parse_address("+1 (800) 555-2067"),
+            Some((ThreePidMedium::Msisdn, "18005552067".to_owned())),
+        );
+        assert_eq!(ThreePidMedium::parse_address("@alice:example.com"), None);
+        assert_eq!(ThreePidMedium::parse_address("8005552067"), None);
+        assert_eq!(ThreePidMedium::parse_address("alice"), None);
+    }
+
+    #[test]
+    fn test_hash_address() {
+        // The example from the Identity Service API specification.
+        assert_eq!(
+            hash_address(ThreePidMedium::Email, "alice@example.com", "matrixrocks"),
+            "4kenr7N9drpCJ4AfalmlGQVsOn3o2RHjkADUpXJWZUc",
+        );
+    }
+
+    #[test]
+    fn test_parse_terms() {
+        let response = json!({
+            "policies": {
+                "privacy_policy": {
+                    "version": "1.2",
+                    "de": { "name": "Datenschutzerklärung", "url": "https://example.org/de/privacy" },
+                    "en": { "name": "Privacy Policy", "url": "https://example.org/en/privacy" },
+                },
+            },
+        });
+        assert_eq!(
+            parse_terms(&response),
+            [TermsPolicy { name: "Privacy Policy".into(), url: "https://example.org/en/privacy".into() }],
+        );
+        assert!(parse_terms(&json!({})).is_empty());
+    }
+}
@@ -0,0 +1,197 @@
+//! Decoding of image data into `ImageBuffer`s on a pool of background threads,
+//! such that decoding large images doesn't cause frame hitches
+//! when they would otherwise be decoded within a draw routine.
+//!
+//! The decoded images are only accessible from the main UI thread,
+//! which keeps the most recently used ones in a bounded cache of textures.
+
+use std::{cell::RefCell, collections::{BTreeMap, BTreeSet}, sync::{mpsc, Arc, Mutex, OnceLock}};
+use crossbeam_queue::SegQueue;
+use makepad_widgets::{image_cache::{ImageBuffer, ImageError}, Cx, SignalToUI, Texture};
+use tracing::error;
+use matrix_sdk::ruma::{OwnedMxcUri, OwnedRoomId};
+
+use crate::utils;
+
+/// The maximum number of threads that decode images concurrently.
+const MAX_DECODING_THREADS: usize = 4;
+/// The maximum total size (in bytes) of the decoded textures kept in the cache.
+const MAX_CACHED_TEXTURE_BYTES: usize = 256 * 1024 * 1024;
+
+thread_local! {
+    /// Images that are being decoded or have been decoded, indexed by Matrix URI.
+    ///
+    /// To be of any use, this must only be accessed by the main UI thread.
+    static DECODED_IMAGES: RefCell<DecodedImageCache> = const { RefCell::new(DecodedImageCache::new()) };
+}
+
+/// The state of an image that has been submitted for decoding.
+pub enum DecodedImage {
+    /// The image is being decoded on a background thread.
+    Decoding,
+    /// The image has been decoded into the given texture.
+    Decoded(Texture),
+    /// The image could not be decoded, for the given reason.
+    Failed(String),
+}
+
+/// An entry in the [`DecodedImageCache`].
+enum CacheEntry {
+    /// The image is being decoded for display in the given rooms.
+    Decoding { room_ids: BTreeSet<OwnedRoomId> },
+    Decoded {
+        texture: Texture,
+        size_bytes: usize,
+        /// When this texture was last used, used to evict the least-recently used textures.
+        last_used: u64,
+    },
+    Failed(String),
+}
+
+/// A cache of decoded images, whose textures are evicted once their total size exceeds
+/// [`MAX_CACHED_TEXTURE_BYTES`], least-recently used first.
+struct DecodedImageCache {
+    entries: BTreeMap<OwnedMxcUri, CacheEntry>,
+    total_bytes: usize,
+    /// A counter that is incremented upon each use of a texture.
+    use_counter: u64,
+}
+
+impl DecodedImageCache {
+    const fn new() -> Self {
+        Self { entries: BTreeMap::new(), total_bytes: 0, use_counter: 0 }
+    }
+
+    /// Inserts the given decoded texture, evicting other textures if the cache is full.
+    fn insert_decoded(&mut self, mxc_uri: OwnedMxcUri, texture: Texture, size_bytes: usize) {
+        self.use_counter += 1;
+        self.entries.insert(mxc_uri, CacheEntry::Decoded { texture, size_bytes, last_used: self.use_counter });
+        self.total_bytes += size_bytes;
+        while self.total_bytes > MAX_CACHED_TEXTURE_BYTES {
+            let least_recently_used = self.entries.iter()
+                .filter_map(|(uri, entry)| match entry {
+                    CacheEntry::Decoded { last_used, .. } => Some((*last_used, uri)),
+                    _ => None,
+                })
+                .min()
+                .map(|(_, uri)| uri.clone());
+            let Some(uri) = least_recently_used else { break };
+            if let Some(CacheEntry::Decoded { size_bytes, .. }) = self.entries.remove(&uri) {
+                self.total_bytes -= size_bytes;
+            }
+        }
+    }
+}
+
+struct DecodedImageUpdate {
+    mxc_uri: OwnedMxcUri,
+    image: Result<ImageBuffer, ImageError>,
+}
+
+/// The queue of decoded images waiting to be processed by the UI thread's event handler.
+static PENDING_DECODED_IMAGES: SegQueue<DecodedImageUpdate> = SegQueue::new();
+
+/// An action emitted by [`process_decoded_images()`] once images have finished decoding.
+#[derive(Clone, Debug)]
+pub enum ImageDecodingAction {
+    /// The images requested by the given rooms have finished decoding,
+    /// so those rooms should be redrawn.
+    Decoded(BTreeSet<OwnedRoomId>),
+}
+
+/// Processes all pending decoded images in the queue,
+/// converting each one into a texture that can be drawn.
+///
+/// If any images were processed, this emits an [`ImageDecodingAction::Decoded`]
+/// such that the rooms displaying those images can be redrawn.
+///
+/// This should be called once upon each `Event::Signal`, by the top-level app.
+pub fn process_decoded_images(cx: &mut Cx) {
+    let mut room_ids = BTreeSet::new();
+    DECODED_IMAGES.with_borrow_mut(|cache| {
+        while let Some(update) = PENDING_DECODED_IMAGES.pop() {
+            if let Some(CacheEntry::Decoding { room_ids: requesting_rooms }) = cache.entries.remove(&update.mxc_uri) {
+                room_ids.extend(requesting_rooms);
+            }
+            match update.image {
+                Ok(image_buffer) => {
+                    let size_bytes = image_buffer.data.len() * std::mem::size_of::<u32>();
+                    cache.insert_decoded(update.mxc_uri, image_buffer.into_new_texture(cx), size_bytes);
+                }
+                Err(e) => {
+                    error!("Failed to decode image {}: {e:?}", update.mxc_uri);
+                    cache.entries.insert(update.mxc_uri, CacheEntry::Failed(format!("{e:?}")));
+                }
+            }
+        }
+    });
+    if !room_ids.is_empty() {
+        cx.action(ImageDecodingAction::Decoded(room_ids));
+    }
+}
+
+/// Returns the decoded image for the given Matrix URI if it has finished decoding,
+/// otherwise starts decoding the given image `data` on a background thread
+/// (if not already in progress) and returns [`DecodedImage::Decoding`].
+///
+/// Once decoding has finished, the given room is notified via an [`ImageDecodingAction`].
+///
+/// This function requires passing in a reference to `Cx`,
+/// which isn't used, but acts as a guarantee that this function
+/// must only be called by the main UI thread.
+pub fn get_or_decode_image(_cx: &mut Cx, room_id: &OwnedRoomId, mxc_uri: &OwnedMxcUri, data: &Arc<[u8]>) -> DecodedImage {
+    DECODED_IMAGES.with_borrow_mut(|cache| {
+        cache.use_counter += 1;
+        let use_counter = cache.use_counter;
+        match cache.entries.get_mut(mxc_uri) {
+            Some(CacheEntry::Decoded { texture, last_used, .. }) => {
+                *last_used = use_counter;
+                DecodedImage::Decoded(texture.clone())
+            }
+            Some(CacheEntry::Failed(e)) => DecodedImage::Failed(e.clone()),
+            Some(CacheEntry::Decoding { room_ids }) => {
+                room_ids.insert(room_id.clone());
+                DecodedImage::Decoding
+            }
+            None => {
+                cache.entries.insert(
+                    mxc_uri.clone(),
+                    CacheEntry::Decoding { room_ids: BTreeSet::from([room_id.clone()]) },
+                );
+                submit_decoding_job(mxc_uri.clone(), Arc::clone(data));
+                DecodedImage::Decoding
+            }
+        }
+    })
+}
+
+/// A request to decode the given image data on a background thread.
+type DecodingJob = (OwnedMxcUri, Arc<[u8]>);
+
+/// Queues the given image to be decoded by the pool of decoding threads,
+/// starting that pool if it isn't running yet.
+fn submit_decoding_job(mxc_uri: OwnedMxcUri, data: Arc<[u8]>) {
+    static JOB_SENDER: OnceLock<mpsc::Sender<DecodingJob>> = OnceLock::new();
+    let sender = JOB_SENDER.get_or_init(|| {
+        let (sender, receiver) = mpsc::channel::<DecodingJob>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let num_threads = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(MAX_DECODING_THREADS);
+        for _ in 0..num_threads {
+            let receiver = Arc::clone(&receiver);
+            std::thread::spawn(move || loop {
+                // Only hold the lock while waiting for the next job, not while decoding it.
+                let job = receiver.lock().unwrap().recv();
+                let Ok((mxc_uri, data)) = job else { break };
+                let image = utils::decode_png_or_jpg(&data);
+                PENDING_DECODED_IMAGES.push(DecodedImageUpdate { mxc_uri, image });
+                SignalToUI::set_ui_signal();
+            });
+        }
+        sender
+    });
+    if sender.send((mxc_uri, data)).is_err() {
+        error!("BUG: the image decoding threads have stopped.");
+    }
+}
@@ -5,6 +5,8 @@ use robius_directories::ProjectDirs;
 pub use makepad_widgets;
 pub mod app;
 pub mod persistent_state;
+/// Versioning and migration of persisted state.
+pub mod versioned_state;
 
 /// Login screen
 pub mod login;
@@ -14,17 +16,40 @@ pub mod home;
 mod profile;
 /// A modal/dialog popup for interactive verification of users/devices.
 mod verification_modal;
+/// Locking the app behind a PIN or biometrics upon launch and after inactivity.
+pub mod app_lock;
 /// Shared UI components.
 pub mod shared;
 /// Generating text previews of timeline events/messages.
 mod event_preview;
+/// App settings and the settings screen.
+pub mod settings;
+/// Translations of user-facing text.
+pub mod i18n;
+/// Handling of `matrix:` and `matrix.to` deep links.
+pub mod deep_link;
+/// Forwarding of command-line arguments to an already-running instance of Robrix.
+pub mod single_instance;
+/// Structured logging to stdout and to rotating log files.
+pub mod logging;
+/// Spell checking of composed messages using Hunspell dictionaries.
+pub mod spell_check;
 
 
 // Matrix stuff
 pub mod sliding_sync;
+pub mod app_badge;
 pub mod avatar_cache;
 pub mod media_cache;
+pub mod network_status;
+pub mod image_decoding;
 pub mod verification;
+pub mod room_export;
+pub mod bridges;
+pub mod room_wallpaper;
+pub mod policy_lists;
+pub mod scheduled_messages;
+pub mod identity_server;
 
 pub mod utils;
 pub mod temp_storage;
@@ -2,7 +2,8 @@
 
 use std::{sync::{mpsc::{self, Receiver, Sender}, Mutex}, time::SystemTime};
 
-use makepad_widgets::{Cx, error, log};
+use makepad_widgets::Cx;
+use tracing::{error, info};
 use robius_location::{Access, Accuracy, Coordinates, Location, Manager};
 
 /// The action emitted upon every location update.
@@ -38,7 +39,7 @@ struct LocationHandler;
 impl robius_location::Handler for LocationHandler {
     fn handle(&self, location: Location<'_>) {
         let coords = location.coordinates();
-        log!("Received location update: {coords:?}");
+        info!("Received location update: {coords:?}");
         match coords {
             Ok(coords) => {
                 let update = LocationUpdate {
@@ -122,7 +123,7 @@ pub fn request_location_update(request: LocationRequest) {
 pub fn init_location_subscriber(_cx: &mut Cx) -> Result<(), robius_location::Error> {
     let mut lrs = LOCATION_REQUEST_SENDER.lock().unwrap();
     if lrs.is_some() {
-        log!("Location subscriber already initialized.");
+        info!("Location subscriber already initialized.");
         return Ok(());
     }
     let manager = ManagerWrapper(Manager::new(LocationHandler)?);
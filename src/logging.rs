@@ -0,0 +1,275 @@
+//! Structured logging via `tracing`, written both to stdout and to daily-rotated log files
+//! in the app data directory, such that users can provide logs when reporting issues.
+//!
+//! The log level of each part of the app is configurable via [`LogLevels`] in the settings screen,
+//! which can be overridden entirely by setting the `RUST_LOG` environment variable.
+
+use std::{fs::File, io::{self, Read, Seek, SeekFrom}, path::{Path, PathBuf}, sync::OnceLock};
+
+use makepad_widgets::error;
+use serde::{Deserialize, Serialize};
+use tracing_appender::{non_blocking::WorkerGuard, rolling::{RollingFileAppender, Rotation}};
+use tracing_subscriber::{fmt, layer::SubscriberExt, reload, util::SubscriberInitExt, EnvFilter, Registry};
+
+use crate::{app_data_dir, settings::app_settings::app_settings};
+
+const LOG_DIR_NAME: &str = "logs";
+const LOG_FILE_PREFIX: &str = "robrix";
+const LOG_FILE_SUFFIX: &str = "log";
+/// The number of daily log files to keep before the oldest ones are deleted.
+const MAX_LOG_FILES: usize = 7;
+
+/// Keeps the background thread that writes to the log file alive for the app's lifetime.
+static LOG_FILE_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+/// Allows changing the log filter after logging has been initialized.
+///
+/// This is only set if the log levels come from the app settings rather than `RUST_LOG`.
+static FILTER_RELOAD_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// A level of detail of log output, from least to most detailed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    /// All log levels, in the order they are shown in the settings screen.
+    pub const ALL: [LogLevel; 6] = [
+        LogLevel::Off,
+        LogLevel::Error,
+        LogLevel::Warn,
+        LogLevel::Info,
+        LogLevel::Debug,
+        LogLevel::Trace,
+    ];
+
+    /// Parses the given level as printed in a log line, e.g., `INFO`.
+    fn from_log_line_str(s: &str) -> Option<Self> {
+        match s {
+            "ERROR" => Some(LogLevel::Error),
+            "WARN" => Some(LogLevel::Warn),
+            "INFO" => Some(LogLevel::Info),
+            "DEBUG" => Some(LogLevel::Debug),
+            "TRACE" => Some(LogLevel::Trace),
+            _ => None,
+        }
+    }
+
+    /// Returns this level as used in `tracing` filter directives.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LogLevel::Off => "off",
+            LogLevel::Error => "error",
+            LogLevel::Warn => "warn",
+            LogLevel::Info => "info",
+            LogLevel::Debug => "debug",
+            LogLevel::Trace => "trace",
+        }
+    }
+}
+
+/// The log level of each part of the app.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogLevels {
+    /// The background Matrix sync and request handling in `sliding_sync`.
+    pub sliding_sync: LogLevel,
+    /// All other parts of Robrix, i.e., the UI.
+    pub ui: LogLevel,
+    /// All dependencies, including the Matrix SDK.
+    pub dependencies: LogLevel,
+}
+
+impl Default for LogLevels {
+    fn default() -> Self {
+        Self {
+            sliding_sync: LogLevel::Info,
+            ui: LogLevel::Info,
+            dependencies: LogLevel::Warn,
+        }
+    }
+}
+
+impl LogLevels {
+    /// Returns the `tracing` filter directives for these log levels.
+    pub fn filter_directives(&self) -> String {
+        let crate_name = env!("CARGO_CRATE_NAME");
+        format!(
+            "{},{crate_name}={},{crate_name}::sliding_sync={}",
+            self.dependencies.as_str(),
+            self.ui.as_str(),
+            self.sliding_sync.as_str(),
+        )
+    }
+}
+
+/// Returns the directory in which log files are stored.
+pub fn log_dir() -> PathBuf {
+    app_data_dir().join(LOG_DIR_NAME)
+}
+
+/// Returns the paths of all log files, sorted from oldest to most recent.
+pub fn log_files() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(log_dir()) else { return Vec::new() };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX))
+        )
+        .collect();
+    // Log file names end with their date, so sorting them by name sorts them by age.
+    paths.sort();
+    paths
+}
+
+/// Returns the path of the most recent log file, if any.
+pub fn current_log_file() -> Option<PathBuf> {
+    log_files().pop()
+}
+
+/// Reads up to the last `max_bytes` of the given log file,
+/// skipping the partial line at the start of that range, if any.
+pub fn read_log_tail(path: &Path, max_bytes: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = len.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+    let mut bytes = Vec::with_capacity((len - start) as usize);
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes);
+    Ok(match (start > 0, text.find('\n')) {
+        (true, Some(first_newline)) => text[first_newline + 1 ..].to_string(),
+        _ => text.into_owned(),
+    })
+}
+
+/// Returns the last `max_lines` lines of the given log text that are at or above `min_level`
+/// and contain the given `search` text (case-insensitively), if it's non-empty.
+///
+/// Lines without a level, e.g., the continuation of a multi-line message,
+/// are considered to have the level of the preceding line.
+pub fn filter_log_lines<'t>(
+    text: &'t str,
+    min_level: LogLevel,
+    search: &str,
+    max_lines: usize,
+) -> Vec<&'t str> {
+    let search = search.trim().to_lowercase();
+    let mut current_level = LogLevel::Info;
+    let mut lines: Vec<&str> = text.lines()
+        .filter(|line| {
+            // Log lines start with a timestamp, followed by the level.
+            if let Some(level) = line.split_whitespace().nth(1).and_then(LogLevel::from_log_line_str) {
+                current_level = level;
+            }
+            current_level != LogLevel::Off
+                && current_level <= min_level
+                && (search.is_empty() || line.to_lowercase().contains(&search))
+        })
+        .collect();
+    let excess = lines.len().saturating_sub(max_lines);
+    lines.drain(..excess);
+    lines
+}
+
+/// Initializes logging to stdout and to a daily-rotated log file in [`log_dir()`].
+///
+/// If the log file can't be created, logging continues to stdout only.
+/// This should be called once, as early as possible upon app startup;
+/// subsequent calls have no effect. Messages logged before this is called are dropped.
+pub fn init() {
+    let env_filter = EnvFilter::try_from_default_env().ok();
+    let is_env_filter = env_filter.is_some();
+    let filter = env_filter
+        .unwrap_or_else(|| EnvFilter::new(app_settings().log_levels.filter_directives()));
+    let (filter, reload_handle) = reload::Layer::new(filter);
+
+    let file_appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir());
+    let file_layer = match file_appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = LOG_FILE_GUARD.set(guard);
+            Some(fmt::layer().with_writer(writer).with_ansi(false))
+        }
+        Err(e) => {
+            error!("Failed to create log file in {}: {e}", log_dir().display());
+            None
+        }
+    };
+
+    let initialized = tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .with(file_layer)
+        .try_init();
+    if initialized.is_ok() && !is_env_filter {
+        let _ = FILTER_RELOAD_HANDLE.set(reload_handle);
+    }
+}
+
+/// Applies the given log levels immediately, unless they're overridden by `RUST_LOG`.
+pub fn apply_log_levels(levels: &LogLevels) {
+    let Some(handle) = FILTER_RELOAD_HANDLE.get() else { return };
+    if let Err(e) = handle.reload(EnvFilter::new(levels.filter_directives())) {
+        error!("Failed to apply new log levels: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests_logging {
+    use super::*;
+
+    #[test]
+    fn test_filter_directives() {
+        let levels = LogLevels {
+            sliding_sync: LogLevel::Debug,
+            ui: LogLevel::Info,
+            dependencies: LogLevel::Off,
+        };
+        assert_eq!(
+            levels.filter_directives(),
+            "off,robrix=info,robrix::sliding_sync=debug",
+        );
+    }
+
+    #[test]
+    fn test_filter_log_lines() {
+        let text = "\
+            2026-01-01T00:00:00Z  INFO robrix::app: started\n\
+            2026-01-01T00:00:01Z ERROR robrix::sliding_sync: sync failed\n\
+            with a second line\n\
+            2026-01-01T00:00:02Z DEBUG robrix::home: drew rooms list\n\
+            2026-01-01T00:00:03Z  WARN matrix_sdk: slow Sync response";
+        assert_eq!(
+            filter_log_lines(text, LogLevel::Warn, "", 10),
+            [
+                "2026-01-01T00:00:01Z ERROR robrix::sliding_sync: sync failed",
+                "with a second line",
+                "2026-01-01T00:00:03Z  WARN matrix_sdk: slow Sync response",
+            ],
+        );
+        assert_eq!(
+            filter_log_lines(text, LogLevel::Trace, "SYNC", 10),
+            [
+                "2026-01-01T00:00:01Z ERROR robrix::sliding_sync: sync failed",
+                "2026-01-01T00:00:03Z  WARN matrix_sdk: slow Sync response",
+            ],
+        );
+        assert_eq!(
+            filter_log_lines(text, LogLevel::Trace, "", 1),
+            ["2026-01-01T00:00:03Z  WARN matrix_sdk: slow Sync response"],
+        );
+    }
+}
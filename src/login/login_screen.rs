@@ -1,6 +1,7 @@
 use std::ops::Not;
 
 use makepad_widgets::*;
+use tracing::info;
 use url::Url;
 
 use crate::sliding_sync::{submit_async_request, LoginByPassword, LoginRequest, MatrixRequest};
@@ -311,7 +312,7 @@ impl MatchEvent for LoginScreen {
         let login_status_modal_inner = self.view.login_status_modal(id!(login_status_modal_inner));
 
         if signup_button.clicked(actions) {
-            log!("Opening URL \"{}\"", MATRIX_SIGN_UP_URL);
+            info!("Opening URL \"{}\"", MATRIX_SIGN_UP_URL);
             let _ = robius_open::Uri::new(MATRIX_SIGN_UP_URL).open();
         }
 
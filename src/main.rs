@@ -2,5 +2,9 @@
 // as dll (mobile / wasm) and some require to be built as executable
 // unfortunately cargo doesn't facilitate this without a main.rs stub
 fn main() {
+    // If Robrix is already running, let that instance handle our arguments instead.
+    if robrix::single_instance::forward_to_running_instance() {
+        return;
+    }
     robrix::app::app_main()
 }
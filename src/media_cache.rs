@@ -1,10 +1,31 @@
-use std::{sync::{Mutex, Arc}, collections::{BTreeMap, btree_map::Entry}, time::SystemTime, ops::{Deref, DerefMut}};
-use makepad_widgets::{error, log, SignalToUI};
+use std::{sync::{Mutex, Arc, atomic::{AtomicU64, Ordering}}, collections::{BTreeMap, VecDeque, btree_map::Entry}, time::SystemTime, ops::{Deref, DerefMut}};
+use makepad_widgets::SignalToUI;
+use tracing::{error, info};
 use matrix_sdk::{ruma::{OwnedMxcUri, events::room::MediaSource}, media::{MediaRequest, MediaFormat}};
-use crate::{home::room_screen::TimelineUpdate, sliding_sync::{self, MatrixRequest}, utils::MediaFormatConst};
+use crate::{home::room_screen::TimelineUpdate, settings::app_settings::app_settings, sliding_sync::{self, MatrixRequest}, utils::MediaFormatConst};
 
 pub type MediaCacheEntryRef = Arc<Mutex<MediaCacheEntry>>;
 
+/// The total size in bytes of the media loaded into all media caches.
+static TOTAL_MEDIA_CACHE_SIZE: AtomicU64 = AtomicU64::new(0);
+
+/// Incremented upon every request to clear all media caches.
+///
+/// Each media cache lazily clears itself the next time it is accessed
+/// after noticing that this generation has changed.
+static MEDIA_CACHE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Returns the total size in bytes of the media loaded into all media caches.
+pub fn total_media_cache_size() -> u64 {
+    TOTAL_MEDIA_CACHE_SIZE.load(Ordering::Relaxed)
+}
+
+/// Requests that all media caches be cleared.
+pub fn clear_all_media_caches() {
+    MEDIA_CACHE_GENERATION.fetch_add(1, Ordering::Relaxed);
+    SignalToUI::set_ui_signal();
+}
+
 /// An entry in the media cache. 
 #[derive(Debug, Clone)]
 pub enum MediaCacheEntry {
@@ -24,6 +45,11 @@ pub struct MediaCache {
     default_format: MediaFormatConst,
     /// A channel to send updates to a particular timeline when a media request has completed.
     timeline_update_sender: Option<crossbeam_channel::Sender<TimelineUpdate>>,
+    /// The keys of the `cache`, in the order they were inserted (oldest first),
+    /// which is used to evict the oldest media when the cache size limit is exceeded.
+    insertion_order: VecDeque<OwnedMxcUri>,
+    /// The value of `MEDIA_CACHE_GENERATION` when this cache was last cleared.
+    generation: u64,
 }
 impl Deref for MediaCache {
     type Target = BTreeMap<OwnedMxcUri, MediaCacheEntryRef>;
@@ -51,6 +77,8 @@ impl MediaCache {
             cache: BTreeMap::new(),
             default_format,
             timeline_update_sender,
+            insertion_order: VecDeque::new(),
+            generation: 0,
         }
     }
 
@@ -71,12 +99,15 @@ impl MediaCache {
         mxc_uri: OwnedMxcUri,
         media_format: Option<MediaFormat>,
     ) -> MediaCacheEntry {
-        let value_ref = match self.entry(mxc_uri.clone()) {
-            Entry::Vacant(vacant) => vacant.insert(
-                Arc::new(Mutex::new(MediaCacheEntry::Requested))
-            ),
-            Entry::Occupied(occupied) => return occupied.get().lock().unwrap().deref().clone(),
-        };
+        self.clear_if_stale();
+        if let Entry::Occupied(occupied) = self.cache.entry(mxc_uri.clone()) {
+            return occupied.get().lock().unwrap().deref().clone();
+        }
+        self.evict_to_fit(app_settings().media_cache_max_bytes());
+        self.insertion_order.push_back(mxc_uri.clone());
+        let value_ref = self.cache.entry(mxc_uri.clone()).or_insert_with(||
+            Arc::new(Mutex::new(MediaCacheEntry::Requested))
+        );
 
         let destination = Arc::clone(value_ref);
         let format = media_format.unwrap_or_else(||
@@ -95,6 +126,62 @@ impl MediaCache {
         );
         MediaCacheEntry::Requested
     }
+
+    /// Clears this cache if all media caches have been requested to be cleared
+    /// since the last time this cache was cleared.
+    ///
+    /// Media that is still being fetched is kept, such that those requests can complete.
+    fn clear_if_stale(&mut self) {
+        let current_generation = MEDIA_CACHE_GENERATION.load(Ordering::Relaxed);
+        if self.generation == current_generation {
+            return;
+        }
+        self.generation = current_generation;
+        self.cache.retain(|_, entry| match entry.lock().unwrap().deref() {
+            MediaCacheEntry::Requested => true,
+            MediaCacheEntry::Loaded(data) => {
+                TOTAL_MEDIA_CACHE_SIZE.fetch_sub(data.len() as u64, Ordering::Relaxed);
+                false
+            }
+            MediaCacheEntry::Failed => false,
+        });
+        let cache = &self.cache;
+        self.insertion_order.retain(|mxc_uri| cache.contains_key(mxc_uri));
+    }
+
+    /// Evicts the oldest loaded media from this cache until the total size of all media caches
+    /// is no larger than the given size, or until there is no more media in this cache to evict.
+    fn evict_to_fit(&mut self, max_size: u64) {
+        let mut remaining = self.insertion_order.len();
+        while remaining > 0 && total_media_cache_size() > max_size {
+            remaining -= 1;
+            let Some(mxc_uri) = self.insertion_order.pop_front() else { break };
+            let Entry::Occupied(occupied) = self.cache.entry(mxc_uri.clone()) else { continue };
+            let size = match occupied.get().lock().unwrap().deref() {
+                // Don't evict media that is still being fetched.
+                MediaCacheEntry::Requested => None,
+                MediaCacheEntry::Loaded(data) => Some(data.len() as u64),
+                MediaCacheEntry::Failed => Some(0),
+            };
+            match size {
+                Some(size) => {
+                    occupied.remove();
+                    TOTAL_MEDIA_CACHE_SIZE.fetch_sub(size, Ordering::Relaxed);
+                }
+                None => self.insertion_order.push_back(mxc_uri),
+            }
+        }
+    }
+}
+
+impl Drop for MediaCache {
+    fn drop(&mut self) {
+        for entry in self.cache.values() {
+            if let MediaCacheEntry::Loaded(data) = entry.lock().unwrap().deref() {
+                TOTAL_MEDIA_CACHE_SIZE.fetch_sub(data.len() as u64, Ordering::Relaxed);
+            }
+        }
+    }
 }
 
 /// Insert data into a previously-requested media cache entry.
@@ -111,7 +198,7 @@ fn insert_into_cache<D: Into<Arc<[u8]>>>(
             // debugging: dump out the media image to disk
             if false {
                 if let MediaSource::Plain(mxc_uri) = _request.source {
-                    log!("Fetched media for {mxc_uri}");
+                    info!("Fetched media for {mxc_uri}");
                     let mut path = crate::temp_storage::get_temp_dir_path().clone();
                     let filename = format!("{}_{}_{}",
                         SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis(),
@@ -119,12 +206,13 @@ fn insert_into_cache<D: Into<Arc<[u8]>>>(
                     );
                     path.push(filename);
                     path.set_extension("png");
-                    log!("Writing user media image to disk: {:?}", path);
+                    info!("Writing user media image to disk: {:?}", path);
                     std::fs::write(path, &data)
                         .expect("Failed to write user media image to disk");
                 }
             }
 
+            TOTAL_MEDIA_CACHE_SIZE.fetch_add(data.len() as u64, Ordering::Relaxed);
             MediaCacheEntry::Loaded(data)
         }
         Err(e) => {
@@ -0,0 +1,111 @@
+//! Detection of whether the current network connection is metered (e.g., cellular),
+//! such that media isn't downloaded automatically if the user enabled data saving.
+//!
+//! There is no robius crate for querying the network status yet, so detection is currently
+//! only supported on Linux, via NetworkManager. On other platforms, the connection
+//! is assumed not to be metered, such that media is only withheld if auto-download is disabled entirely.
+
+use std::{sync::{atomic::{AtomicBool, Ordering}, Once}, time::Duration};
+
+use makepad_widgets::Cx;
+use tracing::info;
+
+use crate::settings::app_settings::{app_settings, MediaAutoDownload};
+
+/// How often the network status is checked for changes.
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+static IS_METERED: AtomicBool = AtomicBool::new(false);
+
+/// Actions sent to the UI thread when whether media should be auto-downloaded may have changed.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum NetworkStatusAction {
+    /// The network connection became metered (`true`) or not metered (`false`).
+    MeteredChanged(bool),
+    /// The user changed the media auto-download setting.
+    AutoDownloadSettingChanged,
+}
+
+/// Returns whether the current network connection is known to be metered.
+pub fn is_metered() -> bool {
+    IS_METERED.load(Ordering::Relaxed)
+}
+
+/// Returns whether media should currently be downloaded automatically,
+/// based on the user's auto-download setting and the network status.
+pub fn should_auto_download_media() -> bool {
+    match app_settings().media_auto_download {
+        MediaAutoDownload::Always => true,
+        MediaAutoDownload::UnlessMetered => !is_metered(),
+        MediaAutoDownload::Never => false,
+    }
+}
+
+/// Starts periodically checking whether the network connection is metered on a background thread.
+///
+/// This only has an effect the first time it is called.
+pub fn start_monitoring() {
+    static START: Once = Once::new();
+    START.call_once(|| {
+        std::thread::spawn(|| loop {
+            if let Some(metered) = query_metered() {
+                if IS_METERED.swap(metered, Ordering::Relaxed) != metered {
+                    info!("Network connection is now {}metered", if metered { "" } else { "not " });
+                    Cx::post_action(NetworkStatusAction::MeteredChanged(metered));
+                }
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        });
+    });
+}
+
+/// Queries NetworkManager's global `Metered` property, returning `None` if it is unavailable.
+#[cfg(target_os = "linux")]
+fn query_metered() -> Option<bool> {
+    let output = std::process::Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_network_manager_metered(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn query_metered() -> Option<bool> {
+    None
+}
+
+/// Parses the output of `busctl get-property` for NetworkManager's `Metered` property,
+/// e.g., `u 4`, whose value is an `NMMetered`: 0 (unknown), 1 (yes), 2 (no), 3 (guess yes), or 4 (guess no).
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_network_manager_metered(output: &str) -> Option<bool> {
+    let value = output.trim().strip_prefix("u ")?.parse::<u32>().ok()?;
+    match value {
+        1 | 3 => Some(true),
+        0 | 2 | 4 => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests_network_status {
+    use super::*;
+
+    #[test]
+    fn test_parse_network_manager_metered() {
+        assert_eq!(parse_network_manager_metered("u 1\n"), Some(true));
+        assert_eq!(parse_network_manager_metered("u 3"), Some(true));
+        assert_eq!(parse_network_manager_metered("u 4"), Some(false));
+        assert_eq!(parse_network_manager_metered("u 0"), Some(false));
+        assert_eq!(parse_network_manager_metered("u 9"), None);
+        assert_eq!(parse_network_manager_metered("Failed to get property"), None);
+    }
+}
@@ -1,15 +1,19 @@
 //! Handles app persistence by saving and restoring client session data to/from the filesystem.
 
-use std::path::PathBuf;
+use std::{path::{Path, PathBuf}, sync::Mutex};
 use anyhow::{anyhow, bail};
-use makepad_widgets::{log, Cx};
+use makepad_widgets::Cx;
+use tracing::{info, warn};
 use matrix_sdk::{
-    matrix_auth::MatrixSession, ruma::{OwnedUserId, UserId}, sliding_sync::VersionBuilder, Client
+    matrix_auth::MatrixSession, ruma::{OwnedRoomId, OwnedUserId, UserId}, sliding_sync::VersionBuilder, Client
 };
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 
-use crate::{app_data_dir, login::login_screen::LoginAction};
+use crate::{
+    app::SelectedRoom, app_data_dir, login::login_screen::LoginAction, settings::app_settings::app_settings,
+    versioned_state::{self, unversioned_to_v1, Migration, VersionedState},
+};
 
 /// The data needed to re-build a client.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +46,11 @@ pub struct FullSessionPersisted {
     pub sync_token: Option<String>,
 }
 
+impl VersionedState for FullSessionPersisted {
+    const CURRENT_VERSION: u64 = 1;
+    const MIGRATIONS: &'static [Migration] = &[unversioned_to_v1];
+}
+
 fn user_id_to_file_name(user_id: &UserId) -> String {
     user_id.as_str()
         .replace(":", "_")
@@ -58,6 +67,57 @@ pub fn session_file_path(user_id: &UserId) -> PathBuf {
     persistent_state_dir(user_id).join("session")
 }
 
+pub fn dock_state_file_path(user_id: &UserId) -> PathBuf {
+    persistent_state_dir(user_id).join("dock_state.json")
+}
+
+pub fn policy_lists_file_path(user_id: &UserId) -> PathBuf {
+    persistent_state_dir(user_id).join("policy_lists.json")
+}
+
+pub fn scheduled_messages_file_path(user_id: &UserId) -> PathBuf {
+    persistent_state_dir(user_id).join("scheduled_messages.json")
+}
+
+/// The state of the main desktop dock, i.e., which rooms are open in tabs,
+/// which is saved such that the user's workspace can be restored on startup.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedDockState {
+    /// The rooms that are open in tabs, in the order they were opened.
+    pub open_rooms: Vec<SelectedRoom>,
+    /// The room whose tab is selected, if any.
+    pub selected_room: Option<OwnedRoomId>,
+}
+
+impl VersionedState for SavedDockState {
+    const CURRENT_VERSION: u64 = 1;
+    const MIGRATIONS: &'static [Migration] = &[unversioned_to_v1];
+}
+
+/// Saves the given user's dock state to the filesystem.
+pub fn save_dock_state(user_id: &UserId, dock_state: &SavedDockState) -> anyhow::Result<()> {
+    let dock_state_file = dock_state_file_path(user_id);
+    if let Some(parent) = dock_state_file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&dock_state_file, versioned_state::to_versioned_json(dock_state)?)?;
+    Ok(())
+}
+
+/// Loads the given user's previously-saved dock state from the filesystem, if any.
+pub fn load_dock_state(user_id: &UserId) -> Option<SavedDockState> {
+    let dock_state_file = dock_state_file_path(user_id);
+    let serialized_dock_state = std::fs::read_to_string(&dock_state_file).ok()?;
+    match versioned_state::from_versioned_json(&serialized_dock_state) {
+        Ok(dock_state) => Some(dock_state),
+        Err(e) => {
+            info!("Failed to load saved dock state for user {user_id}: {e}");
+            versioned_state::back_up_unreadable_file(&dock_state_file);
+            None
+        }
+    }
+}
+
 const LATEST_USER_ID_FILE_NAME: &str = "latest_user_id.txt";
 
 /// Returns the user ID of the most recently-logged in user session.
@@ -71,6 +131,42 @@ pub fn most_recent_user_id() -> Option<OwnedUserId> {
     .ok()
 }
 
+/// The database paths of the clients built by this running instance of Robrix,
+/// which may not (yet) have been saved to a session file.
+static DB_PATHS_IN_USE: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Records that a client in this running instance of Robrix uses the database at the given path,
+/// such that it is never considered unused.
+pub fn mark_db_path_in_use(db_path: &Path) {
+    let mut in_use = DB_PATHS_IN_USE.lock().unwrap();
+    if !in_use.iter().any(|p| p == db_path) {
+        in_use.push(db_path.to_path_buf());
+    }
+}
+
+/// Returns the database paths of all user sessions saved to the filesystem,
+/// plus those of the clients built by this running instance of Robrix.
+///
+/// Any client database not in this list is no longer used by any saved session.
+///
+/// Fails if any session file can't be read or parsed (e.g., because it was saved
+/// by a newer version of Robrix), since we then can't know which database it uses.
+pub fn saved_db_paths() -> anyhow::Result<Vec<PathBuf>> {
+    let mut db_paths = DB_PATHS_IN_USE.lock().unwrap().clone();
+    for entry in std::fs::read_dir(app_data_dir())? {
+        let session_file = entry?.path().join("persistent_state").join("session");
+        if !session_file.exists() {
+            continue;
+        }
+        let serialized_session = std::fs::read_to_string(&session_file)
+            .map_err(|e| anyhow!("Failed to read session file {}: {e}", session_file.display()))?;
+        let session: FullSessionPersisted = versioned_state::from_versioned_json(&serialized_session)
+            .map_err(|e| anyhow!("Failed to parse session file {}: {e}", session_file.display()))?;
+        db_paths.push(session.client_session.db_path);
+    }
+    Ok(db_paths)
+}
+
 /// Save which user was the most recently logged in.
 async fn save_latest_user_id(user_id: &UserId) -> anyhow::Result<()> {
     fs::write(
@@ -81,6 +177,62 @@ async fn save_latest_user_id(user_id: &UserId) -> anyhow::Result<()> {
 }
 
 
+/// The name of the keyring service under which session secrets are stored.
+#[cfg(not(target_os = "android"))]
+pub(crate) const KEYRING_SERVICE: &str = "org.robius.robrix";
+
+/// The secrets of a persisted session, which are stored in the platform keyring
+/// (if available) instead of in the plaintext session file.
+#[derive(Serialize, Deserialize)]
+struct SessionSecrets {
+    access_token: String,
+    refresh_token: Option<String>,
+    /// The passphrase of the session's database.
+    passphrase: String,
+}
+
+impl SessionSecrets {
+    /// Moves the secrets out of the given session info, leaving empty values in their place.
+    fn take_from(client_session: &mut ClientSessionPersisted, user_session: &mut MatrixSession) -> Self {
+        Self {
+            access_token: std::mem::take(&mut user_session.tokens.access_token),
+            refresh_token: user_session.tokens.refresh_token.take(),
+            passphrase: std::mem::take(&mut client_session.passphrase),
+        }
+    }
+
+    /// Moves these secrets back into the given session info.
+    fn put_into(self, client_session: &mut ClientSessionPersisted, user_session: &mut MatrixSession) {
+        user_session.tokens.access_token = self.access_token;
+        user_session.tokens.refresh_token = self.refresh_token;
+        client_session.passphrase = self.passphrase;
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn save_secrets_to_keyring(user_id: &UserId, secrets: &SessionSecrets) -> anyhow::Result<()> {
+    keyring::Entry::new(KEYRING_SERVICE, user_id.as_str())?
+        .set_password(&serde_json::to_string(secrets)?)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "android"))]
+fn load_secrets_from_keyring(user_id: &UserId) -> anyhow::Result<SessionSecrets> {
+    let serialized_secrets = keyring::Entry::new(KEYRING_SERVICE, user_id.as_str())?.get_password()?;
+    Ok(serde_json::from_str(&serialized_secrets)?)
+}
+
+#[cfg(target_os = "android")]
+fn save_secrets_to_keyring(_user_id: &UserId, _secrets: &SessionSecrets) -> anyhow::Result<()> {
+    bail!("The platform keyring is not supported on Android")
+}
+
+#[cfg(target_os = "android")]
+fn load_secrets_from_keyring(_user_id: &UserId) -> anyhow::Result<SessionSecrets> {
+    bail!("The platform keyring is not supported on Android")
+}
+
+
 /// Restores the given user's previous session from the filesystem.
 ///
 /// If no User ID is specified, the ID of the most recently-logged in user
@@ -89,16 +241,16 @@ pub async fn restore_session(
     user_id: Option<OwnedUserId>
 ) -> anyhow::Result<(Client, Option<String>)> {
     let Some(user_id) = user_id.or_else(most_recent_user_id) else {
-        log!("Could not find previous latest User ID");
+        info!("Could not find previous latest User ID");
         bail!("Could not find previous latest User ID");
     };
     let session_file = session_file_path(&user_id);
     if !session_file.exists() {
-        log!("Could not find previous session file for user {user_id}");
+        info!("Could not find previous session file for user {user_id}");
         bail!("Could not find previous session file");
     }
     let status_str = format!("Loading previous session file for {user_id}...");
-    log!("{status_str}: '{}'", session_file.display());
+    info!("{status_str}: '{}'", session_file.display());
     Cx::post_action(LoginAction::Status {
         title: "Restoring session".into(),
         status: status_str,
@@ -106,30 +258,46 @@ pub async fn restore_session(
 
     // The session was serialized as JSON in a file.
     let serialized_session = fs::read_to_string(session_file).await?;
-    let FullSessionPersisted { client_session, user_session, sync_token } =
-        serde_json::from_str(&serialized_session)?;
+    // A session file that can't be read, e.g., one saved by a newer version of Robrix,
+    // is left untouched, such that it isn't lost.
+    let FullSessionPersisted { mut client_session, mut user_session, sync_token } =
+        versioned_state::from_versioned_json(&serialized_session)?;
+
+    // The session's secrets are stored in the keyring, unless that wasn't possible
+    // or the session was saved before we started using the keyring.
+    let secrets_in_session_file = !user_session.tokens.access_token.is_empty();
+    if !secrets_in_session_file {
+        load_secrets_from_keyring(&user_id)
+            .map_err(|e| anyhow!("Failed to load session secrets from the keyring: {e}"))?
+            .put_into(&mut client_session, &mut user_session);
+    }
+    let client_session_to_migrate = secrets_in_session_file.then(|| client_session.clone());
 
     let status_str = format!(
         "Loaded session file for {user_id}. Trying to connect to homeserver ({})...",
         client_session.homeserver,
     );
-    log!("{status_str}");
+    info!("{status_str}");
     Cx::post_action(LoginAction::Status {
         title: "Connecting to homeserver".into(),
         status: status_str,
     });
 
     // Build the client with the previous settings from the session.
-    let client = Client::builder()
+    mark_db_path_in_use(&client_session.db_path);
+    let mut builder = Client::builder()
         .server_name_or_homeserver_url(client_session.homeserver)
         .sqlite_store(client_session.db_path, Some(&client_session.passphrase))
         .sliding_sync_version_builder(VersionBuilder::DiscoverNative)
-        .handle_refresh_tokens()
-        .build()
-        .await?;
+        .handle_refresh_tokens();
+    let proxy = app_settings().proxy.url();
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy);
+    }
+    let client = builder.build().await?;
 
     let status_str = format!("Authenticating previous login session for {}...", user_session.meta.user_id);
-    log!("{status_str}");
+    info!("{status_str}");
     Cx::post_action(LoginAction::Status {
         title: "Authenticating session".into(),
         status: status_str,
@@ -139,30 +307,44 @@ pub async fn restore_session(
     client.restore_session(user_session).await?;
     save_latest_user_id(&user_id).await?;
 
+    // Re-save a session whose secrets were in plaintext, which moves them into the keyring.
+    if let Some(client_session) = client_session_to_migrate {
+        if let Err(e) = save_session(&client, client_session).await {
+            warn!("Failed to re-save session for {user_id}: {e:?}");
+        }
+    }
+
     Ok((client, sync_token))
 }
 
 
 /// Persist a logged-in client session to the filesystem for later use.
 ///
-/// TODO: This is not very secure, for simplicity. We should use robius-keychain
-///       or `keyring-rs` to storing secrets securely.
+/// The session's secrets (its access tokens and database passphrase) are stored
+/// in the platform keyring. If that isn't possible, e.g., on Android or on Linux systems
+/// without a Secret Service provider, they're stored in the session file instead.
 ///
 /// Note that we could also build the user session from the login response.
 pub async fn save_session(
     client: &Client,
-    client_session: ClientSessionPersisted,
+    mut client_session: ClientSessionPersisted,
 ) -> anyhow::Result<()> {
-    let user_session = client
+    let mut user_session = client
         .matrix_auth()
         .session()
         .ok_or_else(|| anyhow!("A logged-in client should have a session"))?;
 
     save_latest_user_id(&user_session.meta.user_id).await?;
 
+    let secrets = SessionSecrets::take_from(&mut client_session, &mut user_session);
+    if let Err(e) = save_secrets_to_keyring(&user_session.meta.user_id, &secrets) {
+        warn!("Failed to save session secrets to the keyring, saving them in the session file instead: {e}");
+        secrets.put_into(&mut client_session, &mut user_session);
+    }
+
     // Save that user's session.
     let session_file = session_file_path(&user_session.meta.user_id);
-    let serialized_session = serde_json::to_string(&FullSessionPersisted {
+    let serialized_session = versioned_state::to_versioned_json(&FullSessionPersisted {
         client_session,
         user_session,
         sync_token: None,
@@ -172,7 +354,7 @@ pub async fn save_session(
     }
     fs::write(&session_file, serialized_session).await?;
 
-    log!("Session persisted to: {}", session_file.display());
+    info!("Session persisted to: {}", session_file.display());
 
     // After logging in, you might want to verify this session with another one (see
     // the `emoji_verification` example), or bootstrap cross-signing if this is your
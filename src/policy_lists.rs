@@ -0,0 +1,476 @@
+//! Subscriptions of rooms to moderation policy lists, as defined by MSC2313.
+//!
+//! A policy list is a room whose `m.policy.rule.*` state events recommend banning
+//! certain users, rooms, or servers, matched by glob patterns.
+//! A room admin can subscribe a room that they moderate to one or more policy lists,
+//! after which Robrix automatically bans any member of that room who matches a ban rule,
+//! using the rule's list and reason as the ban reason such that it's visible in the timeline.
+//! Members are checked upon joining, upon subscribing, and whenever a list's rules change.
+//!
+//! Multiple moderators of a room may have subscribed it to the same list, so only one of them
+//! should issue each ban. Robrix elects the moderator with the highest power level (see
+//! [`ban_issuer_rank()`]); lower-ranked moderators wait before banning, and only do so
+//! if the user hasn't been banned in the meantime.
+//!
+//! Subscriptions are saved and kept in memory per user account, while the rules themselves
+//! are fetched from the homeserver upon login and then kept up to date by the background sync.
+
+use std::{cmp::Reverse, collections::{BTreeMap, BTreeSet}, sync::Mutex, time::Duration};
+
+use anyhow::{bail, Context};
+use makepad_widgets::Cx;
+use tracing::{error, info};
+use matrix_sdk::{
+    ruma::{
+        api::client::state::get_state_events,
+        events::{room::{member::{MembershipState, SyncRoomMemberEvent}, power_levels::RoomPowerLevels}, AnySyncStateEvent},
+        serde::Raw,
+        OwnedRoomId, OwnedRoomOrAliasId, OwnedUserId, RoomId, UserId,
+    },
+    Client, Room, RoomMemberships, RoomState,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::runtime::Handle;
+
+use crate::{
+    i18n::tr_args,
+    persistent_state,
+    shared::popup_list::enqueue_popup_notification,
+    sliding_sync::{current_user_id, UserPowerLevels},
+    versioned_state::{self, Migration, VersionedState},
+};
+
+/// The state event types of policy rules, including their older unstable equivalents,
+/// along with the kind of entity that each one applies to.
+const POLICY_RULE_EVENT_TYPES: &[(&str, PolicyEntityKind)] = &[
+    ("m.policy.rule.user", PolicyEntityKind::User),
+    ("m.policy.rule.room", PolicyEntityKind::Room),
+    ("m.policy.rule.server", PolicyEntityKind::Server),
+    ("m.room.rule.user", PolicyEntityKind::User),
+    ("m.room.rule.room", PolicyEntityKind::Room),
+    ("m.room.rule.server", PolicyEntityKind::Server),
+    ("org.matrix.mjolnir.rule.user", PolicyEntityKind::User),
+    ("org.matrix.mjolnir.rule.room", PolicyEntityKind::Room),
+    ("org.matrix.mjolnir.rule.server", PolicyEntityKind::Server),
+];
+
+/// The recommendations of a policy rule that mean its entities should be banned.
+const BAN_RECOMMENDATIONS: [&str; 2] = ["m.ban", "org.matrix.mjolnir.ban"];
+
+/// How long a moderator waits per higher-ranked moderator before issuing a ban themselves,
+/// giving the higher-ranked moderators' clients a chance to issue it first.
+const BAN_ISSUER_BACKOFF: Duration = Duration::from_secs(30);
+
+/// The kind of entity that a policy rule applies to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PolicyEntityKind {
+    User,
+    Room,
+    Server,
+}
+
+/// A rule recommending that matching users, rooms, or servers be banned.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolicyRule {
+    pub kind: PolicyEntityKind,
+    /// The glob pattern of the entities that this rule applies to, e.g., `@spam*:example.org`.
+    pub entity: String,
+    /// The human-readable reason for this rule.
+    pub reason: String,
+    /// The policy list room that this rule was declared in.
+    pub list_room_id: OwnedRoomId,
+}
+
+impl PolicyRule {
+    /// Parses a policy rule from the type and content of its state event.
+    ///
+    /// Returns `None` if the event is not a policy rule, if it recommends something other than a ban,
+    /// or if its content is empty, which is how a rule is removed from a list.
+    pub fn from_state_event(event_type: &str, content: &Value, list_room_id: OwnedRoomId) -> Option<Self> {
+        let kind = POLICY_RULE_EVENT_TYPES.iter()
+            .find(|(ty, _)| *ty == event_type)
+            .map(|(_, kind)| *kind)?;
+        let recommendation = content.get("recommendation")?.as_str()?;
+        if !BAN_RECOMMENDATIONS.contains(&recommendation) {
+            return None;
+        }
+        let entity = content.get("entity")?.as_str()?.to_owned();
+        let reason = content.get("reason")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_owned();
+        Some(Self { kind, entity, reason, list_room_id })
+    }
+
+    /// Returns whether this rule applies to the given user.
+    ///
+    /// Room rules never apply to users.
+    pub fn matches_user(&self, user_id: &UserId) -> bool {
+        match self.kind {
+            PolicyEntityKind::User => glob_matches(&self.entity, user_id.as_str()),
+            PolicyEntityKind::Server => glob_matches(&self.entity, user_id.server_name().as_str()),
+            PolicyEntityKind::Room => false,
+        }
+    }
+}
+
+/// Returns whether the given text matches the given glob pattern,
+/// in which `*` matches any number of characters and `?` matches exactly one character.
+fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+    let (mut p, mut t) = (0, 0);
+    // The position of the most recent `*` in the pattern, and the text position it was matched at.
+    let mut backtrack = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the most recent `*` match one more character and try again.
+                Some((star_p, star_t)) => {
+                    backtrack = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Actions sent from background tasks when a room's policy list subscriptions have changed.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum PolicyListAction {
+    Updated { room_id: OwnedRoomId },
+}
+
+/// The policy lists that each room is subscribed to, as saved to the filesystem.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedPolicyListSubscriptions {
+    /// A map from each subscribed room to the policy list rooms it is subscribed to.
+    subscriptions: BTreeMap<OwnedRoomId, BTreeSet<OwnedRoomId>>,
+}
+
+impl VersionedState for SavedPolicyListSubscriptions {
+    const CURRENT_VERSION: u64 = 0;
+    const MIGRATIONS: &'static [Migration] = &[];
+}
+
+/// The current policy list subscriptions and the ban rules of all subscribed lists.
+#[derive(Default)]
+struct PolicyListsState {
+    saved: SavedPolicyListSubscriptions,
+    /// A map from each policy list room to its rules, keyed by each rule's state event type and state key.
+    rules: BTreeMap<OwnedRoomId, BTreeMap<(String, String), PolicyRule>>,
+}
+
+/// The policy list state of each logged-in user account.
+static POLICY_LISTS: Mutex<BTreeMap<OwnedUserId, PolicyListsState>> = Mutex::new(BTreeMap::new());
+
+/// Runs the given function on the given user account's policy list state, initializing it if necessary.
+fn with_state<R>(account: &UserId, f: impl FnOnce(&mut PolicyListsState) -> R) -> R {
+    f(POLICY_LISTS.lock().unwrap().entry(account.to_owned()).or_default())
+}
+
+/// Returns the ID of the user account that the given client is logged in to.
+fn account_of(client: &Client) -> anyhow::Result<OwnedUserId> {
+    client.user_id().map(ToOwned::to_owned).context("Not logged in")
+}
+
+/// Returns the policy list rooms that the given room is subscribed to by the current user.
+pub fn subscribed_lists(room_id: &RoomId) -> Vec<OwnedRoomId> {
+    let Some(account) = current_user_id() else { return Vec::new() };
+    with_state(&account, |state| state.saved.subscriptions.get(room_id)
+        .map(|lists| lists.iter().cloned().collect())
+        .unwrap_or_default()
+    )
+}
+
+/// Returns the first ban rule that applies to the given user
+/// among the policy lists that the given account has subscribed the given room to.
+pub fn matching_ban_rule(account: &UserId, room_id: &RoomId, user_id: &UserId) -> Option<PolicyRule> {
+    with_state(account, |state| {
+        let lists = state.saved.subscriptions.get(room_id)?;
+        lists.iter()
+            .filter_map(|list_room_id| state.rules.get(list_room_id))
+            .flat_map(|rules| rules.values())
+            .find(|rule| rule.matches_user(user_id))
+            .cloned()
+    })
+}
+
+/// Loads the given user's saved policy list subscriptions and fetches the rules of all subscribed lists.
+///
+/// This should be called once upon login.
+pub async fn load_subscriptions(client: Client, user_id: OwnedUserId) {
+    let file = persistent_state::policy_lists_file_path(&user_id);
+    let saved = match std::fs::read_to_string(&file) {
+        Ok(serialized) => versioned_state::from_versioned_json(&serialized).unwrap_or_else(|e| {
+            error!("Failed to load policy list subscriptions for user {user_id}: {e}");
+            versioned_state::back_up_unreadable_file(&file);
+            SavedPolicyListSubscriptions::default()
+        }),
+        Err(_) => SavedPolicyListSubscriptions::default(),
+    };
+    let list_room_ids = saved.subscriptions.values().flatten().cloned().collect::<BTreeSet<_>>();
+    let subscribed_room_ids = saved.subscriptions.keys().cloned().collect::<Vec<_>>();
+    with_state(&user_id, |state| state.saved = saved);
+    for list_room_id in list_room_ids {
+        if let Err(e) = fetch_rules(&client, &list_room_id).await {
+            error!("Failed to fetch the rules of policy list {list_room_id}: {e:?}");
+        }
+    }
+    // The lists' rules may have changed while we were offline.
+    for room in subscribed_room_ids.iter().filter_map(|room_id| client.get_room(room_id)) {
+        Handle::current().spawn(async move { ban_matching_members(&room).await });
+    }
+}
+
+/// Saves the given user account's policy list subscriptions to the filesystem.
+fn save_subscriptions(account: &UserId) -> anyhow::Result<()> {
+    let file = persistent_state::policy_lists_file_path(account);
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let serialized = with_state(account, |state| versioned_state::to_versioned_json(&state.saved))?;
+    std::fs::write(&file, serialized)?;
+    Ok(())
+}
+
+/// Subscribes the given room to the given policy list, joining the list's room if necessary,
+/// and then bans the room's current members that match any of the list's ban rules.
+///
+/// Returns the ID of the policy list room.
+pub async fn subscribe(client: &Client, room: &Room, list: OwnedRoomOrAliasId) -> anyhow::Result<OwnedRoomId> {
+    let list_room_id = match OwnedRoomId::try_from(list.clone()) {
+        Ok(room_id) if client.get_room(&room_id).is_some_and(|r| r.state() == RoomState::Joined) => room_id,
+        _ => client.join_room_by_id_or_alias(&list, &[]).await?.room_id().to_owned(),
+    };
+    let account = account_of(client)?;
+    fetch_rules(client, &list_room_id).await?;
+    with_state(&account, |state| state.saved.subscriptions
+        .entry(room.room_id().to_owned())
+        .or_default()
+        .insert(list_room_id.clone())
+    );
+    save_subscriptions(&account)?;
+    info!("Subscribed room {} to policy list {list_room_id}", room.room_id());
+    Cx::post_action(PolicyListAction::Updated { room_id: room.room_id().to_owned() });
+
+    // Banning may be delayed while waiting for higher-ranked moderators, so don't wait for it.
+    let room = room.clone();
+    Handle::current().spawn(async move { ban_matching_members(&room).await });
+    Ok(list_room_id)
+}
+
+/// Unsubscribes the given room from the given policy list.
+pub async fn unsubscribe(client: &Client, room_id: OwnedRoomId, list: OwnedRoomOrAliasId) -> anyhow::Result<()> {
+    let list_room_id = match OwnedRoomId::try_from(list) {
+        Ok(room_id) => room_id,
+        Err(alias) => client.resolve_room_alias(&alias).await?.room_id,
+    };
+    let account = account_of(client)?;
+    let removed = with_state(&account, |state| state.saved.subscriptions
+        .get_mut(&room_id)
+        .is_some_and(|lists| lists.remove(&list_room_id))
+    );
+    if !removed {
+        bail!("Room {room_id} is not subscribed to policy list {list_room_id}");
+    }
+    save_subscriptions(&account)?;
+    info!("Unsubscribed room {room_id} from policy list {list_room_id}");
+    Cx::post_action(PolicyListAction::Updated { room_id });
+    Ok(())
+}
+
+/// Fetches all of the current rules of the given policy list from the homeserver.
+async fn fetch_rules(client: &Client, list_room_id: &RoomId) -> anyhow::Result<()> {
+    let account = account_of(client)?;
+    let request = get_state_events::v3::Request::new(list_room_id.to_owned());
+    let response = client.send(request, None).await?;
+    let mut rules = BTreeMap::new();
+    for raw_event in response.room_state {
+        let Ok(Some(event_type)) = raw_event.get_field::<String>("type") else { continue };
+        let Ok(Some(state_key)) = raw_event.get_field::<String>("state_key") else { continue };
+        let Ok(Some(content)) = raw_event.get_field::<Value>("content") else { continue };
+        if let Some(rule) = PolicyRule::from_state_event(&event_type, &content, list_room_id.to_owned()) {
+            rules.insert((event_type, state_key), rule);
+        }
+    }
+    info!("Fetched {} ban rules from policy list {list_room_id}", rules.len());
+    with_state(&account, |state| state.rules.insert(list_room_id.to_owned(), rules));
+    Ok(())
+}
+
+/// Bans the given room's joined members who match a ban rule of one of its policy lists.
+async fn ban_matching_members(room: &Room) {
+    match room.members(RoomMemberships::JOIN).await {
+        Ok(members) => ban_matching_users(room, members.iter().map(|m| m.user_id().to_owned())).await,
+        Err(e) => error!("Failed to get the members of room {} to check against its policy lists: {e:?}", room.room_id()),
+    }
+}
+
+/// Bans those of the given users who match a ban rule of one of the given room's policy lists,
+/// provided that the current user has the power to do so.
+///
+/// If other moderators rank higher as ban issuers (see [`ban_issuer_rank()`]),
+/// this first waits for them to issue the bans, and then only bans users who are still joined.
+async fn ban_matching_users(room: &Room, user_ids: impl IntoIterator<Item = OwnedUserId>) {
+    let Ok(own_user_id) = account_of(&room.client()) else { return };
+    let matches = user_ids.into_iter()
+        .filter(|user_id| *user_id != own_user_id)
+        .filter_map(|user_id| matching_ban_rule(&own_user_id, room.room_id(), &user_id).map(|rule| (user_id, rule)))
+        .collect::<Vec<_>>();
+    if matches.is_empty() { return }
+    let Ok(power_levels) = room.power_levels().await else { return };
+    if !UserPowerLevels::from(&power_levels, &own_user_id).can_ban() {
+        return;
+    }
+
+    let rank = ban_issuer_rank(room, &own_user_id, &power_levels).await;
+    if rank > 0 {
+        info!("Waiting for {rank} higher-ranked moderators to ban {} users from room {}", matches.len(), room.room_id());
+        tokio::time::sleep(BAN_ISSUER_BACKOFF * rank as u32).await;
+    }
+
+    for (user_id, rule) in matches {
+        // Only ban users who are still joined, since a join event may be an old one from the timeline,
+        // or another moderator may have banned the user in the meantime.
+        let is_joined = room.get_member_no_sync(&user_id).await.ok().flatten()
+            .is_some_and(|member| *member.membership() == MembershipState::Join);
+        if !is_joined {
+            continue;
+        }
+
+        let list_name = room.client().get_room(&rule.list_room_id)
+            .and_then(|list_room| list_room.cached_display_name())
+            .map(|dn| dn.to_string())
+            .unwrap_or_else(|| rule.list_room_id.to_string());
+        // The ban reason is visible to all room members, so it isn't translated.
+        let reason = format!("Banned by policy list {list_name} (rule {}): {}", rule.entity, rule.reason);
+        match room.ban_user(&user_id, Some(&reason)).await {
+            Ok(()) => {
+                info!("Banned {user_id} from room {} due to policy rule {rule:?}", room.room_id());
+                enqueue_popup_notification(tr_args(
+                    "policy.auto_banned",
+                    &[("user_id", user_id.as_str()), ("list", &list_name), ("reason", &rule.reason)],
+                ));
+            }
+            Err(e) => error!("Failed to ban {user_id} from room {} due to policy rule {rule:?}: {e:?}", room.room_id()),
+        }
+    }
+}
+
+/// Returns the given user's rank among the room's joined moderators who can ban,
+/// ordered by descending power level and then by user ID.
+///
+/// The moderator ranked `0` is the elected issuer of automatic bans. Since we can't know
+/// which other moderators have subscribed to the same policy list, a moderator ranked `n`
+/// waits `n` times [`BAN_ISSUER_BACKOFF`] before issuing a ban themselves.
+async fn ban_issuer_rank(room: &Room, own_user_id: &UserId, power_levels: &RoomPowerLevels) -> usize {
+    let Ok(members) = room.members(RoomMemberships::JOIN).await else { return 0 };
+    let mut moderators = members.iter()
+        .map(|member| member.user_id())
+        .filter(|user_id| UserPowerLevels::from(power_levels, user_id).can_ban())
+        .map(|user_id| (Reverse(power_levels.for_user(user_id)), user_id))
+        .collect::<Vec<_>>();
+    moderators.sort();
+    moderators.iter()
+        .position(|(_, user_id)| *user_id == own_user_id)
+        .unwrap_or(0)
+}
+
+/// Adds the event handlers that keep policy list rules up to date via the background sync,
+/// and that ban users who join a subscribed room while matching one of its ban rules.
+pub fn add_policy_list_event_handlers(client: &Client) {
+    client.add_event_handler(|raw_event: Raw<AnySyncStateEvent>, room: Room| async move {
+        let Ok(Some(event_type)) = raw_event.get_field::<String>("type") else { return };
+        if !POLICY_RULE_EVENT_TYPES.iter().any(|(ty, _)| *ty == event_type) {
+            return;
+        }
+        let Ok(account) = account_of(&room.client()) else { return };
+        let list_room_id = room.room_id().to_owned();
+        let is_subscribed_list = with_state(&account, |state| state.rules.contains_key(&list_room_id));
+        if !is_subscribed_list { return }
+        let Ok(Some(state_key)) = raw_event.get_field::<String>("state_key") else { return };
+        let content = raw_event.get_field::<Value>("content").ok().flatten().unwrap_or_default();
+        let rule = PolicyRule::from_state_event(&event_type, &content, list_room_id.clone());
+        info!("Policy list {list_room_id} updated rule {state_key}: {rule:?}");
+        let is_new_ban_rule = rule.is_some();
+        let subscribed_room_ids = with_state(&account, |state| {
+            if let Some(rules) = state.rules.get_mut(&list_room_id) {
+                match rule {
+                    Some(rule) => { rules.insert((event_type, state_key), rule); }
+                    // A rule with empty or unrecognized content has been removed.
+                    None => { rules.remove(&(event_type, state_key)); }
+                }
+            }
+            state.saved.subscriptions.iter()
+                .filter(|(_, lists)| lists.contains(&list_room_id))
+                .map(|(room_id, _)| room_id.clone())
+                .collect::<Vec<_>>()
+        });
+        // A new or changed ban rule may apply to the current members of the subscribed rooms.
+        // Banning may be delayed while waiting for higher-ranked moderators, so don't block the sync.
+        if is_new_ban_rule {
+            let client = room.client();
+            for subscribed_room in subscribed_room_ids.iter().filter_map(|room_id| client.get_room(room_id)) {
+                Handle::current().spawn(async move { ban_matching_members(&subscribed_room).await });
+            }
+        }
+    });
+
+    client.add_event_handler(|event: SyncRoomMemberEvent, room: Room| async move {
+        let SyncRoomMemberEvent::Original(event) = event else { return };
+        if event.content.membership == MembershipState::Join {
+            Handle::current().spawn(async move { ban_matching_users(&room, [event.state_key]).await });
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests_policy_lists {
+    use matrix_sdk::ruma::{owned_room_id, user_id};
+
+    use super::*;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("@spam*:example.org", "@spammer:example.org"));
+        assert!(glob_matches("*.example.org", "evil.example.org"));
+        assert!(glob_matches("@user?:example.org", "@user1:example.org"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("@user?:example.org", "@user:example.org"));
+        assert!(!glob_matches("*.example.org", "example.org"));
+    }
+
+    #[test]
+    fn test_policy_rule_from_state_event() {
+        let list_room_id = owned_room_id!("!list:example.org");
+        let content = serde_json::json!({
+            "entity": "*.spam.example",
+            "recommendation": "m.ban",
+            "reason": "spam",
+        });
+        let rule = PolicyRule::from_state_event("m.policy.rule.server", &content, list_room_id.clone()).unwrap();
+        assert_eq!(rule.kind, PolicyEntityKind::Server);
+        assert!(rule.matches_user(user_id!("@bot:evil.spam.example")));
+        assert!(!rule.matches_user(user_id!("@alice:example.org")));
+
+        // Removed rules have empty content.
+        let empty = serde_json::json!({});
+        assert_eq!(PolicyRule::from_state_event("m.policy.rule.user", &empty, list_room_id.clone()), None);
+        assert_eq!(PolicyRule::from_state_event("m.room.topic", &content, list_room_id), None);
+    }
+}
@@ -1,6 +1,7 @@
 use makepad_widgets::Cx;
 
 pub mod my_profile_screen;
+pub mod presence;
 pub mod user_profile;
 pub mod user_profile_cache;
 
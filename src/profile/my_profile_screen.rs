@@ -1,168 +1,233 @@
+//! A view of the current user's own profile, in which they can change
+//! their display name, presence and custom status message.
+
 use makepad_widgets::*;
 
+use crate::{
+    avatar_cache::{self, AvatarCacheEntry},
+    i18n::tr,
+    shared::{avatar::AvatarWidgetExt, popup_list::enqueue_popup_notification},
+    sliding_sync::{current_user_id, submit_async_request, MatrixRequest},
+    utils,
+};
+
+use super::{presence::{get_presence, presence_state_key, PresenceState}, user_profile::{AvatarState, UserProfile}, user_profile_cache::{self, enqueue_user_profile_update, UserProfileUpdate}};
+
 live_design! {
     use link::theme::*;
     use link::shaders::*;
     use link::widgets::*;
 
-    use crate::shared::helpers::FillerX;
-    use crate::shared::helpers::Divider;
-    use crate::shared::search_bar::SearchBar;
     use crate::shared::styles::*;
+    use crate::shared::avatar::Avatar;
+    use crate::shared::icon_button::*;
 
-    IMG_DEFAULT_AVATAR = dep("crate://self/resources/img/default_avatar.png")
-    IMG_QR = dep("crate://self/resources/img/qr_icon.png")
-
-    ActionIcon = <Label> {
-        width: Fit, height: Fit
-        text: ">"
-        draw_text: {
-            color: #b4,
-            text_style: <REGULAR_TEXT>{font_size: 16},
-        }
-    }
-
-    OptionsItem = <View> {
+    pub MyProfileScreen = {{MyProfileScreen}} {
         width: Fill, height: Fit
-        padding: {left: 10., top: 10., right: 10. bottom: 2.}, spacing: 8., flow: Down
-        show_bg: true
-        draw_bg: {
-            color: #fff
+        flow: Right, spacing: 20
+        align: {y: 0.5}
+
+        avatar = <Avatar> {
+            width: 80, height: 80
+            text_view = { text = { draw_text: {
+                text_style: { font_size: 28.0 }
+            }}}
         }
 
-        content = <View> {
-            width: Fill, height: 36.
-            padding: 0, align: {x: 0.0, y: 0.5}, spacing: 10., flow: Right
+        <View> {
+            width: Fill, height: Fit
+            flow: Down, spacing: 8
 
-            label = <Label> {
-                width: Fit, height: Fit
+            user_id_label = <Label> {
+                width: Fill, height: Fit
                 draw_text: {
-                    color: #000,
-                    text_style: <REGULAR_TEXT>{},
-                },
-            }
-
-            <FillerX> {}
-
-            item_data = <View> {
-                width: 0., height: 0.
+                    wrap: Word,
+                    color: (MESSAGE_NOTICE_TEXT_COLOR),
+                    text_style: <REGULAR_TEXT>{}
+                }
             }
 
-            action_icon = <ActionIcon> {}
-        }
-
-        divider = <Divider> {}
-    }
-
-    Options = <View> {
-        width: Fill, height: Fit
-        padding: 0, spacing: 0., flow: Down
-    }
-
-    pub MyProfileScreen = <View> {
-        width: Fill, height: Fill
-        flow: Down, spacing: 10.
-        show_bg: true,
-        draw_bg: {
-            color: #eee
-        }
-
-        <Options> {
-            <OptionsItem> {
-                content = {
-                    width: Fill, height: Fit
-                    label = {
-                        text: "Profile Photo"
-                    }
-                    item_data = <Image> {
-                        source: (IMG_DEFAULT_AVATAR),
-                        width: 60., height: 60.
+            <View> {
+                width: Fill, height: Fit
+                flow: Right, spacing: 10
+                align: {y: 0.5}
+
+                display_name_input = <RobrixTextInput> {
+                    width: 250, height: Fit
+                    empty_message: "Display name"
+                    padding: 8
+                    draw_bg: {
+                        border_width: 1.0
                     }
                 }
-            }
 
-            <OptionsItem> {
-                content = {
-                    label = {
-                        text: "Name"
+                save_display_name_button = <RobrixIconButton> {
+                    padding: {left: 15, right: 15, top: 10, bottom: 10}
+                    draw_bg: {
+                        color: (COLOR_SELECTED_PRIMARY)
                     }
-                    item_data = <Label> {
-                        width: Fit, height: Fit
-                        draw_text:{
-                            color: #6
-                            text_style: <REGULAR_TEXT>{},
-                        }
-                        text: "Profile"
+                    draw_text: {
+                        color: (COLOR_PRIMARY)
                     }
                 }
             }
 
-            <OptionsItem> {
-                content = {
-                    label = {
-                        text: "Tickle"
-                    }
+            <View> {
+                width: Fill, height: Fit
+                flow: Right, spacing: 10
+                align: {y: 0.5}
+
+                presence_dropdown = <DropDown> {
+                    width: 120, height: Fit
+                    // Must be kept in sync with `PRESENCE_CHOICES`.
+                    labels: ["Online", "Away", "Offline"]
                 }
-            }
 
-            <OptionsItem> {
-                content = {
-                    label = {
-                        text: "WeChat ID"
-                    }
-                    item_data = <Label> {
-                        width: Fit, height: Fit
-                        draw_text: {
-                            color: #6
-                            text_style: <REGULAR_TEXT>{},
-                        }
-                        text:"wxid_123n43kjl123hjg"
+                status_msg_input = <RobrixTextInput> {
+                    width: 250, height: Fit
+                    empty_message: ""
+                    padding: 8
+                    draw_bg: {
+                        border_width: 1.0
                     }
                 }
-            }
 
-            <OptionsItem> {
-                content = {
-                    label = {
-                        text: "My QR Code"
+                set_status_button = <RobrixIconButton> {
+                    padding: {left: 15, right: 15, top: 10, bottom: 10}
+                    draw_bg: {
+                        color: (COLOR_SELECTED_PRIMARY)
                     }
-                    item_data = <Image> {
-                        source: (IMG_QR),
-                        width: 20., height: 20.
+                    draw_text: {
+                        color: (COLOR_PRIMARY)
                     }
                 }
             }
+        }
+    }
+}
 
-            <OptionsItem> {
-                content = {
-                    label = {
-                        text: "More Info"
-                    }
-                }
-                divider = <View> {}
-            }
+/// The presence states that the user can choose from, in the order shown in the `presence_dropdown`.
+const PRESENCE_CHOICES: [PresenceState; 3] = [
+    PresenceState::Online,
+    PresenceState::Unavailable,
+    PresenceState::Offline,
+];
+
+/// Shows the current user's avatar, user ID and display name,
+/// and lets the user change their display name, presence and status message.
+#[derive(Live, LiveHook, Widget)]
+pub struct MyProfileScreen {
+    #[deref] view: View,
+    /// Whether the display name input has been filled in with the user's current display name.
+    ///
+    /// This is only done once, such that we don't overwrite any text the user is editing.
+    #[rust] display_name_populated: bool,
+    /// Whether the presence dropdown and status message input have been filled in
+    /// with the user's current presence, which is also only done once.
+    #[rust] status_populated: bool,
+}
+
+impl Widget for MyProfileScreen {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if let Event::Signal = event {
+            // Our own profile or avatar may have just been fetched.
+            user_profile_cache::process_user_profile_updates(cx);
+            avatar_cache::process_avatar_updates(cx);
+            self.redraw(cx);
         }
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
 
-        <Options> {
-            <OptionsItem> {
-                content = {
-                    label = {
-                        text: "Ringtone for Incoming Calls"
-                    }
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.button(id!(save_display_name_button)).set_text(cx, tr("profile.save_display_name"));
+        self.button(id!(set_status_button)).set_text(cx, tr("presence.set_status"));
+        self.drop_down(id!(presence_dropdown)).set_labels(
+            cx,
+            PRESENCE_CHOICES.iter().map(|state| tr(presence_state_key(state)).to_string()).collect(),
+        );
+        self.text_input(id!(status_msg_input))
+            .apply_over(cx, live!{ empty_message: (tr("presence.status_placeholder")) });
+
+        if !self.status_populated {
+            if let Some(presence) = current_user_id().and_then(|user_id| get_presence(&user_id)) {
+                self.status_populated = true;
+                if let Some(index) = PRESENCE_CHOICES.iter().position(|p| *p == presence.state) {
+                    self.drop_down(id!(presence_dropdown)).set_selected_item(cx, index);
                 }
-                divider = <View> {}
+                self.text_input(id!(status_msg_input))
+                    .set_text(cx, presence.status_msg.as_deref().unwrap_or_default());
             }
         }
 
-        <Options> {
-            <OptionsItem> {
-                content = {
-                    label = {
-                        text: "WeBeans"
-                    }
-                }
-                divider = <View> {}
+        if let Some(profile) = own_profile(cx) {
+            self.label(id!(user_id_label)).set_text(cx, profile.user_id.as_str());
+            let avatar_data = match &profile.avatar_state {
+                AvatarState::Loaded(data) => Some(data.clone()),
+                AvatarState::Known(Some(uri)) => match avatar_cache::get_or_fetch_avatar(cx, uri.clone()) {
+                    AvatarCacheEntry::Loaded(data) => Some(data),
+                    _ => None,
+                },
+                _ => None,
+            };
+            let avatar_ref = self.avatar(id!(avatar));
+            avatar_data
+                .and_then(|data| avatar_ref.show_image(cx, None, |cx, img| utils::load_png_or_jpg(&img, cx, &data)).ok())
+                .unwrap_or_else(|| avatar_ref.show_text(cx, None, profile.displayable_name()));
+
+            if !self.display_name_populated {
+                self.display_name_populated = true;
+                self.text_input(id!(display_name_input))
+                    .set_text(cx, profile.username.as_deref().unwrap_or_default());
             }
         }
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for MyProfileScreen {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        let status_msg_input = self.text_input(id!(status_msg_input));
+        if self.button(id!(set_status_button)).clicked(actions)
+            || status_msg_input.returned(actions).is_some()
+        {
+            let presence = PRESENCE_CHOICES
+                .get(self.drop_down(id!(presence_dropdown)).selected_item())
+                .cloned()
+                .unwrap_or(PresenceState::Online);
+            let status_msg = status_msg_input.text().trim().to_string();
+            submit_async_request(MatrixRequest::SetPresence {
+                presence,
+                status_msg: (!status_msg.is_empty()).then_some(status_msg),
+            });
+        }
+
+        let display_name_input = self.text_input(id!(display_name_input));
+        let save_clicked = self.button(id!(save_display_name_button)).clicked(actions);
+        if !save_clicked && display_name_input.returned(actions).is_none() {
+            return;
+        }
+        let new_display_name = display_name_input.text().trim().to_string();
+        let Some(mut profile) = own_profile(cx) else {
+            enqueue_popup_notification(tr("profile.not_loaded").to_string());
+            return;
+        };
+        let new_display_name = (!new_display_name.is_empty()).then_some(new_display_name);
+        if profile.username == new_display_name {
+            return;
+        }
+
+        // Optimistically update our own profile in the cache, such that the new display name
+        // is shown immediately. If the request fails, our profile will be re-fetched.
+        profile.username = new_display_name.clone();
+        enqueue_user_profile_update(UserProfileUpdate::UserProfileOnly(profile));
+        submit_async_request(MatrixRequest::SetDisplayName { new_display_name });
+        self.redraw(cx);
     }
 }
+
+/// Returns the current user's cached profile, fetching it from the server if necessary.
+fn own_profile(cx: &mut Cx) -> Option<UserProfile> {
+    let user_id = current_user_id()?;
+    user_profile_cache::with_user_profile(cx, user_id, true, |profile, _rooms| profile.clone())
+}
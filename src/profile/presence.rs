@@ -0,0 +1,130 @@
+//! A cache of users' presence (online status), which is fetched from the homeserver on demand.
+//!
+//! Sliding sync doesn't deliver presence updates, so a user's presence is fetched
+//! when a room or profile showing that user is opened (see [`fetch_presence()`]),
+//! and re-fetched upon a later opening once it has become stale.
+//! Widgets only read the cache while drawing, via [`get_presence()`].
+
+use std::{collections::BTreeMap, sync::Mutex, time::{Duration, Instant}};
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::{OwnedUserId, UserId};
+
+use crate::{i18n::tr, sliding_sync::{submit_async_request, MatrixRequest}};
+
+pub use matrix_sdk::ruma::presence::PresenceState;
+
+/// How long a user's fetched presence is considered to be up to date.
+const PRESENCE_STALE_AFTER: Duration = Duration::from_secs(60);
+
+const COLOR_PRESENCE_ONLINE: Vec4 = vec4(0.18, 0.8, 0.44, 1.0);
+const COLOR_PRESENCE_UNAVAILABLE: Vec4 = vec4(0.96, 0.65, 0.14, 1.0);
+const COLOR_PRESENCE_OFFLINE: Vec4 = vec4(0.62, 0.62, 0.62, 1.0);
+
+/// A user's presence and custom status message.
+#[derive(Clone, Debug)]
+pub struct UserPresence {
+    pub state: PresenceState,
+    pub status_msg: Option<String>,
+}
+
+impl UserPresence {
+    /// Returns a short user-facing description of this presence, including the status message.
+    pub fn description(&self) -> String {
+        let state = tr(presence_state_key(&self.state));
+        match self.status_msg.as_deref().filter(|msg| !msg.is_empty()) {
+            Some(status_msg) => format!("{state} — {status_msg}"),
+            None => state.to_string(),
+        }
+    }
+
+    /// Returns the color of the dot used to represent this presence on top of an avatar.
+    pub fn color(&self) -> Vec4 {
+        match self.state {
+            PresenceState::Online => COLOR_PRESENCE_ONLINE,
+            PresenceState::Unavailable => COLOR_PRESENCE_UNAVAILABLE,
+            _ => COLOR_PRESENCE_OFFLINE,
+        }
+    }
+}
+
+/// Returns the i18n key of the user-facing name of the given presence state.
+pub fn presence_state_key(state: &PresenceState) -> &'static str {
+    match state {
+        PresenceState::Online => "presence.online",
+        PresenceState::Unavailable => "presence.unavailable",
+        _ => "presence.offline",
+    }
+}
+
+enum PresenceCacheEntry {
+    /// A request was issued at the given time, and we're waiting for it to complete.
+    Requested(Instant),
+    /// The presence was fetched at the given time.
+    Loaded(UserPresence, Instant),
+}
+
+static PRESENCE_CACHE: Mutex<BTreeMap<OwnedUserId, PresenceCacheEntry>> = Mutex::new(BTreeMap::new());
+
+/// Returns the cached presence of the given user, if any.
+///
+/// This never fetches the presence, so it is cheap enough to call while drawing.
+pub fn get_presence(user_id: &UserId) -> Option<UserPresence> {
+    match PRESENCE_CACHE.lock().unwrap().get(user_id) {
+        Some(PresenceCacheEntry::Loaded(presence, _)) => Some(presence.clone()),
+        _ => None,
+    }
+}
+
+/// Submits a request to fetch the given user's presence if it isn't cached or is stale,
+/// upon completion of which a [`PresenceAction::Updated`] is posted.
+///
+/// This should be called when a room or profile that shows the user's presence is opened.
+pub fn fetch_presence(user_id: &UserId) {
+    let mut cache = PRESENCE_CACHE.lock().unwrap();
+    let (presence, is_stale) = match cache.get(user_id) {
+        Some(PresenceCacheEntry::Loaded(presence, fetched_at)) => (Some(presence.clone()), fetched_at.elapsed() >= PRESENCE_STALE_AFTER),
+        Some(PresenceCacheEntry::Requested(requested_at)) => (None, requested_at.elapsed() >= PRESENCE_STALE_AFTER),
+        None => (None, true),
+    };
+    if is_stale {
+        cache.insert(user_id.to_owned(), match presence {
+            // Keep showing the stale presence until the new one arrives.
+            Some(presence) => PresenceCacheEntry::Loaded(presence, Instant::now()),
+            None => PresenceCacheEntry::Requested(Instant::now()),
+        });
+        submit_async_request(MatrixRequest::GetPresence { user_id: user_id.to_owned() });
+    }
+}
+
+/// Inserts the given user's newly-fetched presence into the cache
+/// and notifies the UI that it should be redrawn.
+pub fn insert_presence(user_id: OwnedUserId, presence: UserPresence) {
+    PRESENCE_CACHE.lock().unwrap().insert(
+        user_id.clone(),
+        PresenceCacheEntry::Loaded(presence, Instant::now()),
+    );
+    Cx::post_action(PresenceAction::Updated(user_id));
+}
+
+/// Actions related to users' presence.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum PresenceAction {
+    /// The presence of the given user has been fetched,
+    /// so any widgets showing that user's presence should be redrawn.
+    Updated(OwnedUserId),
+}
+
+/// Shows the given presence as a colored dot in the given view, or hides the view if `None`.
+///
+/// The view is expected to be a `PresenceDot`.
+pub fn show_presence_dot(cx: &mut Cx, dot: &ViewRef, presence: Option<&UserPresence>) {
+    dot.set_visible(cx, presence.is_some());
+    if let Some(presence) = presence {
+        dot.apply_over(cx, live! {
+            draw_bg: { dot_color: (presence.color()) }
+        });
+    }
+}
@@ -1,11 +1,14 @@
-use std::{borrow::Cow, ops::{Deref, DerefMut}, sync::Arc};
+use std::{borrow::Cow, ops::{Deref, DerefMut}, sync::Arc, time::{Duration, SystemTime}};
 use makepad_widgets::*;
-use matrix_sdk::{room::{RoomMember, RoomMemberRole}, ruma::{events::room::member::MembershipState, OwnedMxcUri, OwnedRoomId, OwnedUserId}};
+use tracing::info;
+use matrix_sdk::{room::{RoomMember, RoomMemberRole}, ruma::{events::room::member::MembershipState, MilliSecondsSinceUnixEpoch, OwnedMxcUri, OwnedRoomId, OwnedUserId, UInt}};
 use crate::{
-    avatar_cache::{self, AvatarCacheEntry}, shared::avatar::AvatarWidgetExt, sliding_sync::{current_user_id, is_user_ignored, submit_async_request, MatrixRequest}, utils
+    avatar_cache::{self, AvatarCacheEntry}, bridges, home::rooms_list::RoomsListAction, i18n::{tr, tr_args},
+    settings::app_settings::{app_settings, update_app_settings, DEFAULT_USER_PROFILE_PANE_WIDTH}, shared::avatar::AvatarWidgetExt, sliding_sync::{current_user_id, is_user_ignored, submit_async_request, MatrixRequest, RedactUserMessagesAction, MAX_REDACT_SCANNED_EVENTS}, utils,
+    verification::{UserDevice, UserDevicesAction, VerificationAction},
 };
 
-use super::user_profile_cache::{self, get_user_profile_and_room_member};
+use super::{presence::{fetch_presence, get_presence, PresenceAction}, user_profile_cache::{self, get_shared_rooms, get_user_profile_and_room_member, SharedRoom}};
 
 /// The currently-known state of a user's avatar.
 #[derive(Clone, Debug)]
@@ -116,6 +119,42 @@ live_design! {
 
     ICON_DOUBLE_CHAT = dep("crate://self/resources/icons/double_chat.svg")
 
+    UserDevicesList = {{UserDevicesList}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 5
+        margin: { left: 7 }
+        item: <View> {
+            width: Fill, height: Fit
+            flow: Right, spacing: 5
+            align: {y: 0.5}
+            device_label = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    color: (MESSAGE_TEXT_COLOR),
+                    text_style: <MESSAGE_TEXT_STYLE>{ font_size: 10 },
+                }
+            }
+            verify_button = <RobrixIconButton> {
+                padding: {left: 10, right: 10, top: 6, bottom: 6}
+                text: "Verify"
+            }
+        }
+    }
+
+    SharedRoomsList = {{SharedRoomsList}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 5
+        margin: { left: 7 }
+        item: <RobrixIconButton> {
+            width: Fill, height: Fit
+            padding: {left: 10, right: 10, top: 8, bottom: 8}
+            draw_text: {
+                wrap: Ellipsis,
+            }
+        }
+    }
+
     UserProfileView = <ScrollXYView> {
         width: Fill,
         height: Fill,
@@ -163,6 +202,26 @@ live_design! {
                 }
                 text: "User ID"
             }
+
+            bridged_label = <Label> {
+                width: Fit, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    color: (MESSAGE_TEXT_COLOR),
+                    text_style: <MESSAGE_TEXT_STYLE>{ font_size: 10 },
+                }
+                text: ""
+            }
+
+            presence_label = <Label> {
+                width: Fit, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    color: (MESSAGE_TEXT_COLOR),
+                    text_style: <MESSAGE_TEXT_STYLE>{ font_size: 10 },
+                }
+                text: ""
+            }
         }
 
         <LineH> { padding: 15 }
@@ -210,6 +269,48 @@ live_design! {
 
         <LineH> { padding: 15 }
 
+        shared_rooms = <View> {
+            width: Fill, height: Fit
+            flow: Down,
+            spacing: 10,
+            padding: {left: 10, right: 10}
+
+            shared_rooms_title_label = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <USERNAME_TEXT_STYLE>{ font_size: 11.5 },
+                    color: #000
+                }
+                text: "Rooms in common"
+            }
+
+            shared_rooms_list = <SharedRoomsList> {}
+        }
+
+        shared_rooms_divider = <LineH> { padding: 15 }
+
+        devices = <View> {
+            width: Fill, height: Fit
+            flow: Down,
+            spacing: 10,
+            padding: {left: 10, right: 10}
+
+            devices_title_label = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <USERNAME_TEXT_STYLE>{ font_size: 11.5 },
+                    color: #000
+                }
+                text: "Devices"
+            }
+
+            user_devices_list = <UserDevicesList> {}
+        }
+
+        <LineH> { padding: 15 }
+
         actions = <View> {
             width: Fill, height: Fit
             flow: Down,
@@ -226,8 +327,6 @@ live_design! {
             }
 
             direct_message_button = <RobrixIconButton> {
-                // TODO: support this button. Once this is implemented, uncomment the line in draw_walk()
-                enabled: false,
                 draw_icon: {
                     svg_file: (ICON_DOUBLE_CHAT)
                 }
@@ -268,6 +367,51 @@ live_design! {
                     color: (COLOR_DANGER_RED),
                 }
             }
+
+            <View> {
+                width: Fill, height: Fit
+                flow: Right, spacing: 7
+                align: {y: 0.5}
+
+                remove_messages_timeframe = <DropDown> {
+                    width: 95, height: Fit
+                    // Must be kept in sync with `REMOVE_MESSAGES_TIMEFRAMES`.
+                    labels: ["Last hour", "Last day", "Last week", "Last month"]
+                }
+
+                remove_messages_button = <RobrixIconButton> {
+                    width: Fill
+                    draw_icon: {
+                        svg_file: (ICON_TRASH)
+                        color: (COLOR_DANGER_RED),
+                    }
+                    icon_walk: {width: 16, height: 16, margin: {left: -2, right: -1} }
+
+                    draw_bg: {
+                        border_color: (COLOR_DANGER_RED),
+                        color: #fff0f0
+                    }
+                    text: "Remove Recent Messages"
+                    draw_text:{
+                        color: (COLOR_DANGER_RED),
+                    }
+                }
+            }
+
+            remove_messages_reason = <RobrixTextInput> {
+                width: Fill, height: Fit
+                empty_message: "Reason (optional)"
+            }
+
+            remove_messages_progress = <Label> {
+                visible: false,
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    text_style: <REGULAR_TEXT>{ font_size: 10 },
+                    color: (MESSAGE_TEXT_COLOR)
+                }
+            }
         }
     }
 
@@ -298,6 +442,16 @@ live_design! {
 
             user_profile_view = <UserProfileView> { }
 
+            // A thin handle along the left edge that can be dragged to resize this pane.
+            resize_handle = <View> {
+                width: 6, height: Fill
+                cursor: ColResize
+                show_bg: true
+                draw_bg: {
+                    color: #0000
+                }
+            }
+
             // The "X" close button on the top left
             close_button = <RobrixIconButton> {
                 width: Fit,
@@ -337,6 +491,26 @@ live_design! {
 }
 
 
+/// The minimum width that the user profile pane can be resized to.
+const MIN_PANE_WIDTH: f64 = 250.0;
+/// The maximum width that the user profile pane can be resized to.
+const MAX_PANE_WIDTH: f64 = 700.0;
+
+/// The reason given for removing a user's recent messages if the moderator didn't enter one.
+///
+/// This is visible to all room members, so it isn't translated into the moderator's UI language.
+const DEFAULT_REMOVE_MESSAGES_REASON: &str = "Removed by a moderator along with this user's other recent messages";
+
+/// The timeframes that can be chosen when removing a user's recent messages.
+///
+/// Must be kept in sync with the labels of the `remove_messages_timeframe` dropdown.
+const REMOVE_MESSAGES_TIMEFRAMES: [Duration; 4] = [
+    Duration::from_secs(60 * 60),
+    Duration::from_secs(24 * 60 * 60),
+    Duration::from_secs(7 * 24 * 60 * 60),
+    Duration::from_secs(30 * 24 * 60 * 60),
+];
+
 #[derive(Clone, DefaultNone, Debug)]
 pub enum ShowUserProfileAction {
     ShowUserProfile(UserProfileAndRoomId),
@@ -403,6 +577,10 @@ pub struct UserProfileSlidingPane {
 
     #[rust] info: Option<UserProfilePaneInfo>,
     #[rust] is_animating_out: bool,
+    /// The room and user whose recent messages are currently being removed, if any.
+    #[rust] removing_messages_of: Option<(OwnedRoomId, OwnedUserId)>,
+    /// The width of this pane, which the user can change by dragging its resize handle.
+    #[rust(DEFAULT_USER_PROFILE_PANE_WIDTH)] width: f64,
 }
 
 impl Widget for UserProfileSlidingPane {
@@ -433,6 +611,20 @@ impl Widget for UserProfileSlidingPane {
             }
         }
 
+        // Resize this pane when its resize handle is dragged,
+        // and remember the new width once the user lets go of it.
+        match event.hits(cx, self.view(id!(resize_handle)).area()) {
+            Hit::FingerMove(fe) => {
+                let right_edge = self.view(id!(main_content)).area().rect(cx).pos.x + self.width;
+                self.set_width(cx, right_edge - fe.abs.x);
+            }
+            Hit::FingerUp(_) => {
+                let width = self.width;
+                update_app_settings(|s| s.user_profile_pane_width = width);
+            }
+            _ => { }
+        }
+
         let area = self.view.area();
 
         // Close the pane if:
@@ -440,9 +632,11 @@ impl Widget for UserProfileSlidingPane {
         // 2. The back navigational gesture/action occurs (e.g., Back on Android),
         // 3. The escape key is pressed if this pane has key focus,
         // 4. The back mouse button is clicked within this view,
-        // 5. The user clicks/touches outside the main_content view area.
+        // 5. The user clicks/touches outside the main_content view area,
+        // 6. The user selects one of the rooms they share with this user.
         let close_pane = match event {
-            Event::Actions(actions) => self.button(id!(close_button)).clicked(actions),  // 1
+            Event::Actions(actions) => self.button(id!(close_button)).clicked(actions)  // 1
+                || self.shared_rooms_list(id!(shared_rooms_list)).selected(actions),     // 6
             Event::BackPressed => true,                                                  // 2
             _ => false,
         } || match event.hits_with_capture_overload(cx, area, true) {
@@ -504,6 +698,10 @@ impl Widget for UserProfileSlidingPane {
                     }
                     redraw_this_pane = true;
                 }
+                if let Some(shared_rooms) = get_shared_rooms(cx, &our_info.user_id) {
+                    self.view.shared_rooms_list(id!(shared_rooms_list)).set_rooms(cx, shared_rooms);
+                    redraw_this_pane = true;
+                }
             }
             if redraw_this_pane {
                 self.redraw(cx);
@@ -513,14 +711,69 @@ impl Widget for UserProfileSlidingPane {
         let Some(info) = self.info.as_ref() else { return };
 
         if let Event::Actions(actions) = event {
+            for action in actions {
+                // Show this user's devices once they've been fetched.
+                if let Some(UserDevicesAction { user_id, devices }) = action.downcast_ref() {
+                    if *user_id == info.user_id {
+                        self.user_devices_list(id!(user_devices_list)).set_devices(cx, user_id.clone(), devices.clone());
+                    }
+                }
+                // Show this user's presence once it has been fetched.
+                if let Some(PresenceAction::Updated(user_id)) = action.downcast_ref() {
+                    if *user_id == info.user_id {
+                        self.view.redraw(cx);
+                    }
+                }
+                // Re-fetch this user's devices after a verification, as one of them may now be verified.
+                if let Some(VerificationAction::RequestCompleted) = action.downcast_ref() {
+                    submit_async_request(MatrixRequest::GetUserDevices { user_id: info.user_id.clone() });
+                }
+                // Show the progress of removing this user's recent messages.
+                match action.downcast_ref() {
+                    Some(RedactUserMessagesAction::Progress { room_id, user_id, redacted, found })
+                        if *room_id == info.room_id && *user_id == info.user_id =>
+                    {
+                        self.label(id!(remove_messages_progress)).set_text(cx, &tr_args(
+                            "profile.remove_messages_progress",
+                            &[("redacted", &redacted.to_string()), ("found", &found.to_string())],
+                        ));
+                        self.view.redraw(cx);
+                    }
+                    Some(RedactUserMessagesAction::Finished { room_id, user_id, redacted, failed, reached_scan_limit }) => {
+                        if self.removing_messages_of.as_ref().is_some_and(|(r, u)| r == room_id && u == user_id) {
+                            self.removing_messages_of = None;
+                        }
+                        if *room_id == info.room_id && *user_id == info.user_id {
+                            let mut text = tr_args(
+                                "profile.remove_messages_finished",
+                                &[("redacted", &redacted.to_string()), ("failed", &failed.to_string())],
+                            );
+                            if *reached_scan_limit {
+                                text.push(' ');
+                                text.push_str(&tr_args(
+                                    "profile.remove_messages_scan_limit",
+                                    &[("count", &MAX_REDACT_SCANNED_EVENTS.to_string())],
+                                ));
+                            }
+                            self.label(id!(remove_messages_progress)).set_text(cx, &text);
+                        }
+                        self.view.redraw(cx);
+                    }
+                    _ => { }
+                }
+            }
 
-            // TODO: handle actions for the `direct_message_button`
+            if self.button(id!(direct_message_button)).clicked(actions) {
+                submit_async_request(MatrixRequest::OpenOrCreateDirectMessage {
+                    user_id: info.user_id.clone(),
+                });
+            }
 
             if self.button(id!(copy_link_to_user_button)).clicked(actions) {
                 let matrix_to_uri = info.user_id.matrix_to_uri().to_string();
                 cx.copy_to_clipboard(&matrix_to_uri);
                 // TODO: show a toast message instead of a log message
-                log!("Copied user ID to clipboard: {matrix_to_uri}");
+                info!("Copied user ID to clipboard: {matrix_to_uri}");
             }
 
             // TODO: implement the third button: `jump_to_read_receipt_button`,
@@ -535,11 +788,39 @@ impl Widget for UserProfileSlidingPane {
                         room_id: info.room_id.clone(),
                         room_member: room_member.clone(),
                     });
-                    log!("Submitting request to {}ignore user {}.",
+                    info!("Submitting request to {}ignore user {}.",
                         if room_member.is_ignored() { "un" } else { "" },
                         info.user_id,
                     );
                 }
+
+                if self.button(id!(remove_messages_button)).clicked(actions) && self.removing_messages_of.is_none() {
+                    let timeframe = REMOVE_MESSAGES_TIMEFRAMES
+                        .get(self.drop_down(id!(remove_messages_timeframe)).selected_item())
+                        .copied()
+                        .unwrap_or(REMOVE_MESSAGES_TIMEFRAMES[0]);
+                    let since = SystemTime::now().checked_sub(timeframe)
+                        .and_then(MilliSecondsSinceUnixEpoch::from_system_time)
+                        .unwrap_or(MilliSecondsSinceUnixEpoch(UInt::MIN));
+                    let reason_input = self.text_input(id!(remove_messages_reason));
+                    let reason = reason_input.text().trim().to_owned();
+                    reason_input.set_text(cx, "");
+                    submit_async_request(MatrixRequest::RedactUserMessages {
+                        room_id: info.room_id.clone(),
+                        user_id: info.user_id.clone(),
+                        since,
+                        reason: Some(if reason.is_empty() { DEFAULT_REMOVE_MESSAGES_REASON.to_owned() } else { reason }),
+                    });
+                    info!("Submitting request to remove recent messages from user {} in room {}.", info.user_id, info.room_id);
+                    let progress_label = self.label(id!(remove_messages_progress));
+                    progress_label.set_text(cx, &tr_args(
+                        "profile.remove_messages_progress",
+                        &[("redacted", "0"), ("found", "0")],
+                    ));
+                    progress_label.set_visible(cx, true);
+                    self.removing_messages_of = Some((info.room_id.clone(), info.user_id.clone()));
+                    self.view.redraw(cx);
+                }
             }
         }
     }
@@ -555,6 +836,22 @@ impl Widget for UserProfileSlidingPane {
         self.label(id!(user_name)).set_text(cx, info.displayable_name());
         self.label(id!(user_id)).set_text(cx, info.user_id.as_str());
 
+        // Show which platform the user is bridged from, if any.
+        let bridged_platform = bridges::bridged_platform(&info.user_id, Some(&info.room_id));
+        let bridged_label = self.label(id!(bridged_label));
+        bridged_label.set_visible(cx, bridged_platform.is_some());
+        if let Some(platform) = bridged_platform {
+            bridged_label.set_text(cx, &tr_args("profile.bridged_from", &[("platform", &platform)]));
+        }
+
+        // Show the user's presence and status message, if known.
+        let presence = get_presence(&info.user_id);
+        let presence_label = self.label(id!(presence_label));
+        presence_label.set_visible(cx, presence.is_some());
+        if let Some(presence) = presence {
+            presence_label.set_text(cx, &presence.description());
+        }
+
         // Set the avatar image, using the user name as a fallback.
         let avatar_ref = self.avatar(id!(avatar));
         info.avatar_state
@@ -579,8 +876,19 @@ impl Widget for UserProfileSlidingPane {
             .map(|rm| rm.is_account_user())
             .unwrap_or_else(|| current_user_id().is_some_and(|uid| uid == info.user_id));
 
-        // TODO: uncomment the line below once the `direct_message_button` logic is implemented.
-        // self.button(id!(direct_message_button)).set_enabled(!is_pane_showing_current_account);
+        // The rooms in common are only shown for other users, since the current user
+        // trivially shares all of their rooms with themselves.
+        let shared_rooms_count = self.shared_rooms_list(id!(shared_rooms_list)).num_rooms();
+        self.view(id!(shared_rooms)).set_visible(cx, !is_pane_showing_current_account);
+        self.view(id!(shared_rooms_divider)).set_visible(cx, !is_pane_showing_current_account);
+        self.label(id!(shared_rooms_title_label)).set_text(
+            cx,
+            &tr_args("profile.shared_rooms", &[("count", &shared_rooms_count.to_string())]),
+        );
+
+        self.label(id!(devices_title_label)).set_text(cx, tr("profile.devices"));
+
+        self.button(id!(direct_message_button)).set_enabled(cx, !is_pane_showing_current_account);
 
         let ignore_user_button = self.button(id!(ignore_user_button));
         ignore_user_button.set_enabled(cx, !is_pane_showing_current_account && info.room_member.is_some());
@@ -593,6 +901,19 @@ impl Widget for UserProfileSlidingPane {
             if is_ignored { "Unignore (Unblock) User" } else { "Ignore (Block) User" }
         );
 
+        // Removing a user's recent messages also requires room membership info,
+        // and only one such removal can be ongoing at a time.
+        let remove_messages_button = self.button(id!(remove_messages_button));
+        remove_messages_button.set_enabled(
+            cx,
+            !is_pane_showing_current_account
+                && info.room_member.is_some()
+                && self.removing_messages_of.is_none(),
+        );
+        remove_messages_button.set_text(cx, tr("profile.remove_messages"));
+        self.text_input(id!(remove_messages_reason))
+            .apply_over(cx, live!{ empty_message: (tr("profile.remove_messages_reason")) });
+
         self.view.draw_walk(cx, scope, walk)
     }
 }
@@ -618,7 +939,7 @@ impl UserProfileSlidingPane {
                 &info.room_id,
                 true,
             ) {
-                log!("Found user {} room member info in cache", info.user_id);
+                info!("Found user {} room member info in cache", info.user_id);
                 // Update avatar state, preferring that of the room member info.
                 if let Some(uri) = room_member.avatar_url() {
                     info.avatar_state = AvatarState::Known(Some(uri.to_owned()));
@@ -645,10 +966,53 @@ impl UserProfileSlidingPane {
                 info.avatar_state = AvatarState::Loaded(data);
             }
         }
+
+        // Only fetch the shared rooms if they aren't already known,
+        // since doing so requires checking the members of every joined room.
+        let shared_rooms_list = self.view.shared_rooms_list(id!(shared_rooms_list));
+        match get_shared_rooms(_cx, &info.user_id) {
+            Some(shared_rooms) => shared_rooms_list.set_rooms(_cx, shared_rooms),
+            None => {
+                shared_rooms_list.set_rooms(_cx, Vec::new());
+                submit_async_request(MatrixRequest::GetSharedRooms { user_id: info.user_id.clone() });
+            }
+        }
+
+        // Only show the progress of removing recent messages for the user that is being removed.
+        let is_removing_messages = self.removing_messages_of.as_ref()
+            .is_some_and(|(room_id, user_id)| *room_id == info.room_id && *user_id == info.user_id);
+        let progress_label = self.view.label(id!(remove_messages_progress));
+        progress_label.set_visible(_cx, is_removing_messages);
+        if !is_removing_messages {
+            progress_label.set_text(_cx, "");
+        }
+
+        fetch_presence(&info.user_id);
+
+        // Clear the previously-shown user's devices, and fetch this user's devices.
+        self.view.user_devices_list(id!(user_devices_list)).set_devices(_cx, info.user_id.clone(), Vec::new());
+        submit_async_request(MatrixRequest::GetUserDevices { user_id: info.user_id.clone() });
+
         self.info = Some(info);
     }
 
+    /// Sets the width of this pane, clamped to a reasonable range.
+    fn set_width(&mut self, cx: &mut Cx, width: f64) {
+        self.width = width.clamp(MIN_PANE_WIDTH, MAX_PANE_WIDTH);
+        let width = self.width;
+        // The `show` animation must also expand the pane to the new width.
+        self.apply_over(cx, live! {
+            main_content = { width: (width) }
+            animator: { panel = { show = { apply: { main_content = { width: (width) } } } } }
+        });
+        self.redraw(cx);
+    }
+
     pub fn show(&mut self, cx: &mut Cx) {
+        let saved_width = app_settings().user_profile_pane_width;
+        if saved_width != self.width {
+            self.set_width(cx, saved_width);
+        }
         self.visible = true;
         cx.set_key_focus(self.view.area());
         self.animator_play(cx, id!(panel.show));
@@ -676,3 +1040,139 @@ impl UserProfileSlidingPaneRef {
         inner.show(cx);
     }
 }
+
+
+/// A list of buttons, one for each room that the current user shares with another user.
+///
+/// Clicking on a button emits a [`RoomsListAction::Selected`] action for that room.
+#[derive(Live, LiveHook, Widget)]
+pub struct SharedRoomsList {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    #[rust] children: Vec<(ButtonRef, SharedRoom)>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for SharedRoomsList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (button, _) in &self.children {
+            button.handle_event(cx, event, scope);
+        }
+        if let Event::Actions(actions) = event {
+            if let Some((_, room)) = self.children.iter().find(|(button, _)| button.clicked(actions)) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    RoomsListAction::Selected {
+                        room_id: room.room_id.clone(),
+                        room_name: room.room_name.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (button, _) in &self.children {
+            let _ = button.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl SharedRoomsListRef {
+    /// Replaces the displayed list of shared rooms with the given one.
+    pub fn set_rooms(&self, cx: &mut Cx, shared_rooms: Vec<SharedRoom>) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.children.clear();
+        for room in shared_rooms {
+            let button = WidgetRef::new_from_ptr(cx, inner.item).as_button();
+            button.set_text(cx, room.room_name.as_deref().unwrap_or(room.room_id.as_str()));
+            inner.children.push((button, room));
+        }
+        inner.redraw(cx);
+    }
+
+    /// Returns the number of shared rooms in this list.
+    pub fn num_rooms(&self) -> usize {
+        self.borrow().map_or(0, |inner| inner.children.len())
+    }
+
+    /// Returns `true` if one of the shared rooms in this list was selected.
+    pub fn selected(&self, actions: &Actions) -> bool {
+        actions.find_widget_action(self.widget_uid())
+            .is_some_and(|action| matches!(action.cast(), RoomsListAction::Selected { .. }))
+    }
+}
+
+
+/// A list of a user's devices and their verification state,
+/// with a button to start verification of each unverified device.
+#[derive(Live, LiveHook, Widget)]
+pub struct UserDevicesList {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    #[rust] children: Vec<(ViewRef, UserDevice)>,
+    #[rust] user_id: Option<OwnedUserId>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for UserDevicesList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (view, _) in &self.children {
+            view.handle_event(cx, event, scope);
+        }
+        if let Event::Actions(actions) = event {
+            let Some(user_id) = self.user_id.as_ref() else { return };
+            if let Some((_, device)) = self.children.iter()
+                .find(|(view, _)| view.button(id!(verify_button)).clicked(actions))
+            {
+                info!("Requesting verification of device {} of user {user_id}", device.device_id);
+                submit_async_request(MatrixRequest::VerifyDevice {
+                    user_id: user_id.clone(),
+                    device_id: device.device_id.clone(),
+                });
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (view, _) in &self.children {
+            let _ = view.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl UserDevicesListRef {
+    /// Replaces the displayed list of devices with the given devices of the given user.
+    pub fn set_devices(&self, cx: &mut Cx, user_id: OwnedUserId, devices: Vec<UserDevice>) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.children.clear();
+        for device in devices {
+            let view = WidgetRef::new_from_ptr(cx, inner.item).as_view();
+            let key = match (device.is_current_device, device.is_verified) {
+                (true, true) => "profile.device.current_verified",
+                (true, false) => "profile.device.current_unverified",
+                (false, true) => "profile.device.verified",
+                (false, false) => "profile.device.unverified",
+            };
+            view.label(id!(device_label)).set_text(cx, &tr_args(key, &[
+                ("name", device.display_name.as_deref().unwrap_or(tr("profile.device.unknown_name"))),
+                ("device_id", device.device_id.as_str()),
+            ]));
+            let verify_button = view.button(id!(verify_button));
+            verify_button.set_text(cx, tr("profile.device.verify"));
+            verify_button.set_visible(cx, !device.is_verified && !device.is_current_device);
+            inner.children.push((view, device));
+        }
+        inner.user_id = Some(user_id);
+        inner.redraw(cx);
+    }
+}
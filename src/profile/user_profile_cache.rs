@@ -3,7 +3,8 @@
 //! The cache is only accessible from the main UI thread.
 
 use crossbeam_queue::SegQueue;
-use makepad_widgets::{warning, Cx, SignalToUI};
+use makepad_widgets::{Cx, SignalToUI};
+use tracing::warn;
 use matrix_sdk::{room::RoomMember, ruma::{OwnedRoomId, OwnedUserId, RoomId, UserId}};
 use std::{cell::RefCell, collections::{btree_map::Entry, BTreeMap}};
 
@@ -16,7 +17,20 @@ thread_local! {
     ///
     /// To be of any use, this cache must only be accessed by the main UI thread.
     static USER_PROFILE_CACHE: RefCell<BTreeMap<OwnedUserId, UserProfileCacheEntry>> = const { RefCell::new(BTreeMap::new()) };
+
+    /// A cache of the rooms that the current user shares with each other user, indexed by user ID.
+    ///
+    /// Like the above cache, this must only be accessed by the main UI thread.
+    static SHARED_ROOMS_CACHE: RefCell<BTreeMap<OwnedUserId, Vec<SharedRoom>>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// A joined room that the current user has in common with another user.
+#[derive(Clone, Debug)]
+pub struct SharedRoom {
+    pub room_id: OwnedRoomId,
+    pub room_name: Option<String>,
 }
+
 enum UserProfileCacheEntry {
     /// A request has been issued and we're waiting for it to complete.
     Requested,
@@ -52,6 +66,11 @@ pub enum UserProfileUpdate {
     },
     /// An update to the user's profile only, without changes to room membership info.
     UserProfileOnly(UserProfile),
+    /// The full list of joined rooms that the current user shares with the given user.
+    SharedRooms {
+        user_id: OwnedUserId,
+        shared_rooms: Vec<SharedRoom>,
+    },
 }
 impl UserProfileUpdate {
     /// Returns the user ID associated with this update.
@@ -61,11 +80,16 @@ impl UserProfileUpdate {
             UserProfileUpdate::Full { new_profile, .. } => &new_profile.user_id,
             UserProfileUpdate::RoomMemberOnly { room_member, .. } => room_member.user_id(),
             UserProfileUpdate::UserProfileOnly(profile) => &profile.user_id,
+            UserProfileUpdate::SharedRooms { user_id, .. } => user_id,
         }
     }
 
-    /// Applies this update to the given user profile info cache.
-    fn apply_to_cache(self, cache: &mut BTreeMap<OwnedUserId, UserProfileCacheEntry>) {
+    /// Applies this update to the given user profile info cache and shared rooms cache.
+    fn apply_to_cache(
+        self,
+        cache: &mut BTreeMap<OwnedUserId, UserProfileCacheEntry>,
+        shared_rooms_cache: &mut BTreeMap<OwnedUserId, Vec<SharedRoom>>,
+    ) {
         match self {
             UserProfileUpdate::Full { new_profile, room_id, room_member } => {
                 match cache.entry(new_profile.user_id.clone()) {
@@ -102,7 +126,7 @@ impl UserProfileUpdate {
                     Entry::Occupied(mut entry) => match entry.get_mut() {
                         e @ UserProfileCacheEntry::Requested => {
                             // This shouldn't happen, but we can still technically handle it correctly.
-                            warning!("BUG: User profile cache entry was `Requested` for user {} when handling RoomMemberOnly update", room_member.user_id());
+                            warn!("BUG: User profile cache entry was `Requested` for user {} when handling RoomMemberOnly update", room_member.user_id());
                             *e = UserProfileCacheEntry::Loaded {
                                 user_profile: UserProfile {
                                     user_id: room_member.user_id().to_owned(),
@@ -122,7 +146,7 @@ impl UserProfileUpdate {
                     }
                     Entry::Vacant(entry) => {
                         // This shouldn't happen, but we can still technically handle it correctly.
-                        warning!("BUG: User profile cache entry not found for user {} when handling RoomMemberOnly update", room_member.user_id());
+                        warn!("BUG: User profile cache entry not found for user {} when handling RoomMemberOnly update", room_member.user_id());
                         entry.insert(UserProfileCacheEntry::Loaded {
                             user_profile: UserProfile {
                                 user_id: room_member.user_id().to_owned(),
@@ -159,6 +183,9 @@ impl UserProfileUpdate {
                     }
                 }
             }
+            UserProfileUpdate::SharedRooms { user_id, shared_rooms } => {
+                shared_rooms_cache.insert(user_id, shared_rooms);
+            }
         }
     }
 }
@@ -169,12 +196,24 @@ impl UserProfileUpdate {
 /// which isn't used, but acts as a guarantee that this function
 /// must only be called by the main UI thread.
 pub fn process_user_profile_updates(_cx: &mut Cx) {
-    USER_PROFILE_CACHE.with_borrow_mut(|cache| {
+    USER_PROFILE_CACHE.with_borrow_mut(|cache| SHARED_ROOMS_CACHE.with_borrow_mut(|shared_rooms_cache| {
         while let Some(update) = PENDING_USER_PROFILE_UPDATES.pop() {
             // Insert the updated info into the cache
-            update.apply_to_cache(cache);
+            update.apply_to_cache(cache, shared_rooms_cache);
         }
-    });
+    }));
+}
+
+/// Returns a clone of the cached list of rooms that the current user shares with the given user,
+/// if it has been fetched.
+///
+/// Use [`MatrixRequest::GetSharedRooms`] to (re-)fetch that list.
+///
+/// This function requires passing in a reference to `Cx`,
+/// which isn't used, but acts as a guarantee that this function
+/// must only be called by the main UI thread.
+pub fn get_shared_rooms(_cx: &mut Cx, user_id: &UserId) -> Option<Vec<SharedRoom>> {
+    SHARED_ROOMS_CACHE.with_borrow(|cache| cache.get(user_id).cloned())
 }
 
 /// Invokes the given closure with cached user profile info for the given user ID
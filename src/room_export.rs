@@ -0,0 +1,323 @@
+//! Exporting the full history of a room to a JSON or HTML file,
+//! and exporting a room's member list to a CSV file.
+//!
+//! The room's timeline is paginated from the homeserver in the background,
+//! independently of the timeline shown in the room screen.
+//! Progress updates are posted to the UI thread as [`RoomExportAction`]s.
+
+use std::{borrow::Cow, collections::BTreeMap, path::{Path, PathBuf}, sync::{atomic::{AtomicBool, Ordering}, Arc}};
+
+use anyhow::Context;
+use base64::Engine;
+use makepad_widgets::Cx;
+use tracing::info;
+use matrix_sdk::{
+    deserialized_responses::MemberEvent, media::{MediaFormat, MediaRequest}, room::MessagesOptions, ruma::{
+        events::{room::message::MessageType, AnyMessageLikeEvent, AnyTimelineEvent, MessageLikeEvent},
+        OwnedRoomId, OwnedUserId,
+    }, Room, RoomMemberships
+};
+use robius_directories::UserDirs;
+
+use crate::{app_data_dir, utils::unix_time_millis_to_datetime};
+
+/// The number of events requested from the homeserver per pagination request.
+const EVENTS_PER_REQUEST: u32 = 100;
+
+/// The file format of a room history export.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RoomExportFormat {
+    /// The raw JSON of every event, as received from the homeserver.
+    Json,
+    /// A human-readable HTML page of all messages, with images embedded in it.
+    Html,
+}
+
+impl RoomExportFormat {
+    fn file_extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Actions sent from the background room export task to the UI thread.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum RoomExportAction {
+    /// The given number of events have been fetched so far.
+    Progress {
+        room_id: OwnedRoomId,
+        num_events: usize,
+    },
+    /// The export completed successfully and was written to the given file.
+    Finished {
+        room_id: OwnedRoomId,
+        path: PathBuf,
+    },
+    /// The export failed.
+    Failed {
+        room_id: OwnedRoomId,
+        error: String,
+    },
+}
+
+/// Fetches the entire history of the given room and writes it to a new file in the given format.
+///
+/// Returns the path of the written file, or `None` if the export was cancelled
+/// by setting the `cancel` flag.
+pub async fn export_room_history(
+    room: Room,
+    format: RoomExportFormat,
+    cancel: Arc<AtomicBool>,
+) -> anyhow::Result<Option<PathBuf>> {
+    let room_id = room.room_id().to_owned();
+    let mut events = Vec::new();
+    let mut from = None;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            info!("Export of room {room_id} was cancelled.");
+            return Ok(None);
+        }
+        let mut options = MessagesOptions::backward();
+        options.from = from.take();
+        options.limit = EVENTS_PER_REQUEST.into();
+        let messages = room.messages(options).await?;
+        // Paginating backwards returns events in reverse chronological order.
+        events.extend(messages.chunk.into_iter().map(|timeline_event| timeline_event.event));
+        Cx::post_action(RoomExportAction::Progress { room_id: room_id.clone(), num_events: events.len() });
+        match messages.end {
+            Some(end) => from = Some(end),
+            None => break,
+        }
+    }
+    events.reverse();
+
+    let contents = match format {
+        RoomExportFormat::Json => {
+            let raw_events = events.iter().map(|raw| raw.json()).collect::<Vec<_>>();
+            serde_json::to_string_pretty(&raw_events)?
+        }
+        RoomExportFormat::Html => {
+            let events = events.iter()
+                .filter_map(|raw| raw.deserialize().ok())
+                .collect::<Vec<_>>();
+            render_html(&room, &events, &cancel).await
+        }
+    };
+    if cancel.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
+
+    let room_name = room.cached_display_name()
+        .map(|dn| dn.to_string())
+        .unwrap_or_else(|| room_id.to_string());
+    let path = export_file_path(&room_name, format.file_extension())?;
+    std::fs::write(&path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    info!("Exported {} events from room {room_id} to {}", events.len(), path.display());
+    Ok(Some(path))
+}
+
+/// The column headers of a member list export.
+const MEMBER_CSV_HEADER: [&str; 4] = ["user_id", "display_name", "power_level", "joined"];
+
+/// Writes the given room's joined members to a new CSV file, one row per member
+/// with their user ID, display name, power level, and join date.
+///
+/// The join date is the time of the member's latest membership event,
+/// which is later than their actual join if they've since changed their profile in the room.
+///
+/// Returns the path of the written file, or `None` if the export was cancelled
+/// by setting the `cancel` flag.
+pub async fn export_member_list(room: Room, cancel: Arc<AtomicBool>) -> anyhow::Result<Option<PathBuf>> {
+    let room_id = room.room_id().to_owned();
+    let members = room.members(RoomMemberships::JOIN).await?;
+    let power_levels = room.power_levels().await?;
+    if cancel.load(Ordering::Relaxed) {
+        return Ok(None);
+    }
+
+    let rows = members.iter().map(|member| {
+        let joined = match member.event().as_ref() {
+            MemberEvent::Sync(ev) => unix_time_millis_to_datetime(&ev.origin_server_ts())
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+            MemberEvent::Stripped(_) => String::new(),
+        };
+        [
+            member.user_id().to_string(),
+            member.display_name().unwrap_or_default().to_owned(),
+            power_levels.for_user(member.user_id()).to_string(),
+            joined,
+        ]
+    });
+    let contents = to_csv(MEMBER_CSV_HEADER, rows);
+
+    let room_name = room.cached_display_name()
+        .map(|dn| dn.to_string())
+        .unwrap_or_else(|| room_id.to_string());
+    let path = export_file_path(&format!("{room_name} members"), "csv")?;
+    std::fs::write(&path, contents)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    info!("Exported {} members of room {room_id} to {}", members.len(), path.display());
+    Ok(Some(path))
+}
+
+/// Formats the given header and rows as CSV text, as specified by RFC 4180.
+fn to_csv<const N: usize, S: AsRef<str>>(header: [&str; N], rows: impl Iterator<Item = [S; N]>) -> String {
+    let mut csv = csv_line(header.into_iter());
+    for row in rows {
+        csv.push_str(&csv_line(row.iter().map(AsRef::as_ref)));
+    }
+    csv
+}
+
+/// Formats the given fields as a single CSV line, including its trailing line break.
+fn csv_line<'a>(fields: impl Iterator<Item = &'a str>) -> String {
+    let mut line = fields.map(csv_field).collect::<Vec<_>>().join(",");
+    line.push_str("\r\n");
+    line
+}
+
+/// Quotes the given CSV field if it contains a comma, quote, or line break.
+///
+/// Fields that spreadsheet apps would interpret as a formula (e.g., display names
+/// starting with `=`) are prefixed with a `'` and quoted, such that opening
+/// the exported file can't run arbitrary formulas. Plain numbers are left as-is.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    let is_formula = field.starts_with(['=', '+', '-', '@', '\t', '\r'])
+        && field.parse::<f64>().is_err();
+    if is_formula {
+        Cow::Owned(format!("\"'{}\"", field.replace('"', "\"\"")))
+    } else if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Returns the path of a new export file for the given room name with the given file extension,
+/// located in the user's downloads directory if it exists.
+fn export_file_path(room_name: &str, extension: &str) -> anyhow::Result<PathBuf> {
+    let dir = UserDirs::new()
+        .and_then(|dirs| dirs.download_dir().map(Path::to_path_buf))
+        .unwrap_or_else(|| app_data_dir().join("exports"));
+    std::fs::create_dir_all(&dir)?;
+
+    let file_stem: String = room_name.chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    let date = chrono::Local::now().format("%Y-%m-%d");
+    let mut path = dir.join(format!("{file_stem} {date}.{extension}"));
+    let mut suffix = 1;
+    while path.exists() {
+        suffix += 1;
+        path = dir.join(format!("{file_stem} {date} ({suffix}).{extension}"));
+    }
+    Ok(path)
+}
+
+/// Renders the messages among the given events into a self-contained HTML page.
+///
+/// Images are downloaded and embedded into the page as data URIs.
+async fn render_html(room: &Room, events: &[AnyTimelineEvent], cancel: &AtomicBool) -> String {
+    let room_name = room.cached_display_name()
+        .map(|dn| dn.to_string())
+        .unwrap_or_else(|| room.room_id().to_string());
+    let mut sender_names: BTreeMap<OwnedUserId, String> = BTreeMap::new();
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+        <style>\n\
+        body {{ font-family: sans-serif; max-width: 800px; margin: auto; }}\n\
+        .message {{ margin: 12px 0; }}\n\
+        .sender {{ font-weight: bold; }}\n\
+        .timestamp {{ color: #888; font-size: 0.8em; margin-left: 8px; }}\n\
+        .body {{ white-space: pre-wrap; }}\n\
+        img {{ max-width: 100%; }}\n\
+        </style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+        title = htmlize::escape_text(&room_name),
+    );
+
+    for event in events {
+        if cancel.load(Ordering::Relaxed) { break }
+        let AnyTimelineEvent::MessageLike(AnyMessageLikeEvent::RoomMessage(MessageLikeEvent::Original(message))) = event else {
+            continue;
+        };
+        let sender = match sender_names.get(&message.sender) {
+            Some(name) => name.clone(),
+            None => {
+                let name = room.get_member_no_sync(&message.sender).await.ok().flatten()
+                    .and_then(|member| member.display_name().map(ToOwned::to_owned))
+                    .unwrap_or_else(|| message.sender.to_string());
+                sender_names.insert(message.sender.clone(), name.clone());
+                name
+            }
+        };
+        let timestamp = unix_time_millis_to_datetime(&message.origin_server_ts)
+            .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+            .unwrap_or_default();
+
+        let body = match &message.content.msgtype {
+            MessageType::Image(image) => {
+                let media_request = MediaRequest {
+                    source: image.source.clone(),
+                    format: MediaFormat::File,
+                };
+                match room.client().media().get_media_content(&media_request, true).await {
+                    Ok(data) => {
+                        let mimetype = image.info.as_ref()
+                            .and_then(|info| info.mimetype.clone())
+                            .unwrap_or_else(|| String::from("image/png"));
+                        format!(
+                            "<img src=\"data:{mimetype};base64,{}\" alt=\"{}\">",
+                            base64::engine::general_purpose::STANDARD.encode(data),
+                            htmlize::escape_attribute(&image.body),
+                        )
+                    }
+                    Err(_) => format!("[Image: {}]", htmlize::escape_text(&image.body)),
+                }
+            }
+            other => htmlize::escape_text(other.body()).into_owned(),
+        };
+
+        html.push_str(&format!(
+            "<div class=\"message\">\n<span class=\"sender\">{}</span><span class=\"timestamp\">{timestamp}</span>\n<div class=\"body\">{body}</div>\n</div>\n",
+            htmlize::escape_text(&sender),
+        ));
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests_room_export {
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let rows = [
+            ["@alice:example.org", "Alice", "100", "2024-01-02T03:04:05+00:00"],
+            ["@bob:example.org", "Bob \"The Builder\", Jr.", "0", ""],
+        ];
+        assert_eq!(
+            to_csv(MEMBER_CSV_HEADER, rows.into_iter()),
+            "user_id,display_name,power_level,joined\r\n\
+            \"'@alice:example.org\",Alice,100,2024-01-02T03:04:05+00:00\r\n\
+            \"'@bob:example.org\",\"Bob \"\"The Builder\"\", Jr.\",0,\r\n",
+        );
+    }
+
+    #[test]
+    fn test_csv_field_neutralizes_formulas() {
+        assert_eq!(csv_field("=HYPERLINK(\"http://evil\")"), "\"'=HYPERLINK(\"\"http://evil\"\")\"");
+        assert_eq!(csv_field("+1+1"), "\"'+1+1\"");
+        assert_eq!(csv_field("-cmd"), "\"'-cmd\"");
+        assert_eq!(csv_field("@SUM(A1)"), "\"'@SUM(A1)\"");
+        // Plain numbers, such as negative power levels, can't be formulas.
+        assert_eq!(csv_field("-10"), "-10");
+        assert_eq!(csv_field("a=b"), "a=b");
+    }
+}
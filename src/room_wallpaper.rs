@@ -0,0 +1,113 @@
+//! Per-room wallpapers, i.e., a background color or image shown behind a room's timeline.
+//!
+//! A room's wallpaper is stored in the user's room account data under a custom event type,
+//! such that it is synced across all of the user's devices but not visible to other users.
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use makepad_widgets::{vec4, Vec4};
+use matrix_sdk::ruma::{OwnedMxcUri, OwnedRoomId, RoomId};
+use serde::{Deserialize, Serialize};
+
+/// The type of the room account data event in which a room's wallpaper is stored.
+pub const ROOM_WALLPAPER_EVENT_TYPE: &str = "org.robius.robrix.room_wallpaper";
+
+/// The default amount by which a wallpaper image is dimmed, such that messages remain readable.
+pub const DEFAULT_WALLPAPER_DIM: f32 = 0.3;
+
+/// The background shown behind a room's timeline.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoomWallpaper {
+    /// The background color as a hex string, e.g., `#336699`.
+    pub color: Option<String>,
+    /// The Matrix content URI of the background image, drawn above the background color.
+    pub image_url: Option<OwnedMxcUri>,
+    /// How much the wallpaper is dimmed, from `0.0` (not at all) to `1.0` (fully black).
+    pub dim: f32,
+}
+
+impl Default for RoomWallpaper {
+    fn default() -> Self {
+        Self {
+            color: None,
+            image_url: None,
+            dim: DEFAULT_WALLPAPER_DIM,
+        }
+    }
+}
+
+impl RoomWallpaper {
+    /// Returns the background color of this wallpaper, if it has a valid one.
+    pub fn color_vec4(&self) -> Option<Vec4> {
+        self.color.as_deref().and_then(parse_hex_color)
+    }
+
+    /// Returns `true` if this wallpaper has neither a color nor an image,
+    /// in which case the default timeline background is shown.
+    pub fn is_empty(&self) -> bool {
+        self.color_vec4().is_none() && self.image_url.is_none()
+    }
+}
+
+/// Parses a color given as a hex string, with or without a leading `#`,
+/// in the form `rgb` or `rrggbb`.
+pub fn parse_hex_color(text: &str) -> Option<Vec4> {
+    let hex = text.trim();
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |s: &str| u8::from_str_radix(s, 16).ok().map(|v| v as f32 / 255.0);
+    let (r, g, b) = match hex.len() {
+        3 => (
+            channel(&hex[0..1].repeat(2))?,
+            channel(&hex[1..2].repeat(2))?,
+            channel(&hex[2..3].repeat(2))?,
+        ),
+        6 => (channel(&hex[0..2])?, channel(&hex[2..4])?, channel(&hex[4..6])?),
+        _ => return None,
+    };
+    Some(vec4(r, g, b, 1.0))
+}
+
+/// The wallpaper of each room, as most recently fetched from or saved to the room's account data.
+static ROOM_WALLPAPERS: Mutex<BTreeMap<OwnedRoomId, RoomWallpaper>> = Mutex::new(BTreeMap::new());
+
+/// Sets the wallpaper of the given room, or removes it if `None`.
+pub fn set_room_wallpaper(room_id: OwnedRoomId, wallpaper: Option<RoomWallpaper>) {
+    let mut room_wallpapers = ROOM_WALLPAPERS.lock().unwrap();
+    match wallpaper.filter(|w| !w.is_empty()) {
+        Some(wallpaper) => { room_wallpapers.insert(room_id, wallpaper); }
+        None => { room_wallpapers.remove(&room_id); }
+    }
+}
+
+/// Returns the wallpaper of the given room, if it has one.
+pub fn room_wallpaper(room_id: &RoomId) -> Option<RoomWallpaper> {
+    ROOM_WALLPAPERS.lock().unwrap().get(room_id).cloned()
+}
+
+#[cfg(test)]
+mod tests_room_wallpaper {
+    use super::*;
+
+    #[test]
+    fn test_parse_hex_color() {
+        assert_eq!(parse_hex_color("#ff0000"), Some(vec4(1.0, 0.0, 0.0, 1.0)));
+        assert_eq!(parse_hex_color(" 00ff00 "), Some(vec4(0.0, 1.0, 0.0, 1.0)));
+        assert_eq!(parse_hex_color("#00f"), Some(vec4(0.0, 0.0, 1.0, 1.0)));
+        assert_eq!(parse_hex_color("#ff00"), None);
+        assert_eq!(parse_hex_color("#gg0000"), None);
+        assert_eq!(parse_hex_color(""), None);
+    }
+
+    #[test]
+    fn test_wallpaper_deserialize_defaults() {
+        let wallpaper: RoomWallpaper = serde_json::from_str(r##"{ "color": "#336699" }"##).unwrap();
+        assert_eq!(wallpaper.dim, DEFAULT_WALLPAPER_DIM);
+        assert!(wallpaper.image_url.is_none());
+        assert!(!wallpaper.is_empty());
+        assert!(RoomWallpaper { color: Some("nope".into()), ..Default::default() }.is_empty());
+    }
+}
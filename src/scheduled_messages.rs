@@ -0,0 +1,275 @@
+//! Messages that the user has scheduled to be sent later ("send later").
+//!
+//! Scheduled messages are queued locally and saved per user account,
+//! such that they survive restarts of Robrix. They are only sent while Robrix is running:
+//! a background task in `sliding_sync` periodically sends all messages that are due,
+//! including any that became due while Robrix was closed.
+
+use std::sync::Mutex;
+
+use anyhow::bail;
+use chrono::{DateTime, Days, Local, NaiveDateTime, NaiveTime, TimeZone};
+use makepad_widgets::Cx;
+use tracing::{error, info};
+use matrix_sdk::ruma::{MilliSecondsSinceUnixEpoch, OwnedRoomId, RoomId, UInt, UserId};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    persistent_state,
+    sliding_sync::current_user_id,
+    versioned_state::{self, Migration, VersionedState},
+};
+
+/// How often the background task checks for scheduled messages that are due.
+pub const SCHEDULER_INTERVAL_SECS: u64 = 15;
+
+/// The hour of the day at which messages scheduled for "tomorrow morning" are sent.
+const TOMORROW_MORNING_HOUR: u32 = 9;
+
+/// A message that will be sent to a room at a later time.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+    /// The unique ID of this scheduled message, used to cancel it.
+    pub id: u64,
+    pub room_id: OwnedRoomId,
+    /// The text of the message, which is sent as Markdown.
+    pub text: String,
+    /// When this message should be sent.
+    pub send_at: MilliSecondsSinceUnixEpoch,
+    /// The error that occurred when this message was last sent, if sending it failed.
+    ///
+    /// Failed messages stay in the queue, but aren't sent again unless the user retries them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed: Option<String>,
+}
+
+/// The presets offered for when to send a message later.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendLaterTime {
+    InThirtyMinutes,
+    InOneHour,
+    InThreeHours,
+    TomorrowMorning,
+    /// A time entered by the user, see [`parse_send_time()`].
+    Custom,
+}
+
+impl SendLaterTime {
+    /// All presets, in the order they are shown in the "Send later" dropdown.
+    pub const ALL: [SendLaterTime; 5] = [
+        SendLaterTime::InThirtyMinutes,
+        SendLaterTime::InOneHour,
+        SendLaterTime::InThreeHours,
+        SendLaterTime::TomorrowMorning,
+        SendLaterTime::Custom,
+    ];
+
+    /// Returns the time that this preset refers to, relative to `now`.
+    ///
+    /// Returns `None` for [`SendLaterTime::Custom`].
+    pub fn send_at(self, now: DateTime<Local>) -> Option<DateTime<Local>> {
+        match self {
+            SendLaterTime::InThirtyMinutes => Some(now + chrono::Duration::minutes(30)),
+            SendLaterTime::InOneHour => Some(now + chrono::Duration::hours(1)),
+            SendLaterTime::InThreeHours => Some(now + chrono::Duration::hours(3)),
+            SendLaterTime::TomorrowMorning => {
+                let tomorrow = now.date_naive().checked_add_days(Days::new(1))?;
+                let morning = tomorrow.and_time(NaiveTime::from_hms_opt(TOMORROW_MORNING_HOUR, 0, 0)?);
+                Local.from_local_datetime(&morning).earliest()
+            }
+            SendLaterTime::Custom => None,
+        }
+    }
+}
+
+/// Parses a time entered by the user, either as `HH:MM` or as `YYYY-MM-DD HH:MM`, in local time.
+///
+/// A time of day without a date refers to its next occurrence after `now`.
+/// Returns `None` if the text can't be parsed or refers to a time that has already passed.
+pub fn parse_send_time(text: &str, now: DateTime<Local>) -> Option<DateTime<Local>> {
+    let text = text.trim();
+    let send_at = if let Ok(datetime) = NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M") {
+        Local.from_local_datetime(&datetime).earliest()?
+    } else {
+        let time = NaiveTime::parse_from_str(text, "%H:%M").ok()?;
+        let today = Local.from_local_datetime(&now.date_naive().and_time(time)).earliest()?;
+        if today > now {
+            today
+        } else {
+            let tomorrow = now.date_naive().checked_add_days(Days::new(1))?;
+            Local.from_local_datetime(&tomorrow.and_time(time)).earliest()?
+        }
+    };
+    (send_at > now).then_some(send_at)
+}
+
+/// Converts the given local time to a Matrix timestamp.
+pub fn to_timestamp(datetime: DateTime<Local>) -> Option<MilliSecondsSinceUnixEpoch> {
+    UInt::new(datetime.timestamp_millis().try_into().ok()?).map(MilliSecondsSinceUnixEpoch)
+}
+
+/// Actions sent to the UI thread when the scheduled messages of a room have changed.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum ScheduledMessagesAction {
+    Updated { room_id: OwnedRoomId },
+}
+
+/// All of the current user's scheduled messages, as saved to the filesystem.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SavedScheduledMessages {
+    /// The ID that will be given to the next scheduled message.
+    next_id: u64,
+    messages: Vec<ScheduledMessage>,
+}
+
+impl VersionedState for SavedScheduledMessages {
+    const CURRENT_VERSION: u64 = 0;
+    const MIGRATIONS: &'static [Migration] = &[];
+}
+
+static SCHEDULED_MESSAGES: Mutex<Option<SavedScheduledMessages>> = Mutex::new(None);
+
+/// Runs the given function on the global scheduled messages, initializing them if necessary.
+fn with_state<R>(f: impl FnOnce(&mut SavedScheduledMessages) -> R) -> R {
+    f(SCHEDULED_MESSAGES.lock().unwrap().get_or_insert_with(Default::default))
+}
+
+/// Returns the messages scheduled to be sent to the given room, soonest first.
+pub fn scheduled_in(room_id: &RoomId) -> Vec<ScheduledMessage> {
+    let mut messages = with_state(|state| state.messages.iter()
+        .filter(|message| message.room_id == room_id)
+        .cloned()
+        .collect::<Vec<_>>()
+    );
+    messages.sort_by_key(|message| message.send_at);
+    messages
+}
+
+/// Schedules the given text to be sent to the given room at the given time.
+pub fn schedule(room_id: OwnedRoomId, text: String, send_at: MilliSecondsSinceUnixEpoch) -> anyhow::Result<()> {
+    with_state(|state| {
+        let id = state.next_id;
+        state.next_id += 1;
+        state.messages.push(ScheduledMessage { id, room_id: room_id.clone(), text, send_at, failed: None });
+    });
+    save()?;
+    info!("Scheduled a message to be sent to room {room_id} at {send_at:?}");
+    Cx::post_action(ScheduledMessagesAction::Updated { room_id });
+    Ok(())
+}
+
+/// Cancels the scheduled message with the given ID, returning it if it hadn't been sent yet.
+pub fn cancel(id: u64) -> anyhow::Result<Option<ScheduledMessage>> {
+    let removed = with_state(|state| {
+        let index = state.messages.iter().position(|message| message.id == id)?;
+        Some(state.messages.remove(index))
+    });
+    if let Some(message) = &removed {
+        save()?;
+        Cx::post_action(ScheduledMessagesAction::Updated { room_id: message.room_id.clone() });
+    }
+    Ok(removed)
+}
+
+/// Returns all scheduled messages that are due to be sent at the given time
+/// and haven't failed to be sent before.
+///
+/// These remain in the queue until they're marked as sent or failed,
+/// such that they aren't lost if sending them fails or Robrix exits in the meantime.
+pub fn due(now: MilliSecondsSinceUnixEpoch) -> Vec<ScheduledMessage> {
+    with_state(|state| state.messages.iter()
+        .filter(|message| message.failed.is_none() && message.send_at <= now)
+        .cloned()
+        .collect()
+    )
+}
+
+/// Removes the scheduled message with the given ID from the queue, since it has been sent.
+pub fn mark_sent(id: u64) -> anyhow::Result<()> {
+    let removed = with_state(|state| {
+        let index = state.messages.iter().position(|message| message.id == id)?;
+        Some(state.messages.remove(index))
+    });
+    if let Some(message) = removed {
+        save()?;
+        Cx::post_action(ScheduledMessagesAction::Updated { room_id: message.room_id });
+    }
+    Ok(())
+}
+
+/// Marks the scheduled message with the given ID as having failed to be sent with the given error,
+/// or clears its error (if `None`) such that it will be sent again.
+pub fn set_failed(id: u64, error: Option<String>) -> anyhow::Result<()> {
+    let room_id = with_state(|state| {
+        let message = state.messages.iter_mut().find(|message| message.id == id)?;
+        message.failed = error;
+        Some(message.room_id.clone())
+    });
+    if let Some(room_id) = room_id {
+        save()?;
+        Cx::post_action(ScheduledMessagesAction::Updated { room_id });
+    }
+    Ok(())
+}
+
+/// Loads the given user's scheduled messages from the filesystem.
+///
+/// This should be called once upon login.
+pub fn load(user_id: &UserId) {
+    let file = persistent_state::scheduled_messages_file_path(user_id);
+    let saved = match std::fs::read_to_string(&file) {
+        Ok(serialized) => versioned_state::from_versioned_json(&serialized).unwrap_or_else(|e| {
+            error!("Failed to load scheduled messages for user {user_id}: {e}");
+            versioned_state::back_up_unreadable_file(&file);
+            SavedScheduledMessages::default()
+        }),
+        Err(_) => SavedScheduledMessages::default(),
+    };
+    with_state(|state| *state = saved);
+}
+
+/// Saves the current user's scheduled messages to the filesystem.
+fn save() -> anyhow::Result<()> {
+    let Some(user_id) = current_user_id() else {
+        bail!("Not logged in");
+    };
+    let file = persistent_state::scheduled_messages_file_path(&user_id);
+    if let Some(parent) = file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let serialized = with_state(|state| versioned_state::to_versioned_json(&*state))?;
+    std::fs::write(&file, serialized)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_scheduled_messages {
+    use super::*;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_send_later_presets() {
+        let now = local(2024, 5, 10, 22, 15);
+        assert_eq!(SendLaterTime::InThirtyMinutes.send_at(now), Some(local(2024, 5, 10, 22, 45)));
+        assert_eq!(SendLaterTime::InThreeHours.send_at(now), Some(local(2024, 5, 11, 1, 15)));
+        assert_eq!(SendLaterTime::TomorrowMorning.send_at(now), Some(local(2024, 5, 11, 9, 0)));
+        assert_eq!(SendLaterTime::Custom.send_at(now), None);
+    }
+
+    #[test]
+    fn test_parse_send_time() {
+        let now = local(2024, 5, 10, 14, 30);
+        assert_eq!(parse_send_time("16:00", now), Some(local(2024, 5, 10, 16, 0)));
+        // A time of day that has already passed today refers to tomorrow.
+        assert_eq!(parse_send_time("08:05", now), Some(local(2024, 5, 11, 8, 5)));
+        assert_eq!(parse_send_time(" 2024-06-01 12:00 ", now), Some(local(2024, 6, 1, 12, 0)));
+        assert_eq!(parse_send_time("2024-05-01 12:00", now), None);
+        assert_eq!(parse_send_time("tomorrow", now), None);
+        assert_eq!(parse_send_time("25:00", now), None);
+    }
+}
@@ -0,0 +1,541 @@
+//! User-configurable app settings that are persisted to the filesystem.
+//!
+//! These settings are device-wide, i.e., shared by all user accounts on this device,
+//! and are stored as JSON in the app data directory.
+
+use std::{collections::BTreeMap, path::PathBuf, sync::{atomic::{AtomicBool, Ordering}, OnceLock, RwLock, RwLockReadGuard}, time::Duration};
+
+use tracing::{error, info, warn};
+use matrix_sdk::ruma::{OwnedRoomId, RoomId};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{app_data_dir, app_lock::AppLockSettings, home::room_filter_tabs::SavedRoomFilter, i18n::{self, Language}, identity_server::IdentityServerSettings, logging::LogLevels, versioned_state::{self, unversioned_to_v1, Migration, VersionedState}};
+
+use super::{keyboard_shortcuts::KeyboardShortcuts, snippets::Snippet};
+
+const SETTINGS_FILE_NAME: &str = "settings.json";
+const DEFAULT_MEDIA_CACHE_MAX_MB: u32 = 512;
+const DEFAULT_TEMP_STORAGE_MAX_MB: u32 = 256;
+const DEFAULT_TEMP_STORAGE_MAX_AGE_DAYS: u32 = 7;
+const DEFAULT_READ_MARKER_DELAY_SECS: u32 = 5;
+const DEFAULT_SPELL_CHECK_LANGUAGE: &str = "en_US";
+/// The default width of the rooms sidebar, which must match the `MainDesktopUI` DSL.
+pub const DEFAULT_ROOMS_SIDEBAR_WIDTH: f64 = 300.0;
+/// The default width of the user profile pane, which must match the `UserProfileSlidingPane` DSL.
+pub const DEFAULT_USER_PROFILE_PANE_WIDTH: f64 = 300.0;
+
+/// All user-configurable app settings.
+///
+/// Every field must have a sensible default, such that a settings file
+/// saved by an older version of Robrix can always be loaded.
+/// Changes that can't be handled by a default value require a new [`VersionedState`] migration.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// The language used to display the UI.
+    pub language: Language,
+    /// The proxy through which all network connections should be made.
+    pub proxy: ProxySettings,
+    /// The user's custom keyboard shortcuts.
+    pub keyboard_shortcuts: KeyboardShortcuts,
+    /// Whether to let other room members know which messages we have read.
+    ///
+    /// If disabled, only private read receipts are sent.
+    pub send_read_receipts: bool,
+    /// Per-room overrides of `send_read_receipts`, e.g., to stay invisible in large public rooms.
+    pub send_read_receipts_rooms: BTreeMap<OwnedRoomId, bool>,
+    /// Whether to let other room members know when we are typing a message.
+    pub send_typing_notices: bool,
+    /// Whether pressing Enter in the message composer sends the message or inserts a newline.
+    pub enter_key_behavior: EnterKeyBehavior,
+    /// Whether to show the toolbar of Markdown formatting buttons above the message composer.
+    pub show_formatting_toolbar: bool,
+    /// Whether to underline misspelled words in the message composer and suggest corrections.
+    pub spell_check: bool,
+    /// The language of the Hunspell dictionary used for spell checking, e.g., `en_US`.
+    pub spell_check_language: String,
+    /// The user's text-expansion snippets, which are inserted into the composer by typing their trigger.
+    pub snippets: Vec<Snippet>,
+    /// Whether to hide all room content while the app window isn't focused,
+    /// e.g., while sharing the screen during a presentation.
+    pub hide_content_when_unfocused: bool,
+    /// Whether to hide membership and profile changes in room timelines.
+    pub hide_state_events: bool,
+    /// Per-room overrides of `hide_state_events`.
+    pub hide_state_events_rooms: BTreeMap<OwnedRoomId, bool>,
+    /// Whether to show each sender's name in a color derived from their user ID.
+    pub colorize_usernames: bool,
+    /// When images are downloaded automatically, e.g., to save data on metered networks.
+    pub media_auto_download: MediaAutoDownload,
+    /// The maximum size of the in-memory media cache, in megabytes.
+    pub media_cache_max_mb: u32,
+    /// The maximum total size of temporary files, in megabytes,
+    /// beyond which the oldest ones are removed upon startup.
+    pub temp_storage_max_mb: u32,
+    /// The maximum age of temporary files, in days, beyond which they are removed upon startup.
+    pub temp_storage_max_age_days: u32,
+    /// How densely messages and rooms are displayed.
+    pub display_density: DisplayDensity,
+    /// Which unread count is shown on the app icon, e.g., the macOS dock badge.
+    pub app_badge: AppBadge,
+    /// The PIN and inactivity timeout used to lock the app, if enabled.
+    pub app_lock: AppLockSettings,
+    /// Up to what age timestamps are shown relative to the current time, e.g., "5 mins ago".
+    pub relative_timestamps: RelativeTimestamps,
+    /// When the "New Messages" read marker is moved past messages that the user has seen.
+    pub read_marker_clearing: ReadMarkerClearing,
+    /// How long the user must view a room's new messages before the read marker is moved past them,
+    /// in seconds, if `read_marker_clearing` is [`ReadMarkerClearing::AfterDelay`].
+    pub read_marker_delay_secs: u32,
+    /// The user's saved room filters, shown as tabs above the rooms list.
+    pub saved_room_filters: Vec<SavedRoomFilter>,
+    /// The width of the rooms sidebar in the desktop layout, as last resized by the user.
+    pub rooms_sidebar_width: f64,
+    /// The width of the user profile pane, as last resized by the user.
+    pub user_profile_pane_width: f64,
+    /// The log level of each part of the app, which takes effect upon the next startup.
+    pub log_levels: LogLevels,
+    /// The identity server used to find other users by email address or phone number.
+    pub identity_server: IdentityServerSettings,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            language: Language::default(),
+            proxy: ProxySettings::default(),
+            keyboard_shortcuts: KeyboardShortcuts::default(),
+            send_read_receipts: true,
+            send_read_receipts_rooms: BTreeMap::new(),
+            send_typing_notices: true,
+            enter_key_behavior: EnterKeyBehavior::default(),
+            show_formatting_toolbar: false,
+            spell_check: false,
+            spell_check_language: DEFAULT_SPELL_CHECK_LANGUAGE.to_owned(),
+            snippets: Vec::new(),
+            hide_content_when_unfocused: false,
+            hide_state_events: false,
+            hide_state_events_rooms: BTreeMap::new(),
+            colorize_usernames: true,
+            media_auto_download: MediaAutoDownload::default(),
+            media_cache_max_mb: DEFAULT_MEDIA_CACHE_MAX_MB,
+            temp_storage_max_mb: DEFAULT_TEMP_STORAGE_MAX_MB,
+            temp_storage_max_age_days: DEFAULT_TEMP_STORAGE_MAX_AGE_DAYS,
+            display_density: DisplayDensity::default(),
+            app_badge: AppBadge::default(),
+            app_lock: AppLockSettings::default(),
+            relative_timestamps: RelativeTimestamps::default(),
+            read_marker_clearing: ReadMarkerClearing::default(),
+            read_marker_delay_secs: DEFAULT_READ_MARKER_DELAY_SECS,
+            saved_room_filters: Vec::new(),
+            rooms_sidebar_width: DEFAULT_ROOMS_SIDEBAR_WIDTH,
+            user_profile_pane_width: DEFAULT_USER_PROFILE_PANE_WIDTH,
+            log_levels: LogLevels::default(),
+            identity_server: IdentityServerSettings::default(),
+        }
+    }
+}
+
+impl VersionedState for AppSettings {
+    const CURRENT_VERSION: u64 = 1;
+    const MIGRATIONS: &'static [Migration] = &[unversioned_to_v1];
+}
+
+impl AppSettings {
+    /// Returns the maximum size of the in-memory media cache, in bytes.
+    pub fn media_cache_max_bytes(&self) -> u64 {
+        self.media_cache_max_mb as u64 * 1024 * 1024
+    }
+
+    /// Returns the maximum total size of temporary files, in bytes.
+    pub fn temp_storage_max_bytes(&self) -> u64 {
+        self.temp_storage_max_mb as u64 * 1024 * 1024
+    }
+
+    /// Returns the maximum age of temporary files.
+    pub fn temp_storage_max_age(&self) -> Duration {
+        Duration::from_secs(self.temp_storage_max_age_days as u64 * 24 * 60 * 60)
+    }
+
+    /// Returns whether public read receipts should be sent in the given room.
+    pub fn send_read_receipts_in(&self, room_id: &RoomId) -> bool {
+        self.send_read_receipts_rooms.get(room_id)
+            .copied()
+            .unwrap_or(self.send_read_receipts)
+    }
+
+    /// Sets whether public read receipts should be sent in the given room,
+    /// overriding the global `send_read_receipts` setting for that room only.
+    pub fn set_send_read_receipts_in(&mut self, room_id: OwnedRoomId, send: bool) {
+        if send == self.send_read_receipts {
+            self.send_read_receipts_rooms.remove(&room_id);
+        } else {
+            self.send_read_receipts_rooms.insert(room_id, send);
+        }
+    }
+
+    /// Returns whether membership and profile changes should be hidden in the given room.
+    pub fn hide_state_events_in(&self, room_id: &RoomId) -> bool {
+        self.hide_state_events_rooms.get(room_id)
+            .copied()
+            .unwrap_or(self.hide_state_events)
+    }
+
+    /// Sets whether membership and profile changes should be hidden in the given room,
+    /// overriding the global `hide_state_events` setting for that room only.
+    pub fn set_hide_state_events_in(&mut self, room_id: OwnedRoomId, hide: bool) {
+        if hide == self.hide_state_events {
+            self.hide_state_events_rooms.remove(&room_id);
+        } else {
+            self.hide_state_events_rooms.insert(room_id, hide);
+        }
+    }
+}
+
+/// How densely messages in the room timeline and rooms in the rooms list are displayed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayDensity {
+    #[default]
+    Comfortable,
+    Compact,
+}
+
+impl DisplayDensity {
+    /// All display densities, in the order they are shown in the settings screen.
+    pub const ALL: [DisplayDensity; 2] = [DisplayDensity::Comfortable, DisplayDensity::Compact];
+}
+
+/// Which unread count is shown as a badge on the app icon.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AppBadge {
+    /// No badge is shown.
+    Off,
+    /// The number of unread mentions across all rooms.
+    MentionsOnly,
+    /// The number of unread messages across all rooms.
+    #[default]
+    AllUnread,
+}
+
+impl AppBadge {
+    /// All options, in the order they are shown in the settings screen.
+    pub const ALL: [AppBadge; 3] = [AppBadge::Off, AppBadge::MentionsOnly, AppBadge::AllUnread];
+}
+
+/// When media in room timelines is downloaded automatically.
+///
+/// Media that isn't downloaded automatically is shown as a placeholder that can be tapped to download it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MediaAutoDownload {
+    /// Media is always downloaded automatically.
+    #[default]
+    Always,
+    /// Media is only downloaded automatically while the network connection isn't metered,
+    /// e.g., not over a cellular connection.
+    UnlessMetered,
+    /// Media is never downloaded automatically.
+    Never,
+}
+
+impl MediaAutoDownload {
+    /// All options, in the order they are shown in the settings screen.
+    pub const ALL: [MediaAutoDownload; 3] = [
+        MediaAutoDownload::Always,
+        MediaAutoDownload::UnlessMetered,
+        MediaAutoDownload::Never,
+    ];
+}
+
+/// What pressing Enter in the message composer does.
+///
+/// Pressing Ctrl+Enter (or Cmd+Enter on macOS) does the other one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EnterKeyBehavior {
+    /// Enter inserts a newline, and Ctrl/Cmd+Enter sends the message.
+    #[default]
+    InsertNewline,
+    /// Enter sends the message, and Ctrl/Cmd+Enter (or Shift+Enter) inserts a newline.
+    SendMessage,
+}
+
+impl EnterKeyBehavior {
+    /// All options, in the order they are shown in the settings screen.
+    pub const ALL: [EnterKeyBehavior; 2] = [
+        EnterKeyBehavior::InsertNewline,
+        EnterKeyBehavior::SendMessage,
+    ];
+}
+
+/// Up to what age timestamps are shown relative to the current time (e.g., "5 mins ago")
+/// rather than as an absolute time or date.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RelativeTimestamps {
+    /// Timestamps are always absolute.
+    Never,
+    #[default]
+    OneHour,
+    OneDay,
+    OneWeek,
+}
+
+impl RelativeTimestamps {
+    /// All options, in the order they are shown in the settings screen.
+    pub const ALL: [RelativeTimestamps; 4] = [
+        RelativeTimestamps::Never,
+        RelativeTimestamps::OneHour,
+        RelativeTimestamps::OneDay,
+        RelativeTimestamps::OneWeek,
+    ];
+
+    /// Returns the age up to which timestamps are shown relative to the current time, if any.
+    pub fn max_age(self) -> Option<chrono::Duration> {
+        match self {
+            RelativeTimestamps::Never => None,
+            RelativeTimestamps::OneHour => Some(chrono::Duration::hours(1)),
+            RelativeTimestamps::OneDay => Some(chrono::Duration::days(1)),
+            RelativeTimestamps::OneWeek => Some(chrono::Duration::weeks(1)),
+        }
+    }
+}
+
+/// When the "New Messages" read marker (the fully-read marker) is moved past messages
+/// that the user has scrolled through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReadMarkerClearing {
+    /// As soon as the user scrolls past the read marker.
+    #[default]
+    Immediately,
+    /// Once the user has viewed the new messages for `read_marker_delay_secs` seconds.
+    AfterDelay,
+    /// Only when the user explicitly marks the room as read.
+    OnlyWhenMarkedRead,
+}
+
+impl ReadMarkerClearing {
+    /// All options, in the order they are shown in the settings screen.
+    pub const ALL: [ReadMarkerClearing; 3] = [
+        ReadMarkerClearing::Immediately,
+        ReadMarkerClearing::AfterDelay,
+        ReadMarkerClearing::OnlyWhenMarkedRead,
+    ];
+}
+
+/// The kind of proxy server to connect through.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ProxyKind {
+    #[default]
+    Http,
+    Socks5,
+}
+
+impl ProxyKind {
+    /// All proxy kinds, in the order they are shown in the settings screen.
+    pub const ALL: [ProxyKind; 2] = [ProxyKind::Http, ProxyKind::Socks5];
+
+    fn scheme(self) -> &'static str {
+        match self {
+            ProxyKind::Http => "http",
+            // `socks5h` resolves hostnames via the proxy, which is required for Tor.
+            ProxyKind::Socks5 => "socks5h",
+        }
+    }
+}
+
+/// Settings for an HTTP or SOCKS proxy, e.g., for users behind a corporate firewall or using Tor.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProxySettings {
+    /// Whether the proxy should be used at all.
+    pub enabled: bool,
+    pub kind: ProxyKind,
+    pub host: String,
+    pub port: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// The proxy password, which is stored in the platform keyring (if available)
+    /// rather than in the plaintext settings file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+impl ProxySettings {
+    /// Returns the full URL of this proxy, including any credentials,
+    /// or `None` if the proxy is disabled or incompletely specified.
+    pub fn url(&self) -> Option<String> {
+        if !self.enabled || self.host.trim().is_empty() || self.port == 0 {
+            return None;
+        }
+        let mut url = Url::parse(&format!(
+            "{}://{}:{}",
+            self.kind.scheme(),
+            self.host.trim(),
+            self.port,
+        )).ok()?;
+        if let Some(username) = self.username.as_deref().filter(|u| !u.is_empty()) {
+            url.set_username(username).ok()?;
+            url.set_password(self.password.as_deref().filter(|p| !p.is_empty())).ok()?;
+        }
+        Some(url.into())
+    }
+}
+
+static APP_SETTINGS: OnceLock<RwLock<AppSettings>> = OnceLock::new();
+
+fn settings_file_path() -> PathBuf {
+    app_data_dir().join(SETTINGS_FILE_NAME)
+}
+
+fn settings_lock() -> &'static RwLock<AppSettings> {
+    APP_SETTINGS.get_or_init(|| RwLock::new(load_from_disk()))
+}
+
+fn load_from_disk() -> AppSettings {
+    let path = settings_file_path();
+    let Ok(serialized) = std::fs::read_to_string(&path) else {
+        info!("No app settings file found at {}, using default settings.", path.display());
+        return AppSettings::default();
+    };
+    let mut settings: AppSettings = versioned_state::from_versioned_json(&serialized).unwrap_or_else(|e| {
+        error!("Failed to load app settings file {}: {e:?}. Using default settings.", path.display());
+        // Keep the unreadable file around rather than overwriting it upon the next save.
+        versioned_state::back_up_unreadable_file(&path);
+        AppSettings::default()
+    });
+
+    if settings.proxy.password.is_some() {
+        // The password was saved in the settings file, either because the keyring wasn't available
+        // or by an older version of Robrix, so try to move it into the keyring.
+        if store_proxy_password(settings.proxy.password.as_deref()) {
+            save_to_disk(&settings);
+        }
+    } else if settings.proxy.username.is_some() {
+        match load_proxy_password_from_keyring() {
+            Ok(password) => {
+                settings.proxy.password = password;
+                PROXY_PASSWORD_IN_KEYRING.store(true, Ordering::Relaxed);
+            }
+            Err(e) => error!("Failed to load the proxy password from the keyring: {e}"),
+        }
+    }
+    settings
+}
+
+fn save_to_disk(settings: &AppSettings) {
+    let path = settings_file_path();
+    let mut settings = settings.clone();
+    if PROXY_PASSWORD_IN_KEYRING.load(Ordering::Relaxed) {
+        settings.proxy.password = None;
+    }
+    let result = versioned_state::to_versioned_json(&settings)
+        .map_err(std::io::Error::other)
+        .and_then(|serialized| {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, serialized)
+        });
+    if let Err(e) = result {
+        error!("Failed to save app settings to {}: {e:?}", path.display());
+    }
+}
+
+/// The keyring account under which the proxy password is stored.
+#[cfg(not(target_os = "android"))]
+const PROXY_PASSWORD_KEYRING_ACCOUNT: &str = "proxy_password";
+
+/// Whether the proxy password is stored in the platform keyring rather than in the settings file.
+static PROXY_PASSWORD_IN_KEYRING: AtomicBool = AtomicBool::new(false);
+
+/// Stores the given proxy password in the platform keyring, or removes it from the keyring if `None`.
+///
+/// Returns `false` if the keyring isn't available, in which case the password
+/// must be saved in the settings file instead.
+fn store_proxy_password(password: Option<&str>) -> bool {
+    let result = match password {
+        Some(password) => save_proxy_password_to_keyring(password),
+        None => delete_proxy_password_from_keyring(),
+    };
+    match result {
+        Ok(()) => {
+            PROXY_PASSWORD_IN_KEYRING.store(password.is_some(), Ordering::Relaxed);
+            true
+        }
+        Err(e) => {
+            warn!("Failed to save the proxy password to the keyring, saving it in the settings file instead: {e}");
+            PROXY_PASSWORD_IN_KEYRING.store(false, Ordering::Relaxed);
+            false
+        }
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn proxy_password_keyring_entry() -> keyring::Result<keyring::Entry> {
+    keyring::Entry::new(crate::persistent_state::KEYRING_SERVICE, PROXY_PASSWORD_KEYRING_ACCOUNT)
+}
+
+#[cfg(not(target_os = "android"))]
+fn save_proxy_password_to_keyring(password: &str) -> anyhow::Result<()> {
+    proxy_password_keyring_entry()?.set_password(password)?;
+    Ok(())
+}
+
+#[cfg(not(target_os = "android"))]
+fn delete_proxy_password_from_keyring() -> anyhow::Result<()> {
+    match proxy_password_keyring_entry()?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(not(target_os = "android"))]
+fn load_proxy_password_from_keyring() -> anyhow::Result<Option<String>> {
+    match proxy_password_keyring_entry()?.get_password() {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(target_os = "android")]
+fn save_proxy_password_to_keyring(_password: &str) -> anyhow::Result<()> {
+    anyhow::bail!("The platform keyring is not supported on Android")
+}
+
+#[cfg(target_os = "android")]
+fn delete_proxy_password_from_keyring() -> anyhow::Result<()> {
+    Ok(())
+}
+
+#[cfg(target_os = "android")]
+fn load_proxy_password_from_keyring() -> anyhow::Result<Option<String>> {
+    Ok(None)
+}
+
+/// Loads the app settings from the filesystem and applies any settings
+/// that must take effect globally, e.g., the UI language.
+///
+/// This should be called once upon app startup.
+pub fn load_app_settings() {
+    let settings = app_settings();
+    i18n::set_language(settings.language);
+}
+
+/// Returns a read-only reference to the current app settings.
+///
+/// Do not hold onto the returned guard for longer than necessary,
+/// as it blocks any concurrent calls to [`update_app_settings()`].
+pub fn app_settings() -> RwLockReadGuard<'static, AppSettings> {
+    settings_lock().read().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Modifies the app settings using the given closure and then persists them to the filesystem.
+pub fn update_app_settings(f: impl FnOnce(&mut AppSettings)) {
+    let mut settings = settings_lock().write().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let old_proxy_password = settings.proxy.password.clone();
+    f(&mut settings);
+    if settings.proxy.password != old_proxy_password {
+        store_proxy_password(settings.proxy.password.as_deref());
+    }
+    save_to_disk(&settings);
+}
@@ -0,0 +1,283 @@
+//! A registry of configurable keyboard shortcuts for common app-wide actions.
+//!
+//! Upon a key press, the `App` looks up the pressed key combination in the current
+//! [`KeyboardShortcuts`] and, if it is bound to a [`ShortcutAction`],
+//! posts that action such that the relevant widget(s) can handle it.
+//!
+//! Key combinations are written in a human-readable form, e.g., `"Primary+Shift+K"`,
+//! in which `Primary` is the Command key on macOS and the Control key elsewhere.
+//! Each combination must include the Primary or Alt key, such that it doesn't
+//! interfere with typing into a text input.
+
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+use makepad_widgets::{KeyCode, KeyEvent};
+use tracing::warn;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::app_settings::app_settings;
+
+/// The app-wide actions that can be triggered by a keyboard shortcut.
+///
+/// These are posted as non-widget actions via `Cx::action()`,
+/// so widgets must use `action.downcast_ref()` to match on them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ShortcutAction {
+    /// Open the next room (below the current one) that has unread messages.
+    NextUnreadRoom,
+    /// Open the previous room (above the current one) that has unread messages.
+    PreviousUnreadRoom,
+    /// Focus the search bar.
+    FocusSearch,
+    /// Start replying to the latest message in the current room.
+    ReplyToLastMessage,
+    /// Show or hide the spaces sidebar.
+    ToggleSidebar,
+    /// Open the settings screen.
+    OpenSettings,
+    /// Show or hide the performance metrics overlay, for debugging.
+    TogglePerformanceOverlay,
+}
+
+impl ShortcutAction {
+    /// All shortcut actions, in the order they are shown in the settings screen.
+    pub const ALL: [ShortcutAction; 7] = [
+        ShortcutAction::NextUnreadRoom,
+        ShortcutAction::PreviousUnreadRoom,
+        ShortcutAction::FocusSearch,
+        ShortcutAction::ReplyToLastMessage,
+        ShortcutAction::ToggleSidebar,
+        ShortcutAction::OpenSettings,
+        ShortcutAction::TogglePerformanceOverlay,
+    ];
+
+    /// Returns the i18n key of this action's user-facing description.
+    pub fn description_key(self) -> &'static str {
+        match self {
+            ShortcutAction::NextUnreadRoom => "shortcut.next_unread_room",
+            ShortcutAction::PreviousUnreadRoom => "shortcut.previous_unread_room",
+            ShortcutAction::FocusSearch => "shortcut.focus_search",
+            ShortcutAction::ReplyToLastMessage => "shortcut.reply_to_last_message",
+            ShortcutAction::ToggleSidebar => "shortcut.toggle_sidebar",
+            ShortcutAction::OpenSettings => "shortcut.open_settings",
+            ShortcutAction::TogglePerformanceOverlay => "shortcut.toggle_performance_overlay",
+        }
+    }
+
+    /// Returns the key combination bound to this action by default.
+    pub fn default_combo(self) -> KeyCombo {
+        let (key_code, primary, shift, alt) = match self {
+            ShortcutAction::NextUnreadRoom     => (KeyCode::ArrowDown, false, true,  true),
+            ShortcutAction::PreviousUnreadRoom => (KeyCode::ArrowUp,   false, true,  true),
+            ShortcutAction::FocusSearch        => (KeyCode::KeyK,      true,  false, false),
+            ShortcutAction::ReplyToLastMessage => (KeyCode::KeyR,      true,  true,  false),
+            ShortcutAction::ToggleSidebar      => (KeyCode::Period,    true,  false, false),
+            ShortcutAction::OpenSettings       => (KeyCode::Comma,     true,  false, false),
+            ShortcutAction::TogglePerformanceOverlay => (KeyCode::KeyP, true,  true,  false),
+        };
+        KeyCombo { key_code, primary, shift, alt }
+    }
+}
+
+/// A key plus the modifier keys that must be held down along with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct KeyCombo {
+    pub key_code: KeyCode,
+    /// The Command key on macOS, or the Control key on other platforms.
+    pub primary: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyCombo {
+    /// Returns `true` if the given key event matches this key combination exactly.
+    pub fn matches(&self, ke: &KeyEvent) -> bool {
+        ke.key_code == self.key_code
+            && ke.modifiers.is_primary() == self.primary
+            && ke.modifiers.shift == self.shift
+            && ke.modifiers.alt == self.alt
+    }
+}
+
+/// The names of all keys that can be used in a keyboard shortcut.
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::KeyA, "A"), (KeyCode::KeyB, "B"), (KeyCode::KeyC, "C"), (KeyCode::KeyD, "D"),
+    (KeyCode::KeyE, "E"), (KeyCode::KeyF, "F"), (KeyCode::KeyG, "G"), (KeyCode::KeyH, "H"),
+    (KeyCode::KeyI, "I"), (KeyCode::KeyJ, "J"), (KeyCode::KeyK, "K"), (KeyCode::KeyL, "L"),
+    (KeyCode::KeyM, "M"), (KeyCode::KeyN, "N"), (KeyCode::KeyO, "O"), (KeyCode::KeyP, "P"),
+    (KeyCode::KeyQ, "Q"), (KeyCode::KeyR, "R"), (KeyCode::KeyS, "S"), (KeyCode::KeyT, "T"),
+    (KeyCode::KeyU, "U"), (KeyCode::KeyV, "V"), (KeyCode::KeyW, "W"), (KeyCode::KeyX, "X"),
+    (KeyCode::KeyY, "Y"), (KeyCode::KeyZ, "Z"),
+    (KeyCode::Key0, "0"), (KeyCode::Key1, "1"), (KeyCode::Key2, "2"), (KeyCode::Key3, "3"),
+    (KeyCode::Key4, "4"), (KeyCode::Key5, "5"), (KeyCode::Key6, "6"), (KeyCode::Key7, "7"),
+    (KeyCode::Key8, "8"), (KeyCode::Key9, "9"),
+    (KeyCode::ArrowUp, "Up"), (KeyCode::ArrowDown, "Down"),
+    (KeyCode::ArrowLeft, "Left"), (KeyCode::ArrowRight, "Right"),
+    (KeyCode::PageUp, "PageUp"), (KeyCode::PageDown, "PageDown"),
+    (KeyCode::Home, "Home"), (KeyCode::End, "End"),
+    (KeyCode::Escape, "Escape"), (KeyCode::Tab, "Tab"), (KeyCode::Space, "Space"),
+    (KeyCode::Comma, ","), (KeyCode::Period, "."), (KeyCode::Slash, "/"),
+    (KeyCode::Backslash, "\\"), (KeyCode::Semicolon, ";"), (KeyCode::Quote, "'"),
+    (KeyCode::LBracket, "["), (KeyCode::RBracket, "]"),
+    (KeyCode::Minus, "-"), (KeyCode::Equals, "="), (KeyCode::Backtick, "`"),
+];
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.primary { write!(f, "Primary+")?; }
+        if self.shift { write!(f, "Shift+")?; }
+        if self.alt { write!(f, "Alt+")?; }
+        let key_name = KEY_NAMES.iter()
+            .find(|(kc, _)| *kc == self.key_code)
+            .map(|(_, name)| *name)
+            .unwrap_or("?");
+        write!(f, "{key_name}")
+    }
+}
+
+impl FromStr for KeyCombo {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key_name) = s.trim()
+            .rsplit_once('+')
+            .unwrap_or(("", s.trim()));
+        let key_code = KEY_NAMES.iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(key_name.trim()))
+            .map(|(kc, _)| *kc)
+            .ok_or_else(|| format!("unknown key {key_name:?}"))?;
+
+        let mut combo = KeyCombo { key_code, primary: false, shift: false, alt: false };
+        for modifier in modifiers.split('+').map(str::trim).filter(|m| !m.is_empty()) {
+            match modifier.to_ascii_lowercase().as_str() {
+                "primary" | "ctrl" | "control" | "cmd" | "command" => combo.primary = true,
+                "shift" => combo.shift = true,
+                "alt" | "option" => combo.alt = true,
+                other => return Err(format!("unknown modifier key {other:?}")),
+            }
+        }
+        if !combo.primary && !combo.alt {
+            return Err("a shortcut must include the Primary or Alt key".into());
+        }
+        Ok(combo)
+    }
+}
+
+impl TryFrom<String> for KeyCombo {
+    type Error = String;
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<KeyCombo> for String {
+    fn from(combo: KeyCombo) -> Self {
+        combo.to_string()
+    }
+}
+
+/// The user's custom keyboard shortcuts.
+///
+/// Only the shortcuts that the user has changed are stored;
+/// all other actions use their default key combination.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct KeyboardShortcuts {
+    #[serde(deserialize_with = "deserialize_overrides")]
+    overrides: BTreeMap<ShortcutAction, KeyCombo>,
+}
+
+/// Deserializes the user's custom shortcuts, skipping any that are no longer valid
+/// (e.g., for an action that has since been removed) rather than failing entirely.
+fn deserialize_overrides<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BTreeMap<ShortcutAction, KeyCombo>, D::Error> {
+    let raw_overrides = BTreeMap::<String, String>::deserialize(deserializer)?;
+    Ok(raw_overrides.into_iter()
+        .filter_map(|(action, combo)| {
+            let parsed_action = serde_json::from_value(serde_json::Value::String(action.clone()));
+            match (parsed_action, combo.parse()) {
+                (Ok(action), Ok(combo)) => Some((action, combo)),
+                _ => {
+                    warn!("Ignoring invalid keyboard shortcut {combo:?} for action {action:?}");
+                    None
+                }
+            }
+        })
+        .collect())
+}
+
+impl KeyboardShortcuts {
+    /// Returns the key combination currently bound to the given action.
+    pub fn combo_for(&self, action: ShortcutAction) -> KeyCombo {
+        self.overrides.get(&action)
+            .copied()
+            .unwrap_or_else(|| action.default_combo())
+    }
+
+    /// Binds the given action to the given key combination.
+    pub fn set(&mut self, action: ShortcutAction, combo: KeyCombo) {
+        if combo == action.default_combo() {
+            self.overrides.remove(&action);
+        } else {
+            self.overrides.insert(action, combo);
+        }
+    }
+
+    /// Restores all actions to their default key combinations.
+    pub fn reset_to_defaults(&mut self) {
+        self.overrides.clear();
+    }
+
+    /// Returns the action bound to the given key event, if any.
+    pub fn action_for(&self, ke: &KeyEvent) -> Option<ShortcutAction> {
+        ShortcutAction::ALL.into_iter()
+            .find(|action| self.combo_for(*action).matches(ke))
+    }
+}
+
+/// Returns the action bound to the given key event in the current app settings, if any.
+pub fn shortcut_action_for(ke: &KeyEvent) -> Option<ShortcutAction> {
+    app_settings().keyboard_shortcuts.action_for(ke)
+}
+
+#[cfg(test)]
+mod tests_keyboard_shortcuts {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_combo() {
+        let combo: KeyCombo = "Primary+Shift+K".parse().unwrap();
+        assert_eq!(combo, KeyCombo { key_code: KeyCode::KeyK, primary: true, shift: true, alt: false });
+        let combo: KeyCombo = " ctrl + alt + pagedown ".parse().unwrap();
+        assert_eq!(combo, KeyCombo { key_code: KeyCode::PageDown, primary: true, shift: false, alt: true });
+    }
+
+    #[test]
+    fn test_parse_invalid_key_combo() {
+        assert!("Primary+NoSuchKey".parse::<KeyCombo>().is_err());
+        assert!("Hyper+K".parse::<KeyCombo>().is_err());
+        assert!("".parse::<KeyCombo>().is_err());
+        // Shortcuts without Primary or Alt would interfere with typing.
+        assert!("K".parse::<KeyCombo>().is_err());
+        assert!("Shift+K".parse::<KeyCombo>().is_err());
+    }
+
+    #[test]
+    fn test_key_combo_round_trip() {
+        for action in ShortcutAction::ALL {
+            let combo = action.default_combo();
+            assert_eq!(combo.to_string().parse::<KeyCombo>(), Ok(combo));
+        }
+        let combo: KeyCombo = "Alt+,".parse().unwrap();
+        assert_eq!(combo.to_string(), "Alt+,");
+    }
+
+    #[test]
+    fn test_deserialize_skips_invalid_overrides() {
+        let shortcuts: KeyboardShortcuts = serde_json::from_str(
+            r#"{ "FocusSearch": "Primary+J", "EditLastMessage": "Primary+Shift+E", "OpenSettings": "Q" }"#
+        ).unwrap();
+        assert_eq!(shortcuts.combo_for(ShortcutAction::FocusSearch).to_string(), "Primary+J");
+        assert_eq!(shortcuts.combo_for(ShortcutAction::OpenSettings), ShortcutAction::OpenSettings.default_combo());
+    }
+}
@@ -0,0 +1,205 @@
+//! A viewer of the most recent lines of the current log file, with level filtering and search,
+//! such that issues can be diagnosed on devices where accessing files is painful (e.g., mobile).
+
+use makepad_widgets::*;
+
+use crate::{
+    i18n::{tr, tr_args},
+    logging::{current_log_file, filter_log_lines, read_log_tail, LogLevel},
+};
+
+/// The maximum number of bytes read from the end of the log file.
+const MAX_LOG_TAIL_BYTES: u64 = 256 * 1024;
+/// The maximum number of log lines shown at once.
+const MAX_SHOWN_LINES: usize = 500;
+/// How often the log file is checked for new lines while following it, in seconds.
+const FOLLOW_INTERVAL_SECS: f64 = 2.0;
+
+/// The log levels that can be selected as the minimum level to show,
+/// in the order of the `level_dropdown` labels.
+const SELECTABLE_LEVELS: [LogLevel; 5] = [
+    LogLevel::Error,
+    LogLevel::Warn,
+    LogLevel::Info,
+    LogLevel::Debug,
+    LogLevel::Trace,
+];
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::*;
+
+    pub LogViewer = {{LogViewer}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 8
+
+        controls = <View> {
+            width: Fill, height: Fit
+            flow: Right, spacing: 10
+            align: {y: 0.5}
+
+            level_dropdown = <DropDown> {
+                width: 100, height: Fit
+                labels: ["Error", "Warn", "Info", "Debug", "Trace"]
+                selected_item: 2
+            }
+
+            search_input = <RobrixTextInput> {
+                width: 200, height: Fit
+                padding: 8
+                draw_bg: {
+                    border_width: 1.0
+                }
+                empty_message: "Search logs"
+            }
+
+            follow_checkbox = <CheckBox> { text: "" }
+            follow_label = <Label> {
+                width: Fit, height: Fit
+                draw_text: {
+                    color: (COLOR_TEXT)
+                    text_style: <REGULAR_TEXT>{}
+                }
+            }
+
+            refresh_button = <RobrixIconButton> {
+                padding: {left: 15, right: 15, top: 10, bottom: 10}
+                draw_bg: {
+                    color: (COLOR_SELECTED_PRIMARY)
+                }
+                draw_text: {
+                    color: (COLOR_PRIMARY)
+                }
+            }
+        }
+
+        log_file_label = <Label> {
+            width: Fill, height: Fit
+            draw_text: {
+                wrap: Word,
+                color: (MESSAGE_NOTICE_TEXT_COLOR)
+                text_style: <REGULAR_TEXT>{font_size: 9}
+            }
+        }
+
+        <ScrollYView> {
+            width: Fill, height: 300
+            padding: 8
+            show_bg: true
+            draw_bg: {
+                color: #f5f5f5
+            }
+
+            log_text = <Label> {
+                width: Fill, height: Fit
+                draw_text: {
+                    wrap: Word,
+                    color: #000
+                    text_style: <THEME_FONT_CODE>{font_size: 8.5}
+                }
+            }
+        }
+    }
+}
+
+#[derive(Live, LiveHook, Widget)]
+pub struct LogViewer {
+    #[deref] view: View,
+    /// The timer used to check the log file for new lines while following it.
+    #[rust] follow_timer: Timer,
+    /// The length of the log file when it was last read, used to skip unnecessary re-reads.
+    #[rust] last_read_len: Option<u64>,
+}
+
+impl Widget for LogViewer {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if self.follow_timer.is_event(event).is_some() {
+            self.refresh(cx, false);
+            self.follow_timer = cx.start_timeout(FOLLOW_INTERVAL_SECS);
+        }
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.label(id!(follow_label)).set_text(cx, tr("settings.logs.follow"));
+        self.button(id!(refresh_button)).set_text(cx, tr("settings.logs.refresh"));
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for LogViewer {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, _scope: &mut Scope) {
+        if self.drop_down(id!(level_dropdown)).selected(actions).is_some()
+            || self.text_input(id!(search_input)).changed(actions).is_some()
+            || self.button(id!(refresh_button)).clicked(actions)
+        {
+            self.refresh(cx, true);
+        }
+
+        if let Some(follow) = self.check_box(id!(follow_checkbox)).changed(actions) {
+            cx.stop_timer(self.follow_timer);
+            if follow {
+                self.refresh(cx, true);
+                self.follow_timer = cx.start_timeout(FOLLOW_INTERVAL_SECS);
+            }
+        }
+    }
+}
+
+impl LogViewer {
+    /// Re-reads the current log file and shows its most recent lines that match the current filters.
+    ///
+    /// If `force` is `false`, the log file is only re-read if its length has changed.
+    fn refresh(&mut self, cx: &mut Cx, force: bool) {
+        let Some(path) = current_log_file() else {
+            self.label(id!(log_file_label)).set_text(cx, tr("settings.logs.no_file"));
+            self.label(id!(log_text)).set_text(cx, "");
+            self.last_read_len = None;
+            self.redraw(cx);
+            return;
+        };
+        let len = std::fs::metadata(&path).map(|m| m.len()).ok();
+        if !force && len.is_some() && len == self.last_read_len {
+            return;
+        }
+        self.last_read_len = len;
+
+        let text = match read_log_tail(&path, MAX_LOG_TAIL_BYTES) {
+            Ok(text) => text,
+            Err(e) => {
+                self.label(id!(log_file_label)).set_text(cx, &tr_args(
+                    "settings.logs.read_failed",
+                    &[("path", &path.display().to_string()), ("error", &e.to_string())],
+                ));
+                self.redraw(cx);
+                return;
+            }
+        };
+        let min_level = SELECTABLE_LEVELS
+            .get(self.drop_down(id!(level_dropdown)).selected_item())
+            .copied()
+            .unwrap_or(LogLevel::Info);
+        let search = self.text_input(id!(search_input)).text();
+        let lines = filter_log_lines(&text, min_level, &search, MAX_SHOWN_LINES);
+
+        self.label(id!(log_file_label)).set_text(cx, &tr_args(
+            "settings.logs.showing",
+            &[("count", &lines.len().to_string()), ("path", &path.display().to_string())],
+        ));
+        self.label(id!(log_text)).set_text(cx, &lines.join("\n"));
+        self.redraw(cx);
+    }
+}
+
+impl LogViewerRef {
+    /// See [`LogViewer::refresh()`].
+    pub fn refresh(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.refresh(cx, true);
+        }
+    }
+}
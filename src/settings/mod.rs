@@ -0,0 +1,15 @@
+use makepad_widgets::Cx;
+
+pub mod app_settings;
+pub mod diagnostics;
+pub mod keyboard_shortcuts;
+pub mod log_viewer;
+pub mod settings_screen;
+pub mod snippets;
+pub mod storage;
+
+pub fn live_design(cx: &mut Cx) {
+    log_viewer::live_design(cx);
+    snippets::live_design(cx);
+    settings_screen::live_design(cx);
+}
@@ -0,0 +1,1015 @@
+use bytesize::ByteSize;
+use makepad_widgets::*;
+use tracing::{error, info};
+
+use crate::{app_badge::refresh_app_badge, app_lock::{is_valid_pin, start_setting_pin, AppLockAction, AppLockTimeout, MAX_PIN_LEN, MIN_PIN_LEN}, i18n::{self, tr, tr_args, Language}, logging::{apply_log_levels, LogLevel, LogLevels}, network_status::NetworkStatusAction, shared::popup_list::enqueue_popup_notification, spell_check};
+
+use super::{
+    app_settings::{app_settings, update_app_settings, AppBadge, DisplayDensity, EnterKeyBehavior, MediaAutoDownload, ProxyKind, ProxySettings, ReadMarkerClearing, RelativeTimestamps},
+    diagnostics::{export_diagnostics_report, DiagnosticsInfo},
+    log_viewer::LogViewerWidgetRefExt,
+    keyboard_shortcuts::{KeyCombo, KeyboardShortcuts, ShortcutAction},
+    storage::{self, StorageAction, StorageKind, StorageUsage},
+};
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::helpers::*;
+    use crate::shared::icon_button::*;
+    use crate::profile::my_profile_screen::MyProfileScreen;
+    use crate::settings::log_viewer::LogViewer;
+    use crate::settings::snippets::SnippetEditor;
+
+    SettingsSectionTitle = <Label> {
+        width: Fit, height: Fit
+        draw_text: {
+            color: (COLOR_TEXT)
+            text_style: <TITLE_TEXT>{font_size: 12.0}
+        }
+    }
+
+    SettingsDescription = <Label> {
+        width: Fill, height: Fit
+        draw_text: {
+            wrap: Word,
+            color: (MESSAGE_NOTICE_TEXT_COLOR)
+            text_style: <REGULAR_TEXT>{}
+        }
+    }
+
+    SettingsLabel = <Label> {
+        width: Fit, height: Fit
+        draw_text: {
+            color: (COLOR_TEXT)
+            text_style: <REGULAR_TEXT>{}
+        }
+    }
+
+    SettingsTextInput = <RobrixTextInput> {
+        width: 200, height: Fit
+        padding: 8
+        draw_bg: {
+            border_width: 1.0
+        }
+    }
+
+    SettingsRow = <View> {
+        width: Fill, height: Fit
+        flow: Right, spacing: 10
+        align: {y: 0.5}
+    }
+
+    SettingsButton = <RobrixIconButton> {
+        padding: {left: 15, right: 15, top: 10, bottom: 10}
+        draw_bg: {
+            color: (COLOR_SELECTED_PRIMARY)
+        }
+        draw_text: {
+            color: (COLOR_PRIMARY)
+        }
+    }
+
+    // A row showing how much storage is used by one kind of data, with a button to clear it.
+    StorageRow = <SettingsRow> {
+        label = <SettingsLabel> { width: 200 }
+        size = <SettingsLabel> { width: 100, text: "..." }
+        clear_button = <SettingsButton> {}
+    }
+
+    // A row describing one keyboard shortcut, along with an input for its key combination.
+    ShortcutRow = <SettingsRow> {
+        label = <SettingsLabel> { width: 250 }
+        input = <SettingsTextInput> { width: 180 }
+    }
+
+    // A dropdown for choosing a log level. KEEP IN SYNC with `LogLevel::ALL`.
+    LogLevelDropDown = <DropDown> {
+        width: 150, height: Fit
+        labels: ["Off", "Errors", "Warnings", "Info", "Debug", "Trace"]
+    }
+
+    // A section of the settings screen: a title, a short description, and its controls.
+    pub SettingsSection = <View> {
+        width: Fill, height: Fit
+        flow: Down, spacing: 8
+        padding: {top: 10, bottom: 15}
+
+        title = <SettingsSectionTitle> {}
+        description = <SettingsDescription> {}
+    }
+
+    pub SettingsScreen = {{SettingsScreen}} {
+        width: Fill, height: Fill
+        flow: Down
+        show_bg: true
+        draw_bg: {
+            color: (COLOR_PRIMARY)
+        }
+
+        header = <View> {
+            width: Fill, height: Fit
+            flow: Right
+            padding: {top: 20, left: 25, right: 15, bottom: 10}
+            align: {y: 0.5}
+
+            title = <Label> {
+                width: Fit, height: Fit
+                draw_text: {
+                    color: (COLOR_TEXT)
+                    text_style: <TITLE_TEXT>{font_size: 18.0}
+                }
+                text: "Settings"
+            }
+
+            <FillerX> {}
+
+            close_button = <RobrixIconButton> {
+                padding: {left: 10, right: 10}
+                draw_icon: {
+                    svg_file: (ICON_CLOSE)
+                    color: (COLOR_TEXT)
+                }
+                icon_walk: {width: 14, height: 14}
+            }
+        }
+
+        <LineH> {}
+
+        <ScrollYView> {
+            width: Fill, height: Fill
+            flow: Down
+            padding: {left: 25, right: 25, bottom: 30}
+
+            profile_section = <SettingsSection> {
+                <MyProfileScreen> {}
+            }
+
+            <LineH> {}
+
+            language_section = <SettingsSection> {
+                language_dropdown = <DropDown> {
+                    width: 200, height: Fit
+                    labels: ["English", "简体中文"]
+                }
+            }
+
+            <LineH> {}
+
+            display_section = <SettingsSection> {
+                display_density_dropdown = <DropDown> {
+                    width: 200, height: Fit
+                    labels: ["Comfortable", "Compact"]
+                }
+            }
+
+            <LineH> {}
+
+            app_badge_section = <SettingsSection> {
+                app_badge_dropdown = <DropDown> {
+                    width: 200, height: Fit
+                    labels: ["No badge", "Unread mentions", "All unread messages"]
+                }
+            }
+
+            <LineH> {}
+
+            proxy_section = <SettingsSection> {
+                <SettingsRow> {
+                    proxy_enabled_checkbox = <CheckBox> { text: "" }
+                    proxy_enabled_label = <SettingsLabel> {}
+                }
+                <SettingsRow> {
+                    proxy_kind_dropdown = <DropDown> {
+                        width: 100, height: Fit
+                        labels: ["HTTP", "SOCKS5"]
+                    }
+                    proxy_host_input = <SettingsTextInput> {
+                        width: 250
+                        empty_message: ""
+                    }
+                    proxy_port_input = <SettingsTextInput> {
+                        width: 80
+                        empty_message: ""
+                    }
+                }
+                <SettingsRow> {
+                    proxy_username_input = <SettingsTextInput> {
+                        empty_message: ""
+                    }
+                    proxy_password_input = <SettingsTextInput> {
+                        empty_message: ""
+                        draw_text: { text_style: { is_secret: true } }
+                    }
+                }
+                save_proxy_button = <SettingsButton> {}
+            }
+
+            <LineH> {}
+
+            timeline_section = <SettingsSection> {
+                <SettingsRow> {
+                    hide_state_events_checkbox = <CheckBox> { text: "" }
+                    hide_state_events_label = <SettingsLabel> {}
+                }
+                <SettingsRow> {
+                    colorize_usernames_checkbox = <CheckBox> { text: "" }
+                    colorize_usernames_label = <SettingsLabel> {}
+                }
+                <SettingsRow> {
+                    relative_timestamps_label = <SettingsLabel> {}
+                    relative_timestamps_dropdown = <DropDown> {
+                        width: 150, height: Fit
+                        labels: ["Never", "Up to 1 hour", "Up to 1 day", "Up to 1 week"]
+                    }
+                }
+                <SettingsRow> {
+                    read_marker_clearing_label = <SettingsLabel> {}
+                    read_marker_clearing_dropdown = <DropDown> {
+                        width: 200, height: Fit
+                        labels: ["Immediately", "After viewing for a while", "Only when marked as read"]
+                    }
+                }
+                <SettingsRow> {
+                    read_marker_delay_label = <SettingsLabel> { width: 200 }
+                    read_marker_delay_input = <SettingsTextInput> { width: 100 }
+                    save_read_marker_delay_button = <SettingsButton> {}
+                }
+            }
+
+            <LineH> {}
+
+            privacy_section = <SettingsSection> {
+                <SettingsRow> {
+                    send_read_receipts_checkbox = <CheckBox> { text: "" }
+                    send_read_receipts_label = <SettingsLabel> {}
+                }
+                <SettingsRow> {
+                    send_typing_notices_checkbox = <CheckBox> { text: "" }
+                    send_typing_notices_label = <SettingsLabel> {}
+                }
+                <SettingsRow> {
+                    hide_content_when_unfocused_checkbox = <CheckBox> { text: "" }
+                    hide_content_when_unfocused_label = <SettingsLabel> {}
+                }
+            }
+
+            <LineH> {}
+
+            composer_section = <SettingsSection> {
+                <SettingsRow> {
+                    enter_key_behavior_label = <SettingsLabel> { width: 200 }
+                    // KEEP IN SYNC with `EnterKeyBehavior::ALL`.
+                    enter_key_behavior_dropdown = <DropDown> {
+                        width: 300, height: Fit
+                        labels: ["Insert a newline (Ctrl/Cmd+Enter sends)", "Send the message (Ctrl/Cmd+Enter inserts a newline)"]
+                    }
+                }
+                <SettingsRow> {
+                    formatting_toolbar_checkbox = <CheckBox> { text: "" }
+                    formatting_toolbar_label = <SettingsLabel> {}
+                }
+                <SettingsRow> {
+                    spell_check_checkbox = <CheckBox> { text: "" }
+                    spell_check_label = <SettingsLabel> {}
+                }
+                <SettingsRow> {
+                    spell_check_language_label = <SettingsLabel> { width: 200 }
+                    spell_check_language_input = <SettingsTextInput> {
+                        width: 100
+                        empty_message: "en_US"
+                    }
+                    save_spell_check_language_button = <SettingsButton> {}
+                }
+                spell_check_dictionaries = <SettingsDescription> {}
+            }
+
+            <LineH> {}
+
+            snippets_section = <SettingsSection> {
+                snippet_editor = <SnippetEditor> {}
+            }
+
+            <LineH> {}
+
+            app_lock_section = <SettingsSection> {
+                app_lock_status = <SettingsLabel> {}
+                <SettingsRow> {
+                    app_lock_pin_input = <SettingsTextInput> {
+                        width: 150
+                        empty_message: "New PIN"
+                        draw_text: { text_style: { is_secret: true } }
+                    }
+                    set_app_lock_pin_button = <SettingsButton> {}
+                    remove_app_lock_button = <SettingsButton> {}
+                }
+                <SettingsRow> {
+                    app_lock_timeout_label = <SettingsLabel> {}
+                    app_lock_timeout_dropdown = <DropDown> {
+                        width: 200, height: Fit
+                        labels: ["Only on launch", "After 1 minute", "After 5 minutes", "After 15 minutes", "After 1 hour"]
+                    }
+                }
+                <SettingsRow> {
+                    app_lock_biometrics_checkbox = <CheckBox> { text: "" }
+                    app_lock_biometrics_label = <SettingsLabel> {}
+                }
+            }
+
+            <LineH> {}
+
+            storage_section = <SettingsSection> {
+                sdk_store_row = <StorageRow> {}
+                media_cache_row = <StorageRow> {}
+                temp_storage_row = <StorageRow> {}
+                <SettingsRow> {
+                    media_auto_download_label = <SettingsLabel> { width: 200 }
+                    // KEEP IN SYNC with `MediaAutoDownload::ALL`.
+                    media_auto_download_dropdown = <DropDown> {
+                        width: 250, height: Fit
+                        labels: ["Always", "Only when not on a metered network", "Never"]
+                    }
+                }
+                <SettingsRow> {
+                    media_cache_max_label = <SettingsLabel> { width: 200 }
+                    media_cache_max_input = <SettingsTextInput> { width: 100 }
+                    save_media_cache_max_button = <SettingsButton> {}
+                }
+                <SettingsRow> {
+                    temp_storage_max_label = <SettingsLabel> { width: 200 }
+                    temp_storage_max_input = <SettingsTextInput> { width: 100 }
+                }
+                <SettingsRow> {
+                    temp_storage_max_age_label = <SettingsLabel> { width: 200 }
+                    temp_storage_max_age_input = <SettingsTextInput> { width: 100 }
+                    save_temp_storage_limits_button = <SettingsButton> {}
+                }
+            }
+
+            <LineH> {}
+
+            shortcuts_section = <SettingsSection> {
+                next_unread_room_shortcut = <ShortcutRow> {}
+                previous_unread_room_shortcut = <ShortcutRow> {}
+                focus_search_shortcut = <ShortcutRow> {}
+                reply_to_last_message_shortcut = <ShortcutRow> {}
+                toggle_sidebar_shortcut = <ShortcutRow> {}
+                open_settings_shortcut = <ShortcutRow> {}
+                toggle_performance_overlay_shortcut = <ShortcutRow> {}
+                <SettingsRow> {
+                    save_shortcuts_button = <SettingsButton> {}
+                    reset_shortcuts_button = <SettingsButton> {}
+                }
+            }
+
+            <LineH> {}
+
+            diagnostics_section = <SettingsSection> {
+                diagnostics_info = <SettingsLabel> {
+                    width: Fill
+                    draw_text: { wrap: Word }
+                }
+                <SettingsRow> {
+                    export_diagnostics_button = <SettingsButton> {}
+                    copy_diagnostics_button = <SettingsButton> {}
+                }
+            }
+
+            <LineH> {}
+
+            developer_section = <SettingsSection> {
+                <SettingsRow> {
+                    ui_log_level_label = <SettingsLabel> { width: 200 }
+                    ui_log_level_dropdown = <LogLevelDropDown> {}
+                }
+                <SettingsRow> {
+                    sliding_sync_log_level_label = <SettingsLabel> { width: 200 }
+                    sliding_sync_log_level_dropdown = <LogLevelDropDown> {}
+                }
+                <SettingsRow> {
+                    dependencies_log_level_label = <SettingsLabel> { width: 200 }
+                    dependencies_log_level_dropdown = <LogLevelDropDown> {}
+                }
+                log_viewer = <LogViewer> {}
+            }
+        }
+    }
+}
+
+/// Actions emitted by or sent to the `SettingsScreen`.
+#[derive(Clone, Debug, DefaultNone)]
+pub enum SettingsAction {
+    /// The user requested to open the settings screen.
+    Open,
+    /// The user requested to close the settings screen.
+    Close,
+    None,
+}
+
+/// The screen in which the user can view and modify the app settings.
+#[derive(Live, Widget)]
+pub struct SettingsScreen {
+    #[deref] view: View,
+    /// The proxy settings being edited, which are only saved
+    /// once the user clicks the `save_proxy_button`.
+    #[rust] proxy_draft: ProxySettings,
+    /// The language that the dropdowns' option labels were last set in.
+    #[rust] dropdown_labels_language: Option<Language>,
+}
+
+impl LiveHook for SettingsScreen {
+    fn after_new_from_doc(&mut self, cx: &mut Cx) {
+        let settings = app_settings();
+        if let Some(index) = Language::ALL.iter().position(|l| *l == settings.language) {
+            self.drop_down(id!(language_dropdown)).set_selected_item(cx, index);
+        }
+
+        if let Some(index) = DisplayDensity::ALL.iter().position(|d| *d == settings.display_density) {
+            self.drop_down(id!(display_density_dropdown)).set_selected_item(cx, index);
+        }
+        if let Some(index) = AppBadge::ALL.iter().position(|b| *b == settings.app_badge) {
+            self.drop_down(id!(app_badge_dropdown)).set_selected_item(cx, index);
+        }
+
+        self.proxy_draft = settings.proxy.clone();
+        self.check_box(id!(proxy_enabled_checkbox)).set_selected(cx, self.proxy_draft.enabled);
+        if let Some(index) = ProxyKind::ALL.iter().position(|k| *k == self.proxy_draft.kind) {
+            self.drop_down(id!(proxy_kind_dropdown)).set_selected_item(cx, index);
+        }
+        self.text_input(id!(proxy_host_input)).set_text(cx, &self.proxy_draft.host);
+        if self.proxy_draft.port != 0 {
+            self.text_input(id!(proxy_port_input)).set_text(cx, &self.proxy_draft.port.to_string());
+        }
+        self.text_input(id!(proxy_username_input))
+            .set_text(cx, self.proxy_draft.username.as_deref().unwrap_or_default());
+        self.text_input(id!(proxy_password_input))
+            .set_text(cx, self.proxy_draft.password.as_deref().unwrap_or_default());
+
+        self.check_box(id!(hide_state_events_checkbox)).set_selected(cx, settings.hide_state_events);
+        self.check_box(id!(colorize_usernames_checkbox)).set_selected(cx, settings.colorize_usernames);
+        if let Some(index) = RelativeTimestamps::ALL.iter().position(|r| *r == settings.relative_timestamps) {
+            self.drop_down(id!(relative_timestamps_dropdown)).set_selected_item(cx, index);
+        }
+        if let Some(index) = ReadMarkerClearing::ALL.iter().position(|r| *r == settings.read_marker_clearing) {
+            self.drop_down(id!(read_marker_clearing_dropdown)).set_selected_item(cx, index);
+        }
+        self.text_input(id!(read_marker_delay_input)).set_text(cx, &settings.read_marker_delay_secs.to_string());
+        if let Some(index) = MediaAutoDownload::ALL.iter().position(|m| *m == settings.media_auto_download) {
+            self.drop_down(id!(media_auto_download_dropdown)).set_selected_item(cx, index);
+        }
+        self.text_input(id!(media_cache_max_input)).set_text(cx, &settings.media_cache_max_mb.to_string());
+        self.text_input(id!(temp_storage_max_input)).set_text(cx, &settings.temp_storage_max_mb.to_string());
+        self.text_input(id!(temp_storage_max_age_input)).set_text(cx, &settings.temp_storage_max_age_days.to_string());
+        self.check_box(id!(send_read_receipts_checkbox)).set_selected(cx, settings.send_read_receipts);
+        self.check_box(id!(send_typing_notices_checkbox)).set_selected(cx, settings.send_typing_notices);
+        self.check_box(id!(hide_content_when_unfocused_checkbox)).set_selected(cx, settings.hide_content_when_unfocused);
+        if let Some(index) = EnterKeyBehavior::ALL.iter().position(|e| *e == settings.enter_key_behavior) {
+            self.drop_down(id!(enter_key_behavior_dropdown)).set_selected_item(cx, index);
+        }
+        self.check_box(id!(formatting_toolbar_checkbox)).set_selected(cx, settings.show_formatting_toolbar);
+        self.check_box(id!(spell_check_checkbox)).set_selected(cx, settings.spell_check);
+        self.text_input(id!(spell_check_language_input)).set_text(cx, &settings.spell_check_language);
+        if let Some(index) = AppLockTimeout::ALL.iter().position(|t| *t == settings.app_lock.timeout) {
+            self.drop_down(id!(app_lock_timeout_dropdown)).set_selected_item(cx, index);
+        }
+        self.check_box(id!(app_lock_biometrics_checkbox)).set_selected(cx, settings.app_lock.use_biometrics);
+        let log_level_dropdowns = [
+            (id!(ui_log_level_dropdown), settings.log_levels.ui),
+            (id!(sliding_sync_log_level_dropdown), settings.log_levels.sliding_sync),
+            (id!(dependencies_log_level_dropdown), settings.log_levels.dependencies),
+        ];
+        for (dropdown, level) in log_level_dropdowns {
+            if let Some(index) = LogLevel::ALL.iter().position(|l| *l == level) {
+                self.drop_down(dropdown).set_selected_item(cx, index);
+            }
+        }
+
+        let shortcuts = settings.keyboard_shortcuts.clone();
+        drop(settings);
+        self.show_shortcuts(cx, &shortcuts);
+    }
+}
+
+impl SettingsScreen {
+    /// Sets the option labels of each dropdown in the current language,
+    /// if they haven't already been set in that language.
+    fn set_dropdown_labels(&mut self, cx: &mut Cx) {
+        let language = i18n::current_language();
+        if self.dropdown_labels_language == Some(language) { return }
+        self.dropdown_labels_language = Some(language);
+
+        self.drop_down(id!(language_dropdown)).set_labels(
+            cx,
+            Language::ALL.iter().map(|l| l.display_name().to_string()).collect(),
+        );
+        // Each list of keys must be in the same order as the corresponding enum's `ALL` options.
+        let log_level_keys: &[&'static str] = &[
+            "settings.logs.level.off",
+            "settings.logs.level.error",
+            "settings.logs.level.warn",
+            "settings.logs.level.info",
+            "settings.logs.level.debug",
+            "settings.logs.level.trace",
+        ];
+        let dropdowns: [(&[LiveId], &[&'static str]); 10] = [
+            (id!(display_density_dropdown), &["settings.display.comfortable", "settings.display.compact"]),
+            (id!(app_badge_dropdown), &["settings.app_badge.none", "settings.app_badge.mentions", "settings.app_badge.all"]),
+            (id!(relative_timestamps_dropdown), &[
+                "settings.timeline.relative_timestamps.never",
+                "settings.timeline.relative_timestamps.hour",
+                "settings.timeline.relative_timestamps.day",
+                "settings.timeline.relative_timestamps.week",
+            ]),
+            (id!(read_marker_clearing_dropdown), &[
+                "settings.timeline.read_marker_clearing.immediately",
+                "settings.timeline.read_marker_clearing.after_viewing",
+                "settings.timeline.read_marker_clearing.when_marked_read",
+            ]),
+            (id!(enter_key_behavior_dropdown), &[
+                "settings.composer.enter_key_behavior.newline",
+                "settings.composer.enter_key_behavior.send",
+            ]),
+            (id!(app_lock_timeout_dropdown), &[
+                "settings.app_lock.timeout.on_launch",
+                "settings.app_lock.timeout.one_minute",
+                "settings.app_lock.timeout.five_minutes",
+                "settings.app_lock.timeout.fifteen_minutes",
+                "settings.app_lock.timeout.one_hour",
+            ]),
+            (id!(media_auto_download_dropdown), &[
+                "settings.storage.media_auto_download.always",
+                "settings.storage.media_auto_download.unmetered",
+                "settings.storage.media_auto_download.never",
+            ]),
+            (id!(ui_log_level_dropdown), log_level_keys),
+            (id!(sliding_sync_log_level_dropdown), log_level_keys),
+            (id!(dependencies_log_level_dropdown), log_level_keys),
+        ];
+        for (dropdown, keys) in dropdowns {
+            self.drop_down(dropdown).set_labels(cx, keys.iter().map(|key| tr(*key).to_string()).collect());
+        }
+    }
+}
+
+impl Widget for SettingsScreen {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        self.widget_match_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        // These labels are set here (rather than in the DSL) such that
+        // they're always displayed in the currently-selected language.
+        self.set_dropdown_labels(cx);
+        self.label(id!(header.title)).set_text(cx, tr("settings.title"));
+        self.label(id!(profile_section.title)).set_text(cx, tr("settings.profile"));
+        self.label(id!(profile_section.description)).set_text(cx, tr("settings.profile.description"));
+        self.label(id!(language_section.title)).set_text(cx, tr("settings.language"));
+        self.label(id!(language_section.description)).set_text(cx, tr("settings.language.description"));
+        self.label(id!(display_section.title)).set_text(cx, tr("settings.display"));
+        self.label(id!(display_section.description)).set_text(cx, tr("settings.display.description"));
+        self.label(id!(app_badge_section.title)).set_text(cx, tr("settings.app_badge"));
+        self.label(id!(app_badge_section.description)).set_text(cx, tr("settings.app_badge.description"));
+        self.label(id!(proxy_section.title)).set_text(cx, tr("settings.proxy"));
+        self.label(id!(proxy_section.description)).set_text(cx, tr("settings.proxy.description"));
+        self.label(id!(proxy_enabled_label)).set_text(cx, tr("settings.proxy.enabled"));
+        self.button(id!(save_proxy_button)).set_text(cx, tr("settings.proxy.save"));
+        let placeholders = [
+            (id!(proxy_host_input), "settings.proxy.host_placeholder"),
+            (id!(proxy_port_input), "settings.proxy.port_placeholder"),
+            (id!(proxy_username_input), "settings.proxy.username_placeholder"),
+            (id!(proxy_password_input), "settings.proxy.password_placeholder"),
+        ];
+        for (input, key) in placeholders {
+            self.text_input(input).apply_over(cx, live!{ empty_message: (tr(key)) });
+        }
+        self.label(id!(timeline_section.title)).set_text(cx, tr("settings.timeline"));
+        self.label(id!(timeline_section.description)).set_text(cx, tr("settings.timeline.description"));
+        self.label(id!(hide_state_events_label)).set_text(cx, tr("settings.timeline.hide_state_events"));
+        self.label(id!(colorize_usernames_label)).set_text(cx, tr("settings.timeline.colorize_usernames"));
+        self.label(id!(relative_timestamps_label)).set_text(cx, tr("settings.timeline.relative_timestamps"));
+        self.label(id!(read_marker_clearing_label)).set_text(cx, tr("settings.timeline.read_marker_clearing"));
+        self.label(id!(read_marker_delay_label)).set_text(cx, tr("settings.timeline.read_marker_delay"));
+        self.button(id!(save_read_marker_delay_button)).set_text(cx, tr("settings.storage.save"));
+        self.label(id!(privacy_section.title)).set_text(cx, tr("settings.privacy"));
+        self.label(id!(privacy_section.description)).set_text(cx, tr("settings.privacy.description"));
+        self.label(id!(send_read_receipts_label)).set_text(cx, tr("settings.privacy.send_read_receipts"));
+        self.label(id!(send_typing_notices_label)).set_text(cx, tr("settings.privacy.send_typing_notices"));
+        self.label(id!(hide_content_when_unfocused_label)).set_text(cx, tr("settings.privacy.hide_content_when_unfocused"));
+        self.label(id!(composer_section.title)).set_text(cx, tr("settings.composer"));
+        self.label(id!(composer_section.description)).set_text(cx, tr("settings.composer.description"));
+        self.label(id!(enter_key_behavior_label)).set_text(cx, tr("settings.composer.enter_key_behavior"));
+        self.label(id!(formatting_toolbar_label)).set_text(cx, tr("settings.composer.formatting_toolbar"));
+        self.label(id!(spell_check_label)).set_text(cx, tr("settings.composer.spell_check"));
+        self.label(id!(spell_check_language_label)).set_text(cx, tr("settings.composer.spell_check_language"));
+        self.button(id!(save_spell_check_language_button)).set_text(cx, tr("settings.storage.save"));
+        self.label(id!(snippets_section.title)).set_text(cx, tr("settings.snippets"));
+        self.label(id!(snippets_section.description)).set_text(cx, tr("settings.snippets.description"));
+        self.label(id!(app_lock_section.title)).set_text(cx, tr("settings.app_lock"));
+        self.label(id!(app_lock_section.description)).set_text(cx, tr("settings.app_lock.description"));
+        let app_lock_status = if app_settings().app_lock.is_enabled() {
+            tr("settings.app_lock.enabled")
+        } else {
+            tr("settings.app_lock.disabled")
+        };
+        self.label(id!(app_lock_status)).set_text(cx, app_lock_status);
+        self.button(id!(set_app_lock_pin_button)).set_text(cx, tr("settings.app_lock.set_pin"));
+        self.button(id!(remove_app_lock_button)).set_text(cx, tr("settings.app_lock.remove"));
+        self.label(id!(app_lock_timeout_label)).set_text(cx, tr("settings.app_lock.timeout"));
+        self.label(id!(app_lock_biometrics_label)).set_text(cx, tr("settings.app_lock.biometrics"));
+        self.label(id!(storage_section.title)).set_text(cx, tr("settings.storage"));
+        self.label(id!(storage_section.description)).set_text(cx, tr("settings.storage.description"));
+        self.label(id!(sdk_store_row.label)).set_text(cx, tr("settings.storage.sdk_store"));
+        self.label(id!(media_cache_row.label)).set_text(cx, tr("settings.storage.media_cache"));
+        self.label(id!(temp_storage_row.label)).set_text(cx, tr("settings.storage.temp_storage"));
+        self.button(id!(sdk_store_row.clear_button)).set_text(cx, tr("settings.storage.remove_unused"));
+        self.button(id!(media_cache_row.clear_button)).set_text(cx, tr("settings.storage.clear"));
+        self.button(id!(temp_storage_row.clear_button)).set_text(cx, tr("settings.storage.clear"));
+        self.label(id!(media_auto_download_label)).set_text(cx, tr("settings.storage.media_auto_download"));
+        self.label(id!(media_cache_max_label)).set_text(cx, tr("settings.storage.media_cache_max"));
+        self.button(id!(save_media_cache_max_button)).set_text(cx, tr("settings.storage.save"));
+        self.label(id!(temp_storage_max_label)).set_text(cx, tr("settings.storage.temp_storage_max"));
+        self.label(id!(temp_storage_max_age_label)).set_text(cx, tr("settings.storage.temp_storage_max_age"));
+        self.button(id!(save_temp_storage_limits_button)).set_text(cx, tr("settings.storage.save"));
+        self.label(id!(shortcuts_section.title)).set_text(cx, tr("settings.shortcuts"));
+        self.label(id!(shortcuts_section.description)).set_text(cx, tr("settings.shortcuts.description"));
+        for action in ShortcutAction::ALL {
+            self.view(shortcut_row_id(action))
+                .label(id!(label))
+                .set_text(cx, tr(action.description_key()));
+        }
+        self.button(id!(save_shortcuts_button)).set_text(cx, tr("settings.shortcuts.save"));
+        self.button(id!(reset_shortcuts_button)).set_text(cx, tr("settings.shortcuts.reset"));
+        self.label(id!(diagnostics_section.title)).set_text(cx, tr("settings.diagnostics"));
+        self.label(id!(diagnostics_section.description)).set_text(cx, tr("settings.diagnostics.description"));
+        self.button(id!(export_diagnostics_button)).set_text(cx, tr("settings.diagnostics.export"));
+        self.button(id!(copy_diagnostics_button)).set_text(cx, tr("settings.diagnostics.copy"));
+        self.label(id!(developer_section.title)).set_text(cx, tr("settings.logs"));
+        self.label(id!(developer_section.description)).set_text(cx, tr("settings.logs.description"));
+        self.label(id!(ui_log_level_label)).set_text(cx, tr("settings.logs.ui_level"));
+        self.label(id!(sliding_sync_log_level_label)).set_text(cx, tr("settings.logs.sliding_sync_level"));
+        self.label(id!(dependencies_log_level_label)).set_text(cx, tr("settings.logs.dependencies_level"));
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl WidgetMatchEvent for SettingsScreen {
+    fn handle_actions(&mut self, cx: &mut Cx, actions: &Actions, scope: &mut Scope) {
+        if self.button(id!(close_button)).clicked(actions) {
+            cx.widget_action(self.widget_uid(), &scope.path, SettingsAction::Close);
+        }
+
+        if let Some(index) = self.drop_down(id!(language_dropdown)).selected(actions) {
+            if let Some(language) = Language::ALL.get(index).copied() {
+                info!("Changing UI language to {language:?}");
+                i18n::set_language(language);
+                update_app_settings(|s| s.language = language);
+                // Redraw everything such that all translated text is updated.
+                cx.redraw_all();
+            }
+        }
+
+        if let Some(index) = self.drop_down(id!(display_density_dropdown)).selected(actions) {
+            if let Some(density) = DisplayDensity::ALL.get(index).copied() {
+                update_app_settings(|s| s.display_density = density);
+                // Redraw everything such that all timelines and the rooms list adopt the new density.
+                cx.redraw_all();
+            }
+        }
+
+        if let Some(index) = self.drop_down(id!(app_badge_dropdown)).selected(actions) {
+            if let Some(app_badge) = AppBadge::ALL.get(index).copied() {
+                update_app_settings(|s| s.app_badge = app_badge);
+                refresh_app_badge();
+            }
+        }
+
+        if let Some(enabled) = self.check_box(id!(proxy_enabled_checkbox)).changed(actions) {
+            self.proxy_draft.enabled = enabled;
+        }
+        if let Some(index) = self.drop_down(id!(proxy_kind_dropdown)).selected(actions) {
+            self.proxy_draft.kind = ProxyKind::ALL.get(index).copied().unwrap_or_default();
+        }
+        if self.button(id!(save_proxy_button)).clicked(actions) {
+            self.save_proxy_settings();
+        }
+
+        if let Some(hide) = self.check_box(id!(hide_state_events_checkbox)).changed(actions) {
+            update_app_settings(|s| s.hide_state_events = hide);
+            cx.redraw_all();
+        }
+        if let Some(colorize) = self.check_box(id!(colorize_usernames_checkbox)).changed(actions) {
+            update_app_settings(|s| s.colorize_usernames = colorize);
+            cx.redraw_all();
+        }
+        if let Some(index) = self.drop_down(id!(relative_timestamps_dropdown)).selected(actions) {
+            if let Some(relative_timestamps) = RelativeTimestamps::ALL.get(index).copied() {
+                update_app_settings(|s| s.relative_timestamps = relative_timestamps);
+                cx.redraw_all();
+            }
+        }
+        if let Some(index) = self.drop_down(id!(read_marker_clearing_dropdown)).selected(actions) {
+            if let Some(read_marker_clearing) = ReadMarkerClearing::ALL.get(index).copied() {
+                update_app_settings(|s| s.read_marker_clearing = read_marker_clearing);
+            }
+        }
+        if self.button(id!(save_read_marker_delay_button)).clicked(actions) {
+            match self.text_input(id!(read_marker_delay_input)).text().trim().parse::<u32>() {
+                Ok(delay_secs) if delay_secs > 0 => {
+                    update_app_settings(|s| s.read_marker_delay_secs = delay_secs);
+                    enqueue_popup_notification(tr("settings.timeline.read_marker_delay_saved").to_string());
+                }
+                _ => enqueue_popup_notification(tr("settings.timeline.invalid_read_marker_delay").to_string()),
+            }
+        }
+        if let Some(enabled) = self.check_box(id!(send_read_receipts_checkbox)).changed(actions) {
+            update_app_settings(|s| s.send_read_receipts = enabled);
+        }
+        if let Some(enabled) = self.check_box(id!(send_typing_notices_checkbox)).changed(actions) {
+            update_app_settings(|s| s.send_typing_notices = enabled);
+        }
+        if let Some(enabled) = self.check_box(id!(hide_content_when_unfocused_checkbox)).changed(actions) {
+            update_app_settings(|s| s.hide_content_when_unfocused = enabled);
+        }
+
+        if let Some(index) = self.drop_down(id!(enter_key_behavior_dropdown)).selected(actions) {
+            if let Some(enter_key_behavior) = EnterKeyBehavior::ALL.get(index).copied() {
+                update_app_settings(|s| s.enter_key_behavior = enter_key_behavior);
+            }
+        }
+        if let Some(shown) = self.check_box(id!(formatting_toolbar_checkbox)).changed(actions) {
+            update_app_settings(|s| s.show_formatting_toolbar = shown);
+            cx.redraw_all();
+        }
+        if let Some(enabled) = self.check_box(id!(spell_check_checkbox)).changed(actions) {
+            update_app_settings(|s| s.spell_check = enabled);
+            spell_check::load_dictionary_from_settings();
+        }
+        if self.button(id!(save_spell_check_language_button)).clicked(actions) {
+            let language = self.text_input(id!(spell_check_language_input)).text().trim().to_owned();
+            if spell_check::available_languages().contains(&language) {
+                update_app_settings(|s| s.spell_check_language = language);
+                spell_check::load_dictionary_from_settings();
+                enqueue_popup_notification(tr("settings.composer.spell_check_language_saved").to_string());
+            } else {
+                enqueue_popup_notification(tr_args(
+                    "settings.composer.unknown_spell_check_language",
+                    &[("language", &language)],
+                ));
+            }
+        }
+
+        if self.button(id!(set_app_lock_pin_button)).clicked(actions) {
+            let pin_input = self.text_input(id!(app_lock_pin_input));
+            let pin = pin_input.text();
+            if is_valid_pin(pin.trim()) {
+                start_setting_pin(pin.trim().to_owned());
+                pin_input.set_text(cx, "");
+            } else {
+                enqueue_popup_notification(tr_args(
+                    "settings.app_lock.invalid_pin",
+                    &[("min", &MIN_PIN_LEN.to_string()), ("max", &MAX_PIN_LEN.to_string())],
+                ));
+            }
+        }
+        if self.button(id!(remove_app_lock_button)).clicked(actions) {
+            update_app_settings(|s| s.app_lock.remove_pin());
+            enqueue_popup_notification(tr("settings.app_lock.removed").to_string());
+            self.redraw(cx);
+        }
+        if let Some(index) = self.drop_down(id!(app_lock_timeout_dropdown)).selected(actions) {
+            if let Some(timeout) = AppLockTimeout::ALL.get(index).copied() {
+                update_app_settings(|s| s.app_lock.timeout = timeout);
+            }
+        }
+        if let Some(enabled) = self.check_box(id!(app_lock_biometrics_checkbox)).changed(actions) {
+            update_app_settings(|s| s.app_lock.use_biometrics = enabled);
+        }
+
+        for (row_id, kind) in [
+            (id!(sdk_store_row.clear_button), StorageKind::UnusedSdkStores),
+            (id!(media_cache_row.clear_button), StorageKind::MediaCache),
+            (id!(temp_storage_row.clear_button), StorageKind::TempStorage),
+        ] {
+            if self.button(row_id).clicked(actions) {
+                storage::request_clear_storage(kind);
+            }
+        }
+        let log_level_dropdowns: [(&[LiveId], fn(&mut LogLevels) -> &mut LogLevel); 3] = [
+            (id!(ui_log_level_dropdown), |levels| &mut levels.ui),
+            (id!(sliding_sync_log_level_dropdown), |levels| &mut levels.sliding_sync),
+            (id!(dependencies_log_level_dropdown), |levels| &mut levels.dependencies),
+        ];
+        for (dropdown, level_of) in log_level_dropdowns {
+            let Some(level) = self.drop_down(dropdown).selected(actions).and_then(|i| LogLevel::ALL.get(i).copied()) else { continue };
+            update_app_settings(|s| *level_of(&mut s.log_levels) = level);
+            apply_log_levels(&app_settings().log_levels);
+        }
+        if let Some(index) = self.drop_down(id!(media_auto_download_dropdown)).selected(actions) {
+            if let Some(media_auto_download) = MediaAutoDownload::ALL.get(index).copied() {
+                update_app_settings(|s| s.media_auto_download = media_auto_download);
+                Cx::post_action(NetworkStatusAction::AutoDownloadSettingChanged);
+            }
+        }
+        if self.button(id!(save_media_cache_max_button)).clicked(actions) {
+            match self.text_input(id!(media_cache_max_input)).text().trim().parse::<u32>() {
+                Ok(max_mb) if max_mb > 0 => {
+                    update_app_settings(|s| s.media_cache_max_mb = max_mb);
+                    enqueue_popup_notification(tr("settings.storage.media_cache_max_saved").to_string());
+                }
+                _ => enqueue_popup_notification(tr("settings.storage.invalid_media_cache_max").to_string()),
+            }
+        }
+        if self.button(id!(save_temp_storage_limits_button)).clicked(actions) {
+            let max_mb = self.text_input(id!(temp_storage_max_input)).text().trim().parse::<u32>();
+            let max_age_days = self.text_input(id!(temp_storage_max_age_input)).text().trim().parse::<u32>();
+            match (max_mb, max_age_days) {
+                (Ok(max_mb), Ok(max_age_days)) if max_mb > 0 && max_age_days > 0 => {
+                    update_app_settings(|s| {
+                        s.temp_storage_max_mb = max_mb;
+                        s.temp_storage_max_age_days = max_age_days;
+                    });
+                    storage::request_storage_usage();
+                    enqueue_popup_notification(tr("settings.storage.temp_storage_limits_saved").to_string());
+                }
+                _ => enqueue_popup_notification(tr("settings.storage.invalid_temp_storage_limits").to_string()),
+            }
+        }
+        for action in actions {
+            if let Some(AppLockAction::PinSet) = action.downcast_ref() {
+                self.redraw(cx);
+            }
+            match action.downcast_ref() {
+                Some(StorageAction::Usage(usage)) => self.show_storage_usage(cx, usage),
+                Some(StorageAction::Cleared { freed, .. }) => enqueue_popup_notification(tr_args(
+                    "settings.storage.cleared",
+                    &[("size", &ByteSize::b(*freed).to_string())],
+                )),
+                Some(StorageAction::ClearFailed { error, .. }) => enqueue_popup_notification(tr_args(
+                    "settings.storage.clear_failed",
+                    &[("error", error)],
+                )),
+                None => {}
+            }
+        }
+
+        if self.button(id!(export_diagnostics_button)).clicked(actions) {
+            match export_diagnostics_report(&DiagnosticsInfo::collect()) {
+                Ok(path) => enqueue_popup_notification(tr_args(
+                    "settings.diagnostics.exported",
+                    &[("path", &path.display().to_string())],
+                )),
+                Err(e) => {
+                    error!("Failed to export diagnostics report: {e:?}");
+                    enqueue_popup_notification(tr_args(
+                        "settings.diagnostics.export_failed",
+                        &[("error", &e.to_string())],
+                    ));
+                }
+            }
+        }
+        if self.button(id!(copy_diagnostics_button)).clicked(actions) {
+            cx.copy_to_clipboard(&DiagnosticsInfo::collect().to_report());
+            enqueue_popup_notification(tr("settings.diagnostics.copied").to_string());
+        }
+
+        if self.button(id!(save_shortcuts_button)).clicked(actions) {
+            self.save_shortcuts();
+        }
+        if self.button(id!(reset_shortcuts_button)).clicked(actions) {
+            update_app_settings(|s| s.keyboard_shortcuts.reset_to_defaults());
+            self.show_shortcuts(cx, &KeyboardShortcuts::default());
+            enqueue_popup_notification(tr("settings.shortcuts.saved").to_string());
+        }
+    }
+}
+
+impl SettingsScreen {
+    /// Validates the proxy settings entered by the user and saves them.
+    fn save_proxy_settings(&mut self) {
+        let port_text = self.text_input(id!(proxy_port_input)).text();
+        let port = match port_text.trim() {
+            "" => 0,
+            p => match p.parse::<u16>() {
+                Ok(port) => port,
+                Err(_) => {
+                    enqueue_popup_notification(tr("settings.proxy.invalid_port").to_string());
+                    return;
+                }
+            },
+        };
+        let non_empty = |s: String| (!s.trim().is_empty()).then_some(s);
+        self.proxy_draft.host = self.text_input(id!(proxy_host_input)).text().trim().to_string();
+        self.proxy_draft.port = port;
+        self.proxy_draft.username = non_empty(self.text_input(id!(proxy_username_input)).text());
+        self.proxy_draft.password = non_empty(self.text_input(id!(proxy_password_input)).text());
+
+        if self.proxy_draft.enabled && self.proxy_draft.url().is_none() {
+            enqueue_popup_notification(tr("settings.proxy.invalid").to_string());
+            return;
+        }
+        let proxy = self.proxy_draft.clone();
+        update_app_settings(|s| s.proxy = proxy);
+        enqueue_popup_notification(tr("settings.proxy.saved").to_string());
+    }
+
+    /// Displays the current diagnostic info in the diagnostics section.
+    fn show_diagnostics(&mut self, cx: &mut Cx) {
+        self.label(id!(diagnostics_info)).set_text(cx, &DiagnosticsInfo::collect().to_report());
+    }
+
+    /// Displays the languages of all installed spell checking dictionaries.
+    fn show_spell_check_dictionaries(&mut self, cx: &mut Cx) {
+        let languages = spell_check::available_languages();
+        let text = if languages.is_empty() {
+            tr("settings.composer.no_dictionaries").to_string()
+        } else {
+            tr_args("settings.composer.dictionaries", &[("languages", &languages.join(", "))])
+        };
+        self.label(id!(spell_check_dictionaries)).set_text(cx, &text);
+    }
+
+    /// Displays the given storage usage in the storage section.
+    fn show_storage_usage(&mut self, cx: &mut Cx, usage: &StorageUsage) {
+        self.label(id!(sdk_store_row.size)).set_text(cx, &ByteSize::b(usage.sdk_store).to_string());
+        self.label(id!(media_cache_row.size)).set_text(cx, &ByteSize::b(usage.media_cache).to_string());
+        self.label(id!(temp_storage_row.size)).set_text(cx, &tr_args(
+            "settings.storage.usage_of_limit",
+            &[
+                ("size", &ByteSize::b(usage.temp_storage).to_string()),
+                ("limit", &ByteSize::b(app_settings().temp_storage_max_bytes()).to_string()),
+            ],
+        ));
+    }
+
+    /// Populates the shortcut inputs with the key combinations from the given shortcuts.
+    fn show_shortcuts(&mut self, cx: &mut Cx, shortcuts: &KeyboardShortcuts) {
+        for action in ShortcutAction::ALL {
+            self.view(shortcut_row_id(action))
+                .text_input(id!(input))
+                .set_text(cx, &shortcuts.combo_for(action).to_string());
+        }
+    }
+
+    /// Parses the key combinations entered by the user and saves them.
+    ///
+    /// Nothing is saved if any key combination is invalid or bound to more than one action.
+    fn save_shortcuts(&mut self) {
+        let mut shortcuts = KeyboardShortcuts::default();
+        let mut used_combos: Vec<KeyCombo> = Vec::with_capacity(ShortcutAction::ALL.len());
+        for action in ShortcutAction::ALL {
+            let text = self.view(shortcut_row_id(action)).text_input(id!(input)).text();
+            let combo = match text.parse::<KeyCombo>() {
+                Ok(combo) => combo,
+                Err(error) => {
+                    enqueue_popup_notification(tr_args(
+                        "settings.shortcuts.invalid",
+                        &[("action", tr(action.description_key())), ("error", &error)],
+                    ));
+                    return;
+                }
+            };
+            if used_combos.contains(&combo) {
+                enqueue_popup_notification(tr_args(
+                    "settings.shortcuts.duplicate",
+                    &[("combo", &combo.to_string())],
+                ));
+                return;
+            }
+            used_combos.push(combo);
+            shortcuts.set(action, combo);
+        }
+        update_app_settings(|s| s.keyboard_shortcuts = shortcuts);
+        enqueue_popup_notification(tr("settings.shortcuts.saved").to_string());
+    }
+}
+
+impl SettingsScreenRef {
+    /// Refreshes any info in the settings screen that may have changed since it was last shown.
+    ///
+    /// This should be called whenever the settings screen is shown.
+    pub fn on_shown(&self, cx: &mut Cx) {
+        storage::request_storage_usage();
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.show_diagnostics(cx);
+            inner.show_spell_check_dictionaries(cx);
+            inner.log_viewer(id!(log_viewer)).refresh(cx);
+        }
+    }
+}
+
+/// Returns the widget ID of the settings row for the given shortcut action.
+fn shortcut_row_id(action: ShortcutAction) -> &'static [LiveId] {
+    match action {
+        ShortcutAction::NextUnreadRoom => id!(next_unread_room_shortcut),
+        ShortcutAction::PreviousUnreadRoom => id!(previous_unread_room_shortcut),
+        ShortcutAction::FocusSearch => id!(focus_search_shortcut),
+        ShortcutAction::ReplyToLastMessage => id!(reply_to_last_message_shortcut),
+        ShortcutAction::ToggleSidebar => id!(toggle_sidebar_shortcut),
+        ShortcutAction::OpenSettings => id!(open_settings_shortcut),
+        ShortcutAction::TogglePerformanceOverlay => id!(toggle_performance_overlay_shortcut),
+    }
+}
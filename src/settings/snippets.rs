@@ -0,0 +1,347 @@
+//! Text-expansion snippets: saved blocks of text that are inserted into the message composer
+//! by typing their trigger, e.g., `/sig` for a signature.
+//!
+//! A snippet is expanded either by selecting it from the autocomplete suggestions
+//! shown while typing `/` followed by part of its name, or by typing its full trigger followed by a space.
+
+use makepad_widgets::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    i18n::{tr, tr_args},
+    shared::popup_list::enqueue_popup_notification,
+};
+
+use super::app_settings::{app_settings, update_app_settings};
+
+/// The character that starts a snippet trigger.
+pub const SNIPPET_TRIGGER: char = '/';
+/// The maximum length of a snippet's name, in characters.
+pub const MAX_SNIPPET_NAME_LEN: usize = 32;
+/// Names that can't be used for snippets because they're already used by message commands.
+const RESERVED_NAMES: &[&str] = &["html", "plain"];
+
+/// A saved block of text that is inserted by typing `/` followed by its name.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snippet {
+    /// The name of this snippet, without the leading `/`.
+    pub name: String,
+    /// The text that this snippet expands to.
+    pub text: String,
+}
+
+impl Snippet {
+    /// Returns the trigger that expands this snippet, e.g., `/sig`.
+    pub fn trigger(&self) -> String {
+        format!("{SNIPPET_TRIGGER}{}", self.name)
+    }
+}
+
+/// The reasons why a snippet's name can't be used.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidSnippetName {
+    Empty,
+    TooLong,
+    InvalidCharacters,
+    Reserved,
+}
+
+impl InvalidSnippetName {
+    /// Returns the i18n key of this error's user-facing description.
+    pub fn description_key(self) -> &'static str {
+        match self {
+            InvalidSnippetName::Empty => "snippets.name_empty",
+            InvalidSnippetName::TooLong => "snippets.name_too_long",
+            InvalidSnippetName::InvalidCharacters => "snippets.name_invalid_characters",
+            InvalidSnippetName::Reserved => "snippets.name_reserved",
+        }
+    }
+}
+
+/// Validates the given snippet name (with or without the leading `/`),
+/// returning it in its normalized form: lowercase and without the leading `/`.
+pub fn validate_snippet_name(name: &str) -> Result<String, InvalidSnippetName> {
+    let name = name.trim();
+    let name = name.strip_prefix(SNIPPET_TRIGGER).unwrap_or(name).to_lowercase();
+    if name.is_empty() {
+        Err(InvalidSnippetName::Empty)
+    } else if name.chars().count() > MAX_SNIPPET_NAME_LEN {
+        Err(InvalidSnippetName::TooLong)
+    } else if !name.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
+        Err(InvalidSnippetName::InvalidCharacters)
+    } else if RESERVED_NAMES.contains(&name.as_str()) {
+        Err(InvalidSnippetName::Reserved)
+    } else {
+        Ok(name)
+    }
+}
+
+/// Returns the snippets whose names start with the given query (without the `/`), sorted by name.
+pub fn matching_snippets<'s>(query: &str, snippets: &'s [Snippet]) -> Vec<&'s Snippet> {
+    let query = query.to_lowercase();
+    let mut matches = snippets.iter()
+        .filter(|snippet| snippet.name.starts_with(&query))
+        .collect::<Vec<_>>();
+    matches.sort_by(|a, b| a.name.cmp(&b.name));
+    matches
+}
+
+/// If the given text ends with a snippet's full trigger followed by a single space or newline,
+/// returns the text with that trigger replaced by the snippet's text.
+///
+/// The trigger must be at the start of the text or follow whitespace.
+pub fn expand_trailing_snippet(text: &str, snippets: &[Snippet]) -> Option<String> {
+    let separator = text.chars().next_back().filter(|c| *c == ' ' || *c == '\n')?;
+    let without_separator = &text[..text.len() - separator.len_utf8()];
+    let start = without_separator.rfind(char::is_whitespace)
+        .map(|i| i + without_separator[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let name = without_separator[start..].strip_prefix(SNIPPET_TRIGGER)?;
+    let snippet = snippets.iter().find(|snippet| snippet.name == name)?;
+    Some(format!("{}{}{separator}", &text[..start], snippet.text))
+}
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::*;
+
+    // A row showing one saved snippet, with a button to remove it.
+    SnippetRow = <View> {
+        width: Fill, height: Fit
+        flow: Right, spacing: 10
+        align: {y: 0.5}
+        padding: {top: 2, bottom: 2}
+
+        trigger = <Label> {
+            width: 120, height: Fit
+            draw_text: {
+                color: (COLOR_TEXT)
+                text_style: <REGULAR_TEXT>{}
+            }
+        }
+        text = <Label> {
+            width: Fill, height: Fit
+            draw_text: {
+                wrap: Ellipsis,
+                color: (MESSAGE_NOTICE_TEXT_COLOR)
+                text_style: <REGULAR_TEXT>{}
+            }
+        }
+        remove_button = <RobrixIconButton> {
+            padding: {left: 8, right: 8, top: 4, bottom: 4}
+            draw_icon: {
+                svg_file: (ICON_CLOSE)
+                color: (COLOR_DANGER_RED),
+            }
+            icon_walk: {width: 10, height: 10}
+            draw_text: {
+                color: (COLOR_DANGER_RED),
+            }
+        }
+    }
+
+    SnippetRows = {{SnippetRows}} {
+        width: Fill, height: Fit
+        flow: Down
+        item: <SnippetRow> {}
+    }
+
+    pub SnippetEditor = {{SnippetEditor}} {
+        width: Fill, height: Fit
+        flow: Down, spacing: 8
+
+        snippet_rows = <SnippetRows> {}
+
+        <View> {
+            width: Fill, height: Fit
+            flow: Right, spacing: 10
+            align: {y: 0.0}
+
+            name_input = <RobrixTextInput> {
+                width: 120, height: Fit
+                padding: 8
+                draw_bg: {
+                    border_width: 1.0
+                }
+                empty_message: "/sig"
+            }
+            content_input = <RobrixTextInput> {
+                width: Fill, height: Fit
+                padding: 8
+                draw_bg: {
+                    border_width: 1.0
+                }
+                empty_message: "Text to insert"
+            }
+            add_button = <RobrixIconButton> {
+                padding: {left: 15, right: 15, top: 10, bottom: 10}
+                draw_bg: {
+                    color: (COLOR_SELECTED_PRIMARY)
+                }
+                draw_text: {
+                    color: (COLOR_PRIMARY)
+                }
+            }
+        }
+    }
+}
+
+/// Lets the user add and remove their text-expansion snippets.
+#[derive(Live, Widget)]
+pub struct SnippetEditor {
+    #[deref] view: View,
+}
+
+impl LiveHook for SnippetEditor {
+    fn after_new_from_doc(&mut self, cx: &mut Cx) {
+        self.refresh(cx);
+    }
+}
+
+impl Widget for SnippetEditor {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        let Event::Actions(actions) = event else { return };
+
+        if self.button(id!(add_button)).clicked(actions) {
+            self.add_snippet(cx);
+        }
+        let removed = self.snippet_rows(id!(snippet_rows)).borrow().and_then(|rows|
+            rows.children.iter()
+                .find(|(row, _)| row.button(id!(remove_button)).clicked(actions))
+                .map(|(_, name)| name.clone())
+        );
+        if let Some(name) = removed {
+            update_app_settings(|s| s.snippets.retain(|snippet| snippet.name != name));
+            self.refresh(cx);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.button(id!(add_button)).set_text(cx, tr("snippets.add"));
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl SnippetEditor {
+    /// Validates the entered snippet and saves it, replacing any existing snippet with the same name.
+    fn add_snippet(&mut self, cx: &mut Cx) {
+        let name_input = self.text_input(id!(name_input));
+        let content_input = self.text_input(id!(content_input));
+        let name = match validate_snippet_name(&name_input.text()) {
+            Ok(name) => name,
+            Err(e) => {
+                enqueue_popup_notification(tr(e.description_key()).to_string());
+                return;
+            }
+        };
+        let text = content_input.text();
+        if text.trim().is_empty() {
+            enqueue_popup_notification(tr("snippets.text_empty").to_string());
+            return;
+        }
+        let snippet = Snippet { name, text };
+        let trigger = snippet.trigger();
+        update_app_settings(|s| {
+            s.snippets.retain(|existing| existing.name != snippet.name);
+            s.snippets.push(snippet);
+        });
+        enqueue_popup_notification(tr_args("snippets.saved", &[("trigger", &trigger)]));
+        name_input.set_text(cx, "");
+        content_input.set_text(cx, "");
+        self.refresh(cx);
+    }
+
+    /// Rebuilds the list of snippets from the app settings.
+    fn refresh(&mut self, cx: &mut Cx) {
+        let mut snippets = app_settings().snippets.clone();
+        snippets.sort_by(|a, b| a.name.cmp(&b.name));
+        let snippet_rows = self.snippet_rows(id!(snippet_rows));
+        let Some(mut rows) = snippet_rows.borrow_mut() else { return };
+        rows.children.clear();
+        for snippet in snippets {
+            let row = WidgetRef::new_from_ptr(cx, rows.item);
+            row.label(id!(trigger)).set_text(cx, &snippet.trigger());
+            row.label(id!(text)).set_text(cx, snippet.text.lines().next().unwrap_or_default());
+            row.button(id!(remove_button)).set_text(cx, tr("snippets.remove"));
+            rows.children.push((row, snippet.name));
+        }
+        rows.redraw(cx);
+    }
+}
+
+/// The rows of saved snippets within a [`SnippetEditor`].
+#[derive(Live, LiveHook, Widget)]
+struct SnippetRows {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    /// Each displayed row along with the name of the snippet it shows.
+    #[rust] children: Vec<(WidgetRef, String)>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for SnippetRows {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (row, _) in &self.children {
+            row.handle_event(cx, event, scope);
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (row, _) in &self.children {
+            let _ = row.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+#[cfg(test)]
+mod tests_snippets {
+    use super::*;
+
+    fn snippets() -> Vec<Snippet> {
+        vec![
+            Snippet { name: "sig".into(), text: "Best regards,\nThe Support Team".into() },
+            Snippet { name: "signup".into(), text: "https://example.org/signup".into() },
+            Snippet { name: "hi".into(), text: "Hello! How can I help?".into() },
+        ]
+    }
+
+    #[test]
+    fn test_validate_snippet_name() {
+        assert_eq!(validate_snippet_name(" /Sig "), Ok("sig".to_owned()));
+        assert_eq!(validate_snippet_name("thanks-2"), Ok("thanks-2".to_owned()));
+        assert_eq!(validate_snippet_name("/"), Err(InvalidSnippetName::Empty));
+        assert_eq!(validate_snippet_name("two words"), Err(InvalidSnippetName::InvalidCharacters));
+        assert_eq!(validate_snippet_name("/html"), Err(InvalidSnippetName::Reserved));
+        assert_eq!(validate_snippet_name(&"a".repeat(MAX_SNIPPET_NAME_LEN + 1)), Err(InvalidSnippetName::TooLong));
+    }
+
+    #[test]
+    fn test_matching_snippets() {
+        let snippets = snippets();
+        let names = |query| matching_snippets(query, &snippets).into_iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names("si"), ["sig", "signup"]);
+        assert_eq!(names("H"), ["hi"]);
+        assert!(names("x").is_empty());
+    }
+
+    #[test]
+    fn test_expand_trailing_snippet() {
+        let snippets = snippets();
+        assert_eq!(
+            expand_trailing_snippet("Thanks! /sig ", &snippets).as_deref(),
+            Some("Thanks! Best regards,\nThe Support Team "),
+        );
+        assert_eq!(expand_trailing_snippet("/hi\n", &snippets).as_deref(), Some("Hello! How can I help?\n"));
+        assert_eq!(expand_trailing_snippet("/sig", &snippets), None);
+        assert_eq!(expand_trailing_snippet("a/sig ", &snippets), None);
+        assert_eq!(expand_trailing_snippet("/si ", &snippets), None);
+    }
+}
@@ -0,0 +1,261 @@
+//! Measuring and clearing the storage used by Robrix on this device.
+//!
+//! Walking directory trees can be slow, so all filesystem work is done
+//! on a background thread, which then posts a [`StorageAction`] to the UI thread.
+
+use std::{io, path::{Path, PathBuf}, time::{Duration, SystemTime}};
+
+use makepad_widgets::Cx;
+use tracing::{error, info};
+
+use crate::{app_data_dir, media_cache, persistent_state, settings::app_settings::app_settings, temp_storage::get_temp_dir_path};
+
+/// The prefix of the name of each client database directory within the app data directory.
+const DB_DIR_PREFIX: &str = "db_";
+
+/// The amount of storage (in bytes) used by each kind of data that Robrix stores.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StorageUsage {
+    /// The Matrix SDK databases, which hold the state and encryption keys of each session.
+    pub sdk_store: u64,
+    /// The in-memory cache of fetched media, e.g., images.
+    pub media_cache: u64,
+    /// Temporary files, which are safe to delete at any time.
+    pub temp_storage: u64,
+}
+
+/// The kinds of storage that can be cleared from the settings screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StorageKind {
+    /// SDK databases that are no longer used by any saved session.
+    UnusedSdkStores,
+    MediaCache,
+    TempStorage,
+}
+
+/// Actions posted from the background thread that measures or clears storage.
+#[derive(Clone, Debug)]
+pub enum StorageAction {
+    /// The storage usage has been measured.
+    Usage(StorageUsage),
+    /// The given kind of storage has been cleared, freeing up the given number of bytes.
+    Cleared { kind: StorageKind, freed: u64 },
+    /// Clearing the given kind of storage failed.
+    ClearFailed { kind: StorageKind, error: String },
+}
+
+/// Spawns a background thread to measure the storage usage,
+/// which is posted to the UI thread as a [`StorageAction::Usage`].
+pub fn request_storage_usage() {
+    std::thread::spawn(|| {
+        Cx::post_action(StorageAction::Usage(measure_storage_usage()));
+    });
+}
+
+/// Spawns a background thread to clear the given kind of storage.
+///
+/// Once done, a [`StorageAction::Cleared`] or [`StorageAction::ClearFailed`] is posted,
+/// followed by a [`StorageAction::Usage`] with the updated storage usage.
+pub fn request_clear_storage(kind: StorageKind) {
+    std::thread::spawn(move || {
+        let result = match kind {
+            StorageKind::UnusedSdkStores => clear_unused_sdk_stores(),
+            StorageKind::MediaCache => {
+                let freed = media_cache::total_media_cache_size();
+                media_cache::clear_all_media_caches();
+                Ok(freed)
+            }
+            StorageKind::TempStorage => clear_dir_contents(get_temp_dir_path()),
+        };
+        match result {
+            Ok(freed) => {
+                info!("Cleared {kind:?}, freeing {freed} bytes.");
+                Cx::post_action(StorageAction::Cleared { kind, freed });
+            }
+            Err(e) => {
+                error!("Failed to clear {kind:?}: {e:?}");
+                Cx::post_action(StorageAction::ClearFailed { kind, error: e.to_string() });
+            }
+        }
+        Cx::post_action(StorageAction::Usage(measure_storage_usage()));
+    });
+}
+
+/// Spawns a background thread to remove temporary files that exceed the limits in the app settings.
+///
+/// This should be called once at startup, before any temporary files are created.
+pub fn request_temp_storage_cleanup() {
+    let (max_age, max_bytes) = {
+        let settings = app_settings();
+        (settings.temp_storage_max_age(), settings.temp_storage_max_bytes())
+    };
+    std::thread::spawn(move || {
+        match clean_up_temp_storage(get_temp_dir_path(), max_age, max_bytes) {
+            Ok(0) => { }
+            Ok(freed) => info!("Cleaned up temporary files, freeing {freed} bytes."),
+            Err(e) => error!("Failed to clean up temporary files: {e:?}"),
+        }
+    });
+}
+
+/// A file or directory directly within the temp directory.
+struct TempEntry {
+    path: PathBuf,
+    /// How long ago this entry was last modified.
+    age: Duration,
+    size: u64,
+}
+
+/// Removes all entries in the given temp directory that are older than `max_age`,
+/// and then the oldest remaining entries until their total size is at most `max_bytes`.
+///
+/// Returns the number of bytes freed.
+fn clean_up_temp_storage(dir: &Path, max_age: Duration, max_bytes: u64) -> io::Result<u64> {
+    let now = SystemTime::now();
+    let entries = std::fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .map(|entry| {
+            let path = entry.path();
+            let age = entry.metadata()
+                .and_then(|m| m.modified())
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .unwrap_or_default();
+            let size = dir_size(&path);
+            TempEntry { path, age, size }
+        })
+        .collect();
+
+    let mut freed = 0;
+    for entry in temp_entries_to_remove(entries, max_age, max_bytes) {
+        let result = if entry.path.is_dir() {
+            std::fs::remove_dir_all(&entry.path)
+        } else {
+            std::fs::remove_file(&entry.path)
+        };
+        match result {
+            Ok(()) => freed += entry.size,
+            // A file that is still in use may fail to be removed, which is fine.
+            Err(e) => error!("Failed to remove temporary file {}: {e}", entry.path.display()),
+        }
+    }
+    Ok(freed)
+}
+
+/// Returns the entries that must be removed to satisfy the given age and size limits,
+/// preferring to remove older entries first.
+fn temp_entries_to_remove(mut entries: Vec<TempEntry>, max_age: Duration, max_bytes: u64) -> Vec<TempEntry> {
+    // Sort from newest to oldest, such that the oldest entries can be popped off the end.
+    entries.sort_by_key(|entry| entry.age);
+    let mut total_size: u64 = entries.iter().map(|entry| entry.size).sum();
+    let mut to_remove = Vec::new();
+    while let Some(oldest) = entries.pop() {
+        if oldest.age <= max_age && total_size <= max_bytes {
+            break;
+        }
+        total_size -= oldest.size;
+        to_remove.push(oldest);
+    }
+    to_remove
+}
+
+fn measure_storage_usage() -> StorageUsage {
+    let sdk_store = sdk_store_dirs()
+        .map(|dirs| dirs.iter().map(|dir| dir_size(dir)).sum())
+        .unwrap_or_default();
+    StorageUsage {
+        sdk_store,
+        media_cache: media_cache::total_media_cache_size(),
+        temp_storage: dir_size(get_temp_dir_path()),
+    }
+}
+
+/// Returns the paths of all client database directories within the app data directory.
+fn sdk_store_dirs() -> io::Result<Vec<std::path::PathBuf>> {
+    Ok(std::fs::read_dir(app_data_dir())?
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(DB_DIR_PREFIX))
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect())
+}
+
+/// Removes all client databases that aren't used by any saved session,
+/// e.g., those left behind by previous logins.
+///
+/// Nothing is removed if any saved session can't be read,
+/// since its database would otherwise look unused.
+fn clear_unused_sdk_stores() -> io::Result<u64> {
+    let in_use = persistent_state::saved_db_paths().map_err(io::Error::other)?;
+    let mut freed = 0;
+    for dir in sdk_store_dirs()? {
+        if in_use.contains(&dir) {
+            continue;
+        }
+        let size = dir_size(&dir);
+        std::fs::remove_dir_all(&dir)?;
+        freed += size;
+    }
+    Ok(freed)
+}
+
+/// Removes everything within the given directory, but not the directory itself.
+fn clear_dir_contents(dir: &Path) -> io::Result<u64> {
+    let mut freed = 0;
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let size = dir_size(&path);
+        if path.is_dir() {
+            std::fs::remove_dir_all(&path)?;
+        } else {
+            std::fs::remove_file(&path)?;
+        }
+        freed += size;
+    }
+    Ok(freed)
+}
+
+/// Returns the total size of the given file, or of all files within the given directory.
+///
+/// Any files that cannot be read are ignored.
+fn dir_size(path: &Path) -> u64 {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else { return 0 };
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+    std::fs::read_dir(path)
+        .map(|entries| entries
+            .filter_map(Result::ok)
+            .map(|entry| dir_size(&entry.path()))
+            .sum()
+        )
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests_storage {
+    use super::*;
+
+    fn entry(name: &str, age_secs: u64, size: u64) -> TempEntry {
+        TempEntry { path: PathBuf::from(name), age: Duration::from_secs(age_secs), size }
+    }
+
+    fn removed_names(entries: Vec<TempEntry>, max_age_secs: u64, max_bytes: u64) -> Vec<PathBuf> {
+        temp_entries_to_remove(entries, Duration::from_secs(max_age_secs), max_bytes)
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect()
+    }
+
+    #[test]
+    fn test_temp_entries_to_remove() {
+        let entries = || vec![entry("new", 10, 100), entry("old", 1000, 100), entry("mid", 100, 100)];
+        // Within both limits: nothing is removed.
+        assert!(removed_names(entries(), 10_000, 1000).is_empty());
+        // Only entries exceeding the age limit are removed.
+        assert_eq!(removed_names(entries(), 500, 1000), [PathBuf::from("old")]);
+        // The oldest entries are removed until the total size is within the size limit.
+        assert_eq!(removed_names(entries(), 10_000, 150), [PathBuf::from("old"), PathBuf::from("mid")]);
+        assert_eq!(removed_names(entries(), 10_000, 0).len(), 3);
+    }
+}
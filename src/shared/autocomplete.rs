@@ -0,0 +1,415 @@
+//! Autocompletion of the token currently being typed at the end of a text input,
+//! e.g., an emoji shortcode like `:thumbsup:`, a room mention like `#robrix`, or a snippet like `/sig`.
+//!
+//! A token starts with a trigger character (e.g., `:` or `#`) at the start of a word.
+//! Matching suggestions are shown in an [`AutocompleteList`], and selecting one
+//! replaces the token with that suggestion's replacement text.
+
+use makepad_widgets::*;
+use matrix_sdk::ruma::{OwnedRoomAliasId, OwnedRoomId};
+
+use crate::{i18n::tr, settings::snippets::{self, Snippet}};
+
+/// The trigger character that starts an emoji shortcode.
+pub const EMOJI_TRIGGER: char = ':';
+/// The minimum number of characters after the trigger before emoji suggestions are shown,
+/// such that typing a colon in normal prose (e.g., "note: ...") doesn't show suggestions.
+const MIN_EMOJI_QUERY_LEN: usize = 2;
+/// The trigger character that starts a room mention.
+pub const ROOM_TRIGGER: char = '#';
+/// The trigger character that starts a user mention, including the `@room` mention.
+pub const USER_TRIGGER: char = '@';
+/// The text of a mention that notifies everyone in the room.
+pub const ROOM_MENTION: &str = "@room";
+/// The maximum number of suggestions shown at once.
+pub const MAX_SUGGESTIONS: usize = 8;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+
+    SuggestionRow = <View> {
+        width: Fill, height: Fit
+        button = <Button> {
+            width: Fill, height: Fit
+            padding: {left: 10, right: 10, top: 6, bottom: 6}
+            align: {x: 0.0, y: 0.5}
+            draw_bg: {
+                instance hover: 0.0
+                fn pixel(self) -> vec4 {
+                    return mix(#fff, #eef2f4, self.hover);
+                }
+            }
+            draw_text: {
+                text_style: <REGULAR_TEXT>{font_size: 10},
+                fn get_color(self) -> vec4 {
+                    return #000;
+                }
+            }
+        }
+    }
+
+    SuggestionList = {{SuggestionList}} {
+        width: Fill, height: Fit
+        flow: Down
+        item: <SuggestionRow> {}
+    }
+
+    pub AutocompleteList = {{AutocompleteList}} {
+        visible: false
+        width: Fill, height: Fit
+        margin: {left: 8, right: 8}
+        padding: 4
+        show_bg: true
+        draw_bg: {
+            color: #fff
+            border_width: 1.0
+            border_color: #D0D5DD
+            radius: 4.0
+        }
+
+        suggestion_list = <SuggestionList> {}
+    }
+}
+
+/// A single autocomplete suggestion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// The text shown in the list of suggestions.
+    pub label: String,
+    /// The text that replaces the token being typed if this suggestion is selected.
+    pub replacement: String,
+    /// The trigger character of the token that this suggestion completes.
+    pub trigger: char,
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum AutocompleteAction {
+    None,
+    /// The given suggestion was selected by the user.
+    Selected(Suggestion),
+}
+
+/// Returns the token being typed at the end of the given text if it starts with `trigger`,
+/// as the byte index of the trigger and the query text following the trigger.
+///
+/// The trigger must be at the start of the text or follow whitespace.
+pub fn trailing_token(text: &str, trigger: char) -> Option<(usize, &str)> {
+    let start = text.rfind(char::is_whitespace)
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let query = text[start..].strip_prefix(trigger)?;
+    Some((start, query))
+}
+
+/// Returns the given text with the token starting at `token_start` replaced by `replacement`,
+/// followed by a space such that the user can continue typing.
+pub fn replace_trailing_token(text: &str, token_start: usize, replacement: &str) -> String {
+    format!("{}{replacement} ", &text[..token_start])
+}
+
+/// Returns suggestions of emoji whose shortcodes match the given query (without colons),
+/// with shortcodes that start with the query listed before those that merely contain it.
+pub fn emoji_suggestions(query: &str) -> Vec<Suggestion> {
+    if query.chars().count() < MIN_EMOJI_QUERY_LEN || query.contains(EMOJI_TRIGGER) {
+        return Vec::new();
+    }
+    let query = query.to_lowercase();
+    let mut prefix_matches = Vec::new();
+    let mut other_matches = Vec::new();
+    for emoji in emojis::iter() {
+        let Some(shortcode) = emoji.shortcodes().find(|s| s.contains(query.as_str())) else { continue };
+        let suggestion = Suggestion {
+            label: format!("{}  :{shortcode}:", emoji.as_str()),
+            replacement: emoji.as_str().to_owned(),
+            trigger: EMOJI_TRIGGER,
+        };
+        if shortcode.starts_with(query.as_str()) {
+            prefix_matches.push(suggestion);
+        } else if other_matches.len() < MAX_SUGGESTIONS {
+            other_matches.push(suggestion);
+        }
+    }
+    prefix_matches.extend(other_matches);
+    prefix_matches.truncate(MAX_SUGGESTIONS);
+    prefix_matches
+}
+
+/// If the given text ends with a complete emoji shortcode (e.g., `:thumbsup:`),
+/// returns the text with that shortcode replaced by its emoji.
+pub fn replace_trailing_emoji_shortcode(text: &str) -> Option<String> {
+    let without_closing = text.strip_suffix(EMOJI_TRIGGER)?;
+    let (start, shortcode) = trailing_token(without_closing, EMOJI_TRIGGER)?;
+    let emoji = emojis::get_by_shortcode(shortcode)?;
+    Some(format!("{}{}", &text[..start], emoji.as_str()))
+}
+
+/// A room that can be mentioned by typing its alias or name after a [`ROOM_TRIGGER`].
+pub struct MentionableRoom {
+    pub room_id: OwnedRoomId,
+    pub name: Option<String>,
+    pub canonical_alias: Option<OwnedRoomAliasId>,
+}
+
+/// Returns suggestions of rooms whose alias or name matches the given query (without the `#`).
+///
+/// Each suggestion's replacement is a Markdown link to the room,
+/// which is sent as a pill that other clients render as a clickable mention.
+pub fn room_suggestions(query: &str, rooms: impl IntoIterator<Item = MentionableRoom>) -> Vec<Suggestion> {
+    let query = query.to_lowercase();
+    let mut prefix_matches = Vec::new();
+    let mut other_matches = Vec::new();
+    for room in rooms {
+        let alias = room.canonical_alias.as_ref().map(|a| a.as_str());
+        let name = room.name.as_deref().filter(|n| !n.trim().is_empty());
+        // The alias's localpart, which follows the `#`.
+        let alias_localpart = alias.map(|a| a[1..].split(':').next().unwrap_or_default().to_lowercase());
+        let lowercase_name = name.map(str::to_lowercase);
+        let is_prefix_match = alias_localpart.as_ref().is_some_and(|a| a.starts_with(&query))
+            || lowercase_name.as_ref().is_some_and(|n| n.starts_with(&query));
+        let is_match = is_prefix_match
+            || alias.is_some_and(|a| a.to_lowercase().contains(&query))
+            || lowercase_name.as_ref().is_some_and(|n| n.contains(&query));
+        if !is_match {
+            continue;
+        }
+
+        let (link_text, uri) = match &room.canonical_alias {
+            Some(alias) => (alias.to_string(), alias.matrix_to_uri().to_string()),
+            None => (
+                name.unwrap_or(room.room_id.as_str()).to_owned(),
+                room.room_id.matrix_to_uri().to_string(),
+            ),
+        };
+        let label = match (name, alias) {
+            (Some(name), Some(alias)) => format!("{name}  {alias}"),
+            (Some(name), None) => name.to_owned(),
+            (None, Some(alias)) => alias.to_owned(),
+            (None, None) => room.room_id.to_string(),
+        };
+        let suggestion = Suggestion {
+            label,
+            replacement: format!("[{}]({uri})", link_text.replace(['[', ']'], "")),
+            trigger: ROOM_TRIGGER,
+        };
+        if is_prefix_match {
+            prefix_matches.push(suggestion);
+        } else if other_matches.len() < MAX_SUGGESTIONS {
+            other_matches.push(suggestion);
+        }
+    }
+    prefix_matches.extend(other_matches);
+    prefix_matches.truncate(MAX_SUGGESTIONS);
+    prefix_matches
+}
+
+/// Returns the suggestion to mention everyone in the room if it matches the given query
+/// (without the `@`) and the user is allowed to notify the whole room.
+pub fn room_mention_suggestion(query: &str, can_notify_room: bool) -> Option<Suggestion> {
+    let keyword = &ROOM_MENTION[1..];
+    (can_notify_room && keyword.starts_with(query.to_lowercase().as_str())).then(|| Suggestion {
+        label: format!("{ROOM_MENTION}  {}", tr("composer.mention_room")),
+        replacement: ROOM_MENTION.to_owned(),
+        trigger: USER_TRIGGER,
+    })
+}
+
+/// Returns suggestions of the user's snippets whose names start with the given query (without the `/`).
+pub fn snippet_suggestions(query: &str, snippets: &[Snippet]) -> Vec<Suggestion> {
+    snippets::matching_snippets(query, snippets)
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|snippet| Suggestion {
+            label: format!("{}  {}", snippet.trigger(), snippet.text.lines().next().unwrap_or_default()),
+            replacement: snippet.text.clone(),
+            trigger: snippets::SNIPPET_TRIGGER,
+        })
+        .collect()
+}
+
+/// Returns whether the given message text contains an `@room` mention as a separate word.
+pub fn contains_room_mention(text: &str) -> bool {
+    text.split(|c: char| c.is_whitespace() || matches!(c, ',' | '.' | '!' | '?' | ':' | ';'))
+        .any(|word| word == ROOM_MENTION)
+}
+
+/// A popup list of autocomplete suggestions, each of which can be clicked to select it.
+///
+/// This hides itself when it has no suggestions.
+#[derive(Live, LiveHook, Widget)]
+pub struct AutocompleteList {
+    #[deref] view: View,
+}
+
+impl Widget for AutocompleteList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl AutocompleteListRef {
+    /// Replaces the displayed suggestions with the given ones, hiding this list if there are none.
+    pub fn set_suggestions(&self, cx: &mut Cx, suggestions: Vec<Suggestion>) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        inner.view.set_visible(cx, !suggestions.is_empty());
+        let suggestion_list = inner.suggestion_list(id!(suggestion_list));
+        let Some(mut list) = suggestion_list.borrow_mut() else { return };
+        list.children.clear();
+        for suggestion in suggestions {
+            let row = WidgetRef::new_from_ptr(cx, list.item);
+            row.button(id!(button)).set_text(cx, &suggestion.label);
+            list.children.push((row, suggestion));
+        }
+        list.redraw(cx);
+    }
+
+    /// Returns the first suggestion, which is selected when the user presses Tab.
+    pub fn first_suggestion(&self) -> Option<Suggestion> {
+        let inner = self.borrow()?;
+        let suggestion_list = inner.suggestion_list(id!(suggestion_list));
+        let list = suggestion_list.borrow()?;
+        list.children.first().map(|(_, s)| s.clone())
+    }
+
+    /// Returns the suggestion that was selected by the user, if any.
+    pub fn selected(&self, actions: &Actions) -> Option<Suggestion> {
+        let inner = self.borrow()?;
+        let uid = inner.suggestion_list(id!(suggestion_list)).widget_uid();
+        match actions.find_widget_action(uid)?.cast() {
+            AutocompleteAction::Selected(suggestion) => Some(suggestion),
+            AutocompleteAction::None => None,
+        }
+    }
+}
+
+/// The list of suggestion rows within an [`AutocompleteList`].
+#[derive(Live, LiveHook, Widget)]
+struct SuggestionList {
+    #[redraw] #[rust] area: Area,
+    #[live] item: Option<LivePtr>,
+    #[rust] children: Vec<(WidgetRef, Suggestion)>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for SuggestionList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (row, _) in &self.children {
+            row.handle_event(cx, event, scope);
+        }
+        let Event::Actions(actions) = event else { return };
+        for (row, suggestion) in &self.children {
+            if row.button(id!(button)).clicked(actions) {
+                cx.widget_action(
+                    self.widget_uid(),
+                    &scope.path,
+                    AutocompleteAction::Selected(suggestion.clone()),
+                );
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (row, _) in &self.children {
+            let _ = row.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+#[cfg(test)]
+mod tests_autocomplete {
+    use super::*;
+
+    #[test]
+    fn test_trailing_token() {
+        assert_eq!(trailing_token(":thu", ':'), Some((0, "thu")));
+        assert_eq!(trailing_token("nice :thu", ':'), Some((5, "thu")));
+        assert_eq!(trailing_token("nice\u{3000}:thu", ':'), Some((7, "thu")));
+        assert_eq!(trailing_token("note:", ':'), None);
+        assert_eq!(trailing_token("nice :thu ", ':'), None);
+    }
+
+    #[test]
+    fn test_replace_trailing_token() {
+        assert_eq!(replace_trailing_token("nice :thu", 5, "👍"), "nice 👍 ");
+    }
+
+    #[test]
+    fn test_replace_trailing_emoji_shortcode() {
+        assert_eq!(replace_trailing_emoji_shortcode("nice :thumbsup:").as_deref(), Some("nice 👍"));
+        assert_eq!(replace_trailing_emoji_shortcode(":+1:").as_deref(), Some("👍"));
+        assert_eq!(replace_trailing_emoji_shortcode("nice :notanemoji:"), None);
+        assert_eq!(replace_trailing_emoji_shortcode("at 10:30:"), None);
+        assert_eq!(replace_trailing_emoji_shortcode("nice :thumbsup"), None);
+    }
+
+    #[test]
+    fn test_emoji_suggestions() {
+        assert!(emoji_suggestions("t").is_empty());
+        let suggestions = emoji_suggestions("thumbs");
+        assert!(!suggestions.is_empty() && suggestions.len() <= MAX_SUGGESTIONS);
+        assert!(suggestions.iter().any(|s| s.replacement == "👍"));
+    }
+
+    fn room(room_id: &str, name: Option<&str>, alias: Option<&str>) -> MentionableRoom {
+        MentionableRoom {
+            room_id: room_id.try_into().unwrap(),
+            name: name.map(Into::into),
+            canonical_alias: alias.map(|a| a.try_into().unwrap()),
+        }
+    }
+
+    #[test]
+    fn test_room_suggestions() {
+        let rooms = || vec![
+            room("!a:example.org", Some("Robrix Dev"), Some("#robrix:example.org")),
+            room("!b:example.org", Some("Makepad"), None),
+            room("!c:example.org", Some("Off topic about robrix"), None),
+        ];
+        let suggestions = room_suggestions("rob", rooms());
+        assert_eq!(suggestions.len(), 2);
+        assert_eq!(suggestions[0].label, "Robrix Dev  #robrix:example.org");
+        assert!(suggestions[0].replacement.starts_with("[#robrix:example.org](https://matrix.to/#/"));
+        assert_eq!(suggestions[0].trigger, ROOM_TRIGGER);
+        assert_eq!(suggestions[1].label, "Off topic about robrix");
+
+        let suggestions = room_suggestions("make", rooms());
+        assert_eq!(suggestions.len(), 1);
+        assert!(suggestions[0].replacement.starts_with("[Makepad](https://matrix.to/#/"));
+    }
+
+    #[test]
+    fn test_room_mention_suggestion() {
+        assert!(room_mention_suggestion("ro", true).is_some_and(|s| s.replacement == ROOM_MENTION));
+        assert!(room_mention_suggestion("", true).is_some());
+        assert!(room_mention_suggestion("ro", false).is_none());
+        assert!(room_mention_suggestion("alice", true).is_none());
+    }
+
+    #[test]
+    fn test_snippet_suggestions() {
+        let snippets = vec![Snippet { name: "sig".into(), text: "Best regards,\nAlice".into() }];
+        let suggestions = snippet_suggestions("s", &snippets);
+        assert_eq!(suggestions.len(), 1);
+        assert_eq!(suggestions[0].label, "/sig  Best regards,");
+        assert_eq!(suggestions[0].replacement, "Best regards,\nAlice");
+        assert!(snippet_suggestions("x", &snippets).is_empty());
+    }
+
+    #[test]
+    fn test_contains_room_mention() {
+        assert!(contains_room_mention("@room please read this"));
+        assert!(contains_room_mention("hi @room!"));
+        assert!(!contains_room_mention("@roommate"));
+        assert!(!contains_room_mention("email@room.org"));
+    }
+}
@@ -0,0 +1,307 @@
+//! A toolbar above the message input with buttons that insert Markdown formatting,
+//! or apply it to the text currently selected in the message input.
+
+use std::ops::Range;
+
+use makepad_widgets::{text::selection::Cursor, *};
+
+use crate::i18n::tr_args;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::IconButton;
+
+    FormattingButton = <IconButton> {
+        padding: {left: 8, right: 8, top: 4, bottom: 4}
+        draw_text: {
+            text_style: <REGULAR_TEXT>{font_size: 10.5}
+        }
+    }
+
+    pub FormattingToolbar = {{FormattingToolbar}} {
+        visible: false
+        width: Fill, height: Fit
+        flow: Right
+        spacing: 2
+        align: {y: 0.5}
+        padding: {left: 8, right: 8, top: 2, bottom: 2}
+        show_bg: true
+        draw_bg: {
+            color: (COLOR_PRIMARY)
+        }
+
+        bold_button = <FormattingButton> {
+            text: "B"
+            draw_text: { text_style: <THEME_FONT_BOLD>{font_size: 10.5} }
+        }
+        italic_button = <FormattingButton> {
+            text: "I"
+            draw_text: { text_style: <THEME_FONT_ITALIC>{font_size: 10.5} }
+        }
+        strikethrough_button = <FormattingButton> { text: "S" }
+        code_button = <FormattingButton> { text: "</>" }
+        quote_button = <FormattingButton> { text: ">" }
+        link_button = <FormattingButton> { text: "Link" }
+        list_button = <FormattingButton> { text: "•" }
+    }
+}
+
+/// The placeholder URL inserted by [`Formatting::Link`], which is selected so it can be typed over.
+const LINK_URL_PLACEHOLDER: &str = "https://";
+
+/// A kind of Markdown formatting that can be applied from the [`FormattingToolbar`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Formatting {
+    Bold,
+    Italic,
+    Strikethrough,
+    /// Inline code, or a code block if the selection spans multiple lines.
+    Code,
+    Quote,
+    Link,
+    /// A bulleted list item.
+    List,
+}
+
+impl Formatting {
+    /// Returns the prefix that this formatting adds to each selected line,
+    /// if it applies to entire lines rather than wrapping the selection.
+    fn line_prefix(self) -> Option<&'static str> {
+        match self {
+            Formatting::Quote => Some("> "),
+            Formatting::List => Some("- "),
+            _ => None,
+        }
+    }
+}
+
+/// Applies the given formatting to the `selection` (a byte range) of the given text.
+///
+/// If the selection is empty, the formatting markers are inserted at that position.
+/// Applying the same inline formatting to text that already has it removes the formatting.
+///
+/// Returns the new text and the range that should then be selected,
+/// which is the formatted text (or the position between inserted markers).
+pub fn apply_formatting(text: &str, selection: Range<usize>, formatting: Formatting) -> (String, Range<usize>) {
+    let selection = clamp_selection(text, selection);
+    if let Some(prefix) = formatting.line_prefix() {
+        return toggle_line_prefix(text, selection, prefix);
+    }
+
+    let selected = &text[selection.clone()];
+    let (open, close) = match formatting {
+        Formatting::Bold => ("**", "**"),
+        Formatting::Italic => ("*", "*"),
+        Formatting::Strikethrough => ("~~", "~~"),
+        Formatting::Code if selected.contains('\n') => ("```\n", "\n```"),
+        Formatting::Code => ("`", "`"),
+        Formatting::Link => {
+            let new_text = format!(
+                "{}[{selected}]({LINK_URL_PLACEHOLDER}){}",
+                &text[..selection.start],
+                &text[selection.end..],
+            );
+            // Select the placeholder URL such that the user can immediately replace it.
+            let url_start = selection.start + "[".len() + selected.len() + "](".len();
+            return (new_text, url_start .. url_start + LINK_URL_PLACEHOLDER.len());
+        }
+        Formatting::Quote | Formatting::List => unreachable!("handled as line prefixes above"),
+    };
+
+    // Remove the markers if the selection is already surrounded by them.
+    let is_wrapped = text[..selection.start].ends_with(open) && text[selection.end..].starts_with(close);
+    if is_wrapped {
+        let start = selection.start - open.len();
+        let new_text = format!("{}{selected}{}", &text[..start], &text[selection.end + close.len()..]);
+        return (new_text, start .. start + selected.len());
+    }
+
+    let new_text = format!("{}{open}{selected}{close}{}", &text[..selection.start], &text[selection.end..]);
+    let start = selection.start + open.len();
+    (new_text, start .. start + selected.len())
+}
+
+/// Adds the given prefix to every line touched by the selection,
+/// or removes it if all of those lines already start with it.
+fn toggle_line_prefix(text: &str, selection: Range<usize>, prefix: &str) -> (String, Range<usize>) {
+    let lines_start = text[..selection.start].rfind('\n').map_or(0, |i| i + 1);
+    let lines_end = text[selection.end..].find('\n').map_or(text.len(), |i| selection.end + i);
+    let lines = &text[lines_start..lines_end];
+
+    let all_prefixed = lines.split('\n').all(|line| line.starts_with(prefix));
+    let new_lines = lines.split('\n')
+        .map(|line| if all_prefixed {
+            line[prefix.len()..].to_owned()
+        } else {
+            format!("{prefix}{line}")
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let new_text = format!("{}{new_lines}{}", &text[..lines_start], &text[lines_end..]);
+    let new_end = lines_start + new_lines.len();
+    if selection.is_empty() {
+        // Keep the cursor at the end of the (single) formatted line.
+        (new_text, new_end..new_end)
+    } else {
+        (new_text, lines_start..new_end)
+    }
+}
+
+/// Formats the given message body as a Markdown block quote attributed to the given sender,
+/// followed by an empty line on which the user can write their response.
+pub fn markdown_quote(body: &str, sender: &str) -> String {
+    let mut quote = format!("> {}\n>\n", tr_args("message.quote_attribution", &[("user", sender)]));
+    for line in body.trim().lines() {
+        if line.is_empty() {
+            quote.push_str(">\n");
+        } else {
+            quote.push_str("> ");
+            quote.push_str(line);
+            quote.push('\n');
+        }
+    }
+    quote.push('\n');
+    quote
+}
+
+/// Ensures the given selection is ordered, within bounds, and on char boundaries of the text.
+fn clamp_selection(text: &str, selection: Range<usize>) -> Range<usize> {
+    let clamp = |mut index: usize| {
+        index = index.min(text.len());
+        while !text.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    };
+    let (start, end) = (clamp(selection.start), clamp(selection.end));
+    start.min(end) .. start.max(end)
+}
+
+/// Returns the byte range of the text currently selected in the given text input.
+pub fn text_input_selection(text_input: &TextInputRef) -> Range<usize> {
+    text_input.borrow().map_or(0..0, |text_input| {
+        let selection = text_input.selection();
+        selection.start().index .. selection.end().index
+    })
+}
+
+/// Selects the given byte range of the text in the given text input.
+pub fn set_text_input_selection(cx: &mut Cx, text_input: &TextInputRef, selection: Range<usize>) {
+    text_input.set_cursor(cx, Cursor { index: selection.start, prefer_next_row: false }, false);
+    text_input.set_cursor(cx, Cursor { index: selection.end, prefer_next_row: false }, true);
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum FormattingToolbarAction {
+    None,
+    /// The user clicked the button for the given formatting.
+    Apply(Formatting),
+}
+
+/// A row of buttons that apply Markdown formatting to the message being composed.
+///
+/// This is only shown if the user enabled it in the settings.
+#[derive(Live, LiveHook, Widget)]
+pub struct FormattingToolbar {
+    #[deref] view: View,
+}
+
+impl Widget for FormattingToolbar {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        let Event::Actions(actions) = event else { return };
+
+        let buttons = [
+            (id!(bold_button), Formatting::Bold),
+            (id!(italic_button), Formatting::Italic),
+            (id!(strikethrough_button), Formatting::Strikethrough),
+            (id!(code_button), Formatting::Code),
+            (id!(quote_button), Formatting::Quote),
+            (id!(link_button), Formatting::Link),
+            (id!(list_button), Formatting::List),
+        ];
+        for (button, formatting) in buttons {
+            if self.button(button).clicked(actions) {
+                cx.widget_action(self.widget_uid(), &scope.path, FormattingToolbarAction::Apply(formatting));
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl FormattingToolbarRef {
+    /// Returns the formatting that the user chose to apply, if any.
+    pub fn applied(&self, actions: &Actions) -> Option<Formatting> {
+        match actions.find_widget_action(self.widget_uid())?.cast() {
+            FormattingToolbarAction::Apply(formatting) => Some(formatting),
+            FormattingToolbarAction::None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_formatting_toolbar {
+    use super::*;
+
+    #[test]
+    fn test_wrap_selection() {
+        let (text, selection) = apply_formatting("hello world", 6..11, Formatting::Bold);
+        assert_eq!(text, "hello **world**");
+        assert_eq!(&text[selection], "world");
+    }
+
+    #[test]
+    fn test_insert_markers_at_cursor() {
+        let (text, selection) = apply_formatting("hello ", 6..6, Formatting::Strikethrough);
+        assert_eq!(text, "hello ~~~~");
+        assert_eq!(selection, 8..8);
+    }
+
+    #[test]
+    fn test_unwrap_formatted_selection() {
+        let (text, selection) = apply_formatting("a *b* c", 3..4, Formatting::Italic);
+        assert_eq!(text, "a b c");
+        assert_eq!(&text[selection], "b");
+    }
+
+    #[test]
+    fn test_multiline_code_block() {
+        let (text, _) = apply_formatting("x\ny", 0..3, Formatting::Code);
+        assert_eq!(text, "```\nx\ny\n```");
+    }
+
+    #[test]
+    fn test_link_selects_url_placeholder() {
+        let (text, selection) = apply_formatting("see docs", 4..8, Formatting::Link);
+        assert_eq!(text, "see [docs](https://)");
+        assert_eq!(&text[selection], "https://");
+    }
+
+    #[test]
+    fn test_toggle_line_prefix() {
+        let (text, selection) = apply_formatting("one\ntwo\nthree", 1..5, Formatting::List);
+        assert_eq!(text, "- one\n- two\nthree");
+        assert_eq!(&text[selection.clone()], "- one\n- two");
+        let (text, _) = apply_formatting(&text, selection, Formatting::List);
+        assert_eq!(text, "one\ntwo\nthree");
+    }
+
+    #[test]
+    fn test_markdown_quote() {
+        let quote = markdown_quote("first line\n\nsecond line\n", "Alice");
+        assert_eq!(quote, "> Alice wrote:\n>\n> first line\n>\n> second line\n\n");
+    }
+
+    #[test]
+    fn test_selection_is_clamped() {
+        let (text, _) = apply_formatting("héllo", 2..99, Formatting::Code);
+        assert_eq!(text, "h`éllo`");
+    }
+}
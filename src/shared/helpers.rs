@@ -2,6 +2,7 @@ use makepad_widgets::*;
 
 live_design! {
     use link::theme::*;
+    use link::shaders::*;
     use link::widgets::*;
 
     use crate::shared::styles::*;
@@ -27,4 +28,23 @@ live_design! {
 
     pub FillerX = <View> { width: Fill, height: Fit }
     pub FillerY = <View> { width: Fit, height: Fill }
+
+    // A small colored dot shown on top of an avatar to indicate a user's presence.
+    // Its color is set from Rust code based on the user's presence.
+    pub PresenceDot = <View> {
+        visible: false
+        width: 12, height: 12
+        show_bg: true
+        draw_bg: {
+            instance dot_color: #9E9E9E
+            fn pixel(self) -> vec4 {
+                let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                let c = self.rect_size * 0.5;
+                sdf.circle(c.x, c.y, c.x - 1.0);
+                sdf.fill_keep(self.dot_color);
+                sdf.stroke((COLOR_PRIMARY), 1.5);
+                return sdf.result;
+            }
+        }
+    }
 }
@@ -1,9 +1,17 @@
 //! A `HtmlOrPlaintext` view can display either plaintext or rich HTML content.
 
 use makepad_widgets::{makepad_html::HtmlDoc, *};
+use tracing::error;
+use matrix_sdk::ruma::{matrix_uri::MatrixId, MatrixToUri};
 
 /// The color of the text used to print the spoiler reason before the hidden text.
 const COLOR_SPOILER_REASON: Vec4 = vec4(0.6, 0.6, 0.6, 1.0);
+/// The background color of a pill that mentions a user.
+const COLOR_USER_PILL: Vec4 = vec4(0.85, 0.91, 0.98, 1.0);
+/// The background color of a pill that mentions a room.
+const COLOR_ROOM_PILL: Vec4 = vec4(0.87, 0.94, 0.88, 1.0);
+/// The color of the text within a mention pill.
+const COLOR_PILL_TEXT: Vec4 = vec4(0.06, 0.25, 0.5, 1.0);
 
 live_design! {
     use link::theme::*;
@@ -24,6 +32,13 @@ live_design! {
         align: {x: 0., y: 0.}
     }
 
+    // This is an HTML subwidget used to display a mention of a user or room
+    // as a colored pill, which can be clicked to open that user's profile or that room.
+    pub MatrixMentionPill = {{MatrixMentionPill}} {
+        width: Fit, height: Fit,
+        align: {x: 0., y: 0.}
+    }
+
 
     // A centralized widget where we define styles and custom elements for HTML
     // message content. This is a wrapper around Makepad's built-in `Html` widget.
@@ -56,6 +71,9 @@ live_design! {
 
         font = <MatrixHtmlSpan> { }
         span = <MatrixHtmlSpan> { }
+        // Links to users and rooms are converted into `<mention>` tags before being shown.
+        // See `utils::mention_pills()`.
+        mention = <MatrixMentionPill> { }
 
         a = {
             hover_color: #21b070
@@ -99,6 +117,21 @@ live_design! {
             width: Fill, height: Fit, // see above comment
             html = <MessageHtml> {}
         }
+
+        // Shown instead of the plaintext view for messages that consist solely of a few emoji.
+        large_emoji_view = <View> {
+            visible: false,
+            width: Fill, height: Fit, // see above comment
+            emoji_label = <Label> {
+                width: Fill, height: Fit, // see above comment
+                draw_text: {
+                    wrap: Word,
+                    color: (MESSAGE_TEXT_COLOR),
+                    text_style: <MESSAGE_TEXT_STYLE> { font_size: (LARGE_EMOJI_FONT_SIZE) },
+                }
+                text: "",
+            }
+        }
     }
 }
 
@@ -318,6 +351,120 @@ impl Widget for MatrixHtmlSpan {
 }
 
 
+/// The kind of entity that a [`MatrixMentionPill`] refers to.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum MentionKind {
+    #[default]
+    User,
+    Room,
+}
+
+/// A widget used to display a `<mention>` tag, i.e., a link to a user or room, as a pill.
+///
+/// Clicking the pill emits the same [`HtmlLinkAction`] as clicking a regular link would,
+/// such that the user's profile or the room is opened by the existing link handling.
+#[derive(Live, Widget)]
+struct MatrixMentionPill {
+    #[redraw] #[area] area: Area,
+    #[walk] walk: Walk,
+    #[layout] layout: Layout,
+
+    #[rust] drawn_areas: SmallVec<[Area; 2]>,
+
+    /// The text content within the `<mention>` tag, typically the user's or room's name.
+    #[live] text: ArcStringMut,
+    /// The `matrix.to` URL of the mentioned user or room, from the `href` attribute.
+    #[rust] href: String,
+    #[rust] kind: MentionKind,
+}
+
+impl LiveHook for MatrixMentionPill {
+    fn after_apply(&mut self, _cx: &mut Cx, apply: &mut Apply, _index: usize, _nodes: &[LiveNode]) {
+        if let ApplyFrom::NewFromDoc {..} = apply.from {
+            if let Some(scope) = apply.scope.as_ref() {
+                if let Some(doc) = scope.props.get::<HtmlDoc>() {
+                    let mut walker = doc.new_walker_with_index(scope.index + 1);
+                    while let Some((lc, attr)) = walker.while_attr_lc() {
+                        if lc == live_id!(href) {
+                            self.href = attr.trim_matches(['"', '\'']).replace("&amp;", "&");
+                        }
+                    }
+                }
+            } else {
+                error!("BUG: MatrixMentionPill::after_apply(): scope not found, cannot set attributes.");
+            }
+            self.kind = match MatrixToUri::parse(&self.href).as_ref().map(MatrixToUri::id) {
+                Ok(MatrixId::Room(_) | MatrixId::RoomAlias(_)) => MentionKind::Room,
+                _ => MentionKind::User,
+            };
+        }
+    }
+}
+
+impl Widget for MatrixMentionPill {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for area in self.drawn_areas.clone().into_iter() {
+            match event.hits(cx, area) {
+                Hit::FingerHoverIn(_) => {
+                    cx.set_cursor(MouseCursor::Hand);
+                }
+                Hit::FingerUp(fe) if fe.is_over => {
+                    cx.widget_action(
+                        self.widget_uid(),
+                        &scope.path,
+                        HtmlLinkAction::Clicked {
+                            url: self.href.clone(),
+                            key_modifiers: fe.modifiers,
+                        },
+                    );
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, _walk: Walk) -> DrawStep {
+        let Some(tf) = scope.data.get_mut::<TextFlow>() else {
+            return DrawStep::done();
+        };
+
+        tf.areas_tracker.push_tracker();
+        // Reuse the inline code drawblock to draw the pill's background.
+        tf.inline_code.push();
+        let old_code_color = tf.draw_block.code_color;
+        tf.draw_block.code_color = match self.kind {
+            MentionKind::User => COLOR_USER_PILL,
+            MentionKind::Room => COLOR_ROOM_PILL,
+        };
+        tf.font_colors.push(COLOR_PILL_TEXT);
+        tf.bold.push();
+
+        tf.draw_text(cx, self.text.as_ref());
+
+        tf.bold.pop();
+        tf.font_colors.pop();
+        tf.draw_block.code_color = old_code_color;
+        tf.inline_code.pop();
+
+        let (start, end) = tf.areas_tracker.pop_tracker();
+        self.drawn_areas = SmallVec::from(
+            &tf.areas_tracker.areas[start..end]
+        );
+
+        DrawStep::done()
+    }
+
+    fn text(&self) -> String {
+        self.text.as_ref().to_string()
+    }
+
+    fn set_text(&mut self, cx: &mut Cx, v: &str) {
+        self.text.as_mut_empty().push_str(v);
+        self.redraw(cx);
+    }
+}
+
+
 #[derive(LiveHook, Live, Widget)]
 pub struct HtmlOrPlaintext {
     #[deref] view: View,
@@ -337,6 +484,7 @@ impl HtmlOrPlaintext {
     /// Sets the plaintext content and makes it visible, hiding the rich HTML content.
     pub fn show_plaintext<T: AsRef<str>>(&mut self, cx: &mut Cx, text: T) {
         self.view(id!(html_view)).set_visible(cx, false);
+        self.view(id!(large_emoji_view)).set_visible(cx, false);
         self.view(id!(plaintext_view)).set_visible(cx, true);
         self.label(id!(plaintext_view.pt_label)).set_text(cx, text.as_ref());
     }
@@ -346,6 +494,15 @@ impl HtmlOrPlaintext {
         self.html(id!(html_view.html)).set_text(cx, html_body.as_ref());
         self.view(id!(html_view)).set_visible(cx, true);
         self.view(id!(plaintext_view)).set_visible(cx, false);
+        self.view(id!(large_emoji_view)).set_visible(cx, false);
+    }
+
+    /// Sets the given emoji-only text as the content, displaying it at a large font size.
+    pub fn show_large_emoji<T: AsRef<str>>(&mut self, cx: &mut Cx, emoji_text: T) {
+        self.label(id!(large_emoji_view.emoji_label)).set_text(cx, emoji_text.as_ref());
+        self.view(id!(large_emoji_view)).set_visible(cx, true);
+        self.view(id!(html_view)).set_visible(cx, false);
+        self.view(id!(plaintext_view)).set_visible(cx, false);
     }
 }
 
@@ -363,4 +520,11 @@ impl HtmlOrPlaintextRef {
             inner.show_html(cx, html_body);
         }
     }
+
+    /// See [`HtmlOrPlaintext::show_large_emoji()`].
+    pub fn show_large_emoji<T: AsRef<str>>(&self, cx: &mut Cx, emoji_text: T) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.show_large_emoji(cx, emoji_text);
+        }
+    }
 }
@@ -1,6 +1,8 @@
 use makepad_widgets::*;
 
 const SCROLL_TO_BOTTOM_SPEED: f64 = 90.0;
+/// The maximum number of characters of a new message's first line shown in its preview.
+const MAX_PREVIEW_CHARS: usize = 60;
 
 live_design! {
     use link::theme::*;
@@ -20,72 +22,108 @@ live_design! {
         align: {x: 1.0, y: 1.0},
         visible: false,
         <View> {
-            width: 65, height: 75,
-            align: {x: 0.5, y: 1.0},
-            flow: Overlay,
-            jump_to_bottom_button = <IconButton> {
-                width: 50, height: 50,
-                margin: {bottom: 8},
-                draw_icon: {svg_file: (ICO_JUMP_TO_BOTTOM)},
-                icon_walk: {width: 20, height: 20, margin: {top: 10, right: 4.5} }
-                // draw a circular background for the button
+            width: Fit, height: Fit,
+            flow: Right,
+            align: {y: 1.0},
+
+            // A preview of the newest message that arrived while the timeline was scrolled up.
+            new_message_preview = <RoundedView> {
+                visible: false,
+                width: Fit, height: Fit,
+                margin: {bottom: 18},
+                padding: {top: 6, bottom: 6, left: 10, right: 10},
+                flow: Down,
+                spacing: 2,
+                cursor: Hand,
+                show_bg: true,
                 draw_bg: {
-                    instance background_color: #edededce,
-                    fn pixel(self) -> vec4 {
-                        let sdf = Sdf2d::viewport(self.pos * self.rect_size);
-                        let c = self.rect_size * 0.5;
-                        sdf.circle(c.x, c.x, c.x);
-                        sdf.fill_keep(self.background_color);
-                        return sdf.result
+                    color: #edededce,
+                    radius: 6.0,
+                }
+
+                preview_sender = <Label> {
+                    width: Fit, height: Fit,
+                    draw_text: {
+                        text_style: <USERNAME_TEXT_STYLE>{font_size: 9},
+                        color: (USERNAME_TEXT_COLOR),
+                    }
+                }
+                preview_text = <Label> {
+                    width: Fit, height: Fit,
+                    draw_text: {
+                        text_style: <REGULAR_TEXT>{font_size: 9.5},
+                        color: (MESSAGE_TEXT_COLOR),
                     }
                 }
             }
 
-            // A badge overlay on the jump to bottom button showing unread messages
-            unread_message_badge = <View> {
-                width: 25, height: 20,
-                align: {
-                    x: 0.5,
-                    y: 0.5
-                }
-                visible: false,
+            <View> {
+                width: 65, height: 75,
+                align: {x: 0.5, y: 1.0},
                 flow: Overlay,
-                green_rounded_label = <View> {
-                    width: Fill,
-                    height: Fill,
-                    show_bg: true,
+                jump_to_bottom_button = <IconButton> {
+                    width: 50, height: 50,
+                    margin: {bottom: 8},
+                    draw_icon: {svg_file: (ICO_JUMP_TO_BOTTOM)},
+                    icon_walk: {width: 20, height: 20, margin: {top: 10, right: 4.5} }
+                    // draw a circular background for the button
                     draw_bg: {
-                        color: (COLOR_UNREAD_MESSAGE_BADGE)
-                        instance radius: 4.0
-                        // Adjust this border_width to larger value to make oval smaller 
-                        instance border_width: 2.0
+                        instance background_color: #edededce,
                         fn pixel(self) -> vec4 {
-                            let sdf = Sdf2d::viewport(self.pos * self.rect_size)
-                            sdf.box(
-                                self.border_width,
-                                self.border_width,
-                                self.rect_size.x - (self.border_width * 2.0),
-                                self.rect_size.y - (self.border_width * 2.0),
-                                max(1.0, self.radius)
-                            )
-                            sdf.fill_keep(self.color)
-                            return sdf.result;
+                            let sdf = Sdf2d::viewport(self.pos * self.rect_size);
+                            let c = self.rect_size * 0.5;
+                            sdf.circle(c.x, c.x, c.x);
+                            sdf.fill_keep(self.background_color);
+                            return sdf.result
                         }
                     }
                 }
-                // Label that displays the unread message count
-                unread_messages_count = <Label> {
-                    width: Fit,
-                    height: Fit,
-                    text: "",
-                    draw_text: {
-                        color: #ffffff,
-                        text_style: {font_size: 8.0},
+
+                // A badge overlay on the jump to bottom button showing unread messages
+                unread_message_badge = <View> {
+                    width: 25, height: 20,
+                    align: {
+                        x: 0.5,
+                        y: 0.5
+                    }
+                    visible: false,
+                    flow: Overlay,
+                    green_rounded_label = <View> {
+                        width: Fill,
+                        height: Fill,
+                        show_bg: true,
+                        draw_bg: {
+                            color: (COLOR_UNREAD_MESSAGE_BADGE)
+                            instance radius: 4.0
+                            // Adjust this border_width to larger value to make oval smaller 
+                            instance border_width: 2.0
+                            fn pixel(self) -> vec4 {
+                                let sdf = Sdf2d::viewport(self.pos * self.rect_size)
+                                sdf.box(
+                                    self.border_width,
+                                    self.border_width,
+                                    self.rect_size.x - (self.border_width * 2.0),
+                                    self.rect_size.y - (self.border_width * 2.0),
+                                    max(1.0, self.radius)
+                                )
+                                sdf.fill_keep(self.color)
+                                return sdf.result;
+                            }
+                        }
+                    }
+                    // Label that displays the unread message count
+                    unread_messages_count = <Label> {
+                        width: Fit,
+                        height: Fit,
+                        text: "",
+                        draw_text: {
+                            color: #ffffff,
+                            text_style: {font_size: 8.0},
+                        }
                     }
                 }
             }
         }
-        
     }
 }
 
@@ -109,8 +147,9 @@ impl JumpToBottomButton {
     /// Updates the visibility of the jump to bottom button *without* redrawing.
     ///
     /// * If `is_at_bottom` is `true`, both the main jump to bottom view and
-    ///   the unread message badge are made invisible, because we consider all messages
-    ///   to be read by the user if the timeline has reached the bottom.
+    ///   the unread message badge (and new message preview) are made invisible,
+    ///   because we consider all messages to be read by the user
+    ///   if the timeline has reached the bottom.
     /// * If `is_at_bottom` is `false`, only the main jump to bottom "parent" view
     ///   is made visible; the unread message badge is *not* made visible, as that is done
     ///   via a separate call to [`JumpToBottomButton::show_unread_message_badge()`].
@@ -118,6 +157,7 @@ impl JumpToBottomButton {
         if is_at_bottom {
             self.visible = false;
             self.view(id!(unread_message_badge)).set_visible(cx, false);
+            self.view(id!(new_message_preview)).set_visible(cx, false);
         } else {
             self.visible = true;
         }
@@ -137,6 +177,7 @@ impl JumpToBottomButton {
             UnreadMessageCount::Known(0) => {
                 self.visible = false;
                 self.view(id!(unread_message_badge)).set_visible(cx, false);
+                self.view(id!(new_message_preview)).set_visible(cx, false);
                 self.label(id!(unread_messages_count)).set_text(cx, "");
             }
             UnreadMessageCount::Known(unread_message_count) => {
@@ -160,7 +201,22 @@ impl JumpToBottomButton {
                 });
             }
         }
-        
+    }
+
+    /// Shows a preview of the newest message next to the jump to bottom button,
+    /// consisting of the message's sender and the first line of its text.
+    ///
+    /// This does not automatically redraw any views.
+    pub fn show_new_message_preview(&mut self, cx: &mut Cx, sender: &str, text: &str) {
+        let first_line = text.lines().find(|line| !line.trim().is_empty()).unwrap_or_default().trim();
+        let first_line = if first_line.chars().count() > MAX_PREVIEW_CHARS {
+            format!("{}…", first_line.chars().take(MAX_PREVIEW_CHARS).collect::<String>())
+        } else {
+            first_line.to_string()
+        };
+        self.label(id!(new_message_preview.preview_sender)).set_text(cx, sender);
+        self.label(id!(new_message_preview.preview_text)).set_text(cx, &first_line);
+        self.view(id!(new_message_preview)).set_visible(cx, true);
     }
 
     /// Updates the visibility of the jump to bottom button and the unread message badge
@@ -181,7 +237,9 @@ impl JumpToBottomButton {
         //       to check if the portallist has been scrolled than to just directly
         //       query the portallist's `at_end` state and set the visibility accordingly.
 
-        if self.button(id!(jump_to_bottom_button)).clicked(actions) {
+        if self.button(id!(jump_to_bottom_button)).clicked(actions)
+            || self.view(id!(new_message_preview)).finger_up(actions).is_some()
+        {
             portal_list.smooth_scroll_to_end(
                 cx,
                 SCROLL_TO_BOTTOM_SPEED,
@@ -214,6 +272,13 @@ impl JumpToBottomButtonRef {
         }
     }
 
+    /// See [`JumpToBottomButton::show_new_message_preview()`].
+    pub fn show_new_message_preview(&self, cx: &mut Cx, sender: &str, text: &str) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.show_new_message_preview(cx, sender, text);
+        }
+    }
+
     /// See [`JumpToBottomButton::update_from_actions()`].
     pub fn update_from_actions(
         &self,
@@ -1,16 +1,20 @@
 use makepad_widgets::Cx;
 
+pub mod autocomplete;
 pub mod avatar;
 pub mod color_tooltip;
+pub mod formatting_toolbar;
 pub mod helpers;
 pub mod html_or_plaintext;
 pub mod icon_button;
 pub mod jump_to_bottom_button;
 pub mod search_bar;
+pub mod spelling_bar;
 pub mod styles;
 pub mod text_or_image;
 pub mod typing_animation;
 pub mod popup_list;
+pub mod performance_overlay;
 pub mod verification_badge;
 
 pub fn live_design(cx: &mut Cx) {
@@ -25,6 +29,10 @@ pub fn live_design(cx: &mut Cx) {
     typing_animation::live_design(cx);
     jump_to_bottom_button::live_design(cx);
     popup_list::live_design(cx);
+    autocomplete::live_design(cx);
+    spelling_bar::live_design(cx);
+    formatting_toolbar::live_design(cx);
     verification_badge::live_design(cx);
     color_tooltip::live_design(cx);
+    performance_overlay::live_design(cx);
 }
@@ -0,0 +1,165 @@
+//! A debug overlay that shows performance metrics on top of the app,
+//! which helps to diagnose reports of jank or unresponsiveness.
+//!
+//! The overlay is shown or hidden via the `TogglePerformanceOverlay` keyboard shortcut.
+//! The metrics themselves are always recorded, as doing so is very cheap.
+
+use std::{sync::{atomic::{AtomicU64, Ordering}, Mutex}, time::{Duration, Instant}};
+
+use makepad_widgets::*;
+
+use crate::sliding_sync::num_pending_requests;
+
+/// How often the overlay's metrics are refreshed, in seconds.
+const REFRESH_INTERVAL_SECS: f64 = 1.0;
+
+/// The total time spent handling draw events since the overlay was last refreshed, in microseconds.
+static FRAME_TIME_TOTAL_MICROS: AtomicU64 = AtomicU64::new(0);
+/// The longest time spent handling a single draw event since the overlay was last refreshed, in microseconds.
+static FRAME_TIME_MAX_MICROS: AtomicU64 = AtomicU64::new(0);
+/// The number of draw events handled since the overlay was last refreshed.
+static NUM_FRAMES: AtomicU64 = AtomicU64::new(0);
+/// The number of times a room timeline has been redrawn since the overlay was last refreshed.
+static NUM_TIMELINE_REDRAWS: AtomicU64 = AtomicU64::new(0);
+/// The time at which the latest sync response was received,
+/// and the time between that response and the one before it.
+static LATEST_SYNC_RESPONSE: Mutex<Option<(Instant, Option<Duration>)>> = Mutex::new(None);
+
+/// Records the time spent handling a single draw event, i.e., drawing one frame.
+pub fn record_frame_time(frame_time: Duration) {
+    let micros = frame_time.as_micros() as u64;
+    FRAME_TIME_TOTAL_MICROS.fetch_add(micros, Ordering::Relaxed);
+    FRAME_TIME_MAX_MICROS.fetch_max(micros, Ordering::Relaxed);
+    NUM_FRAMES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Records that a room timeline has been redrawn.
+pub fn record_timeline_redraw() {
+    NUM_TIMELINE_REDRAWS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Records that a sync response has been received from the homeserver.
+pub fn record_sync_response() {
+    let now = Instant::now();
+    let mut latest = LATEST_SYNC_RESPONSE.lock().unwrap();
+    let interval = latest.map(|(prev, _)| now.duration_since(prev));
+    *latest = Some((now, interval));
+}
+
+live_design! {
+    use link::theme::*;
+    use link::shaders::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+
+    MetricLabel = <Label> {
+        width: Fit, height: Fit
+        draw_text: {
+            text_style: <REGULAR_TEXT>{font_size: 9},
+            color: #fff
+        }
+    }
+
+    pub PerformanceOverlay = {{PerformanceOverlay}} {
+        visible: false
+        width: Fill, height: Fill
+        align: {x: 1.0, y: 1.0}
+
+        <RoundedView> {
+            width: Fit, height: Fit
+            margin: 10
+            padding: 8
+            spacing: 3
+            flow: Down
+            show_bg: true
+            draw_bg: {
+                color: #000000B0
+                radius: 4.0
+            }
+
+            frame_time_label = <MetricLabel> {}
+            timeline_redraws_label = <MetricLabel> {}
+            pending_requests_label = <MetricLabel> {}
+            sync_label = <MetricLabel> {}
+        }
+    }
+}
+
+/// A heads-up display of performance metrics, shown on top of all other app content.
+#[derive(Live, LiveHook, Widget)]
+pub struct PerformanceOverlay {
+    #[deref] view: View,
+    #[rust] refresh_timer: Timer,
+}
+
+impl Widget for PerformanceOverlay {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        if self.refresh_timer.is_event(event).is_some() {
+            self.refresh(cx);
+        }
+        self.view.handle_event(cx, event, scope);
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl PerformanceOverlay {
+    /// Shows this overlay if it's hidden, or hides it if it's shown.
+    fn toggle(&mut self, cx: &mut Cx) {
+        let visible = !self.view.visible();
+        self.view.set_visible(cx, visible);
+        if visible {
+            self.refresh(cx);
+            self.refresh_timer = cx.start_interval(REFRESH_INTERVAL_SECS);
+        } else {
+            cx.stop_timer(self.refresh_timer);
+        }
+        self.redraw(cx);
+    }
+
+    /// Updates the displayed metrics and resets the metrics that are counted per refresh interval.
+    fn refresh(&mut self, cx: &mut Cx) {
+        let num_frames = NUM_FRAMES.swap(0, Ordering::Relaxed);
+        let total_micros = FRAME_TIME_TOTAL_MICROS.swap(0, Ordering::Relaxed);
+        let max_micros = FRAME_TIME_MAX_MICROS.swap(0, Ordering::Relaxed);
+        let avg_micros = total_micros.checked_div(num_frames).unwrap_or(0);
+        self.label(id!(frame_time_label)).set_text(cx, &format!(
+            "Frame time: avg {:.1} ms, max {:.1} ms ({num_frames} frames)",
+            avg_micros as f64 / 1000.0,
+            max_micros as f64 / 1000.0,
+        ));
+
+        let num_timeline_redraws = NUM_TIMELINE_REDRAWS.swap(0, Ordering::Relaxed);
+        self.label(id!(timeline_redraws_label)).set_text(cx, &format!(
+            "Timeline redraws: {num_timeline_redraws}"
+        ));
+
+        self.label(id!(pending_requests_label)).set_text(cx, &format!(
+            "Pending requests: {}", num_pending_requests()
+        ));
+
+        let sync_text = match *LATEST_SYNC_RESPONSE.lock().unwrap() {
+            Some((received, Some(interval))) => format!(
+                "Last sync: {:.1} s ago (interval {:.1} s)",
+                received.elapsed().as_secs_f64(),
+                interval.as_secs_f64(),
+            ),
+            Some((received, None)) => format!("Last sync: {:.1} s ago", received.elapsed().as_secs_f64()),
+            None => String::from("Last sync: none yet"),
+        };
+        self.label(id!(sync_label)).set_text(cx, &sync_text);
+        self.redraw(cx);
+    }
+}
+
+impl PerformanceOverlayRef {
+    /// See [`PerformanceOverlay::toggle()`].
+    pub fn toggle(&self, cx: &mut Cx) {
+        if let Some(mut inner) = self.borrow_mut() {
+            inner.toggle(cx);
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use makepad_widgets::*;
 
+use crate::settings::keyboard_shortcuts::ShortcutAction;
+
 live_design! {
     use link::theme::*;
     use link::shaders::*;
@@ -126,5 +128,12 @@ impl WidgetMatchEvent for SearchBar {
                 SearchBarAction::ResetSearch,
             );
         }
+
+        // Handle the keyboard shortcut for focusing the search bar.
+        for action in actions {
+            if let Some(ShortcutAction::FocusSearch) = action.downcast_ref() {
+                input.set_key_focus(cx);
+            }
+        }
     }
 }
@@ -0,0 +1,305 @@
+//! A bar above the message input that lists the misspelled words in the message being composed.
+//!
+//! Makepad's `TextInput` can't underline individual words, so each misspelled word is instead shown
+//! in this bar with a squiggly underline. Clicking (or right-clicking) a misspelled word shows
+//! suggested corrections, and selecting one replaces that word in the message input.
+
+use std::ops::Range;
+
+use makepad_widgets::*;
+
+use crate::{
+    i18n::{tr, tr_args},
+    spell_check::{self, SpellCheckAction},
+};
+
+/// The maximum number of misspelled words shown at once.
+const MAX_MISSPELLED_WORDS: usize = 10;
+
+live_design! {
+    use link::theme::*;
+    use link::widgets::*;
+
+    use crate::shared::styles::*;
+    use crate::shared::icon_button::RobrixIconButton;
+
+    // A misspelled word, drawn with a red squiggly underline.
+    MisspelledWord = <View> {
+        width: Fit, height: Fit
+        padding: {left: 4, right: 4, top: 2, bottom: 5}
+        cursor: Hand
+        show_bg: true
+        draw_bg: {
+            fn pixel(self) -> vec4 {
+                let p = self.pos * self.rect_size;
+                let wave_y = self.rect_size.y - 2.5 + 1.2 * sin(p.x * 1.3);
+                let alpha = clamp(1.2 - abs(p.y - wave_y), 0.0, 1.0);
+                let color = (COLOR_DANGER_RED);
+                return vec4(color.rgb * alpha, alpha);
+            }
+        }
+        text = <Label> {
+            draw_text: {
+                text_style: <REGULAR_TEXT>{font_size: 9.5},
+                color: (MESSAGE_TEXT_COLOR)
+            }
+        }
+    }
+
+    // A suggested correction of a misspelled word.
+    SpellingSuggestion = <RoundedView> {
+        width: Fit, height: Fit
+        padding: {left: 6, right: 6, top: 3, bottom: 3}
+        cursor: Hand
+        show_bg: true
+        draw_bg: {
+            color: (COLOR_SELECTED_PRIMARY)
+            radius: 3.0
+        }
+        text = <Label> {
+            draw_text: {
+                text_style: <REGULAR_TEXT>{font_size: 9.5},
+                color: (COLOR_TEXT)
+            }
+        }
+    }
+
+    SpellingWordList = {{SpellingWordList}} {
+        width: Fill, height: Fit
+        flow: RightWrap
+        spacing: 6
+        align: {y: 0.5}
+        misspelled_item: <MisspelledWord> {}
+        suggestion_item: <SpellingSuggestion> {}
+    }
+
+    pub SpellingBar = {{SpellingBar}} {
+        visible: false
+        width: Fill, height: Fit
+        flow: Right
+        spacing: 8
+        align: {y: 0.5}
+        padding: {left: 12, right: 10, top: 4, bottom: 4}
+
+        prompt_label = <Label> {
+            width: Fit, height: Fit
+            draw_text: {
+                text_style: <REGULAR_TEXT>{font_size: 9},
+                color: (MESSAGE_NOTICE_TEXT_COLOR)
+            }
+        }
+        word_list = <SpellingWordList> {}
+        back_button = <RobrixIconButton> {
+            visible: false
+            padding: {left: 8, right: 8, top: 4, bottom: 4}
+            draw_icon: {
+                svg_file: (ICON_CLOSE)
+                color: (COLOR_TEXT),
+            }
+            icon_walk: {width: 10, height: 10}
+        }
+    }
+}
+
+/// A word shown in the [`SpellingBar`].
+#[derive(Clone, Debug)]
+enum SpellingWord {
+    /// A misspelled word at the given byte range of the message text.
+    Misspelled { range: Range<usize>, word: String },
+    /// A suggested correction of the currently-selected misspelled word.
+    Suggestion(String),
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+enum SpellingWordAction {
+    None,
+    /// The given word was clicked by the user.
+    Clicked(SpellingWord),
+}
+
+/// A replacement of a misspelled word in the message input, chosen by the user.
+#[derive(Clone, Debug)]
+pub struct SpellingReplacement {
+    /// The byte range of the misspelled word within the message text.
+    pub range: Range<usize>,
+    /// The misspelled word, used to check that the message text hasn't changed since.
+    pub word: String,
+    pub replacement: String,
+}
+
+#[derive(Clone, Debug, DefaultNone)]
+pub enum SpellingBarAction {
+    None,
+    /// The user chose to replace a misspelled word with a suggested correction.
+    Replace(SpellingReplacement),
+}
+
+/// Shows the misspelled words in the message being composed, along with suggested corrections.
+///
+/// This hides itself when spell checking is disabled or there are no misspelled words.
+#[derive(Live, LiveHook, Widget)]
+pub struct SpellingBar {
+    #[deref] view: View,
+    /// The message text that was last checked.
+    #[rust] text: String,
+    /// The misspelled word whose suggestions are being shown, if any.
+    #[rust] selected: Option<(Range<usize>, String)>,
+}
+
+impl Widget for SpellingBar {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        self.view.handle_event(cx, event, scope);
+        let Event::Actions(actions) = event else { return };
+
+        if self.button(id!(back_button)).clicked(actions) {
+            self.selected = None;
+            self.refresh(cx);
+        }
+        let word_list_uid = self.spelling_word_list(id!(word_list)).widget_uid();
+        if let Some(action) = actions.find_widget_action(word_list_uid) {
+            match action.cast() {
+                SpellingWordAction::Clicked(SpellingWord::Misspelled { range, word }) => {
+                    self.selected = Some((range, word));
+                    self.refresh(cx);
+                }
+                SpellingWordAction::Clicked(SpellingWord::Suggestion(replacement)) => {
+                    if let Some((range, word)) = self.selected.take() {
+                        cx.widget_action(
+                            self.widget_uid(),
+                            &scope.path,
+                            SpellingBarAction::Replace(SpellingReplacement { range, word, replacement }),
+                        );
+                    }
+                }
+                SpellingWordAction::None => {}
+            }
+        }
+
+        // `SpellCheckAction`s are posted from a background thread, so they are NOT widget actions.
+        for action in actions {
+            if let Some(SpellCheckAction::DictionaryChanged) = action.downcast_ref() {
+                self.selected = None;
+                self.refresh(cx);
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        self.view.draw_walk(cx, scope, walk)
+    }
+}
+
+impl SpellingBar {
+    /// Shows either the misspelled words in the last-checked text,
+    /// or the suggested corrections of the selected misspelled word.
+    fn refresh(&mut self, cx: &mut Cx) {
+        let words = match spell_check::dictionary() {
+            None => Vec::new(),
+            Some(dictionary) => match &self.selected {
+                Some((_, word)) => spell_check::suggestions(word, &dictionary)
+                    .into_iter()
+                    .map(SpellingWord::Suggestion)
+                    .collect(),
+                None => spell_check::misspelled_words(&self.text, &dictionary)
+                    .into_iter()
+                    // Don't flag the word that is still being typed at the end of the message.
+                    .filter(|range| range.end < self.text.len())
+                    .take(MAX_MISSPELLED_WORDS)
+                    .map(|range| SpellingWord::Misspelled { word: self.text[range.clone()].to_owned(), range })
+                    .collect(),
+            },
+        };
+
+        let prompt = match &self.selected {
+            Some((_, word)) if words.is_empty() => tr_args("spell_check.no_suggestions", &[("word", word)]),
+            Some((_, word)) => tr_args("spell_check.suggestions_for", &[("word", word)]),
+            None => tr("spell_check.misspelled").to_string(),
+        };
+        self.label(id!(prompt_label)).set_text(cx, &prompt);
+        self.button(id!(back_button)).set_visible(cx, self.selected.is_some());
+        self.view.set_visible(cx, self.selected.is_some() || !words.is_empty());
+
+        let word_list = self.spelling_word_list(id!(word_list));
+        if let Some(mut list) = word_list.borrow_mut() {
+            list.set_words(cx, words);
+        }
+        self.redraw(cx);
+    }
+}
+
+impl SpellingBarRef {
+    /// Spell checks the given message text, showing any misspelled words in this bar.
+    pub fn check(&self, cx: &mut Cx, text: &str) {
+        let Some(mut inner) = self.borrow_mut() else { return };
+        if inner.text == text {
+            return;
+        }
+        inner.text = text.to_owned();
+        inner.selected = None;
+        inner.refresh(cx);
+    }
+
+    /// Returns the replacement of a misspelled word that was chosen by the user, if any.
+    pub fn replacement(&self, actions: &Actions) -> Option<SpellingReplacement> {
+        match actions.find_widget_action(self.widget_uid())?.cast() {
+            SpellingBarAction::Replace(replacement) => Some(replacement),
+            SpellingBarAction::None => None,
+        }
+    }
+}
+
+/// The list of words within a [`SpellingBar`].
+#[derive(Live, LiveHook, Widget)]
+struct SpellingWordList {
+    #[redraw] #[rust] area: Area,
+    #[live] misspelled_item: Option<LivePtr>,
+    #[live] suggestion_item: Option<LivePtr>,
+    #[rust] children: Vec<(WidgetRef, SpellingWord)>,
+    #[layout] layout: Layout,
+    #[walk] walk: Walk,
+}
+
+impl Widget for SpellingWordList {
+    fn handle_event(&mut self, cx: &mut Cx, event: &Event, scope: &mut Scope) {
+        for (row, word) in &self.children {
+            row.handle_event(cx, event, scope);
+            // Both left and right clicks select a word, as right-clicking a misspelled word
+            // is how most apps show its suggestions.
+            if let Hit::FingerUp(fe) = event.hits(cx, row.area()) {
+                if fe.is_over && fe.was_tap() {
+                    cx.widget_action(
+                        self.widget_uid(),
+                        &scope.path,
+                        SpellingWordAction::Clicked(word.clone()),
+                    );
+                }
+            }
+        }
+    }
+
+    fn draw_walk(&mut self, cx: &mut Cx2d, scope: &mut Scope, walk: Walk) -> DrawStep {
+        cx.begin_turtle(walk, self.layout);
+        for (row, _) in &self.children {
+            let _ = row.draw(cx, scope);
+        }
+        cx.end_turtle_with_area(&mut self.area);
+        DrawStep::done()
+    }
+}
+
+impl SpellingWordList {
+    /// Replaces the displayed words with the given ones.
+    fn set_words(&mut self, cx: &mut Cx, words: Vec<SpellingWord>) {
+        self.children.clear();
+        for word in words {
+            let (item, text) = match &word {
+                SpellingWord::Misspelled { word, .. } => (self.misspelled_item, word),
+                SpellingWord::Suggestion(suggestion) => (self.suggestion_item, suggestion),
+            };
+            let row = WidgetRef::new_from_ptr(cx, item);
+            row.label(id!(text)).set_text(cx, text);
+            self.children.push((row, word));
+        }
+        self.redraw(cx);
+    }
+}
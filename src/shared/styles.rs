@@ -1,10 +1,13 @@
 use makepad_widgets::*;
 
+use crate::settings::app_settings::DisplayDensity;
+
 live_design! {
     use link::theme::*;
     use link::shaders::*;
     use link::widgets::*;
 
+    pub ICON_ADD          = dep("crate://self/resources/icons/add.svg")
     pub ICON_ADD_REACTION = dep("crate://self/resources/icons/add_reaction.svg")
     pub ICON_BLOCK_USER   = dep("crate://self/resources/icons/forbidden.svg")
     pub ICON_CHECKMARK    = dep("crate://self/resources/icons/checkmark.svg")
@@ -15,6 +18,7 @@ live_design! {
     pub ICON_JUMP         = dep("crate://self/resources/icons/go_back.svg")
     pub ICON_LINK         = dep("crate://self/resources/icons/link.svg")
     pub ICON_PIN          = dep("crate://self/resources/icons/pin.svg")
+    pub ICON_QUOTE        = dep("crate://self/resources/icons/quote.svg")
     pub ICON_REPLY        = dep("crate://self/resources/icons/reply.svg")
     pub ICON_SEND         = dep("crate://self/resources/icon_send.svg")
     pub ICON_TRASH        = dep("crate://self/resources/icons/trash.svg")
@@ -42,6 +46,7 @@ live_design! {
     pub TYPING_NOTICE_TEXT_COLOR = #121570
 
     pub MESSAGE_FONT_SIZE = 11
+    pub LARGE_EMOJI_FONT_SIZE = 28
     pub MESSAGE_TEXT_COLOR = #x333
     // notices (automated messages from bots) use a lighter color
     pub MESSAGE_NOTICE_TEXT_COLOR = #x888
@@ -191,3 +196,47 @@ live_design! {
         }
     }
 }
+
+/// Sizes and spacings in the room timeline and rooms list that depend on
+/// the user's chosen [`DisplayDensity`], which are applied to widgets at runtime.
+///
+/// The `Comfortable` values must match the defaults defined in each widget's DSL.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DensityStyle {
+    /// The padding around the body of a full (non-condensed) message.
+    pub message_padding: f64,
+    /// The width of the column left of a message that shows the sender's avatar and the timestamp.
+    pub message_profile_width: f64,
+    /// The size of the sender's avatar next to a full message.
+    pub message_avatar_size: f64,
+    /// The padding around a room preview in the rooms list.
+    pub room_preview_padding: f64,
+    /// The size of a room's avatar in the rooms list.
+    pub room_avatar_size: f64,
+    /// The height of the room name and latest message next to a room's avatar.
+    pub room_preview_text_height: f64,
+}
+
+impl DensityStyle {
+    /// Returns the sizes and spacings for the given display density.
+    pub const fn of(density: DisplayDensity) -> Self {
+        match density {
+            DisplayDensity::Comfortable => Self {
+                message_padding: 10.0,
+                message_profile_width: 65.0,
+                message_avatar_size: 50.0,
+                room_preview_padding: 10.0,
+                room_avatar_size: 36.0,
+                room_preview_text_height: 56.0,
+            },
+            DisplayDensity::Compact => Self {
+                message_padding: 4.0,
+                message_profile_width: 45.0,
+                message_avatar_size: 32.0,
+                room_preview_padding: 5.0,
+                room_avatar_size: 28.0,
+                room_preview_text_height: 40.0,
+            },
+        }
+    }
+}
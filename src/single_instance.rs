@@ -0,0 +1,136 @@
+//! Ensures that only a single instance of Robrix runs at a time on desktop platforms.
+//!
+//! The first instance listens on a local socket in the app data directory.
+//! Subsequent invocations of Robrix forward the room or user they were asked to open
+//! (via `--room`, `--user` or a deep link) to that instance and then exit immediately,
+//! such that the already-running instance raises its window and navigates to that room or user.
+//!
+//! Currently this is only supported on Unix-like desktop platforms;
+//! elsewhere, multiple instances are not prevented.
+
+#[cfg(all(unix, not(any(target_os = "android", target_os = "ios"))))]
+pub use unix::{forward_to_running_instance, start_listener};
+
+/// Forwards the room or user that this process was asked to open to an already-running instance of Robrix.
+///
+/// Returns `true` if another instance received it, in which case this process should exit.
+#[cfg(not(all(unix, not(any(target_os = "android", target_os = "ios")))))]
+pub fn forward_to_running_instance() -> bool {
+    false
+}
+
+/// Starts listening for messages from later invocations of Robrix.
+#[cfg(not(all(unix, not(any(target_os = "android", target_os = "ios")))))]
+pub fn start_listener() { }
+
+#[cfg(all(unix, not(any(target_os = "android", target_os = "ios"))))]
+mod unix {
+    use std::{io::{self, Read, Write}, os::unix::net::{UnixListener, UnixStream}, path::{Path, PathBuf}, time::Duration};
+
+    use makepad_widgets::Cx;
+    use tracing::{error, info, warn};
+
+    use crate::{deep_link::{self, DeepLinkAction}, sliding_sync::launch_target_from_args};
+
+    /// The message asking the running instance to bring its window to the front.
+    const FOCUS_MESSAGE: &str = "focus";
+    /// The prefix of a message asking the running instance to open the `matrix.to` link that follows it.
+    const OPEN_MESSAGE_PREFIX: &str = "open ";
+    /// The maximum length of a message from another instance, which is far longer than any valid one.
+    const MAX_MESSAGE_LEN: u64 = 4096;
+    /// How long to wait for another instance to send its message before giving up on it.
+    const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+    /// Returns the path of the socket that the running instance listens on.
+    fn socket_path() -> PathBuf {
+        crate::app_data_dir().join("robrix.sock")
+    }
+
+    /// Forwards the room or user that this process was asked to open to an already-running
+    /// instance of Robrix, or asks that instance to bring its window to the front.
+    ///
+    /// Only the target room or user is forwarded, never the other command-line arguments,
+    /// which may contain credentials like a password.
+    ///
+    /// Returns `true` if another instance received the message, in which case this process should exit.
+    pub fn forward_to_running_instance() -> bool {
+        let Ok(mut stream) = UnixStream::connect(socket_path()) else {
+            return false;
+        };
+        let args = std::env::args().collect::<Vec<_>>();
+        let message = match launch_target_from_args(&args) {
+            Some(target) => format!("{OPEN_MESSAGE_PREFIX}{}", target.matrix_to_uri()),
+            None => FOCUS_MESSAGE.to_owned(),
+        };
+        match stream.write_all(message.as_bytes()) {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Failed to forward a message to the running instance of Robrix: {e}");
+                false
+            }
+        }
+    }
+
+    /// Starts listening for messages from later invocations of Robrix.
+    ///
+    /// Each message raises this instance's window via [`DeepLinkAction::Focus`],
+    /// and each forwarded room or user is then posted as a [`DeepLinkAction::Navigate`] action.
+    pub fn start_listener() {
+        let path = socket_path();
+        let listener = match bind_listener(&path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                error!("Failed to listen for other instances of Robrix on {}: {e}", path.display());
+                return;
+            }
+        };
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream.and_then(receive_message) {
+                    Ok(message) => handle_message(&message),
+                    Err(e) => error!("Failed to receive a message from another instance of Robrix: {e}"),
+                }
+            }
+        });
+    }
+
+    /// Binds a listener to the socket at the given path.
+    ///
+    /// A socket left over from an instance that didn't exit cleanly is removed first,
+    /// but only if no other instance accepts connections on it.
+    fn bind_listener(path: &Path) -> io::Result<UnixListener> {
+        match UnixListener::bind(path) {
+            Err(e) if e.kind() == io::ErrorKind::AddrInUse && UnixStream::connect(path).is_err() => {
+                std::fs::remove_file(path)?;
+                UnixListener::bind(path)
+            }
+            result => result,
+        }
+    }
+
+    /// Reads the message sent over the given stream, waiting only a short while for it.
+    fn receive_message(stream: UnixStream) -> io::Result<String> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut message = String::new();
+        stream.take(MAX_MESSAGE_LEN).read_to_string(&mut message)?;
+        Ok(message)
+    }
+
+    /// Handles a message received from another instance of Robrix.
+    fn handle_message(message: &str) {
+        if message.is_empty() {
+            // Another instance merely checked whether we're still running.
+            return;
+        }
+        info!("Received a message from another instance of Robrix: {message:?}");
+        Cx::post_action(DeepLinkAction::Focus);
+        if let Some(link) = message.strip_prefix(OPEN_MESSAGE_PREFIX) {
+            match deep_link::parse_deep_link(link) {
+                Some(target) => Cx::post_action(DeepLinkAction::Navigate(target)),
+                None => warn!("Ignoring an invalid link from another instance of Robrix: {link:?}"),
+            }
+        } else if message != FOCUS_MESSAGE {
+            warn!("Ignoring an unknown message from another instance of Robrix: {message:?}");
+        }
+    }
+}
@@ -5,45 +5,47 @@ use eyeball::Subscriber;
 use eyeball_im::VectorDiff;
 use futures_util::{pin_mut, StreamExt};
 use imbl::Vector;
-use makepad_widgets::{error, log, warning, Cx, SignalToUI};
+use makepad_widgets::{Cx, SignalToUI};
 use matrix_sdk::{
-    config::RequestConfig, event_handler::EventHandlerDropGuard, media::MediaRequest, room::RoomMember, ruma::{
-        api::client::receipt::create_receipt::v3::ReceiptType, events::{
-            receipt::ReceiptThread, room::{
-                message::{ForwardThread, RoomMessageEventContent}, power_levels::RoomPowerLevels, MediaSource
-            }, FullStateEventContent, MessageLikeEventType, StateEventType
-        }, MilliSecondsSinceUnixEpoch, OwnedEventId, OwnedMxcUri, OwnedRoomAliasId, OwnedRoomId, OwnedUserId, UserId
+    config::RequestConfig, deserialized_responses::RawAnySyncOrStrippedState, event_handler::EventHandlerDropGuard, media::MediaRequest, notification_settings::{NotificationSettings, RoomNotificationMode}, room::{MessagesOptions, RoomMember}, ruma::{
+        api::client::{membership::joined_rooms, presence::{get_presence, set_presence}, receipt::create_receipt::v3::ReceiptType, room::upgrade_room}, events::{
+            fully_read::FullyReadEventContent, receipt::ReceiptThread, room::{
+                guest_access::{GuestAccess, RoomGuestAccessEventContent}, history_visibility::{HistoryVisibility, RoomHistoryVisibilityEventContent}, member::MembershipState, message::{ForwardThread, RoomMessageEventContent}, power_levels::RoomPowerLevels, MediaSource
+            }, typing::SyncTypingEvent, AnyTimelineEvent, FullStateEventContent, MessageLikeEventType, RoomAccountDataEventType, StateEventType
+        }, matrix_uri::MatrixId, presence::PresenceState, Int, MilliSecondsSinceUnixEpoch, OwnedDeviceId, OwnedEventId, OwnedMxcUri, OwnedRoomAliasId, OwnedRoomId, OwnedRoomOrAliasId, OwnedUserId, RoomId, RoomVersionId, UserId, serde::Raw
     }, sliding_sync::VersionBuilder, Client, ClientBuildError, Error, Room, RoomMemberships
 };
 use matrix_sdk_ui::{
     room_list_service::{self, RoomListLoadingState}, sync_service::{self, SyncService}, timeline::{AnyOtherFullStateEventContent, EventTimelineItem, MembershipChange, RepliedToInfo, TimelineEventItemId, TimelineItem, TimelineItemContent}, RoomListService, Timeline
 };
 use robius_open::Uri;
+use tracing::{error, info, warn};
 use tokio::{
     runtime::Handle,
-    sync::{mpsc::{Receiver, Sender, UnboundedReceiver, UnboundedSender}, watch, Notify}, task::JoinHandle,
+    sync::{broadcast, mpsc::{Receiver, Sender, UnboundedReceiver, UnboundedSender}, watch, Notify, OnceCell}, task::JoinHandle,
 };
 use unicode_segmentation::UnicodeSegmentation;
 use url::Url;
-use std::{cmp::{max, min}, collections::{BTreeMap, BTreeSet}, ops::Not, path:: Path, sync::{Arc, LazyLock, Mutex, OnceLock}};
+use std::{cmp::{max, min}, collections::{BTreeMap, BTreeSet}, ops::Not, path:: Path, sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, LazyLock, Mutex, OnceLock}};
 use std::io;
 use crate::{
-    app_data_dir, avatar_cache::AvatarUpdate, event_preview::text_preview_of_timeline_item, home::{
-        room_screen::TimelineUpdate, rooms_list::{self, enqueue_rooms_list_update, RoomPreviewAvatar, RoomsListEntry, RoomsListUpdate}
-    }, login::login_screen::LoginAction, media_cache::MediaCacheEntry, persistent_state::{self, ClientSessionPersisted}, profile::{
+    app_data_dir, avatar_cache::AvatarUpdate, bridges::{self, RoomBridge}, deep_link::{self, DeepLinkAction}, event_preview::text_preview_of_timeline_item, i18n::{tr, tr_args}, home::{
+        contacts::{Contact, ContactsUpdate}, inbox::{enqueue_inbox_entry, InboxEntry}, leave_room_modal::LeaveRoomModalAction, moderation_dashboard::{RoomUpgradeAction, RoomUpgradeStep}, room_hover_preview::{RoomHoverPreviewAction, RoomHoverPreviewInfo, NUM_HOVER_PREVIEW_MESSAGES}, room_screen::TimelineUpdate, rooms_list::{self, enqueue_rooms_list_update, RoomPreviewAvatar, RoomsListEntry, RoomsListUpdate}
+    }, identity_server::{self, IdentityServerError, ThreePidMedium}, login::login_screen::LoginAction, media_cache::MediaCacheEntry, persistent_state::{self, ClientSessionPersisted}, policy_lists, profile::{
+        presence::{insert_presence, UserPresence},
         user_profile::{AvatarState, UserProfile},
-        user_profile_cache::{enqueue_user_profile_update, UserProfileUpdate},
-    }, shared::{jump_to_bottom_button::UnreadMessageCount, popup_list::enqueue_popup_notification}, utils::{self, AVATAR_THUMBNAIL_FORMAT}, verification::add_verification_event_handlers_and_sync_client
+        user_profile_cache::{enqueue_user_profile_update, SharedRoom, UserProfileUpdate},
+    }, room_export::{self, RoomExportAction, RoomExportFormat}, room_wallpaper::{self, RoomWallpaper, ROOM_WALLPAPER_EVENT_TYPE}, scheduled_messages, settings::app_settings::app_settings, shared::{jump_to_bottom_button::UnreadMessageCount, performance_overlay, popup_list::enqueue_popup_notification}, utils::{self, AVATAR_THUMBNAIL_FORMAT}, verification::{self, add_verification_event_handlers_and_sync_client}
 };
 
 #[derive(Parser, Debug, Default)]
 struct Cli {
     /// The user ID to login with.
-    #[clap(value_parser)]
+    #[clap(value_parser, default_value = "")]
     user_id: String,
 
     /// The password that should be used for the login.
-    #[clap(value_parser)]
+    #[clap(value_parser, default_value = "")]
     password: String,
 
     /// The homeserver to connect to.
@@ -61,6 +63,14 @@ struct Cli {
     /// Enable verbose logging output.
     #[clap(short, long, action)]
     verbose: bool,
+
+    /// The ID or alias of a room to open upon launch.
+    #[clap(long, conflicts_with = "user")]
+    room: Option<OwnedRoomOrAliasId>,
+
+    /// The ID of a user whose direct message room should be opened upon launch.
+    #[clap(long)]
+    user: Option<OwnedUserId>,
 }
 impl From<LoginByPassword> for Cli {
     fn from(login: LoginByPassword) -> Self {
@@ -71,10 +81,38 @@ impl From<LoginByPassword> for Cli {
             proxy: None,
             login_screen: false,
             verbose: false,
+            room: None,
+            user: None,
         }
     }
 }
 
+/// Returns the room or user that should be opened upon launch, if any,
+/// as specified by the given command-line arguments.
+///
+/// This is either a deep link (see [`deep_link::parse_deep_link()`])
+/// or the value of the `--room` or `--user` argument.
+pub fn launch_target_from_args(args: &[String]) -> Option<MatrixId> {
+    if let Some(target) = args.iter().skip(1).find_map(|arg| deep_link::parse_deep_link(arg)) {
+        return Some(target);
+    }
+    let cli = parse_cli(args.iter().cloned()).ok()?;
+    match (cli.room, cli.user) {
+        (Some(room_or_alias_id), _) => Some(match OwnedRoomId::try_from(room_or_alias_id) {
+            Ok(room_id) => MatrixId::Room(room_id),
+            Err(room_alias_id) => MatrixId::RoomAlias(room_alias_id),
+        }),
+        (None, Some(user_id)) => Some(MatrixId::User(user_id)),
+        (None, None) => None,
+    }
+}
+
+/// Parses the given command-line arguments, ignoring any deep links,
+/// which are handled by the app itself.
+fn parse_cli(args: impl IntoIterator<Item = String>) -> Result<Cli, clap::Error> {
+    Cli::try_parse_from(args.into_iter().filter(|arg| !deep_link::is_deep_link(arg)))
+}
+
 
 /// Build a new client.
 async fn build_client(
@@ -86,6 +124,7 @@ async fn build_client(
     let now = chrono::Local::now();
     let db_subfolder_name: String = format!("db_{}", now.format("%F_%H_%M_%S_%f"));
     let db_path = data_dir.join(db_subfolder_name);
+    persistent_state::mark_db_path_in_use(&db_path);
 
     // Generate a random passphrase.
     let passphrase: String = {
@@ -109,8 +148,10 @@ async fn build_client(
         .sliding_sync_version_builder(VersionBuilder::DiscoverNative)
         .handle_refresh_tokens();
 
-    if let Some(proxy) = cli.proxy.as_ref() {
-        builder = builder.proxy(proxy.clone());
+    // A proxy given on the command line takes precedence over the one in the app settings.
+    let proxy = cli.proxy.clone().or_else(|| app_settings().proxy.url());
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy);
     }
 
     // Use a 60 second timeout for all requests to the homeserver.
@@ -156,7 +197,7 @@ async fn login(
                 .send()
                 .await?;
             if client.logged_in() {
-                log!("Logged in successfully? {:?}", client.logged_in());
+                info!("Logged in successfully? {:?}", client.logged_in());
                 let status = format!("Logged in as {}.\n → Loading rooms...", cli.user_id);
                 // enqueue_popup_notification(status.clone());
                 enqueue_rooms_list_update(RoomsListUpdate::Status { status });
@@ -232,6 +273,11 @@ pub enum MatrixRequest {
         room_id: OwnedRoomId,
         event_id: OwnedEventId,
     },
+    /// Request to fetch the avatar image of the given room,
+    /// which is then sent to the rooms list as a `RoomsListUpdate::UpdateRoomAvatar`.
+    FetchRoomAvatar {
+        room_id: OwnedRoomId,
+    },
     /// Request to fetch profile information for all members of a room.
     /// This can be *very* slow depending on the number of members in the room.
     FetchRoomMembers {
@@ -249,6 +295,42 @@ pub enum MatrixRequest {
         /// * If `false` (recommended), details will be fetched from the server.
         local_only: bool,
     },
+    /// Request to find all joined rooms that the current user shares with the given user.
+    ///
+    /// Upon completion, the list of shared rooms is inserted into the user profile cache.
+    GetSharedRooms {
+        user_id: OwnedUserId,
+    },
+    /// Request to fetch the currently-known devices of the given user,
+    /// which are then posted to the main UI thread as a `UserDevicesAction`.
+    GetUserDevices {
+        user_id: OwnedUserId,
+    },
+    /// Request to start interactive verification with the given device of the given user.
+    VerifyDevice {
+        user_id: OwnedUserId,
+        device_id: OwnedDeviceId,
+    },
+    /// Request to change the current user's global display name.
+    ///
+    /// If this fails, the user's profile is re-fetched such that any optimistic
+    /// updates to the user profile cache are reverted.
+    SetDisplayName {
+        /// The new display name, or `None` to remove the current display name.
+        new_display_name: Option<String>,
+    },
+    /// Request to fetch the presence (online status) of the given user.
+    ///
+    /// Upon completion, the fetched presence is inserted into the presence cache.
+    GetPresence {
+        user_id: OwnedUserId,
+    },
+    /// Request to set the current user's presence and custom status message.
+    SetPresence {
+        presence: PresenceState,
+        /// The new status message, or `None` to clear the current status message.
+        status_msg: Option<String>,
+    },
     /// Request to fetch the number of unread messages in the given room.
     GetNumberUnreadMessages {
         room_id: OwnedRoomId,
@@ -265,6 +347,50 @@ pub enum MatrixRequest {
     },
     /// Request to resolve a room alias into a room ID and the servers that know about that room.
     ResolveRoomAlias(OwnedRoomAliasId),
+    /// Request to export the entire history of the given room to a file.
+    ///
+    /// Setting the `cancel` flag stops the export as soon as possible.
+    ExportRoomHistory {
+        room_id: OwnedRoomId,
+        format: RoomExportFormat,
+        cancel: Arc<AtomicBool>,
+    },
+    /// Request to export the given room's member list to a CSV file,
+    /// with the result being posted as a [`RoomExportAction`].
+    ExportRoomMembers {
+        room_id: OwnedRoomId,
+        cancel: Arc<AtomicBool>,
+    },
+    /// Request to subscribe the given room to the given moderation policy list,
+    /// such that members matching the list's ban rules are banned automatically.
+    SubscribePolicyList {
+        room_id: OwnedRoomId,
+        list: OwnedRoomOrAliasId,
+    },
+    /// Request to unsubscribe the given room from the given moderation policy list.
+    UnsubscribePolicyList {
+        room_id: OwnedRoomId,
+        list: OwnedRoomOrAliasId,
+    },
+    /// Request to change who can read the given room's history.
+    SetHistoryVisibility {
+        room_id: OwnedRoomId,
+        visibility: HistoryVisibility,
+    },
+    /// Request to change whether guest users can join the given room.
+    SetGuestAccess {
+        room_id: OwnedRoomId,
+        guest_access: GuestAccess,
+    },
+    /// Request to upgrade the given room to a newer room version,
+    /// which replaces it with a successor room and announces the upgrade in the old room.
+    ///
+    /// If `new_version` is `None`, the server's default room version is used.
+    /// Progress is posted as [`RoomUpgradeAction`]s.
+    UpgradeRoom {
+        room_id: OwnedRoomId,
+        new_version: Option<RoomVersionId>,
+    },
     /// Request to fetch an Avatar image from the server.
     /// Upon completion of the async media request, the `on_fetched` function
     /// will be invoked with the content of an `AvatarUpdate`.
@@ -326,24 +452,113 @@ pub enum MatrixRequest {
     ReadReceipt {
         room_id: OwnedRoomId,
         event_id: OwnedEventId,
+        /// If `true`, a private read receipt (`m.read.private`) is sent,
+        /// which is only visible to our own user and not to other room members.
+        private: bool,
     },
     /// Sends a fully-read receipt for the given event in the given room.
     FullyReadReceipt {
         room_id: OwnedRoomId,
         event_id: OwnedEventId,
     },
+    /// Moves the fully-read marker to the given event in the given room.
+    ///
+    /// Servers ignore attempts to move the marker backwards, so this is refused
+    /// if the given event is earlier than the marker's current position.
+    MoveFullyReadMarker {
+        room_id: OwnedRoomId,
+        event_id: OwnedEventId,
+    },
+    /// Marks the given room as read up to its latest event.
+    MarkRoomAsRead {
+        room_id: OwnedRoomId,
+    },
+    /// Mutes all notifications for the given room,
+    /// or restores its default notification mode if it's already muted.
+    ToggleRoomMute {
+        room_id: OwnedRoomId,
+    },
+    /// Adds the given room to or removes it from the user's favorites
+    /// by setting or removing its `m.favourite` tag.
+    SetRoomFavourite {
+        room_id: OwnedRoomId,
+        is_favourite: bool,
+    },
+    /// Leaves the given room.
+    LeaveRoom {
+        room_id: OwnedRoomId,
+        /// Whether to also forget the room after leaving it,
+        /// which removes it from the user's list of past rooms.
+        forget: bool,
+        /// The user to whom the current user's power level should be granted before leaving,
+        /// e.g., because the current user is the room's last admin.
+        transfer_admin_to: Option<OwnedUserId>,
+    },
+    /// Checks whether the current user is the last admin of the given room.
+    ///
+    /// The result is posted as a [`LeaveRoomModalAction::LastAdminChecked`] action.
+    CheckLastAdmin {
+        room_id: OwnedRoomId,
+    },
     /// Sends a request to obtain the power levels for this room.
     ///
     /// The response is delivered back to the main UI thread via [`TimelineUpdate::UserPowerLevels`].
     GetRoomPowerLevels {
         room_id: OwnedRoomId,
     },
+    /// Request to fetch the bridges to other chat platforms that are set up in the given room.
+    ///
+    /// Upon completion, a [`TimelineUpdate::RoomBridgesFetched`] is sent to the room's timeline.
+    GetRoomBridges {
+        room_id: OwnedRoomId,
+    },
+    /// Request to fetch the topic, member count, and latest messages of the given room,
+    /// which are shown when hovering over that room in the rooms list.
+    ///
+    /// Upon completion, a [`RoomHoverPreviewAction::Loaded`] action is posted.
+    GetRoomHoverPreview {
+        room_id: OwnedRoomId,
+    },
+    /// Request to fetch the wallpaper of the given room from the user's room account data.
+    ///
+    /// Upon completion, a [`TimelineUpdate::RoomWallpaperChanged`] is sent to the room's timeline.
+    GetRoomWallpaper {
+        room_id: OwnedRoomId,
+    },
+    /// Saves the given wallpaper to the given room's account data,
+    /// or removes the room's wallpaper if `wallpaper` is `None`.
+    SetRoomWallpaper {
+        room_id: OwnedRoomId,
+        wallpaper: Option<RoomWallpaper>,
+    },
     /// Toggles the given reaction to the given event in the given room.
     ToggleReaction {
         room_id: OwnedRoomId,
         timeline_event_id: TimelineEventItemId,
         reaction: String,
     },
+    /// Kicks the given user from the given room.
+    KickUser {
+        room_id: OwnedRoomId,
+        user_id: OwnedUserId,
+        reason: Option<String>,
+    },
+    /// Bans the given user from the given room.
+    BanUser {
+        room_id: OwnedRoomId,
+        user_id: OwnedUserId,
+        reason: Option<String>,
+    },
+    /// Redacts all messages that the given user sent in the given room since the given time.
+    ///
+    /// Progress is reported via [`RedactUserMessagesAction`]s.
+    RedactUserMessages {
+        room_id: OwnedRoomId,
+        user_id: OwnedUserId,
+        since: MilliSecondsSinceUnixEpoch,
+        /// The reason given for each redaction, which is visible to other room members.
+        reason: Option<String>,
+    },
     /// Redacts (deletes) the given event in the given room.
     #[doc(alias("delete"))]
     RedactMessage {
@@ -351,10 +566,44 @@ pub enum MatrixRequest {
         timeline_event_id: TimelineEventItemId,
         reason: Option<String>,
     },
+    /// Retries sending the given local echo of a message that failed to send.
+    RetrySendMessage {
+        room_id: OwnedRoomId,
+        timeline_event_id: TimelineEventItemId,
+    },
+    /// Request to fetch the current user's contacts, i.e., the users with whom they have a DM room.
+    ///
+    /// Upon completion, a [`ContactsUpdate::Loaded`] action is posted.
+    GetContacts,
+    /// Request to open the DM room with the given user, creating it if it doesn't yet exist.
+    ///
+    /// Upon completion, either a [`ContactsUpdate::DirectMessageReady`]
+    /// or a [`ContactsUpdate::DirectMessageFailed`] action is posted.
+    OpenOrCreateDirectMessage {
+        user_id: OwnedUserId,
+    },
+    /// Request to look up the user bound to the given email address or phone number
+    /// via the identity server, which the user must have already consented to.
+    ///
+    /// Upon completion, a [`ContactsUpdate`] action with the result is posted.
+    LookupThreePid {
+        medium: ThreePidMedium,
+        address: String,
+    },
+}
+
+/// The number of requests that have been submitted to the worker thread
+/// but not yet received by it.
+static NUM_PENDING_REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of requests that are waiting to be handled by the worker thread.
+pub fn num_pending_requests() -> usize {
+    NUM_PENDING_REQUESTS.load(Ordering::Relaxed)
 }
 
 /// Submits a request to the worker thread to be executed asynchronously.
 pub fn submit_async_request(req: MatrixRequest) {
+    NUM_PENDING_REQUESTS.fetch_add(1, Ordering::Relaxed);
     REQUEST_SENDER.get()
         .unwrap() // this is initialized
         .send(req)
@@ -385,9 +634,10 @@ async fn async_worker(
     mut request_receiver: UnboundedReceiver<MatrixRequest>,
     login_sender: Sender<LoginRequest>,
 ) -> Result<()> {
-    log!("Started async_worker task.");
+    info!("Started async_worker task.");
     let subscribe_to_current_user_read_receipt_changed: std::sync::Arc<tokio::sync::Mutex<BTreeMap<OwnedRoomId, bool>>> = Arc::new(tokio::sync::Mutex::new(BTreeMap::new()));
     while let Some(request) = request_receiver.recv().await {
+        NUM_PENDING_REQUESTS.fetch_sub(1, Ordering::Relaxed);
         match request {
             MatrixRequest::Login(login_request) => {
                 if let Err(e) = login_sender.send(login_request).await {
@@ -401,7 +651,7 @@ async fn async_worker(
                 let (timeline, sender) = {
                     let mut all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get_mut(&room_id) else {
-                        log!("Skipping pagination request for not-yet-known room {room_id}");
+                        info!("Skipping pagination request for not-yet-known room {room_id}");
                         continue;
                     };
 
@@ -412,7 +662,7 @@ async fn async_worker(
 
                 // Spawn a new async task that will make the actual pagination request.
                 let _paginate_task = Handle::current().spawn(async move {
-                    log!("Starting {direction} pagination request for room {room_id}...");
+                    info!("Starting {direction} pagination request for room {room_id}...");
                     sender.send(TimelineUpdate::PaginationRunning(direction)).unwrap();
                     SignalToUI::set_ui_signal();
 
@@ -424,7 +674,7 @@ async fn async_worker(
 
                     match res {
                         Ok(fully_paginated) => {
-                            log!("Completed {direction} pagination request for room {room_id}, hit {} of timeline? {}",
+                            info!("Completed {direction} pagination request for room {room_id}, hit {} of timeline? {}",
                                 if direction == PaginationDirection::Forwards { "end" } else { "start" },
                                 if fully_paginated { "yes" } else { "no" },
                             );
@@ -446,11 +696,20 @@ async fn async_worker(
                 });
             }
 
+            MatrixRequest::FetchRoomAvatar { room_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    info!("Skipping avatar fetch request for unknown room {room_id}");
+                    continue;
+                };
+                spawn_fetch_room_avatar(room);
+            }
+
             MatrixRequest::FetchDetailsForEvent { room_id, event_id } => {
                 let (timeline, sender) = {
                     let mut all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get_mut(&room_id) else {
-                        log!("BUG: room info not found for fetch details for event request {room_id}");
+                        info!("BUG: room info not found for fetch details for event request {room_id}");
                         continue;
                     };
 
@@ -459,11 +718,11 @@ async fn async_worker(
 
                 // Spawn a new async task that will make the actual fetch request.
                 let _fetch_task = Handle::current().spawn(async move {
-                    // log!("Sending request to fetch details for event {event_id} in room {room_id}...");
+                    // info!("Sending request to fetch details for event {event_id} in room {room_id}...");
                     let result = timeline.fetch_details_for_event(&event_id).await;
                     match result {
                         Ok(_) => {
-                            // log!("Successfully fetched details for event {event_id} in room {room_id}.");
+                            // info!("Successfully fetched details for event {event_id} in room {room_id}.");
                         }
                         Err(ref _e) => {
                             // error!("Error fetching details for event {event_id} in room {room_id}: {e:?}");
@@ -481,7 +740,7 @@ async fn async_worker(
                 let (timeline, sender) = {
                     let all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get(&room_id) else {
-                        log!("BUG: room info not found for fetch members request {room_id}");
+                        info!("BUG: room info not found for fetch members request {room_id}");
                         continue;
                     };
 
@@ -490,9 +749,9 @@ async fn async_worker(
 
                 // Spawn a new async task that will make the actual fetch request.
                 let _fetch_task = Handle::current().spawn(async move {
-                    log!("Sending fetch room members request for room {room_id}...");
+                    info!("Sending fetch room members request for room {room_id}...");
                     timeline.fetch_members().await;
-                    log!("Completed fetch room members request for room {room_id}.");
+                    info!("Completed fetch room members request for room {room_id}.");
                     sender.send(TimelineUpdate::RoomMembersFetched).unwrap();
                     SignalToUI::set_ui_signal();
                 });
@@ -501,7 +760,7 @@ async fn async_worker(
             MatrixRequest::GetUserProfile { user_id, room_id, local_only } => {
                 let Some(client) = CLIENT.get() else { continue };
                 let _fetch_task = Handle::current().spawn(async move {
-                    // log!("Sending get user profile request: user: {user_id}, \
+                    // info!("Sending get user profile request: user: {user_id}, \
                     //     room: {room_id:?}, local_only: {local_only}...",
                     // );
 
@@ -525,10 +784,10 @@ async fn async_worker(
                                     room_member,
                                 });
                             } else {
-                                log!("User profile request: user {user_id} was not a member of room {room_id}");
+                                info!("User profile request: user {user_id} was not a member of room {room_id}");
                             }
                         } else {
-                            log!("User profile request: client could not get room with ID {room_id}");
+                            info!("User profile request: client could not get room with ID {room_id}");
                         }
                     }
 
@@ -543,7 +802,7 @@ async fn async_worker(
                                     }
                                 ));
                             } else {
-                                log!("User profile request: client could not get user with ID {user_id}");
+                                info!("User profile request: client could not get user with ID {user_id}");
                             }
                         }
 
@@ -558,10 +817,10 @@ async fn async_worker(
                     }
 
                     if let Some(upd) = update {
-                        // log!("Successfully completed get user profile request: user: {user_id}, room: {room_id:?}, local_only: {local_only}.");
+                        // info!("Successfully completed get user profile request: user: {user_id}, room: {room_id:?}, local_only: {local_only}.");
                         enqueue_user_profile_update(upd);
                     } else {
-                        log!("Failed to get user profile: user: {user_id}, room: {room_id:?}, local_only: {local_only}.");
+                        info!("Failed to get user profile: user: {user_id}, room: {room_id:?}, local_only: {local_only}.");
                     }
                 });
             }
@@ -569,7 +828,7 @@ async fn async_worker(
                 let (timeline, sender) = {
                     let mut all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get_mut(&room_id) else {
-                        log!("Skipping get number of unread messages request for not-yet-known room {room_id}");
+                        info!("Skipping get number of unread messages request for not-yet-known room {room_id}");
                         continue;
                     };
 
@@ -580,7 +839,7 @@ async fn async_worker(
                         UnreadMessageCount::Known(timeline.room().num_unread_messages())
                     )) {
                         Ok(_) => SignalToUI::set_ui_signal(),
-                        Err(e) => log!("Failed to send timeline update: {e:?} for GetNumberUnreadMessages request for room {room_id}"),
+                        Err(e) => info!("Failed to send timeline update: {e:?} for GetNumberUnreadMessages request for room {room_id}"),
                     }
                     enqueue_rooms_list_update(RoomsListUpdate::UpdateNumUnreadMessages {
                         room_id: room_id.clone(),
@@ -589,18 +848,118 @@ async fn async_worker(
                     });
                 });
             }
+            MatrixRequest::GetSharedRooms { user_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let _get_shared_rooms_task = Handle::current().spawn(async move {
+                    let mut shared_rooms = Vec::new();
+                    for room in client.joined_rooms() {
+                        // Only check the locally-known members of each room, because fetching
+                        // the full member list of every joined room would be far too costly.
+                        match room.get_member_no_sync(&user_id).await {
+                            Ok(Some(member)) if *member.membership() == MembershipState::Join => {
+                                shared_rooms.push(SharedRoom {
+                                    room_id: room.room_id().to_owned(),
+                                    room_name: room.cached_display_name().map(|dn| dn.to_string()),
+                                });
+                            }
+                            Ok(_) => { }
+                            Err(e) => warn!("Failed to get member {user_id} of room {}: {e:?}", room.room_id()),
+                        }
+                    }
+                    info!("Found {} rooms shared with user {user_id}", shared_rooms.len());
+                    enqueue_user_profile_update(UserProfileUpdate::SharedRooms { user_id, shared_rooms });
+                });
+            }
+
+            MatrixRequest::GetUserDevices { user_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let _get_user_devices_task = Handle::current().spawn(
+                    verification::fetch_user_devices(client.clone(), user_id)
+                );
+            }
+
+            MatrixRequest::VerifyDevice { user_id, device_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let _verify_device_task = Handle::current().spawn(
+                    verification::request_device_verification(client.clone(), user_id, device_id)
+                );
+            }
+
+            MatrixRequest::SetDisplayName { new_display_name } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let _set_display_name_task = Handle::current().spawn(async move {
+                    match client.account().set_display_name(new_display_name.as_deref()).await {
+                        Ok(()) => {
+                            info!("Successfully set display name to {new_display_name:?}");
+                            enqueue_popup_notification(tr("profile.display_name_saved").to_string());
+                        }
+                        Err(e) => {
+                            error!("Failed to set display name to {new_display_name:?}: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "profile.display_name_failed",
+                                &[("error", &e.to_string())],
+                            ));
+                            if let Some(user_id) = client.user_id() {
+                                submit_async_request(MatrixRequest::GetUserProfile {
+                                    user_id: user_id.to_owned(),
+                                    room_id: None,
+                                    local_only: false,
+                                });
+                            }
+                        }
+                    }
+                });
+            }
+
+            MatrixRequest::GetPresence { user_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let _get_presence_task = Handle::current().spawn(async move {
+                    let request = get_presence::v3::Request::new(user_id.clone());
+                    match client.send(request, None).await {
+                        Ok(response) => insert_presence(user_id, UserPresence {
+                            state: response.presence,
+                            status_msg: response.status_msg,
+                        }),
+                        Err(e) => warn!("Failed to fetch presence of user {user_id}: {e:?}"),
+                    }
+                });
+            }
+
+            MatrixRequest::SetPresence { presence, status_msg } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(user_id) = client.user_id().map(ToOwned::to_owned) else { continue };
+                let _set_presence_task = Handle::current().spawn(async move {
+                    let mut request = set_presence::v3::Request::new(user_id.clone(), presence.clone());
+                    request.status_msg = status_msg.clone();
+                    match client.send(request, None).await {
+                        Ok(_) => {
+                            info!("Successfully set presence to {presence:?}, status {status_msg:?}");
+                            insert_presence(user_id, UserPresence { state: presence, status_msg });
+                            enqueue_popup_notification(tr("presence.status_saved").to_string());
+                        }
+                        Err(e) => {
+                            error!("Failed to set presence to {presence:?}: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "presence.status_failed",
+                                &[("error", &e.to_string())],
+                            ));
+                        }
+                    }
+                });
+            }
+
             MatrixRequest::IgnoreUser { ignore, room_member, room_id } => {
                 let Some(client) = CLIENT.get() else { continue };
                 let _ignore_task = Handle::current().spawn(async move {
                     let user_id = room_member.user_id();
-                    log!("Sending request to {}ignore user: {user_id}...", if ignore { "" } else { "un" });
+                    info!("Sending request to {}ignore user: {user_id}...", if ignore { "" } else { "un" });
                     let ignore_result = if ignore {
                         room_member.ignore().await
                     } else {
                         room_member.unignore().await
                     };
 
-                    log!("{} user {user_id} {}",
+                    info!("{} user {user_id} {}",
                         if ignore { "Ignoring" } else { "Unignoring" },
                         if ignore_result.is_ok() { "succeeded." } else { "failed." },
                     );
@@ -615,7 +974,7 @@ async fn async_worker(
                     // with the now-stale ignored state.
                     if let Some(room) = client.get_room(&room_id) {
                         if let Ok(Some(new_room_member)) = room.get_member(user_id).await {
-                            log!("Enqueueing user profile update for user {user_id}, who went from {}ignored to {}ignored.",
+                            info!("Enqueueing user profile update for user {user_id}, who went from {}ignored to {}ignored.",
                                 if room_member.is_ignored() { "" } else { "un" },
                                 if new_room_member.is_ignored() { "" } else { "un" },
                             );
@@ -658,12 +1017,12 @@ async fn async_worker(
                 let (room, timeline_update_sender, mut typing_notice_receiver) = {
                     let mut all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get_mut(&room_id) else {
-                        log!("BUG: room info not found for subscribe to typing notices request, room {room_id}");
+                        info!("BUG: room info not found for subscribe to typing notices request, room {room_id}");
                         continue;
                     };
                     let (room, recv) = if subscribe {
                         if room_info.typing_notice_subscriber.is_some() {
-                            warning!("Note: room {room_id} is already subscribed to typing notices.");
+                            warn!("Note: room {room_id} is already subscribed to typing notices.");
                             continue;
                         } else {
                             let Some(room) = CLIENT.get().and_then(|c| c.get_room(&room_id)) else {
@@ -684,24 +1043,23 @@ async fn async_worker(
 
                 let _typing_notices_task = Handle::current().spawn(async move {
                     while let Ok(user_ids) = typing_notice_receiver.recv().await {
-                        // log!("Received typing notifications for room {room_id}: {user_ids:?}");
+                        // info!("Received typing notifications for room {room_id}: {user_ids:?}");
                         let mut users = Vec::with_capacity(user_ids.len());
                         for user_id in user_ids {
-                            users.push(
-                                room.get_member_no_sync(&user_id)
-                                    .await
-                                    .ok()
-                                    .flatten()
-                                    .and_then(|m| m.display_name().map(|d| d.to_owned()))
-                                    .unwrap_or_else(|| user_id.to_string())
-                            );
+                            let name = room.get_member_no_sync(&user_id)
+                                .await
+                                .ok()
+                                .flatten()
+                                .and_then(|m| m.display_name().map(|d| d.to_owned()))
+                                .unwrap_or_else(|| user_id.to_string());
+                            users.push((user_id, name));
                         }
                         if let Err(e) = timeline_update_sender.send(TimelineUpdate::TypingUsers { users }) {
                             error!("Error: timeline update sender couldn't send the list of typing users: {e:?}");
                         }
                         SignalToUI::set_ui_signal();
                     }
-                    // log!("Note: typing notifications recv loop has ended for room {}", room_id);
+                    // info!("Note: typing notifications recv loop has ended for room {}", room_id);
                 });
             }
             MatrixRequest::SubscribeToOwnUserReadReceiptsChanged { room_id, subscribe } => {
@@ -709,7 +1067,7 @@ async fn async_worker(
                 let (timeline, sender) = {
                     let mut all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get_mut(&room_id) else {
-                        log!("BUG: room info not found for subscribe to own user read receipts changed request, room {room_id}");
+                        info!("BUG: room info not found for subscribe to own user read receipts changed request, room {room_id}");
                         continue;
                     };
                     (room_info.timeline.clone(), room_info.timeline_update_sender.clone())
@@ -731,7 +1089,7 @@ async fn async_worker(
                     pin_mut!(update_receiver);
                     if let Some(client_user_id) = current_user_id() {
                         if let Some((event_id, receipt)) = timeline.latest_user_read_receipt(&client_user_id).await {
-                            log!("Received own user read receipt: {receipt:?} {event_id:?}");
+                            info!("Received own user read receipt: {receipt:?} {event_id:?}");
                             if let Err(e) = sender.send(TimelineUpdate::OwnUserReadReceipt(receipt)) {
                                 error!("Failed to get own user read receipt: {e:?}");
                             }
@@ -758,22 +1116,170 @@ async fn async_worker(
             MatrixRequest::ResolveRoomAlias(room_alias) => {
                 let Some(client) = CLIENT.get() else { continue };
                 let _resolve_task = Handle::current().spawn(async move {
-                    log!("Sending resolve room alias request for {room_alias}...");
-                    let res = client.resolve_room_alias(&room_alias).await;
-                    log!("Resolved room alias {room_alias} to: {res:?}");
-                    todo!("Send the resolved room alias back to the UI thread somehow.");
+                    info!("Sending resolve room alias request for {room_alias}...");
+                    match client.resolve_room_alias(&room_alias).await {
+                        Ok(response) => {
+                            info!("Resolved room alias {room_alias} to: {}", response.room_id);
+                            // Ask the server which rooms we've joined, since the local state
+                            // may not have been synced yet.
+                            let is_joined = client.send(joined_rooms::v3::Request::new(), None).await
+                                .map(|joined| joined.joined_rooms.contains(&response.room_id))
+                                .unwrap_or_else(|e| {
+                                    warn!("Failed to fetch joined rooms, assuming {} is joined: {e:?}", response.room_id);
+                                    true
+                                });
+                            if is_joined {
+                                Cx::post_action(DeepLinkAction::Navigate(MatrixId::Room(response.room_id)));
+                            } else {
+                                info!("Room {} (alias {room_alias}) isn't joined, opening it externally.", response.room_id);
+                                Cx::post_action(DeepLinkAction::OpenExternally(room_alias.matrix_to_uri().to_string()));
+                            }
+                        }
+                        Err(e) => {
+                            error!("Failed to resolve room alias {room_alias}: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "deep_link.alias_not_found",
+                                &[("alias", room_alias.as_str())],
+                            ));
+                        }
+                    }
+                });
+            }
+            MatrixRequest::SubscribePolicyList { room_id, list } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for policy list subscription request {room_id}");
+                    continue;
+                };
+                let _subscribe_task = Handle::current().spawn(async move {
+                    match policy_lists::subscribe(client, &room, list.clone()).await {
+                        Ok(list_room_id) => enqueue_popup_notification(tr_args(
+                            "policy.subscribed",
+                            &[("list", list_room_id.as_str())],
+                        )),
+                        Err(e) => {
+                            error!("Failed to subscribe room {room_id} to policy list {list}: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "policy.subscribe_failed",
+                                &[("list", list.as_str()), ("error", &e.to_string())],
+                            ));
+                        }
+                    }
+                });
+            }
+            MatrixRequest::UnsubscribePolicyList { room_id, list } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let _unsubscribe_task = Handle::current().spawn(async move {
+                    if let Err(e) = policy_lists::unsubscribe(client, room_id.clone(), list.clone()).await {
+                        error!("Failed to unsubscribe room {room_id} from policy list {list}: {e:?}");
+                        enqueue_popup_notification(tr_args(
+                            "policy.unsubscribe_failed",
+                            &[("list", list.as_str()), ("error", &e.to_string())],
+                        ));
+                    }
+                });
+            }
+            MatrixRequest::SetHistoryVisibility { room_id, visibility } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for set history visibility request {room_id}");
+                    continue;
+                };
+                let _set_history_visibility_task = Handle::current().spawn(async move {
+                    match room.send_state_event(RoomHistoryVisibilityEventContent::new(visibility.clone())).await {
+                        Ok(_) => {
+                            info!("Set history visibility of room {room_id} to {}.", visibility.as_str());
+                            enqueue_popup_notification(tr("room_access.history_updated").to_string());
+                        }
+                        Err(e) => {
+                            error!("Failed to set history visibility of room {room_id} to {}: {e:?}", visibility.as_str());
+                            enqueue_popup_notification(tr_args("room_access.update_failed", &[("error", &e.to_string())]));
+                        }
+                    }
+                });
+            }
+            MatrixRequest::SetGuestAccess { room_id, guest_access } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for set guest access request {room_id}");
+                    continue;
+                };
+                let _set_guest_access_task = Handle::current().spawn(async move {
+                    match room.send_state_event(RoomGuestAccessEventContent::new(guest_access.clone())).await {
+                        Ok(_) => {
+                            info!("Set guest access of room {room_id} to {}.", guest_access.as_str());
+                            enqueue_popup_notification(tr("room_access.guests_updated").to_string());
+                        }
+                        Err(e) => {
+                            error!("Failed to set guest access of room {room_id} to {}: {e:?}", guest_access.as_str());
+                            enqueue_popup_notification(tr_args("room_access.update_failed", &[("error", &e.to_string())]));
+                        }
+                    }
+                });
+            }
+            MatrixRequest::UpgradeRoom { room_id, new_version } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for upgrade request {room_id}");
+                    continue;
+                };
+                let _upgrade_task = Handle::current().spawn(async move {
+                    match upgrade_room_version(client, &room, new_version).await {
+                        Ok(new_room_id) => {
+                            info!("Upgraded room {room_id} to its successor {new_room_id}.");
+                            Cx::post_action(RoomUpgradeAction::Finished { room_id, new_room_id });
+                        }
+                        Err(e) => {
+                            error!("Failed to upgrade room {room_id}: {e:?}");
+                            Cx::post_action(RoomUpgradeAction::Failed { room_id, error: e.to_string() });
+                        }
+                    }
+                });
+            }
+            MatrixRequest::ExportRoomHistory { room_id, format, cancel } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for export request {room_id}");
+                    continue;
+                };
+                let _export_task = Handle::current().spawn(async move {
+                    match room_export::export_room_history(room, format, cancel).await {
+                        Ok(Some(path)) => Cx::post_action(RoomExportAction::Finished { room_id, path }),
+                        Ok(None) => { }
+                        Err(e) => {
+                            error!("Failed to export room {room_id}: {e:?}");
+                            Cx::post_action(RoomExportAction::Failed { room_id, error: e.to_string() });
+                        }
+                    }
+                });
+            }
+            MatrixRequest::ExportRoomMembers { room_id, cancel } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for member export request {room_id}");
+                    continue;
+                };
+                let _export_task = Handle::current().spawn(async move {
+                    match room_export::export_member_list(room, cancel).await {
+                        Ok(Some(path)) => Cx::post_action(RoomExportAction::Finished { room_id, path }),
+                        Ok(None) => { }
+                        Err(e) => {
+                            error!("Failed to export the members of room {room_id}: {e:?}");
+                            Cx::post_action(RoomExportAction::Failed { room_id, error: e.to_string() });
+                        }
+                    }
                 });
             }
             MatrixRequest::FetchAvatar { mxc_uri, on_fetched } => {
                 let Some(client) = CLIENT.get() else { continue };
                 let _fetch_task = Handle::current().spawn(async move {
-                    // log!("Sending fetch avatar request for {mxc_uri:?}...");
+                    // info!("Sending fetch avatar request for {mxc_uri:?}...");
                     let media_request = MediaRequest {
                         source: MediaSource::Plain(mxc_uri.clone()),
                         format: AVATAR_THUMBNAIL_FORMAT.into(),
                     };
                     let res = client.media().get_media_content(&media_request, true).await;
-                    // log!("Fetched avatar for {mxc_uri:?}, succeeded? {}", res.is_ok());
+                    // info!("Fetched avatar for {mxc_uri:?}, succeeded? {}", res.is_ok());
                     on_fetched(AvatarUpdate { mxc_uri, avatar_data: res.map(|v| v.into()) });
                 });
             }
@@ -783,7 +1289,7 @@ async fn async_worker(
                 let media = client.media();
 
                 let _fetch_task = Handle::current().spawn(async move {
-                    // log!("Sending fetch media request for {media_request:?}...");
+                    // info!("Sending fetch media request for {media_request:?}...");
                     let res = media.get_media_content(&media_request, true).await;
                     on_fetched(&destination, media_request, res, update_sender);
                 });
@@ -793,7 +1299,7 @@ async fn async_worker(
                 let timeline = {
                     let all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get(&room_id) else {
-                        log!("BUG: room info not found for send message request {room_id}");
+                        info!("BUG: room info not found for send message request {room_id}");
                         continue;
                     };
                     room_info.timeline.clone()
@@ -801,10 +1307,10 @@ async fn async_worker(
 
                 // Spawn a new async task that will send the actual message.
                 let _send_message_task = Handle::current().spawn(async move {
-                    log!("Sending message to room {room_id}: {message:?}...");
+                    info!("Sending message to room {room_id}: {message:?}...");
                     if let Some(replied_to_info) = replied_to {
                         match timeline.send_reply(message.into(), replied_to_info, ForwardThread::Yes).await {
-                            Ok(_send_handle) => log!("Sent reply message to room {room_id}."),
+                            Ok(_send_handle) => info!("Sent reply message to room {room_id}."),
                             Err(_e) => {
                                 error!("Failed to send reply message to room {room_id}: {_e:?}");
                                 enqueue_popup_notification(format!("Failed to send reply: {_e}"));
@@ -812,7 +1318,7 @@ async fn async_worker(
                         }
                     } else {
                         match timeline.send(message.into()).await {
-                            Ok(_send_handle) => log!("Sent message to room {room_id}."),
+                            Ok(_send_handle) => info!("Sent message to room {room_id}."),
                             Err(_e) => {
                                 error!("Failed to send message to room {room_id}: {_e:?}");
                                 enqueue_popup_notification(format!("Failed to send message: {_e}"));
@@ -823,18 +1329,19 @@ async fn async_worker(
                 });
             }
 
-            MatrixRequest::ReadReceipt { room_id, event_id } => {
+            MatrixRequest::ReadReceipt { room_id, event_id, private } => {
                 let timeline = {
                     let all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get(&room_id) else {
-                        log!("BUG: room info not found when sending read receipt, room {room_id}, {event_id}");
+                        info!("BUG: room info not found when sending read receipt, room {room_id}, {event_id}");
                         continue;
                     };
                     room_info.timeline.clone()
                 };
                 let _send_rr_task = Handle::current().spawn(async move {
-                    match timeline.send_single_receipt(ReceiptType::Read, ReceiptThread::Unthreaded, event_id.clone()).await {
-                        Ok(sent) => log!("{} read receipt to room {room_id} for event {event_id}", if sent { "Sent" } else { "Already sent" }),
+                    let receipt_type = if private { ReceiptType::ReadPrivate } else { ReceiptType::Read };
+                    match timeline.send_single_receipt(receipt_type, ReceiptThread::Unthreaded, event_id.clone()).await {
+                        Ok(sent) => info!("{} read receipt to room {room_id} for event {event_id}", if sent { "Sent" } else { "Already sent" }),
                         Err(_e) => error!("Failed to send read receipt to room {room_id} for event {event_id}; error: {_e:?}"),
                     }
                     // Also update the number of unread messages in the room.
@@ -850,14 +1357,14 @@ async fn async_worker(
                 let timeline = {
                     let all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get(&room_id) else {
-                        log!("BUG: room info not found when sending fully read receipt, room {room_id}, {event_id}");
+                        info!("BUG: room info not found when sending fully read receipt, room {room_id}, {event_id}");
                         continue;
                     };
                     room_info.timeline.clone()
                 };
                 let _send_frr_task = Handle::current().spawn(async move {
                     match timeline.send_single_receipt(ReceiptType::FullyRead, ReceiptThread::Unthreaded, event_id.clone()).await {
-                        Ok(sent) => log!("{} fully read receipt to room {room_id} for event {event_id}",
+                        Ok(sent) => info!("{} fully read receipt to room {room_id} for event {event_id}",
                             if sent { "Sent" } else { "Already sent" }
                         ),
                         Err(_e) => error!("Failed to send fully read receipt to room {room_id} for event {event_id}; error: {_e:?}"),
@@ -869,13 +1376,191 @@ async fn async_worker(
                         unread_mentions: timeline.room().num_unread_mentions()
                     });
                 });
-            },
+            },
+
+            MatrixRequest::MoveFullyReadMarker { room_id, event_id } => {
+                let timeline = {
+                    let all_room_info = ALL_ROOM_INFO.lock().unwrap();
+                    let Some(room_info) = all_room_info.get(&room_id) else {
+                        info!("BUG: room info not found when moving fully read marker, room {room_id}, {event_id}");
+                        continue;
+                    };
+                    room_info.timeline.clone()
+                };
+                let _move_marker_task = Handle::current().spawn(async move {
+                    if is_before_fully_read_marker(&timeline, &event_id).await {
+                        info!("Not moving fully read marker in room {room_id} backwards to event {event_id}");
+                        enqueue_popup_notification(tr("message.read_marker_only_forwards").to_string());
+                        return;
+                    }
+                    // Send this receipt via the room directly, because the timeline doesn't send
+                    // a fully-read receipt for an event that it already considers to be read.
+                    match timeline.room().send_single_receipt(ReceiptType::FullyRead, ReceiptThread::Unthreaded, event_id.clone()).await {
+                        Ok(()) => info!("Moved fully read marker in room {room_id} to event {event_id}"),
+                        Err(e) => {
+                            error!("Failed to move fully read marker in room {room_id} to event {event_id}; error: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "message.read_marker_move_failed",
+                                &[("error", &e.to_string())],
+                            ));
+                        }
+                    }
+                });
+            }
+
+            MatrixRequest::MarkRoomAsRead { room_id } => {
+                let timeline = {
+                    let all_room_info = ALL_ROOM_INFO.lock().unwrap();
+                    let Some(room_info) = all_room_info.get(&room_id) else {
+                        info!("BUG: room info not found when marking room {room_id} as read");
+                        continue;
+                    };
+                    room_info.timeline.clone()
+                };
+                let receipt_type = if app_settings().send_read_receipts_in(&room_id) {
+                    ReceiptType::Read
+                } else {
+                    ReceiptType::ReadPrivate
+                };
+                let _mark_read_task = Handle::current().spawn(async move {
+                    // Explicitly marking a room as read also clears its "New Messages" read marker.
+                    if let Err(e) = timeline.mark_as_read(ReceiptType::FullyRead).await {
+                        error!("Failed to move fully read marker to the latest event in room {room_id}: {e:?}");
+                    }
+                    match timeline.mark_as_read(receipt_type).await {
+                        Ok(sent) => info!("{} read receipt to mark room {room_id} as read",
+                            if sent { "Sent" } else { "Already sent" }
+                        ),
+                        Err(e) => {
+                            error!("Failed to mark room {room_id} as read: {e:?}");
+                            enqueue_popup_notification(tr_args("room_menu.mark_read_failed", &[("error", &e.to_string())]));
+                        }
+                    }
+                    enqueue_rooms_list_update(RoomsListUpdate::UpdateNumUnreadMessages {
+                        room_id: room_id.clone(),
+                        count: UnreadMessageCount::Known(timeline.room().num_unread_messages()),
+                        unread_mentions: timeline.room().num_unread_mentions()
+                    });
+                });
+            }
+
+            MatrixRequest::ToggleRoomMute { room_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let client = client.clone();
+                let _mute_task = Handle::current().spawn(async move {
+                    let settings = get_notification_settings(&client).await;
+                    let was_muted = matches!(
+                        settings.get_user_defined_room_notification_mode(&room_id).await,
+                        Some(RoomNotificationMode::Mute),
+                    );
+                    let result = if was_muted {
+                        settings.delete_user_defined_room_rules(&room_id).await
+                    } else {
+                        settings.set_room_notification_mode(&room_id, RoomNotificationMode::Mute).await
+                    };
+                    match result {
+                        Ok(()) => {
+                            info!("{} room {room_id}.", if was_muted { "Unmuted" } else { "Muted" });
+                            enqueue_rooms_list_update(RoomsListUpdate::UpdateIsMuted {
+                                room_id: room_id.clone(),
+                                is_muted: !was_muted,
+                            });
+                            enqueue_popup_notification(
+                                tr(if was_muted { "room_menu.unmuted" } else { "room_menu.muted" }).to_string()
+                            );
+                        }
+                        Err(e) => {
+                            error!("Failed to change the notification mode of room {room_id}: {e:?}");
+                            enqueue_popup_notification(tr_args("room_menu.mute_failed", &[("error", &e.to_string())]));
+                        }
+                    }
+                });
+            }
+
+            MatrixRequest::SetRoomFavourite { room_id, is_favourite } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for set favourite request {room_id}");
+                    continue;
+                };
+                let _favourite_task = Handle::current().spawn(async move {
+                    // The rooms list is updated via the room's tags once the server echoes them back.
+                    if let Err(e) = room.set_is_favourite(is_favourite, None).await {
+                        error!("Failed to set favourite={is_favourite} for room {room_id}: {e:?}");
+                        enqueue_popup_notification(tr_args("room_menu.favorite_failed", &[("error", &e.to_string())]));
+                    }
+                });
+            }
+
+            MatrixRequest::LeaveRoom { room_id, forget, transfer_admin_to } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for leave room request {room_id}");
+                    continue;
+                };
+                let own_user_id = client.user_id().map(ToOwned::to_owned);
+                let _leave_task = Handle::current().spawn(async move {
+                    let room_name = room.cached_display_name()
+                        .map(|name| name.to_string())
+                        .unwrap_or_else(|| room_id.to_string());
+                    if let (Some(new_admin), Some(own_user_id)) = (transfer_admin_to, own_user_id) {
+                        if let Err(e) = transfer_power_level(&room, &own_user_id, &new_admin).await {
+                            error!("Failed to transfer power level to {new_admin} in room {room_id}: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "leave_room.transfer_failed",
+                                &[("user_id", new_admin.as_str()), ("error", &e.to_string())],
+                            ));
+                            return;
+                        }
+                    }
+                    match room.leave().await {
+                        Ok(()) => {
+                            info!("Left room {room_id}.");
+                            enqueue_popup_notification(tr_args("room_menu.left", &[("room", &room_name)]));
+                            if forget {
+                                if let Err(e) = room.forget().await {
+                                    error!("Failed to forget room {room_id}: {e:?}");
+                                    enqueue_popup_notification(tr_args(
+                                        "leave_room.forget_failed",
+                                        &[("room", &room_name), ("error", &e.to_string())],
+                                    ));
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            error!("Failed to leave room {room_id}: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "room_menu.leave_failed",
+                                &[("room", &room_name), ("error", &e.to_string())],
+                            ));
+                        }
+                    }
+                });
+            }
+
+            MatrixRequest::CheckLastAdmin { room_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for check last admin request {room_id}");
+                    continue;
+                };
+                let _check_task = Handle::current().spawn(async move {
+                    let is_last_admin = match is_last_admin(&room).await {
+                        Ok(is_last_admin) => is_last_admin,
+                        Err(e) => {
+                            warn!("Failed to check whether we're the last admin of room {room_id}: {e:?}");
+                            false
+                        }
+                    };
+                    Cx::post_action(LeaveRoomModalAction::LastAdminChecked { room_id, is_last_admin });
+                });
+            }
 
             MatrixRequest::GetRoomPowerLevels { room_id } => {
                 let (timeline, sender) = {
                     let all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get(&room_id) else {
-                        log!("BUG: room info not found for fetch members request {room_id}");
+                        info!("BUG: room info not found for fetch members request {room_id}");
                         continue;
                     };
 
@@ -887,7 +1572,7 @@ async fn async_worker(
                 let _power_levels_task = Handle::current().spawn(async move {
                     match timeline.room().power_levels().await {
                         Ok(power_levels) => {
-                            log!("Successfully fetched power levels for room {room_id}.");
+                            info!("Successfully fetched power levels for room {room_id}.");
                             if let Err(e) = sender.send(TimelineUpdate::UserPowerLevels(
                                 UserPowerLevels::from(&power_levels, &user_id),
                             )) {
@@ -901,32 +1586,224 @@ async fn async_worker(
                     }
                 });
             },
+            MatrixRequest::GetRoomHoverPreview { room_id } => {
+                let timeline = {
+                    let all_room_info = ALL_ROOM_INFO.lock().unwrap();
+                    let Some(room_info) = all_room_info.get(&room_id) else {
+                        info!("BUG: room info not found for get room hover preview request {room_id}");
+                        continue;
+                    };
+                    room_info.timeline.clone()
+                };
+
+                let _hover_preview_task = Handle::current().spawn(async move {
+                    let mut recent_messages = timeline.items().await
+                        .iter()
+                        .rev()
+                        .filter_map(|item| item.as_event())
+                        .filter(|ev| matches!(ev.content(), TimelineItemContent::Message(_) | TimelineItemContent::Sticker(_)))
+                        .take(NUM_HOVER_PREVIEW_MESSAGES)
+                        .map(|ev| get_latest_event_details(ev, Some(&room_id)).1)
+                        .collect::<Vec<_>>();
+                    recent_messages.reverse();
+                    let room = timeline.room();
+                    Cx::post_action(RoomHoverPreviewAction::Loaded(RoomHoverPreviewInfo {
+                        room_id,
+                        topic: room.topic(),
+                        num_joined_members: room.joined_members_count(),
+                        recent_messages,
+                    }));
+                });
+            }
+
+            MatrixRequest::GetRoomBridges { room_id } => {
+                let (timeline, sender) = {
+                    let all_room_info = ALL_ROOM_INFO.lock().unwrap();
+                    let Some(room_info) = all_room_info.get(&room_id) else {
+                        info!("BUG: room info not found for get room bridges request {room_id}");
+                        continue;
+                    };
+                    (room_info.timeline.clone(), room_info.timeline_update_sender.clone())
+                };
+
+                let _bridges_task = Handle::current().spawn(async move {
+                    let mut room_bridges: Vec<RoomBridge> = Vec::new();
+                    for event_type in bridges::BRIDGE_STATE_EVENT_TYPES {
+                        let events = match timeline.room().get_state_events(StateEventType::from(event_type)).await {
+                            Ok(events) => events,
+                            Err(e) => {
+                                error!("Failed to get {event_type} state events for room {room_id}: {e:?}");
+                                continue;
+                            }
+                        };
+                        for raw_event in events {
+                            let content = match &raw_event {
+                                RawAnySyncOrStrippedState::Sync(ev) => ev.get_field::<serde_json::Value>("content"),
+                                RawAnySyncOrStrippedState::Stripped(ev) => ev.get_field::<serde_json::Value>("content"),
+                            };
+                            let bridge = content.ok().flatten()
+                                .and_then(|content| RoomBridge::from_state_event_content(&content));
+                            // The same bridge is often described by both event types.
+                            if let Some(bridge) = bridge.filter(|b| !room_bridges.contains(b)) {
+                                room_bridges.push(bridge);
+                            }
+                        }
+                    }
+                    if !room_bridges.is_empty() {
+                        info!("Room {room_id} is bridged to: {:?}", room_bridges.iter().map(|b| &b.protocol_name).collect::<Vec<_>>());
+                    }
+                    bridges::set_room_bridges(room_id, room_bridges);
+                    if let Err(e) = sender.send(TimelineUpdate::RoomBridgesFetched) {
+                        error!("Failed to send the room bridges update: {e}");
+                    }
+                    SignalToUI::set_ui_signal();
+                });
+            }
+            MatrixRequest::GetRoomWallpaper { room_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for get room wallpaper request {room_id}");
+                    continue;
+                };
+                let _wallpaper_task = Handle::current().spawn(async move {
+                    let wallpaper = match room.account_data(RoomAccountDataEventType::from(ROOM_WALLPAPER_EVENT_TYPE)).await {
+                        Ok(raw_event) => raw_event
+                            .and_then(|ev| ev.get_field::<RoomWallpaper>("content").ok().flatten()),
+                        Err(e) => {
+                            error!("Failed to get the wallpaper of room {room_id}: {e:?}");
+                            return;
+                        }
+                    };
+                    room_wallpaper::set_room_wallpaper(room_id.clone(), wallpaper);
+                    send_room_wallpaper_changed(&room_id);
+                });
+            }
+
+            MatrixRequest::SetRoomWallpaper { room_id, wallpaper } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for set room wallpaper request {room_id}");
+                    continue;
+                };
+                let _wallpaper_task = Handle::current().spawn(async move {
+                    // A removed wallpaper is stored as empty content, as account data cannot be deleted.
+                    let content = match &wallpaper {
+                        Some(wallpaper) => serde_json::to_value(wallpaper),
+                        None => Ok(serde_json::json!({})),
+                    }.and_then(|content| Raw::new(&content));
+                    let result = match content {
+                        Ok(content) => room.set_account_data_raw(
+                            RoomAccountDataEventType::from(ROOM_WALLPAPER_EVENT_TYPE),
+                            content.cast(),
+                        ).await.map_err(|e| e.to_string()),
+                        Err(e) => Err(e.to_string()),
+                    };
+                    match result {
+                        Ok(_) => {
+                            room_wallpaper::set_room_wallpaper(room_id.clone(), wallpaper);
+                            send_room_wallpaper_changed(&room_id);
+                        }
+                        Err(e) => {
+                            error!("Failed to save the wallpaper of room {room_id}: {e}");
+                            enqueue_popup_notification(tr_args("room_wallpaper.save_failed", &[("error", &e)]));
+                        }
+                    }
+                });
+            }
+
             MatrixRequest::ToggleReaction { room_id, timeline_event_id, reaction } => {
                 let timeline = {
                     let all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get(&room_id) else {
-                        log!("BUG: room info not found for send toggle reaction {room_id}");
+                        info!("BUG: room info not found for send toggle reaction {room_id}");
                         continue;
                     };
                     room_info.timeline.clone()
                 };
 
                 let _toggle_reaction_task = Handle::current().spawn(async move {
-                    log!("Toggle Reaction to room {room_id}: ...");
+                    info!("Toggle Reaction to room {room_id}: ...");
                     match timeline.toggle_reaction(&timeline_event_id, &reaction).await {
                         Ok(_send_handle) => {
                             SignalToUI::set_ui_signal();
-                            log!("Sent toggle reaction to room {room_id} {reaction}.")
+                            info!("Sent toggle reaction to room {room_id} {reaction}.")
                         },
                         Err(_e) => error!("Failed to send toggle reaction to room {room_id} {reaction}; error: {_e:?}"),
                     }
                 });
             },
+            MatrixRequest::KickUser { room_id, user_id, reason } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for kick user request {room_id}");
+                    continue;
+                };
+                let _kick_task = Handle::current().spawn(async move {
+                    match room.kick_user(&user_id, reason.as_deref()).await {
+                        Ok(()) => {
+                            info!("Kicked user {user_id} from room {room_id}.");
+                            enqueue_popup_notification(tr_args("moderation.kicked", &[("user_id", user_id.as_str())]));
+                        }
+                        Err(e) => {
+                            error!("Failed to kick user {user_id} from room {room_id}: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "moderation.kick_failed",
+                                &[("user_id", user_id.as_str()), ("error", &e.to_string())],
+                            ));
+                        }
+                    }
+                });
+            }
+            MatrixRequest::BanUser { room_id, user_id, reason } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for ban user request {room_id}");
+                    continue;
+                };
+                let _ban_task = Handle::current().spawn(async move {
+                    match room.ban_user(&user_id, reason.as_deref()).await {
+                        Ok(()) => {
+                            info!("Banned user {user_id} from room {room_id}.");
+                            enqueue_popup_notification(tr_args("moderation.banned", &[("user_id", user_id.as_str())]));
+                        }
+                        Err(e) => {
+                            error!("Failed to ban user {user_id} from room {room_id}: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "moderation.ban_failed",
+                                &[("user_id", user_id.as_str()), ("error", &e.to_string())],
+                            ));
+                        }
+                    }
+                });
+            }
+            MatrixRequest::RedactUserMessages { room_id, user_id, since, reason } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let Some(room) = client.get_room(&room_id) else {
+                    error!("BUG: room not found for redact user messages request {room_id}");
+                    continue;
+                };
+                let _redact_task = Handle::current().spawn(async move {
+                    if let Err(e) = redact_user_messages(room, user_id.clone(), since, reason).await {
+                        error!("Failed to remove recent messages from {user_id} in room {room_id}: {e:?}");
+                        enqueue_popup_notification(tr_args(
+                            "profile.remove_messages_failed",
+                            &[("error", &e.to_string())],
+                        ));
+                        Cx::post_action(RedactUserMessagesAction::Finished {
+                            room_id,
+                            user_id,
+                            redacted: 0,
+                            failed: 0,
+                            reached_scan_limit: false,
+                        });
+                    }
+                });
+            }
             MatrixRequest::RedactMessage { room_id, timeline_event_id, reason } => {
                 let timeline = {
                     let all_room_info = ALL_ROOM_INFO.lock().unwrap();
                     let Some(room_info) = all_room_info.get(&room_id) else {
-                        log!("BUG: room info not found for redact message {room_id}");
+                        info!("BUG: room info not found for redact message {room_id}");
                         continue;
                     };
                     room_info.timeline.clone()
@@ -934,7 +1811,7 @@ async fn async_worker(
 
                 let _redact_task = Handle::current().spawn(async move {
                     match timeline.redact(&timeline_event_id, reason.as_deref()).await {
-                        Ok(()) => log!("Successfully redacted message in room {room_id}."),
+                        Ok(()) => info!("Successfully redacted message in room {room_id}."),
                         Err(e) => {
                             error!("Failed to redact message in {room_id}; error: {e:?}");
                             enqueue_popup_notification(format!("Failed to redact message. Error: {e}"));
@@ -942,6 +1819,115 @@ async fn async_worker(
                     }
                 });
             },
+
+            MatrixRequest::RetrySendMessage { room_id, timeline_event_id } => {
+                let timeline = {
+                    let all_room_info = ALL_ROOM_INFO.lock().unwrap();
+                    let Some(room_info) = all_room_info.get(&room_id) else {
+                        info!("BUG: room info not found for retry send message {room_id}");
+                        continue;
+                    };
+                    room_info.timeline.clone()
+                };
+
+                let _retry_task = Handle::current().spawn(async move {
+                    // The send handle of a local echo is used to unwedge its send queue request,
+                    // which causes the SDK to retry sending it.
+                    let send_handle = timeline.items().await
+                        .iter()
+                        .filter_map(|item| item.as_event())
+                        .find(|ev| ev.identifier() == timeline_event_id)
+                        .and_then(|ev| ev.local_echo_send_handle());
+                    let Some(send_handle) = send_handle else {
+                        error!("Couldn't find unsent message {timeline_event_id:?} to retry in room {room_id}.");
+                        enqueue_popup_notification(tr("message.retry_send_not_found").to_string());
+                        return;
+                    };
+                    match send_handle.unwedge().await {
+                        Ok(()) => info!("Retrying to send message in room {room_id}."),
+                        Err(e) => {
+                            error!("Failed to retry sending message in {room_id}; error: {e:?}");
+                            enqueue_popup_notification(tr_args(
+                                "message.retry_send_failed",
+                                &[("error", &e.to_string())],
+                            ));
+                        }
+                    }
+                });
+            },
+
+            MatrixRequest::GetContacts => {
+                let Some(client) = CLIENT.get() else { continue };
+                let _get_contacts_task = Handle::current().spawn(async move {
+                    let mut contacts = Vec::new();
+                    for room in client.joined_rooms() {
+                        let Some(user_id) = direct_user_id(&room) else { continue };
+                        let display_name = match room.get_member_no_sync(&user_id).await {
+                            Ok(member) => member.and_then(|m| m.display_name().map(ToString::to_string)),
+                            Err(e) => {
+                                warn!("Failed to get member {user_id} of DM room {}: {e:?}", room.room_id());
+                                None
+                            }
+                        };
+                        contacts.push(Contact {
+                            user_id,
+                            display_name,
+                            room_id: room.room_id().to_owned(),
+                            room_name: room.cached_display_name().map(|dn| dn.to_string()),
+                        });
+                    }
+                    contacts.sort_by_cached_key(|c| c.displayable_name().to_lowercase());
+                    info!("Found {} contacts", contacts.len());
+                    Cx::post_action(ContactsUpdate::Loaded(contacts));
+                });
+            }
+
+            MatrixRequest::OpenOrCreateDirectMessage { user_id } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let _open_dm_task = Handle::current().spawn(async move {
+                    let room = match client.get_dm_room(&user_id) {
+                        Some(room) => Ok(room),
+                        None => {
+                            info!("Creating a new DM room with {user_id}...");
+                            client.create_dm(&user_id).await
+                        }
+                    };
+                    match room {
+                        Ok(room) => Cx::post_action(ContactsUpdate::DirectMessageReady {
+                            room_id: room.room_id().to_owned(),
+                            room_name: room.cached_display_name().map(|dn| dn.to_string()),
+                        }),
+                        Err(e) => {
+                            error!("Failed to open or create a DM room with {user_id}: {e:?}");
+                            Cx::post_action(ContactsUpdate::DirectMessageFailed {
+                                user_id,
+                                error: e.to_string(),
+                            });
+                        }
+                    }
+                });
+            }
+
+            MatrixRequest::LookupThreePid { medium, address } => {
+                let Some(client) = CLIENT.get() else { continue };
+                let settings = app_settings().identity_server.clone();
+                if !settings.consent_given {
+                    error!("BUG: attempted to look up a 3PID without the user's consent.");
+                    continue;
+                }
+                let _lookup_3pid_task = Handle::current().spawn(async move {
+                    match identity_server::lookup_3pid(client, &settings, medium, &address).await {
+                        Ok(user_id) => Cx::post_action(ContactsUpdate::ThreePidLookupFinished { address, user_id }),
+                        Err(IdentityServerError::TermsNotAccepted(policies)) => {
+                            Cx::post_action(ContactsUpdate::IdentityServerTermsRequired { medium, address, policies });
+                        }
+                        Err(e) => {
+                            warn!("Failed to look up a 3PID via identity server {}: {e}", settings.url);
+                            Cx::post_action(ContactsUpdate::ThreePidLookupFailed { address, error: e.to_string() });
+                        }
+                    }
+                });
+            }
         }
     }
 
@@ -1078,7 +2064,7 @@ struct RoomInfo {
 }
 impl Drop for RoomInfo {
     fn drop(&mut self) {
-        log!("Dropping RoomInfo for room {}", self.room_id);
+        info!("Dropping RoomInfo for room {}", self.room_id);
         self.timeline_subscriber_handler_task.abort();
         drop(self.typing_notice_subscriber.take());
         if let Some(replaces_tombstoned_room) = self.replaces_tombstoned_room.take() {
@@ -1113,6 +2099,16 @@ pub fn current_user_id() -> Option<OwnedUserId> {
     )
 }
 
+/// The client's notification settings, which keep themselves up to date with the push rules.
+///
+/// This is only created once, because creating it loads all of the account's push rules.
+static NOTIFICATION_SETTINGS: OnceCell<NotificationSettings> = OnceCell::const_new();
+
+/// Returns the given client's notification settings, creating them upon the first call.
+async fn get_notification_settings(client: &Client) -> &'static NotificationSettings {
+    NOTIFICATION_SETTINGS.get_or_init(|| client.notification_settings()).await
+}
+
 /// The singleton sync service.
 static SYNC_SERVICE: OnceLock<SyncService> = OnceLock::new();
 
@@ -1136,6 +2132,11 @@ pub fn is_user_ignored(user_id: &UserId) -> bool {
     IGNORED_USERS.lock().unwrap().contains(user_id)
 }
 
+/// Returns whether the given room has been loaded, i.e., whether its timeline can be shown.
+pub fn is_room_loaded(room_id: &RoomId) -> bool {
+    ALL_ROOM_INFO.lock().unwrap().contains_key(room_id)
+}
+
 
 /// Returns three channel endpoints related to the timeline for the given room.
 ///
@@ -1184,14 +2185,12 @@ fn username_to_full_user_id(
 async fn async_main_loop(
     mut login_receiver: Receiver<LoginRequest>,
 ) -> Result<()> {
-    tracing_subscriber::fmt::init();
-
     let most_recent_user_id = persistent_state::most_recent_user_id();
-    log!("Most recent user ID: {most_recent_user_id:?}");
-    let cli_parse_result = Cli::try_parse();
+    info!("Most recent user ID: {most_recent_user_id:?}");
+    let cli_parse_result = parse_cli(std::env::args());
     let cli_has_valid_username_password = cli_parse_result.as_ref()
         .is_ok_and(|cli| !cli.user_id.is_empty() && !cli.password.is_empty());
-    log!("CLI parsing succeeded? {}. CLI has valid UN+PW? {}",
+    info!("CLI parsing succeeded? {}. CLI has valid UN+PW? {}",
         cli_parse_result.as_ref().is_ok(),
         cli_has_valid_username_password,
     );
@@ -1199,7 +2198,7 @@ async fn async_main_loop(
         most_recent_user_id.is_none()
             || std::env::args().any(|arg| arg == "--login-screen" || arg == "--force-login")
     );
-    log!("Waiting for login? {}", wait_for_login);
+    info!("Waiting for login? {}", wait_for_login);
 
     let new_login_opt = if !wait_for_login {
         let specified_username = cli_parse_result.as_ref().ok().and_then(|cli|
@@ -1208,18 +2207,18 @@ async fn async_main_loop(
                 cli.homeserver.as_deref(),
             )
         );
-        log!("Trying to restore session for user: {:?}",
+        info!("Trying to restore session for user: {:?}",
             specified_username.as_ref().or(most_recent_user_id.as_ref())
         );
         if let Ok(session) = persistent_state::restore_session(specified_username).await {
             Some(session)
         } else {
             let status_err = "Could not restore previous user session.\n\nPlease login again.";
-            log!("{status_err}");
+            info!("{status_err}");
             Cx::post_action(LoginAction::LoginFailure(status_err.to_string()));
 
-            if let Ok(cli) = &cli_parse_result {
-                log!("Attempting auto-login from CLI arguments as user '{}'...", cli.user_id);
+            if let (true, Ok(cli)) = (cli_has_valid_username_password, &cli_parse_result) {
+                info!("Attempting auto-login from CLI arguments as user '{}'...", cli.user_id);
                 Cx::post_action(LoginAction::CliAutoLogin {
                     user_id: cli.user_id.clone(),
                     homeserver: cli.homeserver.clone(),
@@ -1249,7 +2248,7 @@ async fn async_main_loop(
         Some(new_login) => new_login,
         None => {
             loop {
-                log!("Waiting for login request...");
+                info!("Waiting for login request...");
                 match login_receiver.recv().await {
                     Some(login_request) => {
                         match login(&cli, login_request).await {
@@ -1290,9 +2289,15 @@ async fn async_main_loop(
     CLIENT.set(client.clone()).expect("BUG: CLIENT already set!");
 
     add_verification_event_handlers_and_sync_client(client.clone());
+    policy_lists::add_policy_list_event_handlers(&client);
+    Handle::current().spawn(policy_lists::load_subscriptions(client.clone(), logged_in_user_id.to_owned()));
+    scheduled_messages::load(logged_in_user_id);
+    Handle::current().spawn(send_scheduled_messages(client.clone()));
 
     // Listen for updates to the ignored user list.
     handle_ignore_user_list_subscriber(client.clone());
+    handle_sync_response_subscriber(client.clone());
+    add_typing_notice_event_handler(&client);
 
     let sync_service = SyncService::builder(client.clone())
         .build()
@@ -1323,55 +2328,55 @@ async fn async_main_loop(
             match diff {
                 VectorDiff::Append { values: new_rooms } => {
                     let _num_new_rooms = new_rooms.len();
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff Append {_num_new_rooms}"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff Append {_num_new_rooms}"); }
                     for new_room in &new_rooms {
                         add_new_room(new_room, &room_list_service).await?;
                     }
                     all_known_rooms.append(new_rooms);
                 }
                 VectorDiff::Clear => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff Clear"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff Clear"); }
                     all_known_rooms.clear();
                     ALL_ROOM_INFO.lock().unwrap().clear();
                     enqueue_rooms_list_update(RoomsListUpdate::ClearRooms);
                 }
                 VectorDiff::PushFront { value: new_room } => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff PushFront"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff PushFront"); }
                     add_new_room(&new_room, &room_list_service).await?;
                     all_known_rooms.push_front(new_room);
                 }
                 VectorDiff::PushBack { value: new_room } => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff PushBack"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff PushBack"); }
                     add_new_room(&new_room, &room_list_service).await?;
                     all_known_rooms.push_back(new_room);
                 }
                 VectorDiff::PopFront => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff PopFront"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff PopFront"); }
                     if let Some(room) = all_known_rooms.pop_front() {
-                        if LOG_ROOM_LIST_DIFFS { log!("PopFront: removing {}", room.room_id()); }
+                        if LOG_ROOM_LIST_DIFFS { info!("PopFront: removing {}", room.room_id()); }
                         remove_room(&room);
                     }
                 }
                 VectorDiff::PopBack => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff PopBack"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff PopBack"); }
                     if let Some(room) = all_known_rooms.pop_back() {
-                        if LOG_ROOM_LIST_DIFFS { log!("PopBack: removing {}", room.room_id()); }
+                        if LOG_ROOM_LIST_DIFFS { info!("PopBack: removing {}", room.room_id()); }
                         remove_room(&room);
                     }
                 }
                 VectorDiff::Insert { index, value: new_room } => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff Insert at {index}"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff Insert at {index}"); }
                     add_new_room(&new_room, &room_list_service).await?;
                     all_known_rooms.insert(index, new_room);
                 }
                 VectorDiff::Set { index, value: changed_room } => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff Set at {index}"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff Set at {index}"); }
                     let old_room = all_known_rooms.get(index).expect("BUG: Set index out of bounds");
                     update_room(old_room, &changed_room, &room_list_service).await?;
                     all_known_rooms.set(index, changed_room);
                 }
                 VectorDiff::Remove { index: remove_index } => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff Remove at {remove_index}"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff Remove at {remove_index}"); }
                     if remove_index < all_known_rooms.len() {
                         let room = all_known_rooms.remove(remove_index);
                         // Try to optimize a common operation, in which a `Remove` diff
@@ -1384,7 +2389,7 @@ async fn async_main_loop(
                         if let Some(VectorDiff::Insert { index: insert_index, value: new_room }) = peekable_diffs.peek() {
                             if room.room_id() == new_room.room_id() {
                                 if LOG_ROOM_LIST_DIFFS {
-                                    log!("Optimizing Remove({remove_index}) + Insert({insert_index}) into Set (update) for room {}", room.room_id());
+                                    info!("Optimizing Remove({remove_index}) + Insert({insert_index}) into Set (update) for room {}", room.room_id());
                                 }
                                 update_room(&room, new_room, &room_list_service).await?;
                                 all_known_rooms.insert(*insert_index, new_room.clone());
@@ -1394,7 +2399,7 @@ async fn async_main_loop(
                         if next_diff_was_handled {
                             peekable_diffs.next(); // consume the next diff
                         } else {
-                            warning!("UNTESTED SCENARIO: room_list: diff Remove({remove_index}) was NOT followed by an Insert. Removed room: {}", room.room_id());
+                            warn!("UNTESTED SCENARIO: room_list: diff Remove({remove_index}) was NOT followed by an Insert. Removed room: {}", room.room_id());
                             remove_room(&room);
                         }
                     } else {
@@ -1402,7 +2407,7 @@ async fn async_main_loop(
                     }
                 }
                 VectorDiff::Truncate { length } => {
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff Truncate to {length}"); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff Truncate to {length}"); }
                     // Iterate manually so we can know which rooms are being removed.
                     while all_known_rooms.len() > length {
                         if let Some(room) = all_known_rooms.pop_back() {
@@ -1413,7 +2418,7 @@ async fn async_main_loop(
                 }
                 VectorDiff::Reset { values: new_rooms } => {
                     // We implement this by clearing all rooms and then adding back the new values.
-                    if LOG_ROOM_LIST_DIFFS { log!("room_list: diff Reset, old length {}, new length {}", all_known_rooms.len(), new_rooms.len()); }
+                    if LOG_ROOM_LIST_DIFFS { info!("room_list: diff Reset, old length {}, new length {}", all_known_rooms.len(), new_rooms.len()); }
                     // Iterate manually so we can know which rooms are being removed.
                     while let Some(room) = all_known_rooms.pop_back() {
                         remove_room(&room);
@@ -1447,21 +2452,21 @@ async fn update_room(
         if let Some(new_latest_event) = new_room.latest_event().await {
             if let Some(old_latest_event) = old_room.latest_event().await {
                 if new_latest_event.timestamp() > old_latest_event.timestamp() {
-                    log!("Updating latest event for room {}", new_room_id);
+                    info!("Updating latest event for room {}", new_room_id);
                     room_avatar_changed = update_latest_event(new_room_id.clone(), &new_latest_event, None);
                 }
             }
         }
 
         if room_avatar_changed || (old_room.avatar_url() != new_room.avatar_url()) {
-            log!("Updating avatar for room {}", new_room_id);
+            info!("Updating avatar for room {}", new_room_id);
             spawn_fetch_room_avatar(new_room.inner_room().clone());
         }
 
         if let Ok(new_room_name) = new_room.compute_display_name().await {
             let new_room_name = new_room_name.to_string();
             if old_room.cached_display_name().as_ref() != Some(&new_room_name) {
-                log!("Updating room name for room {} to {}", new_room_id, new_room_name);
+                info!("Updating room name for room {} to {}", new_room_id, new_room_name);
                 enqueue_rooms_list_update(RoomsListUpdate::UpdateRoomName {
                     room_id: new_room_id.clone(),
                     new_room_name,
@@ -1485,7 +2490,7 @@ async fn update_room(
         Ok(())
     }
     else {
-        warning!("UNTESTED SCENARIO: update_room(): removing old room {}, replacing with new room {}",
+        warn!("UNTESTED SCENARIO: update_room(): removing old room {}, replacing with new room {}",
             old_room.room_id(), new_room_id,
         );
         remove_room(old_room);
@@ -1494,6 +2499,28 @@ async fn update_room(
 }
 
 
+/// Returns `true` if the given event is at or before the room's current fully-read marker
+/// within the given timeline, such that the marker can't be moved to it.
+///
+/// If the marker's event isn't in the timeline's loaded items, it's older than all of them.
+async fn is_before_fully_read_marker(timeline: &Timeline, event_id: &OwnedEventId) -> bool {
+    let fully_read_event_id = match timeline.room().account_data_static::<FullyReadEventContent>().await {
+        Ok(Some(raw)) => match raw.deserialize() {
+            Ok(event) => event.content.event_id,
+            Err(_) => return false,
+        },
+        _ => return false,
+    };
+    let items = timeline.items().await;
+    let position_of = |id: &OwnedEventId| items.iter().position(
+        |item| item.as_event().and_then(|ev| ev.event_id()) == Some(id)
+    );
+    match (position_of(&fully_read_event_id), position_of(event_id)) {
+        (Some(marker_pos), Some(event_pos)) => event_pos <= marker_pos,
+        _ => false,
+    }
+}
+
 /// Invoked when the room list service has received an update to remove an existing room.
 fn remove_room(room: &room_list_service::Room) {
     ALL_ROOM_INFO.lock().unwrap().remove(room.room_id());
@@ -1512,9 +2539,9 @@ async fn add_new_room(room: &room_list_service::Room, room_list_service: &RoomLi
     //       the room isn't fully synced yet. But I don't know how to force it to fully sync.
     //
     // if !room.is_state_fully_synced() {
-    //     log!("Room {room_id} is not fully synced yet; waiting for sync_up...");
+    //     info!("Room {room_id} is not fully synced yet; waiting for sync_up...");
     //     room.sync_up().await;
-    //     log!("Room {room_id} is now fully synced? {}", room.is_state_fully_synced());
+    //     info!("Room {room_id} is now fully synced? {}", room.is_state_fully_synced());
     // }
 
     // This will sync the room
@@ -1522,7 +2549,7 @@ async fn add_new_room(room: &room_list_service::Room, room_list_service: &RoomLi
 
     // Do not add tombstoned rooms to the rooms list; they require special handling.
     if let Some(tombstoned_info) = room.tombstone() {
-        log!("Room {room_id} has been tombstoned: {tombstoned_info:#?}");
+        info!("Room {room_id} has been tombstoned: {tombstoned_info:#?}");
         // Since we don't know the order in which we'll learn about new rooms,
         // we need to first check to see if the replacement for this tombstoned room
         // refers to an already-known room as its replacement.
@@ -1564,8 +2591,17 @@ async fn add_new_room(room: &room_list_service::Room, room_list_service: &RoomLi
         request_receiver,
     ));
 
+    // Don't fetch the latest event sender's profile here, as doing so for every room
+    // would issue thousands of requests for accounts with many rooms.
+    // Instead, rooms are paginated once they're first drawn in the rooms list,
+    // which re-computes this preview with the sender's fetched details.
     let latest = latest_event.as_ref().map(
-        |ev| get_latest_event_details(ev, &room_id)
+        |ev| get_latest_event_details(ev, None)
+    );
+    let is_muted = matches!(
+        get_notification_settings(&room.inner_room().client()).await
+            .get_user_defined_room_notification_mode(&room_id).await,
+        Some(RoomNotificationMode::Mute),
     );
 
     rooms_list::enqueue_rooms_list_update(RoomsListUpdate::AddRoom(RoomsListEntry {
@@ -1574,22 +2610,25 @@ async fn add_new_room(room: &room_list_service::Room, room_list_service: &RoomLi
         tags: room.tags().await.ok().flatten(),
         num_unread_messages: room.num_unread_messages(),
         num_unread_mentions: room.num_unread_mentions(),
-        // start with a basic text avatar; the avatar image will be fetched asynchronously below.
+        // Start with a basic text avatar; the avatar image will be fetched
+        // asynchronously once this room is first drawn in the rooms list.
         avatar: avatar_from_room_name(room_name.as_deref().unwrap_or_default()),
         room_name,
         canonical_alias: room.canonical_alias(),
         alt_aliases: room.alt_aliases(),
         has_been_paginated: false,
+        has_requested_avatar: false,
         is_selected: false,
+        direct_user_id: direct_user_id(room.inner_room()),
+        is_muted,
+        typing_users: Vec::new(),
     }));
 
-    spawn_fetch_room_avatar(room.inner_room().clone());
-
     let tombstoned_room_replaced_by_this_room = TOMBSTONED_ROOMS.lock()
         .unwrap()
         .remove(&room_id);
 
-    log!("Adding new room {room_id} to ALL_ROOM_INFO. Replaces tombstoned room: {tombstoned_room_replaced_by_this_room:?}");
+    info!("Adding new room {room_id} to ALL_ROOM_INFO. Replaces tombstoned room: {tombstoned_room_replaced_by_this_room:?}");
     ALL_ROOM_INFO.lock().unwrap().insert(
         room_id.clone(),
         RoomInfo {
@@ -1605,6 +2644,19 @@ async fn add_new_room(room: &room_list_service::Room, room_list_service: &RoomLi
     Ok(())
 }
 
+/// Notifies the given room's timeline, if it exists, that the room's wallpaper has changed.
+fn send_room_wallpaper_changed(room_id: &RoomId) {
+    let sender = ALL_ROOM_INFO.lock().unwrap()
+        .get(room_id)
+        .map(|room_info| room_info.timeline_update_sender.clone());
+    if let Some(sender) = sender {
+        if let Err(e) = sender.send(TimelineUpdate::RoomWallpaperChanged) {
+            error!("Failed to send the room wallpaper update: {e}");
+        }
+        SignalToUI::set_ui_signal();
+    }
+}
+
 #[allow(unused)]
 async fn current_ignore_user_list(client: &Client) -> Option<BTreeSet<OwnedUserId>> {
     use matrix_sdk::ruma::events::ignored_user_list::IgnoredUserListEventContent;
@@ -1621,13 +2673,35 @@ async fn current_ignore_user_list(client: &Client) -> Option<BTreeSet<OwnedUserI
     Some(ignored_users)
 }
 
+/// Adds an event handler that keeps track of which users are typing in every room,
+/// such that the rooms list can show a typing indicator without the room being open.
+fn add_typing_notice_event_handler(client: &Client) {
+    client.add_event_handler(|event: SyncTypingEvent, room: Room| async move {
+        let own_user_id = room.own_user_id().to_owned();
+        let mut typing_users = Vec::with_capacity(event.content.user_ids.len());
+        for user_id in event.content.user_ids.into_iter().filter(|user_id| *user_id != own_user_id) {
+            let name = room.get_member_no_sync(&user_id)
+                .await
+                .ok()
+                .flatten()
+                .and_then(|m| m.display_name().map(|d| d.to_owned()))
+                .unwrap_or_else(|| user_id.to_string());
+            typing_users.push(name);
+        }
+        enqueue_rooms_list_update(RoomsListUpdate::UpdateTypingUsers {
+            room_id: room.room_id().to_owned(),
+            typing_users,
+        });
+    });
+}
+
 fn handle_ignore_user_list_subscriber(client: Client) {
     let mut subscriber = client.subscribe_to_ignore_user_list_changes();
-    log!("Initial ignored-user list is: {:?}", subscriber.get());
+    info!("Initial ignored-user list is: {:?}", subscriber.get());
     Handle::current().spawn(async move {
         let mut first_update = true;
         while let Some(ignore_list) = subscriber.next().await {
-            log!("Received an updated ignored-user list: {ignore_list:?}");
+            info!("Received an updated ignored-user list: {ignore_list:?}");
             let ignored_users_new = ignore_list
                 .into_iter()
                 .filter_map(|u| OwnedUserId::try_from(u).ok())
@@ -1658,13 +2732,26 @@ fn handle_ignore_user_list_subscriber(client: Client) {
 }
 
 
+/// Records the time at which each sync response is received, for the performance overlay.
+fn handle_sync_response_subscriber(client: Client) {
+    let mut room_updates_receiver = client.subscribe_to_all_room_updates();
+    Handle::current().spawn(async move {
+        loop {
+            match room_updates_receiver.recv().await {
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => performance_overlay::record_sync_response(),
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
 fn handle_sync_service_state_subscriber(mut subscriber: Subscriber<sync_service::State>) {
-    log!("Initial sync service state is {:?}", subscriber.get());
+    info!("Initial sync service state is {:?}", subscriber.get());
     Handle::current().spawn(async move {
         while let Some(state) = subscriber.next().await {
-            log!("Received a sync service state update: {state:?}");
+            info!("Received a sync service state update: {state:?}");
             if state == sync_service::State::Error {
-                log!("Restarting sync service due to error.");
+                info!("Restarting sync service due to error.");
                 if let Some(ss) = SYNC_SERVICE.get() {
                     ss.start().await;
                 }
@@ -1675,10 +2762,10 @@ fn handle_sync_service_state_subscriber(mut subscriber: Subscriber<sync_service:
 
 
 fn handle_room_list_service_loading_state(mut loading_state: Subscriber<RoomListLoadingState>) {
-    log!("Initial room list loading state is {:?}", loading_state.get());
+    info!("Initial room list loading state is {:?}", loading_state.get());
     Handle::current().spawn(async move {
         while let Some(state) = loading_state.next().await {
-            log!("Received a room list loading state update: {state:?}");
+            info!("Received a room list loading state update: {state:?}");
             match state {
                 RoomListLoadingState::NotLoaded => {
                     enqueue_rooms_list_update(RoomsListUpdate::NotLoaded);
@@ -1695,12 +2782,13 @@ fn handle_room_list_service_loading_state(mut loading_state: Subscriber<RoomList
 ///
 /// If the sender profile of the event is not yet available, this function will
 /// generate a preview using the sender's user ID instead of their display name,
-/// and will submit a background async request to fetch the details for this event.
+/// and, if a `room_id` is given, will submit a background async request
+/// to fetch the details for this event.
 fn get_latest_event_details(
     latest_event: &EventTimelineItem,
-    room_id: &OwnedRoomId,
+    room_id: Option<&OwnedRoomId>,
 ) -> (MilliSecondsSinceUnixEpoch, String) {
-    let sender_username = &utils::get_or_fetch_event_sender(latest_event, Some(room_id));
+    let sender_username = &utils::get_or_fetch_event_sender(latest_event, room_id);
     (
         latest_event.timestamp(),
         text_preview_of_timeline_item(latest_event.content(), sender_username)
@@ -1759,15 +2847,16 @@ async fn timeline_subscriber_handler(
 
 
     let room_id = room.room_id().to_owned();
-    log!("Starting timeline subscriber for room {room_id}...");
+    info!("Starting timeline subscriber for room {room_id}...");
     let (mut timeline_items, mut subscriber) = timeline.subscribe_batched().await;
-    log!("Received initial timeline update of {} items for room {room_id}.", timeline_items.len());
+    info!("Received initial timeline update of {} items for room {room_id}.", timeline_items.len());
 
     timeline_update_sender.send(TimelineUpdate::FirstUpdate {
         initial_items: timeline_items.clone(),
     }).unwrap_or_else(
         |_e| panic!("Error: timeline update sender couldn't send first update ({} items) to room {room_id}!", timeline_items.len())
     );
+    add_to_inbox(&room, timeline_items.iter());
 
     let mut latest_event = timeline.latest_event().await;
 
@@ -1803,7 +2892,7 @@ async fn timeline_subscriber_handler(
                         // Instead, we have no choice but to start from the end of the timeline.
                         timeline_items.len()
                     };
-                    // log!("Received new request to search for event {new_target_event_id} in room {room_id} starting from index {starting_index} (tl len {}).", timeline_items.len());
+                    // info!("Received new request to search for event {new_target_event_id} in room {room_id} starting from index {starting_index} (tl len {}).", timeline_items.len());
                     // Search backwards for the target event in the timeline, starting from the given index.
                     if let Some(target_event_tl_index) = timeline_items
                         .focus()
@@ -1816,7 +2905,7 @@ async fn timeline_subscriber_handler(
                         )
                         .map(|i| starting_index.saturating_sub(i).saturating_sub(1))
                     {
-                        // log!("Found existing target event {new_target_event_id} in room {room_id} at index {target_event_tl_index}.");
+                        // info!("Found existing target event {new_target_event_id} in room {room_id} at index {target_event_tl_index}.");
 
                         // Nice! We found the target event in the current timeline items,
                         // so there's no need to actually proceed with backwards pagination;
@@ -1835,7 +2924,7 @@ async fn timeline_subscriber_handler(
                         SignalToUI::set_ui_signal();
                     }
                     else {
-                        // log!("Target event not in timeline. Starting backwards pagination in room {room_id} to find target event {new_target_event_id} starting from index {starting_index}.");
+                        // info!("Target event not in timeline. Starting backwards pagination in room {room_id} to find target event {new_target_event_id} starting from index {starting_index}.");
 
                         // If we didn't find the target event in the current timeline items,
                         // we need to start loading previous items into the timeline.
@@ -1861,26 +2950,29 @@ async fn timeline_subscriber_handler(
             let mut clear_cache = false;
             // whether the changes include items being appended to the end of the timeline
             let mut is_append = false;
+            // the items that were added or changed, which may need to be added to the inbox
+            let mut new_or_changed_items = Vec::new();
             for diff in batch {
                 num_updates += 1;
                 match diff {
                     VectorDiff::Append { values } => {
                         let _values_len = values.len();
                         index_of_first_change = min(index_of_first_change, timeline_items.len());
+                        new_or_changed_items.extend(values.iter().cloned());
                         timeline_items.extend(values);
                         index_of_last_change = max(index_of_last_change, timeline_items.len());
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff Append {_values_len}. Changes: {index_of_first_change}..{index_of_last_change}"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff Append {_values_len}. Changes: {index_of_first_change}..{index_of_last_change}"); }
                         reobtain_latest_event = true;
                         is_append = true;
                     }
                     VectorDiff::Clear => {
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff Clear"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff Clear"); }
                         clear_cache = true;
                         timeline_items.clear();
                         reobtain_latest_event = true;
                     }
                     VectorDiff::PushFront { value } => {
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff PushFront"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff PushFront"); }
                         if let Some((index, _ev)) = found_target_event_id.as_mut() {
                             *index += 1; // account for this new `value` being prepended.
                         } else {
@@ -1888,19 +2980,21 @@ async fn timeline_subscriber_handler(
                         }
 
                         clear_cache = true;
+                        new_or_changed_items.push(value.clone());
                         timeline_items.push_front(value);
                         reobtain_latest_event |= latest_event.is_none();
                     }
                     VectorDiff::PushBack { value } => {
                         index_of_first_change = min(index_of_first_change, timeline_items.len());
+                        new_or_changed_items.push(value.clone());
                         timeline_items.push_back(value);
                         index_of_last_change = max(index_of_last_change, timeline_items.len());
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff PushBack. Changes: {index_of_first_change}..{index_of_last_change}"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff PushBack. Changes: {index_of_first_change}..{index_of_last_change}"); }
                         reobtain_latest_event = true;
                         is_append = true;
                     }
                     VectorDiff::PopFront => {
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff PopFront"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff PopFront"); }
                         clear_cache = true;
                         timeline_items.pop_front();
                         if let Some((i, _ev)) = found_target_event_id.as_mut() {
@@ -1912,7 +3006,7 @@ async fn timeline_subscriber_handler(
                         timeline_items.pop_back();
                         index_of_first_change = min(index_of_first_change, timeline_items.len());
                         index_of_last_change = usize::MAX;
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff PopBack. Changes: {index_of_first_change}..{index_of_last_change}"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff PopBack. Changes: {index_of_first_change}..{index_of_last_change}"); }
                         reobtain_latest_event = true;
                     }
                     VectorDiff::Insert { index, value } => {
@@ -1936,15 +3030,17 @@ async fn timeline_subscriber_handler(
                                 .map(|(i, ev)| (i + index, ev));
                         }
 
+                        new_or_changed_items.push(value.clone());
                         timeline_items.insert(index, value);
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff Insert at {index}. Changes: {index_of_first_change}..{index_of_last_change}"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff Insert at {index}. Changes: {index_of_first_change}..{index_of_last_change}"); }
                         reobtain_latest_event = true;
                     }
                     VectorDiff::Set { index, value } => {
                         index_of_first_change = min(index_of_first_change, index);
                         index_of_last_change  = max(index_of_last_change, index.saturating_add(1));
+                        new_or_changed_items.push(value.clone());
                         timeline_items.set(index, value);
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff Set at {index}. Changes: {index_of_first_change}..{index_of_last_change}"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff Set at {index}. Changes: {index_of_first_change}..{index_of_last_change}"); }
                         reobtain_latest_event = true;
                     }
                     VectorDiff::Remove { index } => {
@@ -1961,7 +3057,7 @@ async fn timeline_subscriber_handler(
                             }
                         }
                         timeline_items.remove(index);
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff Remove at {index}. Changes: {index_of_first_change}..{index_of_last_change}"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff Remove at {index}. Changes: {index_of_first_change}..{index_of_last_change}"); }
                         reobtain_latest_event = true;
                     }
                     VectorDiff::Truncate { length } => {
@@ -1972,12 +3068,13 @@ async fn timeline_subscriber_handler(
                             index_of_last_change = usize::MAX;
                         }
                         timeline_items.truncate(length);
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff Truncate to length {length}. Changes: {index_of_first_change}..{index_of_last_change}"); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff Truncate to length {length}. Changes: {index_of_first_change}..{index_of_last_change}"); }
                         reobtain_latest_event = true;
                     }
                     VectorDiff::Reset { values } => {
-                        if LOG_TIMELINE_DIFFS { log!("timeline_subscriber: room {room_id} diff Reset, new length {}", values.len()); }
+                        if LOG_TIMELINE_DIFFS { info!("timeline_subscriber: room {room_id} diff Reset, new length {}", values.len()); }
                         clear_cache = true; // we must assume all items have changed.
+                        new_or_changed_items.extend(values.iter().cloned());
                         timeline_items = values;
                         reobtain_latest_event = true;
                     }
@@ -1995,7 +3092,7 @@ async fn timeline_subscriber_handler(
                 let changed_indices = index_of_first_change..index_of_last_change;
 
                 if LOG_TIMELINE_DIFFS {
-                    log!("timeline_subscriber: applied {num_updates} updates for room {room_id}, timeline now has {} items. is_append? {is_append}, clear_cache? {clear_cache}. Changes: {changed_indices:?}.", timeline_items.len());
+                    info!("timeline_subscriber: applied {num_updates} updates for room {room_id}, timeline now has {} items. is_append? {is_append}, clear_cache? {clear_cache}. Changes: {changed_indices:?}.", timeline_items.len());
                 }
                 timeline_update_sender.send(TimelineUpdate::NewItems {
                     new_items: timeline_items.clone(),
@@ -2028,6 +3125,8 @@ async fn timeline_subscriber_handler(
                     latest_event = Some(new_latest);
                 }
 
+                add_to_inbox(&room, new_or_changed_items.iter());
+
                 // Send a Makepad-level signal to update this room's timeline UI view.
                 SignalToUI::set_ui_signal();
             }
@@ -2041,6 +3140,20 @@ async fn timeline_subscriber_handler(
     error!("Error: unexpectedly ended timeline subscriber for room {room_id}.");
 }
 
+/// Adds each of the given timeline items that mentions or replies to the current user to the inbox.
+fn add_to_inbox<'a>(room: &Room, items: impl Iterator<Item = &'a Arc<TimelineItem>>) {
+    let room_id = room.room_id().to_owned();
+    let mut room_name = None;
+    for event_tl_item in items.filter_map(|item| item.as_event()) {
+        let room_name = room_name
+            .get_or_insert_with(|| room.cached_display_name().map(|dn| dn.to_string()))
+            .clone();
+        if let Some(entry) = InboxEntry::from_event(&room_id, room_name, event_tl_item) {
+            enqueue_inbox_entry(entry);
+        }
+    }
+}
+
 /// Handles the given updated latest event for the given room.
 ///
 /// This currently includes checking the given event for:
@@ -2062,7 +3175,7 @@ fn update_latest_event(
 ) -> bool {
     let mut room_avatar_changed = false;
 
-    let (timestamp, latest_message_text) = get_latest_event_details(event_tl_item, &room_id);
+    let (timestamp, latest_message_text) = get_latest_event_details(event_tl_item, Some(&room_id));
     match event_tl_item.content() {
         // Check for relevant state events.
         TimelineItemContent::OtherState(other) => {
@@ -2117,6 +3230,224 @@ fn update_latest_event(
     room_avatar_changed
 }
 
+/// Progress updates of a [`MatrixRequest::RedactUserMessages`] request.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum RedactUserMessagesAction {
+    /// The given number of the user's messages have been redacted so far,
+    /// out of the `found` messages in the part of the room's history scanned so far.
+    Progress {
+        room_id: OwnedRoomId,
+        user_id: OwnedUserId,
+        redacted: usize,
+        found: usize,
+    },
+    /// All of the user's messages have been processed.
+    Finished {
+        room_id: OwnedRoomId,
+        user_id: OwnedUserId,
+        redacted: usize,
+        failed: usize,
+        /// Whether scanning stopped at [`MAX_REDACT_SCANNED_EVENTS`]
+        /// before reaching the start of the requested timeframe.
+        reached_scan_limit: bool,
+    },
+}
+
+/// The maximum number of events in a room's history that are scanned
+/// for messages to redact in a single [`MatrixRequest::RedactUserMessages`] request.
+pub const MAX_REDACT_SCANNED_EVENTS: usize = 5_000;
+
+/// Redacts all messages sent by the given user in the given room since the given time.
+///
+/// The room's history is paginated from the homeserver backwards until the start of that timeframe,
+/// so this also finds messages that haven't been loaded into the room's timeline.
+/// Each page of messages is redacted as soon as it arrives, and at most
+/// [`MAX_REDACT_SCANNED_EVENTS`] events are scanned.
+async fn redact_user_messages(
+    room: Room,
+    user_id: OwnedUserId,
+    since: MilliSecondsSinceUnixEpoch,
+    reason: Option<String>,
+) -> Result<()> {
+    let room_id = room.room_id().to_owned();
+    let Some(own_user_id) = room.client().user_id().map(ToOwned::to_owned) else {
+        bail!("Not logged in");
+    };
+    if !UserPowerLevels::from(&room.power_levels().await?, &own_user_id).can_redact_others() {
+        bail!("You don't have permission to remove other users' messages in this room");
+    }
+
+    info!("Removing recent messages from {user_id} in room {room_id}...");
+    let (mut scanned, mut found, mut redacted, mut failed) = (0, 0, 0, 0);
+    let mut reached_scan_limit = false;
+    let mut from = None;
+    loop {
+        let mut options = MessagesOptions::backward();
+        options.from = from.take();
+        let messages = room.messages(options).await?;
+        let mut reached_since = false;
+        let mut event_ids = Vec::new();
+        for timeline_event in &messages.chunk {
+            if scanned >= MAX_REDACT_SCANNED_EVENTS {
+                reached_scan_limit = true;
+                break;
+            }
+            scanned += 1;
+            let Ok(event) = timeline_event.event.deserialize() else { continue };
+            if event.origin_server_ts() < since {
+                reached_since = true;
+                break;
+            }
+            if event.sender() != &*user_id {
+                continue;
+            }
+            // Skip redactions themselves and messages that have already been redacted.
+            if let AnyTimelineEvent::MessageLike(message) = &event {
+                if message.event_type() != MessageLikeEventType::RoomRedaction && message.original_content().is_some() {
+                    event_ids.push(message.event_id().to_owned());
+                }
+            }
+        }
+
+        found += event_ids.len();
+        for event_id in event_ids {
+            match room.redact(&event_id, reason.as_deref(), None).await {
+                Ok(_) => redacted += 1,
+                Err(e) => {
+                    error!("Failed to redact event {event_id} in room {room_id}: {e:?}");
+                    failed += 1;
+                }
+            }
+            Cx::post_action(RedactUserMessagesAction::Progress {
+                room_id: room_id.clone(),
+                user_id: user_id.clone(),
+                redacted,
+                found,
+            });
+        }
+
+        if reached_since || reached_scan_limit {
+            break;
+        }
+        match messages.end {
+            Some(end) => from = Some(end),
+            None => break,
+        }
+    }
+    if reached_scan_limit {
+        warn!("Stopped scanning for messages from {user_id} in room {room_id} after {scanned} events.");
+    }
+    info!("Removed {redacted} of {found} recent messages from {user_id} in room {room_id}.");
+    Cx::post_action(RedactUserMessagesAction::Finished { room_id, user_id, redacted, failed, reached_scan_limit });
+    Ok(())
+}
+
+/// The power level at and above which a room member is considered an admin.
+const ADMIN_POWER_LEVEL: u8 = 100;
+
+/// Returns `true` if the current user is an admin of the given room
+/// and no other joined member of that room is an admin.
+async fn is_last_admin(room: &Room) -> Result<bool> {
+    let Some(own_user_id) = room.client().user_id().map(ToOwned::to_owned) else {
+        bail!("Not logged in");
+    };
+    let power_levels = room.power_levels().await?;
+    let admin_level = Int::from(ADMIN_POWER_LEVEL);
+    if power_levels.for_user(&own_user_id) < admin_level {
+        return Ok(false);
+    }
+    let members = room.members(RoomMemberships::JOIN).await?;
+    Ok(!members.iter().any(|member|
+        member.user_id() != own_user_id && power_levels.for_user(member.user_id()) >= admin_level
+    ))
+}
+
+/// Grants the given room member the same power level as the current user,
+/// e.g., such that the room still has an admin after the current user leaves it.
+async fn transfer_power_level(room: &Room, own_user_id: &UserId, new_admin: &UserId) -> Result<()> {
+    let is_joined = room.get_member_no_sync(new_admin).await?
+        .is_some_and(|member| *member.membership() == MembershipState::Join);
+    if !is_joined {
+        bail!("{new_admin} is not a member of this room");
+    }
+    let own_level = room.power_levels().await?.for_user(own_user_id);
+    room.update_power_levels(vec![(new_admin, own_level)]).await?;
+    info!("Granted power level {own_level} to {new_admin} in room {}.", room.room_id());
+    Ok(())
+}
+
+/// Periodically sends all scheduled messages that are due, for as long as the app is running.
+///
+/// The first check is delayed by one interval such that the rooms list has a chance to load.
+async fn send_scheduled_messages(client: Client) {
+    let period = std::time::Duration::from_secs(scheduled_messages::SCHEDULER_INTERVAL_SECS);
+    let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    loop {
+        interval.tick().await;
+        for message in scheduled_messages::due(MilliSecondsSinceUnixEpoch::now()) {
+            let room_id = message.room_id.clone();
+            let result = match client.get_room(&room_id) {
+                Some(room) => room.send(RoomMessageEventContent::text_markdown(&message.text)).await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                None => Err(tr("scheduled.room_not_found").to_string()),
+            };
+            // Only remove a message from the queue once it has actually been sent.
+            let update_result = match result {
+                Ok(()) => {
+                    info!("Sent scheduled message {} to room {room_id}.", message.id);
+                    scheduled_messages::mark_sent(message.id)
+                }
+                Err(error) => {
+                    error!("Failed to send scheduled message {} to room {room_id}: {error}", message.id);
+                    enqueue_popup_notification(tr_args(
+                        "scheduled.send_failed",
+                        &[("text", &message.text), ("error", &error)],
+                    ));
+                    scheduled_messages::set_failed(message.id, Some(error))
+                }
+            };
+            if let Err(e) = update_result {
+                error!("Failed to save scheduled messages: {e}");
+            }
+        }
+    }
+}
+
+/// Upgrades the given room to the given room version (or the server's default version),
+/// then posts an announcement in the old room that points members to the new room.
+///
+/// The server creates the successor room and sends the tombstone event to the old room.
+/// Returns the ID of the successor room.
+async fn upgrade_room_version(client: &Client, room: &Room, new_version: Option<RoomVersionId>) -> Result<OwnedRoomId> {
+    let room_id = room.room_id().to_owned();
+    let new_version = match new_version {
+        Some(version) => version,
+        None => client.get_capabilities().await?.room_versions.default,
+    };
+    if room.clone_info().room_version() == Some(&new_version) {
+        bail!("this room already uses room version {new_version}");
+    }
+
+    Cx::post_action(RoomUpgradeAction::Progress { room_id: room_id.clone(), step: RoomUpgradeStep::Upgrading });
+    let request = upgrade_room::v3::Request::new(room_id.clone(), new_version.clone());
+    let new_room_id = client.send(request, None).await?.replacement_room;
+
+    Cx::post_action(RoomUpgradeAction::Progress { room_id: room_id.clone(), step: RoomUpgradeStep::Announcing });
+    // This notice is visible to all room members, so it isn't translated into the admin's UI language.
+    let announcement = format!(
+        "This room has been upgraded to room version {new_version}. Please continue the conversation in the new room: {}",
+        new_room_id.matrix_to_uri(),
+    );
+    // The upgrade itself has already succeeded, so failing to announce it is not fatal.
+    if let Err(e) = room.send(RoomMessageEventContent::notice_plain(announcement)).await {
+        warn!("Failed to announce the upgrade of room {room_id} to {new_room_id}: {e:?}");
+    }
+    Ok(new_room_id)
+}
+
 /// Spawn a new async task to fetch the room's new avatar.
 fn spawn_fetch_room_avatar(room: Room) {
     let room_id = room.room_id().to_owned();
@@ -2153,6 +3484,15 @@ async fn room_avatar(room: &Room, room_name: &Option<String>) -> RoomPreviewAvat
     }
 }
 
+/// Returns the other user in the given room if it is a direct message room with exactly one other user.
+pub fn direct_user_id(room: &Room) -> Option<OwnedUserId> {
+    let mut direct_targets = room.direct_targets().into_iter();
+    match (direct_targets.next(), direct_targets.next()) {
+        (Some(user_id), None) => Some(user_id),
+        _ => None,
+    }
+}
+
 /// Returns a text avatar string containing the first character of the room name.
 fn avatar_from_room_name(room_name: &str) -> RoomPreviewAvatar {
     RoomPreviewAvatar::Text(
@@ -2269,7 +3609,7 @@ async fn spawn_sso_server(
                 if let Some(client) = get_client() {
                     if client.logged_in() {
                         is_logged_in = true;
-                        log!("Already logged in, ignore login with sso");
+                        info!("Already logged in, ignore login with sso");
                     }
                 }
             }) {
@@ -2356,8 +3696,8 @@ bitflags! {
         // const RoomCanonicalAlias = 1 << 40;
         // const RoomCreate = 1 << 41;
         // const RoomEncryption = 1 << 42;
-        // const RoomGuestAccess = 1 << 43;
-        // const RoomHistoryVisibility = 1 << 44;
+        const RoomGuestAccess = 1 << 43;
+        const RoomHistoryVisibility = 1 << 44;
         // const RoomJoinRules = 1 << 45;
         // const RoomMember = 1 << 46;
         // const RoomName = 1 << 47;
@@ -2365,7 +3705,7 @@ bitflags! {
         // const RoomPowerLevels = 1 << 49;
         // const RoomServerAcl = 1 << 50;
         // const RoomThirdPartyInvite = 1 << 51;
-        // const RoomTombstone = 1 << 52;
+        const RoomTombstone = 1 << 52;
         // const RoomTopic = 1 << 53;
         // const SpaceChild = 1 << 54;
         // const SpaceParent = 1 << 55;
@@ -2390,6 +3730,9 @@ impl UserPowerLevels {
         retval.set(UserPowerLevels::RoomRedaction, user_power >= power_levels.for_message(MessageLikeEventType::RoomRedaction));
         retval.set(UserPowerLevels::Sticker, user_power >= power_levels.for_message(MessageLikeEventType::Sticker));
         retval.set(UserPowerLevels::RoomPinnedEvents, user_power >= power_levels.for_state(StateEventType::RoomPinnedEvents));
+        retval.set(UserPowerLevels::RoomGuestAccess, user_power >= power_levels.for_state(StateEventType::RoomGuestAccess));
+        retval.set(UserPowerLevels::RoomHistoryVisibility, user_power >= power_levels.for_state(StateEventType::RoomHistoryVisibility));
+        retval.set(UserPowerLevels::RoomTombstone, user_power >= power_levels.for_state(StateEventType::RoomTombstone));
         retval
     }
 
@@ -2446,4 +3789,17 @@ impl UserPowerLevels {
     pub fn can_pin(self) -> bool {
         self.contains(UserPowerLevels::RoomPinnedEvents)
     }
+
+    pub fn can_change_guest_access(self) -> bool {
+        self.contains(UserPowerLevels::RoomGuestAccess)
+    }
+
+    pub fn can_change_history_visibility(self) -> bool {
+        self.contains(UserPowerLevels::RoomHistoryVisibility)
+    }
+
+    /// Upgrading a room requires the power to replace it with a tombstone event.
+    pub fn can_upgrade_room(self) -> bool {
+        self.contains(UserPowerLevels::RoomTombstone)
+    }
 }
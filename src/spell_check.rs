@@ -0,0 +1,201 @@
+//! Spell checking of the message being composed, using Hunspell dictionaries.
+//!
+//! A dictionary is a pair of `.aff` and `.dic` files named after its language,
+//! e.g., `en_US.aff` and `en_US.dic`. Dictionaries are looked up in the `dictionaries` directory
+//! within the app data directory, followed by the directories where the OS usually installs them.
+
+use std::{collections::BTreeSet, ops::Range, path::PathBuf, sync::{Arc, Mutex}};
+
+use anyhow::{anyhow, bail};
+use makepad_widgets::Cx;
+use tracing::{error, info};
+use spellbook::Dictionary;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::{
+    app_data_dir,
+    i18n::tr_args,
+    settings::app_settings::app_settings,
+    shared::popup_list::enqueue_popup_notification,
+};
+
+/// The maximum number of suggestions shown for a misspelled word.
+pub const MAX_SPELLING_SUGGESTIONS: usize = 6;
+
+/// The directories in which the OS or other apps usually install Hunspell dictionaries.
+const SYSTEM_DICTIONARY_DIRS: &[&str] = &[
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+    "/Library/Spelling",
+];
+
+/// Characters that mark a word as part of a mention, link, emoji shortcode, or other markup,
+/// which shouldn't be spell checked.
+const MARKUP_CHARS: &[char] = &['@', '#', ':', '/', '\\', '`', '<', '>', '[', ']', '(', ')', '=', '_'];
+
+/// Actions sent to the UI thread when the spell checking dictionary has been loaded or unloaded.
+///
+/// These are NOT widget actions, so they must be matched using `action.downcast_ref()`.
+#[derive(Clone, Debug)]
+pub enum SpellCheckAction {
+    DictionaryChanged,
+}
+
+/// The currently-loaded dictionary along with its language.
+struct LoadedDictionary {
+    language: String,
+    dictionary: Arc<Dictionary>,
+}
+
+static DICTIONARY: Mutex<Option<LoadedDictionary>> = Mutex::new(None);
+
+/// Returns the directories in which dictionaries are looked up, in order of precedence.
+fn dictionary_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![app_data_dir().join("dictionaries")];
+    if let Some(home) = std::env::var_os("HOME") {
+        dirs.push(PathBuf::from(home).join("Library").join("Spelling"));
+    }
+    dirs.extend(SYSTEM_DICTIONARY_DIRS.iter().map(PathBuf::from));
+    dirs
+}
+
+/// Returns the languages of all dictionaries that are installed, e.g., `en_US`.
+pub fn available_languages() -> Vec<String> {
+    let mut languages = BTreeSet::new();
+    for dir in dictionary_dirs() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for path in entries.filter_map(|entry| entry.ok().map(|e| e.path())) {
+            if path.extension().is_some_and(|ext| ext == "dic") && path.with_extension("aff").is_file() {
+                if let Some(language) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    languages.insert(language.to_owned());
+                }
+            }
+        }
+    }
+    languages.into_iter().collect()
+}
+
+/// Returns the currently-loaded dictionary, if spell checking is enabled and a dictionary was found.
+pub fn dictionary() -> Option<Arc<Dictionary>> {
+    DICTIONARY.lock().unwrap().as_ref().map(|loaded| loaded.dictionary.clone())
+}
+
+/// Loads the dictionary of the spell checking language chosen in the app settings,
+/// or unloads the current dictionary if spell checking is disabled.
+///
+/// Because parsing a dictionary can take a while, it is loaded on a background thread.
+pub fn load_dictionary_from_settings() {
+    let (enabled, language) = {
+        let settings = app_settings();
+        (settings.spell_check, settings.spell_check_language.trim().to_owned())
+    };
+    if !enabled {
+        if DICTIONARY.lock().unwrap().take().is_some() {
+            Cx::post_action(SpellCheckAction::DictionaryChanged);
+        }
+        return;
+    }
+    if DICTIONARY.lock().unwrap().as_ref().is_some_and(|loaded| loaded.language == language) {
+        return;
+    }
+    std::thread::spawn(move || match load_dictionary(&language) {
+        Ok(dictionary) => {
+            info!("Loaded the spell checking dictionary for {language}");
+            *DICTIONARY.lock().unwrap() = Some(LoadedDictionary {
+                language,
+                dictionary: Arc::new(dictionary),
+            });
+            Cx::post_action(SpellCheckAction::DictionaryChanged);
+        }
+        Err(e) => {
+            error!("Failed to load the spell checking dictionary for {language}: {e}");
+            enqueue_popup_notification(tr_args(
+                "spell_check.load_failed",
+                &[("language", &language), ("error", &e.to_string())],
+            ));
+        }
+    });
+}
+
+/// Loads the dictionary for the given language from the first directory that contains it.
+fn load_dictionary(language: &str) -> anyhow::Result<Dictionary> {
+    for dir in dictionary_dirs() {
+        let aff_path = dir.join(format!("{language}.aff"));
+        let dic_path = dir.join(format!("{language}.dic"));
+        if !aff_path.is_file() || !dic_path.is_file() {
+            continue;
+        }
+        let aff = std::fs::read_to_string(&aff_path)?;
+        let dic = std::fs::read_to_string(&dic_path)?;
+        return Dictionary::new(&aff, &dic).map_err(|e| anyhow!("invalid dictionary {}: {e}", dic_path.display()));
+    }
+    bail!("no dictionary was found")
+}
+
+/// Returns the byte ranges of all misspelled words in the given text.
+pub fn misspelled_words(text: &str, dictionary: &Dictionary) -> Vec<Range<usize>> {
+    text.split_word_bound_indices()
+        .filter(|(start, word)| should_check(text, *start, word))
+        .filter(|(_, word)| !dictionary.check(word))
+        .map(|(start, word)| start..start + word.len())
+        .collect()
+}
+
+/// Returns suggested corrections of the given misspelled word, best first.
+pub fn suggestions(word: &str, dictionary: &Dictionary) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    dictionary.suggest(word, &mut suggestions);
+    suggestions.truncate(MAX_SPELLING_SUGGESTIONS);
+    suggestions
+}
+
+/// Returns whether the word starting at byte index `start` of `text` should be spell checked,
+/// i.e., it consists of letters (and apostrophes) and isn't part of any markup.
+fn should_check(text: &str, start: usize, word: &str) -> bool {
+    let is_word = word.chars().any(char::is_alphabetic)
+        && word.chars().all(|c| c.is_alphabetic() || c == '\'' || c == '’');
+    if !is_word {
+        return false;
+    }
+    // The whitespace-delimited token that contains this word.
+    let token_start = text[..start].rfind(char::is_whitespace)
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let token_end = text[start..].find(char::is_whitespace)
+        .map_or(text.len(), |i| start + i);
+    !text[token_start..token_end].contains(MARKUP_CHARS)
+}
+
+#[cfg(test)]
+mod tests_spell_check {
+    use super::*;
+
+    fn test_dictionary() -> Dictionary {
+        Dictionary::new("SET UTF-8\n", "4\nhello\nworld\nthere\ndon't\n").unwrap()
+    }
+
+    #[test]
+    fn test_misspelled_words() {
+        let dictionary = test_dictionary();
+        let text = "Hello wrold, don't go there helo";
+        let misspelled = misspelled_words(text, &dictionary)
+            .into_iter()
+            .map(|range| &text[range])
+            .collect::<Vec<_>>();
+        assert_eq!(misspelled, ["wrold", "go", "helo"]);
+    }
+
+    #[test]
+    fn test_markup_is_not_checked() {
+        let dictionary = test_dictionary();
+        let text = "hello @alice:example.org #robrix :thumbsup: https://example.org `cde` 42";
+        assert!(misspelled_words(text, &dictionary).is_empty());
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let dictionary = test_dictionary();
+        assert!(suggestions("helo", &dictionary).contains(&"hello".to_owned()));
+    }
+}
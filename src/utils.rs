@@ -1,11 +1,13 @@
 use std::{borrow::Cow, time::SystemTime};
 
 use chrono::{DateTime, Duration, Local, TimeZone};
-use makepad_widgets::{error, image_cache::ImageError, Cx, Event, ImageRef};
-use matrix_sdk::{media::{MediaFormat, MediaThumbnailSettings, MediaThumbnailSize}, ruma::{api::client::media::get_content_thumbnail::v3::Method, MilliSecondsSinceUnixEpoch, OwnedRoomId}};
+use makepad_widgets::{image_cache::{ImageBuffer, ImageError}, vec4, Cx, DVec2, Event, ImageRef, Vec4};
+use tracing::error;
+use matrix_sdk::{media::{MediaFormat, MediaThumbnailSettings, MediaThumbnailSize}, ruma::{api::client::media::get_content_thumbnail::v3::Method, matrix_uri::MatrixId, MatrixToUri, MilliSecondsSinceUnixEpoch, OwnedRoomId, UserId}};
 use matrix_sdk_ui::timeline::{EventTimelineItem, TimelineDetails};
+use qrcode::{Color, QrCode};
 
-use crate::sliding_sync::{submit_async_request, MatrixRequest};
+use crate::{settings::app_settings::app_settings, sliding_sync::{submit_async_request, MatrixRequest}};
 
 
 /// Returns true if the given event is an interactive hit-related event
@@ -86,21 +88,40 @@ pub fn load_png_or_jpg(img: &ImageRef, cx: &mut Cx, data: &[u8]) -> Result<(), I
     res
 }
 
+/// Decodes the given image `data` as either a PNG or JPEG into an [`ImageBuffer`],
+/// using the `imghdr` library to determine which format it is.
+///
+/// Unlike [`load_png_or_jpg()`], this doesn't require a `Cx`,
+/// so it can be used to decode images on a background thread.
+pub fn decode_png_or_jpg(data: &[u8]) -> Result<ImageBuffer, ImageError> {
+    match imghdr::from_bytes(data) {
+        Some(imghdr::Type::Png) => ImageBuffer::from_png(data),
+        Some(imghdr::Type::Jpeg) => ImageBuffer::from_jpg(data),
+        // Attempt to decode it as a PNG or JPEG anyway, since imghdr isn't perfect.
+        _ => ImageBuffer::from_png(data)
+            .or_else(|_| ImageBuffer::from_jpg(data))
+            .map_err(|_| ImageError::UnsupportedFormat),
+    }
+}
+
 
 pub fn unix_time_millis_to_datetime(millis: &MilliSecondsSinceUnixEpoch) -> Option<DateTime<Local>> {
     let millis: i64 = millis.get().into();
     Local.timestamp_millis_opt(millis).single()
 }
 
-/// Formats a given Unix timestamp in milliseconds into a relative human-readable date.
+/// How often timestamps shown relative to the current time (e.g., "5 mins ago") are refreshed, in seconds.
+pub const RELATIVE_TIMESTAMP_REFRESH_SECS: f64 = 30.0;
+
+/// Formats a given Unix timestamp in milliseconds into a human-readable date relative to now.
 ///
 /// # Cases:
-/// - **Less than 60 seconds ago**: Returns `"Just now"`.
-/// - **Less than 60 minutes ago**: Returns `"X minutes ago"`, where X is the number of minutes.
+/// - **Within the relative timestamps threshold from the app settings**: Returns `"Now"`
+///   for less than 60 seconds ago, otherwise `"X mins ago"`, `"X hours ago"`, or `"X days ago"`.
 /// - **Same day**: Returns `"HH:MM"` (current time format for today).
 /// - **Yesterday**: Returns `"Yesterday at HH:MM"` for messages from the previous day.
 /// - **Within the past week**: Returns the name of the day (e.g., "Tuesday").
-/// - **Older than a week**: Returns `"DD/MM/YY"` as the absolute date.
+/// - **Older than a week**: Returns `"YYYY-MM-DD"` as the absolute date.
 ///
 /// # Arguments:
 /// - `millis`: The Unix timestamp in milliseconds to format.
@@ -109,33 +130,60 @@ pub fn unix_time_millis_to_datetime(millis: &MilliSecondsSinceUnixEpoch) -> Opti
 /// - `Option<String>` representing the human-readable time or `None` if formatting fails.
 pub fn relative_format(millis: &MilliSecondsSinceUnixEpoch) -> Option<String> {
     let datetime = unix_time_millis_to_datetime(millis)?;
+    let max_relative_age = app_settings().relative_timestamps.max_age();
+    Some(relative_format_at(datetime, Local::now(), max_relative_age))
+}
 
-    // Calculate the time difference between now and the given timestamp
-    let now = Local::now();
+/// Formats the given `datetime` relative to `now`; see [`relative_format()`].
+fn relative_format_at(
+    datetime: DateTime<Local>,
+    now: DateTime<Local>,
+    max_relative_age: Option<Duration>,
+) -> String {
     let duration = now - datetime;
-
-    // Handle different time ranges and format accordingly
-    if duration < Duration::seconds(60) {
-        Some("Now".to_string())
-    } else if duration < Duration::minutes(60) {
-        let minutes_text = if duration.num_minutes() == 1 { "min" } else { "mins" };
-        Some(format!("{} {} ago", duration.num_minutes(), minutes_text))
-    } else if duration < Duration::hours(24) && now.date_naive() == datetime.date_naive() {
-        Some(format!("{}", datetime.format("%H:%M"))) // "HH:MM" format for today
-    } else if duration < Duration::hours(48) {
-        if let Some(yesterday) = now.date_naive().succ_opt() {
-            if yesterday == datetime.date_naive() {
-                return Some(format!("Yesterday at {}", datetime.format("%H:%M")));
-            }
-        }
-        Some(format!("{}", datetime.format("%A"))) // Fallback to day of the week if not yesterday
+    if let Some(text) = relative_duration_text(duration, max_relative_age) {
+        text
+    } else if now.date_naive() == datetime.date_naive() {
+        datetime.format("%H:%M").to_string() // "HH:MM" format for today
+    } else if now.date_naive().pred_opt() == Some(datetime.date_naive()) {
+        format!("Yesterday at {}", datetime.format("%H:%M"))
     } else if duration < Duration::weeks(1) {
-        Some(format!("{}", datetime.format("%A"))) // Day of the week (e.g., "Tuesday")
+        datetime.format("%A").to_string() // Day of the week (e.g., "Tuesday")
     } else {
-        Some(format!("{}", datetime.format("%F"))) // "YYYY-MM-DD" format for older messages
+        datetime.format("%F").to_string() // "YYYY-MM-DD" format for older messages
     }
 }
 
+/// Formats the given timestamp of a timeline event: relative to now (e.g., `"5 mins ago"`)
+/// if it's within the relative timestamps threshold from the app settings,
+/// otherwise as a 12-hour time (e.g., `"3:45 pm"`).
+pub fn timeline_timestamp_text(millis: &MilliSecondsSinceUnixEpoch) -> Option<String> {
+    let datetime = unix_time_millis_to_datetime(millis)?;
+    let max_relative_age = app_settings().relative_timestamps.max_age();
+    Some(
+        relative_duration_text(Local::now() - datetime, max_relative_age)
+            .unwrap_or_else(|| datetime.time().format("%l:%M %P").to_string())
+    )
+}
+
+/// Returns how long ago something happened, e.g., `"5 mins ago"`,
+/// or `None` if `duration` is not less than `max_relative_age`.
+fn relative_duration_text(duration: Duration, max_relative_age: Option<Duration>) -> Option<String> {
+    if duration >= max_relative_age? {
+        return None;
+    }
+    let ago = |n: i64, unit: &str| format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" });
+    Some(if duration < Duration::seconds(60) {
+        "Now".to_string()
+    } else if duration < Duration::hours(1) {
+        ago(duration.num_minutes(), "min")
+    } else if duration < Duration::days(1) {
+        ago(duration.num_hours(), "hour")
+    } else {
+        ago(duration.num_days(), "day")
+    })
+}
+
 /// Returns the first "letter" (Unicode grapheme) of given user name,
 /// skipping any leading "@" characters.
 pub fn user_name_first_letter(user_name: &str) -> Option<&str> {
@@ -145,6 +193,59 @@ pub fn user_name_first_letter(user_name: &str) -> Option<&str> {
         .find(|&g| g != "@")
 }
 
+/// The palette of colors from which each user's username color is chosen,
+/// which matches the one used by Element such that users are recognizable across clients.
+pub const USERNAME_COLORS: [Vec4; 8] = [
+    vec4(0.212, 0.545, 0.839, 1.0), // #368bd6
+    vec4(0.675, 0.231, 0.659, 1.0), // #ac3ba8
+    vec4(0.012, 0.702, 0.506, 1.0), // #03b381
+    vec4(0.902, 0.310, 0.478, 1.0), // #e64f7a
+    vec4(1.000, 0.506, 0.176, 1.0), // #ff812d
+    vec4(0.176, 0.761, 0.773, 1.0), // #2dc2c5
+    vec4(0.361, 0.337, 0.961, 1.0), // #5c56f5
+    vec4(0.455, 0.820, 0.173, 1.0), // #74d12c
+];
+
+/// Returns the index into [`USERNAME_COLORS`] of the given user's color.
+///
+/// This uses the same stable hash of the user ID as Element,
+/// so a given user always has the same color, on every device.
+pub fn username_color_index(user_id: &UserId) -> usize {
+    let hash = user_id.as_str()
+        .encode_utf16()
+        .fold(0i32, |hash, unit| hash.wrapping_shl(5).wrapping_sub(hash).wrapping_add(unit as i32));
+    hash.unsigned_abs() as usize % USERNAME_COLORS.len()
+}
+
+/// Returns the color in which the given user's name is shown.
+pub fn username_color(user_id: &UserId) -> Vec4 {
+    USERNAME_COLORS[username_color_index(user_id)]
+}
+
+/// The maximum number of emoji that a message can consist of
+/// in order for it to be displayed with large emoji.
+pub const MAX_LARGE_EMOJI_COUNT: usize = 3;
+
+/// Returns `true` if the given text consists solely of
+/// 1 to [`MAX_LARGE_EMOJI_COUNT`] emoji, ignoring any whitespace.
+pub fn is_large_emoji_text(text: &str) -> bool {
+    use unicode_segmentation::UnicodeSegmentation;
+    let mut count = 0;
+    for grapheme in text.graphemes(true) {
+        if grapheme.trim().is_empty() {
+            continue;
+        }
+        // Emoji can be sent without the variation selector that requests emoji presentation.
+        let is_emoji = emojis::get(grapheme).is_some()
+            || emojis::get(&format!("{grapheme}\u{FE0F}")).is_some();
+        count += 1;
+        if !is_emoji || count > MAX_LARGE_EMOJI_COUNT {
+            return false;
+        }
+    }
+    count > 0
+}
+
 
 /// A const-compatible version of [`MediaFormat`].
 #[derive(Clone, Debug)]
@@ -307,6 +408,45 @@ pub fn linkify(text: &str, is_html: bool) -> Cow<'_, str> {
     Cow::Owned(linkified_text)
 }
 
+/// Converts HTML links to users and rooms (`matrix.to` links) into `<mention>` tags,
+/// which are displayed as interactive pills rather than as plain links.
+///
+/// Links to other things, such as events, are left unchanged.
+pub fn mention_pills(html: &str) -> Cow<'_, str> {
+    const LINK_START: &str = "<a href=\"";
+    const LINK_END: &str = "</a>";
+    if !html.contains("matrix.to/#/") {
+        return Cow::Borrowed(html);
+    }
+
+    let mut converted = String::with_capacity(html.len());
+    let mut rest = html;
+    let mut changed = false;
+    while let Some(start) = rest.find(LINK_START) {
+        let after_start = &rest[start + LINK_START.len() ..];
+        let (Some(href_end), Some(close)) = (after_start.find('"'), after_start.find(LINK_END)) else {
+            break;
+        };
+        let is_mention = href_end < close && MatrixToUri::parse(&after_start[..href_end].replace("&amp;", "&"))
+            .is_ok_and(|uri| matches!(uri.id(), MatrixId::User(_) | MatrixId::Room(_) | MatrixId::RoomAlias(_)));
+        if is_mention {
+            converted.push_str(&rest[..start]);
+            converted.push_str("<mention href=\"");
+            converted.push_str(&after_start[..close]);
+            converted.push_str("</mention>");
+            changed = true;
+        } else {
+            converted.push_str(&rest[.. start + LINK_START.len() + close + LINK_END.len()]);
+        }
+        rest = &after_start[close + LINK_END.len() ..];
+    }
+    if !changed {
+        return Cow::Borrowed(html);
+    }
+    converted.push_str(rest);
+    Cow::Owned(converted)
+}
+
 
 /// Returns true if the given `text` string ends with a valid href attribute opener.
 ///
@@ -426,6 +566,112 @@ pub fn get_or_fetch_event_sender(
 }
 
 
+/// The minimum horizontal distance (in logical pixels) that a finger must travel
+/// for a touch gesture to be considered a swipe.
+pub const SWIPE_MIN_DISTANCE: f64 = 60.0;
+
+/// The width of the area along the left edge of the screen in which
+/// a rightwards swipe is treated as a "go back" gesture.
+pub const SWIPE_BACK_EDGE_WIDTH: f64 = 24.0;
+
+/// The direction of a horizontal swipe gesture.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SwipeDirection {
+    Left,
+    Right,
+}
+
+/// The number of pixels used to draw each module (square) of a QR code image.
+const QR_MODULE_PIXELS: usize = 8;
+/// The number of blank modules around a QR code, which scanners need to find it.
+const QR_QUIET_ZONE_MODULES: usize = 4;
+
+/// Renders the given data as a black-on-white QR code image, including its quiet zone.
+pub fn qr_code_image(data: &[u8]) -> Option<ImageBuffer> {
+    let code = QrCode::new(data)
+        .inspect_err(|e| error!("Failed to generate QR code: {e:?}"))
+        .ok()?;
+    let num_modules = code.width();
+    let colors = code.to_colors();
+    let size = (num_modules + 2 * QR_QUIET_ZONE_MODULES) * QR_MODULE_PIXELS;
+    let mut image = ImageBuffer::new(size, size).ok()?;
+    for y in 0..size {
+        for x in 0..size {
+            let module_x = (x / QR_MODULE_PIXELS).checked_sub(QR_QUIET_ZONE_MODULES);
+            let module_y = (y / QR_MODULE_PIXELS).checked_sub(QR_QUIET_ZONE_MODULES);
+            let is_dark = match (module_x, module_y) {
+                (Some(mx), Some(my)) if mx < num_modules && my < num_modules => {
+                    colors[my * num_modules + mx] == Color::Dark
+                }
+                _ => false,
+            };
+            image.data[y * size + x] = if is_dark { 0xFF000000 } else { 0xFFFFFFFF };
+        }
+    }
+    Some(image)
+}
+
+/// Returns the direction of the horizontal swipe from `start` to `end`, if any.
+///
+/// A swipe must travel at least [`SWIPE_MIN_DISTANCE`] horizontally,
+/// and at least twice as far horizontally as it did vertically,
+/// such that vertical scrolling isn't mistaken for a swipe.
+pub fn horizontal_swipe(start: DVec2, end: DVec2) -> Option<SwipeDirection> {
+    let dx = end.x - start.x;
+    let dy = end.y - start.y;
+    if dx.abs() < SWIPE_MIN_DISTANCE || dx.abs() < dy.abs() * 2.0 {
+        return None;
+    }
+    Some(if dx > 0.0 { SwipeDirection::Right } else { SwipeDirection::Left })
+}
+
+/// Moves a selection by `steps` selectable items within a list of `len` items,
+/// moving backwards (towards index 0) if `steps` is negative.
+///
+/// The movement begins at `start`, which may be just outside the list (e.g., `-1` or `len`)
+/// such that the first step selects the first selectable item at that end of the list.
+/// Movement stops at either end of the list.
+///
+/// Returns the newly-selected index, or `None` if no selectable item was found.
+pub fn step_selection(
+    len: usize,
+    start: isize,
+    steps: isize,
+    is_selectable: impl Fn(usize) -> bool,
+) -> Option<usize> {
+    let direction = steps.signum();
+    let mut remaining = steps.unsigned_abs();
+    let mut index = start;
+    let mut selected = None;
+    while remaining > 0 {
+        index += direction;
+        if index < 0 || index >= len as isize {
+            break;
+        }
+        if is_selectable(index as usize) {
+            selected = Some(index as usize);
+            remaining -= 1;
+        }
+    }
+    selected
+}
+
+
+#[cfg(test)]
+mod tests_username_color {
+    use matrix_sdk::ruma::user_id;
+
+    use super::*;
+
+    #[test]
+    fn test_username_color_index() {
+        // These indices match the ones computed by Element for the same user IDs.
+        assert_eq!(username_color_index(user_id!("@alice:example.org")), 6);
+        assert_eq!(username_color_index(user_id!("@bob:example.org")), 3);
+        assert_eq!(username_color_index(user_id!("@carol:matrix.org")), 0);
+    }
+}
+
 #[cfg(test)]
 mod tests_human_readable_list {
     use super::*;
@@ -576,6 +822,38 @@ mod tests_linkify {
     }
 }
 
+#[cfg(test)]
+mod tests_mention_pills {
+    use super::*;
+
+    #[test]
+    fn test_mention_pills_user_and_room() {
+        let html = "Hi <a href=\"https://matrix.to/#/@alice:example.org\">Alice</a>, see \
+            <a href=\"https://matrix.to/#/%23robrix:example.org\">#robrix:example.org</a>";
+        assert_eq!(
+            mention_pills(html),
+            "Hi <mention href=\"https://matrix.to/#/@alice:example.org\">Alice</mention>, see \
+            <mention href=\"https://matrix.to/#/%23robrix:example.org\">#robrix:example.org</mention>",
+        );
+    }
+
+    #[test]
+    fn test_mention_pills_leaves_other_links() {
+        let html = "<a href=\"https://example.org\">site</a> and \
+            <a href=\"https://matrix.to/#/!room:example.org/$event:example.org\">a message</a>";
+        assert!(matches!(mention_pills(html), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_mention_pills_mixed() {
+        let html = "<a href=\"https://example.org\">site</a> <a href=\"https://matrix.to/#/@bob:example.org\">Bob</a>!";
+        assert_eq!(
+            mention_pills(html),
+            "<a href=\"https://example.org\">site</a> <mention href=\"https://matrix.to/#/@bob:example.org\">Bob</mention>!",
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests_ends_with_href {
     use super::*;
@@ -775,3 +1053,126 @@ mod tests_ends_with_href {
         assert!(!ends_with_href(" hrf= "));
     }
 }
+
+#[cfg(test)]
+mod tests_horizontal_swipe {
+    use makepad_widgets::dvec2;
+    use super::*;
+
+    #[test]
+    fn test_swipe_right() {
+        assert_eq!(horizontal_swipe(dvec2(10.0, 100.0), dvec2(90.0, 110.0)), Some(SwipeDirection::Right));
+    }
+
+    #[test]
+    fn test_swipe_left() {
+        assert_eq!(horizontal_swipe(dvec2(200.0, 100.0), dvec2(100.0, 95.0)), Some(SwipeDirection::Left));
+    }
+
+    #[test]
+    fn test_too_short() {
+        assert_eq!(horizontal_swipe(dvec2(10.0, 100.0), dvec2(50.0, 100.0)), None);
+    }
+
+    #[test]
+    fn test_mostly_vertical() {
+        assert_eq!(horizontal_swipe(dvec2(10.0, 100.0), dvec2(90.0, 250.0)), None);
+    }
+}
+
+#[cfg(test)]
+mod tests_is_large_emoji_text {
+    use super::*;
+
+    #[test]
+    fn test_single_emoji() {
+        assert!(is_large_emoji_text("😀"));
+    }
+
+    #[test]
+    fn test_three_emoji_with_whitespace() {
+        assert!(is_large_emoji_text(" 😀 🎉🚀 "));
+    }
+
+    #[test]
+    fn test_too_many_emoji() {
+        assert!(!is_large_emoji_text("😀😀😀😀"));
+    }
+
+    #[test]
+    fn test_emoji_with_text() {
+        assert!(!is_large_emoji_text("hi 😀"));
+    }
+
+    #[test]
+    fn test_empty() {
+        assert!(!is_large_emoji_text(""));
+        assert!(!is_large_emoji_text("   "));
+    }
+
+    #[test]
+    fn test_plain_text() {
+        assert!(!is_large_emoji_text("abc"));
+    }
+}
+
+#[cfg(test)]
+mod tests_step_selection {
+    use super::*;
+
+    #[test]
+    fn test_step_forwards_and_backwards() {
+        assert_eq!(step_selection(10, 4, 1, |_| true), Some(5));
+        assert_eq!(step_selection(10, 4, -1, |_| true), Some(3));
+    }
+
+    #[test]
+    fn test_skips_unselectable_items() {
+        assert_eq!(step_selection(10, 4, 1, |i| i % 3 == 0), Some(6));
+        assert_eq!(step_selection(10, 4, -2, |i| i % 3 == 0), Some(0));
+    }
+
+    #[test]
+    fn test_stops_at_end_of_list() {
+        assert_eq!(step_selection(10, 7, 5, |_| true), Some(9));
+        assert_eq!(step_selection(10, 9, 1, |_| true), None);
+    }
+
+    #[test]
+    fn test_start_outside_list() {
+        assert_eq!(step_selection(10, -1, 1, |_| true), Some(0));
+        assert_eq!(step_selection(10, 10, -1, |_| true), Some(9));
+    }
+}
+
+#[cfg(test)]
+mod tests_relative_format {
+    use super::*;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, 0).unwrap()
+    }
+
+    #[test]
+    fn test_relative_format_within_threshold() {
+        let now = local(2024, 5, 15, 12, 0);
+        let one_day = Some(Duration::days(1));
+        assert_eq!(relative_format_at(local(2024, 5, 15, 12, 0), now, one_day), "Now");
+        assert_eq!(relative_format_at(local(2024, 5, 15, 11, 59), now, one_day), "1 min ago");
+        assert_eq!(relative_format_at(local(2024, 5, 15, 11, 15), now, one_day), "45 mins ago");
+        assert_eq!(relative_format_at(local(2024, 5, 15, 9, 0), now, one_day), "3 hours ago");
+    }
+
+    #[test]
+    fn test_relative_format_beyond_threshold() {
+        let now = local(2024, 5, 15, 12, 0);
+        let one_hour = Some(Duration::hours(1));
+        assert_eq!(relative_format_at(local(2024, 5, 15, 11, 15), now, one_hour), "45 mins ago");
+        assert_eq!(relative_format_at(local(2024, 5, 15, 9, 5), now, one_hour), "09:05");
+        assert_eq!(relative_format_at(local(2024, 5, 14, 18, 30), now, one_hour), "Yesterday at 18:30");
+        assert_eq!(relative_format_at(local(2024, 5, 12, 18, 30), now, one_hour), "Sunday");
+        assert_eq!(relative_format_at(local(2024, 4, 1, 8, 0), now, one_hour), "2024-04-01");
+        // With relative timestamps disabled, even very recent times are absolute.
+        assert_eq!(relative_format_at(local(2024, 5, 15, 11, 59), now, None), "11:59");
+    }
+}
@@ -1,19 +1,32 @@
 use std::sync::Arc;
 use futures_util::StreamExt;
-use makepad_widgets::{log, warning, ActionDefaultRef, Cx, DefaultNone};
+use makepad_widgets::{ActionDefaultRef, Cx, DefaultNone};
+use tracing::{info, warn};
 use matrix_sdk::{
     crypto::{AcceptedProtocols, CancelInfo, EmojiShortAuthString}, encryption::{verification::{
-        SasState, SasVerification, Verification, VerificationRequest,
-        VerificationRequestState,
+        QrVerification, QrVerificationState, SasState, SasVerification, Verification,
+        VerificationRequest, VerificationRequestState,
     }, VerificationState}, ruma::{
         events::{
             key::verification::{request::ToDeviceKeyVerificationRequestEvent, VerificationMethod}, room::message::{MessageType, OriginalSyncRoomMessageEvent}
         },
-        UserId,
+        OwnedDeviceId, OwnedUserId, UserId,
     }, Client
 };
 use tokio::{runtime::Handle, sync::mpsc::{UnboundedReceiver, UnboundedSender}};
 
+use crate::{i18n::tr_args, shared::popup_list::enqueue_popup_notification};
+
+/// Returns the verification methods that we support:
+/// emoji/decimal SAS, and showing a QR code for the other device to scan.
+fn supported_methods() -> Vec<VerificationMethod> {
+    vec![
+        VerificationMethod::SasV1,
+        VerificationMethod::QrCodeShowV1,
+        VerificationMethod::ReciprocateV1,
+    ]
+}
+
 #[derive(Clone, Debug, DefaultNone)]
 pub enum VerificationStateAction {
     Update(VerificationState),
@@ -22,10 +35,10 @@ pub enum VerificationStateAction {
 
 pub fn add_verification_event_handlers_and_sync_client(client: Client) {
     let mut verification_state_subscriber = client.encryption().verification_state();
-    log!("Initial verification state is {:?}", verification_state_subscriber.get());
+    info!("Initial verification state is {:?}", verification_state_subscriber.get());
     Handle::current().spawn(async move {
         while let Some(state) = verification_state_subscriber.next().await {
-            log!("Received a verification state update: {state:?}");
+            info!("Received a verification state update: {state:?}");
             Cx::post_action(VerificationStateAction::Update(state));
             if let VerificationState::Verified = state {
                 break;
@@ -43,7 +56,7 @@ pub fn add_verification_event_handlers_and_sync_client(client: Client) {
                 Handle::current().spawn(request_verification_handler(client, request));
             }
             else {
-                warning!("Skipping invalid verification request from {}, transaction ID: {}\n   Content: {:?}",
+                warn!("Skipping invalid verification request from {}, transaction ID: {}\n   Content: {:?}",
                     ev.sender, ev.content.transaction_id, ev.content,
                 );
             }
@@ -61,7 +74,7 @@ pub fn add_verification_event_handlers_and_sync_client(client: Client) {
                     Handle::current().spawn(request_verification_handler(client, request));
                 }
                 else {
-                    warning!("Skipping invalid verification request from {}, event ID: {}\n   Content: {:?}",
+                    warn!("Skipping invalid verification request from {}, event ID: {}\n   Content: {:?}",
                         ev.sender, ev.event_id, ev.content,
                     );
                 }
@@ -94,19 +107,22 @@ async fn sas_verification_handler(
     sas: SasVerification,
     response_receiver: UnboundedReceiver<VerificationUserResponse>,
 ) {
-    log!(
+    info!(
         "Starting verification with {} {}",
         &sas.other_device().user_id(),
         &sas.other_device().device_id()
     );
-    log!("[Pre-verification] {}", dump_devices(sas.other_device().user_id(), &client).await);
+    info!("[Pre-verification] {}", dump_devices(sas.other_device().user_id(), &client).await);
 
     let mut stream = sas.changes();
     // Accept the SAS verification with both default methods: emoji and decimal.
-    if let Err(e) = sas.accept().await {
-        log!("Error accepting SAS verification request: {:?}", e);
-        Cx::post_action(VerificationAction::RequestAcceptError(Arc::new(e)));
-        return;
+    // If we started the SAS verification, it's up to the other side to accept it.
+    if !sas.we_started() {
+        if let Err(e) = sas.accept().await {
+            info!("Error accepting SAS verification request: {:?}", e);
+            Cx::post_action(VerificationAction::RequestAcceptError(Arc::new(e)));
+            return;
+        }
     }
 
     // A little trick to allow us to move the response_receiver into the async block below.
@@ -125,19 +141,23 @@ async fn sas_verification_handler(
                 if let Some(mut receiver) = receiver_opt.take() {
                     let sas2 = sas.clone();
                     Handle::current().spawn(async move {
-                        log!("Waiting for user to confirm SAS verification keys...");
+                        info!("Waiting for user to confirm SAS verification keys...");
                         match receiver.recv().await {
                             Some(VerificationUserResponse::Accept) => {
-                                log!("User confirmed SAS verification keys");
+                                info!("User confirmed SAS verification keys");
                                 if let Err(e) = sas2.confirm().await {
-                                    log!("Failed to confirm SAS verification keys; error: {:?}", e);
+                                    info!("Failed to confirm SAS verification keys; error: {:?}", e);
                                     Cx::post_action(VerificationAction::SasConfirmationError(Arc::new(e)));
                                 }
                                 // If successful, SAS verification will now transition to the Confirmed state,
                                 // which will be sent to the main UI thread in the `SasState::Confirmed` match arm below.
                             }
-                            Some(VerificationUserResponse::Cancel) | None => {
-                                log!("User did not confirm SAS verification keys");
+                            // The QR code responses are only sent while our QR code is shown.
+                            Some(VerificationUserResponse::Cancel
+                                | VerificationUserResponse::ConfirmQrScanned
+                                | VerificationUserResponse::StartSas
+                            ) | None => {
+                                info!("User did not confirm SAS verification keys");
                                 let _ = sas2.cancel().await;
                             }
                         }
@@ -145,7 +165,7 @@ async fn sas_verification_handler(
                 } else {
                     // Receiving a second `KeysExchanged` state indicates that the other device
                     // confirmed their keys match the ones we have *before* we confirmed them.
-                    log!("The other side confirmed that the displayed keys matched.");
+                    info!("The other side confirmed that the displayed keys matched.");
                 };
 
             }
@@ -154,17 +174,17 @@ async fn sas_verification_handler(
 
             SasState::Done { verified_devices, verified_identities } => {
                 let device = sas.other_device();
-                log!("SAS verification done.
+                info!("SAS verification done.
                     Devices: {verified_devices:?}
                     Identities: {verified_identities:?}",
                 );
-                log!(
+                info!(
                     "Successfully verified device {} {} {:?}",
                     device.user_id(),
                     device.device_id(),
                     device.local_trust_state()
                 );
-                log!("[Post-verification] {}", dump_devices(sas.other_device().user_id(), &client).await);
+                info!("[Post-verification] {}", dump_devices(sas.other_device().user_id(), &client).await);
                 // We go ahead and send the RequestCompleted action here,
                 // because it is not guaranteed that the VerificationRequestState stream loop
                 // will receive an update an enter the `Done` state.
@@ -172,7 +192,7 @@ async fn sas_verification_handler(
                 break;
             }
             SasState::Cancelled(cancel_info) => {
-                log!("SAS verification has been cancelled, reason: {}", cancel_info.reason());
+                info!("SAS verification has been cancelled, reason: {}", cancel_info.reason());
                 // We go ahead and send the RequestCancelled action here,
                 // because it is not guaranteed that the VerificationRequestState stream loop
                 // will receive an update an enter the `Cancelled` state.
@@ -184,7 +204,7 @@ async fn sas_verification_handler(
 }
 
 async fn request_verification_handler(client: Client, request: VerificationRequest) {
-    log!("Received a verification request in room {:?}: {:?}", request.room_id(), request.state());
+    info!("Received a verification request in room {:?}: {:?}", request.room_id(), request.state());
     let (sender, mut response_receiver) = tokio::sync::mpsc::unbounded_channel::<VerificationUserResponse>();
     Cx::post_action(
         VerificationAction::RequestReceived(
@@ -197,10 +217,8 @@ async fn request_verification_handler(client: Client, request: VerificationReque
 
     let mut stream = request.changes();
 
-    // We currently only support SAS verification.
-    let supported_methods = vec![VerificationMethod::SasV1];
     match response_receiver.recv().await {
-        Some(VerificationUserResponse::Accept) => match request.accept_with_methods(supported_methods).await {
+        Some(VerificationUserResponse::Accept) => match request.accept_with_methods(supported_methods()).await {
             Ok(()) => {
                 Cx::post_action(VerificationAction::RequestAccepted);
                 // Fall through to the stream loop below.
@@ -210,7 +228,10 @@ async fn request_verification_handler(client: Client, request: VerificationReque
                 return;
             }
         }
-        Some(VerificationUserResponse::Cancel) | None => match request.cancel().await {
+        Some(VerificationUserResponse::Cancel
+            | VerificationUserResponse::ConfirmQrScanned
+            | VerificationUserResponse::StartSas
+        ) | None => match request.cancel().await {
             Ok(()) => { } // response will be sent in the stream loop below
             Err(e) => {
                 Cx::post_action(VerificationAction::RequestCancelError(Arc::new(e)));
@@ -222,27 +243,30 @@ async fn request_verification_handler(client: Client, request: VerificationReque
     while let Some(state) = stream.next().await {
         match state {
             VerificationRequestState::Created { .. }
-            | VerificationRequestState::Requested { .. }
-            | VerificationRequestState::Ready { .. } => { }
+            | VerificationRequestState::Requested { .. } => { }
+            // Both sides agreed on verification methods, so we show our QR code if possible.
+            VerificationRequestState::Ready { .. } => {
+                Handle::current().spawn(ready_verification_handler(client, request, response_receiver));
+                return;
+            }
             VerificationRequestState::Transitioned { verification } => match verification {
-                // We only support SAS verification.
                 Verification::SasV1(sas) => {
-                    log!("Verification request transitioned to SAS V1.");
+                    info!("Verification request transitioned to SAS V1.");
                     Handle::current().spawn(sas_verification_handler(client, sas, response_receiver));
                     return;
                 }
                 unsupported => {
-                    log!("Verification request transitioned to unsupported method: {:?}", unsupported);
+                    info!("Verification request transitioned to unsupported method: {:?}", unsupported);
                     Cx::post_action(VerificationAction::RequestTransitionedToUnsupportedMethod(unsupported));
                     return;
                 }
             }
             VerificationRequestState::Cancelled(info) => {
-                log!("Verification request was cancelled, reason: {}", info.reason());
+                info!("Verification request was cancelled, reason: {}", info.reason());
                 Cx::post_action(VerificationAction::RequestCancelled(info));
             }
             VerificationRequestState::Done => {
-                log!("Verification request is done!");
+                info!("Verification request is done!");
                 Cx::post_action(VerificationAction::RequestCompleted);
                 return;
             }
@@ -251,11 +275,297 @@ async fn request_verification_handler(client: Client, request: VerificationReque
 }
 
 
+/// Drives a verification request that both sides have accepted to completion.
+///
+/// If the other device can scan QR codes, we show it our QR code and let the user choose
+/// to compare emoji instead; otherwise, we go straight to SAS (emoji) verification.
+async fn ready_verification_handler(
+    client: Client,
+    request: VerificationRequest,
+    mut response_receiver: UnboundedReceiver<VerificationUserResponse>,
+) {
+    let qr = match request.generate_qr_code().await {
+        Ok(qr) => qr,
+        Err(e) => {
+            warn!("Failed to generate a QR code for verification: {e:?}");
+            None
+        }
+    };
+    let qr_code_data = qr.as_ref().and_then(|qr| qr.to_bytes()
+        .inspect_err(|e| warn!("Failed to encode the verification QR code: {e:?}"))
+        .ok()
+    );
+    let (Some(qr), Some(qr_code_data)) = (qr, qr_code_data) else {
+        // Without a QR code, the device that sent the request starts SAS verification,
+        // while the other device waits for it to do so.
+        let start = request.we_started();
+        sas_verification_for_request(client, request, response_receiver, start).await;
+        return;
+    };
+    Cx::post_action(VerificationAction::QrCodeGenerated(qr_code_data));
+
+    let mut request_stream = request.changes();
+    let mut qr_stream = qr.changes();
+    // Whether the other device has scanned our QR code and is waiting for the user to confirm it.
+    let mut qr_code_scanned = false;
+    loop {
+        tokio::select! {
+            state = qr_stream.next() => match state {
+                Some(QrVerificationState::Started) => { }
+                Some(QrVerificationState::Scanned) => {
+                    qr_code_scanned = true;
+                    Cx::post_action(VerificationAction::QrCodeScanned);
+                }
+                Some(QrVerificationState::Confirmed) => Cx::post_action(VerificationAction::QrCodeConfirmed),
+                // We only show our QR code, so we never reciprocate the other device's QR code.
+                Some(QrVerificationState::Reciprocated) => { }
+                Some(QrVerificationState::Done { .. }) => {
+                    info!("QR code verification done.");
+                    info!("[Post-verification] {}", dump_devices(qr.other_user_id(), &client).await);
+                    Cx::post_action(VerificationAction::RequestCompleted);
+                    return;
+                }
+                Some(QrVerificationState::Cancelled(info)) => {
+                    info!("QR code verification has been cancelled, reason: {}", info.reason());
+                    Cx::post_action(VerificationAction::RequestCancelled(info));
+                    return;
+                }
+                None => return,
+            },
+            state = request_stream.next() => match state {
+                // The other side started SAS verification instead of scanning our QR code.
+                Some(VerificationRequestState::Transitioned { verification: Verification::SasV1(sas) }) => {
+                    Handle::current().spawn(sas_verification_handler(client, sas, response_receiver));
+                    return;
+                }
+                Some(VerificationRequestState::Cancelled(info)) => {
+                    info!("Verification request was cancelled, reason: {}", info.reason());
+                    Cx::post_action(VerificationAction::RequestCancelled(info));
+                    return;
+                }
+                Some(_) => { }
+                None => return,
+            },
+            response = response_receiver.recv() => match response {
+                // The user confirmed that the other device successfully scanned our QR code.
+                Some(VerificationUserResponse::ConfirmQrScanned) => {
+                    if !qr_code_scanned {
+                        warn!("Ignoring confirmation of a QR code that hasn't been scanned yet.");
+                        continue;
+                    }
+                    if let Err(e) = qr.confirm().await {
+                        Cx::post_action(VerificationAction::QrCodeConfirmationError(Arc::new(e)));
+                        return;
+                    }
+                }
+                // The user chose to compare emoji instead of scanning our QR code.
+                Some(VerificationUserResponse::StartSas) => {
+                    sas_verification_for_request(client, request, response_receiver, true).await;
+                    return;
+                }
+                Some(VerificationUserResponse::Accept) => {
+                    warn!("Ignoring an unexpected response to our QR code: Accept");
+                }
+                Some(VerificationUserResponse::Cancel) | None => {
+                    if let Err(e) = qr.cancel().await {
+                        Cx::post_action(VerificationAction::RequestCancelError(Arc::new(e)));
+                        return;
+                    }
+                    // The cancellation will be reported by the above `Cancelled` state.
+                }
+            },
+        }
+    }
+}
+
+/// Drives SAS verification for the given ready verification request to completion.
+///
+/// If `start` is `true`, we start SAS verification ourselves;
+/// otherwise, we wait for the other side to start it.
+async fn sas_verification_for_request(
+    client: Client,
+    request: VerificationRequest,
+    response_receiver: UnboundedReceiver<VerificationUserResponse>,
+    start: bool,
+) {
+    if start {
+        match request.start_sas().await {
+            Ok(Some(sas)) => {
+                info!("Started SAS verification with user {}", request.other_user_id());
+                sas_verification_handler(client, sas, response_receiver).await;
+                return;
+            }
+            Ok(None) => {
+                // The other side may start SAS verification instead,
+                // in which case we'll get a `Transitioned` state.
+                info!("Could not start SAS verification; waiting for the other side.");
+            }
+            Err(e) => {
+                Cx::post_action(VerificationAction::RequestAcceptError(Arc::new(e)));
+                return;
+            }
+        }
+    }
+
+    let mut stream = request.changes();
+    while let Some(state) = stream.next().await {
+        match state {
+            VerificationRequestState::Transitioned { verification: Verification::SasV1(sas) } => {
+                sas_verification_handler(client, sas, response_receiver).await;
+                return;
+            }
+            VerificationRequestState::Cancelled(info) => {
+                info!("Verification request was cancelled, reason: {}", info.reason());
+                Cx::post_action(VerificationAction::RequestCancelled(info));
+                return;
+            }
+            _ => { }
+        }
+    }
+}
+
+
+/// Sends a request to verify the given device of the given user,
+/// and then drives that verification request to completion.
+pub async fn request_device_verification(client: Client, user_id: OwnedUserId, device_id: OwnedDeviceId) {
+    let device = match client.encryption().get_device(&user_id, &device_id).await {
+        Ok(Some(device)) => device,
+        Ok(None) => {
+            warn!("Cannot verify unknown device {device_id} of user {user_id}");
+            enqueue_popup_notification(tr_args(
+                "verification.unknown_device",
+                &[("device", device_id.as_str()), ("user", user_id.as_str())],
+            ));
+            return;
+        }
+        Err(e) => {
+            warn!("Failed to get device {device_id} of user {user_id}: {e:?}");
+            enqueue_popup_notification(tr_args(
+                "verification.get_device_failed",
+                &[("device", device_id.as_str()), ("user", user_id.as_str()), ("error", &e.to_string())],
+            ));
+            return;
+        }
+    };
+    let request = match device.request_verification_with_methods(supported_methods()).await {
+        Ok(request) => request,
+        Err(e) => {
+            warn!("Failed to request verification of device {device_id} of user {user_id}: {e:?}");
+            enqueue_popup_notification(tr_args(
+                "verification.request_failed",
+                &[("device", device_id.as_str()), ("error", &e.to_string())],
+            ));
+            return;
+        }
+    };
+    info!("Sent a verification request to device {device_id} of user {user_id}");
+
+    let (sender, mut response_receiver) = tokio::sync::mpsc::unbounded_channel::<VerificationUserResponse>();
+    Cx::post_action(
+        VerificationAction::RequestSent(
+            VerificationRequestActionState {
+                request: request.clone(),
+                response_sender: sender,
+            }
+        )
+    );
+
+    let mut stream = request.changes();
+    let mut cancel_requested = false;
+    loop {
+        tokio::select! {
+            state = stream.next() => match state {
+                Some(VerificationRequestState::Created { .. })
+                | Some(VerificationRequestState::Requested { .. }) => { }
+                // The other device accepted our request, so we show our QR code if possible.
+                Some(VerificationRequestState::Ready { .. }) => {
+                    info!("Device {device_id} of user {user_id} accepted our verification request");
+                    Handle::current().spawn(ready_verification_handler(client, request, response_receiver));
+                    return;
+                }
+                Some(VerificationRequestState::Transitioned { verification }) => match verification {
+                    Verification::SasV1(sas) => {
+                        Handle::current().spawn(sas_verification_handler(client, sas, response_receiver));
+                        return;
+                    }
+                    unsupported => {
+                        Cx::post_action(VerificationAction::RequestTransitionedToUnsupportedMethod(unsupported));
+                        return;
+                    }
+                }
+                Some(VerificationRequestState::Cancelled(info)) => {
+                    info!("Verification request was cancelled, reason: {}", info.reason());
+                    Cx::post_action(VerificationAction::RequestCancelled(info));
+                    return;
+                }
+                Some(VerificationRequestState::Done) => {
+                    Cx::post_action(VerificationAction::RequestCompleted);
+                    return;
+                }
+                None => return,
+            },
+            // The user cancelled the request (or closed the modal) before the other device responded.
+            response = response_receiver.recv(), if !cancel_requested => {
+                if !matches!(response, Some(VerificationUserResponse::Cancel) | None) { continue; }
+                cancel_requested = true;
+                if let Err(e) = request.cancel().await {
+                    Cx::post_action(VerificationAction::RequestCancelError(Arc::new(e)));
+                    return;
+                }
+                // The cancellation will be reported by the above `Cancelled` state.
+            }
+        }
+    }
+}
+
+/// Info about one of a user's devices, for display in the UI.
+#[derive(Clone, Debug)]
+pub struct UserDevice {
+    pub device_id: OwnedDeviceId,
+    pub display_name: Option<String>,
+    pub is_verified: bool,
+    /// Whether this device is the one that Robrix is currently running on.
+    pub is_current_device: bool,
+}
+
+/// An action that delivers the list of a user's devices, fetched in the background,
+/// to the main UI thread.
+#[derive(Clone, Debug)]
+pub struct UserDevicesAction {
+    pub user_id: OwnedUserId,
+    pub devices: Vec<UserDevice>,
+}
+
+/// Fetches the currently-known devices of the given user
+/// and posts them to the main UI thread as a [`UserDevicesAction`].
+pub async fn fetch_user_devices(client: Client, user_id: OwnedUserId) {
+    let user_devices = match client.encryption().get_user_devices(&user_id).await {
+        Ok(user_devices) => user_devices,
+        Err(e) => {
+            warn!("Failed to get devices of user {user_id}: {e:?}");
+            return;
+        }
+    };
+    let devices = user_devices.devices()
+        .map(|device| UserDevice {
+            device_id: device.device_id().to_owned(),
+            display_name: device.display_name().map(ToOwned::to_owned),
+            is_verified: device.is_verified(),
+            is_current_device: client.device_id().is_some_and(|id| id == device.device_id()),
+        })
+        .collect();
+    Cx::post_action(UserDevicesAction { user_id, devices });
+}
+
+
 /// Actions related to verification that should be handled by the top-level app context.
 #[derive(Clone, Debug, DefaultNone)]
 pub enum VerificationAction {
     /// Informs the main UI thread that a verification request has been received.
     RequestReceived(VerificationRequestActionState),
+    /// Informs the main UI thread that the current user sent a verification request
+    /// to another device, which hasn't yet responded.
+    RequestSent(VerificationRequestActionState),
     /// Informs the main UI thread that a verification request was cancelled successfully.
     RequestCancelled(CancelInfo),
     /// Informs the main UI thread that a verification request was accepted successfully.
@@ -281,6 +591,18 @@ pub enum VerificationAction {
         emojis: Option<EmojiShortAuthString>,
         decimals: (u16, u16, u16),
     },
+    /// Informs the main UI thread that our QR code for verification has been generated.
+    /// The UI should display the QR code (encoded from the given data) for the other device to scan,
+    /// and also offer to compare emoji instead.
+    QrCodeGenerated(Vec<u8>),
+    /// Informs the main UI thread that the other device has scanned our QR code.
+    /// The UI should ask the user to confirm that the other device shows a successful scan.
+    QrCodeScanned,
+    /// Informs the main UI thread that the current user confirmed the QR code scan,
+    /// and that we're just waiting for the other side to finish.
+    QrCodeConfirmed,
+    /// Informs the main UI thread that an error occurred while confirming a QR code scan.
+    QrCodeConfirmationError(Arc<matrix_sdk::Error>),
     /// Informs the main UI thread that SAS verification keys have been confirmed by the current user,
     /// and that we're just waiting for the other side to confirm too.
     SasConfirmed,
@@ -305,7 +627,13 @@ pub struct VerificationRequestActionState {
 /// Responses that the user can make to a verification request,
 /// which are then sent from the main UI thread to the background async task
 /// that originally received the verification request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum VerificationUserResponse {
+    /// Accept the request, or confirm that the SAS emoji match.
     Accept,
+    /// Confirm that the other device has successfully scanned our QR code.
+    ConfirmQrScanned,
+    /// Compare emoji (SAS verification) instead of scanning our QR code.
+    StartSas,
     Cancel,
 }
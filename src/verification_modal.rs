@@ -1,9 +1,13 @@
 use std::borrow::Cow;
 
 use makepad_widgets::*;
+use tracing::info;
 use matrix_sdk::encryption::verification::Verification;
 
-use crate::verification::{VerificationAction, VerificationRequestActionState, VerificationUserResponse};
+use crate::{
+    utils::qr_code_image,
+    verification::{VerificationAction, VerificationRequestActionState, VerificationUserResponse},
+};
 
 live_design! {
     use link::theme::*;
@@ -63,6 +67,18 @@ live_design! {
                     }
                 }
 
+                // Our QR code, shown for the other device to scan.
+                qr_code_view = <View> {
+                    visible: false
+                    width: Fill, height: Fit
+                    align: {x: 0.5}
+
+                    qr_code = <Image> {
+                        width: 220, height: 220
+                        fit: Smallest
+                    }
+                }
+
                 <View> {
                     width: Fill, height: Fit
                     flow: Right,
@@ -120,6 +136,9 @@ pub struct VerificationModal {
     /// meaning that the verification process has ended
     /// and that any further interaction with it should close the modal.
     #[rust(false)] is_final: bool,
+    /// The response sent when the user clicks the accept button,
+    /// which depends on the current step of the verification process.
+    #[rust(VerificationUserResponse::Accept)] accept_response: VerificationUserResponse,
 }
 
 #[derive(Clone, Debug, DefaultNone)]
@@ -170,7 +189,7 @@ impl WidgetMatchEvent for VerificationModal {
                 self.reset_state();
             } else {
                 if let Some(state) = self.state.as_ref() {
-                    let _ = state.response_sender.send(VerificationUserResponse::Accept);
+                    let _ = state.response_sender.send(self.accept_response);
                 }
             }
         }
@@ -180,6 +199,12 @@ impl WidgetMatchEvent for VerificationModal {
             // `VerificationAction`s come from a background thread, so they are NOT widget actions.
             // Therefore, we cannot use `as_widget_action().cast()` to match them.
             if let Some(verification_action) = action.downcast_ref::<VerificationAction>() {
+                // Our QR code is only shown until the verification proceeds past that step.
+                if !matches!(verification_action, VerificationAction::QrCodeGenerated(_)) {
+                    self.view(id!(qr_code_view)).set_visible(cx, false);
+                }
+                // The accept button's meaning is only different while our QR code is shown.
+                self.accept_response = VerificationUserResponse::Accept;
                 match verification_action {
                     VerificationAction::RequestCancelled(cancel_info) => {
                         self.label(id!(prompt)).set_text(
@@ -285,6 +310,70 @@ impl WidgetMatchEvent for VerificationModal {
                         cancel_button.set_visible(cx, true);
                     }
 
+                    VerificationAction::QrCodeGenerated(qr_code_data) => {
+                        let qr_code_view = self.view(id!(qr_code_view));
+                        if let Some(image_buffer) = qr_code_image(qr_code_data) {
+                            self.image(id!(qr_code)).set_texture(cx, Some(image_buffer.into_new_texture(cx)));
+                            qr_code_view.set_visible(cx, true);
+                            self.label(id!(prompt)).set_text(
+                                cx,
+                                "Scan this QR code with the other device to verify it.\n\n\
+                                If the other device can't scan QR codes, you can compare emoji instead."
+                            );
+                        } else {
+                            qr_code_view.set_visible(cx, false);
+                            self.label(id!(prompt)).set_text(
+                                cx,
+                                "Failed to display the QR code for verification.\n\n\
+                                Please compare emoji instead."
+                            );
+                        }
+                        self.accept_response = VerificationUserResponse::StartSas;
+                        accept_button.set_enabled(cx, true);
+                        accept_button.set_text(cx, "Compare Emoji");
+                        cancel_button.set_text(cx, "Cancel");
+                        cancel_button.set_enabled(cx, true);
+                        cancel_button.set_visible(cx, true);
+                    }
+
+                    VerificationAction::QrCodeScanned => {
+                        self.label(id!(prompt)).set_text(
+                            cx,
+                            "The other device has scanned your QR code.\n\n\
+                            Does the other device show that the scan was successful?"
+                        );
+                        self.accept_response = VerificationUserResponse::ConfirmQrScanned;
+                        accept_button.set_enabled(cx, true);
+                        accept_button.set_text(cx, "Yes");
+                        cancel_button.set_text(cx, "No");
+                        cancel_button.set_enabled(cx, true);
+                        cancel_button.set_visible(cx, true);
+                    }
+
+                    VerificationAction::QrCodeConfirmed => {
+                        self.label(id!(prompt)).set_text(
+                            cx,
+                            "You successfully confirmed the QR code scan.\n\n\
+                            Waiting for the other device to finish..."
+                        );
+                        accept_button.set_enabled(cx, false);
+                        accept_button.set_text(cx, "Waiting...");
+                        cancel_button.set_text(cx, "Cancel");
+                        cancel_button.set_enabled(cx, true);
+                        cancel_button.set_visible(cx, true);
+                    }
+
+                    VerificationAction::QrCodeConfirmationError(error) => {
+                        self.label(id!(prompt)).set_text(
+                            cx,
+                            &format!("Error confirming the QR code scan: {}\n\nPlease retry the verification process.", error)
+                        );
+                        accept_button.set_text(cx, "Ok");
+                        accept_button.set_enabled(cx, true);
+                        cancel_button.set_visible(cx, false);
+                        self.is_final = true;
+                    }
+
                     VerificationAction::SasConfirmed => {
                         self.label(id!(prompt)).set_text(
                             cx,
@@ -333,6 +422,7 @@ impl VerificationModal {
     fn reset_state(&mut self) {
         self.state = None;
         self.is_final = false;
+        self.accept_response = VerificationUserResponse::Accept;
     }
 
     fn initialize_with_data(
@@ -340,8 +430,34 @@ impl VerificationModal {
         cx: &mut Cx,
         state: VerificationRequestActionState,
     ) {
-        log!("Initializing verification modal with state: {:?}", state);
+        info!("Initializing verification modal with state: {:?}", state);
         let request = &state.request;
+        let accept_button = self.button(id!(accept_button));
+        let cancel_button = self.button(id!(cancel_button));
+        cancel_button.set_text(cx, "Cancel");
+        cancel_button.set_enabled(cx, true);
+        cancel_button.set_visible(cx, true);
+        accept_button.set_visible(cx, true);
+        self.view(id!(qr_code_view)).set_visible(cx, false);
+        self.state = Some(state.clone());
+        self.is_final = false;
+        self.accept_response = VerificationUserResponse::Accept;
+
+        // If we sent this request, there's nothing for the user to accept;
+        // we just wait for the other device to respond.
+        if request.we_started() {
+            self.label(id!(prompt)).set_text(
+                cx,
+                &format!("Sent a verification request to user {}.\n\n\
+                    Waiting for their device to accept it...",
+                    request.other_user_id(),
+                ),
+            );
+            accept_button.set_text(cx, "Waiting...");
+            accept_button.set_enabled(cx, false);
+            return;
+        }
+
         let prompt_text = if request.is_self_verification() {
             Cow::from("Do you wish to verify your own device?")
         } else {
@@ -357,18 +473,8 @@ impl VerificationModal {
             }
         };
         self.label(id!(prompt)).set_text(cx, &prompt_text);
-
-        let accept_button = self.button(id!(accept_button));
-        let cancel_button = self.button(id!(cancel_button));
         accept_button.set_text(cx, "Yes");
         accept_button.set_enabled(cx, true);
-        accept_button.set_visible(cx, true);
-        cancel_button.set_text(cx, "Cancel");
-        cancel_button.set_enabled(cx, true);
-        cancel_button.set_visible(cx, true);
-
-        self.state = Some(state);
-        self.is_final = false;
     }
 }
 
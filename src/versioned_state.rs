@@ -0,0 +1,135 @@
+//! Versioning and migration of state that Robrix persists to the filesystem.
+//!
+//! Each persisted file is a JSON object with a top-level `version` field.
+//! When loading a file saved by an older version of Robrix, the raw JSON is migrated
+//! step by step to the current version before being deserialized.
+//! Files without a `version` field predate versioning and are treated as version 0.
+
+use std::path::Path;
+
+use anyhow::{anyhow, bail};
+use tracing::{error, info};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+/// The name of the top-level JSON field holding the version of a persisted state's format.
+const VERSION_FIELD: &str = "version";
+
+/// A migration of a persisted state's raw JSON from one version to the next.
+pub type Migration = fn(&mut Value) -> anyhow::Result<()>;
+
+/// A type whose format, when persisted, carries a version number.
+pub trait VersionedState: Serialize + DeserializeOwned {
+    /// The current version of this type's persisted format.
+    ///
+    /// Increment this whenever the format changes in a way that older files
+    /// can't be deserialized as-is, and add the corresponding migration to [`Self::MIGRATIONS`].
+    const CURRENT_VERSION: u64;
+
+    /// The migrations of this type's persisted format,
+    /// in which `MIGRATIONS[i]` migrates version `i` to version `i + 1`.
+    ///
+    /// Its length must equal [`Self::CURRENT_VERSION`].
+    const MIGRATIONS: &'static [Migration];
+}
+
+/// A migration for a format that didn't change when versioning was introduced.
+pub fn unversioned_to_v1(_value: &mut Value) -> anyhow::Result<()> {
+    Ok(())
+}
+
+/// Serializes the given state to JSON, including its current version.
+pub fn to_versioned_json<T: VersionedState>(state: &T) -> anyhow::Result<String> {
+    let mut value = serde_json::to_value(state)?;
+    let object = value.as_object_mut()
+        .ok_or_else(|| anyhow!("Versioned state must be serialized as a JSON object"))?;
+    object.insert(VERSION_FIELD.into(), T::CURRENT_VERSION.into());
+    Ok(serde_json::to_string_pretty(&value)?)
+}
+
+/// Deserializes the given JSON into a state of type `T`,
+/// first migrating it to the current version if necessary.
+///
+/// Fails if the JSON was saved by a newer version of Robrix than this one,
+/// since we can't know how to read it.
+pub fn from_versioned_json<T: VersionedState>(serialized: &str) -> anyhow::Result<T> {
+    let mut value: Value = serde_json::from_str(serialized)?;
+    let object = value.as_object_mut()
+        .ok_or_else(|| anyhow!("Versioned state must be a JSON object"))?;
+    let version = match object.remove(VERSION_FIELD) {
+        Some(v) => v.as_u64().ok_or_else(|| anyhow!("Invalid version: {v}"))?,
+        None => 0,
+    };
+    if version > T::CURRENT_VERSION {
+        bail!("State was saved with version {version}, but the latest known version is {}", T::CURRENT_VERSION);
+    }
+    for from_version in version..T::CURRENT_VERSION {
+        let migration = T::MIGRATIONS.get(from_version as usize)
+            .ok_or_else(|| anyhow!("BUG: missing migration from version {from_version}"))?;
+        migration(&mut value)
+            .map_err(|e| anyhow!("Failed to migrate state from version {from_version}: {e}"))?;
+        info!("Migrated {} from version {from_version} to {}", std::any::type_name::<T>(), from_version + 1);
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Moves an unreadable state file aside, such that it isn't overwritten
+/// and can still be recovered manually, e.g., by a newer version of Robrix.
+pub fn back_up_unreadable_file(path: &Path) {
+    let mut backup_path = path.as_os_str().to_owned();
+    backup_path.push(".bak");
+    match std::fs::rename(path, &backup_path) {
+        Ok(()) => info!("Moved unreadable file {} to {}", path.display(), Path::new(&backup_path).display()),
+        Err(e) => error!("Failed to back up unreadable file {}: {e}", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests_versioned_state {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct TestState {
+        name: String,
+        count: u32,
+    }
+
+    impl VersionedState for TestState {
+        const CURRENT_VERSION: u64 = 2;
+        const MIGRATIONS: &'static [Migration] = &[unversioned_to_v1, rename_num_to_count];
+    }
+
+    fn rename_num_to_count(value: &mut Value) -> anyhow::Result<()> {
+        let object = value.as_object_mut().unwrap();
+        let num = object.remove("num").ok_or_else(|| anyhow!("missing num"))?;
+        object.insert("count".into(), num);
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let state = TestState { name: "a".into(), count: 3 };
+        let serialized = to_versioned_json(&state).unwrap();
+        assert!(serialized.contains("\"version\": 2"));
+        assert_eq!(from_versioned_json::<TestState>(&serialized).unwrap(), state);
+    }
+
+    #[test]
+    fn test_migrates_unversioned_state() {
+        let state = from_versioned_json::<TestState>(r#"{"name": "a", "num": 3}"#).unwrap();
+        assert_eq!(state, TestState { name: "a".into(), count: 3 });
+    }
+
+    #[test]
+    fn test_migrates_older_version() {
+        let state = from_versioned_json::<TestState>(r#"{"version": 1, "name": "a", "num": 3}"#).unwrap();
+        assert_eq!(state, TestState { name: "a".into(), count: 3 });
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        assert!(from_versioned_json::<TestState>(r#"{"version": 3, "name": "a", "count": 3}"#).is_err());
+    }
+}